@@ -47,6 +47,10 @@ pub enum Codec {
     /// compression library. Each compressed block is followed by the 4-byte, big-endian
     /// CRC32 checksum of the uncompressed data in the block.
     Snappy,
+    #[cfg(feature = "zstd")]
+    /// The `Zstandard` codec uses Facebook's [Zstandard](https://facebook.github.io/zstd/)
+    /// compression library.
+    Zstandard,
 }
 
 impl ToAvro for Codec {
@@ -57,6 +61,8 @@ impl ToAvro for Codec {
                 Codec::Deflate => "deflate",
                 #[cfg(feature = "snappy")]
                 Codec::Snappy => "snappy",
+                #[cfg(feature = "zstd")]
+                Codec::Zstandard => "zstandard",
             }
             .to_owned()
             .into_bytes(),
@@ -73,6 +79,8 @@ impl FromStr for Codec {
             "deflate" => Ok(Codec::Deflate),
             #[cfg(feature = "snappy")]
             "snappy" => Ok(Codec::Snappy),
+            #[cfg(feature = "zstd")]
+            "zstandard" => Ok(Codec::Zstandard),
             other => Err(DecodeError::UnrecognizedCodec(other.to_string()).into()),
         }
     }
@@ -106,6 +114,10 @@ impl Codec {
 
                 *stream = encoded;
             }
+            #[cfg(feature = "zstd")]
+            Codec::Zstandard => {
+                *stream = zstd::stream::encode_all(&stream[..], 0)?;
+            }
         };
 
         Ok(())
@@ -150,6 +162,10 @@ impl Codec {
                 }
                 *stream = decoded;
             }
+            #[cfg(feature = "zstd")]
+            Codec::Zstandard => {
+                *stream = zstd::stream::decode_all(&stream[..])?;
+            }
         };
 
         Ok(())
@@ -194,4 +210,16 @@ mod tests {
         codec.decompress(&mut stream).unwrap();
         assert_eq!(INPUT, stream.as_slice());
     }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstandard_compress_and_decompress() {
+        let codec = Codec::Zstandard;
+        let mut stream = INPUT.to_vec();
+        codec.compress(&mut stream).unwrap();
+        assert_ne!(INPUT, stream.as_slice());
+        assert!(INPUT.len() > stream.len());
+        codec.decompress(&mut stream).unwrap();
+        assert_eq!(INPUT, stream.as_slice());
+    }
 }
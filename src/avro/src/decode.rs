@@ -31,7 +31,7 @@ use flate2::read::MultiGzDecoder;
 
 use crate::error::{DecodeError, Error as AvroError};
 use crate::schema::{
-    RecordField, ResolvedDefaultValueField, ResolvedRecordField, SchemaNode, SchemaPiece,
+    FullName, RecordField, ResolvedDefaultValueField, ResolvedRecordField, SchemaNode, SchemaPiece,
     SchemaPieceOrNamed,
 };
 use crate::types::{AvroMap, Scalar, Value};
@@ -226,6 +226,8 @@ impl<'b, R: AvroRead> AvroFieldAccess<'b, R> {
 }
 
 pub trait AvroRecordAccess<R: AvroRead> {
+    /// The full name of the record being accessed, if known.
+    fn name(&self) -> Option<&FullName>;
     fn next_field<'b>(
         &'b mut self,
     ) -> Result<Option<(&'b str, usize, AvroFieldAccess<'b, R>)>, AvroError>;
@@ -250,6 +252,10 @@ impl<'a, R: AvroRead> SimpleRecordAccess<'a, R> {
 }
 
 impl<'a, R: AvroRead> AvroRecordAccess<R> for SimpleRecordAccess<'a, R> {
+    fn name(&self) -> Option<&FullName> {
+        self.schema.name
+    }
+
     fn next_field<'b>(
         &'b mut self,
     ) -> Result<Option<(&'b str, usize, AvroFieldAccess<'b, R>)>, AvroError> {
@@ -282,6 +288,10 @@ impl<'a> ValueRecordAccess<'a> {
 }
 
 impl<'a> AvroRecordAccess<&'a [u8]> for ValueRecordAccess<'a> {
+    fn name(&self) -> Option<&FullName> {
+        None
+    }
+
     fn next_field<'b>(
         &'b mut self,
     ) -> Result<Option<(&'b str, usize, AvroFieldAccess<'b, &'a [u8]>)>, AvroError> {
@@ -362,6 +372,10 @@ impl<'a, R: AvroRead> ResolvedRecordAccess<'a, R> {
 }
 
 impl<'a, R: AvroRead> AvroRecordAccess<R> for ResolvedRecordAccess<'a, R> {
+    fn name(&self) -> Option<&FullName> {
+        self.schema.name
+    }
+
     fn next_field<'b>(
         &'b mut self,
     ) -> Result<Option<(&'b str, usize, AvroFieldAccess<'b, R>)>, AvroError> {
@@ -158,7 +158,7 @@
 //!
 //! ## Using codecs to compress data
 //!
-//! Avro supports three different compression codecs when encoding data:
+//! Avro supports four different compression codecs when encoding data:
 //!
 //! * **Null**: leaves data uncompressed;
 //! * **Deflate**: writes the data block using the deflate algorithm as specified in RFC 1951, and
@@ -167,6 +167,8 @@
 //! * **Snappy**: uses Google's [Snappy](http://google.github.io/snappy/) compression library. Each
 //! compressed block is followed by the 4-byte, big-endianCRC32 checksum of the uncompressed data in
 //! the block. You must enable the `snappy` feature to use this codec.
+//! * **Zstandard**: uses Facebook's [Zstandard](https://facebook.github.io/zstd/) compression
+//! library. You must enable the `zstd` feature to use this codec.
 //!
 //! To specify a codec to use to compress data, just specify it while creating a `Writer`:
 //! ```
@@ -350,7 +352,9 @@ pub use crate::reader::{from_avro_datum, Block, BlockIter, Reader};
 pub use crate::schema::{ParseSchemaError, Schema};
 pub use crate::types::SchemaResolutionError;
 pub use crate::util::max_allocation_bytes;
-pub use crate::writer::{to_avro_datum, write_avro_datum, ValidationError, Writer};
+pub use crate::writer::{
+    to_avro_datum, write_avro_datum, ValidationError, Writer, DEFAULT_BLOCK_SIZE,
+};
 
 #[cfg(test)]
 mod tests {
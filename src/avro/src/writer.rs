@@ -37,7 +37,10 @@ use crate::types::{ToAvro, Value};
 use crate::{decode::AvroRead, Codec};
 
 const SYNC_SIZE: usize = 16;
-const SYNC_INTERVAL: usize = 1000 * SYNC_SIZE; // TODO: parametrize in Writer
+
+/// The default number of uncompressed bytes a [`Writer`] buffers before it
+/// writes out a block followed by a sync marker.
+pub const DEFAULT_BLOCK_SIZE: usize = 1000 * SYNC_SIZE;
 
 const AVRO_OBJECT_HEADER: &[u8] = &[b'O', b'b', b'j', 1u8];
 
@@ -69,6 +72,7 @@ pub struct Writer<W> {
     buffer: Vec<u8>,
     num_values: usize,
     codec: Option<Codec>,
+    block_size: usize,
     marker: [u8; 16],
     has_header: bool,
 }
@@ -101,9 +105,10 @@ impl<W: Write> Writer<W> {
         Writer {
             schema,
             writer,
-            buffer: Vec::with_capacity(SYNC_INTERVAL),
+            buffer: Vec::with_capacity(DEFAULT_BLOCK_SIZE),
             num_values: 0,
             codec,
+            block_size: DEFAULT_BLOCK_SIZE,
             marker,
             has_header: false,
         }
@@ -120,14 +125,25 @@ impl<W: Write> Writer<W> {
         Ok(Writer {
             schema,
             writer: file,
-            buffer: Vec::with_capacity(SYNC_INTERVAL),
+            buffer: Vec::with_capacity(DEFAULT_BLOCK_SIZE),
             num_values: 0,
             codec: Some(codec),
+            block_size: DEFAULT_BLOCK_SIZE,
             marker,
             has_header: true,
         })
     }
 
+    /// Sets the number of uncompressed bytes the `Writer` buffers before
+    /// writing out a block, which also determines how often sync markers
+    /// appear in the output. Defaults to [`DEFAULT_BLOCK_SIZE`].
+    ///
+    /// A block size of zero writes out a block for every appended value.
+    pub fn with_block_size(mut self, block_size: usize) -> Writer<W> {
+        self.block_size = block_size;
+        self
+    }
+
     /// Get a reference to the `Schema` associated to a `Writer`.
     pub fn schema(&self) -> &Schema {
         &self.schema
@@ -155,7 +171,7 @@ impl<W: Write> Writer<W> {
 
         self.num_values += 1;
 
-        if self.buffer.len() >= SYNC_INTERVAL {
+        if self.buffer.len() >= self.block_size {
             return self.flush().map(|b| b + n);
         }
 
@@ -183,7 +199,7 @@ impl<W: Write> Writer<W> {
 
         self.num_values += 1;
 
-        if self.buffer.len() >= SYNC_INTERVAL {
+        if self.buffer.len() >= self.block_size {
             return self.flush().map(|b| b + n);
         }
 
@@ -631,4 +647,38 @@ mod tests {
             actual
         );
     }
+
+    #[test]
+    fn test_writer_block_size() {
+        let schema = Schema::from_str(SCHEMA).unwrap();
+        let make_record = |a: i64, b| {
+            let mut record = Record::new(schema.top_node()).unwrap();
+            record.put("a", a);
+            record.put("b", b);
+            record.avro()
+        };
+
+        // With the default block size, small values stay buffered until the
+        // writer is flushed.
+        let mut writer = Writer::new(schema.clone(), Vec::new());
+        writer.append(make_record(27, "foo")).unwrap();
+        assert_eq!(writer.append(make_record(54, "bar")).unwrap(), 0);
+
+        // A block size of zero writes out a block for every value.
+        let mut writer =
+            Writer::with_codec(schema.clone(), Vec::new(), Codec::Deflate).with_block_size(0);
+        let n1 = writer.append(make_record(27, "foo")).unwrap();
+        let n2 = writer.append(make_record(54, "bar")).unwrap();
+        assert_ne!(n2, 0);
+        assert_eq!(writer.flush().unwrap(), 0);
+        let buf = writer.into_inner();
+        assert_eq!(n1 + n2, buf.len());
+
+        let reader = Reader::new(&buf[..]).unwrap();
+        let actual: Result<Vec<_>, _> = reader.collect();
+        assert_eq!(
+            vec![make_record(27, "foo"), make_record(54, "bar")],
+            actual.unwrap()
+        );
+    }
 }
@@ -9,7 +9,7 @@
 
 //! AWS S3 client and utilities.
 
-use aws_sdk_s3::Client;
+pub use aws_sdk_s3::Client;
 
 use crate::config::AwsConfig;
 use crate::util;
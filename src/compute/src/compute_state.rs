@@ -13,7 +13,7 @@ use std::rc::Rc;
 use std::time::{Duration, Instant};
 
 use differential_dataflow::operators::arrange::arrangement::Arrange;
-use differential_dataflow::trace::TraceReader;
+use differential_dataflow::trace::{BatchReader, TraceReader};
 use differential_dataflow::Collection;
 use timely::communication::Allocate;
 use timely::logging::Logger;
@@ -24,10 +24,11 @@ use timely::progress::ChangeBatch;
 use timely::worker::Worker as TimelyWorker;
 use tokio::sync::mpsc;
 
-use mz_dataflow_types::client::{ComputeCommand, ComputeResponse};
+use mz_dataflow_types::client::{ArrangementStatistics, ComputeCommand, ComputeResponse};
 use mz_dataflow_types::logging::LoggingConfig;
 use mz_dataflow_types::{DataflowError, PeekResponse, TailResponse};
 use mz_expr::GlobalId;
+use mz_ore::cast::CastFrom;
 use mz_repr::{Diff, Row, Timestamp};
 use mz_storage::boundary::ComputeReplay;
 use mz_timely_util::activator::RcActivator;
@@ -38,6 +39,9 @@ use crate::logging;
 use crate::logging::materialized::ComputeEvent;
 use crate::sink::SinkBaseMetrics;
 
+/// The minimum interval between reports of arrangement statistics.
+const STATISTICS_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Worker-local state that is maintained across dataflows.
 ///
 /// This state is restricted to the COMPUTE state, the deterministic, idempotent work
@@ -60,6 +64,10 @@ pub struct ComputeState {
     pub pending_peeks: Vec<PendingPeek>,
     /// Tracks the frontier information that has been sent over `response_tx`.
     pub reported_frontiers: HashMap<GlobalId, Antichain<Timestamp>>,
+    /// Tracks the arrangement statistics that have been sent over `response_tx`.
+    pub reported_statistics: HashMap<GlobalId, ArrangementStatistics>,
+    /// The time at which arrangement statistics were last reported.
+    pub last_statistics_report: Instant,
     /// Undocumented
     pub sink_metrics: SinkBaseMetrics,
     /// The logger, from Timely's logging framework, if logs are enabled.
@@ -140,6 +148,7 @@ impl<'a, A: Allocate, B: ComputeReplay> ActiveComputeState<'a, A, B> {
                         self.compute_state.dataflow_tokens.remove(&id);
                         // Index-specific work:
                         self.compute_state.traces.del_trace(&id);
+                        self.compute_state.reported_statistics.remove(&id);
 
                         // Work common to sinks and indexes (removing frontier tracking and cleaning up logging).
                         let frontier = self
@@ -565,6 +574,38 @@ impl<'a, A: Allocate, B: ComputeReplay> ActiveComputeState<'a, A, B> {
         }
     }
 
+    /// Send size estimates for maintained arrangements to the coordinator.
+    ///
+    /// Statistics are reported at most once per [`STATISTICS_INTERVAL`], and
+    /// only for arrangements whose statistics have changed since they were
+    /// last reported.
+    pub fn report_arrangement_statistics(&mut self) {
+        if self.compute_state.last_statistics_report.elapsed() < STATISTICS_INTERVAL {
+            return;
+        }
+        self.compute_state.last_statistics_report = Instant::now();
+
+        let mut updates = Vec::new();
+        for (id, traces) in self.compute_state.traces.traces.iter_mut() {
+            let mut statistics = ArrangementStatistics::default();
+            traces.oks_mut().map_batches(|batch| {
+                statistics.records += u64::cast_from(batch.len());
+                statistics.keys += u64::cast_from(batch.layer.keys.len());
+            });
+            let reported = self.compute_state.reported_statistics.get(id);
+            if reported != Some(&statistics) {
+                self.compute_state
+                    .reported_statistics
+                    .insert(*id, statistics);
+                updates.push((*id, statistics));
+            }
+        }
+
+        if !updates.is_empty() {
+            self.send_compute_response(ComputeResponse::ArrangementStatistics(updates));
+        }
+    }
+
     /// Scan pending peeks and attempt to retire each.
     pub fn process_peeks(&mut self) {
         let mut upper = Antichain::new();
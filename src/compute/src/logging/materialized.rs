@@ -48,6 +48,19 @@ pub enum ComputeEvent {
         /// Globally unique identifier for the source on which the dataflow depends.
         source: GlobalId,
     },
+    /// The range of operators that were constructed to render a node of a
+    /// dataflow's physical plan.
+    PlanNode {
+        /// Globally unique identifier for the dataflow.
+        dataflow: GlobalId,
+        /// Globally unique identifier for the object whose plan contains the node.
+        object: GlobalId,
+        /// The position of the node in a pre-order traversal of the plan.
+        node: usize,
+        /// The identifiers of the operators constructed for the node and its
+        /// inputs, as a half-open range.
+        operators: (usize, usize),
+    },
     /// Peek command, true for install and false for retire.
     Peek(Peek, bool),
     /// Available frontier information for views.
@@ -116,6 +129,7 @@ pub fn construct<A: Allocate>(
         let mut input = demux.new_input(&compute_logs, Pipeline);
         let (mut dataflow_out, dataflow) = demux.new_output();
         let (mut dependency_out, dependency) = demux.new_output();
+        let (mut plan_node_out, plan_node) = demux.new_output();
         let (mut frontier_out, frontier) = demux.new_output();
         let (mut peek_out, peek) = demux.new_output();
         let (mut peek_duration_out, peek_duration) = demux.new_output();
@@ -123,10 +137,12 @@ pub fn construct<A: Allocate>(
         let mut demux_buffer = Vec::new();
         demux.build(move |_capability| {
             let mut active_dataflows = std::collections::HashMap::new();
+            let mut active_plan_nodes = std::collections::HashMap::new();
             let mut peek_stash = std::collections::HashMap::new();
             move |_frontiers| {
                 let mut dataflow = dataflow_out.activate();
                 let mut dependency = dependency_out.activate();
+                let mut plan_node = plan_node_out.activate();
                 let mut frontier = frontier_out.activate();
                 let mut peek = peek_out.activate();
                 let mut peek_duration = peek_duration_out.activate();
@@ -136,6 +152,7 @@ pub fn construct<A: Allocate>(
 
                     let mut dataflow_session = dataflow.session(&time);
                     let mut dependency_session = dependency.session(&time);
+                    let mut plan_node_session = plan_node.session(&time);
                     let mut frontier_session = frontier.session(&time);
                     let mut peek_session = peek.session(&time);
                     let mut peek_duration_session = peek_duration.session(&time);
@@ -175,8 +192,24 @@ pub fn construct<A: Allocate>(
                                             key.0, worker
                                         ),
                                     }
+                                    for node in active_plan_nodes.remove(key).unwrap_or_default() {
+                                        plan_node_session.give((node, time_ms, -1));
+                                    }
                                 }
                             }
+                            ComputeEvent::PlanNode {
+                                dataflow,
+                                object,
+                                node,
+                                operators,
+                            } => {
+                                let datum = (dataflow, object, node, worker, operators);
+                                plan_node_session.give((datum, time_ms, 1));
+                                active_plan_nodes
+                                    .entry((dataflow, worker))
+                                    .or_insert_with(Vec::new)
+                                    .push(datum);
+                            }
                             ComputeEvent::DataflowDependency { dataflow, source } => {
                                 dependency_session.give(((dataflow, source, worker), time_ms, 1));
                                 let key = (dataflow, worker);
@@ -245,12 +278,14 @@ pub fn construct<A: Allocate>(
         let mut input = demux.new_input(&storage_logs, Pipeline);
         let (mut kafka_source_statistics_out, kafka_source_statistics) = demux.new_output();
         let (mut source_info_out, source_info) = demux.new_output();
+        let (mut source_decode_errors_out, source_decode_errors) = demux.new_output();
 
         let mut demux_buffer = Vec::new();
         demux.build(move |_capability| {
             move |_frontiers| {
                 let mut kafka_source_statistics = kafka_source_statistics_out.activate();
                 let mut source_info = source_info_out.activate();
+                let mut source_decode_errors = source_decode_errors_out.activate();
 
                 input.for_each(|time, data| {
                     data.swap(&mut demux_buffer);
@@ -258,6 +293,7 @@ pub fn construct<A: Allocate>(
                     let mut kafka_source_statistics_session =
                         kafka_source_statistics.session(&time);
                     let mut source_info_session = source_info.session(&time);
+                    let mut source_decode_errors_session = source_decode_errors.session(&time);
 
                     for (time, worker, datum) in demux_buffer.drain(..) {
                         let time_ms = (((time.as_millis() as Timestamp / granularity_ms) + 1)
@@ -297,6 +333,19 @@ pub fn construct<A: Allocate>(
                                     (offset, timestamp),
                                 ));
                             }
+                            StorageEvent::SourceDecodeError {
+                                source_id,
+                                partition_id,
+                                offset,
+                                error,
+                                diff,
+                            } => {
+                                source_decode_errors_session.give((
+                                    (source_id, partition_id, offset, error),
+                                    time_ms,
+                                    diff,
+                                ));
+                            }
                         }
                     }
                 });
@@ -322,6 +371,19 @@ pub fn construct<A: Allocate>(
             }
         });
 
+        let plan_node_current = plan_node.as_collection().map({
+            move |(dataflow, object, node, worker, (start, end))| {
+                Row::pack_slice(&[
+                    Datum::String(&dataflow.to_string()),
+                    Datum::String(&object.to_string()),
+                    Datum::Int64(node as i64),
+                    Datum::Int64(worker as i64),
+                    Datum::Int64(start as i64),
+                    Datum::Int64(end as i64),
+                ])
+            }
+        });
+
         let frontier_current = frontier.as_collection();
 
         let kafka_source_statistics_current = kafka_source_statistics.as_collection().map({
@@ -359,6 +421,18 @@ pub fn construct<A: Allocate>(
             }
         });
 
+        let source_decode_errors_current = source_decode_errors.as_collection().map({
+            move |(id, pid, offset, error)| {
+                Row::pack_slice(&[
+                    Datum::String(&id.source_id.to_string()),
+                    Datum::Int64(id.dataflow_id as i64),
+                    Datum::from(pid.as_deref()),
+                    Datum::Int64(offset),
+                    Datum::String(&error),
+                ])
+            }
+        });
+
         // Duration statistics derive from the non-rounded event times.
         let peek_duration = peek_duration.as_collection().count_total_core().map({
             move |((worker, pow), count)| {
@@ -379,6 +453,10 @@ pub fn construct<A: Allocate>(
                 LogVariant::Materialized(MaterializedLog::DataflowDependency),
                 dependency_current,
             ),
+            (
+                LogVariant::Materialized(MaterializedLog::DataflowPlanNodes),
+                plan_node_current,
+            ),
             (
                 LogVariant::Materialized(MaterializedLog::FrontierCurrent),
                 frontier_current,
@@ -395,6 +473,10 @@ pub fn construct<A: Allocate>(
                 LogVariant::Materialized(MaterializedLog::PeekDuration),
                 peek_duration,
             ),
+            (
+                LogVariant::Materialized(MaterializedLog::SourceDecodeErrors),
+                source_decode_errors_current,
+            ),
             (
                 LogVariant::Materialized(MaterializedLog::SourceInfo),
                 source_info_current,
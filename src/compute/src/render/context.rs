@@ -77,6 +77,10 @@ where
     pub as_of_frontier: Antichain<T>,
     /// Bindings of identifiers to collections.
     pub bindings: BTreeMap<Id, CollectionBundle<S, V, T>>,
+    /// For each node of the most recently rendered plan, in pre-order, the
+    /// half-open range of identifiers of the operators constructed for it and
+    /// its inputs.
+    pub plan_node_operators: Vec<(usize, usize)>,
 }
 
 impl<S: Scope, V: Data> Context<S, V>
@@ -95,6 +99,7 @@ where
             dataflow_id,
             as_of_frontier,
             bindings: BTreeMap::new(),
+            plan_node_operators: Vec::new(),
         }
     }
 }
@@ -13,3 +13,4 @@
 
 mod delta_join;
 mod linear_join;
+mod outer_join;
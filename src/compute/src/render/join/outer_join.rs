@@ -0,0 +1,446 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Rendering of outer join plans.
+//!
+//! Consult [OuterJoinPlan] documentation for details.
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::operators::arrange::Arranged;
+use differential_dataflow::operators::join::JoinCore;
+use differential_dataflow::trace::BatchReader;
+use differential_dataflow::trace::Cursor;
+use differential_dataflow::trace::TraceReader;
+use differential_dataflow::{AsCollection, Collection};
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::{Capability, OkErr, Operator};
+use timely::dataflow::{Scope, Stream};
+use timely::order::PartialOrder;
+use timely::progress::{timestamp::Refines, Antichain, Timestamp};
+
+use mz_dataflow_types::plan::join::{JoinClosure, OuterJoinPlan};
+use mz_dataflow_types::DataflowError;
+use mz_expr::OuterJoinKind;
+use mz_repr::{Datum, DatumVec, Diff, Row, RowArena};
+
+use crate::render::context::{ArrangementFlavor, CollectionBundle, Context};
+
+/// Recovers records from their key and value, and pads them with nulls.
+struct Padding {
+    closure: JoinClosure,
+    nulls_before: usize,
+    nulls_after: usize,
+    datums: DatumVec,
+    row_builder: Row,
+}
+
+impl Padding {
+    fn new(closure: JoinClosure, nulls_before: usize, nulls_after: usize) -> Self {
+        Padding {
+            closure,
+            nulls_before,
+            nulls_after,
+            datums: DatumVec::new(),
+            row_builder: Row::default(),
+        }
+    }
+
+    fn pad(&mut self, key: &Row, val: &Row) -> Option<Result<Row, DataflowError>> {
+        let temp_storage = RowArena::new();
+        let mut datums_local = self.datums.borrow_with_many(&[key, val]);
+        let row = match self
+            .closure
+            .apply(&mut datums_local, &temp_storage, &mut self.row_builder)
+        {
+            Ok(Some(row)) => row,
+            Ok(None) => return None,
+            Err(err) => return Some(Err(err.into())),
+        };
+        let mut padded = Row::default();
+        padded.packer().extend(
+            iter::repeat(Datum::Null)
+                .take(self.nulls_before)
+                .chain(row.iter())
+                .chain(iter::repeat(Datum::Null).take(self.nulls_after)),
+        );
+        Some(Ok(padded))
+    }
+}
+
+/// The updates of `batch`, as `(key, val, time, diff)`.
+fn batch_updates<B, T>(batch: &B) -> Vec<(Row, Row, T, Diff)>
+where
+    B: BatchReader<Row, Row, T, Diff>,
+    T: Clone,
+{
+    let mut updates = Vec::new();
+    let mut cursor = batch.cursor();
+    while let Some(key) = cursor.get_key(batch) {
+        while let Some(val) = cursor.get_val(batch) {
+            cursor.map_times(batch, |time, diff| {
+                updates.push((key.clone(), val.clone(), time.clone(), *diff));
+            });
+            cursor.step_val(batch);
+        }
+        cursor.step_key(batch);
+    }
+    updates
+}
+
+/// The net change in the number of records of each key, excluding keys that
+/// contain nulls, as these never match.
+fn key_deltas(updates: &[(Row, Row, Diff)]) -> BTreeMap<Row, Diff> {
+    let mut deltas = BTreeMap::new();
+    for (key, _val, diff) in updates.iter() {
+        if !key.iter().any(|datum| datum.is_null()) {
+            *deltas.entry(key.clone()).or_insert(0) += diff;
+        }
+    }
+    deltas.retain(|_key, delta| *delta != 0);
+    deltas
+}
+
+/// Applies `deltas` to `counts`, retaining only non-zero counts.
+fn update_counts(counts: &mut HashMap<Row, Diff>, deltas: BTreeMap<Row, Diff>) {
+    for (key, delta) in deltas {
+        match counts.entry(key) {
+            Entry::Occupied(mut entry) => {
+                *entry.get_mut() += delta;
+                if *entry.get() == 0 {
+                    entry.remove();
+                }
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(delta);
+            }
+        }
+    }
+}
+
+/// The values of `key` in `cursor` as of `time`, with their counts.
+///
+/// Keys must be presented in increasing order, as the cursor only moves forward.
+fn records_at<C, T>(cursor: &mut C, storage: &C::Storage, key: &Row, time: &T) -> Vec<(Row, Diff)>
+where
+    C: Cursor<Row, Row, T, Diff>,
+    T: Timestamp,
+{
+    let mut records = Vec::new();
+    cursor.seek_key(storage, key);
+    if cursor.get_key(storage) == Some(key) {
+        while let Some(val) = cursor.get_val(storage) {
+            let mut count = 0;
+            cursor.map_times(storage, |t, diff| {
+                if t.less_equal(time) {
+                    count += diff;
+                }
+            });
+            if count != 0 {
+                records.push((val.clone(), count));
+            }
+            cursor.step_val(storage);
+        }
+    }
+    records
+}
+
+/// Produces the records of the preserved inputs whose keys have no match in
+/// the other input, padded with nulls.
+///
+/// The operator maintains the number of records of each key in each input,
+/// and reads the arrangements only when a key's count in one input moves
+/// between zero and non-zero, at which point the records of that key in the
+/// other input are retracted or reintroduced. Otherwise, updates to records
+/// of unmatched keys are passed through, and all other updates are ignored.
+///
+/// Both arrangements are partitioned by the hash of their keys, so that all
+/// records with the same key are found on the same worker.
+fn unmatched_records<G, Tr1, Tr2>(
+    left: &Arranged<G, Tr1>,
+    right: &Arranged<G, Tr2>,
+    kind: OuterJoinKind,
+    mut left_padding: Padding,
+    mut right_padding: Padding,
+) -> Stream<G, (Result<Row, DataflowError>, G::Timestamp, Diff)>
+where
+    G: Scope,
+    G::Timestamp: Lattice,
+    Tr1: TraceReader<Key = Row, Val = Row, Time = G::Timestamp, R = Diff> + Clone + 'static,
+    Tr1::Batch: BatchReader<Row, Row, G::Timestamp, Diff> + 'static,
+    Tr1::Cursor: Cursor<Row, Row, G::Timestamp, Diff> + 'static,
+    Tr2: TraceReader<Key = Row, Val = Row, Time = G::Timestamp, R = Diff> + Clone + 'static,
+    Tr2::Batch: BatchReader<Row, Row, G::Timestamp, Diff> + 'static,
+    Tr2::Cursor: Cursor<Row, Row, G::Timestamp, Diff> + 'static,
+{
+    let mut left_trace = left.trace.clone();
+    let mut right_trace = right.trace.clone();
+    let preserve_right = kind == OuterJoinKind::Full;
+
+    left.stream.binary_frontier(
+        &right.stream,
+        Pipeline,
+        Pipeline,
+        "OuterJoinUnmatched",
+        move |_capability, _info| {
+            // Updates to each input by time, awaiting the completion of their time.
+            let mut pending = BTreeMap::<
+                G::Timestamp,
+                (
+                    Capability<G::Timestamp>,
+                    Vec<(Row, Row, Diff)>,
+                    Vec<(Row, Row, Diff)>,
+                ),
+            >::new();
+            // The number of records of each key in each input, as of the last
+            // completed time. Keys with a count of zero are absent.
+            let mut left_counts = HashMap::new();
+            let mut right_counts = HashMap::new();
+
+            move |input1, input2, output| {
+                input1.for_each(|capability, data| {
+                    for batch in data.iter() {
+                        for (key, val, time, diff) in batch_updates(batch) {
+                            pending
+                                .entry(time.clone())
+                                .or_insert_with(|| (capability.delayed(&time), vec![], vec![]))
+                                .1
+                                .push((key, val, diff));
+                        }
+                    }
+                });
+                input2.for_each(|capability, data| {
+                    for batch in data.iter() {
+                        for (key, val, time, diff) in batch_updates(batch) {
+                            pending
+                                .entry(time.clone())
+                                .or_insert_with(|| (capability.delayed(&time), vec![], vec![]))
+                                .2
+                                .push((key, val, diff));
+                        }
+                    }
+                });
+
+                // Process completed times in order, so that the counts reflect each
+                // time in turn. Rendered timestamps are totally ordered.
+                while let Some(time) = pending.keys().next().cloned() {
+                    if input1.frontier.less_equal(&time) || input2.frontier.less_equal(&time) {
+                        break;
+                    }
+                    let (capability, left_updates, right_updates) =
+                        pending.remove(&time).expect("time is pending");
+                    let mut session = output.session(&capability);
+                    let left_deltas = key_deltas(&left_updates);
+                    let right_deltas = key_deltas(&right_updates);
+
+                    // Updates to left records whose keys had no match before `time`
+                    // are passed through. If the key gains a match, all of its
+                    // records as of `time` are retracted below, including these.
+                    for (key, val, diff) in left_updates.iter() {
+                        if !right_counts.contains_key(key) {
+                            if let Some(result) = left_padding.pad(key, val) {
+                                session.give((result, time.clone(), *diff));
+                            }
+                        }
+                    }
+                    if !right_deltas.is_empty() {
+                        let (mut cursor, storage) = left_trace.cursor();
+                        for (key, delta) in right_deltas.iter() {
+                            let old = right_counts.get(key).copied().unwrap_or(0);
+                            let sign = match (old == 0, old + delta == 0) {
+                                (true, false) => -1,
+                                (false, true) => 1,
+                                _ => continue,
+                            };
+                            for (val, count) in records_at(&mut cursor, &storage, key, &time) {
+                                if let Some(result) = left_padding.pad(key, &val) {
+                                    session.give((result, time.clone(), sign * count));
+                                }
+                            }
+                        }
+                    }
+
+                    if preserve_right {
+                        for (key, val, diff) in right_updates.iter() {
+                            if !left_counts.contains_key(key) {
+                                if let Some(result) = right_padding.pad(key, val) {
+                                    session.give((result, time.clone(), *diff));
+                                }
+                            }
+                        }
+                        if !left_deltas.is_empty() {
+                            let (mut cursor, storage) = right_trace.cursor();
+                            for (key, delta) in left_deltas.iter() {
+                                let old = left_counts.get(key).copied().unwrap_or(0);
+                                let sign = match (old == 0, old + delta == 0) {
+                                    (true, false) => -1,
+                                    (false, true) => 1,
+                                    _ => continue,
+                                };
+                                for (val, count) in records_at(&mut cursor, &storage, key, &time) {
+                                    if let Some(result) = right_padding.pad(key, &val) {
+                                        session.give((result, time.clone(), sign * count));
+                                    }
+                                }
+                            }
+                        }
+                        update_counts(&mut left_counts, left_deltas);
+                    }
+                    update_counts(&mut right_counts, right_deltas);
+                }
+
+                // Times not in advance of both input frontiers have been processed,
+                // and the arrangements need only be read at times in advance of them.
+                let mut frontier = Antichain::new();
+                for time in input1
+                    .frontier
+                    .frontier()
+                    .iter()
+                    .chain(input2.frontier.frontier().iter())
+                {
+                    frontier.insert(time.clone());
+                }
+                left_trace.set_logical_compaction(frontier.borrow());
+                left_trace.set_physical_compaction(frontier.borrow());
+                right_trace.set_logical_compaction(frontier.borrow());
+                right_trace.set_physical_compaction(frontier.borrow());
+            }
+        },
+    )
+}
+
+impl<G, T> Context<G, Row, T>
+where
+    G: Scope,
+    G::Timestamp: Lattice + Refines<T>,
+    T: Timestamp + Lattice,
+{
+    pub(crate) fn render_outer_join(
+        &mut self,
+        inputs: Vec<CollectionBundle<G, Row, T>>,
+        outer_plan: OuterJoinPlan,
+        scope: &mut G,
+    ) -> CollectionBundle<G, Row, T> {
+        // Collect all error streams, and concatenate them at the end.
+        let mut errors = Vec::new();
+
+        let left = inputs[0]
+            .arrangement(&outer_plan.left_key)
+            .expect("Arrangement absent despite explicit construction");
+        let right = inputs[1]
+            .arrangement(&outer_plan.right_key)
+            .expect("Arrangement absent despite explicit construction");
+
+        // Demultiplex the four different cross products of arrangement types we might have.
+        let oks = match (left, right) {
+            (ArrangementFlavor::Local(left, errs1), ArrangementFlavor::Local(right, errs2)) => {
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2.as_collection(|k, _v| k.clone()));
+                self.render_outer_join_inner(left, right, outer_plan, &mut errors)
+            }
+            (ArrangementFlavor::Local(left, errs1), ArrangementFlavor::Trace(_, right, errs2)) => {
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2.as_collection(|k, _v| k.clone()));
+                self.render_outer_join_inner(left, right, outer_plan, &mut errors)
+            }
+            (ArrangementFlavor::Trace(_, left, errs1), ArrangementFlavor::Local(right, errs2)) => {
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2.as_collection(|k, _v| k.clone()));
+                self.render_outer_join_inner(left, right, outer_plan, &mut errors)
+            }
+            (
+                ArrangementFlavor::Trace(_, left, errs1),
+                ArrangementFlavor::Trace(_, right, errs2),
+            ) => {
+                errors.push(errs1.as_collection(|k, _v| k.clone()));
+                errors.push(errs2.as_collection(|k, _v| k.clone()));
+                self.render_outer_join_inner(left, right, outer_plan, &mut errors)
+            }
+        };
+
+        CollectionBundle::from_collections(
+            oks,
+            differential_dataflow::collection::concatenate(scope, errors),
+        )
+    }
+
+    /// Joins the arrangements of the two inputs, and adds the unmatched
+    /// records of the preserved inputs padded with nulls. This is split
+    /// into its own method to enable reuse of code with different types
+    /// of arrangements.
+    fn render_outer_join_inner<Tr1, Tr2>(
+        &mut self,
+        left: Arranged<G, Tr1>,
+        right: Arranged<G, Tr2>,
+        OuterJoinPlan {
+            kind,
+            left_arity,
+            right_arity,
+            matched_closure,
+            left_closure,
+            right_closure,
+            left_key: _,
+            right_key: _,
+        }: OuterJoinPlan,
+        errors: &mut Vec<Collection<G, DataflowError, Diff>>,
+    ) -> Collection<G, Row, Diff>
+    where
+        Tr1: TraceReader<Key = Row, Val = Row, Time = G::Timestamp, R = Diff> + Clone + 'static,
+        Tr1::Batch: BatchReader<Row, Row, G::Timestamp, Diff> + 'static,
+        Tr1::Cursor: Cursor<Row, Row, G::Timestamp, Diff> + 'static,
+        Tr2: TraceReader<Key = Row, Val = Row, Time = G::Timestamp, R = Diff> + Clone + 'static,
+        Tr2::Batch: BatchReader<Row, Row, G::Timestamp, Diff> + 'static,
+        Tr2::Cursor: Cursor<Row, Row, G::Timestamp, Diff> + 'static,
+    {
+        // Reuseable allocation for unpacking.
+        let mut datums = DatumVec::new();
+        let mut row_builder = Row::default();
+
+        // Records whose (non-null) keys match.
+        let (matched, errs) = left
+            .join_core(&right, move |key, old, new| {
+                if key.iter().any(|datum| datum.is_null()) {
+                    return None;
+                }
+                let temp_storage = RowArena::new();
+                let mut datums_local = datums.borrow_with_many(&[key, old, key, new]);
+                matched_closure
+                    .apply(&mut datums_local, &temp_storage, &mut row_builder)
+                    .map_err(DataflowError::from)
+                    .transpose()
+            })
+            .inner
+            .ok_err(|(x, t, d)| match x {
+                Ok(x) => Ok((x, t, d)),
+                Err(x) => Err((x, t, d)),
+            });
+        let mut oks = matched.as_collection();
+        errors.push(errs.as_collection());
+
+        // Records of the preserved inputs whose keys are absent from the other input.
+        let (padded, errs) = unmatched_records(
+            &left,
+            &right,
+            kind,
+            Padding::new(left_closure, 0, right_arity),
+            Padding::new(right_closure, left_arity, 0),
+        )
+        .ok_err(|(x, t, d)| match x {
+            Ok(x) => Ok((x, t, d)),
+            Err(x) => Err((x, t, d)),
+        });
+        oks = oks.concat(&padded.as_collection());
+        errors.push(errs.as_collection());
+
+        oks
+    }
+}
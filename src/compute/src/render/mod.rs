@@ -109,7 +109,7 @@ use timely::dataflow::operators::to_stream::ToStream;
 use timely::dataflow::scopes::Child;
 use timely::dataflow::Scope;
 use timely::progress::Timestamp;
-use timely::worker::Worker as TimelyWorker;
+use timely::worker::{AsWorker, Worker as TimelyWorker};
 
 use mz_dataflow_types::*;
 use mz_expr::{GlobalId, Id};
@@ -118,6 +118,7 @@ use mz_repr::Row;
 
 use crate::arrangement::manager::TraceBundle;
 use crate::compute_state::ComputeState;
+use crate::logging::materialized::ComputeEvent;
 pub use context::CollectionBundle;
 use context::{ArrangementFlavor, Context};
 use mz_storage::boundary::ComputeReplay;
@@ -214,9 +215,24 @@ pub fn build_compute_dataflow<A: Allocate, B: ComputeReplay>(
                 .map(|(sink_id, sink)| (*sink_id, dataflow.depends_on(sink.from), sink.clone()))
                 .collect::<Vec<_>>();
 
-            // Build declared objects.
+            // Build declared objects, logging the operators that make up each
+            // node of their plans.
+            let export_ids = dataflow.export_ids().collect::<Vec<_>>();
             for object in dataflow.objects_to_build {
+                let object_id = object.id;
                 context.build_object(region, object);
+                if let Some(logger) = compute_state.materialized_logger.as_mut() {
+                    for (node, operators) in context.plan_node_operators.drain(..).enumerate() {
+                        for export_id in &export_ids {
+                            logger.log(ComputeEvent::PlanNode {
+                                dataflow: *export_id,
+                                object: object_id,
+                                node,
+                                operators,
+                            });
+                        }
+                    }
+                }
             }
 
             // Export declared indexes.
@@ -289,6 +305,7 @@ where
     G::Timestamp: RenderTimestamp,
 {
     pub(crate) fn build_object(&mut self, scope: &mut G, object: BuildDesc<plan::Plan>) {
+        self.plan_node_operators.clear();
         // First, transform the relation expression into a render plan.
         let bundle = self.render_plan(object.plan, scope, scope.index());
         self.insert_id(Id::Global(object.id), bundle);
@@ -360,11 +377,29 @@ where
     ///
     /// The return type reflects the uncertainty about the data representation, perhaps
     /// as a stream of data, perhaps as an arrangement, perhaps as a stream of batches.
+    ///
+    /// The operators constructed for each plan node are recorded in
+    /// `self.plan_node_operators`, so that introspection data can be
+    /// attributed to the node.
     pub fn render_plan(
         &mut self,
         plan: plan::Plan,
         scope: &mut G,
         worker_index: usize,
+    ) -> CollectionBundle<G, Row> {
+        let node = self.plan_node_operators.len();
+        let start = scope.peek_identifier();
+        self.plan_node_operators.push((start, start));
+        let bundle = self.render_plan_node(plan, scope, worker_index);
+        self.plan_node_operators[node].1 = scope.peek_identifier();
+        bundle
+    }
+
+    fn render_plan_node(
+        &mut self,
+        plan: plan::Plan,
+        scope: &mut G,
+        worker_index: usize,
     ) -> CollectionBundle<G, Row> {
         match plan {
             Plan::Constant { rows } => {
@@ -479,6 +514,9 @@ where
                     mz_dataflow_types::plan::join::JoinPlan::Delta(delta_plan) => {
                         self.render_delta_join(inputs, delta_plan, scope)
                     }
+                    mz_dataflow_types::plan::join::JoinPlan::Outer(outer_plan) => {
+                        self.render_outer_join(inputs, outer_plan, scope)
+                    }
                 }
             }
             Plan::Reduce {
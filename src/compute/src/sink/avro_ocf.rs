@@ -8,20 +8,29 @@
 // by the Apache License, Version 2.0.
 
 use std::any::Any;
-use std::fs::OpenOptions;
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Instant;
 
 use differential_dataflow::{Collection, Hashable};
 
 use itertools::repeat_n;
+use serde::{Deserialize, Serialize};
 use timely::dataflow::channels::pact::Exchange;
-use timely::dataflow::operators::generic::Operator;
+use timely::dataflow::operators::generic::builder_rc::OperatorBuilder;
 use timely::dataflow::Scope;
+use timely::progress::timestamp::Timestamp as TimelyTimestamp;
 use tracing::error;
 
-use mz_dataflow_types::sinks::{AvroOcfSinkConnector, SinkDesc};
+use mz_avro::types::Value;
+use mz_avro::{Codec, Schema};
+use mz_dataflow_types::sinks::{AvroOcfCodec, AvroOcfRollover, AvroOcfSinkConnector, SinkDesc};
 use mz_expr::GlobalId;
 use mz_interchange::avro::{encode_datums_as_avro, AvroSchemaGenerator};
+use mz_ore::cast::CastFrom;
 use mz_repr::{Diff, RelationDesc, Row, Timestamp};
 
 use crate::render::sinks::SinkRender;
@@ -84,52 +93,274 @@ fn avro_ocf<G>(
         (schema, columns)
     };
 
-    let mut vector = vec![];
-    let mut avro_writer = None;
-
-    // We want exactly one worker to write to the single output file
+    // We want exactly one worker to write to the output files
     let hashed_id = id.hashed();
 
-    collection.inner.sink(
-        Exchange::new(move |_| hashed_id),
-        &format!("avro-ocf-{}", id),
-        move |input| {
+    let scope = collection.scope();
+    let mut builder = OperatorBuilder::new(format!("avro-ocf-{}", id), scope.clone());
+    let mut input = builder.new_input(&collection.inner, Exchange::new(move |_| hashed_id));
+    let activator = scope.activator_for(&builder.operator_info().address[..]);
+
+    let mut vector = vec![];
+    // Updates whose times the input frontier has not yet passed. Files are
+    // only ever rolled over at a frontier, so updates are held back until
+    // all updates at their time are known.
+    let mut stash: BTreeMap<Timestamp, Vec<(Row, Diff)>> = BTreeMap::new();
+    let mut current_file: Option<OcfFile> = None;
+    // Where to pick up writing files, recovered from the manifest when the
+    // first update arrives, in case the sink has been rendered before.
+    let mut progress: Option<ManifestProgress> = None;
+
+    builder.build(move |_capabilities| {
+        move |frontiers| {
             input.for_each(|_, rows| {
                 rows.swap(&mut vector);
+                for (v, time, diff) in vector.drain(..) {
+                    stash.entry(time).or_default().push((v, diff));
+                }
+            });
+            // The input frontier is totally ordered, so it is either empty or
+            // a single time.
+            let upper = frontiers[0].frontier().first().copied();
+
+            let mut fallible = || -> Result<(), String> {
+                let ready = match upper {
+                    Some(upper) => {
+                        let pending = stash.split_off(&upper);
+                        std::mem::replace(&mut stash, pending)
+                    }
+                    None => std::mem::take(&mut stash),
+                };
 
-                let mut fallible = || -> Result<(), String> {
-                    let avro_writer = match avro_writer.as_mut() {
-                        Some(v) => v,
+                if !ready.is_empty() {
+                    let file = match current_file.as_mut() {
+                        Some(file) => file,
                         None => {
-                            let file = OpenOptions::new()
-                                .append(true)
-                                .open(&connector.path)
-                                .map_err(|e| {
-                                    format!("creating avro ocf file writer for sink failed: {}", e)
-                                })?;
-                            avro_writer.get_or_insert(mz_avro::Writer::new(schema.clone(), file))
+                            let progress = match progress.as_mut() {
+                                Some(progress) => progress,
+                                None => progress.insert(ManifestProgress::recover(&connector)?),
+                            };
+                            let file = OcfFile::open(
+                                &connector,
+                                progress.next_index,
+                                progress.next_lower,
+                                schema.clone(),
+                            )?;
+                            progress.next_index += 1;
+                            if let Some(duration) = connector.rollover.duration {
+                                // Wake up to roll the file over even if no
+                                // more updates arrive.
+                                activator.activate_after(duration);
+                            }
+                            current_file.insert(file)
                         }
                     };
-
-                    for (v, _time, diff) in vector.drain(..) {
-                        let value = encode_datums_as_avro(v.iter(), &columns);
-                        assert!(diff > 0, "can't sink negative multiplicities");
-                        for value in repeat_n(value, diff as usize) {
-                            avro_writer
-                                .append(value)
-                                .map_err(|e| format!("appending to avro ocf failed: {}", e))?;
+                    for updates in ready.into_values() {
+                        for (v, diff) in updates {
+                            let value = encode_datums_as_avro(v.iter(), &columns);
+                            assert!(diff > 0, "can't sink negative multiplicities");
+                            for value in repeat_n(value, diff as usize) {
+                                file.append(value)?;
+                            }
                         }
                     }
-                    avro_writer
-                        .flush()
-                        .map_err(|e| format!("flushing bytes to avro ocf failed: {}", e))?;
-                    Ok(())
-                };
+                    file.flush()?;
+                }
 
-                if let Err(e) = fallible() {
-                    error!("{}", e);
+                // All updates at times before `upper` have now been written,
+                // so the current file can be completed at `upper`.
+                if let (Some(upper), Some(file)) = (upper, current_file.as_ref()) {
+                    if file.should_roll_over(&connector.rollover) {
+                        let file = current_file.take().expect("known to exist");
+                        file.complete(&connector, upper)?;
+                        progress.as_mut().expect("known to exist").next_lower = upper;
+                    }
                 }
-            })
-        },
-    )
+                Ok(())
+            };
+
+            if let Err(e) = fallible() {
+                error!("{}", e);
+            }
+        }
+    })
+}
+
+/// Where an Avro OCF sink picks up writing files.
+struct ManifestProgress {
+    /// The index of the next file to open.
+    next_index: usize,
+    /// The lower bound of the times of the updates in the next file, which
+    /// is the upper bound of the last completed file.
+    next_lower: Timestamp,
+}
+
+impl ManifestProgress {
+    /// Recovers the sink's progress from the files listed in its manifest.
+    ///
+    /// Files that exist but are not listed in the manifest were being
+    /// written when the sink was last shut down. They are left in place and
+    /// skipped, as they may hold a partial data block. The first file, which
+    /// the coordinator creates empty, is the one exception.
+    fn recover(connector: &AvroOcfSinkConnector) -> Result<Self, String> {
+        let manifest_path = connector.manifest_path();
+        let mut progress = ManifestProgress {
+            next_index: 0,
+            next_lower: Timestamp::minimum(),
+        };
+        let manifest = match fs::read_to_string(&manifest_path) {
+            Ok(manifest) => manifest,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(format!(
+                    "reading avro ocf manifest {} failed: {}",
+                    manifest_path.display(),
+                    e
+                ))
+            }
+        };
+        for line in manifest.lines() {
+            let entry: ManifestEntry = serde_json::from_str(line).map_err(|e| {
+                format!(
+                    "parsing avro ocf manifest {} failed: {}",
+                    manifest_path.display(),
+                    e
+                )
+            })?;
+            progress.next_index += 1;
+            progress.next_lower = entry.upper;
+        }
+        loop {
+            let path = connector.file_path(progress.next_index);
+            match fs::metadata(&path) {
+                Ok(metadata) if metadata.len() > 0 || progress.next_index > 0 => {
+                    progress.next_index += 1
+                }
+                _ => break,
+            }
+        }
+        Ok(progress)
+    }
+}
+
+/// A line of an Avro OCF sink's manifest, which describes a completed file.
+///
+/// The file holds every update at times in `[lower, upper)`.
+#[derive(Serialize, Deserialize)]
+struct ManifestEntry {
+    path: String,
+    lower: Timestamp,
+    upper: Timestamp,
+    records: u64,
+    bytes: u64,
+}
+
+/// A file that an Avro OCF sink is currently writing to.
+struct OcfFile {
+    path: PathBuf,
+    writer: mz_avro::Writer<File>,
+    opened_at: Instant,
+    bytes: u64,
+    records: u64,
+    /// The lower bound of the times of the updates in the file.
+    lower: Timestamp,
+}
+
+impl OcfFile {
+    fn open(
+        connector: &AvroOcfSinkConnector,
+        index: usize,
+        lower: Timestamp,
+        schema: Schema,
+    ) -> Result<Self, String> {
+        let path = connector.file_path(index);
+        // The coordinator creates the first file when the sink is created,
+        // while files the sink rolls over to must not exist yet.
+        let file = OpenOptions::new()
+            .append(true)
+            .create_new(index > 0)
+            .open(&path)
+            .map_err(|e| {
+                format!(
+                    "creating avro ocf file writer for sink file {} failed: {}",
+                    path.display(),
+                    e
+                )
+            })?;
+        let codec = match connector.codec {
+            AvroOcfCodec::Null => Codec::Null,
+            AvroOcfCodec::Deflate => Codec::Deflate,
+            AvroOcfCodec::Snappy => Codec::Snappy,
+            AvroOcfCodec::Zstandard => Codec::Zstandard,
+        };
+        let writer =
+            mz_avro::Writer::with_codec(schema, file, codec).with_block_size(connector.block_size);
+        Ok(OcfFile {
+            path,
+            writer,
+            opened_at: Instant::now(),
+            bytes: 0,
+            records: 0,
+            lower,
+        })
+    }
+
+    fn append(&mut self, value: Value) -> Result<(), String> {
+        let n = self
+            .writer
+            .append(value)
+            .map_err(|e| format!("appending to avro ocf failed: {}", e))?;
+        self.bytes += u64::cast_from(n);
+        self.records += 1;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        let n = self
+            .writer
+            .flush()
+            .map_err(|e| format!("flushing bytes to avro ocf failed: {}", e))?;
+        self.bytes += u64::cast_from(n);
+        Ok(())
+    }
+
+    /// Reports whether the file has crossed one of the rollover thresholds.
+    ///
+    /// Files without any records are never rolled over.
+    fn should_roll_over(&self, rollover: &AvroOcfRollover) -> bool {
+        if self.records == 0 {
+            return false;
+        }
+        let too_big = matches!(rollover.bytes, Some(bytes) if self.bytes >= bytes);
+        let too_old = matches!(rollover.duration, Some(d) if self.opened_at.elapsed() >= d);
+        too_big || too_old
+    }
+
+    /// Closes the file and records it in the sink's manifest.
+    ///
+    /// The file must hold every update at times before `upper`.
+    fn complete(self, connector: &AvroOcfSinkConnector, upper: Timestamp) -> Result<(), String> {
+        let entry = ManifestEntry {
+            path: self.path.display().to_string(),
+            lower: self.lower,
+            upper,
+            records: self.records,
+            bytes: self.bytes,
+        };
+        let entry = serde_json::to_string(&entry).expect("manifest entries serialize");
+        let manifest_path = connector.manifest_path();
+        OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&manifest_path)
+            .and_then(|mut manifest| writeln!(manifest, "{}", entry))
+            .map_err(|e| {
+                format!(
+                    "appending to avro ocf manifest {} failed: {}",
+                    manifest_path.display(),
+                    e
+                )
+            })?;
+        Ok(())
+    }
 }
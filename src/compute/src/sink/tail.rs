@@ -9,13 +9,14 @@
 
 use std::any::Any;
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::ops::DerefMut;
 use std::rc::Rc;
 
-use differential_dataflow::Collection;
+use differential_dataflow::{Collection, Hashable};
 
 use timely::dataflow::channels::pact::Pipeline;
-use timely::dataflow::operators::Operator;
+use timely::dataflow::operators::{Exchange, Map, Operator};
 use timely::dataflow::Scope;
 use timely::progress::timestamp::Timestamp as TimelyTimestamp;
 use timely::progress::Antichain;
@@ -25,7 +26,7 @@ use mz_dataflow_types::{
     TailResponse,
 };
 use mz_expr::GlobalId;
-use mz_repr::{Diff, Row, Timestamp};
+use mz_repr::{Datum, Diff, Row, Timestamp};
 
 use crate::render::sinks::SinkRender;
 
@@ -34,11 +35,11 @@ where
     G: Scope<Timestamp = Timestamp>,
 {
     fn uses_keys(&self) -> bool {
-        false
+        self.upsert_key.is_some()
     }
 
     fn get_key_indices(&self) -> Option<&[usize]> {
-        None
+        self.upsert_key.as_deref()
     }
 
     fn get_relation_key_indices(&self) -> Option<&[usize]> {
@@ -62,6 +63,10 @@ where
             sink_id,
             tail_response_buffer: Some(Rc::clone(&compute_state.tail_response_buffer)),
             prev_upper: Antichain::from_elem(Timestamp::minimum()),
+            upsert: self
+                .upsert_key
+                .clone()
+                .map(|key| UpsertState::new(key, sink.from_desc.arity())),
         })));
         let tail_protocol_weak = Rc::downgrade(&tail_protocol_handle);
 
@@ -69,6 +74,8 @@ where
             sinked_collection,
             sink_id,
             sink.as_of.clone(),
+            self.distinct_timestamps,
+            self.upsert_key.is_some(),
             tail_protocol_handle,
         );
 
@@ -87,33 +94,53 @@ fn tail<G>(
     sinked_collection: Collection<G, (Option<Row>, Option<Row>), Diff>,
     sink_id: GlobalId,
     as_of: SinkAsOf,
+    distinct_timestamps: bool,
+    upsert: bool,
     tail_protocol_handle: Rc<RefCell<Option<TailProtocol>>>,
 ) where
     G: Scope<Timestamp = Timestamp>,
 {
+    let updates = if distinct_timestamps {
+        // Every change counts once, so that changes at the same timestamp can't
+        // cancel out, and a single worker reports each timestamp.
+        sinked_collection
+            .inner
+            .map(|(_, time, _)| ((None, Some(Row::default())), time, 1))
+            .exchange(|_| 0)
+    } else if upsert {
+        // The state of a key is kept by the one worker that all of its updates
+        // are routed to.
+        sinked_collection
+            .inner
+            .exchange(|((key, _), _, _)| key.hashed())
+    } else {
+        sinked_collection.inner
+    };
     let mut results = Vec::new();
-    sinked_collection
-        .inner
-        .sink(Pipeline, &format!("tail-{}", sink_id), move |input| {
-            input.for_each(|_, rows| {
-                for ((k, v), time, diff) in rows.iter() {
-                    assert!(k.is_none(), "tail does not support keys");
-                    let row = v.as_ref().expect("tail must have values");
-                    let should_emit = if as_of.strict {
-                        as_of.frontier.less_than(time)
-                    } else {
-                        as_of.frontier.less_equal(time)
-                    };
-                    if should_emit {
-                        results.push((*time, row.clone(), *diff));
-                    }
+    updates.sink(Pipeline, &format!("tail-{}", sink_id), move |input| {
+        input.for_each(|_, rows| {
+            // Keys, if any, are only used to route updates to workers.
+            for ((_, v), time, diff) in rows.iter() {
+                let row = v.as_ref().expect("tail must have values");
+                let should_emit = if as_of.strict {
+                    as_of.frontier.less_than(time)
+                } else {
+                    as_of.frontier.less_equal(time)
+                };
+                if should_emit {
+                    results.push((*time, row.clone(), *diff));
                 }
-            });
-
-            if let Some(tail_protocol) = tail_protocol_handle.borrow_mut().deref_mut() {
-                tail_protocol.send_batch(input.frontier().frontier().to_owned(), &mut results);
             }
-        })
+        });
+        if distinct_timestamps {
+            results.sort();
+            results.dedup();
+        }
+
+        if let Some(tail_protocol) = tail_protocol_handle.borrow_mut().deref_mut() {
+            tail_protocol.send_batch(input.frontier().frontier().to_owned(), &mut results);
+        }
+    })
 }
 
 /// A type that guides the transmission of rows back to the coordinator.
@@ -125,6 +152,8 @@ struct TailProtocol {
     pub sink_id: GlobalId,
     pub tail_response_buffer: Option<Rc<RefCell<Vec<(GlobalId, TailResponse)>>>>,
     pub prev_upper: Antichain<Timestamp>,
+    /// The state of each key, if the tail emits upserts rather than raw updates.
+    upsert: Option<UpsertState>,
 }
 
 impl TailProtocol {
@@ -141,6 +170,14 @@ impl TailProtocol {
                 }
             }
             *rows = keep;
+            let ship = match &mut self.upsert {
+                None => ship,
+                Some(upsert) => upsert
+                    .apply(ship)
+                    .into_iter()
+                    .map(|(time, row)| (time, row, 1))
+                    .collect(),
+            };
 
             let input_exhausted = upper.is_empty();
             let buffer = self
@@ -175,3 +212,202 @@ impl Drop for TailProtocol {
         }
     }
 }
+
+/// The state of each key of a tail with `ENVELOPE UPSERT`.
+struct UpsertState {
+    /// The indices of the key columns.
+    key: Vec<usize>,
+    /// The number of columns of the sinked relation.
+    arity: usize,
+    /// The values of each key that has any, with their multiplicities.
+    values: BTreeMap<Row, BTreeMap<Row, Diff>>,
+}
+
+impl UpsertState {
+    fn new(key: Vec<usize>, arity: usize) -> Self {
+        UpsertState {
+            key,
+            arity,
+            values: BTreeMap::new(),
+        }
+    }
+
+    /// Applies the consolidated updates of complete timestamps to the state of
+    /// each key.
+    ///
+    /// Returns one row per changed key per time, which consists of the key's
+    /// new state (`upsert`, `delete`, or `key_violation`), the key columns,
+    /// the remaining columns of the key's previous value, and those of its new
+    /// value. The columns of a value are null unless the key had exactly one
+    /// value.
+    fn apply(&mut self, updates: Vec<(Timestamp, Row, Diff)>) -> Vec<(Timestamp, Row)> {
+        let value_arity = self.arity - self.key.len();
+        let mut changes = BTreeMap::<(Timestamp, Row), Vec<(Row, Diff)>>::new();
+        for (time, row, diff) in updates {
+            let datums = row.unpack();
+            let key = Row::pack(self.key.iter().map(|i| datums[*i]));
+            let value = Row::pack(
+                datums
+                    .iter()
+                    .enumerate()
+                    .filter(|(i, _)| !self.key.contains(i))
+                    .map(|(_, datum)| *datum),
+            );
+            changes.entry((time, key)).or_default().push((value, diff));
+        }
+
+        let mut rows = Vec::new();
+        let mut row_buf = Row::default();
+        for ((time, key), updates) in changes {
+            let values = self.values.entry(key.clone()).or_default();
+            let before = single_value(values).cloned();
+            let before_len = values.len();
+            for (value, diff) in updates {
+                let count = values.entry(value.clone()).or_insert(0);
+                *count += diff;
+                if *count == 0 {
+                    values.remove(&value);
+                }
+            }
+            let after = single_value(values).cloned();
+            let state = match (values.len(), &after) {
+                (0, _) => "delete",
+                (_, Some(_)) => "upsert",
+                _ => "key_violation",
+            };
+            if values.is_empty() {
+                self.values.remove(&key);
+                if before_len == 0 {
+                    // The updates of the key cancelled out.
+                    continue;
+                }
+            }
+
+            let mut packer = row_buf.packer();
+            packer.push(Datum::String(state));
+            packer.extend_by_row(&key);
+            for value in [before, after] {
+                match value {
+                    Some(value) => packer.extend_by_row(&value),
+                    None => {
+                        for _ in 0..value_arity {
+                            packer.push(Datum::Null);
+                        }
+                    }
+                }
+            }
+            rows.push((time, row_buf.clone()));
+        }
+        rows
+    }
+}
+
+/// The value of a key, if it has exactly one.
+fn single_value(values: &BTreeMap<Row, Diff>) -> Option<&Row> {
+    match values.iter().next() {
+        Some((value, 1)) if values.len() == 1 => Some(value),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(datums: &[Datum]) -> Row {
+        Row::pack_slice(datums)
+    }
+
+    #[test]
+    fn test_upserts() {
+        let mut state = UpsertState::new(vec![1], 2);
+        let snapshot = vec![
+            (1, row(&[Datum::String("a"), Datum::Int32(1)]), 1),
+            (1, row(&[Datum::String("b"), Datum::Int32(2)]), 1),
+        ];
+        assert_eq!(
+            state.apply(snapshot),
+            vec![
+                (
+                    1,
+                    row(&[
+                        Datum::String("upsert"),
+                        Datum::Int32(1),
+                        Datum::Null,
+                        Datum::String("a")
+                    ])
+                ),
+                (
+                    1,
+                    row(&[
+                        Datum::String("upsert"),
+                        Datum::Int32(2),
+                        Datum::Null,
+                        Datum::String("b")
+                    ])
+                ),
+            ]
+        );
+
+        // The insertion of the new value of key 1 comes before the retraction
+        // of its old value, and key 2 is deleted.
+        let updates = vec![
+            (2, row(&[Datum::String("c"), Datum::Int32(1)]), 1),
+            (2, row(&[Datum::String("b"), Datum::Int32(2)]), -1),
+            (2, row(&[Datum::String("a"), Datum::Int32(1)]), -1),
+        ];
+        assert_eq!(
+            state.apply(updates),
+            vec![
+                (
+                    2,
+                    row(&[
+                        Datum::String("upsert"),
+                        Datum::Int32(1),
+                        Datum::String("a"),
+                        Datum::String("c")
+                    ])
+                ),
+                (
+                    2,
+                    row(&[
+                        Datum::String("delete"),
+                        Datum::Int32(2),
+                        Datum::String("b"),
+                        Datum::Null
+                    ])
+                ),
+            ]
+        );
+
+        // A second value for key 1 is a violation, and the retraction of
+        // either value restores the other.
+        let updates = vec![
+            (3, row(&[Datum::String("d"), Datum::Int32(1)]), 1),
+            (4, row(&[Datum::String("c"), Datum::Int32(1)]), -1),
+        ];
+        assert_eq!(
+            state.apply(updates),
+            vec![
+                (
+                    3,
+                    row(&[
+                        Datum::String("key_violation"),
+                        Datum::Int32(1),
+                        Datum::String("c"),
+                        Datum::Null
+                    ])
+                ),
+                (
+                    4,
+                    row(&[
+                        Datum::String("upsert"),
+                        Datum::Int32(1),
+                        Datum::Null,
+                        Datum::String("d")
+                    ])
+                ),
+            ]
+        );
+    }
+}
@@ -49,17 +49,19 @@ use mz_sql::names::{
     SchemaSpecifier,
 };
 use mz_sql::plan::{
-    ComputeInstanceConfig, ComputeInstanceIntrospectionConfig, CreateIndexPlan, CreateSecretPlan,
-    CreateSinkPlan, CreateSourcePlan, CreateTablePlan, CreateTypePlan, CreateViewPlan, Params,
-    Plan, PlanContext, StatementDesc,
+    CheckConstraint, ComputeInstanceConfig, ComputeInstanceIntrospectionConfig, CreateFunctionPlan,
+    CreateIndexPlan, CreateNotificationPlan, CreateSecretPlan, CreateSinkPlan, CreateSourcePlan,
+    CreateTablePlan, CreateTypePlan, CreateViewPlan, Params, Plan, PlanContext, StatementDesc,
+    UniqueConstraint,
 };
 use mz_sql::DEFAULT_SCHEMA;
 use mz_transform::Optimizer;
 use uuid::Uuid;
 
 use crate::catalog::builtin::{
-    Builtin, BuiltinLog, BuiltinTable, BuiltinType, Fingerprint, BUILTINS, BUILTIN_ROLES,
-    INFORMATION_SCHEMA, MZ_CATALOG_SCHEMA, MZ_INTERNAL_SCHEMA, MZ_TEMP_SCHEMA, PG_CATALOG_SCHEMA,
+    Builtin, BuiltinLog, BuiltinTable, BuiltinType, BuiltinView, Fingerprint, BUILTINS,
+    BUILTIN_ROLES, INFORMATION_SCHEMA, MZ_CATALOG_SCHEMA, MZ_INTERNAL_SCHEMA, MZ_TEMP_SCHEMA,
+    PG_CATALOG_SCHEMA,
 };
 use crate::persistcfg::PersistConfig;
 use crate::session::{PreparedStatement, Session, DEFAULT_DATABASE_NAME};
@@ -191,6 +193,8 @@ impl CatalogState {
             CatalogItem::Table(_) => true,
             item @ CatalogItem::View(_) => item.uses().iter().any(|id| self.uses_tables(*id)),
             CatalogItem::Index(idx) => self.uses_tables(idx.on),
+            CatalogItem::Notification(notification) => self.uses_tables(notification.on),
+            item @ CatalogItem::Function(_) => item.uses().iter().any(|id| self.uses_tables(*id)),
             CatalogItem::Source(_)
             | CatalogItem::Func(_)
             | CatalogItem::Sink(_)
@@ -307,6 +311,9 @@ impl CatalogState {
             })
             | CatalogItem::Sink(Sink {
                 compute_instance, ..
+            })
+            | CatalogItem::Notification(Notification {
+                compute_instance, ..
             }) = item
             {
                 self.compute_instances_by_id
@@ -340,7 +347,7 @@ impl CatalogState {
             &entry.name().qualifiers.schema_spec,
             conn_id,
         );
-        if let CatalogItem::Func(_) = entry.item() {
+        if let CatalogItem::Func(_) | CatalogItem::Function(_) = entry.item() {
             schema.functions.insert(entry.name.item.clone(), entry.id);
         } else {
             schema.items.insert(entry.name.item.clone(), entry.id);
@@ -559,6 +566,13 @@ impl CatalogState {
         self.resolve_builtin_object(&Builtin::<IdReference>::Log(builtin))
     }
 
+    /// Optimized lookup for a builtin view
+    ///
+    /// Panics if the builtin view doesn't exist in the catalog
+    pub fn resolve_builtin_view(&self, builtin: &'static BuiltinView) -> GlobalId {
+        self.resolve_builtin_object(&Builtin::<IdReference>::View(builtin))
+    }
+
     /// Optimized lookup for a builtin object
     ///
     /// Panics if the builtin object doesn't exist in the catalog
@@ -584,7 +598,11 @@ impl CatalogState {
                 },
                 SourceConnector::Local { .. } => Volatile,
             },
-            CatalogItem::Index(_) | CatalogItem::View(_) | CatalogItem::Sink(_) => {
+            CatalogItem::Index(_)
+            | CatalogItem::View(_)
+            | CatalogItem::Sink(_)
+            | CatalogItem::Notification(_)
+            | CatalogItem::Function(_) => {
                 // Volatility follows trinary logic like SQL. If even one
                 // volatile dependency exists, then this item is volatile.
                 // Otherwise, if a single dependency with unknown volatility
@@ -685,6 +703,8 @@ pub enum CatalogItem {
     Type(Type),
     Func(Func),
     Secret(Secret),
+    Notification(Notification),
+    Function(Function),
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -693,6 +713,10 @@ pub struct Table {
     pub desc: RelationDesc,
     #[serde(skip)]
     pub defaults: Vec<Expr<Aug>>,
+    #[serde(skip)]
+    pub unique_constraints: Vec<UniqueConstraint>,
+    #[serde(skip)]
+    pub check_constraints: Vec<CheckConstraint>,
     pub conn_id: Option<u32>,
     pub depends_on: Vec<GlobalId>,
     pub persist_name: Option<String>,
@@ -712,6 +736,8 @@ pub struct Source {
     pub connector: SourceConnector,
     pub persist_details: Option<SerializedSourcePersistDetails>,
     pub desc: RelationDesc,
+    /// The tables that the source writes messages that fail to decode to.
+    pub depends_on: Vec<GlobalId>,
 }
 
 impl Source {
@@ -783,6 +809,25 @@ pub struct Secret {
     pub create_sql: String,
 }
 
+#[derive(Debug, Clone, Serialize)]
+pub struct Notification {
+    pub create_sql: String,
+    pub on: GlobalId,
+    pub predicate: Option<MirScalarExpr>,
+    pub depends_on: Vec<GlobalId>,
+    pub compute_instance: ComputeInstanceId,
+}
+
+/// A function defined in SQL with `CREATE FUNCTION`, as opposed to a builtin
+/// [`Func`].
+#[derive(Debug, Clone, Serialize)]
+pub struct Function {
+    pub create_sql: String,
+    #[serde(skip)]
+    pub inner: mz_sql::func::SqlFunc,
+    pub depends_on: Vec<GlobalId>,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub enum Volatility {
     Volatile,
@@ -810,8 +855,11 @@ impl CatalogItem {
             CatalogItem::View(_) => mz_sql::catalog::CatalogItemType::View,
             CatalogItem::Index(_) => mz_sql::catalog::CatalogItemType::Index,
             CatalogItem::Type(_) => mz_sql::catalog::CatalogItemType::Type,
-            CatalogItem::Func(_) => mz_sql::catalog::CatalogItemType::Func,
+            CatalogItem::Func(_) | CatalogItem::Function(_) => {
+                mz_sql::catalog::CatalogItemType::Func
+            }
             CatalogItem::Secret(_) => mz_sql::catalog::CatalogItemType::Secret,
+            CatalogItem::Notification(_) => mz_sql::catalog::CatalogItemType::Notification,
         }
     }
 
@@ -824,7 +872,9 @@ impl CatalogItem {
             | CatalogItem::Index(_)
             | CatalogItem::Sink(_)
             | CatalogItem::Type(_)
-            | CatalogItem::Secret(_) => Err(SqlCatalogError::InvalidDependency {
+            | CatalogItem::Secret(_)
+            | CatalogItem::Notification(_)
+            | CatalogItem::Function(_) => Err(SqlCatalogError::InvalidDependency {
                 name: name.to_string(),
                 typ: self.typ(),
             }),
@@ -841,6 +891,13 @@ impl CatalogItem {
         }
     }
 
+    pub fn sql_func(&self) -> Option<&mz_sql::func::SqlFunc> {
+        match &self {
+            CatalogItem::Function(function) => Some(&function.inner),
+            _ => None,
+        }
+    }
+
     pub fn source_connector(
         &self,
         name: &QualifiedObjectName,
@@ -858,11 +915,13 @@ impl CatalogItem {
             CatalogItem::Func(_) => &[],
             CatalogItem::Index(idx) => &idx.depends_on,
             CatalogItem::Sink(sink) => &sink.depends_on,
-            CatalogItem::Source(_) => &[],
+            CatalogItem::Source(source) => &source.depends_on,
             CatalogItem::Table(table) => &table.depends_on,
             CatalogItem::Type(typ) => &typ.depends_on,
             CatalogItem::View(view) => &view.depends_on,
             CatalogItem::Secret(_) => &[],
+            CatalogItem::Notification(notification) => &notification.depends_on,
+            CatalogItem::Function(function) => &function.depends_on,
         }
     }
 
@@ -876,7 +935,9 @@ impl CatalogItem {
             | CatalogItem::Table(_)
             | CatalogItem::Type(_)
             | CatalogItem::View(_)
-            | CatalogItem::Secret(_) => false,
+            | CatalogItem::Secret(_)
+            | CatalogItem::Notification(_)
+            | CatalogItem::Function(_) => false,
             CatalogItem::Sink(s) => match s.connector {
                 SinkConnectorState::Pending(_) => true,
                 SinkConnectorState::Ready(_) => false,
@@ -894,8 +955,10 @@ impl CatalogItem {
             CatalogItem::Source(_) => None,
             CatalogItem::Sink(_) => None,
             CatalogItem::Secret(_) => None,
+            CatalogItem::Notification(_) => None,
             CatalogItem::Type(_) => None,
             CatalogItem::Func(_) => None,
+            CatalogItem::Function(_) => None,
         }
     }
 
@@ -954,6 +1017,16 @@ impl CatalogItem {
                 i.create_sql = do_rewrite(i.create_sql)?;
                 Ok(CatalogItem::Secret(i))
             }
+            CatalogItem::Notification(i) => {
+                let mut i = i.clone();
+                i.create_sql = do_rewrite(i.create_sql)?;
+                Ok(CatalogItem::Notification(i))
+            }
+            CatalogItem::Function(i) => {
+                let mut i = i.clone();
+                i.create_sql = do_rewrite(i.create_sql)?;
+                Ok(CatalogItem::Function(i))
+            }
             CatalogItem::Func(_) | CatalogItem::Type(_) => {
                 unreachable!("{}s cannot be renamed", self.typ())
             }
@@ -984,6 +1057,12 @@ impl CatalogEntry {
         self.item.func(self.name())
     }
 
+    /// Returns the inner [`mz_sql::func::SqlFunc`] if this entry is a function
+    /// defined with `CREATE FUNCTION`, else `None`.
+    pub fn sql_func(&self) -> Option<&mz_sql::func::SqlFunc> {
+        self.item.sql_func()
+    }
+
     /// Returns the inner [`Index`] if this entry is an index, else `None`.
     pub fn index(&self) -> Option<&Index> {
         match self.item() {
@@ -1000,6 +1079,14 @@ impl CatalogEntry {
         }
     }
 
+    /// Returns the inner [`Table`] if this entry is a table, else `None`.
+    pub fn table(&self) -> Option<&Table> {
+        match self.item() {
+            CatalogItem::Table(table) => Some(table),
+            _ => None,
+        }
+    }
+
     /// Returns the inner [`Sink`] if this entry is a sink, else `None`.
     pub fn sink(&self) -> Option<&Sink> {
         match self.item() {
@@ -1016,6 +1103,15 @@ impl CatalogEntry {
         }
     }
 
+    /// Returns the inner [`Notification`] if this entry is a notification,
+    /// else `None`.
+    pub fn notification(&self) -> Option<&Notification> {
+        match self.item() {
+            CatalogItem::Notification(notification) => Some(notification),
+            _ => None,
+        }
+    }
+
     /// Returns the [`mz_dataflow_types::sources::SourceConnector`] associated with
     /// this `CatalogEntry`.
     pub fn source_connector(&self) -> Result<&SourceConnector, SqlCatalogError> {
@@ -1230,6 +1326,7 @@ impl Catalog {
                             },
                             persist_details: None,
                             desc: log.variant.desc(),
+                            depends_on: vec![],
                         }),
                     );
                 }
@@ -1251,6 +1348,8 @@ impl Catalog {
                             create_sql: "TODO".to_string(),
                             desc: table.desc.clone(),
                             defaults: vec![Expr::null(); table.desc.arity()],
+                            unique_constraints: vec![],
+                            check_constraints: vec![],
                             conn_id: None,
                             depends_on: vec![],
                             persist_name,
@@ -1835,6 +1934,11 @@ impl Catalog {
         self.state.resolve_builtin_log(builtin)
     }
 
+    /// Resolves a `BuiltinView`.
+    pub fn resolve_builtin_view(&self, builtin: &'static BuiltinView) -> GlobalId {
+        self.state.resolve_builtin_view(builtin)
+    }
+
     /// Resolves `name` to a function [`CatalogEntry`].
     pub fn resolve_function(
         &self,
@@ -2010,6 +2114,15 @@ impl Catalog {
             .contains_key(item_name)
     }
 
+    /// Reports whether the connection `conn_id` has created any temporary
+    /// items.
+    pub fn has_temporary_items(&self, conn_id: u32) -> bool {
+        self.state
+            .temporary_schemas
+            .get(&conn_id)
+            .map_or(false, |schema| !schema.items.is_empty())
+    }
+
     pub fn drop_temp_item_ops(&mut self, conn_id: u32) -> Vec<Op> {
         let ids: Vec<GlobalId> = self.state.temporary_schemas[&conn_id]
             .items
@@ -2663,8 +2776,11 @@ impl Catalog {
                         &metadata.name().qualifiers.schema_spec,
                         conn_id,
                     );
-                    schema
-                        .items
+                    let items = match metadata.item {
+                        CatalogItem::Function(_) => &mut schema.functions,
+                        _ => &mut schema.items,
+                    };
+                    items
                         .remove(&metadata.name().item)
                         .expect("catalog out of sync");
 
@@ -2673,6 +2789,9 @@ impl Catalog {
                     })
                     | CatalogItem::Sink(Sink {
                         compute_instance, ..
+                    })
+                    | CatalogItem::Notification(Notification {
+                        compute_instance, ..
                     }) = metadata.item
                     {
                         assert!(
@@ -2706,11 +2825,15 @@ impl Catalog {
                         &old_entry.name().qualifiers.schema_spec,
                         conn_id,
                     );
-                    schema.items.remove(&old_entry.name().item);
+                    let items = match old_entry.item {
+                        CatalogItem::Function(_) => &mut schema.functions,
+                        _ => &mut schema.items,
+                    };
+                    items.remove(&old_entry.name().item);
                     let mut new_entry = old_entry.clone();
                     new_entry.name = to_name;
                     new_entry.item = to_item;
-                    schema.items.insert(new_entry.name().item.clone(), id);
+                    items.insert(new_entry.name().item.clone(), id);
                     state.entry_by_id.insert(id, new_entry.clone());
                     builtin_table_updates.extend(state.pack_item_update(id, 1));
                 }
@@ -2778,6 +2901,18 @@ impl Catalog {
                 table_persist_name: None,
                 source_persist_details: None,
             },
+            CatalogItem::Notification(notification) => SerializedCatalogItem::V1 {
+                create_sql: notification.create_sql.clone(),
+                eval_env: None,
+                table_persist_name: None,
+                source_persist_details: None,
+            },
+            CatalogItem::Function(function) => SerializedCatalogItem::V1 {
+                create_sql: function.create_sql.clone(),
+                eval_env: None,
+                table_persist_name: None,
+                source_persist_details: None,
+            },
             CatalogItem::Func(_) => unreachable!("cannot serialize functions yet"),
         };
         serde_json::to_vec(&item).expect("catalog serialization cannot fail")
@@ -2799,6 +2934,28 @@ impl Catalog {
         )
     }
 
+    /// Returns a copy of the catalog in which the source `source_id` is
+    /// replaced by `source`, e.g. to plan the views that depend on the source
+    /// against its new definition with [`Catalog::plan_view`].
+    pub fn with_source(&self, source_id: GlobalId, source: Source) -> Catalog {
+        let mut catalog = self.clone();
+        catalog
+            .state
+            .entry_by_id
+            .get_mut(&source_id)
+            .expect("source must exist")
+            .item = CatalogItem::Source(source);
+        catalog
+    }
+
+    /// Plans the view `id`, which is defined by `create_sql`.
+    pub fn plan_view(&self, id: GlobalId, create_sql: String) -> Result<View, anyhow::Error> {
+        match self.parse_item(id, create_sql, Some(&PlanContext::zero()), None, None)? {
+            CatalogItem::View(view) => Ok(view),
+            _ => unreachable!("view definition must plan to a view"),
+        }
+    }
+
     // Parses the given SQL string into a `CatalogItem`.
     //
     // The given `persist_details` are an optional description of the persisted streams that this
@@ -2823,6 +2980,8 @@ impl Catalog {
                     create_sql: table.create_sql,
                     desc: table.desc,
                     defaults: table.defaults,
+                    unique_constraints: table.unique_constraints,
+                    check_constraints: table.check_constraints,
                     conn_id: None,
                     depends_on: table.depends_on,
                     persist_name: table_persist_name,
@@ -2838,6 +2997,7 @@ impl Catalog {
                     connector: source.connector,
                     persist_details: source_persist_details,
                     desc: source.desc,
+                    depends_on: source.depends_on,
                 })
             }
             Plan::CreateView(CreateViewPlan { view, .. }) => {
@@ -2885,6 +3045,22 @@ impl Catalog {
             Plan::CreateSecret(CreateSecretPlan { secret, .. }) => CatalogItem::Secret(Secret {
                 create_sql: secret.create_sql,
             }),
+            Plan::CreateNotification(CreateNotificationPlan { notification, .. }) => {
+                CatalogItem::Notification(Notification {
+                    create_sql: notification.create_sql,
+                    on: notification.on,
+                    predicate: notification.predicate,
+                    depends_on: notification.depends_on,
+                    compute_instance: notification.compute_instance,
+                })
+            }
+            Plan::CreateFunction(CreateFunctionPlan { function, .. }) => {
+                CatalogItem::Function(Function {
+                    create_sql: function.create_sql,
+                    inner: function.func,
+                    depends_on: function.depends_on,
+                })
+            }
             _ => bail!("catalog entry generated inappropriate plan"),
         })
     }
@@ -3079,6 +3255,7 @@ impl From<SerializedPlanContext> for PlanContext {
         PlanContext {
             wall_time: cx.wall_time.unwrap_or_else(|| Utc.timestamp(0, 0)),
             qgm_optimizations: false,
+            qgm_optimizations_fallback: true,
         }
     }
 }
@@ -3440,6 +3617,10 @@ impl mz_sql::catalog::CatalogItem for CatalogEntry {
         Ok(self.func()?)
     }
 
+    fn sql_func(&self) -> Option<&mz_sql::func::SqlFunc> {
+        self.sql_func()
+    }
+
     fn source_connector(&self) -> Result<&SourceConnector, SqlCatalogError> {
         Ok(self.source_connector()?)
     }
@@ -3453,6 +3634,8 @@ impl mz_sql::catalog::CatalogItem for CatalogEntry {
             CatalogItem::Index(Index { create_sql, .. }) => create_sql,
             CatalogItem::Type(Type { create_sql, .. }) => create_sql,
             CatalogItem::Secret(Secret { create_sql, .. }) => create_sql,
+            CatalogItem::Notification(Notification { create_sql, .. }) => create_sql,
+            CatalogItem::Function(Function { create_sql, .. }) => create_sql,
             CatalogItem::Func(_) => "TODO",
         }
     }
@@ -3477,6 +3660,17 @@ impl mz_sql::catalog::CatalogItem for CatalogEntry {
         }
     }
 
+    fn table_unique_constraints(&self) -> Option<&[UniqueConstraint]> {
+        if let CatalogItem::Table(Table {
+            unique_constraints, ..
+        }) = self.item()
+        {
+            Some(unique_constraints)
+        } else {
+            None
+        }
+    }
+
     fn type_details(&self) -> Option<&CatalogTypeDetails<IdReference>> {
         if let CatalogItem::Type(Type { details, .. }) = self.item() {
             Some(details)
@@ -879,6 +879,12 @@ pub const MZ_MATERIALIZATION_DEPENDENCIES: BuiltinLog = BuiltinLog {
     variant: LogVariant::Materialized(MaterializedLog::DataflowDependency),
 };
 
+pub const MZ_DATAFLOW_PLAN_NODES: BuiltinLog = BuiltinLog {
+    name: "mz_dataflow_plan_nodes",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::DataflowPlanNodes),
+};
+
 pub const MZ_WORKER_MATERIALIZATION_FRONTIERS: BuiltinLog = BuiltinLog {
     name: "mz_worker_materialization_frontiers",
     schema: MZ_CATALOG_SCHEMA,
@@ -897,6 +903,12 @@ pub const MZ_PEEK_DURATIONS: BuiltinLog = BuiltinLog {
     variant: LogVariant::Materialized(MaterializedLog::PeekDuration),
 };
 
+pub const MZ_SOURCE_DECODE_ERRORS: BuiltinLog = BuiltinLog {
+    name: "mz_source_decode_errors",
+    schema: MZ_CATALOG_SCHEMA,
+    variant: LogVariant::Materialized(MaterializedLog::SourceDecodeErrors),
+};
+
 pub const MZ_SOURCE_INFO: BuiltinLog = BuiltinLog {
     name: "mz_source_info",
     schema: MZ_CATALOG_SCHEMA,
@@ -1211,6 +1223,18 @@ lazy_static! {
             .with_column("name", ScalarType::String.nullable(false)),
         persistent: false,
     };
+    pub static ref MZ_NOTIFICATIONS: BuiltinTable = BuiltinTable {
+        name: "mz_notifications",
+        schema: MZ_CATALOG_SCHEMA,
+        desc: RelationDesc::empty()
+            .with_column("id", ScalarType::String.nullable(false))
+            .with_column("oid", ScalarType::Oid.nullable(false))
+            .with_column("schema_id", ScalarType::Int64.nullable(false))
+            .with_column("name", ScalarType::String.nullable(false))
+            .with_column("on_id", ScalarType::String.nullable(false))
+            .with_column("cluster_id", ScalarType::Int64.nullable(false)),
+        persistent: false,
+    };
 
 }
 
@@ -2090,6 +2114,7 @@ lazy_static! {
             Builtin::Log(&MZ_DATAFLOW_OPERATORS),
             Builtin::Log(&MZ_DATAFLOW_OPERATORS_ADDRESSES),
             Builtin::Log(&MZ_DATAFLOW_OPERATOR_REACHABILITY_INTERNAL),
+            Builtin::Log(&MZ_DATAFLOW_PLAN_NODES),
             Builtin::Log(&MZ_KAFKA_SOURCE_STATISTICS),
             Builtin::Log(&MZ_MATERIALIZATIONS),
             Builtin::Log(&MZ_MATERIALIZATION_DEPENDENCIES),
@@ -2100,6 +2125,7 @@ lazy_static! {
             Builtin::Log(&MZ_SCHEDULING_ELAPSED_INTERNAL),
             Builtin::Log(&MZ_SCHEDULING_HISTOGRAM_INTERNAL),
             Builtin::Log(&MZ_SCHEDULING_PARKS_INTERNAL),
+            Builtin::Log(&MZ_SOURCE_DECODE_ERRORS),
             Builtin::Log(&MZ_SOURCE_INFO),
             Builtin::Log(&MZ_WORKER_MATERIALIZATION_FRONTIERS),
             Builtin::Table(&MZ_VIEW_KEYS),
@@ -2128,6 +2154,7 @@ lazy_static! {
             Builtin::Table(&MZ_PROMETHEUS_METRICS),
             Builtin::Table(&MZ_CLUSTERS),
             Builtin::Table(&MZ_SECRETS),
+            Builtin::Table(&MZ_NOTIFICATIONS),
             Builtin::View(&MZ_RELATIONS),
             Builtin::View(&MZ_OBJECTS),
             Builtin::View(&MZ_CATALOG_NAMES),
@@ -13,21 +13,22 @@ use mz_dataflow_types::sinks::{AvroOcfSinkConnector, KafkaSinkConnector};
 use mz_expr::{GlobalId, MirScalarExpr};
 use mz_ore::collections::CollectionExt;
 use mz_repr::adt::array::ArrayDimension;
-use mz_repr::{Datum, Diff, Row};
+use mz_repr::{Datum, Diff, Row, ScalarType};
 use mz_sql::ast::{CreateIndexStatement, Statement};
 use mz_sql::catalog::{CatalogDatabase, CatalogType};
+use mz_sql::func::SqlFuncBody;
 use mz_sql::names::{DatabaseId, ResolvedDatabaseSpecifier, SchemaId, SchemaSpecifier};
 use mz_sql_parser::ast::display::AstDisplay;
 
 use crate::catalog::builtin::{
     MZ_ARRAY_TYPES, MZ_AVRO_OCF_SINKS, MZ_BASE_TYPES, MZ_CLUSTERS, MZ_COLUMNS, MZ_DATABASES,
     MZ_FUNCTIONS, MZ_INDEXES, MZ_INDEX_COLUMNS, MZ_KAFKA_SINKS, MZ_LIST_TYPES, MZ_MAP_TYPES,
-    MZ_PSEUDO_TYPES, MZ_ROLES, MZ_SCHEMAS, MZ_SECRETS, MZ_SINKS, MZ_SOURCES, MZ_TABLES, MZ_TYPES,
-    MZ_VIEWS,
+    MZ_NOTIFICATIONS, MZ_PSEUDO_TYPES, MZ_ROLES, MZ_SCHEMAS, MZ_SECRETS, MZ_SINKS, MZ_SOURCES,
+    MZ_TABLES, MZ_TYPES, MZ_VIEWS,
 };
 use crate::catalog::{
-    CatalogItem, CatalogState, Func, Index, Sink, SinkConnector, SinkConnectorState, Source, Table,
-    Type, View, SYSTEM_CONN_ID,
+    CatalogItem, CatalogState, Func, Function, Index, Notification, Sink, SinkConnector,
+    SinkConnectorState, Source, Table, Type, View, SYSTEM_CONN_ID,
 };
 
 /// An update to a built-in table.
@@ -132,6 +133,12 @@ impl CatalogState {
             CatalogItem::Type(ty) => self.pack_type_update(id, oid, schema_id, name, ty, diff),
             CatalogItem::Func(func) => self.pack_func_update(id, schema_id, name, func, diff),
             CatalogItem::Secret(_) => self.pack_secret_update(id, schema_id, name, diff),
+            CatalogItem::Notification(notification) => {
+                self.pack_notification_update(id, oid, schema_id, name, notification, diff)
+            }
+            CatalogItem::Function(function) => {
+                self.pack_function_update(id, oid, schema_id, name, function, diff)
+            }
         };
 
         if let Ok(desc) = entry.desc(&self.resolve_full_name(entry.name(), entry.conn_id())) {
@@ -495,6 +502,63 @@ impl CatalogState {
         updates
     }
 
+    fn pack_function_update(
+        &self,
+        id: GlobalId,
+        oid: u32,
+        schema_id: &SchemaSpecifier,
+        name: &str,
+        function: &Function,
+        diff: Diff,
+    ) -> Vec<BuiltinTableUpdate> {
+        let type_id = |ty: &ScalarType| {
+            let oid = mz_pgrepr::Type::from(ty).oid();
+            self.get_entry_by_oid(&oid).id().to_string()
+        };
+        let arg_ids = function
+            .inner
+            .params
+            .iter()
+            .map(type_id)
+            .collect::<Vec<_>>();
+        let mut row = Row::default();
+        row.packer()
+            .push_array(
+                &[ArrayDimension {
+                    lower_bound: 1,
+                    length: arg_ids.len(),
+                }],
+                arg_ids.iter().map(|id| Datum::String(&id)),
+            )
+            .unwrap();
+        let arg_ids = row.unpack_first();
+
+        // Functions that return a single column report its type, like the
+        // builtin set-returning functions do.
+        let ret_id = match &function.inner.body {
+            SqlFuncBody::Scalar { return_type, .. } => Some(type_id(return_type)),
+            SqlFuncBody::Table { desc, .. } if desc.arity() == 1 => {
+                Some(type_id(&desc.typ().column_types[0].scalar_type))
+            }
+            SqlFuncBody::Table { .. } => None,
+        };
+
+        vec![BuiltinTableUpdate {
+            id: self.resolve_builtin_table(&MZ_FUNCTIONS),
+            row: Row::pack_slice(&[
+                Datum::String(&id.to_string()),
+                Datum::UInt32(oid),
+                Datum::Int64(schema_id.into()),
+                Datum::String(name),
+                arg_ids,
+                Datum::Null,
+                Datum::from(ret_id.as_deref()),
+                function.inner.returns_set().into(),
+            ]),
+            diff,
+        }]
+    }
+
     fn pack_secret_update(
         &self,
         id: GlobalId,
@@ -512,4 +576,27 @@ impl CatalogState {
             diff,
         }]
     }
+
+    fn pack_notification_update(
+        &self,
+        id: GlobalId,
+        oid: u32,
+        schema_id: &SchemaSpecifier,
+        name: &str,
+        notification: &Notification,
+        diff: Diff,
+    ) -> Vec<BuiltinTableUpdate> {
+        vec![BuiltinTableUpdate {
+            id: self.resolve_builtin_table(&MZ_NOTIFICATIONS),
+            row: Row::pack_slice(&[
+                Datum::String(&id.to_string()),
+                Datum::UInt32(oid),
+                Datum::Int64(schema_id.into()),
+                Datum::String(name),
+                Datum::String(&notification.on.to_string()),
+                Datum::Int64(notification.compute_instance),
+            ]),
+            diff,
+        }]
+    }
 }
@@ -22,8 +22,8 @@ use mz_repr::{Datum, Row, ScalarType};
 use mz_sql::ast::{Raw, Statement};
 
 use crate::command::{
-    Canceled, Command, ExecuteResponse, Response, SimpleExecuteResponse, SimpleResult,
-    StartupResponse,
+    AsyncNotification, Canceled, Command, ExecuteResponse, Response, SimpleExecuteResponse,
+    SimpleResult, StartupResponse,
 };
 use crate::error::CoordError;
 use crate::id_alloc::IdAllocator;
@@ -154,17 +154,23 @@ impl ConnClient {
         // an in-progress statement.
         let (cancel_tx, cancel_rx) = watch::channel(Canceled::NotCanceled);
         let cancel_tx = Arc::new(cancel_tx);
+        // Notifications on the channels that the session listens on are
+        // delivered through a channel whose sender the coordinator holds for
+        // as long as the session exists.
+        let (notification_tx, notification_rx) = mpsc::unbounded_channel();
         let mut client = SessionClient {
             inner: self,
             session: Some(session),
             cancel_tx: Arc::clone(&cancel_tx),
             cancel_rx,
+            notification_rx,
         };
         let response = client
             .send(|tx, session| Command::Startup {
                 session,
                 create_user_if_not_exists,
                 cancel_tx,
+                notification_tx,
                 tx,
             })
             .await;
@@ -220,6 +226,7 @@ pub struct SessionClient {
     session: Option<Session>,
     cancel_tx: Arc<watch::Sender<Canceled>>,
     cancel_rx: watch::Receiver<Canceled>,
+    notification_rx: mpsc::UnboundedReceiver<AsyncNotification>,
 }
 
 impl SessionClient {
@@ -244,6 +251,19 @@ impl SessionClient {
         let _ = self.cancel_tx.send(Canceled::NotCanceled);
     }
 
+    /// Waits for a notification on one of the channels that the session
+    /// listens on.
+    ///
+    /// This method is cancel safe.
+    pub async fn recv_notification(&mut self) -> AsyncNotification {
+        match self.notification_rx.recv().await {
+            Some(notification) => notification,
+            // The coordinator only forgets the session after it terminates, so
+            // no notification can arrive anymore.
+            None => std::future::pending().await,
+        }
+    }
+
     // Verify and return the named prepared statement. We need to verify each use
     // to make sure the prepared statement is still safe to use.
     pub async fn get_prepared_statement(
@@ -14,7 +14,7 @@ use std::sync::Arc;
 
 use derivative::Derivative;
 use serde::Serialize;
-use tokio::sync::oneshot;
+use tokio::sync::{mpsc, oneshot};
 
 use mz_dataflow_types::PeekResponseUnary;
 use mz_expr::GlobalId;
@@ -33,6 +33,7 @@ pub enum Command {
         session: Session,
         create_user_if_not_exists: bool,
         cancel_tx: Arc<watch::Sender<Canceled>>,
+        notification_tx: mpsc::UnboundedSender<AsyncNotification>,
         tx: oneshot::Sender<Response<StartupResponse>>,
     },
 
@@ -200,6 +201,12 @@ pub enum ExecuteResponse {
     CreatedSecret {
         existed: bool,
     },
+    /// The requested notification was created.
+    CreatedNotification {
+        existed: bool,
+    },
+    /// The requested function was created.
+    CreatedFunction,
     /// The requested sink was created.
     CreatedSink {
         existed: bool,
@@ -254,6 +261,10 @@ pub enum ExecuteResponse {
     DroppedType,
     /// The requested secret was dropped.
     DroppedSecret,
+    /// The requested notification was dropped.
+    DroppedNotification,
+    /// The requested function was dropped.
+    DroppedFunction,
     /// The provided query was empty.
     EmptyQuery,
     /// Fetch results from a cursor.
@@ -267,6 +278,8 @@ pub enum ExecuteResponse {
     },
     /// The specified number of rows were inserted into the requested table.
     Inserted(usize),
+    /// The session started listening on a notification channel.
+    Listen,
     /// The specified prepared statement was created.
     Prepare,
     /// Rows will be delivered via the specified future.
@@ -284,6 +297,8 @@ pub enum ExecuteResponse {
     Tailing {
         rx: RowBatchStream,
     },
+    /// The session stopped listening on one or all notification channels.
+    Unlisten,
     /// The specified number of rows were updated in the requested table.
     Updated(usize),
     /// Raise a warning.
@@ -304,6 +319,15 @@ pub struct SimpleResult {
     pub col_names: Vec<String>,
 }
 
+/// A notification raised on a channel that a session listens on.
+#[derive(Debug, Clone)]
+pub struct AsyncNotification {
+    /// The name of the channel.
+    pub channel: String,
+    /// The payload of the notification.
+    pub payload: String,
+}
+
 /// The state of a cancellation request.
 #[derive(Debug, Clone, Copy)]
 pub enum Canceled {
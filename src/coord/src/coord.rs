@@ -102,16 +102,18 @@ use mz_dataflow_types::sources::{
 };
 use mz_dataflow_types::{
     BuildDesc, DataflowDesc, DataflowDescription, IndexDesc, PeekResponse, PeekResponseUnary,
-    Update,
+    PlanNodeStatistics, TailResponse, Update,
 };
 use mz_expr::{
-    permutation_for_arrangement, CollectionPlan, ExprHumanizer, GlobalId, MirRelationExpr,
-    MirScalarExpr, OptimizedMirRelationExpr, RowSetFinishing,
+    permutation_for_arrangement, BinaryFunc, CollectionPlan, ExprHumanizer, GlobalId,
+    MirRelationExpr, MirScalarExpr, OptimizedMirRelationExpr, RowSetFinishing,
 };
+use mz_ore::collections::CollectionExt;
 use mz_ore::metrics::MetricsRegistry;
 use mz_ore::now::{to_datetime, EpochMillis, NowFn};
 use mz_ore::retry::Retry;
 use mz_ore::soft_assert_eq;
+use mz_ore::str::StrExt;
 use mz_ore::task;
 use mz_ore::thread::JoinHandleExt;
 use mz_repr::adt::interval::Interval;
@@ -131,21 +133,24 @@ use mz_sql::names::{
 };
 use mz_sql::plan::{
     AlterComputeInstancePlan, AlterIndexEnablePlan, AlterIndexResetOptionsPlan,
-    AlterIndexSetOptionsPlan, AlterItemRenamePlan, ComputeInstanceIntrospectionConfig,
-    CreateComputeInstancePlan, CreateDatabasePlan, CreateIndexPlan, CreateRolePlan,
-    CreateSchemaPlan, CreateSecretPlan, CreateSinkPlan, CreateSourcePlan, CreateTablePlan,
-    CreateTypePlan, CreateViewPlan, CreateViewsPlan, DropComputeInstancesPlan, DropDatabasePlan,
-    DropItemsPlan, DropRolesPlan, DropSchemaPlan, ExecutePlan, ExplainPlan, FetchPlan,
-    HirRelationExpr, IndexOption, IndexOptionName, InsertPlan, MutationKind, OptimizerConfig,
-    Params, PeekPlan, Plan, QueryWhen, RaisePlan, ReadThenWritePlan, SendDiffsPlan,
-    SetVariablePlan, ShowVariablePlan, StatementDesc, TailFrom, TailPlan, View,
+    AlterIndexSetOptionsPlan, AlterItemRenamePlan, AlterSourceRefreshSchemaPlan,
+    ComputeInstanceIntrospectionConfig, CopyFormat, CreateComputeInstancePlan, CreateDatabasePlan,
+    CreateFunctionPlan, CreateIndexPlan, CreateNotificationPlan, CreateRolePlan, CreateSchemaPlan,
+    CreateSecretPlan, CreateSinkPlan, CreateSourcePlan, CreateTablePlan, CreateTypePlan,
+    CreateViewPlan, CreateViewsPlan, DropComputeInstancesPlan, DropDatabasePlan, DropItemsPlan,
+    DropRolesPlan, DropSchemaPlan, ExecutePlan, ExplainAnalyzePlan, ExplainPlan, FetchPlan,
+    HirRelationExpr, IndexOption, IndexOptionName, InsertPlan, ListenPlan, MutationKind,
+    OptimizerConfig, Params, PeekPlan, Plan, QueryWhen, RaisePlan, ReadThenWritePlan,
+    SendDiffsPlan, SetVariablePlan, ShowVariablePlan, StatementDesc, TailFrom, TailPlan,
+    UnlistenPlan, View,
 };
 use mz_sql_parser::ast::RawObjectName;
-use mz_transform::Optimizer;
+use mz_transform::{IndexOracle, Optimizer};
 
 use self::prometheus::Scraper;
 use crate::catalog::builtin::{
-    BUILTINS, MZ_PROMETHEUS_HISTOGRAMS, MZ_PROMETHEUS_METRICS, MZ_PROMETHEUS_READINGS,
+    BUILTINS, MZ_ARRANGEMENT_SIZES, MZ_DATAFLOW_PLAN_NODES, MZ_MESSAGE_COUNTS,
+    MZ_PROMETHEUS_HISTOGRAMS, MZ_PROMETHEUS_METRICS, MZ_PROMETHEUS_READINGS, MZ_SCHEDULING_ELAPSED,
     MZ_VIEW_FOREIGN_KEYS, MZ_VIEW_KEYS,
 };
 use crate::catalog::{
@@ -153,14 +158,16 @@ use crate::catalog::{
 };
 use crate::client::{Client, Handle};
 use crate::command::{
-    Canceled, Command, ExecuteResponse, Response, StartupMessage, StartupResponse,
+    AsyncNotification, Canceled, Command, ExecuteResponse, Response, StartupMessage,
+    StartupResponse,
 };
 use crate::coord::dataflow_builder::{prep_relation_expr, prep_scalar_expr, ExprPrepStyle};
 use crate::coord::id_bundle::CollectionIdBundle;
+use crate::coord::plan_cache::{CachedPeek, PlanCache, PlanCacheKey};
 use crate::error::CoordError;
 use crate::persistcfg::PersisterWithConfig;
 use crate::session::{
-    EndTransactionAction, PreparedStatement, Session, Transaction, TransactionOps,
+    EndTransactionAction, IsolationLevel, PreparedStatement, Session, Transaction, TransactionOps,
     TransactionStatus, WriteOp,
 };
 use crate::sink_connector;
@@ -169,8 +176,10 @@ use crate::util::ClientTransmitter;
 
 pub mod id_bundle;
 
+mod constraints;
 mod dataflow_builder;
 mod indexes;
+mod plan_cache;
 mod prometheus;
 
 #[derive(Debug)]
@@ -178,6 +187,7 @@ pub enum Message {
     Command(Command),
     Worker(mz_dataflow_types::client::Response),
     CreateSourceStatementReady(CreateSourceStatementReady),
+    RefreshSourceSchemaReady(RefreshSourceSchemaReady),
     SinkConnectorReady(SinkConnectorReady),
     ScrapeMetrics,
     SendDiffs(SendDiffs),
@@ -206,6 +216,17 @@ pub struct CreateSourceStatementReady {
     pub params: Params,
 }
 
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct RefreshSourceSchemaReady {
+    pub session: Session,
+    #[derivative(Debug = "ignore")]
+    pub tx: ClientTransmitter<ExecuteResponse>,
+    pub id: GlobalId,
+    pub cascade: bool,
+    pub result: Result<CreateSourceStatement<Raw>, CoordError>,
+}
+
 /// This is the struct meant to be paired with [`Message::WriteLockGrant`], but
 /// could theoretically be used to queue any deferred plan.
 #[derive(Derivative)]
@@ -326,6 +347,11 @@ pub struct Coordinator {
     client_pending_peeks: HashMap<u32, BTreeSet<Uuid>>,
     /// A map from pending tails to the tail description.
     pending_tails: HashMap<GlobalId, PendingTail>,
+    /// The rendered plans of the dataflows that maintain indexes, for use by
+    /// `EXPLAIN ANALYZE`.
+    dataflow_plans: HashMap<GlobalId, DataflowDescription<mz_dataflow_types::Plan>>,
+    /// Optimized plans of recently executed `SELECT` statements.
+    plan_cache: PlanCache,
 
     /// Serializes accesses to write critical sections.
     write_lock: Arc<tokio::sync::Mutex<()>>,
@@ -351,6 +377,11 @@ struct ConnMeta {
     /// requests are required to authenticate with the secret of the connection
     /// that they are targeting.
     secret_key: u32,
+    /// A channel shared with the client on which the coordinator delivers the
+    /// notifications raised on the channels in `listening`.
+    notification_tx: mpsc::UnboundedSender<AsyncNotification>,
+    /// The names of the notifications that the connection listens on.
+    listening: HashSet<QualifiedObjectName>,
 }
 
 struct TxnReads {
@@ -606,6 +637,9 @@ impl Coordinator {
                     )
                     .await?;
                 }
+                CatalogItem::Notification(_) => {
+                    self.ship_notification_dataflow(entry.id()).await?;
+                }
                 _ => (), // Handled in prior loop.
             }
         }
@@ -734,6 +768,9 @@ impl Coordinator {
                 Message::CreateSourceStatementReady(ready) => {
                     self.message_create_source_statement_ready(ready).await
                 }
+                Message::RefreshSourceSchemaReady(ready) => {
+                    self.message_refresh_source_schema_ready(ready).await
+                }
                 Message::SinkConnectorReady(ready) => {
                     self.message_sink_connector_ready(ready).await
                 }
@@ -857,9 +894,15 @@ impl Coordinator {
                     if remove {
                         self.pending_tails.remove(&sink_id);
                     }
+                } else {
+                    self.raise_notification(sink_id, response);
                 }
             }
             DataflowResponse::Compute(ComputeResponse::FrontierUppers(_updates)) => {}
+            DataflowResponse::Compute(ComputeResponse::ArrangementStatistics(_updates)) => {
+                // Already recorded by the controller, which makes them
+                // available to the index oracle.
+            }
             DataflowResponse::Storage(StorageResponse::TimestampBindings(
                 TimestampBindingFeedback {
                     bindings: _,
@@ -896,6 +939,9 @@ impl Coordinator {
             )) => {
                 // TODO(guswynn): communicate `bindings` to `sequence_peek`
             }
+            DataflowResponse::Storage(StorageResponse::DeadLetters(letters)) => {
+                self.write_dead_letters(letters).await;
+            }
         }
     }
 
@@ -926,6 +972,38 @@ impl Coordinator {
         tx.send(result, session);
     }
 
+    async fn message_refresh_source_schema_ready(
+        &mut self,
+        RefreshSourceSchemaReady {
+            mut session,
+            tx,
+            id,
+            cascade,
+            result,
+        }: RefreshSourceSchemaReady,
+    ) {
+        let stmt = match result {
+            Ok(stmt) => stmt,
+            Err(e) => return tx.send(Err(e), session),
+        };
+
+        let plan = match self
+            .handle_statement(
+                &mut session,
+                Statement::CreateSource(stmt),
+                &Params::empty(),
+            )
+            .await
+        {
+            Ok(Plan::CreateSource(plan)) => plan,
+            Ok(_) => unreachable!("planning CREATE SOURCE must result in a Plan::CreateSource"),
+            Err(e) => return tx.send(Err(e), session),
+        };
+
+        let result = self.sequence_refresh_source_schema(id, plan, cascade).await;
+        tx.send(result, session);
+    }
+
     async fn message_sink_connector_ready(
         &mut self,
         SinkConnectorReady {
@@ -1018,6 +1096,7 @@ impl Coordinator {
                 session,
                 create_user_if_not_exists,
                 cancel_tx,
+                notification_tx,
                 tx,
             } => {
                 if let Err(e) = self.catalog.create_temporary_schema(session.conn_id()) {
@@ -1068,6 +1147,8 @@ impl Coordinator {
                     ConnMeta {
                         cancel_tx,
                         secret_key,
+                        notification_tx,
+                        listening: HashSet::new(),
                     },
                 );
 
@@ -1132,11 +1213,17 @@ impl Coordinator {
                 id,
                 columns,
                 rows,
-                mut session,
+                session,
                 tx,
             } => {
-                let result = self.sequence_copy_rows(&mut session, id, columns, rows);
-                let _ = tx.send(Response { result, session });
+                self.sequence_copy_rows(
+                    ClientTransmitter::new(tx, self.internal_cmd_tx.clone()),
+                    session,
+                    id,
+                    columns,
+                    rows,
+                )
+                .await;
             }
 
             Command::Terminate { mut session } => {
@@ -1438,7 +1525,9 @@ impl Coordinator {
                     | Statement::SetVariable(_)
                     | Statement::StartTransaction(_)
                     | Statement::Tail(_)
-                    | Statement::Raise(_) => {
+                    | Statement::Raise(_)
+                    | Statement::Listen(_)
+                    | Statement::Unlisten(_) => {
                         // Always safe.
                     }
 
@@ -1457,6 +1546,7 @@ impl Coordinator {
 
                     // Statements below must by run singly (in Started).
                     Statement::AlterIndex(_)
+                    | Statement::AlterSource(_)
                     | Statement::AlterSecret(_)
                     | Statement::AlterCluster(_)
                     | Statement::AlterObjectRename(_)
@@ -1466,6 +1556,8 @@ impl Coordinator {
                     | Statement::CreateCluster(_)
                     | Statement::CreateSchema(_)
                     | Statement::CreateSecret(_)
+                    | Statement::CreateNotification(_)
+                    | Statement::CreateFunction(_)
                     | Statement::CreateSink(_)
                     | Statement::CreateSource(_)
                     | Statement::CreateTable(_)
@@ -1524,6 +1616,8 @@ impl Coordinator {
                 });
             }
 
+            Statement::Select(_) => self.handle_select(tx, session, stmt, params).await,
+
             // All other statements are handled immediately.
             _ => match self.handle_statement(&mut session, stmt, &params).await {
                 Ok(plan) => self.sequence_plan(tx, session, plan).await,
@@ -1532,6 +1626,97 @@ impl Coordinator {
         }
     }
 
+    /// Handles a `SELECT` statement, reusing the optimized plan of a previous
+    /// execution of the statement if possible.
+    async fn handle_select(
+        &mut self,
+        tx: ClientTransmitter<ExecuteResponse>,
+        mut session: Session,
+        stmt: Statement<Raw>,
+        params: Params,
+    ) {
+        // Names may resolve to the session's temporary items, which are
+        // invisible to other sessions, so the cache is bypassed entirely.
+        if self.catalog.has_temporary_items(session.conn_id()) {
+            return match self.handle_statement(&mut session, stmt, &params).await {
+                Ok(plan) => self.sequence_plan(tx, session, plan).await,
+                Err(e) => tx.send(Err(e), session),
+            };
+        }
+
+        let revision = self.catalog.transient_revision();
+        let key = PlanCacheKey::new(&stmt, &params, &session);
+        if let Some(peek) = self.plan_cache.get(revision, &key) {
+            let CachedPeek { plan, optimized } = peek.clone();
+            let source = match optimized {
+                Some(source) => Ok(source),
+                None => plan
+                    .bind(&params)
+                    .map_err(CoordError::from)
+                    .and_then(|source| Ok(self.view_optimizer.optimize(source)?)),
+            };
+            let resp = match source {
+                Ok(source) => {
+                    self.sequence_optimized_peek(
+                        &mut session,
+                        source,
+                        plan.when,
+                        plan.finishing,
+                        None,
+                    )
+                    .await
+                }
+                Err(e) => Err(e),
+            };
+            return tx.send(resp, session);
+        }
+
+        if let Err(e) = plan_cache::fail_on_miss() {
+            return tx.send(Err(e), session);
+        }
+        let stmt = match stmt {
+            Statement::Select(stmt) => stmt,
+            _ => unreachable!("handle_select called with a non-SELECT statement"),
+        };
+        let plan = match mz_sql::plan::plan_select_unbound(
+            session.pcx(),
+            &self.catalog.for_session(&session),
+            stmt,
+            &params.types,
+        ) {
+            Ok(plan) => plan,
+            Err(e) => return tx.send(Err(e.into()), session),
+        };
+        let source = match plan.bind(&params) {
+            Ok(source) => source,
+            Err(e) => return tx.send(Err(e.into()), session),
+        };
+        let catalog = &self.catalog;
+        let cacheable = plan_cache::is_cacheable(&source, &plan.when, |id| {
+            catalog
+                .try_get_entry(&id)
+                .map_or(false, |entry| entry.item().is_temporary())
+        });
+        let source = match self.view_optimizer.optimize(source) {
+            Ok(source) => source,
+            Err(e) => return tx.send(Err(e.into()), session),
+        };
+        if cacheable {
+            // Without parameters, the statement always binds to the same plan,
+            // which can then be cached optimized.
+            let optimized = params.types.is_empty().then(|| source.clone());
+            let peek = CachedPeek {
+                plan: plan.clone(),
+                optimized,
+            };
+            self.plan_cache.insert(revision, key, peek);
+        }
+        let resp = self
+            .sequence_optimized_peek(&mut session, source, plan.when, plan.finishing, None)
+            .await;
+        tx.send(resp, session);
+    }
+
     /// Instruct the dataflow layer to cancel any ongoing, interactive work for
     /// the named `conn_id`.
     async fn handle_cancel(&mut self, conn_id: u32, secret_key: u32) {
@@ -1695,6 +1880,12 @@ impl Coordinator {
             Plan::CreateSecret(plan) => {
                 tx.send(self.sequence_create_secret(&session, plan).await, session);
             }
+            Plan::CreateNotification(plan) => {
+                tx.send(self.sequence_create_notification(plan).await, session);
+            }
+            Plan::CreateFunction(plan) => {
+                tx.send(self.sequence_create_function(plan).await, session);
+            }
             Plan::CreateSource(_) => unreachable!("handled separately"),
             Plan::CreateSink(plan) => {
                 self.sequence_create_sink(session, plan, tx).await;
@@ -1782,6 +1973,12 @@ impl Coordinator {
             Plan::Explain(plan) => {
                 tx.send(self.sequence_explain(&session, plan), session);
             }
+            Plan::ExplainAnalyze(plan) => {
+                tx.send(
+                    self.sequence_explain_analyze(&mut session, plan).await,
+                    session,
+                );
+            }
             Plan::SendDiffs(plan) => {
                 tx.send(self.sequence_send_diffs(&mut session, plan), session);
             }
@@ -1803,6 +2000,9 @@ impl Coordinator {
             Plan::AlterItemRename(plan) => {
                 tx.send(self.sequence_alter_item_rename(plan).await, session);
             }
+            Plan::AlterSourceRefreshSchema(plan) => {
+                self.sequence_alter_source_refresh_schema(session, plan, tx);
+            }
             Plan::AlterIndexSetOptions(plan) => {
                 tx.send(
                     self.sequence_alter_index_set_options(&session, plan).await,
@@ -1828,6 +2028,7 @@ impl Coordinator {
                     self.drop_temp_items(session.conn_id()).await;
                     let drop_sinks = session.reset();
                     self.drop_sinks(drop_sinks).await;
+                    self.sequence_unlisten(&session, UnlistenPlan { channel: None });
                     Ok(ExecuteResponse::DiscardedAll)
                 } else {
                     Err(CoordError::OperationProhibitsTransaction(
@@ -1916,6 +2117,12 @@ impl Coordinator {
             Plan::Raise(RaisePlan { severity }) => {
                 tx.send(Ok(ExecuteResponse::Raise { severity }), session);
             }
+            Plan::Listen(plan) => {
+                tx.send(Ok(self.sequence_listen(&session, plan)), session);
+            }
+            Plan::Unlisten(plan) => {
+                tx.send(Ok(self.sequence_unlisten(&session, plan)), session);
+            }
         }
     }
 
@@ -2192,6 +2399,8 @@ impl Coordinator {
             create_sql: table.create_sql,
             desc: table.desc,
             defaults: table.defaults,
+            unique_constraints: table.unique_constraints,
+            check_constraints: table.check_constraints,
             conn_id,
             depends_on: table.depends_on,
             persist_name: self
@@ -2265,6 +2474,7 @@ impl Coordinator {
             connector: plan.source.connector,
             persist_details,
             desc: plan.source.desc,
+            depends_on: plan.source.depends_on,
         };
         ops.push(catalog::Op::CreateItem {
             id: source_id,
@@ -2325,35 +2535,7 @@ impl Coordinator {
                 // Do everything to instantiate the source at the coordinator and
                 // inform the timestamper and dataflow workers of its existence before
                 // shipping any dataflows that depend on its existence.
-
-                // Ask persistence if it has a since timestamps for any
-                // of the new sources.
-                let since_ts = self
-                    .persister
-                    .load_source_persist_desc(&source)
-                    .map_err(CoordError::Persistence)?
-                    .map(|p| p.since_ts)
-                    .unwrap_or_else(Timestamp::minimum);
-
-                let source_description = self
-                    .catalog
-                    .state()
-                    .source_description_for(source_id)
-                    .unwrap();
-
-                self.dataflow_client
-                    .storage_mut()
-                    .create_sources(vec![(
-                        source_id,
-                        (source_description, Antichain::from_elem(since_ts)),
-                    )])
-                    .await
-                    .unwrap();
-                self.initialize_storage_read_policies(
-                    vec![source_id],
-                    self.logical_compaction_window_ms,
-                )
-                .await;
+                self.instantiate_source(source_id, &source).await?;
                 if let Some((df, compute_instance)) = df {
                     self.ship_dataflow(df, compute_instance).await;
                 }
@@ -2367,6 +2549,487 @@ impl Coordinator {
         }
     }
 
+    /// Instantiates the source `source_id`, which must have been added to the
+    /// catalog, in the storage layer.
+    async fn instantiate_source(
+        &mut self,
+        source_id: GlobalId,
+        source: &catalog::Source,
+    ) -> Result<(), CoordError> {
+        // Ask persistence if it has a since timestamps for any
+        // of the new sources.
+        let since_ts = self
+            .persister
+            .load_source_persist_desc(source)
+            .map_err(CoordError::Persistence)?
+            .map(|p| p.since_ts)
+            .unwrap_or_else(Timestamp::minimum);
+
+        let source_description = self
+            .catalog
+            .state()
+            .source_description_for(source_id)
+            .unwrap();
+
+        self.dataflow_client
+            .storage_mut()
+            .create_sources(vec![(
+                source_id,
+                (source_description, Antichain::from_elem(since_ts)),
+            )])
+            .await
+            .unwrap();
+        self.initialize_storage_read_policies(vec![source_id], self.logical_compaction_window_ms)
+            .await;
+        Ok(())
+    }
+
+    fn sequence_alter_source_refresh_schema(
+        &mut self,
+        session: Session,
+        plan: AlterSourceRefreshSchemaPlan,
+        tx: ClientTransmitter<ExecuteResponse>,
+    ) {
+        let AlterSourceRefreshSchemaPlan {
+            id,
+            create_stmt,
+            cascade,
+        } = plan;
+        // Fetching the latest schema can take an arbitrarily long time, so
+        // it happens off the main coordinator thread of control, just like the
+        // purification of `CREATE SOURCE` statements.
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        let conn_id = session.conn_id();
+        let purify_fut = mz_sql::pure::purify_refresh_source_schema(
+            self.now(),
+            self.catalog.config().aws_external_id.clone(),
+            create_stmt,
+        );
+        task::spawn(|| format!("refresh_source_schema:{conn_id}"), async move {
+            let result = purify_fut.err_into().await;
+            internal_cmd_tx
+                .send(Message::RefreshSourceSchemaReady(
+                    RefreshSourceSchemaReady {
+                        session,
+                        tx,
+                        id,
+                        cascade,
+                        result,
+                    },
+                ))
+                .expect("sending to internal_cmd_tx cannot fail");
+        });
+    }
+
+    /// Refreshes the source `id` in place to the source described by `plan`,
+    /// which must have the same name and a superset of its columns.
+    ///
+    /// The source keeps its ID, so it keeps its timestamp bindings and resumes
+    /// ingestion where it left off. Running dataflows keep decoding with the
+    /// previous schema, while dataflows built from now on decode with the new
+    /// one. Views that depend on the source are planned again. Views that
+    /// select all columns of the source keep their columns, unless `cascade`
+    /// is set, in which case they gain the new columns. Indexes whose columns
+    /// change, i.e. those on the source and on the views that gain columns,
+    /// are recreated.
+    async fn sequence_refresh_source_schema(
+        &mut self,
+        id: GlobalId,
+        plan: CreateSourcePlan,
+        cascade: bool,
+    ) -> Result<ExecuteResponse, CoordError> {
+        // The source may have been dropped while the latest schema was
+        // fetched.
+        let entry = self
+            .catalog
+            .try_get_entry(&id)
+            .ok_or_else(|| anyhow!("source was dropped while its schema was being refreshed"))?;
+        let old_source = match entry.item() {
+            CatalogItem::Source(source) => source.clone(),
+            _ => unreachable!("ALTER SOURCE ... REFRESH SCHEMA planned for a non-source"),
+        };
+        let full_name = self.catalog.resolve_full_name(entry.name(), None);
+        // Persisted data was decoded with the previous schema, and so lacks
+        // the new columns.
+        if old_source.persist_details.is_some() {
+            return Err(anyhow!(
+                "cannot refresh schema of {}: persisted sources do not support REFRESH SCHEMA",
+                full_name
+            )
+            .into());
+        }
+
+        // Existing columns must keep their names and types, so that the
+        // dependents, and clients, that refer to them are unaffected. Only new
+        // columns that are nullable may be added, as data written with the
+        // previous schema has no values for them.
+        let new_desc = &plan.source.desc;
+        let mut permutation = vec![];
+        for (column_name, column_type) in old_source.desc.iter() {
+            match new_desc.iter().position(|(name, _)| name == column_name) {
+                Some(i)
+                    if new_desc.typ().column_types[i].scalar_type == column_type.scalar_type =>
+                {
+                    permutation.push(i)
+                }
+                Some(_) => {
+                    return Err(anyhow!(
+                        "cannot refresh schema of {}: type of column {} changed",
+                        full_name,
+                        column_name.as_str().quoted()
+                    )
+                    .into())
+                }
+                None => {
+                    return Err(anyhow!(
+                        "cannot refresh schema of {}: column {} was removed",
+                        full_name,
+                        column_name.as_str().quoted()
+                    )
+                    .into())
+                }
+            }
+        }
+        for (i, (column_name, column_type)) in new_desc.iter().enumerate() {
+            if !permutation.contains(&i) && !column_type.nullable {
+                return Err(anyhow!(
+                    "cannot refresh schema of {}: new column {} is not nullable",
+                    full_name,
+                    column_name.as_str().quoted()
+                )
+                .into());
+            }
+        }
+
+        let source = catalog::Source {
+            create_sql: plan.source.create_sql,
+            connector: plan.source.connector,
+            persist_details: old_source.persist_details,
+            desc: plan.source.desc,
+            depends_on: plan.source.depends_on,
+        };
+        let mut ops = vec![];
+        let mut updated_items = vec![];
+        // The dependent views are all planned against the new definition of
+        // the source.
+        let catalog_with_source = self.catalog.with_source(id, source.clone());
+        // The indexes to recreate, with the positions of the columns they
+        // arranged in the relation they are on.
+        let mut indexes = vec![(id, permutation)];
+        for dependent_id in entry.used_by() {
+            let dependent = self.catalog.get_entry(dependent_id);
+            let dependent_name = self
+                .catalog
+                .resolve_full_name(dependent.name(), dependent.conn_id());
+            let old_view = match dependent.item() {
+                CatalogItem::Index(_) => continue,
+                CatalogItem::View(view) => view,
+                _ => {
+                    return Err(anyhow!(
+                        "cannot refresh schema of {}: still depended upon by catalog item '{}'",
+                        full_name,
+                        dependent_name
+                    )
+                    .into())
+                }
+            };
+            let plan_view = |create_sql: String| {
+                catalog_with_source
+                    .plan_view(*dependent_id, create_sql)
+                    .map(|view| catalog::View {
+                        conn_id: old_view.conn_id,
+                        ..view
+                    })
+                    .map_err(|e| {
+                        anyhow!(
+                            "cannot refresh schema of {}: cannot plan view {}: {}",
+                            full_name,
+                            dependent_name,
+                            e
+                        )
+                    })
+            };
+            let mut view = plan_view(old_view.create_sql.clone())?;
+            let view_permutation = match match_columns(&old_view.desc, &view.desc) {
+                Some(permutation) => permutation,
+                None => {
+                    return Err(anyhow!(
+                        "cannot refresh schema of {}: columns of view {} would change",
+                        full_name,
+                        dependent_name
+                    )
+                    .into())
+                }
+            };
+            if view.desc.arity() != old_view.desc.arity() {
+                if cascade {
+                    // The view gains the new columns, which must not change
+                    // the columns of anything but the indexes on the view.
+                    for id in dependent.used_by() {
+                        let dependent = self.catalog.get_entry(id);
+                        if !matches!(dependent.item(), CatalogItem::Index(_)) {
+                            return Err(anyhow!(
+                                "cannot refresh schema of {}: cannot add columns to view {}: \
+                                 still depended upon by catalog item '{}'",
+                                full_name,
+                                dependent_name,
+                                self.catalog
+                                    .resolve_full_name(dependent.name(), dependent.conn_id())
+                            )
+                            .into());
+                        }
+                    }
+                    indexes.push((*dependent_id, view_permutation));
+                } else {
+                    // Restrict the view to the previous columns of the source,
+                    // both now and when its definition is planned on restart.
+                    let mut create_stmt = mz_sql::parse::parse(&old_view.create_sql)
+                        .expect("Sql for existing view should be valid sql")
+                        .into_element();
+                    mz_sql::ast::transform::create_stmt_restrict_relation_columns(
+                        &mut create_stmt,
+                        id,
+                        full_name.clone(),
+                        &old_source.desc.iter_names().cloned().collect::<Vec<_>>(),
+                    );
+                    view = plan_view(create_stmt.to_ast_string_stable())?;
+                    if match_columns(&view.desc, &old_view.desc).is_none() {
+                        return Err(anyhow!(
+                            "cannot refresh schema of {}: columns of view {} would change",
+                            full_name,
+                            dependent_name
+                        )
+                        .into());
+                    }
+                }
+            }
+            updated_items.push((*dependent_id, CatalogItem::View(view)));
+        }
+        updated_items.push((id, CatalogItem::Source(source)));
+
+        let mut index_ids = vec![];
+        let mut create_index_ops = vec![];
+        for (on, permutation) in indexes {
+            for index_id in self.catalog.get_entry(&on).used_by().to_vec() {
+                let index_entry = self.catalog.get_entry(&index_id);
+                let (name, index) = match index_entry.item() {
+                    CatalogItem::Index(index) if index.on == on => {
+                        (index_entry.name().clone(), index.clone())
+                    }
+                    _ => continue,
+                };
+                ops.push(catalog::Op::DropItem(index_id));
+                let new_index_id = self.catalog.allocate_user_id()?;
+                let oid = self.catalog.allocate_oid()?;
+                let keys = index
+                    .keys
+                    .into_iter()
+                    .map(|mut key| {
+                        key.permute(&permutation);
+                        key
+                    })
+                    .collect();
+                index_ids.push((new_index_id, index.compute_instance));
+                create_index_ops.push(catalog::Op::CreateItem {
+                    id: new_index_id,
+                    oid,
+                    name,
+                    item: CatalogItem::Index(catalog::Index { keys, ..index }),
+                });
+            }
+        }
+        ops.extend(
+            updated_items
+                .into_iter()
+                .map(|(id, to_item)| catalog::Op::UpdateItem { id, to_item }),
+        );
+        ops.extend(create_index_ops);
+
+        let dfs = self
+            .catalog_transact(ops, |txn| {
+                let mut dfs = vec![];
+                for (index_id, compute_instance) in &index_ids {
+                    let mut builder = txn.dataflow_builder(*compute_instance);
+                    if let Some(df) = builder.build_index_dataflow(*index_id)? {
+                        dfs.push((df, *compute_instance));
+                    }
+                }
+                Ok(dfs)
+            })
+            .await?;
+        for (df, compute_instance) in dfs {
+            self.ship_dataflow(df, compute_instance).await;
+        }
+        Ok(ExecuteResponse::AlteredObject(ObjectType::Source))
+    }
+
+    async fn sequence_create_notification(
+        &mut self,
+        plan: CreateNotificationPlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let CreateNotificationPlan {
+            name,
+            notification,
+            if_not_exists,
+        } = plan;
+        let id = self.catalog.allocate_user_id()?;
+        let oid = self.catalog.allocate_oid()?;
+        let ops = vec![catalog::Op::CreateItem {
+            id,
+            oid,
+            name,
+            item: CatalogItem::Notification(catalog::Notification {
+                create_sql: notification.create_sql,
+                on: notification.on,
+                predicate: notification.predicate,
+                depends_on: notification.depends_on,
+                compute_instance: notification.compute_instance,
+            }),
+        }];
+        match self.catalog_transact(ops, |_| Ok(())).await {
+            Ok(()) => {
+                self.ship_notification_dataflow(id).await?;
+                Ok(ExecuteResponse::CreatedNotification { existed: false })
+            }
+            Err(CoordError::Catalog(catalog::Error {
+                kind: catalog::ErrorKind::ItemAlreadyExists(_),
+                ..
+            })) if if_not_exists => Ok(ExecuteResponse::CreatedNotification { existed: true }),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn sequence_create_function(
+        &mut self,
+        plan: CreateFunctionPlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let CreateFunctionPlan { name, function } = plan;
+        let id = self.catalog.allocate_user_id()?;
+        let oid = self.catalog.allocate_oid()?;
+        let ops = vec![catalog::Op::CreateItem {
+            id,
+            oid,
+            name,
+            item: CatalogItem::Function(catalog::Function {
+                create_sql: function.create_sql,
+                inner: function.func,
+                depends_on: function.depends_on,
+            }),
+        }];
+        self.catalog_transact(ops, |_| Ok(())).await?;
+        Ok(ExecuteResponse::CreatedFunction)
+    }
+
+    /// Ships the dataflow that raises the notification `id`.
+    ///
+    /// The dataflow is a `TAIL` of the rows of the notification's view that
+    /// satisfy its predicate, projected away to zero columns. Its sink reports
+    /// each timestamp at which these rows changed only once, which
+    /// `raise_notification` turns into a notification.
+    async fn ship_notification_dataflow(&mut self, id: GlobalId) -> Result<(), CoordError> {
+        let notification = self
+            .catalog
+            .get_entry(&id)
+            .notification()
+            .expect("id must refer to a notification")
+            .clone();
+        let compute_instance = notification.compute_instance;
+        let on = self.catalog.get_entry(&notification.on);
+        let desc = on
+            .desc(&self.catalog.resolve_full_name(on.name(), on.conn_id()))
+            .expect("notifications can only be created on relations")
+            .clone();
+
+        let mut expr = MirRelationExpr::global_get(notification.on, desc.typ().clone());
+        if let Some(predicate) = notification.predicate {
+            expr = expr.filter(vec![predicate]);
+        }
+        // The notification only depends on when the rows change, not on their
+        // contents, so there is no need to ship the rows out of the dataflow.
+        let expr = expr.project(vec![]);
+        let view_id = self.allocate_transient_id()?;
+        let expr = self.view_optimizer.optimize(expr)?;
+
+        // Only changes that happen from now on raise the notification, not the
+        // changes in the history of the view that is still retained.
+        let id_bundle = self
+            .index_oracle(compute_instance)
+            .sufficient_collections(&[notification.on]);
+        let mut frontier = self.least_valid_read(&id_bundle, compute_instance);
+        let upper = self.least_valid_write(&id_bundle, compute_instance);
+        if let Some(upper) = upper.elements().get(0) {
+            frontier.join_assign(&Antichain::from_elem(upper.saturating_sub(1)));
+        }
+        let sink_desc = SinkDesc {
+            from: view_id,
+            from_desc: RelationDesc::empty(),
+            connector: SinkConnector::Tail(TailSinkConnector {
+                distinct_timestamps: true,
+                upsert_key: None,
+            }),
+            envelope: None,
+            as_of: SinkAsOf {
+                frontier,
+                strict: true,
+            },
+        };
+
+        let mut dataflow = DataflowDesc::new(format!("notification-{}", id));
+        let mut dataflow_builder = self.dataflow_builder(compute_instance);
+        dataflow_builder.import_view_into_dataflow(&view_id, &expr, &mut dataflow)?;
+        dataflow_builder.build_sink_dataflow_into(&mut dataflow, id, sink_desc)?;
+        self.ship_dataflow(dataflow, compute_instance).await;
+        Ok(())
+    }
+
+    /// Raises the notification `id` once for every timestamp in the `response`
+    /// of its dataflow.
+    ///
+    /// The notification is delivered to all sessions that listen on it. Its
+    /// channel is the unqualified name of the notification, and its payload is
+    /// the timestamp of the changes.
+    fn raise_notification(&mut self, id: GlobalId, response: TailResponse) {
+        // The notification may have been dropped already.
+        let name = match self.catalog.try_get_entry(&id) {
+            Some(entry) if entry.notification().is_some() => entry.name(),
+            _ => return,
+        };
+        let updates = match response {
+            TailResponse::Batch(batch) => batch.updates,
+            TailResponse::DroppedAt(_) => return,
+        };
+        for (timestamp, _, _) in updates {
+            for conn_meta in self.active_conns.values() {
+                if conn_meta.listening.contains(name) {
+                    // The session may have terminated already.
+                    let _ = conn_meta.notification_tx.send(AsyncNotification {
+                        channel: name.item.clone(),
+                        payload: timestamp.to_string(),
+                    });
+                }
+            }
+        }
+    }
+
+    fn sequence_listen(&mut self, session: &Session, plan: ListenPlan) -> ExecuteResponse {
+        if let Some(conn_meta) = self.active_conns.get_mut(&session.conn_id()) {
+            conn_meta.listening.insert(plan.channel);
+        }
+        ExecuteResponse::Listen
+    }
+
+    fn sequence_unlisten(&mut self, session: &Session, plan: UnlistenPlan) -> ExecuteResponse {
+        if let Some(conn_meta) = self.active_conns.get_mut(&session.conn_id()) {
+            match plan.channel {
+                Some(channel) => {
+                    conn_meta.listening.remove(&channel);
+                }
+                None => conn_meta.listening.clear(),
+            }
+        }
+        ExecuteResponse::Unlisten
+    }
+
     async fn sequence_create_sink(
         &mut self,
         session: Session,
@@ -2439,7 +3102,7 @@ impl Coordinator {
                                 )
                                 .unwrap()
                                 .clone(),
-                            connector: SinkConnector::Tail(TailSinkConnector {}),
+                            connector: SinkConnector::Tail(TailSinkConnector::default()),
                             envelope: Some(sink.envelope),
                             as_of: SinkAsOf {
                                 frontier: Antichain::new(),
@@ -2792,6 +3455,8 @@ impl Coordinator {
             ObjectType::Index => ExecuteResponse::DroppedIndex,
             ObjectType::Type => ExecuteResponse::DroppedType,
             ObjectType::Secret => ExecuteResponse::DroppedSecret,
+            ObjectType::Notification => ExecuteResponse::DroppedNotification,
+            ObjectType::Func => ExecuteResponse::DroppedFunction,
             ObjectType::Role => unreachable!("DROP ROLE is handled elsewhere"),
             ObjectType::Cluster => unreachable!("DROP CLUSTER is handled elsewhere"),
             ObjectType::Object => unreachable!("generic OBJECT cannot be dropped"),
@@ -3100,7 +3765,28 @@ impl Coordinator {
     async fn sequence_peek(
         &mut self,
         session: &mut Session,
-        plan: PeekPlan,
+        plan: PeekPlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let PeekPlan {
+            source,
+            when,
+            finishing,
+            copy_to,
+        } = plan;
+        let source = self.view_optimizer.optimize(source)?;
+        self.sequence_optimized_peek(session, source, when, finishing, copy_to)
+            .await
+    }
+
+    /// Like [`Coordinator::sequence_peek`], but for a source that has already
+    /// been optimized.
+    async fn sequence_optimized_peek(
+        &mut self,
+        session: &mut Session,
+        mut source: OptimizedMirRelationExpr,
+        when: QueryWhen,
+        finishing: RowSetFinishing,
+        copy_to: Option<CopyFormat>,
     ) -> Result<ExecuteResponse, CoordError> {
         // TODO: remove this function when sources are linearizable.
         // See: #11048.
@@ -3151,13 +3837,6 @@ impl Coordinator {
             }
         }
 
-        let PeekPlan {
-            mut source,
-            when,
-            finishing,
-            copy_to,
-        } = plan;
-
         let compute_instance = self
             .catalog
             .resolve_compute_instance(session.vars().cluster())?
@@ -3180,7 +3859,8 @@ impl Coordinator {
         let timestamp = if in_transaction && when == QueryWhen::Immediately {
             // Queries are independent of the logical timestamp iff there are no referenced
             // sources or indexes and there is no reference to `mz_logical_timestamp()`.
-            let timestamp_independent = source_ids.is_empty() && !source.contains_temporal();
+            let timestamp_independent =
+                source_ids.is_empty() && !source.as_inner_mut().contains_temporal();
 
             // If all previous statements were timestamp-independent and the current one is
             // not, clear the transaction ops so it can get a new timestamp and timedomain.
@@ -3283,8 +3963,6 @@ impl Coordinator {
         // ------------------------------
         // after we have the timestamp \/
 
-        let source = self.view_optimizer.optimize(source)?;
-
         // We create a dataflow and optimize it, to determine if we can avoid building it.
         // This can happen if the result optimizes to a constant, or to a `Get` expression
         // around a maintained arrangement.
@@ -3372,6 +4050,7 @@ impl Coordinator {
             when,
             copy_to,
             emit_progress,
+            upsert_key,
         } = plan;
 
         let compute_instance = self
@@ -3393,6 +4072,19 @@ impl Coordinator {
             let id_bundle = coord
                 .index_oracle(compute_instance)
                 .sufficient_collections(uses);
+            // A TAIL that resumes from an explicit timestamp without a
+            // snapshot can only deliver the updates after that timestamp if
+            // they have not yet been compacted away.
+            if let (QueryWhen::AtTimestamp(timestamp), false) = (&when, with_snapshot) {
+                let timestamp = coord.evaluate_as_of(session, timestamp.clone())?;
+                let since = coord.least_valid_read(&id_bundle, compute_instance);
+                if !since.less_equal(&timestamp) {
+                    return Err(CoordError::TailResumptionCompacted {
+                        timestamp,
+                        since: since.elements().to_vec(),
+                    });
+                }
+            }
             // If a timestamp was explicitly requested, use that.
             let timestamp =
                 coord.determine_timestamp(session, &id_bundle, when, compute_instance)?;
@@ -3400,7 +4092,10 @@ impl Coordinator {
             Ok::<_, CoordError>(SinkDesc {
                 from,
                 from_desc,
-                connector: SinkConnector::Tail(TailSinkConnector::default()),
+                connector: SinkConnector::Tail(TailSinkConnector {
+                    distinct_timestamps: false,
+                    upsert_key: upsert_key.clone(),
+                }),
                 envelope: None,
                 as_of: SinkAsOf {
                     frontier: Antichain::from_elem(timestamp),
@@ -3447,8 +4142,10 @@ impl Coordinator {
         session.add_drop_sink(compute_instance, *sink_id);
         let arity = sink_desc.from_desc.arity();
         let (tx, rx) = mpsc::unbounded_channel();
-        self.pending_tails
-            .insert(*sink_id, PendingTail::new(tx, emit_progress, arity));
+        self.pending_tails.insert(
+            *sink_id,
+            PendingTail::new(tx, emit_progress, upsert_key, arity),
+        );
         self.ship_dataflow(dataflow, compute_instance).await;
 
         let resp = ExecuteResponse::Tailing { rx };
@@ -3524,6 +4221,40 @@ impl Coordinator {
         since
     }
 
+    /// Evaluates the timestamp expression of an `AS OF` clause.
+    fn evaluate_as_of(
+        &self,
+        session: &Session,
+        mut timestamp: MirScalarExpr,
+    ) -> Result<Timestamp, CoordError> {
+        let temp_storage = RowArena::new();
+        prep_scalar_expr(
+            self.catalog.state(),
+            &mut timestamp,
+            ExprPrepStyle::OneShot {
+                logical_time: None,
+                session,
+            },
+        )?;
+        let evaled = timestamp.eval(&[], &temp_storage)?;
+        let ty = timestamp.typ(&RelationType::empty());
+        Ok(match ty.scalar_type {
+            ScalarType::Numeric { .. } => {
+                let n = evaled.unwrap_numeric().0;
+                u64::try_from(n)?
+            }
+            ScalarType::Int16 => evaled.unwrap_int16().try_into()?,
+            ScalarType::Int32 => evaled.unwrap_int32().try_into()?,
+            ScalarType::Int64 => evaled.unwrap_int64().try_into()?,
+            ScalarType::TimestampTz => evaled.unwrap_timestamptz().timestamp_millis().try_into()?,
+            ScalarType::Timestamp => evaled.unwrap_timestamp().timestamp_millis().try_into()?,
+            _ => coord_bail!(
+                "can't use {} as a timestamp for AS OF",
+                self.catalog.for_session(session).humanize_column_type(&ty)
+            ),
+        })
+    }
+
     /// Determines the timestamp for a query.
     ///
     /// Timestamp determination may fail due to the restricted validity of
@@ -3553,42 +4284,18 @@ impl Coordinator {
 
         let since = self.least_valid_read(&id_bundle, compute_instance);
 
+        // Under strict serializability, queries against the epoch milliseconds
+        // timeline must not read at a time earlier than any previously
+        // acknowledged write or read, regardless of whether they use tables.
+        let strict_serializable = matches!(when, QueryWhen::Immediately)
+            && session.vars().transaction_isolation() == &IsolationLevel::StrictSerializable
+            && self.validate_timeline(id_bundle.iter())? == Some(Timeline::EpochMilliseconds);
+
         // First determine the candidate timestamp, which is either the explicitly requested
         // timestamp, or the latest timestamp known to be immediately available.
         let timestamp: Timestamp = match when {
             // Explicitly requested timestamps should be respected.
-            QueryWhen::AtTimestamp(mut timestamp) => {
-                let temp_storage = RowArena::new();
-                prep_scalar_expr(
-                    self.catalog.state(),
-                    &mut timestamp,
-                    ExprPrepStyle::OneShot {
-                        logical_time: None,
-                        session,
-                    },
-                )?;
-                let evaled = timestamp.eval(&[], &temp_storage)?;
-                let ty = timestamp.typ(&RelationType::empty());
-                match ty.scalar_type {
-                    ScalarType::Numeric { .. } => {
-                        let n = evaled.unwrap_numeric().0;
-                        u64::try_from(n)?
-                    }
-                    ScalarType::Int16 => evaled.unwrap_int16().try_into()?,
-                    ScalarType::Int32 => evaled.unwrap_int32().try_into()?,
-                    ScalarType::Int64 => evaled.unwrap_int64().try_into()?,
-                    ScalarType::TimestampTz => {
-                        evaled.unwrap_timestamptz().timestamp_millis().try_into()?
-                    }
-                    ScalarType::Timestamp => {
-                        evaled.unwrap_timestamp().timestamp_millis().try_into()?
-                    }
-                    _ => coord_bail!(
-                        "can't use {} as a timestamp for AS OF",
-                        self.catalog.for_session(session).humanize_column_type(&ty)
-                    ),
-                }
-            }
+            QueryWhen::AtTimestamp(timestamp) => self.evaluate_as_of(session, timestamp)?,
 
             // These two strategies vary in terms of which traces drive the
             // timestamp determination process: either the trace itself or the
@@ -3639,6 +4346,12 @@ impl Coordinator {
                     }
                 };
                 candidate.join_assign(&advance_to);
+                if strict_serializable {
+                    // The candidate may now be in advance of what the inputs
+                    // have made available, in which case the peek will wait
+                    // for them to catch up.
+                    candidate.join_assign(&self.get_local_read_ts());
+                }
                 candidate
             }
         };
@@ -3646,6 +4359,11 @@ impl Coordinator {
         // If the timestamp is greater or equal to some element in `since` we are
         // assured that the answer will be correct.
         if since.less_equal(&timestamp) {
+            if strict_serializable && timestamp < Timestamp::MAX {
+                // Ensure that subsequent queries (and writes) are ordered after
+                // this one.
+                self.global_timeline.observe_read(timestamp);
+            }
             Ok(timestamp)
         } else {
             let invalid_indexes = id_bundle
@@ -3728,6 +4446,7 @@ impl Coordinator {
             let start = Instant::now();
             let decorrelated_plan = raw_plan.optimize_and_lower(&OptimizerConfig {
                 qgm_optimizations: session.vars().qgm_optimizations(),
+                qgm_optimizations_fallback: session.vars().qgm_optimizations_fallback(),
             })?;
             timings.decorrelation = Some(start.elapsed());
             Ok(decorrelated_plan)
@@ -3808,6 +4527,27 @@ impl Coordinator {
                 if let Some(row_set_finishing) = row_set_finishing {
                     explanation.explain_row_set_finishing(row_set_finishing);
                 }
+                if options.estimates {
+                    let compute = self.dataflow_client.compute(compute_instance).unwrap();
+                    let index_estimates = dataflow
+                        .index_imports
+                        .iter()
+                        .map(|(index_id, (desc, _typ))| {
+                            let statistics = compute
+                                .collection(*index_id)
+                                .ok()
+                                .and_then(|collection| collection.statistics);
+                            (desc.on_id, desc.key.clone(), statistics)
+                        })
+                        .collect();
+                    let index_oracle = self.index_oracle(compute_instance);
+                    let source_estimates = dataflow
+                        .source_imports
+                        .keys()
+                        .map(|id| (*id, index_oracle.cardinality(*id)))
+                        .collect();
+                    explanation.explain_estimates(index_estimates, source_estimates);
+                }
                 explanation.to_string()
             }
             ExplainStage::PhysicalPlan => {
@@ -3952,6 +4692,188 @@ impl Coordinator {
         Ok(send_immediate_rows(rows))
     }
 
+    async fn sequence_explain_analyze(
+        &mut self,
+        session: &mut Session,
+        plan: ExplainAnalyzePlan,
+    ) -> Result<ExecuteResponse, CoordError> {
+        let compute_instance = self
+            .catalog
+            .resolve_compute_instance(session.vars().cluster())?
+            .id;
+
+        // Explaining a view explains the dataflow of its first index on the
+        // active cluster.
+        let index_id = match self.catalog.get_entry(&plan.id).item() {
+            CatalogItem::Index(index) if index.compute_instance == compute_instance => {
+                Some(plan.id)
+            }
+            CatalogItem::Index(_) => None,
+            _ => self
+                .catalog
+                .state()
+                .get_indexes_on(plan.id)
+                .find(|(id, index)| {
+                    index.compute_instance == compute_instance && self.catalog.is_index_enabled(id)
+                })
+                .map(|(id, _)| id),
+        };
+        let name = self
+            .catalog
+            .resolve_full_name(
+                self.catalog.get_entry(&plan.id).name(),
+                Some(session.conn_id()),
+            )
+            .to_string();
+        let dataflow = match index_id.and_then(|id| self.dataflow_plans.get(&id)) {
+            Some(dataflow) => dataflow.clone(),
+            None => coord_bail!(
+                "cannot analyze {}: it is not materialized on cluster {}",
+                name.quoted(),
+                session.vars().cluster().quoted()
+            ),
+        };
+        let index_id = index_id.expect("dataflow found");
+
+        let mut explanation = mz_dataflow_types::AnalyzedPlanExplanation::new(
+            dataflow,
+            &self.catalog.for_session(session),
+        );
+
+        let get = |id: GlobalId| {
+            let entry = self.catalog.get_entry(&id);
+            let desc = entry
+                .desc(&self.catalog.resolve_full_name(entry.name(), None))
+                .expect("builtin views have a description");
+            MirRelationExpr::global_get(id, desc.typ().clone())
+        };
+        let peek = |source: MirRelationExpr| PeekPlan {
+            finishing: RowSetFinishing {
+                order_by: vec![],
+                limit: None,
+                offset: 0,
+                project: (0..source.arity()).collect(),
+            },
+            source,
+            when: QueryWhen::Immediately,
+            copy_to: None,
+        };
+        // The plan nodes of the dataflow, as (export_id, object_id, node,
+        // worker, operator_start, operator_end).
+        let plan_nodes =
+            get(self.catalog.resolve_builtin_log(&MZ_DATAFLOW_PLAN_NODES)).filter(vec![
+                MirScalarExpr::Column(0).call_binary(
+                    MirScalarExpr::literal_ok(
+                        Datum::String(&index_id.to_string()),
+                        ScalarType::String,
+                    ),
+                    BinaryFunc::Eq,
+                ),
+            ]);
+        // Restricts `relation` to the rows whose operator (or channel), in
+        // column `operator`, was constructed for a plan node of the dataflow
+        // on the worker in column `worker`. This keeps the statistics of other
+        // dataflows from being shipped to the coordinator only to be dropped.
+        let restrict = |relation: MirRelationExpr, operator: usize, worker: usize| {
+            let arity = relation.arity();
+            let operator = MirScalarExpr::Column(operator);
+            MirRelationExpr::join(
+                vec![relation, plan_nodes.clone()],
+                vec![vec![(0, worker), (1, 3)]],
+            )
+            .filter(vec![
+                operator
+                    .clone()
+                    .call_binary(MirScalarExpr::Column(arity + 4), BinaryFunc::Gte),
+                operator.call_binary(MirScalarExpr::Column(arity + 5), BinaryFunc::Lt),
+            ])
+            .project((0..arity).collect())
+            .distinct()
+        };
+        // (id, worker, elapsed_ns)
+        let elapsed = restrict(
+            get(self.catalog.resolve_builtin_view(&MZ_SCHEDULING_ELAPSED)),
+            0,
+            1,
+        );
+        // (operator, worker, records, batches)
+        let sizes = restrict(
+            get(self.catalog.resolve_builtin_view(&MZ_ARRANGEMENT_SIZES)),
+            0,
+            1,
+        );
+        // (channel, source_worker, target_worker, sent, received). Dataflows
+        // are constructed identically on all workers, so a channel belongs to
+        // the dataflow on its source worker if and only if it does on its
+        // target worker.
+        let messages = restrict(
+            get(self.catalog.resolve_builtin_view(&MZ_MESSAGE_COUNTS)),
+            0,
+            1,
+        );
+        let plans = [peek(plan_nodes), peek(elapsed), peek(sizes), peek(messages)];
+        let mut responses = vec![];
+        for plan in plans {
+            match self.sequence_peek(session, plan).await? {
+                ExecuteResponse::SendingRows(rows) => responses.push(rows),
+                _ => unreachable!("peeks send rows"),
+            }
+        }
+
+        Ok(ExecuteResponse::SendingRows(Box::pin(async move {
+            let mut results = vec![];
+            for response in responses {
+                match response.await {
+                    PeekResponseUnary::Rows(rows) => results.push(rows),
+                    response => return response,
+                }
+            }
+            let int = |datum: Datum| usize::try_from(datum.unwrap_int64()).unwrap_or(0);
+            for row in &results[0] {
+                let datums = row.unpack();
+                let object = datums[1].unwrap_str().parse().expect("valid global id");
+                explanation.add_plan_node(
+                    object,
+                    int(datums[2]),
+                    int(datums[3]),
+                    int(datums[4])..int(datums[5]),
+                );
+            }
+            for row in &results[1] {
+                let datums = row.unpack();
+                let statistics = PlanNodeStatistics {
+                    elapsed_ns: datums[2].unwrap_int64().try_into().unwrap_or(0),
+                    ..Default::default()
+                };
+                explanation.add_operator_statistics(int(datums[1]), int(datums[0]), statistics);
+            }
+            for row in &results[2] {
+                let datums = row.unpack();
+                let statistics = PlanNodeStatistics {
+                    records: datums[2].unwrap_int64(),
+                    batches: datums[3].unwrap_int64(),
+                    ..Default::default()
+                };
+                explanation.add_operator_statistics(int(datums[1]), int(datums[0]), statistics);
+            }
+            for row in &results[3] {
+                let datums = row.unpack();
+                let sent = PlanNodeStatistics {
+                    messages_sent: datums[3].unwrap_int64(),
+                    ..Default::default()
+                };
+                explanation.add_operator_statistics(int(datums[1]), int(datums[0]), sent);
+                let received = PlanNodeStatistics {
+                    messages_received: datums[4].unwrap_int64(),
+                    ..Default::default()
+                };
+                explanation.add_operator_statistics(int(datums[2]), int(datums[0]), received);
+            }
+            let explanation = explanation.to_string();
+            PeekResponseUnary::Rows(vec![Row::pack_slice(&[Datum::from(&*explanation)])])
+        })))
+    }
+
     fn sequence_send_diffs(
         &mut self,
         session: &mut Session,
@@ -3982,14 +4904,30 @@ impl Coordinator {
                 // of rows we see, but the sum of the absolute value of their diffs,
                 // e.g. if one row is retracted and another is added, the total
                 // number of rows affected is 2.
+                //
+                // Inserts only retract rows when `ON CONFLICT DO UPDATE`
+                // replaces them, which counts once, as the added row.
                 for (_, diff) in plan.updates.iter() {
-                    affected_rows += diff.abs();
+                    affected_rows += match plan.kind {
+                        MutationKind::Insert => std::cmp::max(*diff, 0),
+                        _ => diff.abs(),
+                    };
                 }
             }
 
             usize::try_from(affected_rows).expect("positive isize must fit")
         };
 
+        if let Some(entry) = self.catalog.try_get_entry(&plan.id) {
+            if let Some(table) = entry.table() {
+                constraints::validate_checks(
+                    &entry.name().item,
+                    &table.check_constraints,
+                    &plan.updates,
+                )?;
+            }
+        }
+
         session.add_transaction_ops(TransactionOps::Writes(vec![WriteOp {
             id: plan.id,
             rows: plan.updates,
@@ -4021,7 +4959,17 @@ impl Coordinator {
             }
         };
 
+        let has_unique_constraints = self
+            .catalog
+            .try_get_entry(&plan.id)
+            .and_then(|entry| entry.table())
+            .map_or(false, |table| !table.unique_constraints.is_empty());
+
         match optimized_mir.into_inner() {
+            constants @ MirRelationExpr::Constant { .. } if has_unique_constraints => {
+                self.sequence_insert_constant_unique(tx, session, plan.id, constants)
+                    .await
+            }
             constants @ MirRelationExpr::Constant { .. } => tx.send(
                 self.sequence_insert_constant(&mut session, plan.id, constants),
                 session,
@@ -4071,6 +5019,7 @@ impl Coordinator {
                     finishing,
                     assignments: HashMap::new(),
                     kind: MutationKind::Insert,
+                    on_conflict: None,
                 };
 
                 self.sequence_read_then_write(tx, session, read_then_write_plan)
@@ -4121,18 +5070,181 @@ impl Coordinator {
         }
     }
 
-    fn sequence_copy_rows(
+    /// Inserts `constants` into the table `id`, which has unique constraints.
+    ///
+    /// The inserted rows are validated against the rows of the table that
+    /// share a key with them, which are read at the same timestamp as writes
+    /// are validated at, along with the writes the transaction has yet to
+    /// commit. The session takes the write lock first, so that the table can't
+    /// change before the writes commit.
+    ///
+    /// In an explicit transaction, the session keeps the write lock until the
+    /// transaction ends, so the validation of each statement still holds when
+    /// the transaction commits. This blocks all other writes to tables for the
+    /// rest of the transaction.
+    async fn sequence_insert_constant_unique(
         &mut self,
-        session: &mut Session,
+        tx: ClientTransmitter<ExecuteResponse>,
+        mut session: Session,
+        id: GlobalId,
+        constants: MirRelationExpr,
+    ) {
+        guard_write_critical_section!(
+            self,
+            tx,
+            session,
+            Plan::Insert(InsertPlan {
+                id,
+                values: constants
+            })
+        );
+
+        // Insert can be queued, so we need to re-verify the id exists.
+        let (desc, unique_constraints) = match self.catalog.try_get_entry(&id) {
+            Some(entry) => (
+                entry
+                    .desc(
+                        &self
+                            .catalog
+                            .resolve_full_name(entry.name(), Some(session.conn_id())),
+                    )
+                    .expect("desc called on table")
+                    .clone(),
+                entry
+                    .table()
+                    .expect("attempted to insert into non-table")
+                    .unique_constraints
+                    .clone(),
+            ),
+            None => {
+                tx.send(
+                    Err(CoordError::SqlCatalog(CatalogError::UnknownItem(
+                        id.to_string(),
+                    ))),
+                    session,
+                );
+                return;
+            }
+        };
+        let rows = match constants {
+            MirRelationExpr::Constant { rows, typ: _ } => rows,
+            o => panic!(
+                "tried using sequence_insert_constant_unique on non-constant MirRelationExpr {:?}",
+                o
+            ),
+        };
+        let rows = match || -> Result<Vec<(Row, Diff)>, CoordError> {
+            let rows = rows?;
+            for (row, _) in &rows {
+                for (i, datum) in row.iter().enumerate() {
+                    desc.constraints_met(i, &datum)?;
+                }
+            }
+            Ok(rows)
+        }() {
+            Ok(rows) => rows,
+            Err(e) => {
+                tx.send(Err(e), session);
+                return;
+            }
+        };
+
+        let existing_response =
+            match constraints::unique_key_lookup(id, &desc, &unique_constraints, &rows) {
+                Some(source) => {
+                    let ts = self.get_local_read_ts();
+                    let ts = MirScalarExpr::literal_ok(
+                        Datum::from(Numeric::from(ts)),
+                        ScalarType::Numeric {
+                            max_scale: Some(NumericMaxScale::ZERO),
+                        },
+                    );
+                    let plan = PeekPlan {
+                        source,
+                        when: QueryWhen::AtTimestamp(ts),
+                        finishing: RowSetFinishing {
+                            order_by: vec![],
+                            limit: None,
+                            offset: 0,
+                            project: (0..desc.arity()).collect(),
+                        },
+                        copy_to: None,
+                    };
+                    match self.sequence_peek(&mut session, plan).await {
+                        Ok(resp) => Some(resp),
+                        Err(e) => {
+                            tx.send(Err(e), session);
+                            return;
+                        }
+                    }
+                }
+                // None of the inserted rows has a key that could conflict.
+                None => None,
+            };
+        let pending_writes = session.pending_writes(id);
+
+        let internal_cmd_tx = self.internal_cmd_tx.clone();
+        task::spawn(
+            || format!("sequence_insert_constant_unique:{id}"),
+            async move {
+                let existing = match existing_response {
+                    None => Ok(vec![]),
+                    Some(ExecuteResponse::SendingRows(batch)) => match batch.await {
+                        PeekResponseUnary::Rows(rows) => Ok(rows),
+                        PeekResponseUnary::Canceled => {
+                            Err(CoordError::Unstructured(anyhow!("execution canceled")))
+                        }
+                        PeekResponseUnary::Error(e) => Err(CoordError::Unstructured(anyhow!(e))),
+                    },
+                    Some(_) => Err(CoordError::Unstructured(anyhow!("expected SendingRows"))),
+                };
+                let diffs = existing.and_then(|existing| {
+                    constraints::validate_unique(
+                        &desc,
+                        &unique_constraints,
+                        &existing,
+                        &pending_writes,
+                        &rows,
+                    )?;
+                    Ok(rows)
+                });
+                internal_cmd_tx
+                    .send(Message::SendDiffs(SendDiffs {
+                        session,
+                        tx,
+                        id,
+                        diffs,
+                        kind: MutationKind::Insert,
+                    }))
+                    .expect("sending to internal_cmd_tx cannot fail");
+            },
+        );
+    }
+
+    async fn sequence_copy_rows(
+        &mut self,
+        tx: ClientTransmitter<ExecuteResponse>,
+        session: Session,
         id: GlobalId,
         columns: Vec<usize>,
         rows: Vec<Row>,
-    ) -> Result<ExecuteResponse, CoordError> {
-        let catalog = self.catalog.for_session(session);
-        let values = mz_sql::plan::plan_copy_from(&session.pcx(), &catalog, id, columns, rows)?;
-        let values = self.view_optimizer.optimize(values.lower())?;
-        // Copied rows must always be constants.
-        self.sequence_insert_constant(session, id, values.into_inner())
+    ) {
+        let catalog = self.catalog.for_session(&session);
+        let values = match mz_sql::plan::plan_copy_from(&session.pcx(), &catalog, id, columns, rows)
+        {
+            Ok(values) => values,
+            Err(e) => {
+                tx.send(Err(e.into()), session);
+                return;
+            }
+        };
+        // Copied rows are planned like an insert of constants so that they
+        // are subject to the same constraints.
+        let plan = InsertPlan {
+            id,
+            values: values.lower(),
+        };
+        self.sequence_insert(tx, session, plan).await;
     }
 
     // ReadThenWrite is a plan whose writes depend on the results of a
@@ -4153,6 +5265,7 @@ impl Coordinator {
             selection,
             assignments,
             finishing,
+            on_conflict,
         } = plan;
 
         // Read then writes can be queued, so re-verify the id exists.
@@ -4202,7 +5315,7 @@ impl Coordinator {
                 &mut session,
                 PeekPlan {
                     source: selection,
-                    when: QueryWhen::AtTimestamp(ts),
+                    when: QueryWhen::AtTimestamp(ts.clone()),
                     finishing,
                     copy_to: None,
                 },
@@ -4216,6 +5329,46 @@ impl Coordinator {
             }
         };
 
+        // Writes that add rows to a table with unique constraints must be
+        // validated against the contents of the table at the same timestamp
+        // as the read, along with any writes the transaction has yet to
+        // commit. Holding the write lock keeps the table from changing
+        // underneath us before the writes commit, including in explicit
+        // transactions, as in `sequence_insert_constant_unique`.
+        //
+        // TODO: only read the rows that share a key with the new rows, rather
+        // than the whole table.
+        let unique_constraints = self
+            .catalog
+            .try_get_entry(&id)
+            .and_then(|entry| entry.table())
+            .map(|table| table.unique_constraints.clone())
+            .unwrap_or_default();
+        let existing_response =
+            if unique_constraints.is_empty() || matches!(kind, MutationKind::Delete) {
+                None
+            } else {
+                let plan = PeekPlan {
+                    source: MirRelationExpr::global_get(id, desc.typ().clone()),
+                    when: QueryWhen::AtTimestamp(ts),
+                    finishing: RowSetFinishing {
+                        order_by: vec![],
+                        limit: None,
+                        offset: 0,
+                        project: (0..desc.arity()).collect(),
+                    },
+                    copy_to: None,
+                };
+                match self.sequence_peek(&mut session, plan).await {
+                    Ok(resp) => Some(resp),
+                    Err(e) => {
+                        tx.send(Err(e), session);
+                        return;
+                    }
+                }
+            };
+        let pending_writes = session.pending_writes(id);
+
         let internal_cmd_tx = self.internal_cmd_tx.clone();
         task::spawn(|| format!("sequence_read_then_write:{id}"), async move {
             let arena = RowArena::new();
@@ -4257,7 +5410,12 @@ impl Coordinator {
                                     MutationKind::Update | MutationKind::Delete => {
                                         diffs.push((row, -1))
                                     }
-                                    MutationKind::Insert => diffs.push((row, 1)),
+                                    MutationKind::Insert => {
+                                        for (i, datum) in row.iter().enumerate() {
+                                            desc.constraints_met(i, &datum)?;
+                                        }
+                                        diffs.push((row, 1))
+                                    }
                                 }
                             }
                             Ok(diffs)
@@ -4270,6 +5428,38 @@ impl Coordinator {
                 },
                 _ => Err(CoordError::Unstructured(anyhow!("expected SendingRows"))),
             };
+            let diffs = match (diffs, existing_response) {
+                (Ok(diffs), Some(ExecuteResponse::SendingRows(batch))) => match batch.await {
+                    PeekResponseUnary::Rows(existing) => {
+                        || -> Result<Vec<(Row, Diff)>, CoordError> {
+                            let diffs = match &on_conflict {
+                                Some(on_conflict) => constraints::resolve_conflicts(
+                                    &desc,
+                                    on_conflict,
+                                    &existing,
+                                    &pending_writes,
+                                    diffs,
+                                )?,
+                                None => diffs,
+                            };
+                            constraints::validate_unique(
+                                &desc,
+                                &unique_constraints,
+                                &existing,
+                                &pending_writes,
+                                &diffs,
+                            )?;
+                            Ok(diffs)
+                        }()
+                    }
+                    PeekResponseUnary::Canceled => {
+                        Err(CoordError::Unstructured(anyhow!("execution canceled")))
+                    }
+                    PeekResponseUnary::Error(e) => Err(CoordError::Unstructured(anyhow!(e))),
+                },
+                (Ok(_), Some(_)) => Err(CoordError::Unstructured(anyhow!("expected SendingRows"))),
+                (diffs, _) => diffs,
+            };
             internal_cmd_tx
                 .send(Message::SendDiffs(SendDiffs {
                     session,
@@ -4416,6 +5606,11 @@ impl Coordinator {
                     CatalogItem::Secret(_) => {
                         secrets_to_drop.push(*id);
                     }
+                    CatalogItem::Notification(catalog::Notification {
+                        compute_instance, ..
+                    }) => {
+                        sinks_to_drop.push((*compute_instance, *id));
+                    }
                     _ => (),
                 }
             }
@@ -4541,6 +5736,72 @@ impl Coordinator {
         }
     }
 
+    /// Appends rows describing messages that sources failed to decode to the
+    /// tables that the sources redirect them to.
+    ///
+    /// Like builtin table updates, the rows are written at the current write
+    /// timestamp without taking the write lock, as they only ever add rows.
+    /// Rows for tables that have since been dropped are discarded, as are rows
+    /// that violate a check constraint of their table, with a warning.
+    async fn write_dead_letters(&mut self, letters: Vec<(GlobalId, Vec<Row>)>) {
+        let timestamp = self.get_local_write_ts();
+        let mut writes = vec![];
+        for (id, mut rows) in letters {
+            let entry = match self.catalog.try_get_entry(&id) {
+                Some(entry) => entry,
+                None => continue,
+            };
+            if let Some(table) = entry.table() {
+                rows.retain(|row| {
+                    let checked = constraints::validate_checks(
+                        &entry.name().item,
+                        &table.check_constraints,
+                        &[(row.clone(), 1)],
+                    );
+                    if let Err(e) = &checked {
+                        warn!("dropping message that failed to decode: {}", e);
+                    }
+                    checked.is_ok()
+                });
+                writes.push((id, rows));
+            }
+        }
+        for (id, rows) in writes {
+            if rows.is_empty() {
+                continue;
+            }
+            if let Some(persist) = self.persister.table_details.get(&id) {
+                let updates: Vec<((Row, ()), Timestamp, Diff)> = rows
+                    .into_iter()
+                    .map(|row| ((row, ()), timestamp, 1))
+                    .collect();
+                // NB: As for builtin table updates, keep this method call
+                // outside the tokio::spawn, so that writes and seals happen in
+                // order.
+                let write_fut = persist.write_handle.write(&updates);
+                task::spawn(|| "dead_letters_write_fut", async move {
+                    if let Err(e) = write_fut.await {
+                        warn!("failed to write messages that failed to decode: {}", e);
+                    }
+                });
+            } else {
+                let updates = rows
+                    .into_iter()
+                    .map(|row| Update {
+                        row,
+                        diff: 1,
+                        timestamp,
+                    })
+                    .collect();
+                self.dataflow_client
+                    .storage_mut()
+                    .table_insert(id, updates)
+                    .await
+                    .unwrap();
+            }
+        }
+    }
+
     async fn send_builtin_table_updates(&mut self, updates: Vec<BuiltinTableUpdate>) {
         let timestamped = TimestampedUpdate {
             updates,
@@ -4572,6 +5833,7 @@ impl Coordinator {
         let mut by_compute_instance = HashMap::new();
         for (compute_instance, id) in indexes {
             if self.read_capability.remove(&id).is_some() {
+                self.dataflow_plans.remove(&id);
                 by_compute_instance
                     .entry(compute_instance)
                     .or_insert(vec![])
@@ -4668,7 +5930,13 @@ impl Coordinator {
         let mut dataflow_plans = Vec::with_capacity(dataflows.len());
         for dataflow in dataflows.into_iter() {
             output_ids.extend(dataflow.export_ids());
-            dataflow_plans.push(self.finalize_dataflow(dataflow, instance));
+            let dataflow_plan = self.finalize_dataflow(dataflow, instance);
+            for id in dataflow_plan.index_exports.keys() {
+                if !id.is_transient() {
+                    self.dataflow_plans.insert(*id, dataflow_plan.clone());
+                }
+            }
+            dataflow_plans.push(dataflow_plan);
         }
         self.dataflow_client
             .compute_mut(instance)
@@ -4951,6 +6219,8 @@ pub async fn serve(
                 pending_peeks: HashMap::new(),
                 client_pending_peeks: HashMap::new(),
                 pending_tails: HashMap::new(),
+                dataflow_plans: HashMap::new(),
+                plan_cache: PlanCache::default(),
                 write_lock: Arc::new(tokio::sync::Mutex::new(())),
                 write_lock_wait_group: VecDeque::new(),
                 secrets_controller,
@@ -4978,6 +6248,21 @@ pub async fn serve(
     }
 }
 
+/// Matches each column of `old` with the first column of `new` that has the
+/// same name and type and is not matched yet, returning their positions.
+fn match_columns(old: &RelationDesc, new: &RelationDesc) -> Option<Vec<usize>> {
+    let mut matched = vec![false; new.arity()];
+    old.iter()
+        .map(|(old_name, old_type)| {
+            let i = new.iter().enumerate().position(|(i, (name, typ))| {
+                !matched[i] && name == old_name && typ.scalar_type == old_type.scalar_type
+            })?;
+            matched[i] = true;
+            Some(i)
+        })
+        .collect()
+}
+
 /// Constructs an [`ExecuteResponse`] that that will send some rows to the
 /// client immediately, as opposed to asking the dataflow layer to send along
 /// the rows after some computation.
@@ -5700,6 +6985,20 @@ mod timeline {
                 }
             }
         }
+        /// Record that a read has been served at `ts`.
+        ///
+        /// Subsequent values of `self.read_ts()` will be greater or equal to `ts`,
+        /// and subsequent values of `self.write_ts()` strictly greater than `ts`.
+        pub fn observe_read(&mut self, ts: T) {
+            let advance = match &self.state {
+                TimestampOracleState::Writing(cur) => cur.less_equal(&ts),
+                TimestampOracleState::Reading(cur) => cur.less_than(&ts),
+            };
+            if advance {
+                self.advance_to = Some(ts.step_forward());
+                self.state = TimestampOracleState::Reading(ts);
+            }
+        }
         /// Electively advance the tracked times.
         ///
         /// If `lower_bound` is strictly greater than the current time (of either state), the
@@ -0,0 +1,487 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Enforcement of the PRIMARY KEY, UNIQUE, and CHECK constraints declared on
+//! tables, and resolution of the conflicts of `INSERT ... ON CONFLICT`.
+//!
+//! Constraints are validated by the coordinator as writes are added to a
+//! transaction, which is the only place all writes to a table pass through.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use mz_expr::{GlobalId, MirRelationExpr};
+use mz_repr::{Datum, Diff, RelationDesc, RelationType, Row, RowArena};
+use mz_sql::plan::{CheckConstraint, ConflictResolution, OnConflictPlan, UniqueConstraint};
+
+use crate::error::CoordError;
+
+/// Verifies that every row added by `updates` satisfies each of `checks`.
+///
+/// As in PostgreSQL, a check that evaluates to NULL is satisfied.
+pub fn validate_checks(
+    relation: &str,
+    checks: &[CheckConstraint],
+    updates: &[(Row, Diff)],
+) -> Result<(), CoordError> {
+    if checks.is_empty() {
+        return Ok(());
+    }
+    let arena = RowArena::new();
+    let mut datum_vec = mz_repr::DatumVec::new();
+    for (row, diff) in updates {
+        if *diff <= 0 {
+            continue;
+        }
+        let datums = datum_vec.borrow_with(row);
+        for check in checks {
+            if check.expr.eval(&datums, &arena)? == Datum::False {
+                return Err(CoordError::CheckViolation {
+                    relation: relation.into(),
+                    constraint: check.name.clone(),
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that applying `updates` to a table whose contents are `existing`
+/// plus the not yet committed `pending` updates leaves no two rows sharing a
+/// value for any of `constraints`.
+///
+/// Only keys that `updates` adds rows for are checked, so that violations are
+/// reported against the statement that introduced them. Keys containing a
+/// NULL never conflict.
+pub fn validate_unique(
+    desc: &RelationDesc,
+    constraints: &[UniqueConstraint],
+    existing: &[Row],
+    pending: &[(Row, Diff)],
+    updates: &[(Row, Diff)],
+) -> Result<(), CoordError> {
+    for constraint in constraints {
+        let key_of = |row: &Row| key_of(row, &constraint.columns);
+
+        let touched: BTreeSet<Row> = updates
+            .iter()
+            .filter(|(_, diff)| *diff > 0)
+            .filter_map(|(row, _)| key_of(row))
+            .collect();
+        if touched.is_empty() {
+            continue;
+        }
+
+        let mut counts: HashMap<Row, Diff> = HashMap::new();
+        let all = existing.iter().map(|row| (row, 1)).chain(
+            pending
+                .iter()
+                .chain(updates)
+                .map(|(row, diff)| (row, *diff)),
+        );
+        for (row, diff) in all {
+            if let Some(key) = key_of(row) {
+                if touched.contains(&key) {
+                    *counts.entry(key).or_default() += diff;
+                }
+            }
+        }
+
+        if let Some((key, _)) = counts.into_iter().find(|(_, count)| *count > 1) {
+            let names = desc.iter_names().collect::<Vec<_>>();
+            return Err(CoordError::UniqueViolation {
+                constraint: constraint.name.clone(),
+                columns: constraint
+                    .columns
+                    .iter()
+                    .map(|c| names[*c].as_str().to_string())
+                    .collect(),
+                key: key
+                    .iter()
+                    .map(|d| match d {
+                        Datum::String(s) => s.to_string(),
+                        d => d.to_string(),
+                    })
+                    .collect(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns an expression that reads the rows of the table `id` that share a
+/// value for any of `constraints` with a row added by `updates`, or `None` if
+/// there are no such keys.
+///
+/// Only these rows can conflict with `updates`, so they suffice to validate
+/// them with [`validate_unique`]. Each row is read once, even if it shares a
+/// value with `updates` for more than one of `constraints`.
+pub fn unique_key_lookup(
+    id: GlobalId,
+    desc: &RelationDesc,
+    constraints: &[UniqueConstraint],
+    updates: &[(Row, Diff)],
+) -> Option<MirRelationExpr> {
+    let table = MirRelationExpr::global_get(id, desc.typ().clone());
+    let mut lookups = vec![];
+    for constraint in constraints {
+        let keys: BTreeSet<Row> = updates
+            .iter()
+            .filter(|(_, diff)| *diff > 0)
+            .filter_map(|(row, _)| key_of(row, &constraint.columns))
+            .collect();
+        if keys.is_empty() {
+            continue;
+        }
+        let typ = RelationType::new(
+            constraint
+                .columns
+                .iter()
+                .map(|c| desc.typ().column_types[*c].clone().nullable(false))
+                .collect(),
+        );
+        let keys = MirRelationExpr::Constant {
+            rows: Ok(keys.into_iter().map(|key| (key, 1)).collect()),
+            typ,
+        };
+        let equivalences = constraint
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, c)| vec![(0, *c), (1, i)])
+            .collect();
+        lookups.push(
+            MirRelationExpr::join(vec![table.clone(), keys], equivalences)
+                .project((0..desc.arity()).collect()),
+        );
+    }
+    if lookups.is_empty() {
+        None
+    } else {
+        Some(MirRelationExpr::union_many(lookups, desc.typ().clone()).distinct())
+    }
+}
+
+/// Resolves the conflicts between the rows that an `INSERT ... ON CONFLICT`
+/// proposes to insert and the rows of a table whose contents are `existing`
+/// plus the not yet committed `pending` updates.
+///
+/// Returns updates that insert the proposed rows that do not conflict and
+/// resolve the conflicts of the others. As in PostgreSQL, a proposed row may
+/// also conflict with a row proposed earlier in the same command.
+pub fn resolve_conflicts(
+    desc: &RelationDesc,
+    on_conflict: &OnConflictPlan,
+    existing: &[Row],
+    pending: &[(Row, Diff)],
+    proposed: Vec<(Row, Diff)>,
+) -> Result<Vec<(Row, Diff)>, CoordError> {
+    let mut current: Vec<_> = existing
+        .iter()
+        .map(|row| (row.clone(), 1))
+        .chain(pending.iter().cloned())
+        .collect();
+    differential_dataflow::consolidation::consolidate(&mut current);
+
+    // For each key, the current rows by their value of the key.
+    let mut indexes: Vec<HashMap<Row, Row>> = on_conflict
+        .keys
+        .iter()
+        .map(|key| {
+            current
+                .iter()
+                .filter_map(|(row, _)| Some((key_of(row, key)?, row.clone())))
+                .collect()
+        })
+        .collect();
+    // For each key, the values of the key of the rows this command inserted or
+    // updated.
+    let mut claimed = vec![HashSet::new(); on_conflict.keys.len()];
+
+    let arena = RowArena::new();
+    let mut updates = vec![];
+    for (row, diff) in proposed {
+        for _ in 0..diff {
+            let conflict =
+                on_conflict
+                    .keys
+                    .iter()
+                    .zip(&indexes)
+                    .enumerate()
+                    .find_map(|(i, (key, index))| {
+                        let value = key_of(&row, key)?;
+                        let old = index.get(&value)?.clone();
+                        Some((i, value, old))
+                    });
+            let new = match (conflict, &on_conflict.resolution) {
+                (None, _) => row.clone(),
+                (Some(_), ConflictResolution::DoNothing) => continue,
+                (Some((i, value, _)), ConflictResolution::DoUpdate { .. })
+                    if claimed[i].contains(&value) =>
+                {
+                    return Err(CoordError::OnConflictAffectsRowTwice);
+                }
+                (
+                    Some((_, _, old)),
+                    ConflictResolution::DoUpdate {
+                        assignments,
+                        selection,
+                    },
+                ) => {
+                    let datums: Vec<_> = old.iter().chain(row.iter()).collect();
+                    if let Some(selection) = selection {
+                        if selection.eval(&datums, &arena)? != Datum::True {
+                            continue;
+                        }
+                    }
+                    let mut new = Vec::with_capacity(desc.arity());
+                    for (i, datum) in old.iter().enumerate() {
+                        let datum = match assignments.get(&i) {
+                            Some(expr) => expr.eval(&datums, &arena)?,
+                            None => datum,
+                        };
+                        desc.constraints_met(i, &datum)?;
+                        new.push(datum);
+                    }
+                    let new = Row::pack_slice(&new);
+
+                    for (key, index) in on_conflict.keys.iter().zip(&mut indexes) {
+                        if let Some(value) = key_of(&old, key) {
+                            index.remove(&value);
+                        }
+                    }
+                    updates.push((old, -1));
+                    new
+                }
+            };
+
+            for (i, key) in on_conflict.keys.iter().enumerate() {
+                if let Some(value) = key_of(&new, key) {
+                    indexes[i].insert(value.clone(), new.clone());
+                    claimed[i].insert(value);
+                }
+            }
+            updates.push((new, 1));
+        }
+    }
+    Ok(updates)
+}
+
+/// Returns the value of the key with the given columns in `row`, or `None` if
+/// any of them is NULL.
+fn key_of(row: &Row, columns: &[usize]) -> Option<Row> {
+    let datums = row.unpack();
+    let key = columns.iter().map(|c| datums[*c]);
+    if key.clone().any(|d| d.is_null()) {
+        None
+    } else {
+        Some(Row::pack(key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_expr::{CollectionPlan, MirScalarExpr};
+    use mz_repr::{ColumnType, RelationType, ScalarType};
+
+    use super::*;
+
+    fn desc() -> RelationDesc {
+        RelationDesc::new(
+            RelationType::new(vec![
+                ColumnType {
+                    scalar_type: ScalarType::Int64,
+                    nullable: true,
+                },
+                ColumnType {
+                    scalar_type: ScalarType::String,
+                    nullable: true,
+                },
+            ]),
+            vec!["a", "b"],
+        )
+    }
+
+    fn row(a: Option<i64>, b: &str) -> Row {
+        Row::pack_slice(&[a.map(Datum::Int64).unwrap_or(Datum::Null), Datum::String(b)])
+    }
+
+    fn pkey() -> Vec<UniqueConstraint> {
+        vec![UniqueConstraint {
+            name: "t_pkey".into(),
+            columns: vec![0],
+            is_primary: true,
+        }]
+    }
+
+    #[test]
+    fn unique() {
+        let desc = desc();
+        let existing = vec![row(Some(1), "x")];
+
+        // A fresh key is fine, a duplicate of an existing key is not.
+        validate_unique(&desc, &pkey(), &existing, &[], &[(row(Some(2), "y"), 1)]).unwrap();
+        let err =
+            validate_unique(&desc, &pkey(), &existing, &[], &[(row(Some(1), "y"), 1)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "duplicate key value violates unique constraint \"t_pkey\""
+        );
+        assert_eq!(err.detail().as_deref(), Some("Key (a)=(1) already exists."));
+
+        // Duplicates within a single statement and against pending writes of
+        // the same transaction are caught.
+        validate_unique(
+            &desc,
+            &pkey(),
+            &[],
+            &[],
+            &[(row(Some(2), "y"), 1), (row(Some(2), "z"), 1)],
+        )
+        .unwrap_err();
+        validate_unique(
+            &desc,
+            &pkey(),
+            &[],
+            &[(row(Some(2), "y"), 1)],
+            &[(row(Some(2), "z"), 1)],
+        )
+        .unwrap_err();
+
+        // Updating a row in place retracts the old key.
+        validate_unique(
+            &desc,
+            &pkey(),
+            &existing,
+            &[],
+            &[(row(Some(1), "x"), -1), (row(Some(1), "y"), 1)],
+        )
+        .unwrap();
+
+        // NULLs never conflict.
+        validate_unique(
+            &desc,
+            &pkey(),
+            &[row(None, "x")],
+            &[],
+            &[(row(None, "y"), 1)],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn key_lookup() {
+        let desc = desc();
+        let id = GlobalId::User(1);
+
+        // Rows with NULL keys, and retractions, never need to be looked up.
+        assert_eq!(
+            unique_key_lookup(
+                id,
+                &desc,
+                &pkey(),
+                &[(row(None, "x"), 1), (row(Some(1), "y"), -1)]
+            ),
+            None
+        );
+        let lookup = unique_key_lookup(id, &desc, &pkey(), &[(row(Some(1), "x"), 1)])
+            .expect("key to look up");
+        assert_eq!(lookup.arity(), desc.arity());
+        assert_eq!(lookup.depends_on(), [id].into_iter().collect());
+    }
+
+    #[test]
+    fn on_conflict() {
+        let desc = desc();
+        let existing = vec![row(Some(1), "x")];
+
+        let do_nothing = OnConflictPlan {
+            keys: vec![vec![0]],
+            resolution: ConflictResolution::DoNothing,
+        };
+        let updates = resolve_conflicts(
+            &desc,
+            &do_nothing,
+            &existing,
+            &[],
+            vec![
+                (row(Some(1), "y"), 1),
+                (row(Some(2), "y"), 1),
+                (row(Some(2), "z"), 1),
+            ],
+        )
+        .unwrap();
+        assert_eq!(updates, vec![(row(Some(2), "y"), 1)]);
+
+        // DO UPDATE SET b = excluded.b
+        let do_update = OnConflictPlan {
+            keys: vec![vec![0]],
+            resolution: ConflictResolution::DoUpdate {
+                assignments: [(1, MirScalarExpr::column(3))].into_iter().collect(),
+                selection: None,
+            },
+        };
+        let updates = resolve_conflicts(
+            &desc,
+            &do_update,
+            &existing,
+            &[],
+            vec![(row(Some(1), "y"), 1), (row(Some(2), "y"), 1)],
+        )
+        .unwrap();
+        assert_eq!(
+            updates,
+            vec![
+                (row(Some(1), "x"), -1),
+                (row(Some(1), "y"), 1),
+                (row(Some(2), "y"), 1)
+            ]
+        );
+
+        // Pending writes are taken into account.
+        let updates = resolve_conflicts(
+            &desc,
+            &do_update,
+            &existing,
+            &[(row(Some(1), "x"), -1)],
+            vec![(row(Some(1), "y"), 1)],
+        )
+        .unwrap();
+        assert_eq!(updates, vec![(row(Some(1), "y"), 1)]);
+
+        let err = resolve_conflicts(
+            &desc,
+            &do_update,
+            &existing,
+            &[],
+            vec![(row(Some(1), "y"), 1), (row(Some(1), "z"), 1)],
+        )
+        .unwrap_err();
+        assert!(matches!(err, CoordError::OnConflictAffectsRowTwice));
+    }
+
+    #[test]
+    fn checks() {
+        // CHECK (a > 0)
+        let checks = vec![CheckConstraint {
+            name: "t_a_check".into(),
+            expr: MirScalarExpr::column(0).call_binary(
+                MirScalarExpr::literal_ok(Datum::Int64(0), ScalarType::Int64),
+                mz_expr::BinaryFunc::Gt,
+            ),
+        }];
+        validate_checks("t", &checks, &[(row(Some(1), "x"), 1)]).unwrap();
+        validate_checks("t", &checks, &[(row(None, "x"), 1)]).unwrap();
+        validate_checks("t", &checks, &[(row(Some(-1), "x"), -1)]).unwrap();
+        let err = validate_checks("t", &checks, &[(row(Some(-1), "x"), 1)]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "new row for relation \"t\" violates check constraint \"t_a_check\""
+        );
+    }
+}
@@ -10,7 +10,7 @@
 use std::collections::BTreeSet;
 
 use mz_dataflow_types::client::controller::ComputeController;
-use mz_dataflow_types::client::ComputeInstanceId;
+use mz_dataflow_types::client::{ArrangementStatistics, ComputeInstanceId};
 use mz_expr::GlobalId;
 use mz_expr::MirScalarExpr;
 use mz_transform::IndexOracle;
@@ -105,4 +105,28 @@ impl<T: CoordTimestamp> IndexOracle for ComputeInstanceIndexOracle<'_, T> {
                 .map(|(_idx_id, idx)| idx.keys.as_slice()),
         )
     }
+
+    fn statistics(&self, id: GlobalId, key: &[MirScalarExpr]) -> Option<ArrangementStatistics> {
+        ComputeInstanceIndexOracle::indexes_on(self, id)
+            .filter(|(_idx_id, idx)| idx.keys == key)
+            .find_map(|(idx_id, _idx)| self.compute.collection(idx_id).ok()?.statistics)
+    }
+
+    fn cardinality(&self, id: GlobalId) -> Option<u64> {
+        // Sources know how many records they have ingested, whether or not
+        // they are indexed. Other collections are only as large as their
+        // indexes.
+        let ingested = self
+            .compute
+            .storage()
+            .collection(id)
+            .ok()
+            .and_then(|collection| collection.ingested_records());
+        ingested.or_else(|| {
+            ComputeInstanceIndexOracle::indexes_on(self, id)
+                .filter_map(|(idx_id, _idx)| self.compute.collection(idx_id).ok()?.statistics)
+                .map(|statistics| statistics.records)
+                .max()
+        })
+    }
 }
@@ -0,0 +1,242 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A cache of optimized plans for `SELECT` statements.
+//!
+//! Planning and optimizing a query can cost far more than executing it when
+//! the query is answered from an existing arrangement, so clients that issue
+//! the same query over and over again benefit from skipping both. Plans are
+//! cached before their parameters are bound, so that a prepared statement
+//! that is executed with different parameter values reuses its plan too.
+
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+use fail::fail_point;
+
+use mz_expr::{
+    BinaryFunc, CollectionPlan, GlobalId, MirRelationExpr, MirScalarExpr, OptimizedMirRelationExpr,
+};
+use mz_repr::ScalarType;
+use mz_sql::ast::{Raw, Statement};
+use mz_sql::plan::{Params, QueryWhen, UnboundPeekPlan};
+
+use crate::error::CoordError;
+use crate::session::Session;
+
+/// The maximum number of plans to cache.
+const PLAN_CACHE_CAPACITY: usize = 1024;
+
+/// Identifies the plan of a `SELECT` statement.
+///
+/// The key includes the types of the parameters, but not their values, so
+/// that a prepared statement occupies a single entry no matter how often it
+/// is executed with different values. It also includes the session state that
+/// affects how names are resolved and how the query is planned.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlanCacheKey {
+    stmt: Statement<Raw>,
+    param_types: Vec<ScalarType>,
+    database: String,
+    search_path: Vec<String>,
+    qgm_optimizations: bool,
+    qgm_optimizations_fallback: bool,
+}
+
+impl PlanCacheKey {
+    pub fn new(stmt: &Statement<Raw>, params: &Params, session: &Session) -> Self {
+        PlanCacheKey {
+            stmt: stmt.clone(),
+            param_types: params.types.clone(),
+            database: session.vars().database().to_string(),
+            search_path: session
+                .vars()
+                .search_path()
+                .iter()
+                .map(|schema| schema.to_string())
+                .collect(),
+            qgm_optimizations: session.vars().qgm_optimizations(),
+            qgm_optimizations_fallback: session.vars().qgm_optimizations_fallback(),
+        }
+    }
+}
+
+/// A peek, as planned from a `SELECT` statement.
+///
+/// Parameters are bound before optimization, so only the plans of statements
+/// without parameters are cached optimized. The plans of other statements are
+/// bound to the parameter values of each execution and optimized again.
+#[derive(Debug, Clone)]
+pub struct CachedPeek {
+    pub plan: UnboundPeekPlan,
+    pub optimized: Option<OptimizedMirRelationExpr>,
+}
+
+/// Caches the optimized plans of `SELECT` statements.
+///
+/// Plans are only valid for the catalog revision at which they were planned.
+/// The entire cache is invalidated whenever the catalog changes.
+#[derive(Debug, Default)]
+pub struct PlanCache {
+    revision: u64,
+    entries: HashMap<PlanCacheKey, CachedPeek>,
+}
+
+impl PlanCache {
+    /// Returns the plan cached for `key`, if it was planned at catalog
+    /// revision `revision`.
+    pub fn get(&mut self, revision: u64, key: &PlanCacheKey) -> Option<&CachedPeek> {
+        self.invalidate(revision);
+        self.entries.get(key)
+    }
+
+    /// Caches `peek` as the plan for `key` at catalog revision `revision`.
+    pub fn insert(&mut self, revision: u64, key: PlanCacheKey, peek: CachedPeek) {
+        self.invalidate(revision);
+        // Rather than tracking usage, start over when the cache fills up. The
+        // plans that are in use are quickly cached again.
+        if self.entries.len() >= PLAN_CACHE_CAPACITY {
+            self.entries.clear();
+        }
+        self.entries.insert(key, peek);
+    }
+
+    fn invalidate(&mut self, revision: u64) {
+        if self.revision != revision {
+            self.entries.clear();
+            self.revision = revision;
+        }
+    }
+}
+
+/// Fails if the `plan_cache_miss` failpoint is enabled, which lets tests check
+/// that a statement is answered from the cache.
+pub fn fail_on_miss() -> Result<(), CoordError> {
+    fail_point!(
+        "plan_cache_miss",
+        |_| Err(anyhow!("plan cache miss").into())
+    );
+    Ok(())
+}
+
+/// Reports whether the plan of a peek may be reused when the statement is
+/// executed again.
+///
+/// `expr` must be the unoptimized plan, as optimization may fold away the
+/// wall clock time at which the plan was made, e.g. into a literal. Plans that
+/// refer to the wall clock time cannot be reused. Plans that refer to
+/// temporary objects are not cached either, as the same name may refer to a
+/// different temporary object in another session. `is_temporary` reports
+/// whether an object is temporary.
+pub fn is_cacheable<F>(expr: &MirRelationExpr, when: &QueryWhen, is_temporary: F) -> bool
+where
+    F: Fn(GlobalId) -> bool,
+{
+    if expr.depends_on().into_iter().any(is_temporary) {
+        return false;
+    }
+    let mut uses_wall_time = false;
+    let mut check = |e: &MirScalarExpr| {
+        e.visit_post(&mut |e| {
+            if let MirScalarExpr::CallBinary {
+                func: BinaryFunc::TimezoneTime { .. },
+                ..
+            } = e
+            {
+                uses_wall_time = true;
+            }
+        })
+    };
+    let mut expr = expr.clone();
+    expr.visit_scalars_mut(&mut |e| check(e));
+    if let QueryWhen::AtTimestamp(e) = when {
+        check(e);
+    }
+    !uses_wall_time
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_expr::RowSetFinishing;
+    use mz_ore::collections::CollectionExt;
+    use mz_repr::RelationType;
+    use mz_sql::plan::{HirRelationExpr, OptimizerConfig};
+
+    use super::*;
+
+    fn key(sql: &str) -> PlanCacheKey {
+        PlanCacheKey {
+            stmt: mz_sql::parse::parse(sql).unwrap().into_element(),
+            param_types: vec![],
+            database: "materialize".into(),
+            search_path: vec!["public".into()],
+            qgm_optimizations: false,
+            qgm_optimizations_fallback: true,
+        }
+    }
+
+    fn peek() -> CachedPeek {
+        CachedPeek {
+            plan: UnboundPeekPlan {
+                source: HirRelationExpr::constant(vec![], RelationType::empty()),
+                when: QueryWhen::Immediately,
+                finishing: RowSetFinishing {
+                    order_by: vec![],
+                    limit: None,
+                    offset: 0,
+                    project: vec![],
+                },
+                config: OptimizerConfig {
+                    qgm_optimizations: false,
+                    qgm_optimizations_fallback: true,
+                },
+            },
+            optimized: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_cache_invalidation() {
+        let mut cache = PlanCache::default();
+        cache.insert(1, key("SELECT 1"), peek());
+        assert!(cache.get(1, &key("SELECT 1")).is_some());
+        assert!(cache.get(1, &key("SELECT 2")).is_none());
+
+        // Changing the catalog invalidates all plans.
+        assert!(cache.get(2, &key("SELECT 1")).is_none());
+        cache.insert(2, key("SELECT 2"), peek());
+        assert!(cache.get(2, &key("SELECT 2")).is_some());
+        assert!(cache.get(2, &key("SELECT 1")).is_none());
+    }
+
+    #[test]
+    fn test_is_cacheable() {
+        let constant = MirRelationExpr::constant(vec![], RelationType::empty());
+        assert!(is_cacheable(&constant, &QueryWhen::Immediately, |_| false));
+
+        // The wall clock time may be folded away by optimization, so it must
+        // be detected in the unoptimized plan.
+        let wall_time = constant.map(vec![MirScalarExpr::CallBinary {
+            func: BinaryFunc::TimezoneTime {
+                wall_time: chrono::NaiveDateTime::from_timestamp(0, 0),
+            },
+            expr1: Box::new(MirScalarExpr::literal_null(ScalarType::String)),
+            expr2: Box::new(MirScalarExpr::literal_null(ScalarType::Time)),
+        }]);
+        assert!(!is_cacheable(&wall_time, &QueryWhen::Immediately, |_| {
+            false
+        }));
+
+        let get = MirRelationExpr::global_get(GlobalId::User(1), RelationType::empty());
+        assert!(is_cacheable(&get, &QueryWhen::Immediately, |_| false));
+        assert!(!is_cacheable(&get, &QueryWhen::Immediately, |id| {
+            id == GlobalId::User(1)
+        }));
+    }
+}
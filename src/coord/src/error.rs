@@ -17,7 +17,7 @@ use mz_dataflow_types::sources::{ExternalSourceConnector, SourceConnector};
 use mz_expr::{EvalError, UnmaterializableFunc};
 use mz_ore::stack::RecursionLimitError;
 use mz_ore::str::StrExt;
-use mz_repr::NotNullViolation;
+use mz_repr::{NotNullViolation, Timestamp};
 use mz_sql::query_model::QGMError;
 use mz_transform::TransformError;
 
@@ -38,6 +38,13 @@ pub enum CoordError {
     Catalog(catalog::Error),
     /// The cached plan or descriptor changed.
     ChangedPlan,
+    /// A row violated a table's CHECK constraint.
+    CheckViolation {
+        /// The name of the table.
+        relation: String,
+        /// The name of the violated constraint.
+        constraint: String,
+    },
     /// The specified session parameter is constrained to a finite set of values.
     ConstrainedParameter {
         parameter: &'static (dyn Var + Send + Sync),
@@ -74,6 +81,8 @@ pub enum CoordError {
     InvalidTableMutationSelection,
     /// Expression violated a column's constraint
     ConstraintViolation(NotNullViolation),
+    /// An `INSERT ... ON CONFLICT DO UPDATE` would update the same row twice.
+    OnConflictAffectsRowTwice,
     /// The named operation cannot be run in a transaction.
     OperationProhibitsTransaction(String),
     /// The named operation requires an active transaction.
@@ -102,8 +111,25 @@ pub enum CoordError {
     SqlCatalog(mz_sql::catalog::CatalogError),
     /// The transaction is in single-tail mode.
     TailOnlyTransaction,
+    /// A `TAIL` without a snapshot was requested to resume from a timestamp
+    /// whose subsequent updates have been compacted.
+    TailResumptionCompacted {
+        /// The requested timestamp.
+        timestamp: Timestamp,
+        /// The frontier through which the inputs have been compacted.
+        since: Vec<Timestamp>,
+    },
     /// An error occurred in the MIR stage of the optimizer.
     Transform(TransformError),
+    /// A write violated a table's PRIMARY KEY or UNIQUE constraint.
+    UniqueViolation {
+        /// The name of the violated constraint.
+        constraint: String,
+        /// The names of the constrained columns.
+        columns: Vec<String>,
+        /// The duplicated key, rendered as text.
+        key: Vec<String>,
+    },
     /// The named cursor does not exist.
     UnknownCursor(String),
     /// The named role does not exist.
@@ -164,6 +190,11 @@ impl CoordError {
             }
             CoordError::Catalog(c) => c.detail(),
             CoordError::Eval(e) => e.detail(),
+            CoordError::UniqueViolation { columns, key, .. } => Some(format!(
+                "Key ({})=({}) already exists.",
+                columns.join(", "),
+                key.join(", ")
+            )),
             CoordError::RelationOutsideTimeDomain { relations, names } => Some(format!(
                 "The following relations in the query are outside the transaction's time domain:\n{}\n{}",
                 relations
@@ -183,6 +214,10 @@ impl CoordError {
                     ),
                 }
             )),
+            CoordError::TailResumptionCompacted { since, .. } => Some(format!(
+                "The earliest timestamp from which TAIL can resume is {}.",
+                itertools::join(since, ", ")
+            )),
             CoordError::SafeModeViolation(_) => Some(
                 "The Materialize server you are connected to is running in \
                  safe mode, which limits the features that are available."
@@ -242,6 +277,14 @@ impl CoordError {
                 ..
             } => Some(format!("Available values: {}.", valid_values.join(", "))),
             CoordError::Eval(e) => e.hint(),
+            CoordError::TailResumptionCompacted { .. } => {
+                Some("Restart the TAIL with a snapshot.".into())
+            }
+            CoordError::OnConflictAffectsRowTwice => Some(
+                "Ensure that no rows proposed for insertion within the same command \
+                 have duplicate constrained values."
+                    .into(),
+            ),
             CoordError::InvalidAlterOnDisabledIndex(idx) => Some(format!(
                 "To perform this ALTER, first enable the index using ALTER \
                 INDEX {} SET ENABLED",
@@ -286,6 +329,15 @@ impl fmt::Display for CoordError {
                 f.write_str("unable to automatically determine a query timestamp")
             }
             CoordError::ChangedPlan => f.write_str("cached plan must not change result type"),
+            CoordError::CheckViolation {
+                relation,
+                constraint,
+            } => write!(
+                f,
+                "new row for relation {} violates check constraint {}",
+                relation.quoted(),
+                constraint.quoted()
+            ),
             CoordError::Catalog(e) => e.fmt(f),
             CoordError::ConstrainedParameter {
                 parameter, value, ..
@@ -340,6 +392,9 @@ impl fmt::Display for CoordError {
             CoordError::ConstraintViolation(not_null_violation) => {
                 write!(f, "{}", not_null_violation)
             }
+            CoordError::OnConflictAffectsRowTwice => {
+                f.write_str("ON CONFLICT DO UPDATE command cannot affect row a second time")
+            }
             CoordError::OperationProhibitsTransaction(op) => {
                 write!(f, "{} cannot be run inside a transaction block", op)
             }
@@ -370,7 +425,17 @@ impl fmt::Display for CoordError {
             CoordError::TailOnlyTransaction => {
                 f.write_str("TAIL in transactions must be the only read statement")
             }
+            CoordError::TailResumptionCompacted { timestamp, .. } => write!(
+                f,
+                "cannot resume TAIL from timestamp {}: updates have been compacted past it",
+                timestamp
+            ),
             CoordError::Transform(e) => e.fmt(f),
+            CoordError::UniqueViolation { constraint, .. } => write!(
+                f,
+                "duplicate key value violates unique constraint {}",
+                constraint.quoted()
+            ),
             CoordError::UnknownCursor(name) => {
                 write!(f, "cursor {} does not exist", name.quoted())
             }
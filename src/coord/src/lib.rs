@@ -46,7 +46,9 @@ pub mod catalog;
 pub mod session;
 
 pub use crate::client::{Client, ConnClient, Handle, SessionClient};
-pub use crate::command::{Canceled, ExecuteResponse, StartupMessage, StartupResponse};
+pub use crate::command::{
+    AsyncNotification, Canceled, ExecuteResponse, StartupMessage, StartupResponse,
+};
 pub use crate::coord::{serve, Config, LoggingConfig};
 pub use crate::error::CoordError;
 pub use crate::persistcfg::{
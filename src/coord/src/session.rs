@@ -35,8 +35,8 @@ use crate::error::CoordError;
 mod vars;
 
 pub use self::vars::{
-    ClientSeverity, Var, Vars, DEFAULT_DATABASE_NAME, SERVER_MAJOR_VERSION, SERVER_MINOR_VERSION,
-    SERVER_PATCH_VERSION,
+    ClientSeverity, IsolationLevel, Var, Vars, DEFAULT_DATABASE_NAME, SERVER_MAJOR_VERSION,
+    SERVER_MINOR_VERSION, SERVER_PATCH_VERSION,
 };
 
 const DUMMY_CONNECTION_ID: u32 = 0;
@@ -103,7 +103,11 @@ impl<T: CoordTimestamp> Session<T> {
         match self.transaction {
             TransactionStatus::Default | TransactionStatus::Started(_) => {
                 self.transaction = TransactionStatus::InTransaction(Transaction {
-                    pcx: PlanContext::new(wall_time, self.vars.qgm_optimizations()),
+                    pcx: PlanContext::new(
+                        wall_time,
+                        self.vars.qgm_optimizations(),
+                        self.vars.qgm_optimizations_fallback(),
+                    ),
                     ops: TransactionOps::None,
                     write_lock_guard: None,
                     access,
@@ -123,7 +127,11 @@ impl<T: CoordTimestamp> Session<T> {
     pub fn start_transaction_implicit(mut self, wall_time: DateTime<Utc>, stmts: usize) -> Self {
         if let TransactionStatus::Default = self.transaction {
             let txn = Transaction {
-                pcx: PlanContext::new(wall_time, self.vars.qgm_optimizations()),
+                pcx: PlanContext::new(
+                    wall_time,
+                    self.vars.qgm_optimizations(),
+                    self.vars.qgm_optimizations_fallback(),
+                ),
                 ops: TransactionOps::None,
                 write_lock_guard: None,
                 access: None,
@@ -176,6 +184,19 @@ impl<T: CoordTimestamp> Session<T> {
         &self.transaction
     }
 
+    /// Returns the writes to table `id` that the current transaction has made
+    /// but not yet committed.
+    pub fn pending_writes(&self, id: GlobalId) -> Vec<(Row, Diff)> {
+        match self.transaction.inner().map(|txn| &txn.ops) {
+            Some(TransactionOps::Writes(writes)) => writes
+                .iter()
+                .filter(|write| write.id == id)
+                .flat_map(|write| write.rows.iter().cloned())
+                .collect(),
+            _ => vec![],
+        }
+    }
+
     /// Adds operations to the current transaction. An error is produced if they
     /// cannot be merged (i.e., a read cannot be merged to an insert).
     pub fn add_transaction_ops(&mut self, add_ops: TransactionOps<T>) -> Result<(), CoordError> {
@@ -106,6 +106,12 @@ const QGM_OPTIMIZATIONS: ServerVar<bool> = ServerVar {
     description: "Enables optimizations based on a Query Graph Model (QGM) query representation.",
 };
 
+const QGM_OPTIMIZATIONS_FALLBACK: ServerVar<bool> = ServerVar {
+    name: static_uncased_str!("qgm_optimizations_fallback_experimental"),
+    value: &true,
+    description: "Falls back to the default optimization path for queries that the QGM optimizations do not support.",
+};
+
 const SEARCH_PATH: ServerVar<[&str]> = ServerVar {
     name: static_uncased_str!("search_path"),
     value: &[
@@ -157,9 +163,9 @@ const TIMEZONE: ServerVar<TimeZone> = ServerVar {
     description: "Sets the time zone for displaying and interpreting time stamps (PostgreSQL).",
 };
 
-const TRANSACTION_ISOLATION: ServerVar<str> = ServerVar {
+const TRANSACTION_ISOLATION: ServerVar<IsolationLevel> = ServerVar {
     name: static_uncased_str!("transaction_isolation"),
-    value: "serializable",
+    value: &IsolationLevel::Serializable,
     description: "Sets the current transaction's isolation level (PostgreSQL).",
 };
 
@@ -199,13 +205,14 @@ pub struct Vars {
     failpoints: ServerVar<str>,
     integer_datetimes: ServerVar<bool>,
     qgm_optimizations: SessionVar<bool>,
+    qgm_optimizations_fallback: SessionVar<bool>,
     search_path: ServerVar<[&'static str]>,
     server_version: ServerVar<str>,
     server_version_num: ServerVar<i32>,
     sql_safe_updates: SessionVar<bool>,
     standard_conforming_strings: ServerVar<bool>,
     timezone: SessionVar<TimeZone>,
-    transaction_isolation: ServerVar<str>,
+    transaction_isolation: SessionVar<IsolationLevel>,
 }
 
 impl Default for Vars {
@@ -221,13 +228,14 @@ impl Default for Vars {
             failpoints: FAILPOINTS,
             integer_datetimes: INTEGER_DATETIMES,
             qgm_optimizations: SessionVar::new(&QGM_OPTIMIZATIONS),
+            qgm_optimizations_fallback: SessionVar::new(&QGM_OPTIMIZATIONS_FALLBACK),
             search_path: SEARCH_PATH,
             server_version: SERVER_VERSION,
             server_version_num: SERVER_VERSION_NUM,
             sql_safe_updates: SessionVar::new(&SQL_SAFE_UPDATES),
             standard_conforming_strings: STANDARD_CONFORMING_STRINGS,
             timezone: SessionVar::new(&TIMEZONE),
-            transaction_isolation: TRANSACTION_ISOLATION,
+            transaction_isolation: SessionVar::new(&TRANSACTION_ISOLATION),
         }
     }
 }
@@ -247,6 +255,7 @@ impl Vars {
             &self.failpoints,
             &self.integer_datetimes,
             &self.qgm_optimizations,
+            &self.qgm_optimizations_fallback,
             &self.search_path,
             &self.server_version,
             &self.server_version_num,
@@ -304,6 +313,8 @@ impl Vars {
             Ok(&self.integer_datetimes)
         } else if name == QGM_OPTIMIZATIONS.name {
             Ok(&self.qgm_optimizations)
+        } else if name == QGM_OPTIMIZATIONS_FALLBACK.name {
+            Ok(&self.qgm_optimizations_fallback)
         } else if name == SEARCH_PATH.name {
             Ok(&self.search_path)
         } else if name == SERVER_VERSION.name {
@@ -402,6 +413,8 @@ impl Vars {
             Err(CoordError::ReadOnlyParameter(&INTEGER_DATETIMES))
         } else if name == QGM_OPTIMIZATIONS.name {
             self.qgm_optimizations.set(value, local)
+        } else if name == QGM_OPTIMIZATIONS_FALLBACK.name {
+            self.qgm_optimizations_fallback.set(value, local)
         } else if name == SEARCH_PATH.name {
             Err(CoordError::ReadOnlyParameter(&SEARCH_PATH))
         } else if name == SERVER_VERSION.name {
@@ -431,7 +444,15 @@ impl Vars {
                 });
             }
         } else if name == TRANSACTION_ISOLATION.name {
-            Err(CoordError::ReadOnlyParameter(&TRANSACTION_ISOLATION))
+            if let Ok(_) = IsolationLevel::parse(value) {
+                self.transaction_isolation.set(value, local)
+            } else {
+                return Err(CoordError::ConstrainedParameter {
+                    parameter: &TRANSACTION_ISOLATION,
+                    value: value.into(),
+                    valid_values: Some(IsolationLevel::valid_values()),
+                });
+            }
         } else {
             Err(CoordError::UnknownParameter(name.into()))
         }
@@ -453,20 +474,23 @@ impl Vars {
             failpoints: _,
             integer_datetimes: _,
             qgm_optimizations,
+            qgm_optimizations_fallback,
             search_path: _,
             server_version: _,
             server_version_num: _,
             sql_safe_updates,
             standard_conforming_strings: _,
             timezone: _,
-            transaction_isolation: _,
+            transaction_isolation,
         } = self;
         application_name.end_transaction(action);
         client_min_messages.end_transaction(action);
         database.end_transaction(action);
         qgm_optimizations.end_transaction(action);
+        qgm_optimizations_fallback.end_transaction(action);
         extra_float_digits.end_transaction(action);
         sql_safe_updates.end_transaction(action);
+        transaction_isolation.end_transaction(action);
     }
 
     /// Returns the value of the `application_name` configuration parameter.
@@ -514,6 +538,12 @@ impl Vars {
         *self.qgm_optimizations.value()
     }
 
+    /// Returns the value of the `qgm_optimizations_fallback` configuration
+    /// parameter.
+    pub fn qgm_optimizations_fallback(&self) -> bool {
+        *self.qgm_optimizations_fallback.value()
+    }
+
     /// Returns the value of the `search_path` configuration parameter.
     pub fn search_path(&self) -> &'static [&'static str] {
         self.search_path.value
@@ -547,8 +577,8 @@ impl Vars {
 
     /// Returns the value of the `transaction_isolation` configuration
     /// parameter.
-    pub fn transaction_isolation(&self) -> &'static str {
-        self.transaction_isolation.value
+    pub fn transaction_isolation(&self) -> &IsolationLevel {
+        self.transaction_isolation.value()
     }
 }
 
@@ -859,6 +889,56 @@ impl Value for ClientSeverity {
     }
 }
 
+/// Transaction isolation levels.
+///
+/// Materialize always provides serializable transactions. Strict serializable
+/// additionally guarantees that queries observe all writes and reads that
+/// completed before they began, at the cost of potentially waiting for indexes
+/// and sources to catch up.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IsolationLevel {
+    /// Serializable isolation.
+    Serializable,
+    /// Strict serializable isolation.
+    StrictSerializable,
+}
+
+impl IsolationLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IsolationLevel::Serializable => "serializable",
+            IsolationLevel::StrictSerializable => "strict serializable",
+        }
+    }
+
+    fn valid_values() -> Vec<&'static str> {
+        vec![
+            IsolationLevel::Serializable.as_str(),
+            IsolationLevel::StrictSerializable.as_str(),
+        ]
+    }
+}
+
+impl Value for IsolationLevel {
+    const TYPE_NAME: &'static str = "string";
+
+    fn parse(s: &str) -> Result<Self::Owned, ()> {
+        let s = UncasedStr::new(s);
+
+        if s == IsolationLevel::Serializable.as_str() {
+            Ok(IsolationLevel::Serializable)
+        } else if s == IsolationLevel::StrictSerializable.as_str() {
+            Ok(IsolationLevel::StrictSerializable)
+        } else {
+            Err(())
+        }
+    }
+
+    fn format(&self) -> String {
+        self.as_str().into()
+    }
+}
+
 /// List of valid time zones.
 ///
 /// Names are following the tz database, but only time zones equivalent
@@ -370,5 +370,8 @@ fn build_avro_ocf(
     Ok(SinkConnector::AvroOcf(AvroOcfSinkConnector {
         path,
         value_desc: builder.value_desc,
+        codec: builder.codec,
+        block_size: builder.block_size,
+        rollover: builder.rollover,
     }))
 }
@@ -22,6 +22,8 @@ pub(crate) struct PendingTail {
     channel: mpsc::UnboundedSender<PeekResponseUnary>,
     /// Whether progress information should be emitted
     emit_progress: bool,
+    /// The indices of the key columns, if the tail emits upserts rather than raw updates
+    upsert_key: Option<Vec<usize>>,
     /// Number of columns in the output
     arity: usize,
 }
@@ -30,15 +32,19 @@ impl PendingTail {
     /// Create a new [PendingTail].
     /// * The `channel` receives batches of finalized PeekResponses.
     /// * If `emit_progress` is true, the finalized rows are either data or progress updates
+    /// * If `upsert_key` is set, the finalized rows describe the changes to the state of
+    ///   each key, as computed by the sink, rather than raw updates.
     /// * `arity` is the arity of the sink relation.
     pub(crate) fn new(
         channel: mpsc::UnboundedSender<PeekResponseUnary>,
         emit_progress: bool,
+        upsert_key: Option<Vec<usize>>,
         arity: usize,
     ) -> Self {
         Self {
             channel,
             emit_progress,
+            upsert_key,
             arity,
         }
     }
@@ -73,7 +79,11 @@ impl PendingTail {
                             packer.push(Datum::False);
                         }
 
-                        packer.push(Datum::Int64(diff));
+                        // Upserts describe the state of a key instead of a
+                        // change, so they have no diff.
+                        if self.upsert_key.is_none() {
+                            packer.push(Datum::Int64(diff));
+                        }
 
                         packer.extend_by_row(&row);
 
@@ -97,8 +107,13 @@ impl PendingTail {
                     let mut packer = row_buf.packer();
                     packer.push(Datum::from(numeric::Numeric::from(*&upper[0])));
                     packer.push(Datum::True);
-                    // Fill in the diff column and all table columns with NULL.
-                    for _ in 0..(self.arity + 1) {
+                    // Fill in the diff (or state) column and all table columns
+                    // with NULL.
+                    let columns = match &self.upsert_key {
+                        None => self.arity,
+                        Some(key) => key.len() + 2 * (self.arity - key.len()),
+                    };
+                    for _ in 0..(columns + 1) {
                         packer.push(Datum::Null);
                     }
 
@@ -423,6 +423,34 @@ pub enum ComputeResponse<T = mz_repr::Timestamp> {
     PeekResponse(Uuid, PeekResponse),
     /// The worker's next response to a specified tail.
     TailResponse(GlobalId, TailResponse<T>),
+    /// Size estimates for the arrangements backing a list of indexes.
+    ArrangementStatistics(Vec<(GlobalId, ArrangementStatistics)>),
+}
+
+/// Size estimates for an arrangement, used to inform the planning of joins.
+///
+/// Both quantities are estimates: updates that have not yet been compacted
+/// are counted individually, and a key that appears in several batches is
+/// counted once for each batch.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArrangementStatistics {
+    /// The number of updates in the arrangement.
+    pub records: u64,
+    /// The number of distinct keys in the arrangement.
+    pub keys: u64,
+}
+
+impl ArrangementStatistics {
+    /// Accumulates the statistics of another part of the same arrangement.
+    pub fn add(&mut self, other: &ArrangementStatistics) {
+        self.records += other.records;
+        self.keys += other.keys;
+    }
+
+    /// The expected number of records that share a key.
+    pub fn fanout(&self) -> u64 {
+        std::cmp::max(self.records / std::cmp::max(self.keys, 1), 1)
+    }
 }
 
 /// Responses that the storage nature of a worker/dataflow can provide back to the coordinator.
@@ -435,6 +463,10 @@ pub enum StorageResponse<T = mz_repr::Timestamp> {
     /// Data about timestamp bindings, sent to the coordinator, in service
     /// of a specific "linearized" read request
     LinearizedTimestamps(LinearizedTimestampBindingFeedback<T>),
+
+    /// Rows to append to tables, describing messages that sources failed to
+    /// decode and redirected to the tables with `ON DECODE ERROR`.
+    DeadLetters(Vec<(GlobalId, Vec<Row>)>),
 }
 
 /// A client to a running dataflow server.
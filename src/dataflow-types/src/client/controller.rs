@@ -269,6 +269,11 @@ where
                             .update_write_frontiers(&[(*global_id, changes)])
                             .await?;
                     }
+                    ComputeResponse::ArrangementStatistics(updates) => {
+                        self.compute_mut(instance)
+                            .expect("Reference to absent instance")
+                            .update_statistics(updates);
+                    }
                 }
                 Ok(Some(Response::Compute(response)))
             }
@@ -284,7 +289,8 @@ where
                             .persist_timestamp_bindings(&feedback)
                             .await?;
                     }
-                    Some(StorageResponse::LinearizedTimestamps(_)) => {
+                    Some(StorageResponse::LinearizedTimestamps(_))
+                    | Some(StorageResponse::DeadLetters(_)) => {
                         // Nothing to do here.
                     }
                     None => (),
@@ -33,7 +33,7 @@ use uuid::Uuid;
 
 use crate::client::controller::storage::{StorageController, StorageError};
 use crate::client::replicated::ActiveReplication;
-use crate::client::{ComputeClient, ComputeCommand, ComputeInstanceId};
+use crate::client::{ArrangementStatistics, ComputeClient, ComputeCommand, ComputeInstanceId};
 use crate::client::{GenericClient, Peek};
 use crate::logging::LoggingConfig;
 use crate::DataflowDescription;
@@ -534,6 +534,16 @@ where
         Ok(())
     }
 
+    /// Accept arrangement statistics from the compute layer.
+    pub(super) fn update_statistics(&mut self, updates: &[(GlobalId, ArrangementStatistics)]) {
+        for (id, statistics) in updates.iter() {
+            // Statistics may arrive after a collection has been dropped.
+            if let Ok(collection) = self.collection_mut(*id) {
+                collection.statistics = Some(*statistics);
+            }
+        }
+    }
+
     /// Applies `updates`, propagates consequences through other read capabilities, and sends an appropriate compaction command.
     pub(super) async fn update_read_capabilities(
         &mut self,
@@ -643,6 +653,10 @@ pub struct CollectionState<T> {
     /// write capabilities of others. All future writes will have times greater than or
     /// equal to `upper_frontier.frontier()`.
     pub write_frontier: MutableAntichain<T>,
+
+    /// The most recently reported size estimates of the arrangement that
+    /// backs the collection, if any.
+    pub statistics: Option<ArrangementStatistics>,
 }
 
 impl<T: Timestamp> CollectionState<T> {
@@ -661,6 +675,7 @@ impl<T: Timestamp> CollectionState<T> {
             storage_dependencies,
             compute_dependencies,
             write_frontier: MutableAntichain::new_bottom(Timestamp::minimum()),
+            statistics: None,
         }
     }
 
@@ -650,4 +650,21 @@ impl<T: Timestamp> CollectionState<T> {
             last_reported_ts_bindings,
         }
     }
+
+    /// Estimates the number of records that the source has ingested, or
+    /// returns `None` if no timestamp bindings have been reported for it.
+    ///
+    /// The estimate is the sum of the offsets that have been bound in each
+    /// partition. Offsets count messages, so the estimate overcounts the
+    /// records of sources whose envelope retracts or replaces records.
+    pub fn ingested_records(&self) -> Option<u64> {
+        if self.last_reported_ts_bindings.is_empty() {
+            return None;
+        }
+        let offsets = self
+            .last_reported_ts_bindings
+            .values()
+            .map(|offset| u64::try_from(offset.offset).unwrap_or(0));
+        Some(offsets.sum())
+    }
 }
@@ -27,7 +27,8 @@ use mz_ore::cast::CastFrom;
 use mz_repr::{Diff, Row};
 
 use crate::client::{
-    ComputeCommand, ComputeResponse, GenericClient, PeekResponse, StorageCommand, StorageResponse,
+    ArrangementStatistics, ComputeCommand, ComputeResponse, GenericClient, PeekResponse,
+    StorageCommand, StorageResponse,
 };
 use crate::{DataflowDescription, TailResponse};
 
@@ -242,6 +243,10 @@ where
             StorageResponse::LinearizedTimestamps(feedback) => {
                 Some(Ok(StorageResponse::LinearizedTimestamps(feedback)))
             }
+            // Each part reports the messages that it decoded itself.
+            StorageResponse::DeadLetters(letters) => {
+                Some(Ok(StorageResponse::DeadLetters(letters)))
+            }
         }
     }
 }
@@ -261,6 +266,9 @@ pub struct PartitionedComputeState<T> {
     /// Tracks in-progress `TAIL`s, and the stashed rows we are holding
     /// back until their timestamps are complete.
     pending_tails: HashMap<GlobalId, Option<(MutableAntichain<T>, Vec<(T, Row, Diff)>)>>,
+    /// The most recent arrangement statistics reported by each partition;
+    /// reported once all partitions have weighed in.
+    statistics: HashMap<GlobalId, HashMap<usize, ArrangementStatistics>>,
 }
 
 impl<T> Partitionable<ComputeCommand<T>, ComputeResponse<T>>
@@ -276,6 +284,7 @@ where
             uppers: HashMap::new(),
             peek_responses: HashMap::new(),
             pending_tails: HashMap::new(),
+            statistics: HashMap::new(),
         }
     }
 }
@@ -290,10 +299,12 @@ where
             uppers,
             peek_responses,
             pending_tails,
+            statistics,
         } = self;
         uppers.clear();
         peek_responses.clear();
         pending_tails.clear();
+        statistics.clear();
     }
 
     /// Observes commands that move past, and prepares state for responses.
@@ -319,6 +330,7 @@ where
             assert!(previous.is_none(), "Protocol error: starting frontier tracking for already present identifier {:?} due to command {:?}", id, command);
         }
         for id in cease.into_iter() {
+            self.statistics.remove(&id);
             let previous = self.uppers.remove(&id);
             if previous.is_none() {
                 debug!("Protocol error: ceasing frontier tracking for absent identifier {:?} due to command {:?}", id, command);
@@ -504,6 +516,29 @@ where
                     }
                 }
             }
+            ComputeResponse::ArrangementStatistics(list) => {
+                let mut merged = Vec::new();
+                for (id, statistics) in list {
+                    // Ignore statistics for collections that are not tracked.
+                    if !self.uppers.contains_key(&id) {
+                        continue;
+                    }
+                    let entry = self.statistics.entry(id).or_default();
+                    entry.insert(shard_id, statistics);
+                    if entry.len() == self.parts {
+                        let mut total = ArrangementStatistics::default();
+                        for statistics in entry.values() {
+                            total.add(statistics);
+                        }
+                        merged.push((id, total));
+                    }
+                }
+                if merged.is_empty() {
+                    None
+                } else {
+                    Some(Ok(ComputeResponse::ArrangementStatistics(merged)))
+                }
+            }
         }
     }
 }
@@ -285,6 +285,11 @@ where
                                 }
                             }
                         }
+                        Ok(ComputeResponse::ArrangementStatistics(list)) => {
+                            // Replicas maintain the same arrangements, so the
+                            // statistics of any replica are a fine estimate.
+                            return Ok(Some(ComputeResponse::ArrangementStatistics(list)));
+                        }
                         Err(_error) => {
                             errored_replica = Some(replica_id);
                             break;
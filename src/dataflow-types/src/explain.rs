@@ -20,6 +20,9 @@
 //!     [`mz_expr::explain::ViewExplanation`].
 //!   * Last is the view or query being explained. The format is "Query:"
 //!     followed by the output of [`mz_expr::explain::ViewExplanation`].
+//!   * If requested, the size estimates of the indexes and sources imported
+//!     into the dataflow follow. The format is "Estimates:" followed by one
+//!     line per index and then one line per source.
 //!   * If there are no sources with some [`LinearOperator`] and no intermediate
 //!     views, then the format is identical to the format of
 //!     [`mz_expr::explain::ViewExplanation`].
@@ -28,12 +31,20 @@
 //! printed in contexts where trailing whitespace is unacceptable, like
 //! sqllogictest files.
 
+use std::collections::HashMap;
 use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
 
+use crate::client::ArrangementStatistics;
+use crate::plan::Plan;
 use crate::{DataflowDescription, LinearOperator};
 
 use mz_expr::explain::{Indices, ViewExplanation};
-use mz_expr::{ExprHumanizer, GlobalId, OptimizedMirRelationExpr, RowSetFinishing};
+use mz_expr::{
+    CollectionPlan, ExprHumanizer, GlobalId, Id, MirScalarExpr, OptimizedMirRelationExpr,
+    RowSetFinishing,
+};
 use mz_ore::result::ResultExt;
 use mz_ore::str::{bracketed, separated};
 
@@ -60,6 +71,11 @@ where
     sources: Vec<(GlobalId, &'a LinearOperator)>,
     /// One `ViewExplanation` per view in the dataflow.
     views: Vec<(GlobalId, &'a ViewExpr)>,
+    /// The size estimates of indexes to mention at the end, identified by
+    /// the collection they index and their key.
+    index_estimates: Vec<(GlobalId, Vec<MirScalarExpr>, Option<ArrangementStatistics>)>,
+    /// The estimated number of records of sources to mention at the end.
+    source_estimates: Vec<(GlobalId, Option<u64>)>,
     /// An optional `RowSetFinishing` to mention at the end.
     finishing: Option<RowSetFinishing>,
 }
@@ -78,6 +94,8 @@ where
             expr_humanizer,
             sources: vec![],
             views: vec![(GlobalId::Explain, expr)],
+            index_estimates: vec![],
+            source_estimates: vec![],
             finishing: None,
         }
     }
@@ -108,6 +126,8 @@ where
             expr_humanizer,
             sources,
             views,
+            index_estimates: vec![],
+            source_estimates: vec![],
             finishing: None,
         }
     }
@@ -116,6 +136,19 @@ where
     pub fn explain_row_set_finishing(&mut self, finishing: RowSetFinishing) {
         self.finishing = Some(finishing);
     }
+
+    /// Attach the size estimates of indexes and sources to the explanation.
+    ///
+    /// Each index is identified by the collection it indexes and its key.
+    /// Indexes and sources whose size is unknown are explained as such.
+    pub fn explain_estimates(
+        &mut self,
+        indexes: Vec<(GlobalId, Vec<MirScalarExpr>, Option<ArrangementStatistics>)>,
+        sources: Vec<(GlobalId, Option<u64>)>,
+    ) {
+        self.index_estimates = indexes;
+        self.source_estimates = sources;
+    }
 }
 
 impl<'a, Formatter, ViewExpr> fmt::Display for Explanation<'a, Formatter, ViewExpr>
@@ -155,6 +188,43 @@ where
             self.formatter.fmt_view(f, view)?;
         }
 
+        if !self.index_estimates.is_empty() || !self.source_estimates.is_empty() {
+            writeln!(f, "\nEstimates:")?;
+            for (id, key, statistics) in &self.index_estimates {
+                write!(
+                    f,
+                    "| {} ({}) arranged by {}",
+                    self.expr_humanizer
+                        .humanize_id(*id)
+                        .unwrap_or_else(|| "?".to_owned()),
+                    id,
+                    bracketed("(", ")", separated(", ", key)),
+                )?;
+                match statistics {
+                    Some(statistics) => writeln!(
+                        f,
+                        " records={} keys={}",
+                        statistics.records, statistics.keys
+                    )?,
+                    None => writeln!(f, " unknown")?,
+                }
+            }
+            for (id, records) in &self.source_estimates {
+                write!(
+                    f,
+                    "| {} ({})",
+                    self.expr_humanizer
+                        .humanize_id(*id)
+                        .unwrap_or_else(|| "?".to_owned()),
+                    id,
+                )?;
+                match records {
+                    Some(records) => writeln!(f, " records={}", records)?,
+                    None => writeln!(f, " unknown")?,
+                }
+            }
+        }
+
         if let Some(finishing) = &self.finishing {
             writeln!(
                 f,
@@ -272,3 +342,283 @@ impl<T: fmt::Display + fmt::Debug> fmt::Display for TimestampExplanation<T> {
         Ok(())
     }
 }
+
+/// Runtime statistics of the operators that make up a plan node, summed
+/// across workers.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlanNodeStatistics {
+    /// Time spent scheduling the operators, in nanoseconds.
+    pub elapsed_ns: u64,
+    /// Records held in arrangements maintained by the operators.
+    pub records: i64,
+    /// Batches held in arrangements maintained by the operators.
+    pub batches: i64,
+    /// Messages sent on channels constructed for the operators.
+    pub messages_sent: i64,
+    /// Messages received on channels constructed for the operators.
+    pub messages_received: i64,
+}
+
+impl PlanNodeStatistics {
+    fn add(&mut self, other: &PlanNodeStatistics) {
+        self.elapsed_ns += other.elapsed_ns;
+        self.records += other.records;
+        self.batches += other.batches;
+        self.messages_sent += other.messages_sent;
+        self.messages_received += other.messages_received;
+    }
+}
+
+/// A physical plan annotated with the runtime statistics of the dataflow
+/// that renders it, as reported by `EXPLAIN ANALYZE`.
+///
+/// Each plan node is identified by its object and its position in a
+/// pre-order traversal of the object's plan, which is also the order in which
+/// the nodes are rendered.
+#[derive(Debug)]
+pub struct AnalyzedPlanExplanation {
+    dataflow: DataflowDescription<Plan>,
+    /// Human-readable names for the objects referenced by the plan.
+    names: HashMap<GlobalId, String>,
+    /// For each worker, the operators constructed for each plan node and its
+    /// inputs.
+    plan_nodes: HashMap<usize, Vec<(Range<usize>, GlobalId, usize)>>,
+    statistics: HashMap<(GlobalId, usize), PlanNodeStatistics>,
+}
+
+impl AnalyzedPlanExplanation {
+    pub fn new(dataflow: DataflowDescription<Plan>, expr_humanizer: &dyn ExprHumanizer) -> Self {
+        let mut names = HashMap::new();
+        for object in &dataflow.objects_to_build {
+            for id in std::iter::once(object.id).chain(object.plan.depends_on()) {
+                if let Some(name) = expr_humanizer.humanize_id(id) {
+                    names.insert(id, name);
+                }
+            }
+        }
+        Self {
+            dataflow,
+            names,
+            plan_nodes: HashMap::new(),
+            statistics: HashMap::new(),
+        }
+    }
+
+    /// Records that `operators` were constructed on `worker` to render node
+    /// `node` of the plan of `object`, including the node's inputs.
+    pub fn add_plan_node(
+        &mut self,
+        object: GlobalId,
+        node: usize,
+        worker: usize,
+        operators: Range<usize>,
+    ) {
+        self.plan_nodes
+            .entry(worker)
+            .or_default()
+            .push((operators, object, node));
+    }
+
+    /// Attributes the statistics of `operator` on `worker` to the innermost
+    /// plan node that constructed it, if any.
+    ///
+    /// Timely draws the identifiers of channels from the same sequence as
+    /// those of operators, so `operator` may also identify a channel, whose
+    /// message counts are then attributed to the node that connected it.
+    ///
+    /// All plan nodes must be added before any statistics.
+    pub fn add_operator_statistics(
+        &mut self,
+        worker: usize,
+        operator: usize,
+        statistics: PlanNodeStatistics,
+    ) {
+        // The operators of a node's inputs are nested within its own, and
+        // inputs follow their parents in pre-order, so the innermost node has
+        // the latest start and, among nodes with the same start, the highest
+        // position.
+        let innermost = self.plan_nodes.get(&worker).and_then(|nodes| {
+            nodes
+                .iter()
+                .filter(|(operators, _, _)| operators.contains(&operator))
+                .max_by_key(|(operators, _, node)| (operators.start, *node))
+        });
+        if let Some((_, object, node)) = innermost {
+            self.statistics
+                .entry((*object, *node))
+                .or_default()
+                .add(&statistics);
+        }
+    }
+
+    fn fmt_plan(
+        &self,
+        f: &mut fmt::Formatter,
+        object: GlobalId,
+        plan: &Plan,
+        depth: usize,
+        next_node: &mut usize,
+    ) -> fmt::Result {
+        let node = *next_node;
+        *next_node += 1;
+        write!(f, "{:indent$}{}", "", plan.name(), indent = depth * 2)?;
+        match plan {
+            Plan::Get {
+                id: Id::Global(id), ..
+            } => match self.names.get(id) {
+                Some(name) => write!(f, " {}", name)?,
+                None => write!(f, " {}", id)?,
+            },
+            Plan::Get {
+                id: Id::Local(id), ..
+            }
+            | Plan::Let { id, .. } => write!(f, " {}", id)?,
+            _ => (),
+        }
+        let statistics = self
+            .statistics
+            .get(&(object, node))
+            .cloned()
+            .unwrap_or_default();
+        writeln!(
+            f,
+            " (elapsed={:?} records={} batches={} sent={} received={})",
+            Duration::from_nanos(statistics.elapsed_ns),
+            statistics.records,
+            statistics.batches,
+            statistics.messages_sent,
+            statistics.messages_received
+        )?;
+        for child in plan.children() {
+            self.fmt_plan(f, object, child, depth + 1, next_node)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for AnalyzedPlanExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, object) in self.dataflow.objects_to_build.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(
+                f,
+                "View {} ({}):",
+                self.names
+                    .get(&object.id)
+                    .map(|name| name.as_str())
+                    .unwrap_or("?"),
+                object.id
+            )?;
+            self.fmt_plan(f, object.id, &object.plan, 1, &mut 0)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_expr::DummyHumanizer;
+
+    use super::*;
+
+    #[test]
+    fn analyzed_plan_attribution() {
+        let get = Plan::Get {
+            id: Id::Global(GlobalId::User(1)),
+            keys: crate::plan::AvailableCollections::new_raw(),
+            mfp: mz_expr::MapFilterProject::new(1),
+            key_val: None,
+        };
+        let plan = Plan::Negate {
+            input: Box::new(Plan::Negate {
+                input: Box::new(get),
+            }),
+        };
+        let mut dataflow = DataflowDescription::new("test".into());
+        dataflow.objects_to_build.push(crate::BuildDesc {
+            id: GlobalId::User(2),
+            plan,
+        });
+
+        let mut explanation = AnalyzedPlanExplanation::new(dataflow, &DummyHumanizer);
+        // The outer negation owns operator 5, the inner negation operator 3
+        // and channel 4, and the get operators 1 and 2.
+        explanation.add_plan_node(GlobalId::User(2), 0, 0, 1..6);
+        explanation.add_plan_node(GlobalId::User(2), 1, 0, 1..5);
+        explanation.add_plan_node(GlobalId::User(2), 2, 0, 1..3);
+        let statistics = |elapsed_ns, records| PlanNodeStatistics {
+            elapsed_ns,
+            records,
+            batches: 1,
+            ..Default::default()
+        };
+        explanation.add_operator_statistics(0, 1, statistics(10, 0));
+        explanation.add_operator_statistics(0, 2, statistics(20, 5));
+        explanation.add_operator_statistics(0, 3, statistics(40, 0));
+        explanation.add_operator_statistics(0, 5, statistics(80, 7));
+        // Operators outside of any plan node are ignored.
+        explanation.add_operator_statistics(0, 9, statistics(160, 0));
+        explanation.add_operator_statistics(1, 1, statistics(320, 0));
+        explanation.add_operator_statistics(
+            0,
+            4,
+            PlanNodeStatistics {
+                messages_sent: 6,
+                ..Default::default()
+            },
+        );
+        explanation.add_operator_statistics(
+            0,
+            4,
+            PlanNodeStatistics {
+                messages_received: 6,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(
+            explanation.to_string(),
+            "View ? (u2):
+  Negate (elapsed=80ns records=7 batches=1 sent=0 received=0)
+    Negate (elapsed=40ns records=0 batches=1 sent=6 received=6)
+      Get u1 (elapsed=30ns records=5 batches=2 sent=0 received=0)
+"
+        );
+    }
+
+    #[test]
+    fn explain_estimates() {
+        let expr = OptimizedMirRelationExpr::declare_optimized(mz_expr::MirRelationExpr::Get {
+            id: Id::Global(GlobalId::User(1)),
+            typ: mz_repr::RelationType::new(vec![mz_repr::ScalarType::Int32.nullable(false)]),
+        });
+        let formatter = DataflowGraphFormatter::new(&DummyHumanizer, false);
+        let mut explanation = Explanation::new(&expr, &DummyHumanizer, &formatter);
+        explanation.explain_estimates(
+            vec![
+                (
+                    GlobalId::User(1),
+                    vec![MirScalarExpr::Column(0)],
+                    Some(ArrangementStatistics {
+                        records: 100,
+                        keys: 10,
+                    }),
+                ),
+                (GlobalId::User(2), vec![MirScalarExpr::Column(1)], None),
+            ],
+            vec![(GlobalId::User(3), Some(1000)), (GlobalId::User(4), None)],
+        );
+
+        assert!(explanation.to_string().ends_with(
+            "
+Estimates:
+| ? (u1) arranged by (#0) records=100 keys=10
+| ? (u2) arranged by (#1) unknown
+| ? (u3) records=1000
+| ? (u4) unknown
+"
+        ));
+    }
+}
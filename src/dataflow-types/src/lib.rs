@@ -24,9 +24,11 @@ mod gen;
 mod types;
 
 pub use errors::*;
+pub use explain::AnalyzedPlanExplanation;
 pub use explain::DataflowGraphFormatter;
 pub use explain::Explanation;
 pub use explain::JsonViewFormatter;
+pub use explain::PlanNodeStatistics;
 pub use explain::TimestampExplanation;
 pub use explain::TimestampSource;
 pub use gen::*;
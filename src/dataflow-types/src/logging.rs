@@ -61,10 +61,12 @@ pub enum DifferentialLog {
 pub enum MaterializedLog {
     DataflowCurrent,
     DataflowDependency,
+    DataflowPlanNodes,
     FrontierCurrent,
     KafkaSourceStatistics,
     PeekCurrent,
     PeekDuration,
+    SourceDecodeErrors,
     SourceInfo,
 }
 
@@ -172,11 +174,28 @@ impl LogVariant {
                 .with_column("timestamp", ScalarType::Int64.nullable(false))
                 .with_key(vec![0, 1, 2, 3]),
 
+            LogVariant::Materialized(MaterializedLog::SourceDecodeErrors) => RelationDesc::empty()
+                .with_column("source_id", ScalarType::String.nullable(false))
+                .with_column("dataflow_id", ScalarType::Int64.nullable(false))
+                .with_column("partition_id", ScalarType::String.nullable(true))
+                .with_column("offset", ScalarType::Int64.nullable(false))
+                .with_column("error", ScalarType::String.nullable(false))
+                .with_key(vec![0, 1, 2, 3]),
+
             LogVariant::Materialized(MaterializedLog::DataflowDependency) => RelationDesc::empty()
                 .with_column("dataflow", ScalarType::String.nullable(false))
                 .with_column("source", ScalarType::String.nullable(false))
                 .with_column("worker", ScalarType::Int64.nullable(false)),
 
+            LogVariant::Materialized(MaterializedLog::DataflowPlanNodes) => RelationDesc::empty()
+                .with_column("export_id", ScalarType::String.nullable(false))
+                .with_column("object_id", ScalarType::String.nullable(false))
+                .with_column("node", ScalarType::Int64.nullable(false))
+                .with_column("worker", ScalarType::Int64.nullable(false))
+                .with_column("operator_start", ScalarType::Int64.nullable(false))
+                .with_column("operator_end", ScalarType::Int64.nullable(false))
+                .with_key(vec![0, 1, 2, 3]),
+
             LogVariant::Materialized(MaterializedLog::FrontierCurrent) => RelationDesc::empty()
                 .with_column("global_id", ScalarType::String.nullable(false))
                 .with_column("worker", ScalarType::Int64.nullable(false))
@@ -246,12 +265,17 @@ impl LogVariant {
             )],
             LogVariant::Materialized(MaterializedLog::DataflowCurrent) => vec![],
             LogVariant::Materialized(MaterializedLog::DataflowDependency) => vec![],
+            LogVariant::Materialized(MaterializedLog::DataflowPlanNodes) => vec![],
             LogVariant::Materialized(MaterializedLog::FrontierCurrent) => vec![],
             LogVariant::Materialized(MaterializedLog::KafkaSourceStatistics) => vec![(
                 LogVariant::Materialized(MaterializedLog::SourceInfo),
                 vec![(0, 1)],
             )],
             LogVariant::Materialized(MaterializedLog::PeekCurrent) => vec![],
+            LogVariant::Materialized(MaterializedLog::SourceDecodeErrors) => vec![(
+                LogVariant::Materialized(MaterializedLog::SourceInfo),
+                vec![(0, 1), (1, 2), (2, 3)],
+            )],
             LogVariant::Materialized(MaterializedLog::SourceInfo) => vec![],
             LogVariant::Materialized(MaterializedLog::PeekDuration) => vec![],
         }
@@ -29,6 +29,7 @@
 
 pub mod delta_join;
 pub mod linear_join;
+pub mod outer_join;
 
 use std::collections::HashMap;
 
@@ -39,6 +40,7 @@ use mz_repr::{Datum, Row, RowArena};
 
 pub use delta_join::DeltaJoinPlan;
 pub use linear_join::LinearJoinPlan;
+pub use outer_join::OuterJoinPlan;
 
 /// A complete enumeration of possible join plans to render.
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -47,6 +49,8 @@ pub enum JoinPlan {
     Linear(LinearJoinPlan),
     /// A join implemented by a delta join.
     Delta(DeltaJoinPlan),
+    /// A join implemented by an outer join of two inputs.
+    Outer(OuterJoinPlan),
 }
 
 /// A manual closure implementation of filtering and logic application.
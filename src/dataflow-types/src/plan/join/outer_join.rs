@@ -0,0 +1,130 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Planning of outer joins.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::plan::join::JoinClosure;
+use crate::plan::AvailableCollections;
+use mz_expr::permutation_for_arrangement;
+use mz_expr::{MapFilterProject, MirScalarExpr, OuterJoinKind};
+
+/// A plan for the execution of a left or full outer join of two inputs.
+///
+/// Each input is arranged once, by its join key. Matching records are
+/// produced by joining the two arrangements. The records of a preserved
+/// input are produced padded with nulls whenever their key is absent
+/// from the other input, which the renderer determines by maintaining
+/// the number of records of each key in each input, in a single operator
+/// over both arrangements. Keys containing nulls never match.
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct OuterJoinPlan {
+    /// Which inputs have their unmatched records preserved.
+    pub kind: OuterJoinKind,
+    /// The key by which the left input is arranged.
+    pub left_key: Vec<MirScalarExpr>,
+    /// The key by which the right input is arranged.
+    pub right_key: Vec<MirScalarExpr>,
+    /// The number of columns of the left input.
+    pub left_arity: usize,
+    /// The number of columns of the right input.
+    pub right_arity: usize,
+    /// The closure to apply to the concatenation of the key and value
+    /// of a left record with the key and value of a matching right record.
+    pub matched_closure: JoinClosure,
+    /// The closure that recovers left records from their key and value.
+    pub left_closure: JoinClosure,
+    /// The closure that recovers right records from their key and value.
+    pub right_closure: JoinClosure,
+}
+
+impl OuterJoinPlan {
+    /// Create a new outer join plan from the required arguments.
+    ///
+    /// Returns the plan, and the arrangements of each input that must be
+    /// formed in addition to those already `available`.
+    pub fn create_from(
+        kind: OuterJoinKind,
+        left_key: Vec<MirScalarExpr>,
+        right_key: Vec<MirScalarExpr>,
+        left_arity: usize,
+        right_arity: usize,
+        available: &[AvailableCollections],
+    ) -> (Self, Vec<AvailableCollections>) {
+        let mut requested: Vec<AvailableCollections> = vec![Default::default(); 2];
+
+        let mut arrangement = |input: usize, key: &Vec<MirScalarExpr>, arity: usize| {
+            available[input]
+                .arranged
+                .iter()
+                .find_map(|(k, permutation, thinning)| {
+                    if k == key {
+                        Some((permutation.clone(), thinning.clone()))
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or_else(|| {
+                    let (permutation, thinning) =
+                        permutation_for_arrangement::<HashMap<_, _>>(key, arity);
+                    requested[input].arranged.push((
+                        key.clone(),
+                        permutation.clone(),
+                        thinning.clone(),
+                    ));
+                    (permutation, thinning)
+                })
+        };
+        let (left_permutation, left_thinning) = arrangement(0, &left_key, left_arity);
+        let (right_permutation, right_thinning) = arrangement(1, &right_key, right_arity);
+
+        let left_width = left_key.len() + left_thinning.len();
+        let right_width = right_key.len() + right_thinning.len();
+
+        let closure = |permutation: HashMap<usize, usize>, arity: usize, width: usize| {
+            let mut mfp = MapFilterProject::new(arity);
+            mfp.permute(permutation, width);
+            JoinClosure {
+                ready_equivalences: vec![],
+                before: mfp.into_plan().unwrap().into_nontemporal().unwrap(),
+            }
+        };
+
+        // Right columns follow all of the left columns, and their locations
+        // follow the left key and value.
+        let matched_permutation = left_permutation
+            .iter()
+            .map(|(column, location)| (*column, *location))
+            .chain(
+                right_permutation
+                    .iter()
+                    .map(|(column, location)| (column + left_arity, location + left_width)),
+            )
+            .collect();
+
+        let plan = OuterJoinPlan {
+            kind,
+            left_key,
+            right_key,
+            left_arity,
+            right_arity,
+            matched_closure: closure(
+                matched_permutation,
+                left_arity + right_arity,
+                left_width + right_width,
+            ),
+            left_closure: closure(left_permutation, left_arity, left_width),
+            right_closure: closure(right_permutation, right_arity, right_width),
+        };
+        (plan, requested)
+    }
+}
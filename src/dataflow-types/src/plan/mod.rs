@@ -24,7 +24,7 @@ use mz_expr::{
 };
 use mz_repr::{Datum, Diff, Row};
 
-use self::join::{DeltaJoinPlan, JoinPlan, LinearJoinPlan};
+use self::join::{DeltaJoinPlan, JoinPlan, LinearJoinPlan, OuterJoinPlan};
 use self::reduce::{KeyValPlan, ReducePlan};
 use self::threshold::ThresholdPlan;
 use self::top_k::TopKPlan;
@@ -644,6 +644,51 @@ This is not expected to cause incorrect results, but could indicate a performanc
                     AvailableCollections::new_raw(),
                 )
             }
+            MirRelationExpr::OuterJoin {
+                left,
+                right,
+                left_key,
+                right_key,
+                kind,
+            } => {
+                let left_arity = left.arity();
+                let right_arity = right.arity();
+                let (left, left_keys) = Plan::from_mir(left, arrangements, debug_info)?;
+                let (right, right_keys) = Plan::from_mir(right, arrangements, debug_info)?;
+                let input_keys = vec![left_keys, right_keys];
+
+                let (plan, missing) = OuterJoinPlan::create_from(
+                    *kind,
+                    left_key.clone(),
+                    right_key.clone(),
+                    left_arity,
+                    right_arity,
+                    &input_keys,
+                );
+                // The renderer expects both inputs to be arranged by their keys;
+                // plan any arrangements that are not yet available.
+                let plans = vec![left, right]
+                    .into_iter()
+                    .zip(input_keys.iter())
+                    .zip(missing.into_iter())
+                    .zip(vec![left_arity, right_arity])
+                    .map(|(((plan, keys), missing), arity)| {
+                        if missing != Default::default() {
+                            plan.arrange_by(missing, keys, arity)
+                        } else {
+                            plan
+                        }
+                    })
+                    .collect();
+                // Return the plan, and no arrangements.
+                (
+                    Plan::Join {
+                        inputs: plans,
+                        plan: JoinPlan::Outer(plan),
+                    },
+                    AvailableCollections::new_raw(),
+                )
+            }
             MirRelationExpr::Reduce {
                 input,
                 group_key,
@@ -1165,6 +1210,53 @@ This is not expected to cause incorrect results, but could indicate a performanc
     }
 }
 
+impl<T> Plan<T> {
+    /// A short name for the kind of this plan node.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Plan::Constant { .. } => "Constant",
+            Plan::Get { .. } => "Get",
+            Plan::Let { .. } => "Let",
+            Plan::Mfp { .. } => "Mfp",
+            Plan::FlatMap { .. } => "FlatMap",
+            Plan::Join {
+                plan: JoinPlan::Linear(_),
+                ..
+            } => "LinearJoin",
+            Plan::Join {
+                plan: JoinPlan::Delta(_),
+                ..
+            } => "DeltaJoin",
+            Plan::Join {
+                plan: JoinPlan::Outer(_),
+                ..
+            } => "OuterJoin",
+            Plan::Reduce { .. } => "Reduce",
+            Plan::TopK { .. } => "TopK",
+            Plan::Negate { .. } => "Negate",
+            Plan::Threshold { .. } => "Threshold",
+            Plan::Union { .. } => "Union",
+            Plan::ArrangeBy { .. } => "ArrangeBy",
+        }
+    }
+
+    /// The inputs of this plan node, in the order in which they are rendered.
+    pub fn children(&self) -> Vec<&Plan<T>> {
+        match self {
+            Plan::Constant { .. } | Plan::Get { .. } => vec![],
+            Plan::Let { value, body, .. } => vec![&**value, &**body],
+            Plan::Join { inputs, .. } | Plan::Union { inputs } => inputs.iter().collect(),
+            Plan::Mfp { input, .. }
+            | Plan::FlatMap { input, .. }
+            | Plan::ArrangeBy { input, .. }
+            | Plan::Reduce { input, .. }
+            | Plan::TopK { input, .. }
+            | Plan::Negate { input }
+            | Plan::Threshold { input, .. } => vec![&**input],
+        }
+    }
+}
+
 impl<T> CollectionPlan for Plan<T> {
     fn depends_on_into(&self, out: &mut BTreeSet<GlobalId>) {
         match self {
@@ -157,6 +157,10 @@ where
                 self.responses
                     .push_back(ComputeResponse::TailResponse(id, response));
             }
+            ComputeResponse::ArrangementStatistics(list) => {
+                self.responses
+                    .push_back(ComputeResponse::ArrangementStatistics(list));
+            }
         }
     }
 
@@ -426,6 +426,7 @@ pub mod sources {
     use uuid::Uuid;
 
     use crate::gen::postgres_source::PostgresSourceDetails;
+    use mz_expr::GlobalId;
     use mz_kafka_util::KafkaAddrs;
     use mz_repr::{ColumnType, RelationDesc, RelationType, ScalarType};
 
@@ -434,7 +435,7 @@ pub mod sources {
         use anyhow::Context;
         use serde::{Deserialize, Serialize};
 
-        use mz_interchange::{avro, protobuf};
+        use mz_interchange::{avro, json, orc, parquet, protobuf};
         use mz_repr::{ColumnType, RelationDesc, ScalarType};
 
         /// A description of how to interpret data from various sources
@@ -462,6 +463,9 @@ pub mod sources {
             Postgres,
             Bytes,
             Text,
+            Parquet(ParquetEncoding),
+            Orc(OrcEncoding),
+            Json(JsonEncoding),
         }
 
         impl SourceDataEncoding {
@@ -573,6 +577,18 @@ pub mod sources {
                     },
                     DataEncoding::Text => RelationDesc::empty()
                         .with_column("text", ScalarType::String.nullable(false)),
+                    DataEncoding::Parquet(ParquetEncoding { schema }) => {
+                        parquet::ParquetSchema::from_json(schema)?.desc()
+                    }
+                    DataEncoding::Orc(OrcEncoding { schema }) => {
+                        orc::OrcSchema::from_json(schema)?.desc()
+                    }
+                    DataEncoding::Json(JsonEncoding { schema: None }) => {
+                        RelationDesc::empty().with_column("data", ScalarType::Jsonb.nullable(false))
+                    }
+                    DataEncoding::Json(JsonEncoding {
+                        schema: Some(schema),
+                    }) => json::JsonSourceSchema::from_json(schema)?.desc(),
                     DataEncoding::Postgres => RelationDesc::empty()
                         .with_column("oid", ScalarType::Int32.nullable(false))
                         .with_column(
@@ -596,6 +612,9 @@ pub mod sources {
                     DataEncoding::Csv(_) => "Csv",
                     DataEncoding::Text => "Text",
                     DataEncoding::Postgres => "Postgres",
+                    DataEncoding::Parquet(_) => "Parquet",
+                    DataEncoding::Orc(_) => "Orc",
+                    DataEncoding::Json(_) => "Json",
                 }
             }
         }
@@ -613,6 +632,35 @@ pub mod sources {
             pub reader_schema: String,
         }
 
+        /// Encoding in Parquet format.
+        ///
+        /// Each Parquet file is decoded as a whole, rather than record by
+        /// record, so this encoding is only valid for file and S3 sources.
+        #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+        pub struct ParquetEncoding {
+            /// The JSON-serialized [`parquet::ParquetSchema`] of the source.
+            pub schema: String,
+        }
+
+        /// Encoding in ORC format.
+        ///
+        /// As for [`ParquetEncoding`], this encoding is only valid for file
+        /// and S3 sources.
+        #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+        pub struct OrcEncoding {
+            /// The JSON-serialized [`orc::OrcSchema`] of the source.
+            pub schema: String,
+        }
+
+        /// Encoding in JSON format, with one JSON value per message or line.
+        #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+        pub struct JsonEncoding {
+            /// The JSON-serialized [`json::JsonSourceSchema`] of the source,
+            /// if messages are to be decoded into typed columns rather than a
+            /// single `jsonb` column.
+            pub schema: Option<String>,
+        }
+
         /// Encoding in Protobuf format.
         #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
         pub struct ProtobufEncoding {
@@ -860,6 +908,38 @@ pub mod sources {
         CdcV2,
     }
 
+    /// What a source does with messages that fail to decode.
+    #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+    pub enum DecodeErrorPolicy {
+        /// Report the error in the source's error collection.
+        Fail,
+        /// Drop the message, recording the error in `mz_source_decode_errors`.
+        Skip,
+        /// Like `Skip`, but additionally write the undecodable message to the
+        /// given topic of the Kafka cluster the source reads from.
+        RedirectToTopic { topic: String },
+        /// Like `Skip`, but additionally write the undecodable message to the
+        /// given table, as a row of the source ID, partition, offset, key,
+        /// value and error.
+        RedirectToTable { id: GlobalId },
+    }
+
+    impl DecodeErrorPolicy {
+        /// The type that a table must have for messages to be redirected to
+        /// it with `RedirectToTable`: the ID of the source, and the partition,
+        /// offset, key, value and error of the message.
+        pub fn dead_letter_table_type() -> RelationType {
+            RelationType::new(vec![
+                ScalarType::String.nullable(false),
+                ScalarType::String.nullable(true),
+                ScalarType::Int64.nullable(false),
+                ScalarType::Bytes.nullable(true),
+                ScalarType::Bytes.nullable(true),
+                ScalarType::String.nullable(false),
+            ])
+        }
+    }
+
     /// `UnplannedSourceEnvelope` is a `SourceEnvelope` missing some information. This information
     /// is obtained in `UnplannedSourceEnvelope::desc`, where
     /// `UnplannedSourceEnvelope::into_source_envelope`
@@ -1206,6 +1286,7 @@ pub mod sources {
             connector: ExternalSourceConnector,
             encoding: encoding::SourceDataEncoding,
             envelope: SourceEnvelope,
+            decode_errors: DecodeErrorPolicy,
             metadata_columns: Vec<IncludedColumnSource>,
             ts_frequency: Duration,
             timeline: Timeline,
@@ -1777,6 +1858,76 @@ pub mod sinks {
     pub struct AvroOcfSinkConnector {
         pub value_desc: RelationDesc,
         pub path: PathBuf,
+        pub codec: AvroOcfCodec,
+        pub block_size: usize,
+        pub rollover: AvroOcfRollover,
+    }
+
+    /// The compression codec used for the data blocks of an Avro OCF sink.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+    pub enum AvroOcfCodec {
+        Null,
+        Deflate,
+        Snappy,
+        Zstandard,
+    }
+
+    impl AvroOcfCodec {
+        /// Returns the name of the codec, as written in the `avro.codec`
+        /// header of the object container file.
+        pub fn name(&self) -> &'static str {
+            match self {
+                AvroOcfCodec::Null => "null",
+                AvroOcfCodec::Deflate => "deflate",
+                AvroOcfCodec::Snappy => "snappy",
+                AvroOcfCodec::Zstandard => "zstandard",
+            }
+        }
+    }
+
+    /// Describes when an Avro OCF sink closes its current file and starts
+    /// writing to a new one.
+    ///
+    /// If neither threshold is set, the sink writes to a single file forever.
+    #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+    pub struct AvroOcfRollover {
+        /// Roll over once the current file holds at least this many bytes.
+        pub bytes: Option<u64>,
+        /// Roll over once the current file has been open for at least this
+        /// long.
+        pub duration: Option<Duration>,
+    }
+
+    impl AvroOcfRollover {
+        /// Reports whether the sink ever rolls over to a new file.
+        pub fn is_enabled(&self) -> bool {
+            self.bytes.is_some() || self.duration.is_some()
+        }
+    }
+
+    impl AvroOcfSinkConnector {
+        /// Returns the path of the `index`th file written by the sink.
+        ///
+        /// The first file is written to `path` itself; later files insert
+        /// the index between the file stem and the extension.
+        pub fn file_path(&self, index: usize) -> PathBuf {
+            if index == 0 {
+                return self.path.clone();
+            }
+            let mut name = self.path.file_stem().unwrap_or_default().to_owned();
+            name.push(format!("-{}", index));
+            if let Some(extension) = self.path.extension() {
+                name.push(".");
+                name.push(extension);
+            }
+            self.path.with_file_name(name)
+        }
+
+        /// Returns the path of the manifest that lists the files the sink has
+        /// completed.
+        pub fn manifest_path(&self) -> PathBuf {
+            self.path.with_extension("manifest")
+        }
     }
 
     impl SinkConnector {
@@ -1823,7 +1974,15 @@ pub mod sinks {
     }
 
     #[derive(Default, Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
-    pub struct TailSinkConnector {}
+    pub struct TailSinkConnector {
+        /// Report only the distinct timestamps at which the sinked collection
+        /// changed, each as a single empty row with a diff of one, rather
+        /// than the changes themselves.
+        pub distinct_timestamps: bool,
+        /// The indices of the key columns of `ENVELOPE UPSERT`, if the tail
+        /// reports the state of each key rather than the changes themselves.
+        pub upsert_key: Option<Vec<usize>>,
+    }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
     pub enum SinkConnectorBuilder {
@@ -1836,6 +1995,9 @@ pub mod sinks {
         pub path: PathBuf,
         pub file_name_suffix: String,
         pub value_desc: RelationDesc,
+        pub codec: AvroOcfCodec,
+        pub block_size: usize,
+        pub rollover: AvroOcfRollover,
     }
 
     #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
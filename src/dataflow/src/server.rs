@@ -185,6 +185,7 @@ pub fn serve_boundary<
                 ts_histories: HashMap::default(),
                 persisted_sources: PersistedSourceManager::new(),
                 decode_metrics,
+                dead_letters: Rc::new(RefCell::new(Vec::new())),
                 persist: config.persister.clone(),
                 reported_frontiers: HashMap::new(),
                 last_bindings_feedback: Instant::now(),
@@ -300,10 +301,12 @@ where
             // Report frontier information back the coordinator.
             if let Some(mut compute_state) = self.activate_compute() {
                 compute_state.report_compute_frontiers();
+                compute_state.report_arrangement_statistics();
             }
             self.activate_storage().update_rt_timestamps();
             self.activate_storage()
                 .report_conditional_frontier_progress();
+            self.activate_storage().report_dead_letters();
 
             // Handle any received commands.
             let mut cmds = vec![];
@@ -334,6 +337,8 @@ where
                             sink_write_frontiers: HashMap::new(),
                             pending_peeks: Vec::new(),
                             reported_frontiers: HashMap::new(),
+                            reported_statistics: HashMap::new(),
+                            last_statistics_report: Instant::now(),
                             sink_metrics: self.metrics_bundle.1.clone(),
                             materialized_logger: None,
                         });
@@ -155,6 +155,11 @@ impl<'a> TestCatalog {
         self.names.get(id)
     }
 
+    /// Looks up the identifier of the object named `name`.
+    pub fn get_source_id(&'a self, name: &str) -> Option<GlobalId> {
+        self.get(name).map(|(id, _)| *id)
+    }
+
     /// Handles instructions to modify the catalog.
     ///
     /// Currently supported commands:
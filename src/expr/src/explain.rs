@@ -118,6 +118,9 @@ impl<'a> ViewExplanation<'a> {
                 // For join and union, each input may need to go in its own
                 // chain.
                 Join { inputs, .. } => walk_many(inputs, explanation),
+                OuterJoin { left, right, .. } => {
+                    walk_many(iter::once(&**left).chain(iter::once(&**right)), explanation)
+                }
                 Union { base, inputs, .. } => {
                     walk_many(iter::once(&**base).chain(inputs), explanation)
                 }
@@ -276,6 +279,21 @@ impl<'a> ViewExplanation<'a> {
                 write!(f, "| | implementation = ")?;
                 self.fmt_join_implementation(f, inputs, implementation)?;
             }
+            OuterJoin {
+                left,
+                right,
+                left_key,
+                right_key,
+                kind,
+            } => writeln!(
+                f,
+                "| OuterJoin {} %{} %{} left_key={} right_key={}",
+                kind,
+                self.expr_chain(left),
+                self.expr_chain(right),
+                bracketed("(", ")", separated(", ", left_key)),
+                bracketed("(", ")", separated(", ", right_key)),
+            )?,
             Reduce {
                 group_key,
                 aggregates,
@@ -163,6 +163,27 @@ pub enum MirRelationExpr {
         #[serde(default)]
         implementation: JoinImplementation,
     },
+    /// Join two collections on equal keys, retaining the records of the
+    /// preserved inputs that have no match, padded with nulls.
+    ///
+    /// Records match if their keys are equal and contain no nulls, as with
+    /// SQL equality. The output contains the columns of `left` followed by
+    /// the columns of `right`.
+    ///
+    /// The runtime memory footprint of this operator is proportional to the
+    /// sizes of both inputs, plus the number of distinct keys.
+    OuterJoin {
+        /// The left input, always preserved.
+        left: Box<MirRelationExpr>,
+        /// The right input, preserved for full outer joins.
+        right: Box<MirRelationExpr>,
+        /// Key expressions over the columns of `left`.
+        left_key: Vec<MirScalarExpr>,
+        /// Key expressions over the columns of `right`.
+        right_key: Vec<MirScalarExpr>,
+        /// Which inputs are preserved.
+        kind: OuterJoinKind,
+    },
     /// Group a dataflow by some columns and aggregate over each group
     ///
     /// The runtime memory footprint of this operator is at most proportional to the
@@ -573,6 +594,40 @@ impl MirRelationExpr {
                 }
                 typ
             }
+            MirRelationExpr::OuterJoin {
+                right_key, kind, ..
+            } => {
+                let (left_typ, right_typ) = (&input_types[0], &input_types[1]);
+                let column_types = left_typ
+                    .column_types
+                    .iter()
+                    .map(|c| {
+                        c.clone()
+                            .nullable(c.nullable || *kind == OuterJoinKind::Full)
+                    })
+                    .chain(
+                        right_typ
+                            .column_types
+                            .iter()
+                            .map(|c| c.clone().nullable(true)),
+                    )
+                    .collect::<Vec<_>>();
+                let mut typ = RelationType::new(column_types);
+                // Each left record matches at most one right record if the
+                // right key contains a key of `right`, in which case the keys
+                // of `left` are keys of a left outer join.
+                if *kind == OuterJoinKind::Left
+                    && right_typ.keys.iter().any(|key| {
+                        key.iter()
+                            .all(|c| right_key.contains(&MirScalarExpr::Column(*c)))
+                    })
+                {
+                    for key in left_typ.keys.iter() {
+                        typ = typ.with_key(key.clone());
+                    }
+                }
+                typ
+            }
             MirRelationExpr::Reduce {
                 group_key,
                 aggregates,
@@ -742,6 +797,7 @@ impl MirRelationExpr {
             }
             MirRelationExpr::Filter { input, .. } => input.arity(),
             MirRelationExpr::Join { inputs, .. } => inputs.iter().map(|i| i.arity()).sum(),
+            MirRelationExpr::OuterJoin { left, right, .. } => left.arity() + right.arity(),
             MirRelationExpr::Reduce {
                 input: _,
                 group_key,
@@ -921,6 +977,24 @@ impl MirRelationExpr {
         }
     }
 
+    /// Constructs an outer join of `self` and `right` on the equality of
+    /// `left_key` and `right_key`.
+    pub fn outer_join(
+        self,
+        right: MirRelationExpr,
+        left_key: Vec<MirScalarExpr>,
+        right_key: Vec<MirScalarExpr>,
+        kind: OuterJoinKind,
+    ) -> Self {
+        MirRelationExpr::OuterJoin {
+            left: Box::new(self),
+            right: Box::new(right),
+            left_key,
+            right_key,
+            kind,
+        }
+    }
+
     /// Perform a key-wise reduction / aggregation.
     ///
     /// The `group_key` argument indicates columns in the input collection that should
@@ -1396,6 +1470,10 @@ impl MirRelationExprVisitor {
                     f(input)?;
                 }
             }
+            MirRelationExpr::OuterJoin { left, right, .. } => {
+                f(left)?;
+                f(right)?;
+            }
             MirRelationExpr::Reduce { input, .. } => {
                 f(input)?;
             }
@@ -1449,6 +1527,10 @@ impl MirRelationExprVisitor {
                     f(input)?;
                 }
             }
+            MirRelationExpr::OuterJoin { left, right, .. } => {
+                f(left)?;
+                f(right)?;
+            }
             MirRelationExpr::Reduce { input, .. } => {
                 f(input)?;
             }
@@ -1498,6 +1580,10 @@ impl MirRelationExprVisitor {
                     f(input);
                 }
             }
+            MirRelationExpr::OuterJoin { left, right, .. } => {
+                f(left);
+                f(right);
+            }
             MirRelationExpr::Reduce { input, .. } => {
                 f(input);
             }
@@ -1546,6 +1632,10 @@ impl MirRelationExprVisitor {
                     f(input);
                 }
             }
+            MirRelationExpr::OuterJoin { left, right, .. } => {
+                f(left);
+                f(right);
+            }
             MirRelationExpr::Reduce { input, .. } => {
                 f(input);
             }
@@ -1745,6 +1835,18 @@ impl MirRelationExprVisitor {
                 }
                 Ok(())
             }
+            MirRelationExpr::OuterJoin {
+                left_key,
+                right_key,
+                left: _,
+                right: _,
+                kind: _,
+            } => {
+                for expr in left_key.iter_mut().chain(right_key) {
+                    f(expr)?;
+                }
+                Ok(())
+            }
             MirRelationExpr::ArrangeBy { input: _, keys } => {
                 for key in keys {
                     for s in key {
@@ -2112,6 +2214,25 @@ impl Default for JoinImplementation {
     }
 }
 
+/// The inputs of a [`MirRelationExpr::OuterJoin`] whose unmatched records are
+/// retained.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, Hash, MzReflect)]
+pub enum OuterJoinKind {
+    /// Retain the unmatched records of the left input.
+    Left,
+    /// Retain the unmatched records of both inputs.
+    Full,
+}
+
+impl fmt::Display for OuterJoinKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OuterJoinKind::Left => f.write_str("left"),
+            OuterJoinKind::Full => f.write_str("full"),
+        }
+    }
+}
+
 /// Instructions for finishing the result of a query.
 ///
 /// The primary reason for the existence of this structure and attendant code
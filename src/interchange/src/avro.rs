@@ -72,6 +72,35 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn recursive_record() -> anyhow::Result<()> {
+        let schema = r#"{
+            "type": "record",
+            "name": "node",
+            "fields": [
+                { "name": "label", "type": "string" },
+                { "name": "parent", "type": ["null", "node"] },
+                { "name": "children", "type": { "type": "array", "items": "node" } }
+            ]
+        }"#;
+
+        let desc = schema_to_relationdesc(parse_schema(schema)?)?;
+        let expected_desc = RelationDesc::empty()
+            .with_column("label", ScalarType::String.nullable(false))
+            .with_column("parent", ScalarType::Jsonb.nullable(true))
+            .with_column(
+                "children",
+                ScalarType::List {
+                    element_type: Box::new(ScalarType::Jsonb),
+                    custom_oid: None,
+                }
+                .nullable(false),
+            );
+
+        assert_eq!(desc, expected_desc);
+        Ok(())
+    }
+
     #[test]
     /// Test that primitive Avro Schema types are allow Datums to be correctly
     /// serialized into Avro Values.
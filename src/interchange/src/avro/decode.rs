@@ -9,7 +9,6 @@
 
 use anyhow::Context;
 use std::cell::RefCell;
-use std::collections::BTreeMap;
 use std::io::Read;
 use std::rc::Rc;
 
@@ -18,16 +17,17 @@ use tracing::trace;
 use uuid::Uuid;
 
 use mz_avro::error::{DecodeError, Error as AvroError};
+use mz_avro::schema::FullName;
 use mz_avro::{
-    define_unexpected, give_value, AvroArrayAccess, AvroDecode, AvroDeserializer, AvroMapAccess,
-    AvroRead, AvroRecordAccess, GeneralDeserializer, StatefulAvroDecodable, ValueDecoder,
-    ValueOrReader,
+    define_unexpected, AvroArrayAccess, AvroDecode, AvroDeserializer, AvroMapAccess, AvroRead,
+    AvroRecordAccess, GeneralDeserializer, StatefulAvroDecodable, ValueOrReader,
 };
 use mz_ore::result::ResultExt;
 use mz_repr::adt::jsonb::JsonbPacker;
 use mz_repr::adt::numeric;
 use mz_repr::{Datum, Row, RowPacker};
 
+use crate::avro::schema::recursive_record_names;
 use crate::avro::ConfluentAvroResolver;
 
 /// Manages decoding of Avro-encoded bytes.
@@ -37,13 +37,16 @@ pub struct Decoder {
     debug_name: String,
     buf1: Vec<u8>,
     row_buf: Row,
+    records: RecordStack,
 }
 
 #[cfg(test)]
 mod tests {
     use futures::executor::block_on;
+    use serde_json::json;
 
     use crate::avro::Decoder;
+    use mz_repr::adt::jsonb::JsonbPacker;
     use mz_repr::{Datum, Row};
 
     #[test]
@@ -65,6 +68,47 @@ mod tests {
             Row::pack([Datum::Int32(0), Datum::Int32(0)])
         );
     }
+
+    #[test]
+    fn test_recursive_record() {
+        let schema = r#"{
+"type": "record",
+"name": "node",
+"fields": [{"name": "id", "type": "int"}, {"name": "children", "type": {"type": "array", "items": "node"}}]
+}"#;
+        let mut decoder = Decoder::new(&schema, None, "Test".to_string(), false).unwrap();
+        // {"id": 1, "children": [{"id": 2, "children": []}]}
+        let mut bytes: &[u8] = &[2, 2, 4, 0, 0];
+        let mut expected = Row::default();
+        let mut packer = expected.packer();
+        packer.push(Datum::Int32(1));
+        packer.push_list_with(|packer| {
+            JsonbPacker::new(packer)
+                .pack_serde_json(json!({"id": 2, "children": []}))
+                .unwrap()
+        });
+        assert_eq!(block_on(decoder.decode(&mut bytes)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_map_repeated_keys() {
+        let schema = r#"{
+"type": "record",
+"name": "test",
+"fields": [{"name": "m", "type": {"type": "map", "values": "int"}}]
+}"#;
+        let mut decoder = Decoder::new(&schema, None, "Test".to_string(), false).unwrap();
+        // A single block of {"b": 1, "a": 2, "b": 3}.
+        let mut bytes: &[u8] = &[6, 2, b'b', 2, 2, b'a', 4, 2, b'b', 6, 0];
+        let mut expected = Row::default();
+        expected.packer().push_dict_with(|packer| {
+            packer.push(Datum::String("a"));
+            packer.push(Datum::Int32(2));
+            packer.push(Datum::String("b"));
+            packer.push(Datum::Int32(3));
+        });
+        assert_eq!(block_on(decoder.decode(&mut bytes)).unwrap(), expected);
+    }
 }
 
 impl Decoder {
@@ -82,12 +126,14 @@ impl Decoder {
     ) -> anyhow::Result<Decoder> {
         let csr_avro =
             ConfluentAvroResolver::new(reader_schema, schema_registry, confluent_wire_format)?;
+        let records = RecordStack::new(recursive_record_names(csr_avro.reader_schema()));
 
         Ok(Decoder {
             csr_avro,
             debug_name,
             buf1: vec![],
             row_buf: Row::default(),
+            records,
         })
     }
 
@@ -98,12 +144,14 @@ impl Decoder {
         // `dsr.deserialize` call returns an error,
         // causing us to return early.
         let mut packer = self.row_buf.packer();
+        self.records.enclosing.clear();
         let (bytes2, resolved_schema, csr_schema_id) = self.csr_avro.resolve(bytes).await?;
         *bytes = bytes2;
         let dec = AvroFlatDecoder {
             packer: &mut packer,
             buf: &mut self.buf1,
             is_top: true,
+            records: &mut self.records,
         };
         let dsr = GeneralDeserializer {
             schema: resolved_schema.top_node(),
@@ -156,6 +204,7 @@ impl<'a> AvroDecode for AvroStringDecoder<'a> {
 pub(super) struct OptionalRecordDecoder<'a, 'row> {
     pub packer: &'a mut RowPacker<'row>,
     pub buf: &'a mut Vec<u8>,
+    pub records: &'a mut RecordStack,
 }
 
 impl<'a, 'row> AvroDecode for OptionalRecordDecoder<'a, 'row> {
@@ -176,6 +225,7 @@ impl<'a, 'row> AvroDecode for OptionalRecordDecoder<'a, 'row> {
                 packer: self.packer,
                 buf: self.buf,
                 is_top: false,
+                records: self.records,
             };
             deserializer.deserialize(reader, d)?;
             Ok(true)
@@ -203,6 +253,7 @@ impl AvroDecode for RowDecoder {
             packer: &mut packer,
             buf: &mut buf_borrow,
             is_top: true,
+            records: &mut RecordStack::default(),
         };
         inner.record(a)?;
         Ok(RowWrapper(row_borrow.clone()))
@@ -227,11 +278,34 @@ impl StatefulAvroDecodable for RowWrapper {
     }
 }
 
+/// Tracks the records that enclose the value being decoded, so that a record
+/// nested within itself is decoded as `jsonb`, as its column type is
+/// determined by `validate_schema_2`.
+#[derive(Debug, Default)]
+pub struct RecordStack {
+    /// The names of the records that are nested within themselves in the
+    /// reader schema.
+    recursive: Vec<FullName>,
+    /// The enclosing records that are named in `recursive`, as indexes into
+    /// `recursive`.
+    enclosing: Vec<usize>,
+}
+
+impl RecordStack {
+    pub fn new(recursive: Vec<FullName>) -> RecordStack {
+        RecordStack {
+            recursive,
+            enclosing: vec![],
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct AvroFlatDecoder<'a, 'row> {
     pub packer: &'a mut RowPacker<'row>,
     pub buf: &'a mut Vec<u8>,
     pub is_top: bool,
+    pub records: &'a mut RecordStack,
 }
 
 impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
@@ -241,19 +315,29 @@ impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
         self,
         a: &mut A,
     ) -> Result<Self::Out, AvroError> {
+        let recursive = a
+            .name()
+            .and_then(|name| self.records.recursive.iter().position(|r| r == name));
+        if let Some(i) = recursive {
+            if self.records.enclosing.contains(&i) {
+                let val = AvroJsonDecoder.record(a)?;
+                JsonbPacker::new(self.packer)
+                    .pack_serde_json(val)
+                    .map_err_to_string()
+                    .map_err(DecodeError::Custom)?;
+                return Ok(());
+            }
+            self.records.enclosing.push(i);
+        }
         let mut str_buf = std::mem::take(self.buf);
+        let records = &mut *self.records;
         let mut pack_record = |rp: &mut RowPacker| -> Result<(), AvroError> {
             let mut expected = 0;
             let mut stash = vec![];
             // The idea here is that if the deserializer gives us fields in the order we're expecting,
             // we can decode them directly into the row.
-            // If not, we need to decode them into a Value (the old, slow decoding path) and stash them,
+            // If not, we need to decode them into separate rows and stash them,
             // so that we can put everything in the right order at the end.
-            //
-            // TODO(btv) - this is pretty bad, as a misordering at the top of the schema graph will
-            // cause the _entire_ chunk under it to be decoded in the slow way!
-            // Maybe instead, we should decode to separate sub-Rows and then add an API
-            // to Row that just copies in the bytes from another one.
             while let Some((_name, idx, f)) = a.next_field()? {
                 if idx == expected {
                     expected += 1;
@@ -261,22 +345,24 @@ impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
                         packer: rp,
                         buf: &mut str_buf,
                         is_top: false,
+                        records,
                     })?;
                 } else {
-                    let val = f.decode_field(ValueDecoder)?;
-                    stash.push((idx, val));
+                    let mut row = Row::default();
+                    f.decode_field(AvroFlatDecoder {
+                        packer: &mut row.packer(),
+                        buf: &mut str_buf,
+                        is_top: false,
+                        records,
+                    })?;
+                    stash.push((idx, row));
                 }
             }
-            stash.sort_by_key(|(idx, _val)| *idx);
-            for (idx, val) in stash {
+            stash.sort_by_key(|(idx, _row)| *idx);
+            for (idx, row) in stash {
                 assert!(idx == expected);
                 expected += 1;
-                let dec = AvroFlatDecoder {
-                    packer: rp,
-                    buf: &mut str_buf,
-                    is_top: false,
-                };
-                give_value(dec, &val)?;
+                rp.extend(row.iter());
             }
             Ok(())
         };
@@ -286,6 +372,9 @@ impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
             self.packer.push_list_with(pack_record)?;
         }
         *self.buf = str_buf;
+        if recursive.is_some() {
+            self.records.enclosing.pop();
+        }
         Ok(())
     }
     #[inline]
@@ -308,6 +397,7 @@ impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
                     packer: self.packer,
                     buf: self.buf,
                     is_top: false,
+                    records: self.records,
                 };
                 if null_variant != Some(i) {
                     if i == idx {
@@ -482,12 +572,14 @@ impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
     fn array<A: AvroArrayAccess>(mut self, a: &mut A) -> Result<Self::Out, AvroError> {
         self.is_top = false;
         let mut str_buf = std::mem::take(self.buf);
+        let records = &mut *self.records;
         self.packer.push_list_with(|rp| -> Result<(), AvroError> {
             loop {
                 let next = AvroFlatDecoder {
                     packer: rp,
                     buf: &mut str_buf,
                     is_top: false,
+                    records,
                 };
                 if a.decode_next(next)?.is_none() {
                     break;
@@ -500,31 +592,185 @@ impl<'a, 'row> AvroDecode for AvroFlatDecoder<'a, 'row> {
     }
     #[inline]
     fn map<A: AvroMapAccess>(self, a: &mut A) -> Result<Self::Out, AvroError> {
-        // Map (key, value) pairs need to be unique and ordered.
-        let mut map = BTreeMap::new();
-        while let Some((name, f)) = a.next_entry()? {
-            map.insert(name, f.decode_field(ValueDecoder)?);
+        // Map (key, value) pairs need to be unique and ordered, so decode the
+        // values into a scratch row in the order in which they arrive, and
+        // then pack them sorted by key.
+        let mut keys = vec![];
+        let mut values = Row::default();
+        let mut packer = values.packer();
+        while let Some((key, f)) = a.next_entry()? {
+            f.decode_field(AvroFlatDecoder {
+                packer: &mut packer,
+                buf: &mut *self.buf,
+                is_top: false,
+                records: &mut *self.records,
+            })?;
+            keys.push(key);
         }
-        self.packer
-            .push_dict_with(|packer| -> Result<(), AvroError> {
-                for (key, val) in map {
-                    packer.push(Datum::String(key.as_str()));
-                    give_value(
-                        AvroFlatDecoder {
-                            packer,
-                            buf: &mut vec![],
-                            is_top: false,
-                        },
-                        &val,
-                    )?;
+        let mut entries: Vec<_> = keys.into_iter().zip(values.iter()).collect();
+        // The sort is stable, so values for a repeated key stay in the order
+        // in which they arrived, and the last one wins.
+        entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+        let mut entries = entries.into_iter().peekable();
+        self.packer.push_dict_with(|packer| {
+            while let Some((key, val)) = entries.next() {
+                if matches!(entries.peek(), Some((next, _)) if *next == key) {
+                    continue;
                 }
-                Ok(())
-            })?;
-
+                packer.push(Datum::String(&key));
+                packer.push(val);
+            }
+        });
         Ok(())
     }
 }
 
+/// Decodes Avro values into JSON.
+///
+/// Used for records that are nested within themselves, whose type cannot be
+/// represented as a (finite) Materialize type.
+struct AvroJsonDecoder;
+
+impl AvroJsonDecoder {
+    fn read_bytes<'b, R: AvroRead>(
+        r: ValueOrReader<'b, &'b [u8], R>,
+    ) -> Result<Vec<u8>, AvroError> {
+        match r {
+            ValueOrReader::Value(val) => Ok(val.to_vec()),
+            ValueOrReader::Reader { len, r } => {
+                let mut buf = vec![0; len];
+                r.read_exact(&mut buf)?;
+                Ok(buf)
+            }
+        }
+    }
+}
+
+impl AvroDecode for AvroJsonDecoder {
+    type Out = serde_json::Value;
+    fn record<R: AvroRead, A: AvroRecordAccess<R>>(
+        self,
+        a: &mut A,
+    ) -> Result<Self::Out, AvroError> {
+        let mut fields = serde_json::Map::new();
+        while let Some((name, _idx, f)) = a.next_field()? {
+            let name = name.to_owned();
+            fields.insert(name, f.decode_field(AvroJsonDecoder)?);
+        }
+        Ok(serde_json::Value::Object(fields))
+    }
+    fn union_branch<'b, R: AvroRead, D: AvroDeserializer>(
+        self,
+        idx: usize,
+        _n_variants: usize,
+        null_variant: Option<usize>,
+        deserializer: D,
+        reader: &'b mut R,
+    ) -> Result<Self::Out, AvroError> {
+        if null_variant == Some(idx) {
+            Ok(serde_json::Value::Null)
+        } else {
+            deserializer.deserialize(reader, AvroJsonDecoder)
+        }
+    }
+    fn array<A: AvroArrayAccess>(self, a: &mut A) -> Result<Self::Out, AvroError> {
+        let mut elements = vec![];
+        while let Some(element) = a.decode_next(AvroJsonDecoder)? {
+            elements.push(element);
+        }
+        Ok(serde_json::Value::Array(elements))
+    }
+    fn map<A: AvroMapAccess>(self, a: &mut A) -> Result<Self::Out, AvroError> {
+        let mut entries = serde_json::Map::new();
+        while let Some((key, f)) = a.next_entry()? {
+            entries.insert(key, f.decode_field(AvroJsonDecoder)?);
+        }
+        Ok(serde_json::Value::Object(entries))
+    }
+    fn enum_variant(self, symbol: &str, _idx: usize) -> Result<Self::Out, AvroError> {
+        Ok(serde_json::Value::String(symbol.to_owned()))
+    }
+    fn scalar(self, scalar: mz_avro::types::Scalar) -> Result<Self::Out, AvroError> {
+        Ok(match scalar {
+            mz_avro::types::Scalar::Null => serde_json::Value::Null,
+            mz_avro::types::Scalar::Boolean(val) => serde_json::Value::Bool(val),
+            mz_avro::types::Scalar::Int(val) => val.into(),
+            mz_avro::types::Scalar::Long(val) => val.into(),
+            mz_avro::types::Scalar::Float(val) => val.into(),
+            mz_avro::types::Scalar::Double(val) => val.into(),
+            mz_avro::types::Scalar::Date(val) => val.to_string().into(),
+            mz_avro::types::Scalar::Timestamp(val) => val.to_string().into(),
+        })
+    }
+    fn decimal<'b, R: AvroRead>(
+        self,
+        _precision: usize,
+        scale: usize,
+        r: ValueOrReader<'b, &'b [u8], R>,
+    ) -> Result<Self::Out, AvroError> {
+        let mut buf = Self::read_bytes(r)?;
+        let scale = u8::try_from(scale).map_err(|_| {
+            DecodeError::Custom(format!(
+                "Error decoding decimal: scale must fit within u8, but got scale {}",
+                scale,
+            ))
+        })?;
+        let n = numeric::twos_complement_be_to_numeric(&mut buf, scale)
+            .map_err_to_string()
+            .map_err(DecodeError::Custom)?;
+        // Decimals are represented as strings, so as not to lose precision.
+        Ok(n.to_standard_notation_string().into())
+    }
+    fn bytes<'b, R: AvroRead>(
+        self,
+        r: ValueOrReader<'b, &'b [u8], R>,
+    ) -> Result<Self::Out, AvroError> {
+        Ok(base64::encode(Self::read_bytes(r)?).into())
+    }
+    fn string<'b, R: AvroRead>(
+        self,
+        r: ValueOrReader<'b, &'b str, R>,
+    ) -> Result<Self::Out, AvroError> {
+        let s = match r {
+            ValueOrReader::Value(val) => val.to_owned(),
+            ValueOrReader::Reader { len, r } => {
+                let mut buf = vec![0; len];
+                r.read_exact(&mut buf)?;
+                String::from_utf8(buf).map_err(|_| DecodeError::StringUtf8Error)?
+            }
+        };
+        Ok(serde_json::Value::String(s))
+    }
+    fn json<'b, R: AvroRead>(
+        self,
+        r: ValueOrReader<'b, &'b serde_json::Value, R>,
+    ) -> Result<Self::Out, AvroError> {
+        match r {
+            ValueOrReader::Value(val) => Ok(val.clone()),
+            ValueOrReader::Reader { len, r } => {
+                let mut buf = vec![0; len];
+                r.read_exact(&mut buf)?;
+                serde_json::from_slice(&buf)
+                    .map_err(|e| AvroError::Decode(DecodeError::BadJson(e.classify())))
+            }
+        }
+    }
+    fn uuid<'b, R: AvroRead>(
+        self,
+        r: ValueOrReader<'b, &'b [u8], R>,
+    ) -> Result<Self::Out, AvroError> {
+        let buf = Self::read_bytes(r)?;
+        let s = String::from_utf8(buf).map_err(|_e| DecodeError::UuidUtf8Error)?;
+        Ok(serde_json::Value::String(s))
+    }
+    fn fixed<'b, R: AvroRead>(
+        self,
+        r: ValueOrReader<'b, &'b [u8], R>,
+    ) -> Result<Self::Out, AvroError> {
+        self.bytes(r)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DiffPair<T> {
     pub before: Option<T>,
@@ -17,7 +17,9 @@ use anyhow::{anyhow, bail, Context};
 use tracing::warn;
 
 use mz_avro::error::Error as AvroError;
-use mz_avro::schema::{resolve_schemas, Schema, SchemaNode, SchemaPiece, SchemaPieceOrNamed};
+use mz_avro::schema::{
+    resolve_schemas, FullName, Schema, SchemaNode, SchemaPiece, SchemaPieceOrNamed,
+};
 use mz_ore::cast::CastFrom;
 use mz_ore::retry::Retry;
 use mz_repr::adt::numeric::{NumericMaxScale, NUMERIC_DATUM_MAX_PRECISION};
@@ -42,26 +44,27 @@ pub fn schema_to_relationdesc(schema: Schema) -> Result<RelationDesc, anyhow::Er
 
 fn validate_schema_1(schema: SchemaNode) -> anyhow::Result<Vec<(ColumnName, ColumnType)>> {
     let mut columns = vec![];
-    let mut seen_avro_nodes = Default::default();
+    let mut seen_records = HashSet::new();
     match schema.inner {
         SchemaPiece::Record { fields, .. } => {
+            seen_records.extend(schema.name);
             for f in fields {
                 columns.extend(get_named_columns(
-                    &mut seen_avro_nodes,
+                    &mut seen_records,
                     schema.step(&f.schema),
                     Some(&f.name),
                 )?);
             }
         }
         _ => {
-            columns.extend(get_named_columns(&mut seen_avro_nodes, schema, None)?);
+            columns.extend(get_named_columns(&mut seen_records, schema, None)?);
         }
     }
     Ok(columns)
 }
 
 fn get_named_columns<'a>(
-    seen_avro_nodes: &mut HashSet<usize>,
+    seen_records: &mut HashSet<&'a FullName>,
     schema: SchemaNode<'a>,
     base_name: Option<&str>,
 ) -> anyhow::Result<Vec<(ColumnName, ColumnType)>> {
@@ -72,18 +75,6 @@ fn get_named_columns<'a>(
             bail!(anyhow!("Empty or null-only unions are not supported"));
         } else {
             for (i, v) in vs.iter().filter(|v| !is_null(v)).enumerate() {
-                let named_idx = match v {
-                    SchemaPieceOrNamed::Named(idx) => Some(*idx),
-                    _ => None,
-                };
-                if let Some(named_idx) = named_idx {
-                    if !seen_avro_nodes.insert(named_idx) {
-                        bail!(
-                            "Recursive types are not supported: {}",
-                            v.get_human_name(schema.root)
-                        );
-                    }
-                }
                 let node = schema.step(v);
                 if let SchemaPiece::Union(_) = node.inner {
                     unreachable!("Internal error: directly nested avro union!");
@@ -117,31 +108,31 @@ fn get_named_columns<'a>(
                 // If there is more than one variant in the union,
                 // the column's output type is nullable, as this
                 // column will be null whenever it is uninhabited.
-                let ty = validate_schema_2(seen_avro_nodes, node)?;
+                let ty = validate_schema_2(seen_records, node)?;
                 columns.push((name.into(), ty.nullable(vs.len() > 1)));
-                if let Some(named_idx) = named_idx {
-                    seen_avro_nodes.remove(&named_idx);
-                }
             }
         }
         Ok(columns)
     } else {
-        let scalar_type = validate_schema_2(seen_avro_nodes, schema)?;
+        let scalar_type = validate_schema_2(seen_records, schema)?;
         Ok(vec![(
             // TODO(benesch): we should do better than this when there's no base
             // name, e.g., invent a name based on the type.
             base_name.unwrap_or("?column?").into(),
-            scalar_type.nullable(false),
+            // A column of type null is always null.
+            scalar_type.nullable(matches!(schema.inner, SchemaPiece::Null)),
         )])
     }
 }
 
-fn validate_schema_2(
-    seen_avro_nodes: &mut HashSet<usize>,
-    schema: SchemaNode,
+fn validate_schema_2<'a>(
+    seen_records: &mut HashSet<&'a FullName>,
+    schema: SchemaNode<'a>,
 ) -> anyhow::Result<ScalarType> {
     Ok(match schema.inner {
-        SchemaPiece::Null => bail!("null outside of union types is not supported"),
+        // There is no type of which null is the only value, so a field that
+        // can only be null is a nullable string.
+        SchemaPiece::Null => ScalarType::String,
         SchemaPiece::Boolean => ScalarType::Bool,
         SchemaPiece::Int => ScalarType::Int32,
         SchemaPiece::Long => ScalarType::Int64,
@@ -169,27 +160,22 @@ fn validate_schema_2(
         SchemaPiece::Json => ScalarType::Jsonb,
         SchemaPiece::Uuid => ScalarType::Uuid,
         SchemaPiece::Record { fields, .. } => {
+            // A record that is nested within itself would have an infinitely
+            // deep type, so the nested occurrence is decoded as `jsonb`.
+            // See `recursive_record_names`.
+            if let Some(name) = schema.name {
+                if !seen_records.insert(name) {
+                    return Ok(ScalarType::Jsonb);
+                }
+            }
             let mut columns = vec![];
             for f in fields {
-                let named_idx = match &f.schema {
-                    SchemaPieceOrNamed::Named(idx) => Some(*idx),
-                    _ => None,
-                };
-                if let Some(named_idx) = named_idx {
-                    if !seen_avro_nodes.insert(named_idx) {
-                        bail!(
-                            "Recursive types are not supported: {}",
-                            f.schema.get_human_name(schema.root)
-                        );
-                    }
-                }
                 let next_node = schema.step(&f.schema);
-                columns.extend(
-                    get_named_columns(seen_avro_nodes, next_node, Some(&f.name))?.into_iter(),
-                );
-                if let Some(named_idx) = named_idx {
-                    seen_avro_nodes.remove(&named_idx);
-                }
+                columns
+                    .extend(get_named_columns(seen_records, next_node, Some(&f.name))?.into_iter());
+            }
+            if let Some(name) = schema.name {
+                seen_records.remove(name);
             }
             ScalarType::Record {
                 fields: columns,
@@ -197,31 +183,18 @@ fn validate_schema_2(
                 custom_name: None,
             }
         }
-        SchemaPiece::Array(inner) => {
-            let named_idx = match inner.as_ref() {
-                SchemaPieceOrNamed::Named(idx) => Some(*idx),
-                _ => None,
-            };
-            if let Some(named_idx) = named_idx {
-                if !seen_avro_nodes.insert(named_idx) {
-                    bail!(
-                        "Recursive types are not supported: {}",
-                        inner.get_human_name(schema.root)
-                    );
-                }
-            }
-            let next_node = schema.step(inner);
-            let ret = ScalarType::List {
-                element_type: Box::new(validate_schema_2(seen_avro_nodes, next_node)?),
-                custom_oid: None,
-            };
-            if let Some(named_idx) = named_idx {
-                seen_avro_nodes.remove(&named_idx);
-            }
-            ret
-        }
+        SchemaPiece::Array(inner) => ScalarType::List {
+            element_type: Box::new(validate_collection_element(
+                seen_records,
+                schema.step(inner),
+            )?),
+            custom_oid: None,
+        },
         SchemaPiece::Map(inner) => ScalarType::Map {
-            value_type: Box::new(validate_schema_2(seen_avro_nodes, schema.step(inner))?),
+            value_type: Box::new(validate_collection_element(
+                seen_records,
+                schema.step(inner),
+            )?),
             custom_oid: None,
         },
 
@@ -229,6 +202,71 @@ fn validate_schema_2(
     })
 }
 
+/// Like `validate_schema_2`, but for the elements of arrays and the values of
+/// maps, which may additionally be a union of null and one other type.
+fn validate_collection_element<'a>(
+    seen_records: &mut HashSet<&'a FullName>,
+    schema: SchemaNode<'a>,
+) -> anyhow::Result<ScalarType> {
+    if let SchemaPiece::Union(us) = schema.inner {
+        let vs = us.variants();
+        if vs.len() == 2 && vs.iter().any(is_null) {
+            let v = vs
+                .iter()
+                .find(|v| !is_null(v))
+                .expect("union has a non-null variant");
+            return validate_schema_2(seen_records, schema.step(v));
+        }
+    }
+    validate_schema_2(seen_records, schema)
+}
+
+/// Returns the names of the records in `schema` that are nested within
+/// themselves.
+///
+/// When a record is nested within itself, the nested occurrence, and everything
+/// within it, is decoded as `jsonb`.
+pub(super) fn recursive_record_names(schema: &Schema) -> Vec<FullName> {
+    fn visit<'a>(
+        node: SchemaNode<'a>,
+        enclosing: &mut Vec<&'a FullName>,
+        recursive: &mut Vec<FullName>,
+    ) {
+        match node.inner {
+            SchemaPiece::Record { fields, .. } => {
+                if let Some(name) = node.name {
+                    if enclosing.contains(&name) {
+                        if !recursive.contains(name) {
+                            recursive.push(name.clone());
+                        }
+                        return;
+                    }
+                    enclosing.push(name);
+                }
+                for f in fields {
+                    visit(node.step(&f.schema), enclosing, recursive);
+                }
+                if node.name.is_some() {
+                    enclosing.pop();
+                }
+            }
+            SchemaPiece::Union(us) => {
+                for v in us.variants() {
+                    visit(node.step(v), enclosing, recursive);
+                }
+            }
+            SchemaPiece::Array(inner) | SchemaPiece::Map(inner) => {
+                visit(node.step(inner), enclosing, recursive)
+            }
+            _ => (),
+        }
+    }
+
+    let mut recursive = vec![];
+    visit(schema.top_node(), &mut vec![], &mut recursive);
+    recursive
+}
+
 pub struct ConfluentAvroResolver {
     reader_schema: Schema,
     writer_schemas: Option<SchemaCache>,
@@ -250,6 +288,11 @@ impl ConfluentAvroResolver {
         })
     }
 
+    /// Returns the schema that decoded values are expected to conform to.
+    pub fn reader_schema(&self) -> &Schema {
+        &self.reader_schema
+    }
+
     pub async fn resolve<'a, 'b>(
         &'a mut self,
         mut bytes: &'b [u8],
@@ -0,0 +1,255 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Utilities shared by the columnar file formats, Parquet and ORC.
+//!
+//! Files in both formats are split into chunks (Parquet row groups, ORC
+//! stripes) that can be decoded independently of one another, given the
+//! file's footer. A [`FileLayout`] describes where those chunks live, so that
+//! a [`PartialFile`] holding just the bytes needed to decode some of them can
+//! be handed to the worker that decodes them.
+
+use std::io::{self, Read, Seek, SeekFrom};
+use std::ops::Range;
+
+use serde::{Deserialize, Serialize};
+
+use mz_ore::cast::CastFrom;
+
+/// The location of the chunks of a columnar file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileLayout {
+    /// The range at the end of the file that holds its footer, including
+    /// any bytes the reader reads speculatively when looking for it.
+    pub tail: Range<u64>,
+    pub chunks: Vec<FileChunk>,
+}
+
+/// A part of a columnar file that can be decoded independently of the rest
+/// of the file.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FileChunk {
+    pub num_rows: usize,
+    /// The byte ranges of the file that hold the chunk's data.
+    pub byte_ranges: Vec<Range<u64>>,
+}
+
+impl FileLayout {
+    /// Retains the bytes of `file` needed to decode the given chunks.
+    pub fn slice(&self, file: &[u8], chunks: &[usize]) -> PartialFile {
+        let ranges = chunks
+            .iter()
+            .flat_map(|i| self.chunks[*i].byte_ranges.iter().cloned())
+            .chain([self.tail.clone()]);
+        PartialFile::new(file, ranges)
+    }
+}
+
+/// Some byte ranges of a file.
+///
+/// Reading a byte outside of the retained ranges fails with an error of kind
+/// [`io::ErrorKind::InvalidData`].
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PartialFile {
+    len: u64,
+    /// The retained ranges, as their offset and contents. The ranges are
+    /// sorted by offset and do not overlap.
+    slices: Vec<(u64, Vec<u8>)>,
+    #[serde(skip)]
+    position: u64,
+    /// The lowest offset at which a read failed because the byte was not
+    /// retained.
+    #[serde(skip)]
+    first_missing: Option<u64>,
+}
+
+impl PartialFile {
+    /// Retains the given `ranges` of `file`.
+    ///
+    /// Ranges are clamped to the length of the file and may overlap.
+    pub fn new<I>(file: &[u8], ranges: I) -> PartialFile
+    where
+        I: IntoIterator<Item = Range<u64>>,
+    {
+        let len = u64::cast_from(file.len());
+        let mut ranges: Vec<_> = ranges
+            .into_iter()
+            .map(|r| r.start.min(len)..r.end.min(len))
+            .filter(|r| r.start < r.end)
+            .collect();
+        ranges.sort_by_key(|r| r.start);
+        let mut merged: Vec<Range<u64>> = vec![];
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        let slices = merged
+            .into_iter()
+            .map(|r| {
+                let bytes = file[usize::cast_from(r.start)..usize::cast_from(r.end)].to_vec();
+                (r.start, bytes)
+            })
+            .collect();
+        PartialFile {
+            len,
+            slices,
+            position: 0,
+            first_missing: None,
+        }
+    }
+
+    /// Retains all of `file`.
+    pub fn whole(file: Vec<u8>) -> PartialFile {
+        PartialFile {
+            len: u64::cast_from(file.len()),
+            slices: vec![(0, file)],
+            position: 0,
+            first_missing: None,
+        }
+    }
+
+    /// Retains `tail`, the last bytes of a file that is `len` bytes long.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tail` is longer than `len`.
+    pub fn tail(len: u64, tail: Vec<u8>) -> PartialFile {
+        let start = len
+            .checked_sub(u64::cast_from(tail.len()))
+            .expect("tail must not be longer than the file");
+        PartialFile {
+            len,
+            slices: vec![(start, tail)],
+            position: 0,
+            first_missing: None,
+        }
+    }
+
+    /// Additionally retains `head`, the bytes that immediately precede the
+    /// first retained range, and forgets about any failed reads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `head` is longer than the offset of the first retained range.
+    pub fn retain_head(&mut self, head: Vec<u8>) {
+        let end = self.slices.first().map_or(self.len, |(start, _)| *start);
+        let start = end
+            .checked_sub(u64::cast_from(head.len()))
+            .expect("head must not extend past the start of the file");
+        self.slices.insert(0, (start, head));
+        self.first_missing = None;
+    }
+
+    /// Returns the number of bytes retained.
+    pub fn retained_len(&self) -> usize {
+        self.slices.iter().map(|(_, bytes)| bytes.len()).sum()
+    }
+
+    /// Returns the lowest offset that has failed to be read because it was
+    /// not retained, if any.
+    ///
+    /// Retaining the file from that offset on allows the reads to be retried.
+    pub fn first_missing(&self) -> Option<u64> {
+        self.first_missing
+    }
+}
+
+impl Read for PartialFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position >= self.len || buf.is_empty() {
+            return Ok(0);
+        }
+        // The last slice that starts at or before the current position is
+        // the only one that can contain it.
+        let i = self
+            .slices
+            .partition_point(|(start, _)| *start <= self.position);
+        let slice = i.checked_sub(1).map(|i| &self.slices[i]);
+        let (bytes, offset) = match slice {
+            Some((start, bytes)) if self.position - start < u64::cast_from(bytes.len()) => {
+                (bytes, usize::cast_from(self.position - start))
+            }
+            _ => {
+                self.first_missing = Some(match self.first_missing {
+                    Some(offset) => offset.min(self.position),
+                    None => self.position,
+                });
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("byte {} of the file was not retained", self.position),
+                ));
+            }
+        };
+        let n = buf.len().min(bytes.len() - offset);
+        buf[..n].copy_from_slice(&bytes[offset..offset + n]);
+        self.position += u64::cast_from(n);
+        Ok(n)
+    }
+}
+
+impl Seek for PartialFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(n) => i128::from(n),
+            SeekFrom::End(n) => i128::from(self.len) + i128::from(n),
+            SeekFrom::Current(n) => i128::from(self.position) + i128::from(n),
+        };
+        self.position = u64::try_from(position).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn partial_file() {
+        let file: Vec<u8> = (0..10).collect();
+        let mut partial = PartialFile::new(&file, [6..8, 1..3, 2..4, 9..20]);
+        assert_eq!(partial.retained_len(), 6);
+
+        let mut buf = [0; 4];
+        partial.seek(SeekFrom::Start(1)).unwrap();
+        partial.read_exact(&mut buf[..3]).unwrap();
+        assert_eq!(&buf[..3], &[1, 2, 3]);
+        // Reads stop at the end of a retained range...
+        partial.seek(SeekFrom::End(-4)).unwrap();
+        assert_eq!(partial.read(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], &[6, 7]);
+        // ...and fail outside of them.
+        let err = partial.read(&mut buf).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(partial.first_missing(), Some(8));
+        partial.seek(SeekFrom::Current(1)).unwrap();
+        assert_eq!(partial.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 9);
+        assert_eq!(partial.read(&mut buf).unwrap(), 0);
+
+        let mut tail = PartialFile::tail(10, file[7..].to_vec());
+        tail.seek(SeekFrom::End(-3)).unwrap();
+        tail.read_exact(&mut buf[..3]).unwrap();
+        assert_eq!(&buf[..3], &[7, 8, 9]);
+        tail.seek(SeekFrom::Start(2)).unwrap();
+        assert!(tail.read(&mut buf).is_err());
+        assert_eq!(tail.first_missing(), Some(2));
+        tail.retain_head(file[2..7].to_vec());
+        assert_eq!(tail.first_missing(), None);
+        tail.seek(SeekFrom::Start(2)).unwrap();
+        assert_eq!(tail.read(&mut buf).unwrap(), 4);
+        assert_eq!(&buf, &[2, 3, 4, 5]);
+    }
+}
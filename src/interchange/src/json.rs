@@ -10,11 +10,13 @@
 use std::collections::HashSet;
 use std::fmt;
 
+use anyhow::{anyhow, bail, Context};
 use mz_ore::collections::CollectionExt;
 use mz_repr::adt::char;
-use mz_repr::adt::jsonb::JsonbRef;
+use mz_repr::adt::jsonb::{JsonbPacker, JsonbRef};
 use mz_repr::adt::numeric::{NUMERIC_AGG_MAX_PRECISION, NUMERIC_DATUM_MAX_PRECISION};
-use mz_repr::{ColumnName, ColumnType, Datum, RelationDesc, ScalarType};
+use mz_repr::{ColumnName, ColumnType, Datum, RelationDesc, Row, ScalarType};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Map};
 
 use crate::encode::{column_names_and_types, Encode, TypedDatum};
@@ -398,3 +400,298 @@ pub fn build_row_schema_json(
         "name": name
     })
 }
+
+/// The typed columns of a JSON source, in the order they appear in the
+/// source's relation.
+///
+/// Each column is extracted from the top-level field of the same name in
+/// every message.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonSourceSchema {
+    pub fields: Vec<JsonSourceField>,
+}
+
+/// A column of a JSON source.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct JsonSourceField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: JsonSourceType,
+    pub nullable: bool,
+}
+
+/// The types that JSON source columns can be decoded as.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JsonSourceType {
+    Boolean,
+    Int64,
+    Float64,
+    Text,
+    /// Any JSON value, including objects and arrays.
+    Jsonb,
+}
+
+impl JsonSourceType {
+    fn of(value: &serde_json::Value) -> Option<JsonSourceType> {
+        match value {
+            serde_json::Value::Null => None,
+            serde_json::Value::Bool(_) => Some(JsonSourceType::Boolean),
+            serde_json::Value::Number(n) if n.is_i64() => Some(JsonSourceType::Int64),
+            serde_json::Value::Number(_) => Some(JsonSourceType::Float64),
+            serde_json::Value::String(_) => Some(JsonSourceType::Text),
+            serde_json::Value::Array(_) | serde_json::Value::Object(_) => {
+                Some(JsonSourceType::Jsonb)
+            }
+        }
+    }
+
+    /// Returns the narrowest type that can hold values of both types.
+    fn union(self, other: JsonSourceType) -> JsonSourceType {
+        use JsonSourceType::*;
+        match (self, other) {
+            (a, b) if a == b => a,
+            (Int64, Float64) | (Float64, Int64) => Float64,
+            _ => Jsonb,
+        }
+    }
+
+    fn scalar_type(&self) -> ScalarType {
+        match self {
+            JsonSourceType::Boolean => ScalarType::Bool,
+            JsonSourceType::Int64 => ScalarType::Int64,
+            JsonSourceType::Float64 => ScalarType::Float64,
+            JsonSourceType::Text => ScalarType::String,
+            JsonSourceType::Jsonb => ScalarType::Jsonb,
+        }
+    }
+}
+
+impl JsonSourceSchema {
+    /// Infers a schema from sample messages, each of which must be a JSON
+    /// object.
+    ///
+    /// A column is created for every field that appears in any sample. Fields
+    /// whose values have conflicting types are decoded as `jsonb`, and fields
+    /// that are absent or `null` in any sample are nullable.
+    pub fn infer<I, B>(samples: I) -> Result<JsonSourceSchema, anyhow::Error>
+    where
+        I: IntoIterator<Item = B>,
+        B: AsRef<[u8]>,
+    {
+        let mut fields: Vec<(String, Option<JsonSourceType>, bool)> = vec![];
+        let mut n_samples = 0;
+        for sample in samples {
+            let value: serde_json::Value =
+                serde_json::from_slice(sample.as_ref()).context("parsing sample message")?;
+            let object = match value {
+                serde_json::Value::Object(object) => object,
+                _ => bail!("cannot infer a schema from JSON messages that are not objects"),
+            };
+            for (name, _, nullable) in &mut fields {
+                if !object.contains_key(name.as_str()) {
+                    *nullable = true;
+                }
+            }
+            for (name, value) in object {
+                let typ = JsonSourceType::of(&value);
+                match fields.iter_mut().find(|(n, _, _)| *n == name) {
+                    Some((_, prev, nullable)) => {
+                        *nullable |= typ.is_none();
+                        *prev = match (*prev, typ) {
+                            (Some(a), Some(b)) => Some(a.union(b)),
+                            (a, b) => a.or(b),
+                        };
+                    }
+                    // Fields that first appear after the first sample were
+                    // absent from an earlier one.
+                    None => fields.push((name, typ, typ.is_none() || n_samples > 0)),
+                }
+            }
+            n_samples += 1;
+        }
+        if n_samples == 0 {
+            bail!("cannot infer a schema without any sample messages");
+        }
+        let fields = fields
+            .into_iter()
+            .map(|(name, typ, nullable)| JsonSourceField {
+                name,
+                // Fields that were always `null` carry no type information.
+                typ: typ.unwrap_or(JsonSourceType::Jsonb),
+                nullable,
+            })
+            .collect();
+        Ok(JsonSourceSchema { fields })
+    }
+
+    /// Parses a schema previously produced by [`JsonSourceSchema::to_json`].
+    pub fn from_json(s: &str) -> Result<JsonSourceSchema, anyhow::Error> {
+        let schema: JsonSourceSchema = serde_json::from_str(s).context("parsing json schema")?;
+        let mut names = HashSet::new();
+        for field in &schema.fields {
+            if !names.insert(&field.name) {
+                bail!("json schema contains duplicate field {}", field.name);
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Serializes the schema so that it can be recorded in a `CREATE SOURCE`
+    /// statement.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("json schemas serialize")
+    }
+
+    pub fn desc(&self) -> RelationDesc {
+        self.fields
+            .iter()
+            .fold(RelationDesc::empty(), |desc, field| {
+                desc.with_column(
+                    &field.name,
+                    field.typ.scalar_type().nullable(field.nullable),
+                )
+            })
+    }
+}
+
+/// Decodes JSON messages into rows.
+#[derive(Debug)]
+pub struct JsonDecoder {
+    /// The columns to extract from each message. If absent, each message is
+    /// decoded into a single `jsonb` column.
+    schema: Option<JsonSourceSchema>,
+    row: Row,
+}
+
+impl JsonDecoder {
+    pub fn new(schema: Option<JsonSourceSchema>) -> JsonDecoder {
+        JsonDecoder {
+            schema,
+            row: Row::default(),
+        }
+    }
+
+    /// Decodes a single JSON message.
+    pub fn decode(&mut self, bytes: &[u8]) -> Result<Row, anyhow::Error> {
+        let schema = match &self.schema {
+            None => {
+                JsonbPacker::new(&mut self.row.packer()).pack_slice(bytes)?;
+                return Ok(self.row.clone());
+            }
+            Some(schema) => schema,
+        };
+        let mut object = match serde_json::from_slice(bytes)? {
+            serde_json::Value::Object(object) => object,
+            other => bail!("expected a JSON object, but got {}", other),
+        };
+        let mut packer = self.row.packer();
+        for field in &schema.fields {
+            let value = match object.remove(&field.name) {
+                None | Some(serde_json::Value::Null) if field.nullable => {
+                    packer.push(Datum::Null);
+                    continue;
+                }
+                None | Some(serde_json::Value::Null) => {
+                    bail!("missing value for non-nullable field {}", field.name)
+                }
+                Some(value) => value,
+            };
+            let mismatch = || {
+                anyhow!(
+                    "field {} has value {}, which is not of type {:?}",
+                    field.name,
+                    value,
+                    field.typ
+                )
+            };
+            match field.typ {
+                JsonSourceType::Boolean => {
+                    packer.push(Datum::from(value.as_bool().ok_or_else(mismatch)?))
+                }
+                JsonSourceType::Int64 => {
+                    packer.push(Datum::Int64(value.as_i64().ok_or_else(mismatch)?))
+                }
+                JsonSourceType::Float64 => {
+                    packer.push(Datum::from(value.as_f64().ok_or_else(mismatch)?))
+                }
+                JsonSourceType::Text => {
+                    packer.push(Datum::String(value.as_str().ok_or_else(mismatch)?))
+                }
+                JsonSourceType::Jsonb => JsonbPacker::new(&mut packer).pack_serde_json(value)?,
+            }
+        }
+        Ok(self.row.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_repr::adt::jsonb::Jsonb;
+
+    use super::*;
+
+    #[test]
+    fn infer_schema() -> Result<(), anyhow::Error> {
+        let schema = JsonSourceSchema::infer([
+            r#"{"a": 1, "b": "x", "c": 1, "d": null}"#,
+            r#"{"a": 2, "b": "y", "c": 1.5, "e": [1]}"#,
+            r#"{"a": 3, "b": true, "c": 2, "d": {"f": 1}}"#,
+        ])?;
+        let field = |name: &str, typ, nullable| JsonSourceField {
+            name: name.into(),
+            typ,
+            nullable,
+        };
+        let mut fields = schema.fields.clone();
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+        assert_eq!(
+            fields,
+            vec![
+                field("a", JsonSourceType::Int64, false),
+                field("b", JsonSourceType::Jsonb, false),
+                field("c", JsonSourceType::Float64, false),
+                field("d", JsonSourceType::Jsonb, true),
+                field("e", JsonSourceType::Jsonb, true),
+            ]
+        );
+        assert_eq!(JsonSourceSchema::from_json(&schema.to_json())?, schema);
+
+        assert!(JsonSourceSchema::infer(["[1, 2]"]).is_err());
+        assert!(JsonSourceSchema::infer(Vec::<&str>::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn decode() -> Result<(), anyhow::Error> {
+        let mut decoder = JsonDecoder::new(None);
+        let row = decoder.decode(br#"{"a": [1, 2]}"#)?;
+        let expected: Jsonb = r#"{"a": [1, 2]}"#.parse()?;
+        assert_eq!(row, expected.into_row());
+        assert!(decoder.decode(b"{").is_err());
+
+        let schema = JsonSourceSchema {
+            fields: vec![
+                JsonSourceField {
+                    name: "a".into(),
+                    typ: JsonSourceType::Int64,
+                    nullable: false,
+                },
+                JsonSourceField {
+                    name: "b".into(),
+                    typ: JsonSourceType::Text,
+                    nullable: true,
+                },
+            ],
+        };
+        let mut decoder = JsonDecoder::new(Some(schema));
+        let row = decoder.decode(br#"{"a": 1, "b": "x", "c": false}"#)?;
+        assert_eq!(row, Row::pack_slice(&[Datum::Int64(1), Datum::String("x")]));
+        let row = decoder.decode(br#"{"a": 2}"#)?;
+        assert_eq!(row, Row::pack_slice(&[Datum::Int64(2), Datum::Null]));
+        assert!(decoder.decode(br#"{"b": "x"}"#).is_err());
+        assert!(decoder.decode(br#"{"a": "1"}"#).is_err());
+        assert!(decoder.decode(br#"[1]"#).is_err());
+        Ok(())
+    }
+}
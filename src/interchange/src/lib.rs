@@ -12,8 +12,11 @@
 #![warn(missing_debug_implementations)]
 
 pub mod avro;
+pub mod columnar;
 mod confluent;
 pub mod encode;
 pub mod envelopes;
 pub mod json;
+pub mod orc;
+pub mod parquet;
 pub mod protobuf;
@@ -0,0 +1,777 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Decoding of Apache ORC files.
+//!
+//! As for Parquet (see [`crate::parquet`]), the relation schema of an ORC
+//! source is inferred from the footer of a sample file when the source is
+//! created and recorded as an [`OrcSchema`]. Every file the source later
+//! reads is decoded against that schema, matching up columns by name.
+//!
+//! Only files whose root type is a struct of primitive columns are
+//! supported. Integer and string columns must use version 2 of ORC's
+//! encodings, which writers have used by default since ORC 0.12.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+
+use anyhow::{anyhow, bail, Context};
+use chrono::NaiveDate;
+use orc_format::proto::column_encoding::Kind as EncodingKind;
+use orc_format::proto::r#type::Kind as TypeKind;
+use orc_format::proto::stream::Kind as StreamKind;
+use orc_format::proto::{StripeFooter, Type};
+use orc_format::read::decode::{SignedRleV2Iter, UnsignedRleV2Iter};
+use orc_format::read::{read_metadata, read_stripe_column, read_stripe_footer, Column};
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+
+use mz_ore::cast::CastFrom;
+use mz_repr::{Datum, RelationDesc, Row, ScalarType};
+
+use crate::columnar::{FileChunk, FileLayout};
+
+/// The columns of an ORC source, in the order they appear in the source's
+/// relation.
+///
+/// ORC does not record whether a column may contain nulls, so all columns
+/// are nullable.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OrcSchema {
+    pub fields: Vec<OrcField>,
+}
+
+/// A column of an ORC source.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct OrcField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: OrcType,
+}
+
+/// The ORC column types that can be decoded.
+///
+/// `char` and `varchar` columns are decoded as `string` columns.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrcType {
+    Boolean,
+    Byte,
+    Short,
+    Int,
+    Long,
+    Float,
+    Double,
+    String,
+    Binary,
+    Date,
+}
+
+impl OrcType {
+    fn from_kind(kind: TypeKind) -> Result<OrcType, anyhow::Error> {
+        Ok(match kind {
+            TypeKind::Boolean => OrcType::Boolean,
+            TypeKind::Byte => OrcType::Byte,
+            TypeKind::Short => OrcType::Short,
+            TypeKind::Int => OrcType::Int,
+            TypeKind::Long => OrcType::Long,
+            TypeKind::Float => OrcType::Float,
+            TypeKind::Double => OrcType::Double,
+            TypeKind::String | TypeKind::Varchar | TypeKind::Char => OrcType::String,
+            TypeKind::Binary => OrcType::Binary,
+            TypeKind::Date => OrcType::Date,
+            other => bail!("orc columns of type {:?} are not supported", other),
+        })
+    }
+
+    fn scalar_type(&self) -> ScalarType {
+        match self {
+            OrcType::Boolean => ScalarType::Bool,
+            OrcType::Byte | OrcType::Short => ScalarType::Int16,
+            OrcType::Int => ScalarType::Int32,
+            OrcType::Long => ScalarType::Int64,
+            OrcType::Float => ScalarType::Float32,
+            OrcType::Double => ScalarType::Float64,
+            OrcType::String => ScalarType::String,
+            OrcType::Binary => ScalarType::Bytes,
+            OrcType::Date => ScalarType::Date,
+        }
+    }
+}
+
+/// Converts an error from the ORC reader, which does not implement
+/// `std::error::Error`.
+fn orc_error(e: orc_format::error::Error) -> anyhow::Error {
+    anyhow!("{:?}", e)
+}
+
+/// Returns the top-level columns of an ORC file with the given types, as
+/// their name, column ID, and type.
+fn file_columns(types: &[Type]) -> Result<Vec<(&str, u32, TypeKind)>, anyhow::Error> {
+    let root = types
+        .first()
+        .ok_or_else(|| anyhow!("orc file has no types"))?;
+    if root.kind() != TypeKind::Struct {
+        bail!("orc files whose root type is not a struct are not supported");
+    }
+    root.field_names
+        .iter()
+        .zip(&root.subtypes)
+        .map(|(name, id)| {
+            let typ = types
+                .get(usize::cast_from(*id))
+                .ok_or_else(|| anyhow!("orc file is missing type {}", id))?;
+            Ok((name.as_str(), *id, typ.kind()))
+        })
+        .collect()
+}
+
+impl OrcSchema {
+    /// Infers the schema of an ORC source from the footer of one of its
+    /// files.
+    pub fn infer<R: Read + Seek>(reader: &mut R) -> Result<OrcSchema, anyhow::Error> {
+        let metadata = read_metadata(reader)
+            .map_err(orc_error)
+            .context("reading orc file metadata")?;
+        let fields = file_columns(&metadata.footer.types)?
+            .into_iter()
+            .map(|(name, _, kind)| {
+                let typ = OrcType::from_kind(kind).with_context(|| format!("column {}", name))?;
+                Ok(OrcField {
+                    name: name.into(),
+                    typ,
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        Ok(OrcSchema { fields })
+    }
+
+    /// Parses a schema previously produced by [`OrcSchema::to_json`].
+    pub fn from_json(s: &str) -> Result<OrcSchema, anyhow::Error> {
+        serde_json::from_str(s).context("parsing orc schema")
+    }
+
+    /// Serializes the schema so that it can be recorded in a `CREATE SOURCE`
+    /// statement.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("orc schemas serialize")
+    }
+
+    /// Returns the relation described by the schema.
+    pub fn desc(&self) -> RelationDesc {
+        self.fields
+            .iter()
+            .fold(RelationDesc::empty(), |desc, field| {
+                desc.with_column(&field.name, field.typ.scalar_type().nullable(true))
+            })
+    }
+}
+
+/// The number of bytes at the end of an ORC file that the reader reads at
+/// once when looking for the file's metadata.
+pub const FOOTER_READ_SIZE: u64 = 16 * 1024;
+
+/// Returns the layout of an ORC file, whose chunks are its stripes.
+pub fn layout(file: &[u8]) -> Result<FileLayout, anyhow::Error> {
+    let metadata = read_metadata(&mut Cursor::new(file))
+        .map_err(orc_error)
+        .context("reading orc file metadata")?;
+    // The file ends with the metadata, the footer, the postscript, and the
+    // length of the postscript as a single byte.
+    let len = u64::cast_from(file.len());
+    let postscript_len = u64::from(file[file.len() - 1]);
+    let tail_len = metadata.postscript.metadata_length()
+        + metadata.postscript.footer_length()
+        + postscript_len
+        + 1;
+    let tail = len.saturating_sub(tail_len.max(FOOTER_READ_SIZE))..len;
+    let chunks = metadata
+        .footer
+        .stripes
+        .iter()
+        .map(|stripe| {
+            let start = stripe.offset();
+            let end = start + stripe.index_length() + stripe.data_length() + stripe.footer_length();
+            Ok(FileChunk {
+                num_rows: usize::try_from(stripe.number_of_rows())?,
+                byte_ranges: vec![start..end],
+            })
+        })
+        .collect::<Result<_, anyhow::Error>>()?;
+    Ok(FileLayout { tail, chunks })
+}
+
+/// Decodes ORC files into rows of an [`OrcSchema`].
+#[derive(Debug)]
+pub struct Decoder {
+    schema: OrcSchema,
+    /// Whether each column of the schema needs to be read. Columns that are
+    /// not demanded are not read from the file and decode as `NULL`.
+    demanded: Vec<bool>,
+    row: Row,
+}
+
+impl Decoder {
+    /// Creates a decoder that only reads the `demanded` columns of `schema`.
+    pub fn new(schema: OrcSchema, demanded: Vec<bool>) -> Decoder {
+        assert_eq!(schema.fields.len(), demanded.len());
+        Decoder {
+            schema,
+            demanded,
+            row: Row::default(),
+        }
+    }
+
+    /// Decodes the rows of the given stripes of an ORC file, in order,
+    /// passing each to `emit`.
+    ///
+    /// Fails without emitting any rows if the file does not contain every
+    /// demanded column with its expected type.
+    pub fn decode<R, F>(
+        &mut self,
+        mut reader: R,
+        stripes: &[usize],
+        mut emit: F,
+    ) -> Result<(), anyhow::Error>
+    where
+        R: Read + Seek,
+        F: FnMut(Row),
+    {
+        let metadata = read_metadata(&mut reader)
+            .map_err(orc_error)
+            .context("reading orc file metadata")?;
+        let file_columns: HashMap<_, _> = file_columns(&metadata.footer.types)?
+            .into_iter()
+            .map(|(name, id, kind)| (name, (id, kind)))
+            .collect();
+
+        // Map each demanded column to its column ID in the file.
+        let mut column_ids = vec![];
+        for (field, demanded) in self.schema.fields.iter().zip(&self.demanded) {
+            if !*demanded {
+                column_ids.push(None);
+                continue;
+            }
+            let (id, kind) = file_columns
+                .get(field.name.as_str())
+                .ok_or_else(|| anyhow!("orc file is missing column {}", field.name))?;
+            let file_type =
+                OrcType::from_kind(*kind).with_context(|| format!("column {}", field.name))?;
+            if file_type != field.typ {
+                bail!(
+                    "orc file column {} has type {:?}, but the source expects {:?}",
+                    field.name,
+                    file_type,
+                    field.typ
+                );
+            }
+            column_ids.push(Some(*id));
+        }
+
+        let mut scratch = vec![];
+        for stripe in stripes {
+            let footer = read_stripe_footer(&mut reader, &metadata, *stripe, &mut scratch)
+                .map_err(orc_error)
+                .context("reading orc stripe footer")?;
+            let num_rows = usize::try_from(metadata.footer.stripes[*stripe].number_of_rows())?;
+            let mut columns = vec![];
+            for (field, id) in self.schema.fields.iter().zip(&column_ids) {
+                let id = match id {
+                    Some(id) => *id,
+                    None => {
+                        columns.push(None);
+                        continue;
+                    }
+                };
+                let has_nulls = has_stream(&footer, id, StreamKind::Present);
+                let column =
+                    read_stripe_column(&mut reader, &metadata, *stripe, footer.clone(), id, vec![])
+                        .map_err(orc_error)
+                        .with_context(|| format!("reading orc column {}", field.name))?;
+                let values = decode_column(&column, field, has_nulls)
+                    .with_context(|| format!("decoding orc column {}", field.name))?;
+                if values.len() != num_rows {
+                    bail!(
+                        "orc column {} has {} values, but its stripe has {} rows",
+                        field.name,
+                        values.len(),
+                        num_rows
+                    );
+                }
+                columns.push(Some(values));
+            }
+
+            for row_idx in 0..num_rows {
+                let mut packer = self.row.packer();
+                for values in &columns {
+                    match values {
+                        None => packer.push(Datum::Null),
+                        Some(values) => packer.push(values.datum(row_idx)),
+                    }
+                }
+                emit(self.row.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reports whether a stripe contains a stream of the given kind for a
+/// column.
+fn has_stream(footer: &StripeFooter, column: u32, kind: StreamKind) -> bool {
+    footer
+        .streams
+        .iter()
+        .any(|stream| stream.column() == column && stream.kind() == kind)
+}
+
+/// The decoded values of a column of a stripe.
+enum ColumnValues {
+    /// Values of types whose datums do not borrow.
+    Scalars(Vec<Datum<'static>>),
+    Strings(Vec<Option<String>>),
+    Bytes(Vec<Option<Vec<u8>>>),
+}
+
+impl ColumnValues {
+    fn len(&self) -> usize {
+        match self {
+            ColumnValues::Scalars(values) => values.len(),
+            ColumnValues::Strings(values) => values.len(),
+            ColumnValues::Bytes(values) => values.len(),
+        }
+    }
+
+    fn datum(&self, i: usize) -> Datum<'_> {
+        match self {
+            ColumnValues::Scalars(values) => values[i],
+            ColumnValues::Strings(values) => values[i].as_deref().into(),
+            ColumnValues::Bytes(values) => values[i].as_deref().into(),
+        }
+    }
+}
+
+fn decode_column(
+    column: &Column,
+    field: &OrcField,
+    has_nulls: bool,
+) -> Result<ColumnValues, anyhow::Error> {
+    let num_rows = column.number_of_rows();
+    let present = if has_nulls {
+        let stream = column
+            .get_stream(StreamKind::Present, vec![])
+            .map_err(orc_error)?;
+        Some(read_booleans(stream, num_rows)?)
+    } else {
+        None
+    };
+    let num_values = match &present {
+        Some(present) => present.iter().filter(|p| **p).count(),
+        None => num_rows,
+    };
+    let data = column
+        .get_stream(StreamKind::Data, vec![])
+        .map_err(orc_error)?;
+
+    let scalars = |values: Vec<Datum<'static>>| {
+        let values = with_nulls(present.as_deref(), values);
+        ColumnValues::Scalars(
+            values
+                .into_iter()
+                .map(|v| v.unwrap_or(Datum::Null))
+                .collect(),
+        )
+    };
+    Ok(match field.typ {
+        OrcType::Boolean => scalars(
+            read_booleans(data, num_values)?
+                .into_iter()
+                .map(Datum::from)
+                .collect(),
+        ),
+        OrcType::Byte => scalars(
+            read_byte_rle(data, num_values)?
+                .into_iter()
+                .map(|b| Datum::Int16(i8::from_le_bytes([b]).into()))
+                .collect(),
+        ),
+        OrcType::Short | OrcType::Int | OrcType::Long | OrcType::Date => {
+            require_v2_encoding(column)?;
+            let values = SignedRleV2Iter::new(data, num_values, vec![])
+                .map(|v| {
+                    let v = v.map_err(orc_error)?;
+                    Ok(match field.typ {
+                        OrcType::Short => Datum::Int16(i16::try_from(v)?),
+                        OrcType::Int => Datum::Int32(i32::try_from(v)?),
+                        OrcType::Date => Datum::Date(
+                            NaiveDate::from_ymd(1970, 1, 1)
+                                .checked_add_signed(chrono::Duration::days(v))
+                                .ok_or_else(|| anyhow!("date out of range"))?,
+                        ),
+                        _ => Datum::Int64(v),
+                    })
+                })
+                .collect::<Result<_, anyhow::Error>>()?;
+            scalars(values)
+        }
+        OrcType::Float => scalars(
+            read_fixed::<4>(data, num_values)?
+                .into_iter()
+                .map(|b| Datum::Float32(OrderedFloat(f32::from_le_bytes(b))))
+                .collect(),
+        ),
+        OrcType::Double => scalars(
+            read_fixed::<8>(data, num_values)?
+                .into_iter()
+                .map(|b| Datum::Float64(OrderedFloat(f64::from_le_bytes(b))))
+                .collect(),
+        ),
+        OrcType::String => {
+            let values = read_binary(column, data, num_values)?
+                .into_iter()
+                .map(|b| String::from_utf8(b).map_err(|_| anyhow!("invalid UTF-8")))
+                .collect::<Result<_, _>>()?;
+            ColumnValues::Strings(with_nulls(present.as_deref(), values))
+        }
+        OrcType::Binary => {
+            let values = read_binary(column, data, num_values)?;
+            ColumnValues::Bytes(with_nulls(present.as_deref(), values))
+        }
+    })
+}
+
+/// Spreads the non-null `values` of a column over the rows that `present`
+/// marks as not null.
+fn with_nulls<T>(present: Option<&[bool]>, values: Vec<T>) -> Vec<Option<T>> {
+    match present {
+        None => values.into_iter().map(Some).collect(),
+        Some(present) => {
+            let mut values = values.into_iter();
+            present
+                .iter()
+                .map(|p| if *p { values.next() } else { None })
+                .collect()
+        }
+    }
+}
+
+fn require_v2_encoding(column: &Column) -> Result<(), anyhow::Error> {
+    match column.encoding().kind() {
+        EncodingKind::DirectV2 | EncodingKind::DictionaryV2 => Ok(()),
+        other => bail!("orc column encoding {:?} is not supported", other),
+    }
+}
+
+/// Decodes `n` values of ORC's byte run length encoding.
+fn read_byte_rle<R: Read>(mut reader: R, n: usize) -> Result<Vec<u8>, anyhow::Error> {
+    let mut out = Vec::with_capacity(n);
+    while out.len() < n {
+        let mut header = [0; 1];
+        reader.read_exact(&mut header)?;
+        let header = i8::from_le_bytes(header);
+        if header >= 0 {
+            // A run of `header + 3` copies of the next byte.
+            let mut value = [0; 1];
+            reader.read_exact(&mut value)?;
+            let run_len = usize::from(header.unsigned_abs()) + 3;
+            out.extend(std::iter::repeat(value[0]).take(run_len));
+        } else {
+            // `-header` literal bytes.
+            let start = out.len();
+            out.resize(start + usize::from(header.unsigned_abs()), 0);
+            reader.read_exact(&mut out[start..])?;
+        }
+    }
+    out.truncate(n);
+    Ok(out)
+}
+
+/// Decodes `n` booleans, which ORC stores as bits in a byte run length
+/// encoding, most significant bit first.
+fn read_booleans<R: Read>(reader: R, n: usize) -> Result<Vec<bool>, anyhow::Error> {
+    let bytes = read_byte_rle(reader, (n + 7) / 8)?;
+    Ok((0..n)
+        .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+        .collect())
+}
+
+/// Reads `n` fixed-width values.
+fn read_fixed<const N: usize>(
+    mut reader: impl Read,
+    n: usize,
+) -> Result<Vec<[u8; N]>, anyhow::Error> {
+    (0..n)
+        .map(|_| {
+            let mut buf = [0; N];
+            reader.read_exact(&mut buf)?;
+            Ok(buf)
+        })
+        .collect()
+}
+
+fn read_unsigned<R: Read>(reader: R, n: usize) -> Result<Vec<usize>, anyhow::Error> {
+    UnsignedRleV2Iter::new(reader, n, vec![])
+        .map(|v| Ok(usize::try_from(v.map_err(orc_error)?)?))
+        .collect()
+}
+
+/// Splits `bytes` into consecutive values of the given lengths.
+fn split_lengths(bytes: &[u8], lengths: &[usize]) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+    let mut offset = 0;
+    lengths
+        .iter()
+        .map(|len| {
+            let value = bytes
+                .get(offset..offset + len)
+                .ok_or_else(|| anyhow!("value lengths exceed the column's data"))?;
+            offset += len;
+            Ok(value.to_vec())
+        })
+        .collect()
+}
+
+/// Decodes `n` values of a string or binary column, whose `data` stream
+/// holds either the values themselves or, for dictionary encoded columns,
+/// indexes into the dictionary.
+fn read_binary<R: Read>(
+    column: &Column,
+    mut data: R,
+    n: usize,
+) -> Result<Vec<Vec<u8>>, anyhow::Error> {
+    let lengths_stream = column
+        .get_stream(StreamKind::Length, vec![])
+        .map_err(orc_error)?;
+    match column.encoding().kind() {
+        EncodingKind::DirectV2 => {
+            let lengths = read_unsigned(lengths_stream, n)?;
+            let mut bytes = vec![];
+            data.read_to_end(&mut bytes)?;
+            split_lengths(&bytes, &lengths)
+        }
+        EncodingKind::DictionaryV2 => {
+            let dictionary_size = column
+                .dictionary_size()
+                .ok_or_else(|| anyhow!("dictionary encoded column has no dictionary size"))?;
+            let lengths = read_unsigned(lengths_stream, dictionary_size)?;
+            let mut bytes = vec![];
+            column
+                .get_stream(StreamKind::DictionaryData, vec![])
+                .map_err(orc_error)?
+                .read_to_end(&mut bytes)?;
+            let dictionary = split_lengths(&bytes, &lengths)?;
+            read_unsigned(data, n)?
+                .into_iter()
+                .map(|i| {
+                    dictionary
+                        .get(i)
+                        .cloned()
+                        .ok_or_else(|| anyhow!("dictionary index {} out of range", i))
+                })
+                .collect()
+        }
+        other => bail!("orc column encoding {:?} is not supported", other),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use orc_format::proto::{
+        ColumnEncoding, Footer, Metadata, PostScript, Stream, StripeInformation,
+    };
+    use prost::Message;
+
+    use super::*;
+
+    /// Encodes small non-negative integers with the direct sub-encoding of
+    /// ORC's integer run length encoding, version 2, one byte per value.
+    fn rle_v2(values: &[u64]) -> Vec<u8> {
+        assert!(!values.is_empty() && values.len() <= 512);
+        let len = values.len() - 1;
+        // The sub-encoding (direct), the bit width code (7 for 8 bits) and
+        // the number of values, minus one.
+        let mut out = vec![0x40 | (7 << 1) | u8::try_from(len >> 8).unwrap()];
+        out.push(u8::try_from(len & 0xff).unwrap());
+        out.extend(values.iter().map(|v| u8::try_from(*v).unwrap()));
+        out
+    }
+
+    /// Writes an uncompressed ORC file with columns `a bigint` and `b text`,
+    /// holding one stripe per element of `stripes`.
+    fn write_file(stripes: Vec<Vec<(i64, Option<&str>)>>) -> Vec<u8> {
+        let mut file = b"ORC".to_vec();
+        let mut stripe_infos = vec![];
+        for rows in &stripes {
+            // Booleans are bits in a byte run length encoding; a header of
+            // -1 introduces a single literal byte.
+            assert!(rows.len() <= 8);
+            let mut present = 0u8;
+            for (i, (_, b)) in rows.iter().enumerate() {
+                if b.is_some() {
+                    present |= 0x80 >> i;
+                }
+            }
+            // Signed values are zigzag encoded.
+            let a: Vec<_> = rows
+                .iter()
+                .map(|(a, _)| u64::try_from(*a * 2).unwrap())
+                .collect();
+            let b: Vec<_> = rows.iter().filter_map(|(_, b)| *b).collect();
+            let b_lengths: Vec<_> = b.iter().map(|b| u64::cast_from(b.len())).collect();
+            let streams = vec![
+                (1, StreamKind::Data, rle_v2(&a)),
+                (2, StreamKind::Present, vec![0xff, present]),
+                (2, StreamKind::Data, b.concat().into_bytes()),
+                (2, StreamKind::Length, rle_v2(&b_lengths)),
+            ];
+            let offset = u64::cast_from(file.len());
+            let mut stripe_footer = StripeFooter::default();
+            for (column, kind, bytes) in streams {
+                stripe_footer.streams.push(Stream {
+                    kind: Some(kind as i32),
+                    column: Some(column),
+                    length: Some(u64::cast_from(bytes.len())),
+                });
+                file.extend(bytes);
+            }
+            for kind in [
+                EncodingKind::Direct,
+                EncodingKind::DirectV2,
+                EncodingKind::DirectV2,
+            ] {
+                stripe_footer.columns.push(ColumnEncoding {
+                    kind: Some(kind as i32),
+                    ..Default::default()
+                });
+            }
+            let data_length = u64::cast_from(file.len()) - offset;
+            let stripe_footer = stripe_footer.encode_to_vec();
+            file.extend(&stripe_footer);
+            stripe_infos.push(StripeInformation {
+                offset: Some(offset),
+                index_length: Some(0),
+                data_length: Some(data_length),
+                footer_length: Some(u64::cast_from(stripe_footer.len())),
+                number_of_rows: Some(u64::cast_from(rows.len())),
+                ..Default::default()
+            });
+        }
+
+        let metadata = Metadata::default().encode_to_vec();
+        file.extend(&metadata);
+        let footer = Footer {
+            header_length: Some(3),
+            stripes: stripe_infos,
+            types: vec![
+                Type {
+                    kind: Some(TypeKind::Struct as i32),
+                    subtypes: vec![1, 2],
+                    field_names: vec!["a".into(), "b".into()],
+                    ..Default::default()
+                },
+                Type {
+                    kind: Some(TypeKind::Long as i32),
+                    ..Default::default()
+                },
+                Type {
+                    kind: Some(TypeKind::String as i32),
+                    ..Default::default()
+                },
+            ],
+            number_of_rows: Some(u64::cast_from(stripes.iter().map(Vec::len).sum::<usize>())),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        file.extend(&footer);
+        let postscript = PostScript {
+            footer_length: Some(u64::cast_from(footer.len())),
+            compression: Some(0),
+            version: vec![0, 12],
+            metadata_length: Some(u64::cast_from(metadata.len())),
+            magic: Some("ORC".into()),
+            ..Default::default()
+        }
+        .encode_to_vec();
+        file.extend(&postscript);
+        file.push(u8::try_from(postscript.len()).unwrap());
+        file
+    }
+
+    #[test]
+    fn infer_and_decode() {
+        let file = write_file(vec![
+            vec![(1, Some("one")), (2, None)],
+            vec![(3, Some("three"))],
+        ]);
+
+        let schema = OrcSchema::infer(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(
+            schema,
+            OrcSchema {
+                fields: vec![
+                    OrcField {
+                        name: "a".into(),
+                        typ: OrcType::Long,
+                    },
+                    OrcField {
+                        name: "b".into(),
+                        typ: OrcType::String,
+                    },
+                ],
+            }
+        );
+        assert_eq!(OrcSchema::from_json(&schema.to_json()).unwrap(), schema);
+        let layout = layout(&file).unwrap();
+        assert_eq!(
+            layout.chunks.iter().map(|c| c.num_rows).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        // Decode all columns of both stripes.
+        let mut decoder = Decoder::new(schema.clone(), vec![true, true]);
+        let mut rows = vec![];
+        decoder
+            .decode(Cursor::new(&file), &[0, 1], |row| rows.push(row))
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row::pack_slice(&[Datum::Int64(1), Datum::String("one")]),
+                Row::pack_slice(&[Datum::Int64(2), Datum::Null]),
+                Row::pack_slice(&[Datum::Int64(3), Datum::String("three")]),
+            ]
+        );
+
+        // Decode only the second column of the second stripe.
+        let mut decoder = Decoder::new(schema, vec![false, true]);
+        let mut rows = vec![];
+        decoder
+            .decode(Cursor::new(&file), &[1], |row| rows.push(row))
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![Row::pack_slice(&[Datum::Null, Datum::String("three")])]
+        );
+    }
+
+    #[test]
+    fn decode_missing_column() {
+        let file = write_file(vec![vec![(1, Some("one"))]]);
+        let schema = OrcSchema {
+            fields: vec![OrcField {
+                name: "c".into(),
+                typ: OrcType::Long,
+            }],
+        };
+        let mut decoder = Decoder::new(schema, vec![true]);
+        let err = decoder
+            .decode(Cursor::new(&file), &[0], |_| ())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "orc file is missing column c");
+    }
+}
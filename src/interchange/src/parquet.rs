@@ -0,0 +1,635 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Decoding of Apache Parquet files.
+//!
+//! The relation schema of a Parquet source is inferred from the footer of a
+//! sample file when the source is created and recorded as a [`ParquetSchema`].
+//! Every file the source later reads is decoded against that schema: columns
+//! are matched up by name, so files are free to order their columns
+//! differently or to carry extra columns.
+
+use std::collections::HashMap;
+use std::io::{Cursor, Read, Seek};
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Context};
+use arrow2::array::{
+    Array, BinaryArray, BooleanArray, FixedSizeBinaryArray, PrimitiveArray, Utf8Array,
+};
+use arrow2::datatypes::{DataType, TimeUnit as ArrowTimeUnit};
+use arrow2::io::parquet::read::{
+    infer_schema, read_metadata, FileReader, GroupFilter, RowGroupMetaData,
+};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+
+use mz_ore::cast::CastFrom;
+use mz_repr::adt::numeric::{self, Numeric, NumericMaxScale, NUMERIC_DATUM_MAX_PRECISION};
+use mz_repr::{Datum, RelationDesc, Row, RowPacker, ScalarType};
+
+use crate::columnar::{FileChunk, FileLayout};
+
+/// The columns of a Parquet source, in the order they appear in the source's
+/// relation.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ParquetSchema {
+    pub fields: Vec<ParquetField>,
+}
+
+/// A column of a Parquet source.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ParquetField {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub typ: ParquetType,
+    pub nullable: bool,
+}
+
+/// The Parquet column types that can be decoded.
+///
+/// These are the Arrow types that Parquet physical and logical types map to;
+/// types that carry the same values (e.g. `utf8` and `large_utf8`) are
+/// collapsed into one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ParquetType {
+    Boolean,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float32,
+    Float64,
+    Utf8,
+    Binary,
+    Date,
+    Time { unit: TimeUnit },
+    Timestamp { unit: TimeUnit, tz: bool },
+    Decimal { precision: u8, scale: u8 },
+}
+
+/// The resolution of a Parquet time or timestamp column.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeUnit {
+    Second,
+    Millisecond,
+    Microsecond,
+    Nanosecond,
+}
+
+impl From<&ArrowTimeUnit> for TimeUnit {
+    fn from(unit: &ArrowTimeUnit) -> TimeUnit {
+        match unit {
+            ArrowTimeUnit::Second => TimeUnit::Second,
+            ArrowTimeUnit::Millisecond => TimeUnit::Millisecond,
+            ArrowTimeUnit::Microsecond => TimeUnit::Microsecond,
+            ArrowTimeUnit::Nanosecond => TimeUnit::Nanosecond,
+        }
+    }
+}
+
+impl TimeUnit {
+    /// Splits a count of this unit into whole seconds and leftover
+    /// nanoseconds.
+    fn split(self, n: i64) -> (i64, u32) {
+        let per_second = match self {
+            TimeUnit::Second => 1,
+            TimeUnit::Millisecond => 1_000,
+            TimeUnit::Microsecond => 1_000_000,
+            TimeUnit::Nanosecond => 1_000_000_000,
+        };
+        let secs = n.div_euclid(per_second);
+        let nanos = n.rem_euclid(per_second) * (1_000_000_000 / per_second);
+        (secs, u32::try_from(nanos).expect("nanoseconds fit in u32"))
+    }
+}
+
+impl ParquetType {
+    fn from_arrow(data_type: &DataType) -> Result<ParquetType, anyhow::Error> {
+        Ok(match data_type {
+            DataType::Boolean => ParquetType::Boolean,
+            DataType::Int8 => ParquetType::Int8,
+            DataType::Int16 => ParquetType::Int16,
+            DataType::Int32 => ParquetType::Int32,
+            DataType::Int64 => ParquetType::Int64,
+            DataType::UInt8 => ParquetType::UInt8,
+            DataType::UInt16 => ParquetType::UInt16,
+            DataType::UInt32 => ParquetType::UInt32,
+            DataType::UInt64 => ParquetType::UInt64,
+            DataType::Float32 => ParquetType::Float32,
+            DataType::Float64 => ParquetType::Float64,
+            DataType::Utf8 | DataType::LargeUtf8 => ParquetType::Utf8,
+            DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => {
+                ParquetType::Binary
+            }
+            DataType::Date32 => ParquetType::Date,
+            DataType::Time32(unit) | DataType::Time64(unit) => {
+                ParquetType::Time { unit: unit.into() }
+            }
+            DataType::Timestamp(unit, tz) => ParquetType::Timestamp {
+                unit: unit.into(),
+                tz: tz.is_some(),
+            },
+            DataType::Decimal(precision, scale) => {
+                if *precision > usize::cast_from(NUMERIC_DATUM_MAX_PRECISION) {
+                    bail!(
+                        "decimals with precision greater than {} are not supported",
+                        NUMERIC_DATUM_MAX_PRECISION
+                    )
+                }
+                ParquetType::Decimal {
+                    precision: u8::try_from(*precision)?,
+                    scale: u8::try_from(*scale)?,
+                }
+            }
+            other => bail!("parquet columns of type {:?} are not supported", other),
+        })
+    }
+
+    fn scalar_type(&self) -> ScalarType {
+        match self {
+            ParquetType::Boolean => ScalarType::Bool,
+            ParquetType::Int8 | ParquetType::Int16 | ParquetType::UInt8 => ScalarType::Int16,
+            ParquetType::Int32 | ParquetType::UInt16 => ScalarType::Int32,
+            ParquetType::Int64 | ParquetType::UInt32 => ScalarType::Int64,
+            ParquetType::UInt64 => ScalarType::Numeric {
+                max_scale: Some(NumericMaxScale::ZERO),
+            },
+            ParquetType::Float32 => ScalarType::Float32,
+            ParquetType::Float64 => ScalarType::Float64,
+            ParquetType::Utf8 => ScalarType::String,
+            ParquetType::Binary => ScalarType::Bytes,
+            ParquetType::Date => ScalarType::Date,
+            ParquetType::Time { .. } => ScalarType::Time,
+            ParquetType::Timestamp { tz: false, .. } => ScalarType::Timestamp,
+            ParquetType::Timestamp { tz: true, .. } => ScalarType::TimestampTz,
+            ParquetType::Decimal { scale, .. } => ScalarType::Numeric {
+                max_scale: Some(
+                    NumericMaxScale::try_from(usize::from(*scale))
+                        .expect("scale validated when parsing schema"),
+                ),
+            },
+        }
+    }
+}
+
+impl ParquetSchema {
+    /// Infers the schema of a Parquet source from the footer of one of its
+    /// files.
+    pub fn infer<R: Read + Seek>(reader: &mut R) -> Result<ParquetSchema, anyhow::Error> {
+        let metadata = read_metadata(reader).context("reading parquet file metadata")?;
+        let schema = infer_schema(&metadata).context("reading parquet file schema")?;
+        let fields = schema
+            .fields
+            .iter()
+            .map(|field| {
+                let typ = ParquetType::from_arrow(&field.data_type)
+                    .with_context(|| format!("column {}", field.name))?;
+                Ok(ParquetField {
+                    name: field.name.clone(),
+                    typ,
+                    nullable: field.is_nullable,
+                })
+            })
+            .collect::<Result<_, anyhow::Error>>()?;
+        Ok(ParquetSchema { fields })
+    }
+
+    /// Parses a schema previously produced by [`ParquetSchema::to_json`].
+    pub fn from_json(s: &str) -> Result<ParquetSchema, anyhow::Error> {
+        let schema: ParquetSchema = serde_json::from_str(s).context("parsing parquet schema")?;
+        for field in &schema.fields {
+            if let ParquetType::Decimal { precision, scale } = field.typ {
+                if precision > NUMERIC_DATUM_MAX_PRECISION || scale > precision {
+                    bail!(
+                        "invalid decimal precision {} and scale {} for column {}",
+                        precision,
+                        scale,
+                        field.name
+                    );
+                }
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Serializes the schema so that it can be recorded in a `CREATE SOURCE`
+    /// statement.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("parquet schemas serialize")
+    }
+
+    /// Returns the relation described by the schema.
+    pub fn desc(&self) -> RelationDesc {
+        self.fields
+            .iter()
+            .fold(RelationDesc::empty(), |desc, field| {
+                desc.with_column(
+                    &field.name,
+                    field.typ.scalar_type().nullable(field.nullable),
+                )
+            })
+    }
+}
+
+/// The number of bytes at the end of a Parquet file that the reader reads at
+/// once when looking for the file's metadata.
+pub const FOOTER_READ_SIZE: u64 = 64 * 1024;
+
+/// Returns the layout of a Parquet file, whose chunks are its row groups.
+pub fn layout(file: &[u8]) -> Result<FileLayout, anyhow::Error> {
+    let metadata =
+        read_metadata(&mut Cursor::new(file)).context("reading parquet file metadata")?;
+    // The file ends with the metadata, the length of the metadata as a
+    // 4-byte integer, and the 4-byte magic number.
+    let len = u64::cast_from(file.len());
+    let metadata_len = &file[file.len() - 8..file.len() - 4];
+    let metadata_len = u32::from_le_bytes(metadata_len.try_into().expect("known to be 4 bytes"));
+    let footer_len = u64::from(metadata_len) + 8;
+    let tail = len.saturating_sub(footer_len.max(FOOTER_READ_SIZE))..len;
+    let chunks = metadata
+        .row_groups
+        .iter()
+        .map(|row_group| {
+            let byte_ranges = row_group
+                .columns()
+                .iter()
+                .map(|column| {
+                    let (start, len) = column.byte_range();
+                    start..start + len
+                })
+                .collect();
+            Ok(FileChunk {
+                num_rows: usize::try_from(row_group.num_rows())?,
+                byte_ranges,
+            })
+        })
+        .collect::<Result<_, anyhow::Error>>()?;
+    Ok(FileLayout { tail, chunks })
+}
+
+/// Decodes Parquet files into rows of a [`ParquetSchema`].
+#[derive(Debug)]
+pub struct Decoder {
+    schema: ParquetSchema,
+    /// Whether each column of the schema needs to be read. Columns that are
+    /// not demanded are not read from the file and decode as `NULL`.
+    demanded: Vec<bool>,
+    row: Row,
+}
+
+impl Decoder {
+    /// Creates a decoder that only reads the `demanded` columns of `schema`.
+    pub fn new(schema: ParquetSchema, demanded: Vec<bool>) -> Decoder {
+        assert_eq!(schema.fields.len(), demanded.len());
+        Decoder {
+            schema,
+            demanded,
+            row: Row::default(),
+        }
+    }
+
+    /// Decodes the rows of the given row groups of a Parquet file, in
+    /// order, passing each to `emit`.
+    ///
+    /// Fails without emitting any rows if the file does not contain every
+    /// demanded column with its expected type.
+    pub fn decode<R, F>(
+        &mut self,
+        mut reader: R,
+        row_groups: &[usize],
+        mut emit: F,
+    ) -> Result<(), anyhow::Error>
+    where
+        R: Read + Seek,
+        F: FnMut(Row),
+    {
+        let metadata = read_metadata(&mut reader).context("reading parquet file metadata")?;
+        let file_schema = infer_schema(&metadata).context("reading parquet file schema")?;
+        let file_columns: HashMap<_, _> = file_schema
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (field.name.as_str(), (i, field)))
+            .collect();
+
+        // Map each demanded column to its position in the file.
+        let mut file_projection = vec![];
+        for (field, demanded) in self.schema.fields.iter().zip(&self.demanded) {
+            if !*demanded {
+                continue;
+            }
+            let (i, file_field) = file_columns
+                .get(field.name.as_str())
+                .ok_or_else(|| anyhow!("parquet file is missing column {}", field.name))?;
+            let file_type = ParquetType::from_arrow(&file_field.data_type)
+                .with_context(|| format!("column {}", field.name))?;
+            if file_type != field.typ {
+                bail!(
+                    "parquet file column {} has type {:?}, but the source expects {:?}",
+                    field.name,
+                    file_type,
+                    field.typ
+                );
+            }
+            file_projection.push(*i);
+        }
+        // The reader returns projected columns in file order, so remember
+        // where each demanded column ends up.
+        let mut sorted_projection = file_projection.clone();
+        sorted_projection.sort_unstable();
+        let chunk_positions: Vec<_> = file_projection
+            .iter()
+            .map(|i| sorted_projection.binary_search(i).expect("known to exist"))
+            .collect();
+
+        let selected = row_groups.to_vec();
+        let groups_filter: GroupFilter =
+            Arc::new(move |i: usize, _: &RowGroupMetaData| selected.contains(&i));
+        let reader = FileReader::try_new(
+            reader,
+            Some(&sorted_projection),
+            None,
+            None,
+            Some(groups_filter),
+        )?;
+
+        for chunk in reader {
+            let chunk = chunk?;
+            let arrays = chunk.arrays();
+            for row_idx in 0..chunk.len() {
+                let mut packer = self.row.packer();
+                let mut demanded_idx = 0;
+                for (field, demanded) in self.schema.fields.iter().zip(&self.demanded) {
+                    if !*demanded {
+                        packer.push(Datum::Null);
+                        continue;
+                    }
+                    let array = &arrays[chunk_positions[demanded_idx]];
+                    demanded_idx += 1;
+                    push_value(&mut packer, field, array.as_ref(), row_idx)?;
+                }
+                emit(self.row.clone());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn downcast<'a, T: 'static>(array: &'a dyn Array) -> Result<&'a T, anyhow::Error> {
+    array
+        .as_any()
+        .downcast_ref::<T>()
+        .ok_or_else(|| anyhow!("unexpected parquet array type {:?}", array.data_type()))
+}
+
+fn push_value(
+    packer: &mut RowPacker,
+    field: &ParquetField,
+    array: &dyn Array,
+    i: usize,
+) -> Result<(), anyhow::Error> {
+    if array.is_null(i) {
+        if !field.nullable {
+            bail!(
+                "parquet file contains NULL in non-nullable column {}",
+                field.name
+            );
+        }
+        packer.push(Datum::Null);
+        return Ok(());
+    }
+    let datum = match field.typ {
+        ParquetType::Boolean => Datum::from(downcast::<BooleanArray>(array)?.value(i)),
+        ParquetType::Int8 => Datum::Int16(downcast::<PrimitiveArray<i8>>(array)?.value(i).into()),
+        ParquetType::Int16 => Datum::Int16(downcast::<PrimitiveArray<i16>>(array)?.value(i)),
+        ParquetType::Int32 => Datum::Int32(downcast::<PrimitiveArray<i32>>(array)?.value(i)),
+        ParquetType::Int64 => Datum::Int64(downcast::<PrimitiveArray<i64>>(array)?.value(i)),
+        ParquetType::UInt8 => Datum::Int16(downcast::<PrimitiveArray<u8>>(array)?.value(i).into()),
+        ParquetType::UInt16 => {
+            Datum::Int32(downcast::<PrimitiveArray<u16>>(array)?.value(i).into())
+        }
+        ParquetType::UInt32 => {
+            Datum::Int64(downcast::<PrimitiveArray<u32>>(array)?.value(i).into())
+        }
+        ParquetType::UInt64 => {
+            let v = downcast::<PrimitiveArray<u64>>(array)?.value(i);
+            Datum::from(Numeric::from(v))
+        }
+        ParquetType::Float32 => Datum::Float32(OrderedFloat(
+            downcast::<PrimitiveArray<f32>>(array)?.value(i),
+        )),
+        ParquetType::Float64 => Datum::Float64(OrderedFloat(
+            downcast::<PrimitiveArray<f64>>(array)?.value(i),
+        )),
+        ParquetType::Utf8 => match array.data_type() {
+            DataType::LargeUtf8 => Datum::String(downcast::<Utf8Array<i64>>(array)?.value(i)),
+            _ => Datum::String(downcast::<Utf8Array<i32>>(array)?.value(i)),
+        },
+        ParquetType::Binary => match array.data_type() {
+            DataType::LargeBinary => Datum::Bytes(downcast::<BinaryArray<i64>>(array)?.value(i)),
+            DataType::FixedSizeBinary(_) => {
+                Datum::Bytes(downcast::<FixedSizeBinaryArray>(array)?.value(i))
+            }
+            _ => Datum::Bytes(downcast::<BinaryArray<i32>>(array)?.value(i)),
+        },
+        ParquetType::Date => {
+            let days = downcast::<PrimitiveArray<i32>>(array)?.value(i);
+            let date = NaiveDate::from_ymd(1970, 1, 1)
+                .checked_add_signed(chrono::Duration::days(days.into()))
+                .ok_or_else(|| anyhow!("date out of range in column {}", field.name))?;
+            Datum::Date(date)
+        }
+        ParquetType::Time { unit } => {
+            let n = match array.data_type() {
+                DataType::Time32(_) => downcast::<PrimitiveArray<i32>>(array)?.value(i).into(),
+                _ => downcast::<PrimitiveArray<i64>>(array)?.value(i),
+            };
+            let (secs, nanos) = unit.split(n);
+            let time = u32::try_from(secs)
+                .ok()
+                .and_then(|secs| NaiveTime::from_num_seconds_from_midnight_opt(secs, nanos))
+                .ok_or_else(|| anyhow!("time out of range in column {}", field.name))?;
+            Datum::Time(time)
+        }
+        ParquetType::Timestamp { unit, tz } => {
+            let (secs, nanos) = unit.split(downcast::<PrimitiveArray<i64>>(array)?.value(i));
+            let ts = NaiveDateTime::from_timestamp_opt(secs, nanos)
+                .ok_or_else(|| anyhow!("timestamp out of range in column {}", field.name))?;
+            if tz {
+                Datum::TimestampTz(DateTime::<Utc>::from_utc(ts, Utc))
+            } else {
+                Datum::Timestamp(ts)
+            }
+        }
+        ParquetType::Decimal { scale, .. } => {
+            let v = downcast::<PrimitiveArray<i128>>(array)?.value(i);
+            let n = numeric::twos_complement_be_to_numeric(&mut v.to_be_bytes(), scale)?;
+            Datum::from(n)
+        }
+    };
+    packer.push(datum);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow2::array::{Int64Array, Utf8Array};
+    use arrow2::chunk::Chunk;
+    use arrow2::datatypes::{Field, Schema};
+    use arrow2::io::parquet::write::{FileWriter, RowGroupIterator, Version, WriteOptions};
+    use parquet2::compression::Compression;
+    use parquet2::encoding::Encoding;
+
+    use super::*;
+
+    /// Writes a Parquet file with columns `a bigint NOT NULL` and `b text`,
+    /// holding one row group per element of `groups`.
+    fn write_file(groups: Vec<Vec<(i64, Option<&str>)>>) -> Vec<u8> {
+        let schema = Schema::from(vec![
+            Field::new("a", DataType::Int64, false),
+            Field::new("b", DataType::Utf8, true),
+        ]);
+        let chunks = groups.into_iter().map(|rows| {
+            let a = Int64Array::from_vec(rows.iter().map(|(a, _)| *a).collect());
+            let b = Utf8Array::<i32>::from(rows.iter().map(|(_, b)| *b).collect::<Vec<_>>());
+            Ok(Chunk::new(vec![
+                Arc::new(a) as Arc<dyn Array>,
+                Arc::new(b) as Arc<dyn Array>,
+            ]))
+        });
+        let options = WriteOptions {
+            write_statistics: false,
+            compression: Compression::Uncompressed,
+            version: Version::V2,
+        };
+        let row_groups = RowGroupIterator::try_new(
+            chunks,
+            &schema,
+            options,
+            vec![Encoding::Plain, Encoding::Plain],
+        )
+        .unwrap();
+        let mut buf = vec![];
+        let mut writer = FileWriter::try_new(&mut buf, schema, options).unwrap();
+        writer.start().unwrap();
+        for group in row_groups {
+            let (group, len) = group.unwrap();
+            writer.write(group, len).unwrap();
+        }
+        writer.end(None).unwrap();
+        buf
+    }
+
+    #[test]
+    fn infer_and_decode() {
+        let file = write_file(vec![
+            vec![(1, Some("one")), (2, None)],
+            vec![(3, Some("three"))],
+        ]);
+
+        let schema = ParquetSchema::infer(&mut Cursor::new(&file)).unwrap();
+        assert_eq!(
+            schema,
+            ParquetSchema {
+                fields: vec![
+                    ParquetField {
+                        name: "a".into(),
+                        typ: ParquetType::Int64,
+                        nullable: false,
+                    },
+                    ParquetField {
+                        name: "b".into(),
+                        typ: ParquetType::Utf8,
+                        nullable: true,
+                    },
+                ],
+            }
+        );
+        assert_eq!(ParquetSchema::from_json(&schema.to_json()).unwrap(), schema);
+        let layout = layout(&file).unwrap();
+        assert_eq!(
+            layout.chunks.iter().map(|c| c.num_rows).collect::<Vec<_>>(),
+            vec![2, 1]
+        );
+
+        // Decode all columns of both row groups.
+        let mut decoder = Decoder::new(schema.clone(), vec![true, true]);
+        let mut rows = vec![];
+        decoder
+            .decode(Cursor::new(&file), &[0, 1], |row| rows.push(row))
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                Row::pack_slice(&[Datum::Int64(1), Datum::String("one")]),
+                Row::pack_slice(&[Datum::Int64(2), Datum::Null]),
+                Row::pack_slice(&[Datum::Int64(3), Datum::String("three")]),
+            ]
+        );
+
+        // Decode only the second column of the second row group.
+        let mut decoder = Decoder::new(schema, vec![false, true]);
+        let mut rows = vec![];
+        decoder
+            .decode(Cursor::new(&file), &[1], |row| rows.push(row))
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![Row::pack_slice(&[Datum::Null, Datum::String("three")])]
+        );
+    }
+
+    #[test]
+    fn decode_partial_file() {
+        // Make the first row group large enough that the file does not fit
+        // in the footer read size.
+        let big = vec![(0, Some("zero")); 20_000];
+        let file = write_file(vec![big, vec![(1, Some("one"))]]);
+        let schema = ParquetSchema::infer(&mut Cursor::new(&file)).unwrap();
+        let layout = layout(&file).unwrap();
+
+        let partial = layout.slice(&file, &[1]);
+        assert!(partial.retained_len() < file.len() / 2);
+        let mut decoder = Decoder::new(schema, vec![true, true]);
+        let mut rows = vec![];
+        decoder
+            .decode(partial.clone(), &[1], |row| rows.push(row))
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![Row::pack_slice(&[Datum::Int64(1), Datum::String("one")])]
+        );
+        assert!(decoder.decode(partial, &[0], |_| ()).is_err());
+    }
+
+    #[test]
+    fn decode_missing_column() {
+        let file = write_file(vec![vec![(1, Some("one"))]]);
+        let schema = ParquetSchema {
+            fields: vec![ParquetField {
+                name: "c".into(),
+                typ: ParquetType::Int64,
+                nullable: false,
+            }],
+        };
+        let mut decoder = Decoder::new(schema, vec![true]);
+        let err = decoder
+            .decode(Cursor::new(&file), &[0], |_| ())
+            .unwrap_err();
+        assert_eq!(err.to_string(), "parquet file is missing column c");
+    }
+}
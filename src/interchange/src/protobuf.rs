@@ -7,18 +7,55 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::HashSet;
+use std::collections::HashMap;
 
 use anyhow::{anyhow, bail, Context};
 
 use prost_reflect::{
-    Cardinality, DynamicMessage, FieldDescriptor, FileDescriptor, Kind, MessageDescriptor,
+    Cardinality, DynamicMessage, FieldDescriptor, FileDescriptor, Kind, MapKey, MessageDescriptor,
     ReflectMessage, Value,
 };
+use serde_json::json;
 
 use mz_ore::str::StrExt;
+use mz_repr::adt::jsonb::JsonbPacker;
+use mz_repr::adt::numeric::Numeric;
 use mz_repr::{ColumnName, ColumnType, Datum, Row, RowPacker, ScalarType};
 
+/// The number of times a recursive message is expanded into a nested record
+/// within itself.
+///
+/// Occurrences of a recursive message nested any deeper are decoded as `jsonb`.
+const RECURSION_LIMIT: usize = 1;
+
+/// Tracks how many times each message appears along the path from the root
+/// message to the message currently being described or decoded.
+///
+/// Deriving the column types of a message and packing its values must visit
+/// messages in the same order, so that both make the same decision about which
+/// nested messages to decode as `jsonb`.
+#[derive(Debug, Default)]
+struct SeenMessages(HashMap<String, usize>);
+
+impl SeenMessages {
+    /// Reports whether a nested occurrence of the message `m` has exceeded the
+    /// recursion limit.
+    fn exceeds_limit(&self, m: &MessageDescriptor) -> bool {
+        self.0.get(m.full_name()).copied().unwrap_or(0) > RECURSION_LIMIT
+    }
+
+    fn enter(&mut self, m: &MessageDescriptor) {
+        *self.0.entry(m.full_name().to_owned()).or_default() += 1;
+    }
+
+    fn exit(&mut self, m: &MessageDescriptor) {
+        *self
+            .0
+            .get_mut(m.full_name())
+            .expect("exiting message that was entered") -= 1;
+    }
+}
+
 /// A decoded description of the schema of a Protobuf message.
 #[derive(Debug, PartialEq)]
 pub struct DecodedDescriptors {
@@ -38,8 +75,8 @@ impl DecodedDescriptors {
                 message_name.quoted(),
             )
         })?;
-        let mut seen_messages = HashSet::new();
-        seen_messages.insert(message_descriptor.name().to_owned());
+        let mut seen_messages = SeenMessages::default();
+        seen_messages.enter(&message_descriptor);
         let mut columns = vec![];
         for field in message_descriptor.fields() {
             let name = ColumnName::from(field.name());
@@ -108,17 +145,34 @@ impl Decoder {
         }
         let message = DynamicMessage::decode(self.descriptors.message_descriptor.clone(), bytes)?;
         let mut packer = self.row.packer();
-        pack_message(&mut packer, &message)?;
+        let mut seen_messages = SeenMessages::default();
+        seen_messages.enter(&message.descriptor());
+        pack_message(&mut packer, &mut seen_messages, &message)?;
         Ok(Some(self.row.clone()))
     }
 }
 
 fn derive_column_type(
-    seen_messages: &mut HashSet<String>,
+    seen_messages: &mut SeenMessages,
     field: &FieldDescriptor,
 ) -> Result<ColumnType, anyhow::Error> {
     if field.is_map() {
-        bail!("Protobuf map fields are not supported");
+        // Map keys are always scalars, which are converted to strings, as the
+        // keys of a `ScalarType::Map` are always strings.
+        let entry = field
+            .kind()
+            .as_message()
+            .cloned()
+            .ok_or_else(|| anyhow!("internal error: protobuf map field is not a message"))?;
+        let value_field = map_entry_value_field(&entry)?;
+        let ty = derive_inner_type(seen_messages, value_field.kind())?;
+        return Ok(ColumnType {
+            nullable: false,
+            scalar_type: ScalarType::Map {
+                value_type: Box::new(ty.scalar_type),
+                custom_oid: None,
+            },
+        });
     }
 
     let ty = derive_inner_type(seen_messages, field.kind())?;
@@ -136,33 +190,34 @@ fn derive_column_type(
 }
 
 fn derive_inner_type(
-    seen_messages: &mut HashSet<String>,
+    seen_messages: &mut SeenMessages,
     ty: Kind,
 ) -> Result<ColumnType, anyhow::Error> {
     match ty {
         Kind::Bool => Ok(ScalarType::Bool.nullable(false)),
         Kind::Int32 | Kind::Sint32 | Kind::Sfixed32 => Ok(ScalarType::Int32.nullable(false)),
         Kind::Int64 | Kind::Sint64 | Kind::Sfixed64 => Ok(ScalarType::Int64.nullable(false)),
-        Kind::Uint32 | Kind::Fixed32 | Kind::Uint64 | Kind::Fixed64 => {
-            bail!("Protobuf unsigned integer types are not supported")
-        }
+        // Every unsigned 32-bit integer fits in a signed 64-bit integer, while
+        // unsigned 64-bit integers require a numeric.
+        Kind::Uint32 | Kind::Fixed32 => Ok(ScalarType::Int64.nullable(false)),
+        Kind::Uint64 | Kind::Fixed64 => Ok(ScalarType::Numeric { max_scale: None }.nullable(false)),
         Kind::Float => Ok(ScalarType::Float32.nullable(false)),
         Kind::Double => Ok(ScalarType::Float64.nullable(false)),
         Kind::String => Ok(ScalarType::String.nullable(false)),
         Kind::Bytes => Ok(ScalarType::Bytes.nullable(false)),
         Kind::Enum(_) => Ok(ScalarType::String.nullable(false)),
         Kind::Message(m) => {
-            if seen_messages.contains(m.name()) {
-                bail!("Recursive types are not supported: {}", m.name());
+            if seen_messages.exceeds_limit(&m) {
+                return Ok(ScalarType::Jsonb.nullable(true));
             }
-            seen_messages.insert(m.name().to_owned());
+            seen_messages.enter(&m);
             let mut fields = Vec::with_capacity(m.fields().len());
             for field in m.fields() {
                 let column_name = ColumnName::from(field.name());
                 let column_type = derive_column_type(seen_messages, &field)?;
                 fields.push((column_name, column_type))
             }
-            seen_messages.remove(m.name());
+            seen_messages.exit(&m);
             let ty = ScalarType::Record {
                 fields,
                 custom_oid: None,
@@ -173,7 +228,35 @@ fn derive_inner_type(
     }
 }
 
-fn pack_message(packer: &mut RowPacker, message: &DynamicMessage) -> Result<(), anyhow::Error> {
+/// Returns the descriptor of the field that holds the values of the map whose
+/// entries are described by `entry`.
+fn map_entry_value_field(entry: &MessageDescriptor) -> Result<FieldDescriptor, anyhow::Error> {
+    // The key and value of a map entry are always fields 1 and 2.
+    // See: https://developers.google.com/protocol-buffers/docs/proto3#backwards_compatibility
+    entry.get_field(2).ok_or_else(|| {
+        anyhow!(
+            "internal error: protobuf map entry {} missing value field",
+            entry.name()
+        )
+    })
+}
+
+fn map_key_to_string(key: &MapKey) -> String {
+    match key {
+        MapKey::Bool(b) => b.to_string(),
+        MapKey::I32(i) => i.to_string(),
+        MapKey::I64(i) => i.to_string(),
+        MapKey::U32(i) => i.to_string(),
+        MapKey::U64(i) => i.to_string(),
+        MapKey::String(s) => s.clone(),
+    }
+}
+
+fn pack_message(
+    packer: &mut RowPacker,
+    seen_messages: &mut SeenMessages,
+    message: &DynamicMessage,
+) -> Result<(), anyhow::Error> {
     for field_desc in message.descriptor().fields() {
         if !message.has_field(&field_desc) {
             if field_desc.cardinality() == Cardinality::Required {
@@ -182,19 +265,23 @@ fn pack_message(packer: &mut RowPacker, message: &DynamicMessage) -> Result<(),
                     field_desc.name()
                 );
             }
-            if field_desc.kind().as_message().is_some() && !field_desc.is_list() {
+            if field_desc.kind().as_message().is_some()
+                && !field_desc.is_list()
+                && !field_desc.is_map()
+            {
                 packer.push(Datum::Null);
                 continue;
             }
         }
         let value = message.get_field(&field_desc);
-        pack_value(packer, &field_desc, &*value)?;
+        pack_value(packer, seen_messages, &field_desc, &*value)?;
     }
     Ok(())
 }
 
 fn pack_value(
     packer: &mut RowPacker,
+    seen_messages: &mut SeenMessages,
     field_desc: &FieldDescriptor,
     value: &Value,
 ) -> Result<(), anyhow::Error> {
@@ -203,40 +290,139 @@ fn pack_value(
         Value::Bool(true) => packer.push(Datum::True),
         Value::I32(i) => packer.push(Datum::Int32(*i)),
         Value::I64(i) => packer.push(Datum::Int64(*i)),
+        Value::U32(i) => packer.push(Datum::Int64(i64::from(*i))),
+        Value::U64(i) => packer.push(Datum::from(Numeric::from(*i))),
         Value::F32(f) => packer.push(Datum::Float32((*f).into())),
         Value::F64(f) => packer.push(Datum::Float64((*f).into())),
         Value::String(s) => packer.push(Datum::String(s)),
         Value::Bytes(s) => packer.push(Datum::Bytes(s)),
-        Value::EnumNumber(i) => {
-            let kind = field_desc.kind();
-            let enum_desc = kind.as_enum().ok_or_else(|| {
-                anyhow!(
-                    "internal error: decoding protobuf: field {} missing enum descriptor",
-                    field_desc.name()
-                )
+        Value::EnumNumber(i) => packer.push(Datum::String(&enum_value_name(field_desc, *i)?)),
+        Value::Message(m) => {
+            let descriptor = m.descriptor();
+            if seen_messages.exceeds_limit(&descriptor) {
+                JsonbPacker::new(packer).pack_serde_json(message_to_json(m)?)?;
+            } else {
+                seen_messages.enter(&descriptor);
+                packer.push_list_with(|packer| pack_message(packer, seen_messages, m))?;
+                seen_messages.exit(&descriptor);
+            }
+        }
+        Value::List(values) => {
+            packer.push_list_with(|packer| {
+                for value in values {
+                    pack_value(packer, seen_messages, field_desc, value)?;
+                }
+                Ok::<_, anyhow::Error>(())
             })?;
-            let value = enum_desc.get_value(*i).ok_or_else(|| {
+        }
+        Value::Map(entries) => {
+            let entry = field_desc.kind().as_message().cloned().ok_or_else(|| {
                 anyhow!(
-                    "error decoding protobuf: unknown enum value {} while decoding field {}",
-                    i,
+                    "internal error: decoding protobuf: map field {} missing entry descriptor",
                     field_desc.name()
                 )
             })?;
-            packer.push(Datum::String(value.name()));
-        }
-        Value::Message(m) => packer.push_list_with(|packer| pack_message(packer, m))?,
-        Value::List(values) => {
-            packer.push_list_with(|packer| {
-                for value in values {
-                    pack_value(packer, field_desc, value)?;
+            let value_field = map_entry_value_field(&entry)?;
+            // The keys of a map datum must be sorted.
+            let mut entries: Vec<_> = entries
+                .iter()
+                .map(|(key, value)| (map_key_to_string(key), value))
+                .collect();
+            entries.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+            packer.push_dict_with(|packer| {
+                for (key, value) in entries {
+                    packer.push(Datum::String(&key));
+                    pack_value(packer, seen_messages, &value_field, value)?;
                 }
                 Ok::<_, anyhow::Error>(())
             })?;
         }
-        Value::U32(_) | Value::U64(_) | Value::Map(_) => bail!(
-            "internal error: unexpected value while decoding protobuf message: {:?}",
-            value
-        ),
     }
     Ok(())
 }
+
+/// Returns the name of the enum value numbered `i` in the enum type of
+/// `field_desc`.
+fn enum_value_name(field_desc: &FieldDescriptor, i: i32) -> Result<String, anyhow::Error> {
+    let kind = field_desc.kind();
+    let enum_desc = kind.as_enum().ok_or_else(|| {
+        anyhow!(
+            "internal error: decoding protobuf: field {} missing enum descriptor",
+            field_desc.name()
+        )
+    })?;
+    let value = enum_desc.get_value(i).ok_or_else(|| {
+        anyhow!(
+            "error decoding protobuf: unknown enum value {} while decoding field {}",
+            i,
+            field_desc.name()
+        )
+    })?;
+    Ok(value.name().to_owned())
+}
+
+/// Converts a Protobuf message that is nested too deeply within itself to be
+/// decoded as a record into JSON.
+///
+/// Fields that are not set are omitted, with the exception of repeated and map
+/// fields, which are always present.
+fn message_to_json(message: &DynamicMessage) -> Result<serde_json::Value, anyhow::Error> {
+    let mut object = serde_json::Map::new();
+    for field_desc in message.descriptor().fields() {
+        if !message.has_field(&field_desc)
+            && field_desc.kind().as_message().is_some()
+            && !field_desc.is_list()
+            && !field_desc.is_map()
+        {
+            continue;
+        }
+        let value = message.get_field(&field_desc);
+        object.insert(
+            field_desc.name().to_owned(),
+            value_to_json(&field_desc, &*value)?,
+        );
+    }
+    Ok(serde_json::Value::Object(object))
+}
+
+fn value_to_json(
+    field_desc: &FieldDescriptor,
+    value: &Value,
+) -> Result<serde_json::Value, anyhow::Error> {
+    Ok(match value {
+        Value::Bool(b) => json!(b),
+        Value::I32(i) => json!(i),
+        Value::I64(i) => json!(i),
+        Value::U32(i) => json!(i),
+        Value::U64(i) => json!(i),
+        // JSON cannot represent infinite or NaN floats, which become nulls.
+        Value::F32(f) => json!(f),
+        Value::F64(f) => json!(f),
+        Value::String(s) => json!(s),
+        // Like the canonical JSON encoding of Protobuf messages, bytes are
+        // base64 encoded.
+        Value::Bytes(b) => json!(base64::encode(b)),
+        Value::EnumNumber(i) => json!(enum_value_name(field_desc, *i)?),
+        Value::Message(m) => message_to_json(m)?,
+        Value::List(values) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| value_to_json(field_desc, value))
+                .collect::<Result<_, _>>()?,
+        ),
+        Value::Map(entries) => {
+            let entry = field_desc.kind().as_message().cloned().ok_or_else(|| {
+                anyhow!(
+                    "internal error: decoding protobuf: map field {} missing entry descriptor",
+                    field_desc.name()
+                )
+            })?;
+            let value_field = map_entry_value_field(&entry)?;
+            let mut object = serde_json::Map::new();
+            for (key, value) in entries {
+                object.insert(map_key_to_string(key), value_to_json(&value_field, value)?);
+            }
+            serde_json::Value::Object(object)
+        }
+    })
+}
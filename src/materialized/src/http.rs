@@ -11,7 +11,7 @@
 //!
 //! materialized embeds an HTTP server for introspection into the running
 //! process. At the moment, its primary exports are Prometheus metrics, heap
-//! profiles, and catalog dumps.
+//! profiles, and catalog dumps. It also accepts rows to append to tables.
 
 use std::net::SocketAddr;
 use std::pin::Pin;
@@ -45,6 +45,7 @@ mod metrics;
 mod prof;
 mod root;
 mod sql;
+mod tables;
 mod util;
 
 const SYSTEM_USER: &str = "mz_system";
@@ -240,6 +241,9 @@ impl Server {
                     }
                     (&Method::POST, "/prof") => prof::handle_prof(req, &mut coord_client).await,
                     (&Method::POST, "/sql") => sql::handle_sql(req, &mut coord_client).await,
+                    (&Method::POST, path) if path.starts_with(tables::PATH_PREFIX) => {
+                        tables::handle_append(req, &mut coord_client).await
+                    }
                     (&Method::GET, "/internal/catalog") => {
                         catalog::handle_internal_catalog(req, &mut coord_client).await
                     }
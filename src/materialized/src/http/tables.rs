@@ -0,0 +1,294 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! HTTP endpoint for appending rows to tables.
+//!
+//! Rows are posted to `/api/tables/<name>`, where `<name>` is the percent-encoded,
+//! possibly qualified name of a table, e.g. `materialize.public.t`. The name is
+//! parsed like a name in a SQL statement, so unquoted identifiers are folded to
+//! lowercase. The request body is decoded according to its content type:
+//!
+//!   * `text/csv`: CSV rows, decoded like `COPY ... FROM STDIN (FORMAT CSV)`.
+//!     If the `header` query parameter is `true`, the first row is skipped.
+//!   * `application/json`: a JSON object or an array of JSON objects, whose
+//!     keys name the columns of the table. Missing keys take the default value
+//!     of their column, like in `INSERT`.
+//!
+//! All rows of a request are appended atomically.
+
+use std::collections::{BTreeMap, HashMap};
+
+use anyhow::{anyhow, bail};
+use hyper::body::HttpBody;
+use hyper::{header, Body, Request, Response, StatusCode};
+use percent_encoding::percent_decode_str;
+use url::form_urlencoded;
+
+use mz_coord::session::EndTransactionAction;
+use mz_coord::ExecuteResponse;
+use mz_pgcopy::CopyFormatParams;
+use mz_repr::{Datum, RelationDesc, Row, RowArena};
+use mz_sql::ast::{
+    CopyDirection, CopyRelation, CopyStatement, CopyTarget, Raw, RawObjectName, Statement,
+    UnresolvedObjectName,
+};
+use mz_sql::plan::{CopyFormat, CopyParams};
+
+use crate::http::util;
+
+/// The path prefix under which tables are exposed.
+pub const PATH_PREFIX: &str = "/api/tables/";
+
+/// The maximum size of a request body, in bytes.
+const MAX_REQUEST_SIZE: usize = 64 << 20;
+
+/// The formats in which rows can be posted.
+#[derive(Debug, Clone, Copy)]
+enum Format {
+    Csv { header: bool },
+    Json,
+}
+
+pub async fn handle_append(
+    req: Request<Body>,
+    coord_client: &mut mz_coord::SessionClient,
+) -> Result<Response<Body>, anyhow::Error> {
+    let name = match req.uri().path().strip_prefix(PATH_PREFIX) {
+        Some(name) if !name.is_empty() => match parse_name(name) {
+            Ok(name) => name,
+            Err(e) => return Ok(util::error_response(StatusCode::BAD_REQUEST, e.to_string())),
+        },
+        _ => {
+            return Ok(util::error_response(
+                StatusCode::NOT_FOUND,
+                "expected table name",
+            ))
+        }
+    };
+    let format = match request_format(&req) {
+        Ok(format) => format,
+        Err(e) => {
+            return Ok(util::error_response(
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                e.to_string(),
+            ))
+        }
+    };
+    let body = match read_body(req.into_body()).await? {
+        Some(body) => body,
+        None => {
+            return Ok(util::error_response(
+                StatusCode::PAYLOAD_TOO_LARGE,
+                format!("request body exceeds {} bytes", MAX_REQUEST_SIZE),
+            ))
+        }
+    };
+
+    coord_client.start_transaction(Some(1)).await?;
+    match append(coord_client, name, format, &body).await {
+        Ok(count) => {
+            coord_client
+                .end_transaction(EndTransactionAction::Commit)
+                .await?;
+            Ok(Response::builder()
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(serde_json::json!({ "rows": count }).to_string()))
+                .unwrap())
+        }
+        Err(e) => {
+            coord_client
+                .end_transaction(EndTransactionAction::Rollback)
+                .await?;
+            Ok(util::error_response(StatusCode::BAD_REQUEST, e.to_string()))
+        }
+    }
+}
+
+/// Parses the percent-encoded table name in the request path.
+fn parse_name(name: &str) -> Result<UnresolvedObjectName, anyhow::Error> {
+    let name = percent_decode_str(name)
+        .decode_utf8()
+        .map_err(|_| anyhow!("table name is not valid UTF-8"))?;
+    mz_sql::parse::parse_object_name(&name).map_err(|e| anyhow!("invalid table name: {}", e))
+}
+
+/// Determines the format of the rows in the request from its content type.
+fn request_format(req: &Request<Body>) -> Result<Format, anyhow::Error> {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    // Ignore parameters like `charset`.
+    let mime = content_type.split(';').next().unwrap_or("").trim();
+    match mime {
+        "text/csv" => {
+            let query: HashMap<_, _> =
+                form_urlencoded::parse(req.uri().query().unwrap_or("").as_bytes()).collect();
+            let header = match query.get("header").map(|h| h.as_ref()) {
+                None | Some("false") => false,
+                Some("true") => true,
+                Some(other) => bail!("invalid value for header parameter: {}", other),
+            };
+            Ok(Format::Csv { header })
+        }
+        "application/json" => Ok(Format::Json),
+        _ => bail!(
+            "unsupported content type {:?}; expected text/csv or application/json",
+            content_type
+        ),
+    }
+}
+
+/// Reads the request body, or returns `None` if it exceeds
+/// [`MAX_REQUEST_SIZE`].
+async fn read_body(mut body: Body) -> Result<Option<Vec<u8>>, anyhow::Error> {
+    if let Some(size) = body.size_hint().upper() {
+        if size > u64::try_from(MAX_REQUEST_SIZE).unwrap() {
+            return Ok(None);
+        }
+    }
+    let mut buf = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buf.len() + chunk.len() > MAX_REQUEST_SIZE {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk);
+    }
+    Ok(Some(buf))
+}
+
+/// Appends the rows in `body` to the table `name` in the current transaction,
+/// returning the number of appended rows.
+async fn append(
+    coord_client: &mut mz_coord::SessionClient,
+    name: UnresolvedObjectName,
+    format: Format,
+    body: &[u8],
+) -> Result<usize, anyhow::Error> {
+    // Plan the append as a `COPY ... FROM STDIN`, so that the table is
+    // resolved and checked exactly like it is for pgwire clients.
+    const EMPTY_PORTAL: &str = "";
+    let stmt = Statement::<Raw>::Copy(CopyStatement {
+        relation: CopyRelation::Table {
+            name: RawObjectName::Name(name),
+            columns: vec![],
+        },
+        direction: CopyDirection::From,
+        target: CopyTarget::Stdin,
+        options: vec![],
+    });
+    coord_client
+        .declare(EMPTY_PORTAL.into(), stmt, vec![])
+        .await?;
+    let desc = coord_client
+        .session()
+        // We do not need to verify here because `execute` verifies below.
+        .get_portal_unverified(EMPTY_PORTAL)
+        .and_then(|portal| portal.desc.relation_desc.clone())
+        .expect("COPY FROM has a relation description");
+    let (id, columns) = match coord_client.execute(EMPTY_PORTAL.into()).await? {
+        ExecuteResponse::CopyFrom { id, columns, .. } => (id, columns),
+        _ => unreachable!("COPY FROM returns CopyFrom"),
+    };
+
+    let batches = match format {
+        Format::Csv { header } => {
+            let column_types = column_types(&desc);
+            let params = CopyParams {
+                format: CopyFormat::Csv,
+                null: None,
+                delimiter: None,
+                quote: None,
+                escape: None,
+                header: Some(header),
+            };
+            let params =
+                CopyFormatParams::try_from(params).map_err(|e| anyhow!("{}", e.message))?;
+            let rows = mz_pgcopy::decode_copy_format(body, &column_types, params)?;
+            vec![(columns, rows)]
+        }
+        Format::Json => decode_json(body, &desc)?
+            .into_iter()
+            .map(|(present, rows)| (present.into_iter().map(|i| columns[i]).collect(), rows))
+            .collect(),
+    };
+    let mut count = 0;
+    for (columns, rows) in batches {
+        count += rows.len();
+        // Columns that are not in `columns` are filled in with their default
+        // values.
+        coord_client.insert_rows(id, columns, rows).await?;
+    }
+    Ok(count)
+}
+
+fn column_types(desc: &RelationDesc) -> Vec<mz_pgrepr::Type> {
+    desc.typ()
+        .column_types
+        .iter()
+        .map(|typ| mz_pgrepr::Type::from(&typ.scalar_type))
+        .collect()
+}
+
+/// Decodes a JSON object, or an array of JSON objects, into rows of `desc`.
+///
+/// Rows are grouped by the columns of `desc` that are present in their
+/// objects, and contain only the values of those columns. JSON strings,
+/// numbers, and booleans are decoded like their text representation in `COPY`.
+/// Columns of type `jsonb` accept any JSON value.
+fn decode_json(
+    body: &[u8],
+    desc: &RelationDesc,
+) -> Result<BTreeMap<Vec<usize>, Vec<Row>>, anyhow::Error> {
+    let objects = match serde_json::from_slice(body)? {
+        serde_json::Value::Array(values) => values,
+        value @ serde_json::Value::Object(_) => vec![value],
+        _ => bail!("expected a JSON object or an array of JSON objects"),
+    };
+    let column_types = column_types(desc);
+    let mut batches: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for object in objects {
+        let mut object = match object {
+            serde_json::Value::Object(object) => object,
+            _ => bail!("expected a JSON object or an array of JSON objects"),
+        };
+        let arena = RowArena::new();
+        let mut present = Vec::with_capacity(column_types.len());
+        let mut datums = Vec::with_capacity(column_types.len());
+        for (i, (name, typ)) in desc.iter_names().zip(&column_types).enumerate() {
+            let raw = match object.remove(name.as_str()) {
+                None => continue,
+                Some(serde_json::Value::Null) => {
+                    present.push(i);
+                    datums.push(Datum::Null);
+                    continue;
+                }
+                Some(value) if *typ == mz_pgrepr::Type::Jsonb => value.to_string(),
+                Some(serde_json::Value::String(s)) => s,
+                Some(value @ serde_json::Value::Number(_))
+                | Some(value @ serde_json::Value::Bool(_)) => value.to_string(),
+                Some(_) => bail!(
+                    "column {} cannot be decoded from a JSON array or object",
+                    name
+                ),
+            };
+            let value = mz_pgrepr::Value::decode_text(typ, raw.as_bytes())
+                .map_err(|e| anyhow!("unable to decode column {}: {}", name, e))?;
+            present.push(i);
+            datums.push(value.into_datum(&arena, typ));
+        }
+        if let Some(key) = object.keys().next() {
+            bail!("column {} does not exist", key);
+        }
+        batches.entry(present).or_default().push(Row::pack(datums));
+    }
+    Ok(batches)
+}
@@ -294,6 +294,154 @@ fn test_http_sql() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+// Test the /api/tables POST endpoint of the HTTP server.
+#[test]
+fn test_http_tables() -> Result<(), Box<dyn Error>> {
+    let server = util::start_server(util::Config::default())?;
+    let mut client = server.connect(postgres::NoTls)?;
+    client.batch_execute(
+        "CREATE TABLE t (a int, b text DEFAULT 'default', c jsonb);
+         CREATE TABLE \"Mixed Case\" (a int);
+         CREATE VIEW v AS SELECT 1",
+    )?;
+    let base = format!("http://{}/api/tables", server.inner.local_addr());
+
+    struct TestCase {
+        path: &'static str,
+        content_type: &'static str,
+        body: &'static str,
+        status: StatusCode,
+        response: &'static str,
+    }
+
+    let tests = vec![
+        // JSON objects are matched to columns by name.
+        TestCase {
+            path: "/t",
+            content_type: "application/json",
+            body: r#"[{"a": 1, "b": "one", "c": {"x": [1]}}, {"b": "two", "a": "2"}]"#,
+            status: StatusCode::OK,
+            response: r#"{"rows":2}"#,
+        },
+        // A single object is also accepted, and names may be qualified.
+        // Missing keys take the default value of their column, while `null`
+        // is always `NULL`.
+        TestCase {
+            path: "/materialize.public.t",
+            content_type: "application/json; charset=utf-8",
+            body: r#"{"a": 3, "c": null}"#,
+            status: StatusCode::OK,
+            response: r#"{"rows":1}"#,
+        },
+        // Names are parsed like in SQL: unquoted identifiers are case
+        // insensitive, and quoted ones may be percent-encoded.
+        TestCase {
+            path: "/MATERIALIZE.Public.T",
+            content_type: "application/json",
+            body: r#"{"a": 6, "b": null}"#,
+            status: StatusCode::OK,
+            response: r#"{"rows":1}"#,
+        },
+        TestCase {
+            path: "/%22Mixed%20Case%22",
+            content_type: "application/json",
+            body: r#"{"a": 1}"#,
+            status: StatusCode::OK,
+            response: r#"{"rows":1}"#,
+        },
+        TestCase {
+            path: "/t%20u",
+            content_type: "application/json",
+            body: r#"{"a": 1}"#,
+            status: StatusCode::BAD_REQUEST,
+            response: "invalid table name: extra token after object name",
+        },
+        TestCase {
+            path: "/t?header=true",
+            content_type: "text/csv",
+            body: "a,b,c\n4,four,\"{\"\"y\"\": 2}\"\n5,,\n",
+            status: StatusCode::OK,
+            response: r#"{"rows":2}"#,
+        },
+        // Requests are atomic.
+        TestCase {
+            path: "/t",
+            content_type: "application/json",
+            body: r#"[{"a": 7}, {"a": "seven"}]"#,
+            status: StatusCode::BAD_REQUEST,
+            response: r#"unable to decode column a: invalid input syntax for type integer: invalid digit found in string: "seven""#,
+        },
+        TestCase {
+            path: "/t",
+            content_type: "application/json",
+            body: r#"{"d": 1}"#,
+            status: StatusCode::BAD_REQUEST,
+            response: "column d does not exist",
+        },
+        TestCase {
+            path: "/t",
+            content_type: "application/json",
+            body: r#"{"b": ["x"]}"#,
+            status: StatusCode::BAD_REQUEST,
+            response: "column b cannot be decoded from a JSON array or object",
+        },
+        TestCase {
+            path: "/t",
+            content_type: "text/plain",
+            body: "1",
+            status: StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            response: r#"unsupported content type "text/plain"; expected text/csv or application/json"#,
+        },
+        TestCase {
+            path: "/v",
+            content_type: "text/csv",
+            body: "1",
+            status: StatusCode::BAD_REQUEST,
+            response: "cannot insert into view 'materialize.public.v'",
+        },
+        TestCase {
+            path: "/noexist",
+            content_type: "text/csv",
+            body: "1",
+            status: StatusCode::BAD_REQUEST,
+            response: "unknown catalog item 'noexist'",
+        },
+    ];
+
+    for tc in tests {
+        let res = Client::new()
+            .post(format!("{}{}", base, tc.path))
+            .header("Content-Type", tc.content_type)
+            .body(tc.body)
+            .send()?;
+        assert_eq!(res.status(), tc.status);
+        assert_eq!(res.text()?, tc.response);
+    }
+
+    let rows: Vec<(Option<i32>, Option<String>, Option<String>)> = client
+        .query("SELECT a, b, c::text FROM t ORDER BY a", &[])?
+        .into_iter()
+        .map(|row| (row.get(0), row.get(1), row.get(2)))
+        .collect();
+    assert_eq!(
+        rows,
+        vec![
+            (Some(1), Some("one".into()), Some(r#"{"x":[1]}"#.into())),
+            (Some(2), Some("two".into()), None),
+            (Some(3), Some("default".into()), None),
+            (Some(4), Some("four".into()), Some(r#"{"y":2}"#.into())),
+            (Some(5), None, None),
+            (Some(6), None, None),
+        ]
+    );
+    let count: i64 = client
+        .query_one(r#"SELECT count(*) FROM "Mixed Case""#, &[])?
+        .get(0);
+    assert_eq!(count, 1);
+
+    Ok(())
+}
+
 // Test that the server properly handles cancellation requests.
 #[test]
 fn test_cancel_long_running_query() -> Result<(), Box<dyn Error>> {
@@ -17,20 +17,30 @@ use anyhow::anyhow;
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
 use differential_dataflow::trace::Description;
-use mz_persist::location::{Consensus, ExternalError, SeqNo, VersionedData};
+use mz_persist::location::{Atomicity, BlobMulti, Consensus, ExternalError, SeqNo, VersionedData};
 use mz_persist_types::{Codec, Codec64};
 use timely::progress::{Antichain, Timestamp};
-use tracing::debug;
+use tracing::{debug, info};
+use uuid::Uuid;
 
 use crate::error::{InvalidUsage, NoOp};
 use crate::r#impl::state::{ReadCapability, State, WriteCapability};
+use crate::r#impl::state_diff::{StateDiff, StateFieldValDiff};
 use crate::read::ReaderId;
 use crate::write::WriterId;
 use crate::ShardId;
 
+/// The number of state transitions between rollups of the shard's state.
+///
+/// Every time a state transition lands on a seqno that is a multiple of this,
+/// the full state is written to blob and consensus is truncated up to it. This
+/// bounds the number of diffs a reader has to apply to reconstruct state.
+pub const STATE_ROLLUP_INTERVAL: u64 = 64;
+
 #[derive(Debug)]
 pub struct Machine<K, V, T, D> {
     consensus: Arc<dyn Consensus + Send + Sync>,
+    blob: Arc<dyn BlobMulti + Send + Sync>,
 
     seqno: Option<SeqNo>,
     state: State<K, V, T, D>,
@@ -41,6 +51,7 @@ impl<K, V, T: Clone, D> Clone for Machine<K, V, T, D> {
     fn clone(&self) -> Self {
         Self {
             consensus: Arc::clone(&self.consensus),
+            blob: Arc::clone(&self.blob),
             seqno: self.seqno.clone(),
             state: self.state.clone(),
         }
@@ -54,9 +65,14 @@ where
     T: Timestamp + Lattice + Codec64,
     D: Semigroup + Codec64,
 {
-    pub fn new(shard_id: ShardId, consensus: Arc<dyn Consensus + Send + Sync>) -> Self {
+    pub fn new(
+        shard_id: ShardId,
+        consensus: Arc<dyn Consensus + Send + Sync>,
+        blob: Arc<dyn BlobMulti + Send + Sync>,
+    ) -> Self {
         Machine {
             consensus,
+            blob,
             seqno: None,
             state: State::new(shard_id),
         }
@@ -66,6 +82,17 @@ where
         self.state.shard_id()
     }
 
+    /// The seqno of the cached copy of state, or None if the shard hasn't been
+    /// initialized (as of the last time state was fetched).
+    pub fn seqno(&self) -> Option<SeqNo> {
+        self.seqno
+    }
+
+    /// The cached copy of state, as of the last time it was fetched.
+    pub fn state(&self) -> &State<K, V, T, D> {
+        &self.state
+    }
+
     pub async fn register(
         &mut self,
         deadline: Instant,
@@ -234,6 +261,36 @@ where
         R,
         E,
         WorkFn: FnMut(SeqNo, &mut State<K, V, T, D>) -> Result<R, E>,
+    >(
+        &mut self,
+        deadline: Instant,
+        work_fn: WorkFn,
+    ) -> Result<Result<(SeqNo, R), E>, ExternalError> {
+        let res = self
+            .apply_unbatched_cmd_no_rollup(deadline, work_fn)
+            .await?;
+        if let Ok((seqno, _)) = &res {
+            if seqno.0 % STATE_ROLLUP_INTERVAL == 0 {
+                // A failure to write a rollup doesn't affect the correctness
+                // of the command that was just applied (or of any future
+                // ones), so only log it. Some later seqno will try again.
+                if let Err(err) = self.write_rollup(deadline).await {
+                    info!(
+                        "failed to write rollup for shard {} at {:?}: {:?}",
+                        self.shard_id(),
+                        seqno,
+                        err
+                    );
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    async fn apply_unbatched_cmd_no_rollup<
+        R,
+        E,
+        WorkFn: FnMut(SeqNo, &mut State<K, V, T, D>) -> Result<R, E>,
     >(
         &mut self,
         deadline: Instant,
@@ -242,15 +299,17 @@ where
         loop {
             let shard_id = self.state.shard_id();
 
-            let new_seqno = self.seqno.unwrap_or_default().next();
+            let seqno = self.seqno.unwrap_or_default();
+            let new_seqno = seqno.next();
             let mut new_state = self.state.clone();
             let work_ret = match work_fn(new_seqno, &mut new_state) {
                 Ok(x) => x,
                 Err(err) => return Ok(Err(err)),
             };
 
+            let diff = StateDiff::from_diff(seqno, new_seqno, &self.state, &new_state);
             let mut value = Vec::new();
-            new_state.encode(&mut value);
+            diff.encode::<K, V, D, _>(&mut value);
             let cas_res = self
                 .consensus
                 .compare_and_set(
@@ -275,7 +334,7 @@ where
                         self.seqno,
                         current.as_ref().map(|x| x.seqno)
                     );
-                    self.update_state(current).await?;
+                    self.update_state(deadline, current).await?;
 
                     // TODO: Some sort of exponential backoff here?
                     let sleep = Duration::from_secs(0);
@@ -288,13 +347,65 @@ where
         }
     }
 
-    async fn fetch_and_update_state(&mut self, deadline: Instant) -> Result<(), ExternalError> {
+    /// Writes a copy of the current state to blob, registers it as the latest
+    /// rollup, and then truncates everything in consensus that is no longer
+    /// needed to reconstruct the state.
+    async fn write_rollup(&mut self, deadline: Instant) -> Result<(), ExternalError> {
+        let rollup_seqno = match self.seqno {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let key = Uuid::new_v4().to_string();
+        let mut value = Vec::new();
+        self.state.encode(&mut value);
+        self.blob
+            .set(deadline, &key, value, Atomicity::RequireAtomic)
+            .await?;
+
+        let res = self
+            .apply_unbatched_cmd_no_rollup(deadline, |seqno, state| {
+                state.add_and_remove_rollups(seqno, rollup_seqno, &key)
+            })
+            .await?;
+        let removed = match res {
+            Ok((_, removed)) => removed,
+            Err(NoOp { .. }) => {
+                // Someone else beat us to it, clean up the rollup we wrote.
+                return self.blob.delete(deadline, &key).await;
+            }
+        };
+
+        // Now that the new rollup is durably registered, nothing needs the
+        // diffs before it or the rollups it replaced.
+        self.consensus
+            .truncate(&self.shard_id().to_string(), deadline, rollup_seqno)
+            .await?;
+        for key in removed {
+            self.blob.delete(deadline, &key).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn fetch_and_update_state(&mut self, deadline: Instant) -> Result<(), ExternalError> {
         let shard_id = self.shard_id();
-        let current = self.consensus.head(&shard_id.to_string(), deadline).await?;
-        self.update_state(current).await
+        let from = self.seqno.map_or_else(SeqNo::default, |x| x.next());
+        let diffs = self
+            .consensus
+            .scan(&shard_id.to_string(), deadline, from)
+            .await?;
+        if self.apply_diffs(&diffs)? {
+            return Ok(());
+        }
+        // The diffs we need to catch up incrementally have been truncated
+        // away, start over from the latest rollup.
+        self.fetch_and_update_state_from_rollup(deadline).await
     }
 
-    async fn update_state(&mut self, current: Option<VersionedData>) -> Result<(), ExternalError> {
+    async fn update_state(
+        &mut self,
+        deadline: Instant,
+        current: Option<VersionedData>,
+    ) -> Result<(), ExternalError> {
         let current = match current {
             Some(x) => x,
             None => {
@@ -303,11 +414,140 @@ where
                 return Ok(());
             }
         };
-        let current_state = State::decode(&current.data)
-            .map_err(|err| ExternalError::from(anyhow!("unable to decode state: {}", err)))?;
         debug_assert!(self.seqno.unwrap_or_default() <= current.seqno);
-        self.seqno = Some(current.seqno);
-        self.state = current_state;
+        // Fast path: if the current version is the one immediately after ours,
+        // we have everything we need to apply it.
+        if self.apply_diffs(std::slice::from_ref(&current))? {
+            return Ok(());
+        }
+        self.fetch_and_update_state(deadline).await
+    }
+
+    /// Attempts to incrementally apply `diffs` (in ascending seqno order) to
+    /// our cached state.
+    ///
+    /// Returns false, leaving the cached state untouched, if the diffs don't
+    /// start at our current seqno.
+    fn apply_diffs(&mut self, diffs: &[VersionedData]) -> Result<bool, ExternalError> {
+        let first = match diffs.first() {
+            Some(x) => x,
+            None => return Ok(true),
+        };
+        let first = Self::decode_diff(first)?;
+        if first.seqno_from != self.seqno.unwrap_or_default() {
+            return Ok(false);
+        }
+        let mut seqno = self.seqno.unwrap_or_default();
+        let mut state = self.state.clone();
+        for diff in diffs {
+            let diff = Self::decode_diff(diff)?;
+            Self::apply_diff(&mut seqno, &mut state, &diff)?;
+        }
+        self.seqno = Some(seqno);
+        self.state = state;
+        Ok(true)
+    }
+
+    async fn fetch_and_update_state_from_rollup(
+        &mut self,
+        deadline: Instant,
+    ) -> Result<(), ExternalError> {
+        let shard_id = self.shard_id();
+        loop {
+            let diffs = self
+                .consensus
+                .scan(&shard_id.to_string(), deadline, SeqNo::default())
+                .await?
+                .iter()
+                .map(Self::decode_diff)
+                .collect::<Result<Vec<_>, _>>()?;
+            if diffs.is_empty() {
+                self.seqno = None;
+                self.state = State::new(shard_id);
+                return Ok(());
+            }
+
+            // Consensus is only ever truncated up to a rollup after the diff
+            // registering that rollup is durable, so the live diffs always
+            // include the registration of the latest rollup (if any).
+            let latest_rollup = diffs
+                .iter()
+                .flat_map(|diff| diff.rollups.iter())
+                .filter_map(|diff| match &diff.val {
+                    StateFieldValDiff::Insert(key) => Some((diff.key, key)),
+                    _ => None,
+                })
+                .max_by_key(|(seqno, _)| *seqno);
+            let (mut seqno, mut state) = match latest_rollup {
+                Some((rollup_seqno, key)) => {
+                    let value = match self.blob.get(deadline, key).await? {
+                        Some(x) => x,
+                        None => {
+                            // The rollup was replaced (and deleted) after we
+                            // scanned consensus. Try again.
+                            //
+                            // TODO: This should increment a counter.
+                            info!(
+                                "rollup {} for shard {} was concurrently deleted, retrying",
+                                key, shard_id
+                            );
+                            if Instant::now() > deadline {
+                                return Err(ExternalError::from(anyhow!(
+                                    "timeout at {:?}",
+                                    deadline
+                                )));
+                            }
+                            continue;
+                        }
+                    };
+                    let state = State::decode(&value).map_err(|err| {
+                        ExternalError::from(anyhow!("unable to decode state rollup: {}", err))
+                    })?;
+                    (rollup_seqno, state)
+                }
+                None => (SeqNo::default(), State::new(shard_id)),
+            };
+            for diff in diffs.iter() {
+                if diff.seqno_to <= seqno {
+                    // Already included in the rollup.
+                    continue;
+                }
+                Self::apply_diff(&mut seqno, &mut state, diff)?;
+            }
+            self.seqno = Some(seqno);
+            self.state = state;
+            return Ok(());
+        }
+    }
+
+    fn decode_diff(data: &VersionedData) -> Result<StateDiff<T>, ExternalError> {
+        let diff = StateDiff::decode::<K, V, D>(&data.data)
+            .map_err(|err| ExternalError::from(anyhow!("unable to decode state diff: {}", err)))?;
+        if diff.seqno_to != data.seqno {
+            return Err(ExternalError::from(anyhow!(
+                "state diff to {:?} stored at {:?}",
+                diff.seqno_to,
+                data.seqno
+            )));
+        }
+        Ok(diff)
+    }
+
+    fn apply_diff(
+        seqno: &mut SeqNo,
+        state: &mut State<K, V, T, D>,
+        diff: &StateDiff<T>,
+    ) -> Result<(), ExternalError> {
+        if diff.seqno_from != *seqno {
+            return Err(ExternalError::from(anyhow!(
+                "state diff from {:?} cannot be applied to state at {:?}",
+                diff.seqno_from,
+                seqno
+            )));
+        }
+        diff.apply(state)
+            .map_err(|err| ExternalError::from(anyhow!("unable to apply state diff: {}", err)))?;
+        *seqno = diff.seqno_to;
         Ok(())
     }
 }
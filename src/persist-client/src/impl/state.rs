@@ -7,7 +7,7 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::marker::PhantomData;
 
 use anyhow::anyhow;
@@ -38,15 +38,23 @@ pub struct WriteCapability<T> {
 // TODO: Document invariants.
 #[derive(Debug)]
 pub struct State<K, V, T, D> {
-    shard_id: ShardId,
+    pub(crate) shard_id: ShardId,
+
+    pub(crate) writers: HashMap<WriterId, WriteCapability<T>>,
+    pub(crate) readers: HashMap<ReaderId, ReadCapability<T>>,
 
-    writers: HashMap<WriterId, WriteCapability<T>>,
-    readers: HashMap<ReaderId, ReadCapability<T>>,
+    pub(crate) since: Antichain<T>,
+    pub(crate) trace: Vec<(Vec<String>, Description<T>)>,
 
-    since: Antichain<T>,
-    trace: Vec<(Vec<String>, Description<T>)>,
+    /// Blob keys of complete copies of this state, keyed by the seqno of the
+    /// state they contain.
+    ///
+    /// The state stored in consensus is a sequence of diffs, so readers
+    /// reconstruct it by starting from the latest rollup and applying every
+    /// subsequent diff.
+    pub(crate) rollups: BTreeMap<SeqNo, String>,
 
-    _phantom: PhantomData<(K, V, D)>,
+    pub(crate) _phantom: PhantomData<(K, V, D)>,
 }
 
 // Impl Clone regardless of the type params.
@@ -58,6 +66,7 @@ impl<K, V, T: Clone, D> Clone for State<K, V, T, D> {
             readers: self.readers.clone(),
             since: self.since.clone(),
             trace: self.trace.clone(),
+            rollups: self.rollups.clone(),
             _phantom: self._phantom.clone(),
         }
     }
@@ -77,6 +86,7 @@ where
             readers: HashMap::new(),
             since: Antichain::from_elem(T::minimum()),
             trace: Vec::new(),
+            rollups: BTreeMap::new(),
             _phantom: PhantomData,
         }
     }
@@ -205,6 +215,36 @@ where
         Ok(())
     }
 
+    /// Records that `key` holds a rollup of this state as of `rollup_seqno`,
+    /// replacing any previous rollups.
+    ///
+    /// Returns the blob keys of the replaced rollups, which may be deleted once
+    /// this state transition is durable.
+    pub fn add_and_remove_rollups(
+        &mut self,
+        seqno: SeqNo,
+        rollup_seqno: SeqNo,
+        key: &str,
+    ) -> Result<Vec<String>, NoOp> {
+        if let Some((latest_seqno, _)) = self.latest_rollup() {
+            if latest_seqno >= rollup_seqno {
+                // Someone else already wrote a rollup that is at least as new
+                // as this one.
+                return Err(NoOp { seqno });
+            }
+        }
+        let removed = std::mem::take(&mut self.rollups).into_values().collect();
+        self.rollups.insert(rollup_seqno, key.to_owned());
+        Ok(removed)
+    }
+
+    pub fn latest_rollup(&self) -> Option<(SeqNo, &String)> {
+        self.rollups
+            .iter()
+            .next_back()
+            .map(|(seqno, key)| (*seqno, key))
+    }
+
     pub fn snapshot(&self, as_of: &Antichain<T>) -> Result<Vec<String>, InvalidUsage> {
         if PartialOrder::less_than(as_of, &self.since) {
             return Err(InvalidUsage(anyhow!(
@@ -243,7 +283,7 @@ where
         return None;
     }
 
-    fn upper(&self) -> Antichain<T> {
+    pub fn upper(&self) -> Antichain<T> {
         self.trace.last().map_or_else(
             || Antichain::from_elem(T::minimum()),
             |(_, desc)| desc.upper().clone(),
@@ -309,7 +349,7 @@ where
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct AntichainMeta(Vec<[u8; 8]>);
+pub(crate) struct AntichainMeta(Vec<[u8; 8]>);
 
 impl<T: Codec64> From<&Antichain<T>> for AntichainMeta {
     fn from(x: &Antichain<T>) -> Self {
@@ -324,7 +364,7 @@ impl<T: Timestamp + Codec64> From<&AntichainMeta> for Antichain<T> {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-struct DescriptionMeta {
+pub(crate) struct DescriptionMeta {
     lower: AntichainMeta,
     upper: AntichainMeta,
     since: AntichainMeta,
@@ -358,6 +398,7 @@ struct StateRollupMeta {
     readers: Vec<(ReaderId, AntichainMeta, SeqNo)>,
     since: AntichainMeta,
     trace: Vec<(Vec<String>, DescriptionMeta)>,
+    rollups: Vec<(SeqNo, String)>,
 }
 
 impl<K, V, T, D> From<&State<K, V, T, D>> for StateRollupMeta
@@ -390,6 +431,11 @@ where
                 .iter()
                 .map(|(key, desc)| (key.clone(), desc.into()))
                 .collect(),
+            rollups: x
+                .rollups
+                .iter()
+                .map(|(seqno, key)| (*seqno, key.clone()))
+                .collect(),
         }
     }
 }
@@ -461,6 +507,7 @@ where
                 .iter()
                 .map(|(key, desc)| (key.clone(), desc.into()))
                 .collect(),
+            rollups: x.rollups.iter().cloned().collect(),
             _phantom: PhantomData,
         })
     }
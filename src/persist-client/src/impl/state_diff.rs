@@ -0,0 +1,504 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Incremental representation of persist state transitions.
+
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use bytes::BufMut;
+use differential_dataflow::lattice::Lattice;
+use differential_dataflow::trace::Description;
+use mz_persist::location::SeqNo;
+use mz_persist_types::{Codec, Codec64};
+use serde::{Deserialize, Serialize};
+use timely::progress::{Antichain, Timestamp};
+
+use crate::inspect::ShardCodecs;
+use crate::r#impl::state::{
+    AntichainMeta, DescriptionMeta, ReadCapability, State, WriteCapability,
+};
+use crate::read::ReaderId;
+use crate::write::WriterId;
+
+/// The change to a single value in one of the fields of [State].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum StateFieldValDiff<V> {
+    Insert(V),
+    Update(V, V),
+    Delete(V),
+}
+
+/// The change to a single entry (identified by `key`) in one of the fields of
+/// [State].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateFieldDiff<K, V> {
+    pub key: K,
+    pub val: StateFieldValDiff<V>,
+}
+
+/// The difference between the [State]s at two consecutive seqnos.
+///
+/// This is what gets written to consensus for each state transition, so its
+/// size is proportional to the size of the change instead of to the size of
+/// the entire shard.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateDiff<T> {
+    pub seqno_from: SeqNo,
+    pub seqno_to: SeqNo,
+    pub writers: Vec<StateFieldDiff<WriterId, WriteCapability<T>>>,
+    pub readers: Vec<StateFieldDiff<ReaderId, ReadCapability<T>>>,
+    pub since: Vec<StateFieldDiff<(), Antichain<T>>>,
+    pub trace: Vec<StateFieldDiff<usize, (Vec<String>, Description<T>)>>,
+    pub rollups: Vec<StateFieldDiff<SeqNo, String>>,
+}
+
+impl<T: Timestamp + Lattice + Codec64> StateDiff<T> {
+    /// Computes the diff that transforms `from` (the state at `seqno_from`)
+    /// into `to` (the state at `seqno_to`).
+    pub fn from_diff<K, V, D>(
+        seqno_from: SeqNo,
+        seqno_to: SeqNo,
+        from: &State<K, V, T, D>,
+        to: &State<K, V, T, D>,
+    ) -> Self {
+        debug_assert_eq!(from.shard_id, to.shard_id);
+        let mut diff = StateDiff {
+            seqno_from,
+            seqno_to,
+            writers: Vec::new(),
+            readers: Vec::new(),
+            since: Vec::new(),
+            trace: Vec::new(),
+            rollups: Vec::new(),
+        };
+        diff_field(
+            from.writers.iter().map(|(k, v)| (k.clone(), v)),
+            to.writers.iter().map(|(k, v)| (k.clone(), v)),
+            &mut diff.writers,
+        );
+        diff_field(
+            from.readers.iter().map(|(k, v)| (k.clone(), v)),
+            to.readers.iter().map(|(k, v)| (k.clone(), v)),
+            &mut diff.readers,
+        );
+        diff_field(
+            std::iter::once(((), &from.since)),
+            std::iter::once(((), &to.since)),
+            &mut diff.since,
+        );
+        diff_field(
+            from.trace.iter().enumerate(),
+            to.trace.iter().enumerate(),
+            &mut diff.trace,
+        );
+        diff_field(
+            from.rollups.iter().map(|(k, v)| (*k, v)),
+            to.rollups.iter().map(|(k, v)| (*k, v)),
+            &mut diff.rollups,
+        );
+        // Sort the trace diffs so they can be applied in order.
+        diff.trace.sort_by_key(|x| x.key);
+        diff
+    }
+
+    /// Applies this diff to `state`, which must be the state at
+    /// `self.seqno_from`.
+    ///
+    /// Returns an error (and leaves `state` in an unspecified condition) if the
+    /// diff is not consistent with `state`.
+    pub fn apply<K, V, D>(&self, state: &mut State<K, V, T, D>) -> Result<(), String> {
+        apply_diffs_map("writers", &self.writers, &mut state.writers)?;
+        apply_diffs_map("readers", &self.readers, &mut state.readers)?;
+        for diff in self.since.iter() {
+            match &diff.val {
+                StateFieldValDiff::Update(from, to) if from == &state.since => {
+                    state.since.clone_from(to)
+                }
+                x => return Err(format!("invalid since diff {:?} for {:?}", x, state.since)),
+            }
+        }
+        apply_diffs_trace(&self.trace, &mut state.trace)?;
+        for diff in self.rollups.iter() {
+            let current = state.rollups.get(&diff.key);
+            match (&diff.val, current) {
+                (StateFieldValDiff::Insert(to), None) => {
+                    state.rollups.insert(diff.key, to.clone());
+                }
+                (StateFieldValDiff::Update(from, to), Some(current)) if from == current => {
+                    state.rollups.insert(diff.key, to.clone());
+                }
+                (StateFieldValDiff::Delete(from), Some(current)) if from == current => {
+                    state.rollups.remove(&diff.key);
+                }
+                (x, current) => {
+                    return Err(format!(
+                        "invalid rollups diff {:?} for {:?}: {:?}",
+                        x, diff.key, current
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes this diff, including the codecs of the shard it belongs to.
+    pub fn encode<K, V, D, B>(&self, buf: &mut B)
+    where
+        K: Codec,
+        V: Codec,
+        D: Codec64,
+        B: BufMut,
+    {
+        let meta = StateDiffMeta {
+            key_codec: K::codec_name(),
+            val_codec: V::codec_name(),
+            ts_codec: T::codec_name(),
+            diff_codec: D::codec_name(),
+            seqno_from: self.seqno_from,
+            seqno_to: self.seqno_to,
+            writers: map_diffs(&self.writers, |k| k.clone(), |v| (&v.upper).into()),
+            readers: map_diffs(
+                &self.readers,
+                |k| k.clone(),
+                |v| ((&v.since).into(), v.seqno),
+            ),
+            since: map_diffs(&self.since, |_| (), |v| v.into()),
+            trace: map_diffs(
+                &self.trace,
+                |k| u64::try_from(*k).expect("trace index should fit in u64"),
+                |(keys, desc)| (keys.clone(), desc.into()),
+            ),
+            rollups: map_diffs(&self.rollups, |k| *k, |v| v.clone()),
+        };
+        let bytes = bincode::serialize(&meta).expect("unable to serialize StateDiff");
+        buf.put_slice(&bytes);
+    }
+
+    /// Decodes a diff previously encoded by [Self::encode], validating that it
+    /// belongs to a shard with the given codecs.
+    pub fn decode<K, V, D>(buf: &[u8]) -> Result<Self, String>
+    where
+        K: Codec,
+        V: Codec,
+        D: Codec64,
+    {
+        let x: StateDiffMeta = bincode::deserialize(buf)
+            .map_err(|err| format!("unable to decode state diff: {}", err))?;
+        let codecs = [
+            ("key_codec", K::codec_name(), &x.key_codec),
+            ("val_codec", V::codec_name(), &x.val_codec),
+            ("ts_codec", T::codec_name(), &x.ts_codec),
+            ("diff_codec", D::codec_name(), &x.diff_codec),
+        ];
+        for (name, expected, actual) in codecs {
+            if &expected != actual {
+                return Err(format!(
+                    "{} {} doesn't match original: {}",
+                    name, expected, actual
+                ));
+            }
+        }
+        Ok(StateDiff {
+            seqno_from: x.seqno_from,
+            seqno_to: x.seqno_to,
+            writers: map_diffs(
+                &x.writers,
+                |k| k.clone(),
+                |v| WriteCapability { upper: v.into() },
+            ),
+            readers: map_diffs(
+                &x.readers,
+                |k| k.clone(),
+                |(since, seqno)| ReadCapability {
+                    seqno: *seqno,
+                    since: since.into(),
+                },
+            ),
+            since: map_diffs(&x.since, |_| (), |v| v.into()),
+            trace: map_diffs(
+                &x.trace,
+                |k| usize::try_from(*k).expect("trace index should fit in usize"),
+                |(keys, desc)| (keys.clone(), desc.into()),
+            ),
+            rollups: map_diffs(&x.rollups, |k| *k, |v| v.clone()),
+        })
+    }
+}
+
+/// Decodes the codecs of the shard that a diff previously encoded by
+/// [StateDiff::encode] belongs to, without validating them against any
+/// particular types.
+pub fn decode_codecs(buf: &[u8]) -> Result<ShardCodecs, String> {
+    let x: StateDiffMeta =
+        bincode::deserialize(buf).map_err(|err| format!("unable to decode state diff: {}", err))?;
+    Ok(ShardCodecs {
+        key: x.key_codec,
+        val: x.val_codec,
+        ts: x.ts_codec,
+        diff: x.diff_codec,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct StateDiffMeta {
+    key_codec: String,
+    val_codec: String,
+    ts_codec: String,
+    diff_codec: String,
+
+    seqno_from: SeqNo,
+    seqno_to: SeqNo,
+    writers: Vec<StateFieldDiff<WriterId, AntichainMeta>>,
+    readers: Vec<StateFieldDiff<ReaderId, (AntichainMeta, SeqNo)>>,
+    since: Vec<StateFieldDiff<(), AntichainMeta>>,
+    trace: Vec<StateFieldDiff<u64, (Vec<String>, DescriptionMeta)>>,
+    rollups: Vec<StateFieldDiff<SeqNo, String>>,
+}
+
+fn map_diffs<K0, V0, K1, V1, KF, VF>(
+    diffs: &[StateFieldDiff<K0, V0>],
+    mut key_fn: KF,
+    mut val_fn: VF,
+) -> Vec<StateFieldDiff<K1, V1>>
+where
+    KF: FnMut(&K0) -> K1,
+    VF: FnMut(&V0) -> V1,
+{
+    diffs
+        .iter()
+        .map(|diff| StateFieldDiff {
+            key: key_fn(&diff.key),
+            val: match &diff.val {
+                StateFieldValDiff::Insert(to) => StateFieldValDiff::Insert(val_fn(to)),
+                StateFieldValDiff::Update(from, to) => {
+                    StateFieldValDiff::Update(val_fn(from), val_fn(to))
+                }
+                StateFieldValDiff::Delete(from) => StateFieldValDiff::Delete(val_fn(from)),
+            },
+        })
+        .collect()
+}
+
+fn diff_field<'a, K, V, FromI, ToI>(from: FromI, to: ToI, diffs: &mut Vec<StateFieldDiff<K, V>>)
+where
+    K: Hash + Eq,
+    V: PartialEq + Clone + 'a,
+    FromI: Iterator<Item = (K, &'a V)>,
+    ToI: Iterator<Item = (K, &'a V)>,
+{
+    let mut from = from.collect::<HashMap<_, _>>();
+    for (k, to_v) in to {
+        match from.remove(&k) {
+            Some(from_v) if from_v == to_v => {}
+            Some(from_v) => diffs.push(StateFieldDiff {
+                key: k,
+                val: StateFieldValDiff::Update(from_v.clone(), to_v.clone()),
+            }),
+            None => diffs.push(StateFieldDiff {
+                key: k,
+                val: StateFieldValDiff::Insert(to_v.clone()),
+            }),
+        }
+    }
+    for (k, from_v) in from {
+        diffs.push(StateFieldDiff {
+            key: k,
+            val: StateFieldValDiff::Delete(from_v.clone()),
+        });
+    }
+}
+
+fn apply_diffs_map<K, V>(
+    name: &str,
+    diffs: &[StateFieldDiff<K, V>],
+    map: &mut HashMap<K, V>,
+) -> Result<(), String>
+where
+    K: Hash + Eq + Clone + Debug,
+    V: PartialEq + Clone + Debug,
+{
+    for diff in diffs {
+        let current = map.get(&diff.key);
+        match (&diff.val, current) {
+            (StateFieldValDiff::Insert(to), None) => {
+                map.insert(diff.key.clone(), to.clone());
+            }
+            (StateFieldValDiff::Update(from, to), Some(current)) if from == current => {
+                map.insert(diff.key.clone(), to.clone());
+            }
+            (StateFieldValDiff::Delete(from), Some(current)) if from == current => {
+                map.remove(&diff.key);
+            }
+            (x, current) => {
+                return Err(format!(
+                    "invalid {} diff {:?} for {:?}: {:?}",
+                    name, x, diff.key, current
+                ))
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_diffs_trace<V>(
+    diffs: &[StateFieldDiff<usize, V>],
+    trace: &mut Vec<V>,
+) -> Result<(), String>
+where
+    V: PartialEq + Clone + Debug,
+{
+    // The trace is a Vec, so deletes have to come off the end (in descending
+    // order of index) and inserts have to go on the end (in ascending order of
+    // index). Updates can happen in place.
+    for diff in diffs.iter().rev() {
+        match &diff.val {
+            StateFieldValDiff::Update(from, to) => match trace.get_mut(diff.key) {
+                Some(current) if current == from => *current = to.clone(),
+                current => {
+                    return Err(format!(
+                        "invalid trace diff {:?} for {}: {:?}",
+                        diff.val, diff.key, current
+                    ))
+                }
+            },
+            StateFieldValDiff::Delete(from) => {
+                if diff.key + 1 != trace.len() || trace.last() != Some(from) {
+                    return Err(format!(
+                        "invalid trace diff {:?} for {}: {:?}",
+                        diff.val,
+                        diff.key,
+                        trace.get(diff.key)
+                    ));
+                }
+                trace.pop();
+            }
+            StateFieldValDiff::Insert(_) => {}
+        }
+    }
+    for diff in diffs.iter() {
+        if let StateFieldValDiff::Insert(to) = &diff.val {
+            if diff.key != trace.len() {
+                return Err(format!(
+                    "invalid trace diff {:?} for {}: trace len {}",
+                    diff.val,
+                    diff.key,
+                    trace.len()
+                ));
+            }
+            trace.push(to.clone());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ShardId;
+
+    use super::*;
+
+    type TestState = State<String, String, u64, i64>;
+
+    fn desc(lower: u64, upper: u64) -> Description<u64> {
+        Description::new(
+            Antichain::from_elem(lower),
+            Antichain::from_elem(upper),
+            Antichain::from_elem(0),
+        )
+    }
+
+    fn assert_roundtrip(seqno: SeqNo, from: &TestState, to: &TestState) {
+        let diff = StateDiff::from_diff(seqno, seqno.next(), from, to);
+
+        let mut buf = Vec::new();
+        diff.encode::<String, String, i64, _>(&mut buf);
+        let decoded =
+            StateDiff::<u64>::decode::<String, String, i64>(&buf).expect("diff should decode");
+        assert_eq!(decoded, diff);
+        assert!(StateDiff::<u64>::decode::<String, (), i64>(&buf).is_err());
+
+        let mut applied = from.clone();
+        decoded.apply(&mut applied).expect("diff should apply");
+        assert_eq!(applied.writers, to.writers);
+        assert_eq!(applied.readers, to.readers);
+        assert_eq!(applied.since, to.since);
+        assert_eq!(applied.trace, to.trace);
+        assert_eq!(applied.rollups, to.rollups);
+    }
+
+    #[test]
+    fn state_diff_roundtrip() {
+        let (writer_id, reader_id) = (WriterId::new(), ReaderId::new());
+        let mut seqno = SeqNo::default();
+        let mut state = TestState::new(ShardId::new());
+
+        let mut transition = |f: &mut dyn FnMut(SeqNo, &mut TestState)| {
+            let mut new_state = state.clone();
+            f(seqno.next(), &mut new_state);
+            assert_roundtrip(seqno, &state, &new_state);
+            seqno = seqno.next();
+            state = new_state;
+        };
+
+        transition(&mut |seqno, state| {
+            let _ = state.register(seqno, &writer_id, &reader_id);
+        });
+        transition(&mut |_, state| {
+            state
+                .compare_and_append(&writer_id, &["b0".to_owned()], &desc(0, 1))
+                .expect("valid usage")
+                .expect("expected upper");
+        });
+        transition(&mut |_, state| {
+            state
+                .append(&writer_id, &["b1".to_owned(), "b2".to_owned()], &desc(1, 3))
+                .expect("valid usage");
+        });
+        transition(&mut |seqno, state| {
+            state
+                .add_and_remove_rollups(seqno, SeqNo(2), "r0")
+                .expect("rollup");
+        });
+        transition(&mut |_, state| {
+            state
+                .downgrade_since(&reader_id, &Antichain::from_elem(2))
+                .expect("valid usage");
+        });
+        transition(&mut |seqno, state| {
+            state
+                .add_and_remove_rollups(seqno, SeqNo(4), "r1")
+                .expect("rollup");
+        });
+        transition(&mut |_, state| {
+            // Exercise diffs that remove entries from the trace.
+            state.trace.pop();
+        });
+        transition(&mut |seqno, state| {
+            state.expire_writer(seqno, &writer_id).expect("expire");
+            state.expire_reader(seqno, &reader_id).expect("expire");
+        });
+    }
+
+    #[test]
+    fn state_diff_apply_mismatch() {
+        let writer_id = WriterId::new();
+        let shard_id = ShardId::new();
+        let empty = TestState::new(shard_id);
+        let mut registered = empty.clone();
+        let _ = registered.register(SeqNo(1), &writer_id, &ReaderId::new());
+
+        // Applying a diff to a state other than the one it was computed from is
+        // an error.
+        let diff = StateDiff::from_diff(SeqNo(0), SeqNo(1), &empty, &registered);
+        let mut state = registered;
+        assert!(diff.apply(&mut state).is_err());
+    }
+}
@@ -0,0 +1,441 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Read-only introspection of the durable state of persist shards.
+//!
+//! Unlike [crate::Client::open], nothing in here registers a reader or writer,
+//! so it is safe to point at a [crate::Location] that is in active use without
+//! holding back the since of any shard.
+
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use differential_dataflow::consolidation::consolidate_updates;
+use differential_dataflow::difference::Semigroup;
+use differential_dataflow::lattice::Lattice;
+use mz_persist::location::{BlobMulti, Consensus, ExternalError};
+use mz_persist_types::{Codec, Codec64};
+use serde::Serialize;
+use timely::progress::{Antichain, Timestamp};
+use timely::PartialOrder;
+use tracing::trace;
+
+use crate::error::InvalidUsage;
+use crate::r#impl::machine::Machine;
+use crate::r#impl::state_diff;
+use crate::read::SnapshotIter;
+use crate::ShardId;
+
+/// The names of the codecs a shard was created with.
+///
+/// These are the values of [Codec::codec_name] and [Codec64::codec_name] for
+/// the types the shard was first opened with, and can be used to pick which
+/// types to inspect it with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ShardCodecs {
+    /// The codec of the shard's keys.
+    pub key: String,
+    /// The codec of the shard's values.
+    pub val: String,
+    /// The codec of the shard's timestamps.
+    pub ts: String,
+    /// The codec of the shard's diffs.
+    pub diff: String,
+}
+
+/// A point-in-time summary of the state of a shard.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StateSummary<T> {
+    /// The shard this is a summary of.
+    pub shard_id: String,
+    /// The sequence number of the state in consensus.
+    pub seqno: u64,
+    /// The codecs the shard was created with.
+    pub codecs: ShardCodecs,
+    /// The since frontier of the shard.
+    pub since: Vec<T>,
+    /// The upper frontier of the shard.
+    pub upper: Vec<T>,
+    /// The registered readers of the shard, sorted by id.
+    pub readers: Vec<ReaderSummary<T>>,
+    /// The registered writers of the shard, sorted by id.
+    pub writers: Vec<WriterSummary<T>>,
+    /// The batches of data in the shard, in order.
+    pub batches: Vec<BatchSummary<T>>,
+    /// The complete copies of the shard's state in blob, sorted by seqno.
+    pub rollups: Vec<RollupSummary>,
+}
+
+/// A summary of a registered reader of a shard.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ReaderSummary<T> {
+    /// The id of the reader.
+    pub reader_id: String,
+    /// The since capability held by the reader.
+    pub since: Vec<T>,
+    /// The seqno of the state the reader last observed.
+    pub seqno: u64,
+}
+
+/// A summary of a registered writer of a shard.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct WriterSummary<T> {
+    /// The id of the writer.
+    pub writer_id: String,
+    /// The upper of the last write by the writer.
+    pub upper: Vec<T>,
+}
+
+/// A summary of a batch of data in a shard.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BatchSummary<T> {
+    /// The blob keys of the parts of the batch.
+    pub keys: Vec<String>,
+    /// The lower frontier of the batch.
+    pub lower: Vec<T>,
+    /// The upper frontier of the batch.
+    pub upper: Vec<T>,
+    /// The since frontier of the batch.
+    pub since: Vec<T>,
+}
+
+/// A summary of a complete copy of a shard's state in blob.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct RollupSummary {
+    /// The seqno of the state contained in the rollup.
+    pub seqno: u64,
+    /// The blob key of the rollup.
+    pub key: String,
+}
+
+impl<T> StateSummary<T> {
+    /// Returns every blob key referenced by this state: the parts of each
+    /// batch as well as the rollups.
+    pub fn blob_keys(&self) -> impl Iterator<Item = &str> {
+        self.batches
+            .iter()
+            .flat_map(|x| x.keys.iter())
+            .chain(self.rollups.iter().map(|x| &x.key))
+            .map(|x| x.as_str())
+    }
+}
+
+/// Returns every shard with state at the given consensus location, sorted by
+/// id.
+pub async fn list_shards(
+    consensus: &(dyn Consensus + Send + Sync),
+    timeout: Duration,
+) -> Result<Vec<ShardId>, ExternalError> {
+    trace!("inspect::list_shards timeout={:?}", timeout);
+    let deadline = Instant::now() + timeout;
+    let keys = consensus.list_keys(deadline).await?;
+    let mut shards = keys
+        .iter()
+        .map(|key| {
+            key.parse::<ShardId>().map_err(|err| {
+                ExternalError::from(anyhow!("unexpected consensus key {}: {}", key, err))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    shards.sort_by_key(|x| x.0);
+    Ok(shards)
+}
+
+/// Returns the codecs that the given shard was created with, or None if the
+/// shard has no state.
+pub async fn fetch_codecs(
+    consensus: &(dyn Consensus + Send + Sync),
+    timeout: Duration,
+    shard_id: ShardId,
+) -> Result<Option<ShardCodecs>, ExternalError> {
+    trace!(
+        "inspect::fetch_codecs timeout={:?} shard_id={:?}",
+        timeout,
+        shard_id
+    );
+    let deadline = Instant::now() + timeout;
+    let head = match consensus.head(&shard_id.to_string(), deadline).await? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let codecs = state_diff::decode_codecs(&head.data)
+        .map_err(|err| ExternalError::from(anyhow!("shard {}: {}", shard_id, err)))?;
+    Ok(Some(codecs))
+}
+
+/// Returns a summary of the latest state of the given shard, or None if the
+/// shard has no state.
+///
+/// The type parameters must match the codecs the shard was created with (see
+/// [fetch_codecs]).
+pub async fn fetch_state<K, V, T, D>(
+    blob: Arc<dyn BlobMulti + Send + Sync>,
+    consensus: Arc<dyn Consensus + Send + Sync>,
+    timeout: Duration,
+    shard_id: ShardId,
+) -> Result<Option<StateSummary<T>>, ExternalError>
+where
+    K: Debug + Codec,
+    V: Debug + Codec,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64,
+{
+    trace!(
+        "inspect::fetch_state timeout={:?} shard_id={:?}",
+        timeout,
+        shard_id
+    );
+    let deadline = Instant::now() + timeout;
+    let mut machine = Machine::<K, V, T, D>::new(shard_id, consensus, blob);
+    machine.fetch_and_update_state(deadline).await?;
+    let seqno = match machine.seqno() {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+    let state = machine.state();
+
+    let mut readers = state
+        .readers
+        .iter()
+        .map(|(id, cap)| ReaderSummary {
+            reader_id: id.to_string(),
+            since: cap.since.elements().to_vec(),
+            seqno: cap.seqno.0,
+        })
+        .collect::<Vec<_>>();
+    readers.sort_by(|a, b| a.reader_id.cmp(&b.reader_id));
+    let mut writers = state
+        .writers
+        .iter()
+        .map(|(id, cap)| WriterSummary {
+            writer_id: id.to_string(),
+            upper: cap.upper.elements().to_vec(),
+        })
+        .collect::<Vec<_>>();
+    writers.sort_by(|a, b| a.writer_id.cmp(&b.writer_id));
+    let batches = state
+        .trace
+        .iter()
+        .map(|(keys, desc)| BatchSummary {
+            keys: keys.clone(),
+            lower: desc.lower().elements().to_vec(),
+            upper: desc.upper().elements().to_vec(),
+            since: desc.since().elements().to_vec(),
+        })
+        .collect();
+    let rollups = state
+        .rollups
+        .iter()
+        .map(|(seqno, key)| RollupSummary {
+            seqno: seqno.0,
+            key: key.clone(),
+        })
+        .collect();
+
+    Ok(Some(StateSummary {
+        shard_id: shard_id.to_string(),
+        seqno: seqno.0,
+        codecs: ShardCodecs {
+            key: K::codec_name(),
+            val: V::codec_name(),
+            ts: T::codec_name(),
+            diff: D::codec_name(),
+        },
+        since: state.since.elements().to_vec(),
+        upper: state.upper().elements().to_vec(),
+        readers,
+        writers,
+        batches,
+        rollups,
+    }))
+}
+
+/// Returns the consolidated contents of the given shard as of `as_of`.
+///
+/// The type parameters must match the codecs the shard was created with (see
+/// [fetch_codecs]). Every returned timestamp is advanced to `as_of`.
+///
+/// The clunky two-level Result is to enable more obvious error handling in the
+/// caller. See <http://sled.rs/errors.html> for details.
+pub async fn fetch_snapshot<K, V, T, D>(
+    blob: Arc<dyn BlobMulti + Send + Sync>,
+    consensus: Arc<dyn Consensus + Send + Sync>,
+    timeout: Duration,
+    shard_id: ShardId,
+    as_of: Antichain<T>,
+) -> Result<Result<Vec<((Result<K, String>, Result<V, String>), T, D)>, InvalidUsage>, ExternalError>
+where
+    K: Debug + Codec + Ord,
+    V: Debug + Codec + Ord,
+    T: Timestamp + Lattice + Codec64,
+    D: Semigroup + Codec64,
+{
+    trace!(
+        "inspect::fetch_snapshot timeout={:?} shard_id={:?} as_of={:?}",
+        timeout,
+        shard_id,
+        as_of
+    );
+    let deadline = Instant::now() + timeout;
+    let mut machine = Machine::<K, V, T, D>::new(shard_id, consensus, Arc::clone(&blob));
+    let batches = match machine.snapshot(deadline, &as_of).await? {
+        Ok(x) => x,
+        Err(err) => return Ok(Err(err)),
+    };
+    // Nothing waits for the upper to advance here, so a snapshot at a time
+    // that isn't yet closed would silently be missing data.
+    let upper = machine.state().upper();
+    if !PartialOrder::less_than(&as_of, &upper) {
+        return Ok(Err(InvalidUsage(anyhow!(
+            "snapshot with as_of {:?} cannot be served by shard with upper: {:?}",
+            as_of,
+            upper
+        ))));
+    }
+
+    let mut iter = SnapshotIter::<K, V, T, D>::new(as_of.clone(), batches, blob);
+    let mut ret = Vec::new();
+    loop {
+        let timeout = deadline.saturating_duration_since(Instant::now());
+        let mut next = iter.poll_next(timeout).await?;
+        if next.is_empty() {
+            break;
+        }
+        ret.append(&mut next);
+    }
+    for (_, t, _) in ret.iter_mut() {
+        t.advance_by(as_of.borrow());
+    }
+    consolidate_updates(&mut ret);
+    Ok(Ok(ret))
+}
+
+#[cfg(test)]
+mod tests {
+    use mz_persist::mem::{MemBlobMulti, MemBlobMultiConfig, MemConsensus};
+
+    use crate::{Client, NO_TIMEOUT};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn inspect() -> Result<(), Box<dyn std::error::Error>> {
+        mz_ore::test::init_logging();
+
+        let blob = Arc::new(MemBlobMulti::open(MemBlobMultiConfig::default()))
+            as Arc<dyn BlobMulti + Send + Sync>;
+        let consensus = Arc::new(MemConsensus::default()) as Arc<dyn Consensus + Send + Sync>;
+        let client = Client::new(NO_TIMEOUT, Arc::clone(&blob), Arc::clone(&consensus)).await?;
+
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+            (("1".to_owned(), "one".to_owned()), 3, -1),
+        ];
+        let shard_id = ShardId::new();
+        let (mut write, _read) = client
+            .open::<String, String, u64, i64>(NO_TIMEOUT, shard_id)
+            .await?;
+        write.append_slice(&data[..2], 3).await??;
+        write.append_slice(&data[2..], 4).await??;
+
+        // A shard that has never been used has no state.
+        let unused = ShardId::new();
+        assert_eq!(
+            fetch_codecs(consensus.as_ref(), NO_TIMEOUT, unused).await?,
+            None
+        );
+        assert_eq!(
+            fetch_state::<String, String, u64, i64>(
+                Arc::clone(&blob),
+                Arc::clone(&consensus),
+                NO_TIMEOUT,
+                unused,
+            )
+            .await?,
+            None
+        );
+
+        assert_eq!(
+            list_shards(consensus.as_ref(), NO_TIMEOUT).await?,
+            vec![shard_id]
+        );
+        let codecs = ShardCodecs {
+            key: "String".into(),
+            val: "String".into(),
+            ts: "u64".into(),
+            diff: "i64".into(),
+        };
+        assert_eq!(
+            fetch_codecs(consensus.as_ref(), NO_TIMEOUT, shard_id).await?,
+            Some(codecs.clone())
+        );
+
+        let state = fetch_state::<String, String, u64, i64>(
+            Arc::clone(&blob),
+            Arc::clone(&consensus),
+            NO_TIMEOUT,
+            shard_id,
+        )
+        .await?
+        .expect("shard has state");
+        assert_eq!(state.codecs, codecs);
+        assert_eq!(state.since, vec![0]);
+        assert_eq!(state.upper, vec![4]);
+        assert_eq!(state.readers.len(), 1);
+        assert_eq!(state.writers.len(), 1);
+        assert_eq!(state.writers[0].upper, vec![4]);
+        assert_eq!(
+            state
+                .batches
+                .iter()
+                .map(|x| (x.lower.clone(), x.upper.clone()))
+                .collect::<Vec<_>>(),
+            vec![(vec![0], vec![3]), (vec![3], vec![4])]
+        );
+        let mut blob_keys = blob.list_keys(Instant::now() + NO_TIMEOUT).await?;
+        blob_keys.sort();
+        let mut referenced = state.blob_keys().map(|x| x.to_owned()).collect::<Vec<_>>();
+        referenced.sort();
+        assert_eq!(referenced, blob_keys);
+
+        // Inspecting the shard didn't register anything.
+        let state_again = fetch_state::<String, String, u64, i64>(
+            Arc::clone(&blob),
+            Arc::clone(&consensus),
+            NO_TIMEOUT,
+            shard_id,
+        )
+        .await?;
+        assert_eq!(state_again, Some(state));
+
+        // Snapshots are consolidated and advanced to the as_of.
+        let snapshot = |as_of: u64| {
+            fetch_snapshot::<String, String, u64, i64>(
+                Arc::clone(&blob),
+                Arc::clone(&consensus),
+                NO_TIMEOUT,
+                shard_id,
+                Antichain::from_elem(as_of),
+            )
+        };
+        let one = || (Ok("1".to_owned()), Ok("one".to_owned()));
+        let two = || (Ok("2".to_owned()), Ok("two".to_owned()));
+        assert_eq!(snapshot(0).await??, vec![]);
+        assert_eq!(snapshot(2).await??, vec![(one(), 2, 1), (two(), 2, 1)]);
+        assert_eq!(snapshot(3).await??, vec![(two(), 3, 1)]);
+
+        // A snapshot at a time that isn't closed yet is invalid.
+        assert!(snapshot(4).await?.is_err());
+
+        Ok(())
+    }
+}
@@ -20,11 +20,13 @@ use std::fmt::Debug;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+use anyhow::anyhow;
 use differential_dataflow::difference::Semigroup;
 use differential_dataflow::lattice::Lattice;
 use mz_persist::cfg::{BlobMultiConfig, ConsensusConfig};
 use mz_persist::location::{BlobMulti, Consensus, ExternalError};
 use mz_persist_types::{Codec, Codec64};
+use mz_secrets::SecretsReader;
 use serde::{Deserialize, Serialize};
 use timely::progress::Timestamp;
 use tracing::{debug, trace};
@@ -35,15 +37,20 @@ use crate::read::{ReadHandle, ReaderId};
 use crate::write::{WriteHandle, WriterId};
 
 pub mod error;
+pub mod inspect;
 pub mod read;
 pub mod write;
 
+#[cfg(test)]
+pub mod nemesis;
+
 /// An implementation of the public crate interface.
 ///
 /// TODO: Move this to another crate.
 pub(crate) mod r#impl {
     pub mod machine;
     pub mod state;
+    pub mod state_diff;
 }
 
 // Notes
@@ -57,12 +64,9 @@ pub(crate) mod r#impl {
 // - Split errors into definitely failed vs maybe failed variants. More
 //   generally, possibly we just overhaul our errors.
 // - Figure out how to communicate that a lease expired.
-// - Add a cache around location usage.
 // - Crate mz_persist_client shouldn't depend on mz_persist. Pull common code
 //   out into mz_persist_types or a new crate.
 // - Hook up timeouts into various location impls.
-// - Incremental state
-// - After incremental state, state roll-ups and truncation
 // - Permanent storage format for State
 // - Non-polling listener
 // - Impls and tests for setting upper to empty antichain (no more writes)
@@ -71,7 +75,6 @@ pub(crate) mod r#impl {
 // - Leasing
 // - Physical and logical compaction
 // - Garbage collection of blob and consensus data
-// - Nemesis
 // - Benchmarks
 // - Logging
 // - Tests, tests, tests
@@ -93,9 +96,13 @@ pub struct Location {
 
 impl Location {
     /// Opens the associated implementations of [BlobMulti] and [Consensus].
+    ///
+    /// The encryption keys of an encrypted location are secrets, which are read
+    /// from `secrets`.
     pub async fn open(
         &self,
         timeout: Duration,
+        secrets: Option<&dyn SecretsReader>,
     ) -> Result<
         (
             Arc<dyn BlobMulti + Send + Sync>,
@@ -108,13 +115,18 @@ impl Location {
             "Location::open timeout={:?} blob={} consensus={}",
             timeout, self.blob_uri, self.consensus_uri,
         );
+        let read_key = |key_id: &str| -> Result<Vec<u8>, anyhow::Error> {
+            let secrets =
+                secrets.ok_or_else(|| anyhow!("no secrets available to read key from"))?;
+            secrets.read(key_id.parse()?)
+        };
         let blob = BlobMultiConfig::try_from(&self.blob_uri)
             .await?
-            .open(deadline)
+            .open(deadline, &read_key)
             .await?;
         let consensus = ConsensusConfig::try_from(&self.consensus_uri)
             .await?
-            .open(deadline)
+            .open(deadline, &read_key)
             .await?;
         Ok((blob, consensus))
     }
@@ -144,6 +156,19 @@ impl ShardId {
     }
 }
 
+impl std::str::FromStr for ShardId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let uuid = s
+            .strip_prefix('s')
+            .ok_or_else(|| format!("invalid ShardId {}: must start with 's'", s))?;
+        let uuid =
+            Uuid::parse_str(uuid).map_err(|err| format!("invalid ShardId {}: {}", s, err))?;
+        Ok(ShardId(*uuid.as_bytes()))
+    }
+}
+
 /// A handle for interacting with the set of persist shard made durable at a
 /// single [Location].
 #[derive(Debug)]
@@ -159,6 +184,10 @@ impl Client {
     /// The same `location` may be used concurrently from multiple processes.
     /// Concurrent usage is subject to the constraints documented on individual
     /// methods (mostly [WriteHandle::append]).
+    ///
+    /// All handles opened by this client share `blob`, so wrapping it in a
+    /// [mz_persist::cache::CachingBlob] lets readers of the same shard avoid
+    /// refetching the same batch parts.
     pub async fn new(
         timeout: Duration,
         blob: Arc<dyn BlobMulti + Send + Sync>,
@@ -201,7 +230,11 @@ impl Client {
     {
         trace!("Client::open timeout={:?} shard_id={:?}", timeout, shard_id);
         let deadline = Instant::now() + timeout;
-        let mut machine = Machine::new(shard_id, Arc::clone(&self.consensus));
+        let mut machine = Machine::new(
+            shard_id,
+            Arc::clone(&self.consensus),
+            Arc::clone(&self.blob),
+        );
         let (writer_id, reader_id) = (WriterId::new(), ReaderId::new());
         let (write_cap, read_cap) = machine.register(deadline, &writer_id, &reader_id).await?;
         let writer = WriteHandle {
@@ -226,9 +259,13 @@ const NO_TIMEOUT: Duration = Duration::from_secs(1_000_000);
 
 #[cfg(test)]
 mod tests {
+    use mz_ore::metrics::MetricsRegistry;
+    use mz_persist::cache::{CachingBlob, CachingBlobConfig, CachingBlobMetrics};
+    use mz_persist::location::SeqNo;
     use mz_persist::mem::{MemBlobMulti, MemBlobMultiConfig, MemConsensus};
     use timely::progress::Antichain;
 
+    use crate::r#impl::machine::STATE_ROLLUP_INTERVAL;
     use crate::read::ListenEvent;
 
     use super::*;
@@ -430,6 +467,52 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn state_rollups() -> Result<(), Box<dyn std::error::Error>> {
+        mz_ore::test::init_logging();
+
+        let blob = Arc::new(MemBlobMulti::open(MemBlobMultiConfig::default()));
+        let consensus = Arc::new(MemConsensus::default());
+        let client = Client::new(NO_TIMEOUT, Arc::clone(&blob), Arc::clone(&consensus)).await?;
+
+        let id = ShardId::new();
+        let (mut write, _read) = client
+            .open::<String, String, u64, i64>(NO_TIMEOUT, id)
+            .await?;
+
+        // Write enough batches to trigger a few rollups.
+        let num_batches = STATE_ROLLUP_INTERVAL * 3;
+        let data = (0..num_batches)
+            .map(|i| ((i.to_string(), i.to_string()), i, 1))
+            .collect::<Vec<_>>();
+        for update in data.chunks(1) {
+            write.append_slice(update, update[0].1 + 1).await??;
+        }
+
+        // Consensus has been truncated so that it only contains diffs since
+        // the latest rollup.
+        let deadline = Instant::now() + NO_TIMEOUT;
+        let diffs = consensus
+            .scan(&id.to_string(), deadline, SeqNo::default())
+            .await?;
+        assert!(diffs.len() < usize::try_from(STATE_ROLLUP_INTERVAL * 2)?);
+        assert!(diffs[0].seqno > SeqNo::default().next());
+
+        // A new client has to reconstruct the state from the rollup and the
+        // remaining diffs.
+        let client = Client::new(NO_TIMEOUT, blob, consensus).await?;
+        let (write, read) = client
+            .open::<String, String, u64, i64>(NO_TIMEOUT, id)
+            .await?;
+        assert_eq!(write.upper(), &Antichain::from_elem(num_batches));
+        let mut snap = read.snapshot_one(num_batches - 1).await??;
+        let mut expected = all_ok(&data, num_batches - 1);
+        expected.sort();
+        assert_eq!(snap.read_all().await?, expected);
+
+        Ok(())
+    }
+
     #[test]
     fn fmt_ids() {
         assert_eq!(
@@ -440,6 +523,14 @@ mod tests {
             format!("{:?}", ShardId([0u8; 16])),
             "ShardId(00000000-0000-0000-0000-000000000000)"
         );
+        assert_eq!(
+            "s00000000-0000-0000-0000-000000000000".parse::<ShardId>(),
+            Ok(ShardId([0u8; 16]))
+        );
+        assert!("00000000-0000-0000-0000-000000000000"
+            .parse::<ShardId>()
+            .is_err());
+        assert!("snope".parse::<ShardId>().is_err());
         assert_eq!(
             format!("{}", WriterId([0u8; 16])),
             "w00000000-0000-0000-0000-000000000000"
@@ -457,4 +548,59 @@ mod tests {
             "ReaderId(00000000-0000-0000-0000-000000000000)"
         );
     }
+
+    #[tokio::test]
+    async fn caching_blob() -> Result<(), Box<dyn std::error::Error>> {
+        mz_ore::test::init_logging();
+
+        let data = vec![
+            (("1".to_owned(), "one".to_owned()), 1, 1),
+            (("2".to_owned(), "two".to_owned()), 2, 1),
+        ];
+
+        let registry = MetricsRegistry::new();
+        let counter = |name: &str| -> f64 {
+            registry
+                .gather()
+                .iter()
+                .find(|x| x.get_name() == name)
+                .map_or(0.0, |x| x.get_metric()[0].get_counter().get_value())
+        };
+        let misses = || counter("mz_persist_blob_cache_miss_count");
+        let hits = || counter("mz_persist_blob_cache_memory_hit_count");
+
+        let blob = Arc::new(MemBlobMulti::open(MemBlobMultiConfig::default()));
+        let blob = Arc::new(CachingBlob::new(
+            blob,
+            CachingBlobConfig::default(),
+            Arc::new(CachingBlobMetrics::register_with(&registry)),
+        )?);
+        let consensus = Arc::new(MemConsensus::default());
+        let client = Client::new(NO_TIMEOUT, blob, consensus).await?;
+
+        let (mut write, read1) = client
+            .open::<String, String, u64, i64>(NO_TIMEOUT, ShardId::new())
+            .await?;
+        write.append_slice(&data, 3).await??;
+
+        // Readers sharing the cache see the same data. The first read fetches
+        // the one batch that was written and the second is served from
+        // memory.
+        let read2 = read1.clone(NO_TIMEOUT).await?;
+        let (misses_before, hits_before) = (misses(), hits());
+        assert_eq!(
+            read1.snapshot_one(2).await??.read_all().await?,
+            all_ok(&data, 2)
+        );
+        assert_eq!(misses() - misses_before, 1.0);
+        assert_eq!(hits() - hits_before, 0.0);
+        assert_eq!(
+            read2.snapshot_one(2).await??.read_all().await?,
+            all_ok(&data, 2)
+        );
+        assert_eq!(misses() - misses_before, 1.0);
+        assert_eq!(hits() - hits_before, 1.0);
+
+        Ok(())
+    }
 }
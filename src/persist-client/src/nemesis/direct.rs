@@ -0,0 +1,423 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A single threaded runtime for persist-client nemesis traffic.
+
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use mz_persist::location::{BlobMulti, Consensus, ExternalError};
+use mz_persist::unreliable::{UnreliableBlobMulti, UnreliableConsensus, UnreliableHandle};
+use timely::progress::Antichain;
+
+use crate::error::InvalidUsage;
+use crate::nemesis::generator::{NUM_WRITERS_PER_SHARD, SHARDS};
+use crate::nemesis::{
+    CompareAndAppendReq, CompareAndAppendResolved, DowngradeSinceReq, DowngradeSinceResolved,
+    Error, ExpectedUpper, Input, ListenReq, ListenRes, ListenResolved, ReadSnapshotReq,
+    ReadSnapshotRes, Req, Res, SnapshotId, Step, StepMeta, TakeSnapshotReq, TakeSnapshotResolved,
+};
+use crate::read::{ListenEvent, ReadHandle, SnapshotSplit};
+use crate::write::WriteHandle;
+use crate::{Client, ShardId};
+
+/// The timeout used for every persist-client operation.
+///
+/// Storage unavailability errors are returned immediately, so this is only hit
+/// if something has gone wrong internally.
+const TIMEOUT: Duration = Duration::from_secs(60);
+
+/// The maximum distance a listen is asked to read past its as_of.
+const MAX_LISTEN_DISTANCE: u64 = 10;
+
+#[derive(Debug)]
+struct ShardHandles {
+    writers: Vec<WriteHandle<String, (), u64, i64>>,
+    reader: ReadHandle<String, (), u64, i64>,
+    /// The latest upper of the shard known to this runtime.
+    ///
+    /// This is only ever advanced by definite responses, so it is always less
+    /// than or equal to the actual upper of the shard.
+    upper: u64,
+}
+
+impl ShardHandles {
+    fn since(&self) -> u64 {
+        to_u64(self.reader.since())
+    }
+
+    /// Picks a time in `[since, upper)`, if one exists, otherwise the since.
+    fn rng_as_of(&self, seed: u64) -> u64 {
+        let since = self.since();
+        if self.upper > since {
+            since + seed % (self.upper - since)
+        } else {
+            since
+        }
+    }
+}
+
+/// A runtime that executes [Input]s one at a time against shards opened by a
+/// single [Client].
+#[derive(Debug)]
+pub struct Direct {
+    unreliable: UnreliableHandle,
+    shards: HashMap<String, ShardHandles>,
+    snapshots: HashMap<SnapshotId, (String, u64, SnapshotSplit)>,
+}
+
+impl Direct {
+    /// Returns a new [Direct] which runs requests against persist shards
+    /// stored in the given `blob` and `consensus`.
+    ///
+    /// Both are wrapped in unreliable adapters, which are controlled by the
+    /// `StorageAvailable` and `StorageUnavailable` requests.
+    pub async fn new<B, C>(blob: B, consensus: C) -> Result<Self, ExternalError>
+    where
+        B: BlobMulti + Send + Sync + 'static,
+        C: Consensus + Send + Sync + 'static,
+    {
+        let unreliable = UnreliableHandle::default();
+        let blob = UnreliableBlobMulti::from_handle(blob, unreliable.clone());
+        let consensus = UnreliableConsensus::from_handle(consensus, unreliable.clone());
+        let client = Client::new(TIMEOUT, Arc::new(blob), Arc::new(consensus)).await?;
+        let mut shards = HashMap::new();
+        for shard in SHARDS {
+            shards.insert(shard.to_string(), Self::open_shard(&client).await?);
+        }
+        Ok(Direct {
+            unreliable,
+            shards,
+            snapshots: HashMap::new(),
+        })
+    }
+
+    /// Executes the given [Input], returning the result along with some
+    /// metadata about its execution.
+    pub async fn run(&mut self, input: Input) -> Step {
+        let before = Instant::now();
+        let res = match input.req {
+            Req::CompareAndAppend(req) => self.compare_and_append(req).await,
+            Req::DowngradeSince(req) => self.downgrade_since(req).await,
+            Req::TakeSnapshot(req) => self.take_snapshot(req).await,
+            Req::ReadSnapshot(req) => self.read_snapshot(req).await,
+            Req::Listen(req) => self.listen(req).await,
+            Req::StorageUnavailable => {
+                self.unreliable.make_unavailable();
+                Res::StorageUnavailable
+            }
+            Req::StorageAvailable => {
+                self.unreliable.make_available();
+                Res::StorageAvailable
+            }
+        };
+        let after = Instant::now();
+        let meta = StepMeta {
+            req_id: input.req_id,
+            before,
+            after,
+        };
+        Step { meta, res }
+    }
+
+    /// Releases all handles held by this runtime.
+    pub fn finish(mut self) {
+        // Give the handles a chance to cleanly expire themselves.
+        self.unreliable.make_available();
+        self.snapshots.clear();
+        self.shards.clear();
+    }
+
+    fn shard(&mut self, shard: &str) -> &mut ShardHandles {
+        self.shards
+            .get_mut(shard)
+            .expect("internal nemesis error: unknown shard")
+    }
+
+    async fn open_shard(client: &Client) -> Result<ShardHandles, ExternalError> {
+        let shard_id = ShardId::new();
+        let mut writers = Vec::with_capacity(NUM_WRITERS_PER_SHARD);
+        let mut reader = None;
+        for _ in 0..NUM_WRITERS_PER_SHARD {
+            let (writer, new_reader) = client.open(TIMEOUT, shard_id).await?;
+            writers.push(writer);
+            // Keep only the first reader. Dropping the rest expires them.
+            if reader.is_none() {
+                reader = Some(new_reader);
+            }
+        }
+        let reader = reader.expect("NUM_WRITERS_PER_SHARD is non-zero");
+        let upper = writers
+            .iter()
+            .map(|w| to_u64(w.upper()))
+            .max()
+            .expect("NUM_WRITERS_PER_SHARD is non-zero");
+        Ok(ShardHandles {
+            writers,
+            reader,
+            upper,
+        })
+    }
+
+    async fn compare_and_append(&mut self, req: CompareAndAppendReq) -> Res {
+        let CompareAndAppendReq {
+            shard,
+            writer,
+            expected_upper,
+            upper_delta,
+            updates,
+        } = req;
+        let handles = self.shard(&shard);
+
+        let expected_upper = match expected_upper {
+            ExpectedUpper::Latest => handles.upper,
+            ExpectedUpper::Stale => handles.upper.checked_sub(1).unwrap_or(handles.upper + 1),
+        };
+        let new_upper = expected_upper + upper_delta;
+        let updates = updates
+            .into_iter()
+            .map(|(kv, ts_offset, diff)| (kv, expected_upper + ts_offset, diff))
+            .collect::<Vec<_>>();
+
+        let res = handles.writers[writer]
+            .compare_and_append(
+                TIMEOUT,
+                updates.iter().map(|((k, v), t, d)| ((k, v), t, d)),
+                Antichain::from_elem(expected_upper),
+                Antichain::from_elem(new_upper),
+            )
+            .await;
+        let res = match res {
+            Ok(Ok(Ok(()))) => {
+                handles.upper = std::cmp::max(handles.upper, new_upper);
+                Ok(Ok(()))
+            }
+            Ok(Ok(Err(current))) => {
+                handles.upper = std::cmp::max(handles.upper, to_u64(&current));
+                Ok(Err(current))
+            }
+            Ok(Err(err)) => Err(Error::from(err)),
+            Err(err) => Err(Error::from(err)),
+        };
+        let resolved = CompareAndAppendResolved {
+            shard,
+            expected_upper,
+            new_upper,
+            updates,
+        };
+        Res::CompareAndAppend(resolved, res)
+    }
+
+    async fn downgrade_since(&mut self, req: DowngradeSinceReq) -> Res {
+        let DowngradeSinceReq { shard, since_delta } = req;
+        let handles = self.shard(&shard);
+
+        // Never downgrade the since past the known upper, so that we always
+        // have a time at which we can take a complete snapshot.
+        let since = handles.since();
+        let new_since = std::cmp::min(since + since_delta, handles.upper.saturating_sub(1));
+        let new_since = std::cmp::max(new_since, since);
+        let res = handles
+            .reader
+            .downgrade_since(TIMEOUT, Antichain::from_elem(new_since))
+            .await;
+        let res = match res {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(err)) => Err(Error::from(err)),
+            Err(err) => Err(Error::from(err)),
+        };
+        let resolved = DowngradeSinceResolved { shard, new_since };
+        Res::DowngradeSince(resolved, res)
+    }
+
+    async fn take_snapshot(&mut self, req: TakeSnapshotReq) -> Res {
+        let TakeSnapshotReq {
+            shard,
+            snap,
+            as_of_seed,
+        } = req;
+        let handles = self.shard(&shard);
+
+        let as_of = handles.rng_as_of(as_of_seed);
+        let res = handles
+            .reader
+            .snapshot(
+                TIMEOUT,
+                Antichain::from_elem(as_of),
+                NonZeroUsize::new(1).expect("1 is non-zero"),
+            )
+            .await;
+        let res = match res {
+            Ok(Ok(mut splits)) => {
+                assert_eq!(splits.len(), 1);
+                let split = splits.pop().expect("splits is non-empty");
+                self.snapshots.insert(snap, (shard.clone(), as_of, split));
+                Ok(())
+            }
+            Ok(Err(err)) => Err(Error::from(err)),
+            Err(err) => Err(Error::from(err)),
+        };
+        let resolved = TakeSnapshotResolved { shard, snap, as_of };
+        Res::TakeSnapshot(resolved, res)
+    }
+
+    async fn read_snapshot(&mut self, req: ReadSnapshotReq) -> Res {
+        let res = self.read_snapshot_inner(&req).await;
+        Res::ReadSnapshot(req, res)
+    }
+
+    async fn read_snapshot_inner(
+        &mut self,
+        req: &ReadSnapshotReq,
+    ) -> Result<ReadSnapshotRes, Error> {
+        let (shard, as_of, split) = match self.snapshots.remove(&req.snap) {
+            Some(x) => x,
+            None => {
+                // This happens when the TakeSnapshot failed.
+                return Err(Error::InvalidUsage(format!(
+                    "unknown snapshot: {:?}",
+                    req.snap
+                )));
+            }
+        };
+        let handles = self.shard(&shard);
+        let mut iter = handles.reader.snapshot_iter(TIMEOUT, split).await??;
+        let mut contents = Vec::new();
+        loop {
+            let next = iter.poll_next(TIMEOUT).await?;
+            if next.is_empty() {
+                break;
+            }
+            for ((k, v), t, d) in next {
+                contents.push(((decode(k)?, decode(v)?), t, d));
+            }
+        }
+        Ok(ReadSnapshotRes { as_of, contents })
+    }
+
+    async fn listen(&mut self, req: ListenReq) -> Res {
+        let ListenReq {
+            shard,
+            as_of_seed,
+            until_seed,
+        } = req;
+        let handles = self.shard(&shard);
+
+        let as_of = handles.rng_as_of(as_of_seed);
+        // Never ask the listen to read past the known upper, otherwise it would
+        // block waiting for writes that are never coming.
+        let until = as_of + 1 + until_seed % MAX_LISTEN_DISTANCE;
+        let until = std::cmp::min(until, handles.upper);
+        let resolved = ListenResolved {
+            shard,
+            as_of,
+            until,
+        };
+        let res = Self::listen_inner(&handles.reader, as_of, until).await;
+        Res::Listen(resolved, res)
+    }
+
+    async fn listen_inner(
+        reader: &ReadHandle<String, (), u64, i64>,
+        as_of: u64,
+        until: u64,
+    ) -> Result<ListenRes, Error> {
+        let mut listen = reader
+            .listen(TIMEOUT, Antichain::from_elem(as_of))
+            .await??;
+        let mut ret = ListenRes {
+            progress: Vec::new(),
+            updates: Vec::new(),
+        };
+        let mut frontier = as_of;
+        while frontier < until {
+            for event in listen.poll_next(TIMEOUT).await? {
+                match event {
+                    ListenEvent::Progress(progress) => {
+                        frontier = to_u64(&progress);
+                        ret.progress.push(progress);
+                    }
+                    ListenEvent::Updates(updates) => {
+                        for ((k, v), t, d) in updates {
+                            ret.updates.push(((decode(k)?, decode(v)?), t, d));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(ret)
+    }
+}
+
+fn to_u64(frontier: &Antichain<u64>) -> u64 {
+    // The nemesis never closes a shard, so frontiers are never empty.
+    *frontier
+        .as_option()
+        .expect("internal nemesis error: unexpected empty frontier")
+}
+
+fn decode<X>(x: Result<X, String>) -> Result<X, Error> {
+    x.map_err(Error::Decode)
+}
+
+impl From<ExternalError> for Error {
+    fn from(err: ExternalError) -> Self {
+        Error::External(err.to_string())
+    }
+}
+
+impl From<InvalidUsage> for Error {
+    fn from(err: InvalidUsage) -> Self {
+        Error::InvalidUsage(err.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use mz_persist::file::{FileBlobConfig, FileBlobMulti};
+    use mz_persist::mem::{MemBlobMulti, MemBlobMultiConfig, MemConsensus};
+    use tempfile::TempDir;
+
+    use crate::nemesis::generator::GeneratorConfig;
+    use crate::nemesis::run;
+
+    use super::*;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn direct_mem() {
+        let blob = MemBlobMulti::open(MemBlobMultiConfig::default());
+        let consensus = MemConsensus::default();
+        let direct = Direct::new(blob, consensus).await.expect("initialization");
+        run(500, GeneratorConfig::default(), direct).await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn direct_file() {
+        let temp_dir = TempDir::new().expect("tempdir");
+        let deadline = Instant::now() + Duration::from_secs(1_000_000);
+        let blob = FileBlobMulti::open(deadline, FileBlobConfig::from(temp_dir.path()))
+            .await
+            .expect("initialization");
+        let consensus = MemConsensus::default();
+        let direct = Direct::new(blob, consensus).await.expect("initialization");
+        run(500, GeneratorConfig::default(), direct).await
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn direct_mem_no_storage_failures() {
+        let blob = MemBlobMulti::open(MemBlobMultiConfig::default());
+        let consensus = MemConsensus::default();
+        let direct = Direct::new(blob, consensus).await.expect("initialization");
+        run(500, GeneratorConfig::no_storage_failures(), direct).await
+    }
+}
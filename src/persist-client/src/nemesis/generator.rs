@@ -0,0 +1,370 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::BTreeSet;
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::{Distribution, IteratorRandom, SliceRandom, SmallRng};
+use rand::{Rng, SeedableRng};
+
+use crate::nemesis::{
+    CompareAndAppendReq, DowngradeSinceReq, ExpectedUpper, Input, ListenReq, ReadSnapshotReq, Req,
+    ReqId, SnapshotId, TakeSnapshotReq,
+};
+
+/// Configuration of the relative probabilities of producing various
+/// operations.
+#[derive(Clone, Debug)]
+pub struct GeneratorConfig {
+    pub compare_and_append_weight: u32,
+    pub compare_and_append_stale_weight: u32,
+    pub downgrade_since_weight: u32,
+    pub take_snapshot_weight: u32,
+    pub read_snapshot_weight: u32,
+    pub listen_weight: u32,
+    pub storage_unavailable: u32,
+    pub storage_available: u32,
+}
+
+impl GeneratorConfig {
+    fn all_operations() -> GeneratorConfig {
+        GeneratorConfig {
+            compare_and_append_weight: 1,
+            compare_and_append_stale_weight: 1,
+            downgrade_since_weight: 1,
+            take_snapshot_weight: 1,
+            read_snapshot_weight: 1,
+            listen_weight: 1,
+            storage_unavailable: 1,
+            storage_available: 1,
+        }
+    }
+
+    /// A config that never makes storage unavailable.
+    pub fn no_storage_failures() -> GeneratorConfig {
+        GeneratorConfig {
+            storage_unavailable: 0,
+            storage_available: 0,
+            ..GeneratorConfig::default()
+        }
+    }
+}
+
+impl Default for GeneratorConfig {
+    fn default() -> Self {
+        #[allow(unused_mut)]
+        let mut ops = Self::all_operations();
+        // NB: If we need to temporarily disable an operation in all the nemesis
+        // tests, set it to 0 here. (As opposed to clearing it in the impl of
+        // `all_operations`, which will break the Generator tests.)
+        //
+        // Appends are the only operation that makes progress, so weight them
+        // up to keep the shards from being uninterestingly empty.
+        ops.compare_and_append_weight = 4;
+        ops
+    }
+}
+
+#[derive(Debug)]
+struct GeneratorState {
+    snap_id: SnapshotId,
+    outstanding_snaps: BTreeSet<SnapshotId>,
+    storage_available: bool,
+    shards: Vec<String>,
+    shard_weights: WeightedIndex<u32>,
+    keys: Vec<String>,
+    key_weights: WeightedIndex<u32>,
+}
+
+impl Default for GeneratorState {
+    fn default() -> Self {
+        GeneratorState {
+            snap_id: SnapshotId(0),
+            outstanding_snaps: BTreeSet::new(),
+            storage_available: true,
+            // TODO: Allow for a dynamic number of shards and keys
+            shards: SHARDS.iter().map(|x| x.to_string()).collect(),
+            shard_weights: WeightedIndex::new(&[5, 3, 1]).expect("weights are valid"),
+            keys: ('a'..='e').map(|x| x.to_string()).collect(),
+            key_weights: WeightedIndex::new(&[9, 5, 3, 1, 1]).expect("weights are valid"),
+        }
+    }
+}
+
+impl GeneratorState {
+    fn rng_shard(&self, rng: &mut SmallRng) -> String {
+        self.shards[self.shard_weights.sample(rng)].clone()
+    }
+
+    fn rng_key(&self, rng: &mut SmallRng) -> String {
+        self.keys[self.key_weights.sample(rng)].clone()
+    }
+}
+
+enum ReqGenerator {
+    CompareAndAppend,
+    CompareAndAppendStale,
+    DowngradeSince,
+    TakeSnapshot,
+    ReadSnapshot,
+    Listen,
+    StorageUnavailable,
+    StorageAvailable,
+}
+
+/// The names of the shards that requests are generated against.
+pub const SHARDS: &[&str] = &["a", "b", "c"];
+
+/// The number of writers opened for each shard.
+pub const NUM_WRITERS_PER_SHARD: usize = 2;
+
+impl ReqGenerator {
+    fn compare_and_append(
+        rng: &mut SmallRng,
+        state: &mut GeneratorState,
+        expected_upper: ExpectedUpper,
+    ) -> Req {
+        let shard = state.rng_shard(rng);
+        let writer = rng.gen_range(0..NUM_WRITERS_PER_SHARD);
+        let upper_delta = rng.gen_range(1..=3);
+        // NB: This may be empty, which is an interesting edge case in its own
+        // right.
+        let updates = (0..rng.gen_range(0..5))
+            .map(|_| {
+                let key = state.rng_key(rng);
+                let ts_offset = rng.gen_range(0..upper_delta);
+                // TODO: Tables and sources generally will be using -1 and 1.
+                // 0, -2, and 2 might find some more interesting edge cases.
+                let diff = rng.gen_range(-2..=2);
+                ((key, ()), ts_offset, diff)
+            })
+            .collect();
+        Req::CompareAndAppend(CompareAndAppendReq {
+            shard,
+            writer,
+            expected_upper,
+            upper_delta,
+            updates,
+        })
+    }
+
+    fn downgrade_since(rng: &mut SmallRng, state: &mut GeneratorState) -> Req {
+        let shard = state.rng_shard(rng);
+        // NB: A delta of 0 is invalid usage, which we also want to exercise.
+        let since_delta = rng.gen_range(0..3);
+        Req::DowngradeSince(DowngradeSinceReq { shard, since_delta })
+    }
+
+    fn take_snapshot(rng: &mut SmallRng, state: &mut GeneratorState) -> Req {
+        let shard = state.rng_shard(rng);
+        let snap = SnapshotId(state.snap_id.0);
+        state.snap_id = SnapshotId(snap.0 + 1);
+        state.outstanding_snaps.insert(snap);
+        let as_of_seed = rng.gen();
+        Req::TakeSnapshot(TakeSnapshotReq {
+            shard,
+            snap,
+            as_of_seed,
+        })
+    }
+
+    fn read_snapshot(rng: &mut SmallRng, state: &mut GeneratorState) -> Req {
+        let snap = state
+            .outstanding_snaps
+            .iter()
+            .choose(rng)
+            .copied()
+            .expect("internal nemesis error: no outstanding snapshots to read");
+        state.outstanding_snaps.remove(&snap);
+        Req::ReadSnapshot(ReadSnapshotReq { snap })
+    }
+
+    fn listen(rng: &mut SmallRng, state: &mut GeneratorState) -> Req {
+        let shard = state.rng_shard(rng);
+        let (as_of_seed, until_seed) = (rng.gen(), rng.gen());
+        Req::Listen(ListenReq {
+            shard,
+            as_of_seed,
+            until_seed,
+        })
+    }
+
+    fn gen(&self, rng: &mut SmallRng, state: &mut GeneratorState) -> Req {
+        match self {
+            ReqGenerator::CompareAndAppend => {
+                ReqGenerator::compare_and_append(rng, state, ExpectedUpper::Latest)
+            }
+            ReqGenerator::CompareAndAppendStale => {
+                ReqGenerator::compare_and_append(rng, state, ExpectedUpper::Stale)
+            }
+            ReqGenerator::DowngradeSince => ReqGenerator::downgrade_since(rng, state),
+            ReqGenerator::TakeSnapshot => ReqGenerator::take_snapshot(rng, state),
+            ReqGenerator::ReadSnapshot => ReqGenerator::read_snapshot(rng, state),
+            ReqGenerator::Listen => ReqGenerator::listen(rng, state),
+            ReqGenerator::StorageUnavailable => {
+                state.storage_available = false;
+                Req::StorageUnavailable
+            }
+            ReqGenerator::StorageAvailable => {
+                state.storage_available = true;
+                Req::StorageAvailable
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Generator {
+    seed: u64,
+    config: GeneratorConfig,
+    req_id: u64,
+    state: GeneratorState,
+}
+
+impl Generator {
+    pub fn new(seed: u64, config: GeneratorConfig) -> Self {
+        Generator {
+            seed,
+            config,
+            req_id: 0,
+            state: GeneratorState::default(),
+        }
+    }
+
+    // fill_relevant appends a `(weight, ReqGenerator)` pair for each relevant
+    // request type.
+    //
+    // Examples of when requests are only sometimes relevant:
+    // - ReadSnapshot can only apply if we have previously taken a snapshot but
+    //   not consumed (read) it yet.
+    // - StorageAvailable can only apply if storage is currently unavailable.
+    fn fill_relevant(&self, relevant: &mut Vec<(u32, ReqGenerator)>) {
+        let candidates = vec![
+            Some((
+                self.config.compare_and_append_weight,
+                ReqGenerator::CompareAndAppend,
+            )),
+            Some((
+                self.config.compare_and_append_stale_weight,
+                ReqGenerator::CompareAndAppendStale,
+            )),
+            Some((
+                self.config.downgrade_since_weight,
+                ReqGenerator::DowngradeSince,
+            )),
+            Some((self.config.take_snapshot_weight, ReqGenerator::TakeSnapshot)),
+            Some((self.config.read_snapshot_weight, ReqGenerator::ReadSnapshot))
+                .filter(|_| !self.state.outstanding_snaps.is_empty()),
+            Some((self.config.listen_weight, ReqGenerator::Listen)),
+            Some((
+                self.config.storage_unavailable,
+                ReqGenerator::StorageUnavailable,
+            ))
+            .filter(|_| self.state.storage_available),
+            Some((
+                self.config.storage_available,
+                ReqGenerator::StorageAvailable,
+            ))
+            .filter(|_| !self.state.storage_available),
+        ];
+        relevant.extend(candidates.into_iter().flatten());
+    }
+
+    fn gen(&mut self) -> Option<Input> {
+        let req_id = ReqId(self.req_id);
+        self.req_id += 1;
+        let mut rng = SmallRng::seed_from_u64(self.seed.wrapping_add(req_id.0));
+
+        let mut relevant = Vec::new();
+        self.fill_relevant(&mut relevant);
+        if let Ok((_, req)) = relevant.choose_weighted(&mut rng, |(w, _)| *w) {
+            let req = req.gen(&mut rng, &mut self.state);
+            Some(Input { req_id, req })
+        } else {
+            None
+        }
+    }
+}
+
+impl Iterator for Generator {
+    type Item = Input;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.gen()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    // Sanity check that every operation is generated with some reasonable
+    // probability.
+    #[test]
+    fn all_operations_generated() {
+        let (seed, config) = (0, GeneratorConfig::all_operations());
+        let mut counts = HashMap::new();
+        for input in Generator::new(seed, config).take(1000) {
+            let name = match input.req {
+                Req::CompareAndAppend(req) => match req.expected_upper {
+                    ExpectedUpper::Latest => "compare_and_append",
+                    ExpectedUpper::Stale => "compare_and_append_stale",
+                },
+                Req::DowngradeSince(_) => "downgrade_since",
+                Req::TakeSnapshot(_) => "take_snapshot",
+                Req::ReadSnapshot(_) => "read_snapshot",
+                Req::Listen(_) => "listen",
+                Req::StorageUnavailable => "storage_unavailable",
+                Req::StorageAvailable => "storage_available",
+            };
+            *counts.entry(name).or_insert(0) += 1;
+        }
+        assert_eq!(counts.len(), 8, "{:?}", counts);
+        for (name, count) in counts {
+            assert!(count > 10, "{} only generated {} times", name, count);
+        }
+    }
+
+    // Storage availability requests should alternate.
+    #[test]
+    fn storage_availability_alternates() {
+        let (seed, config) = (0, GeneratorConfig::all_operations());
+        let mut available = true;
+        for input in Generator::new(seed, config).take(1000) {
+            match input.req {
+                Req::StorageUnavailable => {
+                    assert!(available);
+                    available = false;
+                }
+                Req::StorageAvailable => {
+                    assert!(!available);
+                    available = true;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // Every generated append fits within the frontiers it will be resolved to.
+    #[test]
+    fn compare_and_append_updates_within_delta() {
+        let (seed, config) = (0, GeneratorConfig::all_operations());
+        for input in Generator::new(seed, config).take(1000) {
+            if let Req::CompareAndAppend(req) = input.req {
+                assert!(req.upper_delta > 0);
+                assert!(req.writer < NUM_WRITERS_PER_SHARD);
+                for (_, ts_offset, _) in req.updates {
+                    assert!(ts_offset < req.upper_delta);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,269 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Invariant-based random tests for the persist-client crate.
+//!
+//! This is a port of the nemesis framework in the mz_persist crate to the
+//! [crate::write::WriteHandle] and [crate::read::ReadHandle] API. It generates
+//! random traffic against the public API of this crate, records the requests
+//! sent and responses received, and afterward checks this history against the
+//! API invariants.
+//!
+//! API traffic is modeled as a series of [Input]s. These are created by a
+//! [Generator], which is totally deterministic given a seed. The [Input]s are
+//! handed to [Direct], which executes the embedded [Req] against persist
+//! shards backed by unreliable [mz_persist::location::BlobMulti] and
+//! [mz_persist::location::Consensus] impls and saves the [Res] wrapped up with
+//! metadata about the execution into a [Step]. These steps collectively form a
+//! history, which is handed to [Validator] to check for API invariant
+//! violations (representing a bug in persist).
+//!
+//! The most interesting invariants are:
+//! - The uppers of a shard are linearizable: every successful
+//!   `compare_and_append` was issued with the shard's actual upper, every
+//!   unsuccessful one reports the shard's actual upper, and listeners observe
+//!   progress through exactly these uppers.
+//! - Snapshots and listens return exactly the data that was durably appended.
+//!
+//! By default, the unit tests all select and use a random seed for traffic
+//! generation. The seed is printed when the test is run and override-able, so
+//! if a test does fail, the previous traffic can be exactly replayed.
+//!
+//! ```shell
+//! MZ_NEMESIS_SEED=16385522461931935384 cargo test -p mz-persist-client -- nemesis
+//! ```
+//!
+//! The cargo-stress crate is useful without fixing the seed to search for new
+//! invariant violations.
+//!
+//! ```shell
+//! cargo stress -p mz-persist-client -- nemesis
+//! ```
+
+// TODO
+// - Concurrent (pipelined) requests from multiple tasks
+// - Storage with variable latency and failures after a mutation has been
+//   applied (ambiguous results)
+// - Multiple clients (processes) against the same location
+// - Expiring and re-opening handles
+
+use std::env;
+use std::time::Instant;
+
+use mz_ore::test::init_logging;
+use rand::rngs::OsRng;
+use rand::RngCore;
+use timely::progress::Antichain;
+use tracing::{debug, info, warn};
+
+use crate::nemesis::direct::Direct;
+use crate::nemesis::generator::{Generator, GeneratorConfig};
+use crate::nemesis::validator::Validator;
+
+pub mod direct;
+pub mod generator;
+pub mod validator;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReqId(u64);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SnapshotId(u64);
+
+/// A single logical operation to run against the persist-client API.
+///
+/// Like in the mz_persist nemesis, snapshots are modeled as two requests: one
+/// takes a snapshot and registers it with a known id and the second removes it
+/// from the registry and reads the contents. This allows us to insert whatever
+/// other `Input`s we like in between them.
+#[derive(Clone, Debug)]
+pub struct Input {
+    req_id: ReqId,
+    req: Req,
+}
+
+#[derive(Debug)]
+pub struct StepMeta {
+    req_id: ReqId,
+    before: Instant,
+    after: Instant,
+}
+
+#[derive(Debug)]
+pub struct Step {
+    meta: StepMeta,
+    res: Res,
+}
+
+#[derive(Clone, Debug)]
+pub enum Req {
+    CompareAndAppend(CompareAndAppendReq),
+    DowngradeSince(DowngradeSinceReq),
+    TakeSnapshot(TakeSnapshotReq),
+    ReadSnapshot(ReadSnapshotReq),
+    Listen(ListenReq),
+    StorageUnavailable,
+    StorageAvailable,
+}
+
+#[derive(Debug)]
+pub enum Res {
+    CompareAndAppend(
+        CompareAndAppendResolved,
+        Result<Result<(), Antichain<u64>>, Error>,
+    ),
+    DowngradeSince(DowngradeSinceResolved, Result<(), Error>),
+    TakeSnapshot(TakeSnapshotResolved, Result<(), Error>),
+    ReadSnapshot(ReadSnapshotReq, Result<ReadSnapshotRes, Error>),
+    Listen(ListenResolved, Result<ListenRes, Error>),
+    StorageUnavailable,
+    StorageAvailable,
+}
+
+/// The ways that a request against the persist-client API can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// An [mz_persist::location::ExternalError]. The request may or may not
+    /// have been applied.
+    External(String),
+    /// A [crate::error::InvalidUsage]. The request was not applied.
+    InvalidUsage(String),
+    /// Data was read back that could not be decoded.
+    Decode(String),
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::External(x) => write!(f, "external: {}", x),
+            Error::InvalidUsage(x) => write!(f, "invalid usage: {}", x),
+            Error::Decode(x) => write!(f, "decode: {}", x),
+        }
+    }
+}
+
+/// Which upper a `compare_and_append` should be issued with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExpectedUpper {
+    /// The latest upper of the shard observed by the runtime.
+    Latest,
+    /// An upper that the runtime believes is not the upper of the shard.
+    Stale,
+}
+
+#[derive(Clone, Debug)]
+pub struct CompareAndAppendReq {
+    shard: String,
+    writer: usize,
+    expected_upper: ExpectedUpper,
+    /// The distance between the expected upper and the new upper.
+    upper_delta: u64,
+    /// Updates with timestamps relative to the expected upper. All offsets are
+    /// less than `upper_delta`.
+    updates: Vec<((String, ()), u64, i64)>,
+}
+
+/// A [CompareAndAppendReq] with the frontiers and timestamps that were actually
+/// sent to persist.
+#[derive(Clone, Debug)]
+pub struct CompareAndAppendResolved {
+    shard: String,
+    expected_upper: u64,
+    new_upper: u64,
+    updates: Vec<((String, ()), u64, i64)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DowngradeSinceReq {
+    shard: String,
+    /// The distance between the reader's current since and the new since.
+    since_delta: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct DowngradeSinceResolved {
+    shard: String,
+    new_since: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct TakeSnapshotReq {
+    shard: String,
+    snap: SnapshotId,
+    /// Used to pick an as_of between the since and upper of the shard.
+    as_of_seed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct TakeSnapshotResolved {
+    shard: String,
+    snap: SnapshotId,
+    as_of: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReadSnapshotReq {
+    snap: SnapshotId,
+}
+
+#[derive(Clone, Debug)]
+pub struct ReadSnapshotRes {
+    as_of: u64,
+    contents: Vec<((String, ()), u64, i64)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListenReq {
+    shard: String,
+    /// Used to pick an as_of between the since and upper of the shard.
+    as_of_seed: u64,
+    /// Used to pick how far the listen should read.
+    until_seed: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListenResolved {
+    shard: String,
+    as_of: u64,
+    until: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListenRes {
+    progress: Vec<Antichain<u64>>,
+    updates: Vec<((String, ()), u64, i64)>,
+}
+
+pub async fn run(steps: usize, config: GeneratorConfig, mut runtime: Direct) {
+    init_logging();
+    let seed =
+        env::var("MZ_NEMESIS_SEED").map_or_else(|_| OsRng.next_u64(), |s| s.parse().unwrap());
+    let steps = env::var("MZ_NEMESIS_STEPS").map_or(steps, |s| s.parse().unwrap());
+    info!("MZ_NEMESIS_SEED={} MZ_NEMESIS_STEPS={}", seed, steps);
+    let generator = Generator::new(seed, config);
+    let mut history = Vec::with_capacity(steps);
+    for input in generator.take(steps) {
+        debug!("{:?} req: {:?}", input.req_id, &input.req);
+        let step = runtime.run(input).await;
+        debug!(
+            "{:?} res: {:?} took {:?}",
+            step.meta.req_id,
+            &step.res,
+            step.meta.after.duration_since(step.meta.before)
+        );
+        history.push(step);
+    }
+    runtime.finish();
+    if let Err(errors) = Validator::validate(history) {
+        for err in errors.iter() {
+            warn!("invariant violation: {}", err)
+        }
+        assert!(errors.is_empty());
+    }
+}
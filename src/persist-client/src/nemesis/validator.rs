@@ -0,0 +1,457 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+use std::collections::HashMap;
+
+use differential_dataflow::consolidation::consolidate_updates;
+use timely::progress::Antichain;
+
+use crate::nemesis::{
+    CompareAndAppendResolved, DowngradeSinceResolved, Error, ListenRes, ListenResolved,
+    ReadSnapshotReq, ReadSnapshotRes, Res, SnapshotId, Step, StepMeta, TakeSnapshotResolved,
+};
+
+type Update = ((String, ()), u64, i64);
+
+/// One possible history of a shard.
+///
+/// A request that fails with an [Error::External] may or may not have been
+/// applied, so the validator tracks every history consistent with the
+/// responses seen so far and narrows them down as definite responses come in.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Candidate {
+    /// Every upper the shard has had, in order.
+    uppers: Vec<u64>,
+    /// Every update written to the shard.
+    updates: Vec<Update>,
+}
+
+impl Candidate {
+    fn upper(&self) -> u64 {
+        *self.uppers.last().expect("uppers is non-empty")
+    }
+
+    /// The contents of the shard at `as_of`, with times advanced to `as_of`.
+    fn snapshot(&self, as_of: u64) -> Vec<Update> {
+        let contents = self
+            .updates
+            .iter()
+            .filter(|(_, ts, _)| *ts <= as_of)
+            .map(|(kv, _, diff)| (kv.clone(), as_of, *diff))
+            .collect();
+        consolidate(contents)
+    }
+
+    /// The updates in the shard at times in `(as_of, until)`.
+    fn listen(&self, as_of: u64, until: u64) -> Vec<Update> {
+        let updates = self
+            .updates
+            .iter()
+            .filter(|(_, ts, _)| as_of < *ts && *ts < until)
+            .cloned()
+            .collect();
+        consolidate(updates)
+    }
+}
+
+#[derive(Debug)]
+struct Shard {
+    candidates: Vec<Candidate>,
+    /// A lower bound on the since of the nemesis reader.
+    since_lo: u64,
+    /// An upper bound on the since of the nemesis reader.
+    since_hi: u64,
+}
+
+impl Default for Shard {
+    fn default() -> Self {
+        Shard {
+            candidates: vec![Candidate {
+                uppers: vec![0],
+                updates: Vec::new(),
+            }],
+            since_lo: 0,
+            since_hi: 0,
+        }
+    }
+}
+
+impl Shard {
+    fn uppers(&self) -> Vec<u64> {
+        self.candidates.iter().map(|c| c.upper()).collect()
+    }
+}
+
+/// The possible results of reading a snapshot, as computed when it was taken.
+#[derive(Debug)]
+struct Snapshot {
+    shard: String,
+    /// The contents of the shard at the as_of for each candidate history or
+    /// None if the as_of was not yet complete (in which case the snapshot may
+    /// be missing data).
+    expected: Option<Vec<Vec<Update>>>,
+}
+
+/// Checks a history of nemesis [Step]s for violations of the persist-client
+/// API invariants.
+#[derive(Debug)]
+pub struct Validator {
+    storage_available: bool,
+    shards: HashMap<String, Shard>,
+    snapshots: HashMap<SnapshotId, Snapshot>,
+    errors: Vec<String>,
+}
+
+impl Validator {
+    /// Validates the given history, returning a description of every invariant
+    /// violation found.
+    pub fn validate(history: Vec<Step>) -> Result<(), Vec<String>> {
+        let mut v = Validator {
+            storage_available: true,
+            shards: HashMap::new(),
+            snapshots: HashMap::new(),
+            errors: Vec::new(),
+        };
+        for step in history {
+            v.step(step);
+        }
+        if v.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(v.errors)
+        }
+    }
+
+    fn step(&mut self, step: Step) {
+        let Step { meta, res } = step;
+        match res {
+            Res::CompareAndAppend(req, res) => self.compare_and_append(&meta, req, res),
+            Res::DowngradeSince(req, res) => self.downgrade_since(&meta, req, res),
+            Res::TakeSnapshot(req, res) => self.take_snapshot(&meta, req, res),
+            Res::ReadSnapshot(req, res) => self.read_snapshot(&meta, req, res),
+            Res::Listen(req, res) => self.listen(&meta, req, res),
+            Res::StorageUnavailable => self.storage_available = false,
+            Res::StorageAvailable => self.storage_available = true,
+        }
+    }
+
+    fn check_success<T>(&mut self, meta: &StepMeta, res: &Result<T, Error>) {
+        match res {
+            Ok(_) => {}
+            Err(Error::External(err)) => {
+                // Liveness: every operation must succeed when storage is
+                // available.
+                if self.storage_available {
+                    self.errors.push(format!(
+                        "{:?} expected success got external error: {}",
+                        meta.req_id, err
+                    ));
+                }
+            }
+            Err(Error::Decode(err)) => {
+                self.errors
+                    .push(format!("{:?} failed to decode data: {}", meta.req_id, err));
+            }
+            Err(Error::InvalidUsage(_)) => {}
+        }
+    }
+
+    fn compare_and_append(
+        &mut self,
+        meta: &StepMeta,
+        req: CompareAndAppendResolved,
+        res: Result<Result<(), Antichain<u64>>, Error>,
+    ) {
+        self.check_success(meta, &res);
+        let CompareAndAppendResolved {
+            shard,
+            expected_upper,
+            new_upper,
+            updates,
+        } = req;
+        let s = self.shards.entry(shard.clone()).or_default();
+        let apply = |c: &Candidate| {
+            let mut c = c.clone();
+            c.uppers.push(new_upper);
+            c.updates.extend(updates.iter().cloned());
+            c
+        };
+        match res {
+            Ok(Ok(())) => {
+                let uppers = s.uppers();
+                let candidates = s
+                    .candidates
+                    .iter()
+                    .filter(|c| c.upper() == expected_upper)
+                    .map(apply)
+                    .collect::<Vec<_>>();
+                if candidates.is_empty() {
+                    self.errors.push(format!(
+                        "{:?} compare_and_append on shard {} succeeded with expected upper {} but shard upper was one of {:?}",
+                        meta.req_id, shard, expected_upper, uppers
+                    ));
+                } else {
+                    s.candidates = candidates;
+                }
+            }
+            Ok(Err(current)) => {
+                let current = match current.as_option() {
+                    Some(x) => *x,
+                    None => {
+                        self.errors.push(format!(
+                            "{:?} compare_and_append on shard {} returned unexpected empty upper",
+                            meta.req_id, shard
+                        ));
+                        return;
+                    }
+                };
+                if current == expected_upper {
+                    self.errors.push(format!(
+                        "{:?} compare_and_append on shard {} failed with current upper {} equal to expected upper",
+                        meta.req_id, shard, current
+                    ));
+                }
+                let uppers = s.uppers();
+                let candidates = s
+                    .candidates
+                    .iter()
+                    .filter(|c| c.upper() == current)
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if candidates.is_empty() {
+                    self.errors.push(format!(
+                        "{:?} compare_and_append on shard {} returned current upper {} but shard upper was one of {:?}",
+                        meta.req_id, shard, current, uppers
+                    ));
+                } else {
+                    s.candidates = candidates;
+                }
+            }
+            Err(Error::External(_)) => {
+                // The append may or may not have been applied.
+                let applied = s
+                    .candidates
+                    .iter()
+                    .filter(|c| c.upper() == expected_upper)
+                    .map(apply)
+                    .collect::<Vec<_>>();
+                for c in applied {
+                    if !s.candidates.contains(&c) {
+                        s.candidates.push(c);
+                    }
+                }
+            }
+            Err(Error::InvalidUsage(err)) => {
+                // The nemesis only generates valid appends.
+                self.errors.push(format!(
+                    "{:?} compare_and_append on shard {} unexpectedly failed with invalid usage: {}",
+                    meta.req_id, shard, err
+                ));
+            }
+            Err(Error::Decode(_)) => {}
+        }
+    }
+
+    fn downgrade_since(
+        &mut self,
+        meta: &StepMeta,
+        req: DowngradeSinceResolved,
+        res: Result<(), Error>,
+    ) {
+        self.check_success(meta, &res);
+        let DowngradeSinceResolved { shard, new_since } = req;
+        let s = self.shards.entry(shard.clone()).or_default();
+        match res {
+            Ok(()) => {
+                if new_since <= s.since_lo {
+                    self.errors.push(format!(
+                        "{:?} downgrade_since on shard {} to {} succeeded but since was at least {}",
+                        meta.req_id, shard, new_since, s.since_lo
+                    ));
+                }
+                s.since_lo = new_since;
+                s.since_hi = new_since;
+            }
+            Err(Error::InvalidUsage(err)) => {
+                if new_since > s.since_hi {
+                    self.errors.push(format!(
+                        "{:?} downgrade_since on shard {} to {} failed but since was at most {}: {}",
+                        meta.req_id, shard, new_since, s.since_hi, err
+                    ));
+                }
+                s.since_lo = std::cmp::max(s.since_lo, new_since);
+            }
+            Err(Error::External(_)) => {
+                // The downgrade may or may not have been applied.
+                s.since_hi = std::cmp::max(s.since_hi, new_since);
+            }
+            Err(Error::Decode(_)) => {}
+        }
+    }
+
+    fn take_snapshot(
+        &mut self,
+        meta: &StepMeta,
+        req: TakeSnapshotResolved,
+        res: Result<(), Error>,
+    ) {
+        self.check_success(meta, &res);
+        let TakeSnapshotResolved { shard, snap, as_of } = req;
+        let s = self.shards.entry(shard.clone()).or_default();
+        match res {
+            Ok(()) => {
+                let expected = s
+                    .candidates
+                    .iter()
+                    .filter(|c| c.upper() > as_of)
+                    .map(|c| c.snapshot(as_of))
+                    .collect::<Vec<_>>();
+                let expected = if expected.is_empty() {
+                    None
+                } else {
+                    Some(expected)
+                };
+                self.snapshots.insert(snap, Snapshot { shard, expected });
+            }
+            Err(Error::InvalidUsage(err)) => {
+                if as_of >= s.since_hi {
+                    self.errors.push(format!(
+                        "{:?} snapshot on shard {} at {} failed but since was at most {}: {}",
+                        meta.req_id, shard, as_of, s.since_hi, err
+                    ));
+                }
+            }
+            Err(Error::External(_)) | Err(Error::Decode(_)) => {}
+        }
+    }
+
+    fn read_snapshot(
+        &mut self,
+        meta: &StepMeta,
+        req: ReadSnapshotReq,
+        res: Result<ReadSnapshotRes, Error>,
+    ) {
+        let snapshot = self.snapshots.remove(&req.snap);
+        match (snapshot, res) {
+            (Some(snapshot), Ok(res)) => {
+                let Snapshot { shard, expected } = snapshot;
+                let expected = match expected {
+                    Some(x) => x,
+                    None => return,
+                };
+                let actual = res
+                    .contents
+                    .into_iter()
+                    .map(|(kv, _, diff)| (kv, res.as_of, diff))
+                    .collect();
+                let actual = consolidate(actual);
+                if !expected.contains(&actual) {
+                    self.errors.push(format!(
+                        "{:?} snapshot on shard {} at {} expected one of {:?} got {:?}",
+                        meta.req_id, shard, res.as_of, expected, actual
+                    ));
+                }
+            }
+            (Some(_), res @ Err(_)) => self.check_success(meta, &res),
+            (None, Ok(_)) => {
+                self.errors.push(format!(
+                    "{:?} read of unknown snapshot {:?} unexpectedly succeeded",
+                    meta.req_id, req.snap
+                ));
+            }
+            (None, Err(_)) => {}
+        }
+    }
+
+    fn listen(&mut self, meta: &StepMeta, req: ListenResolved, res: Result<ListenRes, Error>) {
+        self.check_success(meta, &res);
+        let ListenResolved {
+            shard,
+            as_of,
+            until,
+        } = req;
+        let s = self.shards.entry(shard.clone()).or_default();
+        let res = match res {
+            Ok(x) => x,
+            Err(Error::InvalidUsage(err)) => {
+                if as_of >= s.since_hi {
+                    self.errors.push(format!(
+                        "{:?} listen on shard {} at {} failed but since was at most {}: {}",
+                        meta.req_id, shard, as_of, s.since_hi, err
+                    ));
+                }
+                return;
+            }
+            Err(_) => return,
+        };
+
+        let mut progress = Vec::with_capacity(res.progress.len());
+        for p in res.progress.iter() {
+            match p.as_option() {
+                Some(p) => progress.push(*p),
+                None => {
+                    self.errors.push(format!(
+                        "{:?} listen on shard {} returned unexpected empty progress",
+                        meta.req_id, shard
+                    ));
+                    return;
+                }
+            }
+        }
+        let mut prev = as_of;
+        for p in progress.iter() {
+            if *p <= prev {
+                self.errors.push(format!(
+                    "{:?} listen on shard {} at {} returned non-increasing progress {:?}",
+                    meta.req_id, shard, as_of, progress
+                ));
+                return;
+            }
+            prev = *p;
+        }
+        if until > as_of && prev < until {
+            self.errors.push(format!(
+                "{:?} listen on shard {} at {} returned progress {:?} short of {}",
+                meta.req_id, shard, as_of, progress, until
+            ));
+            return;
+        }
+
+        let updates = consolidate(res.updates);
+        let matches = |c: &Candidate| {
+            progress.iter().all(|p| c.uppers.contains(p)) && c.listen(as_of, prev) == updates
+        };
+        let candidates = s
+            .candidates
+            .iter()
+            .filter(|c| matches(c))
+            .cloned()
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            let expected = s
+                .candidates
+                .iter()
+                .map(|c| (c.uppers.clone(), c.listen(as_of, prev)))
+                .collect::<Vec<_>>();
+            self.errors.push(format!(
+                "{:?} listen on shard {} at {} expected one of {:?} got progress {:?} updates {:?}",
+                meta.req_id, shard, as_of, expected, progress, updates
+            ));
+        } else {
+            // A listen observes the actual history of the shard, so we can
+            // rule out any candidates it doesn't match.
+            s.candidates = candidates;
+        }
+    }
+}
+
+fn consolidate(mut updates: Vec<Update>) -> Vec<Update> {
+    consolidate_updates(&mut updates);
+    updates
+}
@@ -87,6 +87,19 @@ where
     T: Timestamp + Lattice + Codec64,
     D: Semigroup + Codec64,
 {
+    pub(crate) fn new(
+        as_of: Antichain<T>,
+        batches: Vec<String>,
+        blob: Arc<dyn BlobMulti + Send + Sync>,
+    ) -> Self {
+        SnapshotIter {
+            as_of,
+            batches,
+            blob,
+            _phantom: PhantomData,
+        }
+    }
+
     /// The frontier at which we're outputting the contents of the shard.
     pub fn as_of(&self) -> &Antichain<T> {
         &self.as_of
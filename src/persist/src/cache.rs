@@ -0,0 +1,640 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! A size-bounded read cache in front of [BlobMulti] storage.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use async_trait::async_trait;
+use mz_ore::cast::CastFrom;
+use mz_ore::metric;
+use mz_ore::metrics::{IntCounter, MetricsRegistry, ThirdPartyMetric, UIntGauge};
+use tokio::fs;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::error::Error;
+use crate::location::{Atomicity, BlobMulti, ExternalError};
+
+/// Configuration for a [CachingBlob].
+#[derive(Clone, Debug)]
+pub struct CachingBlobConfig {
+    /// The maximum total size of the values held in memory.
+    pub memory_limit_bytes: usize,
+    /// If set, fetched values are also kept in files in this directory, up to
+    /// `disk_limit_bytes` in total.
+    ///
+    /// The files are kept in a subdirectory that the cache creates and marks
+    /// as its own. That subdirectory (but nothing else in this directory) is
+    /// cleared when the cache is opened.
+    pub disk_dir: Option<PathBuf>,
+    /// The maximum total size of the values held on local disk.
+    pub disk_limit_bytes: usize,
+}
+
+const MB: usize = 1024 * 1024;
+const GB: usize = 1024 * MB;
+
+impl Default for CachingBlobConfig {
+    fn default() -> Self {
+        CachingBlobConfig {
+            memory_limit_bytes: 256 * MB,
+            disk_dir: None,
+            disk_limit_bytes: 4 * GB,
+        }
+    }
+}
+
+/// Monitoring metrics for a [CachingBlob].
+///
+/// Intentionally not Clone because we expect this to be passed around in an
+/// Arc.
+#[derive(Debug)]
+pub struct CachingBlobMetrics {
+    pub(crate) memory_hit_count: ThirdPartyMetric<IntCounter>,
+    pub(crate) disk_hit_count: ThirdPartyMetric<IntCounter>,
+    pub(crate) miss_count: ThirdPartyMetric<IntCounter>,
+    pub(crate) fetch_bytes: ThirdPartyMetric<IntCounter>,
+    pub(crate) memory_bytes: ThirdPartyMetric<UIntGauge>,
+    pub(crate) memory_entry_count: ThirdPartyMetric<UIntGauge>,
+    pub(crate) disk_bytes: ThirdPartyMetric<UIntGauge>,
+    pub(crate) disk_entry_count: ThirdPartyMetric<UIntGauge>,
+    pub(crate) disk_error_count: ThirdPartyMetric<IntCounter>,
+}
+
+impl CachingBlobMetrics {
+    /// Returns a new [CachingBlobMetrics] instance connected to the given
+    /// registry.
+    pub fn register_with(registry: &MetricsRegistry) -> Self {
+        CachingBlobMetrics {
+            memory_hit_count: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_memory_hit_count",
+                help: "count of blob reads served by the in-memory cache",
+            )),
+            disk_hit_count: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_disk_hit_count",
+                help: "count of blob reads served by the local disk cache",
+            )),
+            miss_count: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_miss_count",
+                help: "count of blob reads that had to be fetched",
+            )),
+            fetch_bytes: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_fetch_bytes",
+                help: "total size of blob reads that had to be fetched",
+            )),
+            memory_bytes: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_memory_bytes",
+                help: "total size of the values in the in-memory cache",
+            )),
+            memory_entry_count: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_memory_entry_count",
+                help: "count of the values in the in-memory cache",
+            )),
+            disk_bytes: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_disk_bytes",
+                help: "total size of the values in the local disk cache",
+            )),
+            disk_entry_count: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_disk_entry_count",
+                help: "count of the values in the local disk cache",
+            )),
+            disk_error_count: registry.register_third_party_visible(metric!(
+                name: "mz_persist_blob_cache_disk_error_count",
+                help: "count of local disk cache reads and writes that failed",
+            )),
+        }
+    }
+}
+
+impl Default for CachingBlobMetrics {
+    fn default() -> Self {
+        Self::register_with(&MetricsRegistry::new())
+    }
+}
+
+/// A size-bounded map that evicts the least recently used entries.
+#[derive(Debug)]
+struct Lru<V> {
+    limit_bytes: usize,
+    total_bytes: usize,
+    next_tick: u64,
+    entries: HashMap<String, LruEntry<V>>,
+    by_tick: BTreeMap<u64, String>,
+}
+
+#[derive(Debug)]
+struct LruEntry<V> {
+    val: V,
+    size_bytes: usize,
+    tick: u64,
+}
+
+impl<V> Lru<V> {
+    fn new(limit_bytes: usize) -> Self {
+        Lru {
+            limit_bytes,
+            total_bytes: 0,
+            next_tick: 0,
+            entries: HashMap::new(),
+            by_tick: BTreeMap::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Returns the value for `key`, marking it as the most recently used.
+    fn get(&mut self, key: &str) -> Option<&V> {
+        let entry = self.entries.get_mut(key)?;
+        self.by_tick.remove(&entry.tick);
+        entry.tick = self.next_tick;
+        self.next_tick += 1;
+        self.by_tick.insert(entry.tick, key.to_owned());
+        Some(&entry.val)
+    }
+
+    /// Inserts the given value, returning any entries that had to be evicted
+    /// to stay within the size limit.
+    ///
+    /// A value larger than the limit is never inserted and is itself returned
+    /// as evicted.
+    fn insert(&mut self, key: String, val: V, size_bytes: usize) -> Vec<(String, V)> {
+        let mut evicted = Vec::new();
+        if size_bytes > self.limit_bytes {
+            evicted.push((key, val));
+            return evicted;
+        }
+        if let Some(prev) = self.remove(&key) {
+            evicted.push((key.clone(), prev));
+        }
+        while self.total_bytes + size_bytes > self.limit_bytes {
+            let oldest = match self.by_tick.keys().next() {
+                Some(tick) => *tick,
+                None => break,
+            };
+            let oldest_key = self.by_tick[&oldest].clone();
+            let oldest_val = self.remove(&oldest_key).expect("entry is present");
+            evicted.push((oldest_key, oldest_val));
+        }
+        let tick = self.next_tick;
+        self.next_tick += 1;
+        self.by_tick.insert(tick, key.clone());
+        self.total_bytes += size_bytes;
+        self.entries.insert(
+            key,
+            LruEntry {
+                val,
+                size_bytes,
+                tick,
+            },
+        );
+        evicted
+    }
+
+    fn remove(&mut self, key: &str) -> Option<V> {
+        let entry = self.entries.remove(key)?;
+        self.by_tick.remove(&entry.tick);
+        self.total_bytes -= entry.size_bytes;
+        Some(entry.val)
+    }
+}
+
+/// The name of the subdirectory of [CachingBlobConfig::disk_dir] that holds
+/// the disk tier.
+const DISK_CACHE_SUBDIR: &str = "mz-persist-blob-cache";
+
+/// The name of the file that marks [DISK_CACHE_SUBDIR] as created by a
+/// [CachingBlob] and thus safe to clear.
+const DISK_CACHE_SENTINEL: &str = ".mz-persist-blob-cache";
+
+/// A local disk tier for [CachingBlob].
+#[derive(Debug)]
+struct DiskCache {
+    dir: PathBuf,
+    index: Mutex<Lru<()>>,
+}
+
+impl DiskCache {
+    fn open(parent: &Path, limit_bytes: usize) -> Result<Self, Error> {
+        let dir = parent.join(DISK_CACHE_SUBDIR);
+        let sentinel = dir.join(DISK_CACHE_SENTINEL);
+        // We don't trust anything left over from a previous process, so start
+        // fresh. Only ever delete a directory we created ourselves, though.
+        if dir.exists() {
+            if !sentinel.exists() {
+                return Err(Error::from(format!(
+                    "refusing to clear blob cache directory {} which was not created by persist",
+                    dir.display()
+                )));
+            }
+            std::fs::remove_dir_all(&dir)?;
+        }
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(&sentinel, b"")?;
+        Ok(DiskCache {
+            dir,
+            index: Mutex::new(Lru::new(limit_bytes)),
+        })
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        // Blob keys are arbitrary strings, so encode them into something that
+        // is always a valid file name.
+        self.dir
+            .join(base64::encode_config(key, base64::URL_SAFE_NO_PAD))
+    }
+
+    async fn get(&self, key: &str) -> Result<Option<Vec<u8>>, Error> {
+        let present = self.index.lock().expect("lock poisoned").get(key).is_some();
+        if !present {
+            return Ok(None);
+        }
+        // NB: The entry might have been evicted (and the file deleted) since
+        // we checked the index. The caller treats an error here as a miss.
+        let value = fs::read(self.path(key)).await?;
+        Ok(Some(value))
+    }
+
+    async fn set(
+        &self,
+        key: &str,
+        value: &[u8],
+        metrics: &CachingBlobMetrics,
+    ) -> Result<(), Error> {
+        // Write to a temporary file first and rename it into place, so that
+        // readers never see a partially written value.
+        let tmp_path = self.dir.join(format!("tmp-{}", Uuid::new_v4()));
+        fs::write(&tmp_path, value).await?;
+        let path = self.path(key);
+        fs::rename(&tmp_path, &path).await?;
+
+        let (evicted, inserted) = {
+            let mut index = self.index.lock().expect("lock poisoned");
+            let evicted = index.insert(key.to_owned(), (), value.len());
+            metrics.disk_bytes.set(u64::cast_from(index.total_bytes));
+            metrics.disk_entry_count.set(u64::cast_from(index.len()));
+            (evicted, index.contains(key))
+        };
+        for (evicted_key, ()) in evicted {
+            // A previous entry for this key shares the file we just renamed
+            // into place, so leave it alone. On the other hand, if the value
+            // we wrote was rejected (because it's too large), this removes the
+            // file, as intended.
+            if inserted && evicted_key == key {
+                continue;
+            }
+            if let Err(err) = fs::remove_file(self.path(&evicted_key)).await {
+                metrics.disk_error_count.inc();
+                warn!("failed to remove evicted blob cache file: {}", err);
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str, metrics: &CachingBlobMetrics) -> Result<(), Error> {
+        let removed = {
+            let mut index = self.index.lock().expect("lock poisoned");
+            let removed = index.remove(key).is_some();
+            metrics.disk_bytes.set(u64::cast_from(index.total_bytes));
+            metrics.disk_entry_count.set(u64::cast_from(index.len()));
+            removed
+        };
+        if removed {
+            fs::remove_file(self.path(key)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A [BlobMulti] wrapper that caches fetched values in memory and, optionally,
+/// on local disk.
+///
+/// This is intended for values which are never modified after they are first
+/// written (e.g. persist batch parts), so cached values are never invalidated
+/// by writes to the backing storage. Overwriting the value of a key through
+/// this wrapper will drop it from the cache, but overwrites by other processes
+/// are not observed. Deletes through this wrapper also drop the key from the
+/// cache.
+///
+/// A value is only cached once it's been read: writes through this wrapper
+/// don't populate the cache. Concurrent reads of the same uncached key are
+/// coalesced into a single fetch from the backing storage.
+#[derive(Debug)]
+pub struct CachingBlob {
+    blob: Arc<dyn BlobMulti + Send + Sync>,
+    metrics: Arc<CachingBlobMetrics>,
+    memory: Mutex<Lru<Arc<Vec<u8>>>>,
+    disk: Option<DiskCache>,
+    fetches: Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>,
+}
+
+impl CachingBlob {
+    /// Returns a new [CachingBlob] in front of `blob`.
+    pub fn new(
+        blob: Arc<dyn BlobMulti + Send + Sync>,
+        config: CachingBlobConfig,
+        metrics: Arc<CachingBlobMetrics>,
+    ) -> Result<Self, ExternalError> {
+        let disk = match config.disk_dir {
+            Some(dir) => Some(DiskCache::open(&dir, config.disk_limit_bytes)?),
+            None => None,
+        };
+        Ok(CachingBlob {
+            blob,
+            metrics,
+            memory: Mutex::new(Lru::new(config.memory_limit_bytes)),
+            disk,
+            fetches: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Returns the value for `key` if it's in either tier of the cache.
+    async fn get_cached(&self, key: &str) -> Option<Vec<u8>> {
+        if let Some(value) = self.get_memory(key) {
+            self.metrics.memory_hit_count.inc();
+            return Some(value);
+        }
+        let disk = self.disk.as_ref()?;
+        match disk.get(key).await {
+            Ok(Some(value)) => {
+                self.metrics.disk_hit_count.inc();
+                self.insert_memory(key, &value);
+                Some(value)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                self.metrics.disk_error_count.inc();
+                warn!("failed to read blob cache file for {}: {}", key, err);
+                None
+            }
+        }
+    }
+
+    fn get_memory(&self, key: &str) -> Option<Vec<u8>> {
+        let mut memory = self.memory.lock().expect("lock poisoned");
+        memory.get(key).map(|value| value.as_ref().clone())
+    }
+
+    fn insert_memory(&self, key: &str, value: &[u8]) {
+        let mut memory = self.memory.lock().expect("lock poisoned");
+        // NB: Anything evicted from memory is either already on disk or will
+        // be refetched if needed, so we just drop it.
+        let _ = memory.insert(key.to_owned(), Arc::new(value.to_vec()), value.len());
+        self.metrics
+            .memory_bytes
+            .set(u64::cast_from(memory.total_bytes));
+        self.metrics
+            .memory_entry_count
+            .set(u64::cast_from(memory.len()));
+    }
+
+    async fn insert(&self, key: &str, value: &[u8]) {
+        self.insert_memory(key, value);
+        if let Some(disk) = self.disk.as_ref() {
+            let already_on_disk = disk.index.lock().expect("lock poisoned").contains(key);
+            if !already_on_disk {
+                if let Err(err) = disk.set(key, value, &self.metrics).await {
+                    self.metrics.disk_error_count.inc();
+                    warn!("failed to write blob cache file for {}: {}", key, err);
+                }
+            }
+        }
+    }
+
+    async fn remove(&self, key: &str) {
+        {
+            let mut memory = self.memory.lock().expect("lock poisoned");
+            memory.remove(key);
+            self.metrics
+                .memory_bytes
+                .set(u64::cast_from(memory.total_bytes));
+            self.metrics
+                .memory_entry_count
+                .set(u64::cast_from(memory.len()));
+        }
+        if let Some(disk) = self.disk.as_ref() {
+            if let Err(err) = disk.remove(key, &self.metrics).await {
+                self.metrics.disk_error_count.inc();
+                warn!("failed to remove blob cache file for {}: {}", key, err);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BlobMulti for CachingBlob {
+    async fn get(&self, deadline: Instant, key: &str) -> Result<Option<Vec<u8>>, ExternalError> {
+        if let Some(value) = self.get_cached(key).await {
+            return Ok(Some(value));
+        }
+
+        // Only one fetch per key is in flight at a time. Everyone else waits
+        // for it to finish and then (usually) finds the value in the cache.
+        let fetch = Arc::clone(
+            self.fetches
+                .lock()
+                .expect("lock poisoned")
+                .entry(key.to_owned())
+                .or_default(),
+        );
+        let fetch_guard = fetch.lock().await;
+        let res = match self.get_cached(key).await {
+            Some(value) => Ok(Some(value)),
+            None => {
+                self.metrics.miss_count.inc();
+                let res = self.blob.get(deadline, key).await;
+                if let Ok(Some(value)) = res.as_ref() {
+                    self.metrics.fetch_bytes.inc_by(u64::cast_from(value.len()));
+                    self.insert(key, value).await;
+                }
+                // NB: We intentionally don't cache a missing value. The backing
+                // storage may not be linearizable, so it might show up later.
+                res
+            }
+        };
+        drop(fetch_guard);
+        let mut fetches = self.fetches.lock().expect("lock poisoned");
+        if fetches.get(key).map_or(false, |x| Arc::ptr_eq(x, &fetch)) {
+            fetches.remove(key);
+        }
+        res
+    }
+
+    async fn list_keys(&self, deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        self.blob.list_keys(deadline).await
+    }
+
+    async fn set(
+        &self,
+        deadline: Instant,
+        key: &str,
+        value: Vec<u8>,
+        atomic: Atomicity,
+    ) -> Result<(), ExternalError> {
+        // Values are expected to be immutable, but be defensive in case one is
+        // overwritten anyway.
+        self.remove(key).await;
+        self.blob.set(deadline, key, value, atomic).await
+    }
+
+    async fn delete(&self, deadline: Instant, key: &str) -> Result<(), ExternalError> {
+        self.remove(key).await;
+        self.blob.delete(deadline, key).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::mem::{MemBlobMulti, MemBlobMultiConfig};
+
+    use super::*;
+
+    fn no_timeout() -> Instant {
+        Instant::now() + Duration::from_secs(1_000_000)
+    }
+
+    #[test]
+    fn lru() {
+        let mut lru = Lru::new(10);
+        assert!(lru.insert("a".into(), 'a', 4).is_empty());
+        assert!(lru.insert("b".into(), 'b', 4).is_empty());
+        // Touch a so that b is the least recently used.
+        assert_eq!(lru.get("a"), Some(&'a'));
+        assert_eq!(lru.insert("c".into(), 'c', 4), vec![("b".into(), 'b')]);
+        assert_eq!(lru.get("b"), None);
+        assert_eq!(lru.total_bytes, 8);
+
+        // Too big to ever fit.
+        assert_eq!(lru.insert("d".into(), 'd', 11), vec![("d".into(), 'd')]);
+        assert_eq!(lru.len(), 2);
+
+        // Replacing an entry returns the old value.
+        assert_eq!(lru.insert("a".into(), 'A', 2), vec![("a".into(), 'a')]);
+        assert_eq!(lru.total_bytes, 6);
+        assert_eq!(lru.remove("a"), Some('A'));
+        assert_eq!(lru.total_bytes, 4);
+    }
+
+    #[tokio::test]
+    async fn caching_blob_tiers() -> Result<(), ExternalError> {
+        let temp_dir = tempfile::tempdir().map_err(Error::from)?;
+        let backing = Arc::new(MemBlobMulti::open(MemBlobMultiConfig::default()));
+        let metrics = Arc::new(CachingBlobMetrics::default());
+        let config = CachingBlobConfig {
+            memory_limit_bytes: 5,
+            disk_dir: Some(temp_dir.path().to_owned()),
+            disk_limit_bytes: 10,
+        };
+        let blob = CachingBlob::new(
+            Arc::clone(&backing) as Arc<dyn BlobMulti + Send + Sync>,
+            config,
+            Arc::clone(&metrics),
+        )?;
+
+        blob.set(
+            no_timeout(),
+            "a",
+            b"aaaa".to_vec(),
+            Atomicity::RequireAtomic,
+        )
+        .await?;
+        blob.set(
+            no_timeout(),
+            "b",
+            b"bbbb".to_vec(),
+            Atomicity::RequireAtomic,
+        )
+        .await?;
+        blob.set(
+            no_timeout(),
+            "c",
+            b"cccccc".to_vec(),
+            Atomicity::RequireAtomic,
+        )
+        .await?;
+
+        // Writes don't populate the cache, so the first read of each key is a
+        // miss.
+        assert_eq!(blob.get(no_timeout(), "a").await?, Some(b"aaaa".to_vec()));
+        assert_eq!(blob.get(no_timeout(), "b").await?, Some(b"bbbb".to_vec()));
+        assert_eq!(metrics.miss_count.get(), 2);
+        assert_eq!(metrics.fetch_bytes.get(), 8);
+
+        // b is in memory, a was evicted to make room but is still on disk.
+        assert_eq!(blob.get(no_timeout(), "b").await?, Some(b"bbbb".to_vec()));
+        assert_eq!(metrics.memory_hit_count.get(), 1);
+        assert_eq!(blob.get(no_timeout(), "a").await?, Some(b"aaaa".to_vec()));
+        assert_eq!(metrics.disk_hit_count.get(), 1);
+        assert_eq!(metrics.miss_count.get(), 2);
+
+        // c is too big for memory and pushes b (the least recently used) out of
+        // the disk tier.
+        assert_eq!(blob.get(no_timeout(), "c").await?, Some(b"cccccc".to_vec()));
+        assert_eq!(metrics.miss_count.get(), 3);
+        assert_eq!(metrics.memory_entry_count.get(), 1);
+        assert_eq!(metrics.disk_entry_count.get(), 2);
+        assert_eq!(metrics.disk_bytes.get(), 10);
+        assert_eq!(blob.get(no_timeout(), "c").await?, Some(b"cccccc".to_vec()));
+        assert_eq!(metrics.disk_hit_count.get(), 2);
+
+        // Deletes go through to the backing storage and drop the cached
+        // values.
+        blob.delete(no_timeout(), "b").await?;
+        assert_eq!(backing.get(no_timeout(), "b").await?, None);
+        assert_eq!(blob.get(no_timeout(), "b").await?, None);
+
+        // Missing values aren't cached.
+        assert_eq!(blob.get(no_timeout(), "d").await?, None);
+        backing
+            .set(no_timeout(), "d", b"d".to_vec(), Atomicity::RequireAtomic)
+            .await?;
+        assert_eq!(blob.get(no_timeout(), "d").await?, Some(b"d".to_vec()));
+        assert_eq!(metrics.disk_error_count.get(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn disk_cache_dir() -> Result<(), Error> {
+        let temp_dir = tempfile::tempdir()?;
+        let user_file = temp_dir.path().join("keep");
+        std::fs::write(&user_file, b"keep")?;
+
+        // Opening the cache only touches its own subdirectory, and reopening
+        // it clears anything left over.
+        let disk = DiskCache::open(temp_dir.path(), 10)?;
+        let leftover = disk.dir.join("leftover");
+        std::fs::write(&leftover, b"leftover")?;
+        drop(disk);
+        let _disk = DiskCache::open(temp_dir.path(), 10)?;
+        assert!(user_file.exists());
+        assert!(!leftover.exists());
+
+        // A subdirectory that isn't marked as ours is left alone.
+        let other_dir = tempfile::tempdir()?;
+        let unmarked = other_dir.path().join(DISK_CACHE_SUBDIR);
+        std::fs::create_dir(&unmarked)?;
+        std::fs::write(unmarked.join("keep"), b"keep")?;
+        assert!(DiskCache::open(other_dir.path(), 10).is_err());
+        assert!(unmarked.join("keep").exists());
+
+        Ok(())
+    }
+}
@@ -9,6 +9,7 @@
 
 //! Configuration for [crate::location] implementations.
 
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
@@ -16,6 +17,7 @@ use std::time::Instant;
 use anyhow::anyhow;
 use url::Url;
 
+use crate::encryption::{EncryptedBlobMulti, EncryptedConsensus, EncryptionConfig, EncryptionKeys};
 use crate::file::{FileBlobConfig, FileBlobMulti};
 use crate::location::{BlobMulti, Consensus, ExternalError};
 use crate::s3::{S3BlobConfig, S3BlobMulti};
@@ -28,21 +30,41 @@ pub enum BlobMultiConfig {
     File(FileBlobConfig),
     /// Config for [S3BlobMulti].
     S3(S3BlobConfig),
+    /// Config for [EncryptedBlobMulti] wrapping another [BlobMulti].
+    Encrypted(Box<BlobMultiConfig>, EncryptionConfig),
 }
 
 impl BlobMultiConfig {
     /// Opens the associated implementation of [BlobMulti].
+    ///
+    /// If the blob is encrypted, its keys are read with `read_key`.
     pub async fn open(
         self,
         deadline: Instant,
+        read_key: &(dyn Fn(&str) -> Result<Vec<u8>, anyhow::Error> + Sync),
     ) -> Result<Arc<dyn BlobMulti + Send + Sync>, ExternalError> {
-        match self {
+        let (config, encryption) = match self {
+            BlobMultiConfig::Encrypted(config, encryption) => (*config, Some(encryption)),
+            config => (config, None),
+        };
+        // Load the keys first, so we fail fast if they're misconfigured.
+        let keys = encryption
+            .map(|encryption| EncryptionKeys::load(&encryption, read_key))
+            .transpose()?;
+        let blob = match config {
             BlobMultiConfig::File(config) => FileBlobMulti::open(deadline, config)
                 .await
                 .map(|x| Arc::new(x) as Arc<dyn BlobMulti + Send + Sync>),
             BlobMultiConfig::S3(config) => S3BlobMulti::open(deadline, config)
                 .await
                 .map(|x| Arc::new(x) as Arc<dyn BlobMulti + Send + Sync>),
+            BlobMultiConfig::Encrypted(..) => Err(ExternalError::from(anyhow!(
+                "nested blob encryption is not supported"
+            ))),
+        }?;
+        match keys {
+            Some(keys) => Ok(Arc::new(EncryptedBlobMulti::new(blob, keys))),
+            None => Ok(blob),
         }
     }
 
@@ -78,6 +100,11 @@ impl BlobMultiConfig {
             )),
         }?;
 
+        let config = match encryption_config(&mut query_params) {
+            Some(encryption) => BlobMultiConfig::Encrypted(Box::new(config), encryption),
+            None => config,
+        };
+
         if !query_params.is_empty() {
             return Err(ExternalError::from(anyhow!(
                 "unknown blob location params {}: {}",
@@ -94,22 +121,57 @@ impl BlobMultiConfig {
     }
 }
 
+/// Parses the encryption config shared by all [BlobMulti] and [Consensus]
+/// locations out of `query_params`, if any.
+///
+/// Encryption is orthogonal to the type of storage, so it's configured the
+/// same way for all of them. Only the ids of the keys are part of the location;
+/// the keys themselves are secrets, which are read by the process opening the
+/// location.
+fn encryption_config(query_params: &mut HashMap<Cow<str>, Cow<str>>) -> Option<EncryptionConfig> {
+    query_params
+        .remove("encryption_key_ids")
+        .map(|key_ids| EncryptionConfig {
+            key_ids: key_ids.split(',').map(|x| x.to_owned()).collect(),
+        })
+}
+
 /// Config for an implementation of [Consensus].
 #[derive(Debug)]
 pub enum ConsensusConfig {
     /// Config for [SqliteConsensus].
     Sqlite(String),
+    /// Config for [EncryptedConsensus] wrapping another [Consensus].
+    Encrypted(Box<ConsensusConfig>, EncryptionConfig),
 }
 
 impl ConsensusConfig {
     /// Opens the associated implementation of [Consensus].
+    ///
+    /// If the consensus is encrypted, its keys are read with `read_key`.
     pub async fn open(
         self,
         _deadline: Instant,
+        read_key: &(dyn Fn(&str) -> Result<Vec<u8>, anyhow::Error> + Sync),
     ) -> Result<Arc<dyn Consensus + Send + Sync>, ExternalError> {
-        match self {
+        let (config, encryption) = match self {
+            ConsensusConfig::Encrypted(config, encryption) => (*config, Some(encryption)),
+            config => (config, None),
+        };
+        // Load the keys first, so we fail fast if they're misconfigured.
+        let keys = encryption
+            .map(|encryption| EncryptionKeys::load(&encryption, read_key))
+            .transpose()?;
+        let consensus = match config {
             ConsensusConfig::Sqlite(config) => SqliteConsensus::open(config)
                 .map(|x| Arc::new(x) as Arc<dyn Consensus + Send + Sync>),
+            ConsensusConfig::Encrypted(..) => Err(ExternalError::from(anyhow!(
+                "nested consensus encryption is not supported"
+            ))),
+        }?;
+        match keys {
+            Some(keys) => Ok(Arc::new(EncryptedConsensus::new(consensus, keys))),
+            None => Ok(consensus),
         }
     }
 
@@ -122,7 +184,7 @@ impl ConsensusConfig {
                 err
             )
         })?;
-        let query_params = url.query_pairs().collect::<HashMap<_, _>>();
+        let mut query_params = url.query_pairs().collect::<HashMap<_, _>>();
 
         let config = match url.scheme() {
             "sqlite" => Ok(ConsensusConfig::Sqlite(url.path().to_owned())),
@@ -133,6 +195,11 @@ impl ConsensusConfig {
             )),
         }?;
 
+        let config = match encryption_config(&mut query_params) {
+            Some(encryption) => ConsensusConfig::Encrypted(Box::new(config), encryption),
+            None => config,
+        };
+
         if !query_params.is_empty() {
             return Err(ExternalError::from(anyhow!(
                 "unknown consensus location params {}: {}",
@@ -0,0 +1,644 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Encryption at rest for [BlobMulti] and [Consensus] storage.
+//!
+//! Values are envelope encrypted: each value is encrypted with a fresh random
+//! data key, and the data key is itself encrypted ("wrapped") with a key
+//! encryption key. Key encryption keys are secrets: they're identified by the
+//! id of the secret and read through the secrets machinery of the process that
+//! opens the location. A location only ever names the ids of its keys, never
+//! where to find them.
+//!
+//! The id of the key encryption key is written in a small header in front of
+//! every encrypted value. This allows keys to be rotated without rewriting
+//! existing data: a new key is used for all writes, while old keys are kept
+//! around for as long as data encrypted with them needs to be read.
+//!
+//! Encrypted values are laid out as:
+//!
+//! ```text
+//! magic (4 bytes) | version (1 byte) | key id length (2 bytes, little endian)
+//! | key id | wrapped data key nonce (12 bytes) | wrapped data key (32 bytes)
+//! | wrapped data key tag (16 bytes) | data nonce (12 bytes) | data tag (16 bytes)
+//! | ciphertext
+//! ```
+//!
+//! Both layers use AES-256-GCM. The data key is authenticated against the key
+//! id and the value is authenticated against the name it is stored under (its
+//! blob key, or its consensus key and sequence number), so a value can't be
+//! silently swapped for another one.
+//!
+//! Consensus holds the state of persist shards, including the incremental
+//! state diffs, which reference blob keys and describe the contents of batches,
+//! so it's encrypted with the same keys as blob. Sequence numbers and keys are
+//! left in plaintext, as consensus needs them to order and list versions.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use openssl::rand::rand_bytes;
+use openssl::symm::{decrypt_aead, encrypt_aead, Cipher};
+
+use crate::location::{Atomicity, BlobMulti, Consensus, ExternalError, SeqNo, VersionedData};
+
+const MAGIC: &[u8; 4] = b"MZPE";
+const VERSION: u8 = 1;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Configuration for [EncryptedBlobMulti] and [EncryptedConsensus].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EncryptionConfig {
+    /// The ids of the keys to load. The first is used to encrypt new values,
+    /// the rest are only used to decrypt existing ones.
+    pub key_ids: Vec<String>,
+}
+
+/// A set of key encryption keys.
+#[derive(Clone)]
+pub struct EncryptionKeys {
+    active: String,
+    keys: HashMap<String, [u8; KEY_LEN]>,
+}
+
+impl std::fmt::Debug for EncryptionKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Intentionally don't print the keys themselves.
+        let mut key_ids = self.keys.keys().collect::<Vec<_>>();
+        key_ids.sort();
+        f.debug_struct("EncryptionKeys")
+            .field("active", &self.active)
+            .field("key_ids", &key_ids)
+            .finish()
+    }
+}
+
+impl EncryptionKeys {
+    /// Loads the keys described by the given config, reading the contents of
+    /// each key with `read_key`.
+    ///
+    /// Each key must contain exactly 32 bytes of key material, either raw or as
+    /// 64 hex characters.
+    pub fn load<F>(config: &EncryptionConfig, read_key: F) -> Result<Self, ExternalError>
+    where
+        F: Fn(&str) -> Result<Vec<u8>, anyhow::Error>,
+    {
+        let mut keys = Vec::with_capacity(config.key_ids.len());
+        for key_id in config.key_ids.iter() {
+            validate_key_id(key_id)?;
+            let contents = read_key(key_id)
+                .map_err(|err| anyhow!("reading encryption key {}: {}", key_id, err))?;
+            let key = parse_key(&contents)
+                .map_err(|err| anyhow!("invalid encryption key {}: {}", key_id, err))?;
+            keys.push((key_id.clone(), key));
+        }
+        Self::from_keys(keys)
+    }
+
+    /// Returns a set of keys made from the given key material.
+    ///
+    /// The first key is used to encrypt new values. Key ids must be unique.
+    pub fn from_keys(keys: Vec<(String, [u8; KEY_LEN])>) -> Result<Self, ExternalError> {
+        let active = keys
+            .first()
+            .ok_or_else(|| anyhow!("at least one encryption key is required"))?
+            .0
+            .clone();
+        let mut ret = HashMap::with_capacity(keys.len());
+        for (key_id, key) in keys {
+            validate_key_id(&key_id)?;
+            if ret.insert(key_id.clone(), key).is_some() {
+                return Err(ExternalError::from(anyhow!(
+                    "duplicate encryption key id: {}",
+                    key_id
+                )));
+            }
+        }
+        Ok(EncryptionKeys { active, keys: ret })
+    }
+
+    /// Encrypts `value`, which is to be stored under `name`, with the active
+    /// key.
+    pub fn encrypt(&self, name: &str, value: &[u8]) -> Result<Vec<u8>, ExternalError> {
+        let kek = &self.keys[&self.active];
+        let cipher = Cipher::aes_256_gcm();
+
+        let mut dek = [0u8; KEY_LEN];
+        rand_bytes(&mut dek).map_err(openssl_err)?;
+        let mut dek_nonce = [0u8; NONCE_LEN];
+        rand_bytes(&mut dek_nonce).map_err(openssl_err)?;
+        let mut dek_tag = [0u8; TAG_LEN];
+        let wrapped_dek = encrypt_aead(
+            cipher,
+            kek,
+            Some(&dek_nonce),
+            self.active.as_bytes(),
+            &dek,
+            &mut dek_tag,
+        )
+        .map_err(openssl_err)?;
+        debug_assert_eq!(wrapped_dek.len(), KEY_LEN);
+
+        let mut data_nonce = [0u8; NONCE_LEN];
+        rand_bytes(&mut data_nonce).map_err(openssl_err)?;
+        let mut data_tag = [0u8; TAG_LEN];
+        let ciphertext = encrypt_aead(
+            cipher,
+            &dek,
+            Some(&data_nonce),
+            name.as_bytes(),
+            value,
+            &mut data_tag,
+        )
+        .map_err(openssl_err)?;
+
+        let key_id_len = u16::try_from(self.active.len())
+            .map_err(|_| anyhow!("encryption key id too long: {}", self.active))?;
+        let mut buf = Vec::with_capacity(
+            MAGIC.len()
+                + 1
+                + 2
+                + self.active.len()
+                + NONCE_LEN
+                + KEY_LEN
+                + TAG_LEN
+                + NONCE_LEN
+                + TAG_LEN
+                + ciphertext.len(),
+        );
+        buf.extend_from_slice(MAGIC);
+        buf.push(VERSION);
+        buf.extend_from_slice(&key_id_len.to_le_bytes());
+        buf.extend_from_slice(self.active.as_bytes());
+        buf.extend_from_slice(&dek_nonce);
+        buf.extend_from_slice(&wrapped_dek);
+        buf.extend_from_slice(&dek_tag);
+        buf.extend_from_slice(&data_nonce);
+        buf.extend_from_slice(&data_tag);
+        buf.extend_from_slice(&ciphertext);
+        Ok(buf)
+    }
+
+    /// Decrypts `buf`, which was stored under `name`.
+    ///
+    /// Returns an error if `buf` is not encrypted, was encrypted with a key
+    /// that is not in this set, or fails authentication.
+    pub fn decrypt(&self, name: &str, buf: &[u8]) -> Result<Vec<u8>, ExternalError> {
+        let mut buf = buf;
+        let magic = take(&mut buf, MAGIC.len(), name)?;
+        if magic != MAGIC {
+            return Err(ExternalError::from(anyhow!(
+                "value {} is not encrypted",
+                name
+            )));
+        }
+        let version = take(&mut buf, 1, name)?[0];
+        if version != VERSION {
+            return Err(ExternalError::from(anyhow!(
+                "value {} has unsupported encryption version {}",
+                name,
+                version
+            )));
+        }
+        let key_id_len = take(&mut buf, 2, name)?;
+        let key_id_len = usize::from(u16::from_le_bytes([key_id_len[0], key_id_len[1]]));
+        let key_id = take(&mut buf, key_id_len, name)?;
+        let key_id = std::str::from_utf8(key_id)
+            .map_err(|_| anyhow!("value {} has invalid encryption key id", name))?;
+        let kek = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| anyhow!("value {} is encrypted with unknown key {}", name, key_id))?;
+        let dek_nonce = take(&mut buf, NONCE_LEN, name)?;
+        let wrapped_dek = take(&mut buf, KEY_LEN, name)?;
+        let dek_tag = take(&mut buf, TAG_LEN, name)?;
+        let data_nonce = take(&mut buf, NONCE_LEN, name)?;
+        let data_tag = take(&mut buf, TAG_LEN, name)?;
+        let ciphertext = buf;
+
+        let cipher = Cipher::aes_256_gcm();
+        let dek = decrypt_aead(
+            cipher,
+            kek,
+            Some(dek_nonce),
+            key_id.as_bytes(),
+            wrapped_dek,
+            dek_tag,
+        )
+        .map_err(|_| {
+            anyhow!(
+                "value {} failed to decrypt data key with key {}",
+                name,
+                key_id
+            )
+        })?;
+        let value = decrypt_aead(
+            cipher,
+            &dek,
+            Some(data_nonce),
+            name.as_bytes(),
+            ciphertext,
+            data_tag,
+        )
+        .map_err(|_| anyhow!("value {} failed to decrypt", name))?;
+        Ok(value)
+    }
+}
+
+fn take<'a>(buf: &mut &'a [u8], len: usize, name: &str) -> Result<&'a [u8], ExternalError> {
+    if buf.len() < len {
+        return Err(ExternalError::from(anyhow!(
+            "value {} has truncated encryption header",
+            name
+        )));
+    }
+    let (ret, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(ret)
+}
+
+fn validate_key_id(key_id: &str) -> Result<(), ExternalError> {
+    // Key ids are the ids of secrets and end up in every encrypted value, so
+    // be strict about what's allowed.
+    let valid = !key_id.is_empty()
+        && key_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(ExternalError::from(anyhow!(
+            "invalid encryption key id: {:?}",
+            key_id
+        )));
+    }
+    Ok(())
+}
+
+fn parse_key(contents: &[u8]) -> Result<[u8; KEY_LEN], String> {
+    if contents.len() == KEY_LEN {
+        let mut key = [0u8; KEY_LEN];
+        key.copy_from_slice(contents);
+        return Ok(key);
+    }
+    let contents = std::str::from_utf8(contents)
+        .map_err(|_| {
+            format!(
+                "expected {} bytes or {} hex characters",
+                KEY_LEN,
+                2 * KEY_LEN
+            )
+        })?
+        .trim();
+    let key = hex::decode(contents)
+        .ok()
+        .filter(|x| x.len() == KEY_LEN)
+        .ok_or_else(|| {
+            format!(
+                "expected {} bytes or {} hex characters",
+                KEY_LEN,
+                2 * KEY_LEN
+            )
+        })?;
+    let mut ret = [0u8; KEY_LEN];
+    ret.copy_from_slice(&key);
+    Ok(ret)
+}
+
+fn openssl_err(err: openssl::error::ErrorStack) -> ExternalError {
+    ExternalError::from(anyhow!("encryption: {}", err))
+}
+
+/// An implementation of [BlobMulti] that transparently encrypts the values
+/// stored in another [BlobMulti].
+#[derive(Debug)]
+pub struct EncryptedBlobMulti {
+    blob: Arc<dyn BlobMulti + Send + Sync>,
+    keys: EncryptionKeys,
+}
+
+impl EncryptedBlobMulti {
+    /// Returns a new [EncryptedBlobMulti] storing values in `blob`.
+    pub fn new(blob: Arc<dyn BlobMulti + Send + Sync>, keys: EncryptionKeys) -> Self {
+        EncryptedBlobMulti { blob, keys }
+    }
+}
+
+#[async_trait]
+impl BlobMulti for EncryptedBlobMulti {
+    async fn get(&self, deadline: Instant, key: &str) -> Result<Option<Vec<u8>>, ExternalError> {
+        match self.blob.get(deadline, key).await? {
+            Some(value) => Ok(Some(self.keys.decrypt(key, &value)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_keys(&self, deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        self.blob.list_keys(deadline).await
+    }
+
+    async fn set(
+        &self,
+        deadline: Instant,
+        key: &str,
+        value: Vec<u8>,
+        atomic: Atomicity,
+    ) -> Result<(), ExternalError> {
+        let value = self.keys.encrypt(key, &value)?;
+        self.blob.set(deadline, key, value, atomic).await
+    }
+
+    async fn delete(&self, deadline: Instant, key: &str) -> Result<(), ExternalError> {
+        self.blob.delete(deadline, key).await
+    }
+}
+
+/// An implementation of [Consensus] that transparently encrypts the data
+/// stored in another [Consensus].
+#[derive(Debug)]
+pub struct EncryptedConsensus {
+    consensus: Arc<dyn Consensus + Send + Sync>,
+    keys: EncryptionKeys,
+}
+
+impl EncryptedConsensus {
+    /// Returns a new [EncryptedConsensus] storing data in `consensus`.
+    pub fn new(consensus: Arc<dyn Consensus + Send + Sync>, keys: EncryptionKeys) -> Self {
+        EncryptedConsensus { consensus, keys }
+    }
+
+    /// The name that the data at `seqno` of `key` is authenticated against,
+    /// so that it can't be replayed at another version or key.
+    fn name(key: &str, seqno: SeqNo) -> String {
+        format!("{}@{}", key, seqno.0)
+    }
+
+    fn encrypt(&self, key: &str, data: VersionedData) -> Result<VersionedData, ExternalError> {
+        let name = Self::name(key, data.seqno);
+        Ok(VersionedData {
+            seqno: data.seqno,
+            data: self.keys.encrypt(&name, &data.data)?,
+        })
+    }
+
+    fn decrypt(&self, key: &str, data: VersionedData) -> Result<VersionedData, ExternalError> {
+        let name = Self::name(key, data.seqno);
+        Ok(VersionedData {
+            seqno: data.seqno,
+            data: self.keys.decrypt(&name, &data.data)?,
+        })
+    }
+}
+
+#[async_trait]
+impl Consensus for EncryptedConsensus {
+    async fn head(
+        &self,
+        key: &str,
+        deadline: Instant,
+    ) -> Result<Option<VersionedData>, ExternalError> {
+        match self.consensus.head(key, deadline).await? {
+            Some(data) => Ok(Some(self.decrypt(key, data)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn compare_and_set(
+        &self,
+        key: &str,
+        deadline: Instant,
+        expected: Option<SeqNo>,
+        new: VersionedData,
+    ) -> Result<Result<(), Option<VersionedData>>, ExternalError> {
+        let new = self.encrypt(key, new)?;
+        match self
+            .consensus
+            .compare_and_set(key, deadline, expected, new)
+            .await?
+        {
+            Ok(()) => Ok(Ok(())),
+            Err(Some(current)) => Ok(Err(Some(self.decrypt(key, current)?))),
+            Err(None) => Ok(Err(None)),
+        }
+    }
+
+    async fn scan(
+        &self,
+        key: &str,
+        deadline: Instant,
+        from: SeqNo,
+    ) -> Result<Vec<VersionedData>, ExternalError> {
+        self.consensus
+            .scan(key, deadline, from)
+            .await?
+            .into_iter()
+            .map(|data| self.decrypt(key, data))
+            .collect()
+    }
+
+    async fn truncate(
+        &self,
+        key: &str,
+        deadline: Instant,
+        seqno: SeqNo,
+    ) -> Result<usize, ExternalError> {
+        self.consensus.truncate(key, deadline, seqno).await
+    }
+
+    async fn list_keys(&self, deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        self.consensus.list_keys(deadline).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::location::tests::{blob_multi_impl_test, consensus_impl_test};
+    use crate::mem::{MemConsensus, MemMultiRegistry};
+
+    use super::*;
+
+    fn test_keys(key_ids: &[&str]) -> EncryptionKeys {
+        let keys = key_ids
+            .iter()
+            .enumerate()
+            .map(|(idx, key_id)| {
+                let idx = u8::try_from(idx).expect("few test keys");
+                (key_id.to_string(), [idx; KEY_LEN])
+            })
+            .collect();
+        EncryptionKeys::from_keys(keys).expect("valid test keys")
+    }
+
+    #[tokio::test]
+    async fn encrypted_blob_multi() -> Result<(), ExternalError> {
+        let registry = Arc::new(tokio::sync::Mutex::new(MemMultiRegistry::new()));
+        blob_multi_impl_test(move |path| {
+            let registry = Arc::clone(&registry);
+            async move {
+                let blob = registry.lock().await.blob_multi(path).await;
+                Ok(EncryptedBlobMulti::new(Arc::new(blob), test_keys(&["k0"])))
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn encrypted_consensus() -> Result<(), ExternalError> {
+        consensus_impl_test(|| {
+            Ok(EncryptedConsensus::new(
+                Arc::new(MemConsensus::default()),
+                test_keys(&["k0"]),
+            ))
+        })
+        .await?;
+
+        // The data is stored encrypted and bound to its key and version.
+        let inner = Arc::new(MemConsensus::default());
+        let consensus = EncryptedConsensus::new(
+            Arc::clone(&inner) as Arc<dyn Consensus + Send + Sync>,
+            test_keys(&["k0"]),
+        );
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        let data = VersionedData {
+            seqno: SeqNo(1),
+            data: b"state diff".to_vec(),
+        };
+        assert_eq!(
+            consensus
+                .compare_and_set("s0", deadline, None, data.clone())
+                .await,
+            Ok(Ok(()))
+        );
+        let stored = inner.head("s0", deadline).await?.expect("data was set");
+        assert_ne!(stored.data, data.data);
+        assert_eq!(consensus.head("s0", deadline).await, Ok(Some(data)));
+
+        // Replaying the data at another version is detected.
+        let replayed = VersionedData {
+            seqno: SeqNo(2),
+            data: stored.data,
+        };
+        assert_eq!(
+            inner
+                .compare_and_set("s0", deadline, Some(SeqNo(1)), replayed)
+                .await,
+            Ok(Ok(()))
+        );
+        assert_eq!(
+            consensus.head("s0", deadline).await,
+            Err(ExternalError::from(anyhow!("value s0@2 failed to decrypt")))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn encrypt_decrypt() {
+        let keys = test_keys(&["k0"]);
+        let value = b"hello".to_vec();
+        let encrypted = keys.encrypt("b0", &value).expect("encrypt");
+        assert!(!encrypted
+            .windows(value.len())
+            .any(|x| x == value.as_slice()));
+        assert_eq!(keys.decrypt("b0", &encrypted), Ok(value));
+
+        // Values are bound to the key they're stored under.
+        assert_eq!(
+            keys.decrypt("b1", &encrypted),
+            Err(ExternalError::from(anyhow!("value b1 failed to decrypt")))
+        );
+
+        // Tampering is detected.
+        let mut tampered = encrypted.clone();
+        *tampered.last_mut().expect("non-empty") ^= 1;
+        assert_eq!(
+            keys.decrypt("b0", &tampered),
+            Err(ExternalError::from(anyhow!("value b0 failed to decrypt")))
+        );
+
+        // Plaintext and truncated values are rejected.
+        assert_eq!(
+            keys.decrypt("b0", b"MZ"),
+            Err(ExternalError::from(anyhow!(
+                "value b0 has truncated encryption header"
+            )))
+        );
+        assert_eq!(
+            keys.decrypt("b0", b"plaintext value"),
+            Err(ExternalError::from(anyhow!("value b0 is not encrypted")))
+        );
+    }
+
+    #[test]
+    fn key_rotation() {
+        let old = test_keys(&["k0"]);
+        let encrypted_old = old.encrypt("b0", b"old").expect("encrypt");
+
+        // After rotation, new values use the new key but old values are still
+        // readable.
+        let rotated = test_keys(&["k1", "k0"]);
+        let encrypted_new = rotated.encrypt("b1", b"new").expect("encrypt");
+        assert_eq!(rotated.decrypt("b0", &encrypted_old), Ok(b"old".to_vec()));
+        assert_eq!(rotated.decrypt("b1", &encrypted_new), Ok(b"new".to_vec()));
+
+        // Reading with a set of keys that doesn't include the one a value was
+        // encrypted with fails loudly.
+        assert_eq!(
+            old.decrypt("b1", &encrypted_new),
+            Err(ExternalError::from(anyhow!(
+                "value b1 is encrypted with unknown key k1"
+            )))
+        );
+    }
+
+    #[test]
+    fn load() {
+        let read_key = |key_id: &str| match key_id {
+            "u1" => Ok(vec![7u8; KEY_LEN]),
+            "u2" => Ok(format!("{}\n", "ab".repeat(KEY_LEN)).into_bytes()),
+            "u3" => Ok(b"too short".to_vec()),
+            _ => Err(anyhow!("unknown secret {}", key_id)),
+        };
+        let config = |key_ids: &[&str]| EncryptionConfig {
+            key_ids: key_ids.iter().map(|x| x.to_string()).collect(),
+        };
+        let keys = EncryptionKeys::load(&config(&["u2", "u1"]), read_key).expect("valid keys");
+        assert_eq!(keys.active, "u2");
+        assert_eq!(keys.keys["u1"], [7u8; KEY_LEN]);
+        assert_eq!(keys.keys["u2"], [0xab; KEY_LEN]);
+
+        assert!(EncryptionKeys::load(&config(&[]), read_key).is_err());
+        assert!(EncryptionKeys::load(&config(&["u3"]), read_key).is_err());
+        assert!(EncryptionKeys::load(&config(&["u4"]), read_key).is_err());
+        assert!(EncryptionKeys::load(&config(&["../u1"]), read_key).is_err());
+        assert_eq!(
+            EncryptionKeys::load(&config(&["u1", "u2", "u1"]), read_key).map(|_| ()),
+            Err(ExternalError::from(anyhow!(
+                "duplicate encryption key id: u1"
+            )))
+        );
+    }
+
+    #[test]
+    fn from_keys_duplicate() {
+        let keys = vec![
+            ("k0".to_owned(), [0u8; KEY_LEN]),
+            ("k0".to_owned(), [1u8; KEY_LEN]),
+        ];
+        assert_eq!(
+            EncryptionKeys::from_keys(keys).map(|_| ()),
+            Err(ExternalError::from(anyhow!(
+                "duplicate encryption key id: k0"
+            )))
+        );
+    }
+}
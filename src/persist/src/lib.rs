@@ -20,8 +20,10 @@
 
 use std::fmt;
 
+pub mod cache;
 pub mod cfg;
 pub mod client;
+pub mod encryption;
 pub mod error;
 pub mod file;
 pub mod gen;
@@ -77,7 +77,9 @@ pub fn check_meta_version_maybe_delete_data<B: Blob>(b: &mut B) -> Result<(), Er
 /// Read-only requests are assigned the SeqNo of a write, indicating that all
 /// mutating requests up to and including that one are reflected in the read
 /// state.
-#[derive(Clone, Copy, Debug, Default, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(
+    Clone, Copy, Debug, Default, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
 pub struct SeqNo(pub u64);
 
 impl timely::PartialOrder for SeqNo {
@@ -285,7 +287,10 @@ pub struct VersionedData {
 /// to allow multiple processes across multiple machines to agree to a total order
 /// of the evolution of the data.
 ///
-/// TODO: Do we need to expose the evolution of this data across a series of versions?
+/// Historical versions of the data are retained until they are explicitly
+/// removed with [Consensus::truncate], which allows users to store the
+/// evolution of the data as a sequence of diffs and read it back with
+/// [Consensus::scan].
 #[async_trait]
 pub trait Consensus: std::fmt::Debug {
     /// Returns a recent version of `data`, and the corresponding sequence number, if
@@ -314,6 +319,35 @@ pub trait Consensus: std::fmt::Debug {
         expected: Option<SeqNo>,
         new: VersionedData,
     ) -> Result<Result<(), Option<VersionedData>>, ExternalError>;
+
+    /// Return all versions of data stored for this `key` at sequence numbers
+    /// >= `from`, in ascending order of sequence number.
+    ///
+    /// Returns an empty vec if `from` is greater than the current sequence
+    /// number or if there is no data at this key.
+    async fn scan(
+        &self,
+        key: &str,
+        deadline: Instant,
+        from: SeqNo,
+    ) -> Result<Vec<VersionedData>, ExternalError>;
+
+    /// Deletes all historical versions of the data stored at `key` that are <
+    /// `seqno`, iff `seqno` <= the current sequence number.
+    ///
+    /// Returns the number of versions deleted on success. Returns an error if
+    /// `seqno` is greater than the current sequence number, or if there is no
+    /// data at this key.
+    async fn truncate(
+        &self,
+        key: &str,
+        deadline: Instant,
+        seqno: SeqNo,
+    ) -> Result<usize, ExternalError>;
+
+    /// Returns every key with data stored at this location, in ascending
+    /// order.
+    async fn list_keys(&self, deadline: Instant) -> Result<Vec<String>, ExternalError>;
 }
 
 /// The partially structured information stored in an exclusive-writer lock.
@@ -1035,6 +1069,67 @@ pub mod tests {
             Ok(Ok(()))
         );
 
+        // Scanning from the beginning returns every version that has been
+        // written, in order.
+        let versions = consensus.scan(key, deadline, SeqNo::default()).await?;
+        assert_eq!(
+            versions.iter().map(|x| x.seqno).collect::<Vec<_>>(),
+            vec![SeqNo(5), SeqNo(10), SeqNo(11)]
+        );
+        assert_eq!(versions[0].data, "abc".as_bytes());
+
+        // Scanning from the middle returns a suffix of the versions.
+        let versions = consensus.scan(key, deadline, SeqNo(6)).await?;
+        assert_eq!(
+            versions.iter().map(|x| x.seqno).collect::<Vec<_>>(),
+            vec![SeqNo(10), SeqNo(11)]
+        );
+
+        // Scanning past the end returns nothing.
+        assert_eq!(consensus.scan(key, deadline, SeqNo(12)).await, Ok(vec![]));
+
+        // Scanning a key that doesn't exist returns nothing.
+        assert_eq!(
+            consensus
+                .scan("not_a_key", deadline, SeqNo::default())
+                .await,
+            Ok(vec![])
+        );
+
+        // Truncating removes every version strictly less than the given seqno.
+        assert_eq!(consensus.truncate(key, deadline, SeqNo(10)).await, Ok(1));
+        let versions = consensus.scan(key, deadline, SeqNo::default()).await?;
+        assert_eq!(
+            versions.iter().map(|x| x.seqno).collect::<Vec<_>>(),
+            vec![SeqNo(10), SeqNo(11)]
+        );
+
+        // Truncating again is a no-op.
+        assert_eq!(consensus.truncate(key, deadline, SeqNo(10)).await, Ok(0));
+
+        // Truncating past the current seqno is an error and doesn't remove
+        // anything.
+        assert!(consensus.truncate(key, deadline, SeqNo(12)).await.is_err());
+        assert_eq!(consensus.scan(key, deadline, SeqNo(11)).await?.len(), 1);
+
+        // Truncating a key that doesn't exist is an error.
+        assert!(consensus
+            .truncate("not_a_key", deadline, SeqNo(1))
+            .await
+            .is_err());
+
+        // Truncating doesn't affect other keys.
+        assert_eq!(
+            consensus.head(other_key, deadline).await,
+            Ok(Some(state.clone()))
+        );
+
+        // Every key with data is listed, in order.
+        assert_eq!(
+            consensus.list_keys(deadline).await,
+            Ok(vec![key.to_owned(), other_key.to_owned()])
+        );
+
         Ok(())
     }
 
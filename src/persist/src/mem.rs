@@ -677,7 +677,7 @@ impl BlobMulti for MemBlobMulti {
 /// An in-memory implementation of [Consensus].
 #[derive(Debug)]
 pub struct MemConsensus {
-    data: Arc<TokioMutex<HashMap<String, VersionedData>>>,
+    data: Arc<TokioMutex<HashMap<String, Vec<VersionedData>>>>,
 }
 
 impl Default for MemConsensus {
@@ -695,7 +695,13 @@ impl Consensus for MemConsensus {
         key: &str,
         _deadline: Instant,
     ) -> Result<Option<VersionedData>, ExternalError> {
-        Ok(self.data.lock().await.get(key).cloned())
+        let store = self.data.lock().await;
+        let values = match store.get(key) {
+            None => return Ok(None),
+            Some(values) => values,
+        };
+
+        Ok(values.last().cloned())
     }
 
     async fn compare_and_set(
@@ -714,17 +720,75 @@ impl Consensus for MemConsensus {
         }
         let mut store = self.data.lock().await;
 
-        let data = store.get(key);
+        let data = match store.get(key) {
+            None => None,
+            Some(values) => values.last(),
+        };
         let seqno = data.as_ref().map(|data| data.seqno);
 
         if seqno != expected {
             return Ok(Err(data.cloned()));
         }
 
-        store.insert(key.to_string(), new);
+        store.entry(key.to_string()).or_default().push(new);
 
         Ok(Ok(()))
     }
+
+    async fn scan(
+        &self,
+        key: &str,
+        _deadline: Instant,
+        from: SeqNo,
+    ) -> Result<Vec<VersionedData>, ExternalError> {
+        let store = self.data.lock().await;
+        let mut results = vec![];
+        if let Some(values) = store.get(key) {
+            // TODO: we could instead binary search to find the first valid
+            // key and then binary search the rest.
+            for value in values {
+                if value.seqno >= from {
+                    results.push(value.clone());
+                }
+            }
+        }
+
+        Ok(results)
+    }
+
+    async fn truncate(
+        &self,
+        key: &str,
+        _deadline: Instant,
+        seqno: SeqNo,
+    ) -> Result<usize, ExternalError> {
+        let mut store = self.data.lock().await;
+        let values = match store.get_mut(key) {
+            None => return Err(ExternalError::from(anyhow!("no data at key: {}", key))),
+            Some(values) => values,
+        };
+
+        let current = values.last().map(|x| x.seqno);
+        if current.map_or(true, |current| current < seqno) {
+            return Err(ExternalError::from(anyhow!(
+                "upper bound too high for truncate: {:?}, current: {:?}",
+                seqno,
+                current
+            )));
+        }
+
+        let count_before = values.len();
+        values.retain(|val| val.seqno >= seqno);
+
+        Ok(count_before - values.len())
+    }
+
+    async fn list_keys(&self, _deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        let store = self.data.lock().await;
+        let mut keys = store.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        Ok(keys)
+    }
 }
 
 #[cfg(test)]
@@ -18,7 +18,6 @@ use async_trait::async_trait;
 use rusqlite::{named_params, params, Connection, OptionalExtension};
 use tokio::sync::Mutex;
 
-use crate::error::Error;
 use crate::location::{Consensus, ExternalError, SeqNo, VersionedData};
 
 const APPLICATION_ID: i32 = 0x0678_ef32; // chosen randomly
@@ -61,22 +60,6 @@ impl SqliteConsensus {
             conn: Arc::new(Mutex::new(conn)),
         })
     }
-
-    /// Remove all versions of data inserted at sequence numbers < `sequence_number`.
-    ///
-    /// TODO: We probably are going to move this function directly into the [Consensus]
-    /// trait itself.
-    async fn truncate(&self, key: &str, sequence_number: SeqNo) -> Result<(), Error> {
-        let conn = self.conn.lock().await;
-        let mut stmt = conn.prepare(
-            "DELETE FROM consensus
-             WHERE shard = $shard AND sequence_number < $sequence_number",
-        )?;
-
-        stmt.execute(named_params! {"$shard": key, "$sequence_number": sequence_number.0})?;
-
-        Ok(())
-    }
 }
 
 #[async_trait]
@@ -163,15 +146,6 @@ impl Consensus for SqliteConsensus {
         };
 
         if result == 1 {
-            // Truncate everything strictly less than the row we just inserted.
-            // We're doing this as a best-effort measure to avoid unbounded
-            // memory growth while using this API, so don't take the result into
-            // account.
-            //
-            // TODO: remove this call once truncate becomes a full featured member
-            // of [Consensus] or, restructure this implementation to not keep historical
-            // data around if we don't need it.
-            let _ = self.truncate(key, new.seqno).await;
             Ok(Ok(()))
         } else {
             // It's safe to call head in a subsequent transaction rather than doing
@@ -184,6 +158,82 @@ impl Consensus for SqliteConsensus {
             Ok(Err(current))
         }
     }
+
+    async fn scan(
+        &self,
+        key: &str,
+        _deadline: Instant,
+        from: SeqNo,
+    ) -> Result<Vec<VersionedData>, ExternalError> {
+        let conn = self.conn.lock().await;
+        let mut stmt = conn.prepare_cached(
+            "SELECT sequence_number, data FROM consensus
+                 WHERE shard = $shard AND sequence_number >= $from
+                 ORDER BY sequence_number",
+        )?;
+        let rows = stmt.query_map(named_params! {"$shard": key, "$from": from.0}, |row| {
+            let sequence_number = row.get("sequence_number")?;
+            let data: Vec<_> = row.get("data")?;
+            Ok(VersionedData {
+                seqno: SeqNo(sequence_number),
+                data,
+            })
+        })?;
+        let mut results = vec![];
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
+
+    async fn truncate(
+        &self,
+        key: &str,
+        _deadline: Instant,
+        seqno: SeqNo,
+    ) -> Result<usize, ExternalError> {
+        let conn = self.conn.lock().await;
+        // Only delete versions iff `seqno` is <= the current sequence number,
+        // which requires that there is at least one version at this key.
+        let mut stmt = conn.prepare_cached(
+            "DELETE FROM consensus
+             WHERE shard = $shard AND sequence_number < $sequence_number AND
+             EXISTS (
+                 SELECT * FROM consensus WHERE shard = $shard AND sequence_number >= $sequence_number
+             )",
+        )?;
+        let result = stmt.execute(named_params! {"$shard": key, "$sequence_number": seqno.0})?;
+        if result == 0 {
+            // We weren't able to delete any rows. Figure out if that's because
+            // the request was invalid or because there was nothing left to
+            // delete.
+            let mut stmt = conn.prepare_cached(
+                "SELECT MAX(sequence_number) FROM consensus WHERE shard = $shard",
+            )?;
+            let current: Option<u64> =
+                stmt.query_row(named_params! {"$shard": key}, |row| row.get(0))?;
+            if current.map_or(true, |current| current < seqno.0) {
+                return Err(ExternalError::from(anyhow!(
+                    "upper bound too high for truncate: {:?}, current: {:?}",
+                    seqno,
+                    current.map(SeqNo)
+                )));
+            }
+        }
+        Ok(result)
+    }
+
+    async fn list_keys(&self, _deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        let conn = self.conn.lock().await;
+        let mut stmt =
+            conn.prepare_cached("SELECT DISTINCT shard FROM consensus ORDER BY shard")?;
+        let rows = stmt.query_map(params![], |row| row.get("shard"))?;
+        let mut results = vec![];
+        for row in rows {
+            results.push(row?);
+        }
+        Ok(results)
+    }
 }
 
 #[cfg(test)]
@@ -11,11 +11,15 @@
 
 use std::ops::Range;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use async_trait::async_trait;
 
 use crate::error::Error;
-use crate::location::{Atomicity, Blob, BlobRead, LockInfo, Log, SeqNo};
+use crate::location::{
+    Atomicity, Blob, BlobMulti, BlobRead, Consensus, ExternalError, LockInfo, Log, SeqNo,
+    VersionedData,
+};
 
 #[derive(Debug)]
 struct UnreliableCore {
@@ -210,10 +214,131 @@ where
     }
 }
 
+/// An unreliable delegate to [BlobMulti].
+#[derive(Debug)]
+pub struct UnreliableBlobMulti<B> {
+    handle: UnreliableHandle,
+    blob: B,
+}
+
+impl<B: BlobMulti> UnreliableBlobMulti<B> {
+    /// Returns a new [UnreliableBlobMulti] and a handle for controlling it.
+    pub fn new(blob: B) -> (Self, UnreliableHandle) {
+        let h = UnreliableHandle::default();
+        let blob = Self::from_handle(blob, h.clone());
+        (blob, h)
+    }
+
+    /// Returns a new [UnreliableBlobMulti] sharing the given handle.
+    pub fn from_handle(blob: B, handle: UnreliableHandle) -> Self {
+        UnreliableBlobMulti { handle, blob }
+    }
+}
+
+#[async_trait]
+impl<B: BlobMulti + Send + Sync> BlobMulti for UnreliableBlobMulti<B> {
+    async fn get(&self, deadline: Instant, key: &str) -> Result<Option<Vec<u8>>, ExternalError> {
+        self.handle.check_unavailable("blob get")?;
+        self.blob.get(deadline, key).await
+    }
+
+    async fn list_keys(&self, deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        self.handle.check_unavailable("blob list keys")?;
+        self.blob.list_keys(deadline).await
+    }
+
+    async fn set(
+        &self,
+        deadline: Instant,
+        key: &str,
+        value: Vec<u8>,
+        atomic: Atomicity,
+    ) -> Result<(), ExternalError> {
+        self.handle.check_unavailable("blob set")?;
+        self.blob.set(deadline, key, value, atomic).await
+    }
+
+    async fn delete(&self, deadline: Instant, key: &str) -> Result<(), ExternalError> {
+        self.handle.check_unavailable("blob delete")?;
+        self.blob.delete(deadline, key).await
+    }
+}
+
+/// An unreliable delegate to [Consensus].
+#[derive(Debug)]
+pub struct UnreliableConsensus<C> {
+    handle: UnreliableHandle,
+    consensus: C,
+}
+
+impl<C: Consensus> UnreliableConsensus<C> {
+    /// Returns a new [UnreliableConsensus] and a handle for controlling it.
+    pub fn new(consensus: C) -> (Self, UnreliableHandle) {
+        let h = UnreliableHandle::default();
+        let consensus = Self::from_handle(consensus, h.clone());
+        (consensus, h)
+    }
+
+    /// Returns a new [UnreliableConsensus] sharing the given handle.
+    pub fn from_handle(consensus: C, handle: UnreliableHandle) -> Self {
+        UnreliableConsensus { handle, consensus }
+    }
+}
+
+#[async_trait]
+impl<C: Consensus + Send + Sync> Consensus for UnreliableConsensus<C> {
+    async fn head(
+        &self,
+        key: &str,
+        deadline: Instant,
+    ) -> Result<Option<VersionedData>, ExternalError> {
+        self.handle.check_unavailable("consensus head")?;
+        self.consensus.head(key, deadline).await
+    }
+
+    async fn compare_and_set(
+        &self,
+        key: &str,
+        deadline: Instant,
+        expected: Option<SeqNo>,
+        new: VersionedData,
+    ) -> Result<Result<(), Option<VersionedData>>, ExternalError> {
+        self.handle.check_unavailable("consensus compare_and_set")?;
+        self.consensus
+            .compare_and_set(key, deadline, expected, new)
+            .await
+    }
+
+    async fn scan(
+        &self,
+        key: &str,
+        deadline: Instant,
+        from: SeqNo,
+    ) -> Result<Vec<VersionedData>, ExternalError> {
+        self.handle.check_unavailable("consensus scan")?;
+        self.consensus.scan(key, deadline, from).await
+    }
+
+    async fn truncate(
+        &self,
+        key: &str,
+        deadline: Instant,
+        seqno: SeqNo,
+    ) -> Result<usize, ExternalError> {
+        self.handle.check_unavailable("consensus truncate")?;
+        self.consensus.truncate(key, deadline, seqno).await
+    }
+
+    async fn list_keys(&self, deadline: Instant) -> Result<Vec<String>, ExternalError> {
+        self.handle.check_unavailable("consensus list_keys")?;
+        self.consensus.list_keys(deadline).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::location::Atomicity::RequireAtomic;
-    use crate::mem::{MemBlob, MemLog};
+    use crate::mem::{MemBlob, MemBlobMulti, MemBlobMultiConfig, MemConsensus, MemLog};
 
     use super::*;
 
@@ -257,4 +382,67 @@ mod tests {
         assert!(blob.set("a", b"3".to_vec(), RequireAtomic).await.is_ok());
         assert!(blob.get("a").await.is_ok());
     }
+
+    #[tokio::test]
+    async fn blob_multi() {
+        let (blob, mut handle) =
+            UnreliableBlobMulti::new(MemBlobMulti::open(MemBlobMultiConfig::default()));
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+
+        // Initially starts reliable.
+        assert!(blob
+            .set(deadline, "a", b"1".to_vec(), RequireAtomic)
+            .await
+            .is_ok());
+        assert!(blob.get(deadline, "a").await.is_ok());
+
+        // Setting it to unavailable causes all operations to fail.
+        handle.make_unavailable();
+        assert!(blob
+            .set(deadline, "a", b"2".to_vec(), RequireAtomic)
+            .await
+            .is_err());
+        assert!(blob.get(deadline, "a").await.is_err());
+
+        // Can be set back to working.
+        handle.make_available();
+        assert_eq!(blob.get(deadline, "a").await, Ok(Some(b"1".to_vec())));
+    }
+
+    #[tokio::test]
+    async fn consensus() {
+        let (consensus, mut handle) = UnreliableConsensus::new(MemConsensus::default());
+        let deadline = Instant::now() + std::time::Duration::from_secs(60);
+        let data = |seqno| VersionedData {
+            seqno: SeqNo(seqno),
+            data: vec![],
+        };
+
+        // Initially starts reliable.
+        assert_eq!(
+            consensus
+                .compare_and_set("a", deadline, None, data(1))
+                .await,
+            Ok(Ok(()))
+        );
+        assert!(consensus.head("a", deadline).await.is_ok());
+
+        // Setting it to unavailable causes all operations to fail.
+        handle.make_unavailable();
+        assert!(consensus
+            .compare_and_set("a", deadline, Some(SeqNo(1)), data(2))
+            .await
+            .is_err());
+        assert!(consensus.head("a", deadline).await.is_err());
+        assert!(consensus
+            .scan("a", deadline, SeqNo::default())
+            .await
+            .is_err());
+        assert!(consensus.truncate("a", deadline, SeqNo(1)).await.is_err());
+        assert!(consensus.list_keys(deadline).await.is_err());
+
+        // Can be set back to working.
+        handle.make_available();
+        assert_eq!(consensus.head("a", deadline).await, Ok(Some(data(1))));
+    }
 }
@@ -0,0 +1,306 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Offline inspection of persist shards.
+//!
+//! Nothing here registers a reader or writer with the shards it inspects, so
+//! it is safe to run against a location that is in active use.
+
+use std::collections::BTreeSet;
+use std::fmt::Debug;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail};
+use clap::{Parser, Subcommand};
+use timely::progress::Antichain;
+use tracing::error;
+use tracing_subscriber::filter::EnvFilter;
+
+use mz_persist::location::{BlobMulti, Consensus};
+use mz_persist_client::inspect::{self, ShardCodecs, StateSummary};
+use mz_persist_client::{Location, ShardId};
+use mz_persist_types::Codec;
+use mz_repr::Row;
+use mz_secrets::SecretsReader;
+use mz_secrets_filesystem::FilesystemSecretsReader;
+
+/// Inspect the persist shards stored at a location.
+#[derive(Parser)]
+struct Args {
+    /// Uri of the blob store, e.g. `file:///path/to/blob` or
+    /// `s3://bucket/prefix`.
+    #[clap(long, value_name = "URI")]
+    blob_uri: String,
+
+    /// Uri of the consensus system, e.g. `sqlite:///path/to/consensus.db`.
+    #[clap(long, value_name = "URI")]
+    consensus_uri: String,
+
+    /// The directory that holds the secrets of the materialized process that
+    /// owns the location, e.g. `mzdata/secrets`. Required to inspect an
+    /// encrypted location.
+    #[clap(long, value_name = "PATH")]
+    secrets_path: Option<PathBuf>,
+
+    /// How long to wait for any single operation against the location.
+    #[clap(long, parse(try_from_str = mz_repr::util::parse_duration), value_name = "DURATION", default_value = "60s")]
+    timeout: Duration,
+
+    /// Which log messages to emit.
+    ///
+    /// See materialized's `--log-filter` option for details.
+    #[clap(long, value_name = "FILTER", default_value = "off")]
+    log_filter: EnvFilter,
+
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List every shard at the location, along with its codecs.
+    ListShards,
+    /// Print the latest state of a shard as JSON.
+    DumpState {
+        /// The shard to dump.
+        #[clap(long)]
+        shard: ShardId,
+    },
+    /// Print the contents of a shard as of a timestamp, one update per line.
+    Snapshot {
+        /// The shard to read.
+        #[clap(long)]
+        shard: ShardId,
+        /// The timestamp to read at. Must be at or beyond the since of the
+        /// shard and less than its upper.
+        #[clap(long)]
+        as_of: u64,
+    },
+    /// Verify that every blob referenced by the state of a shard exists.
+    VerifyBlobs {
+        /// The shard to verify. Every shard at the location if unset.
+        #[clap(long)]
+        shard: Option<ShardId>,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run().await {
+        error!("{:#}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run() -> anyhow::Result<()> {
+    let args: Args = mz_ore::cli::parse_args();
+
+    tracing_subscriber::fmt()
+        .with_env_filter(args.log_filter)
+        .with_writer(io::stderr)
+        .init();
+
+    let location = Location {
+        blob_uri: args.blob_uri,
+        consensus_uri: args.consensus_uri,
+    };
+    let secrets = args.secrets_path.map(FilesystemSecretsReader::new);
+    let (blob, consensus) = location
+        .open(
+            args.timeout,
+            secrets.as_ref().map(|x| x as &dyn SecretsReader),
+        )
+        .await?;
+    let location = OpenLocation {
+        blob,
+        consensus,
+        timeout: args.timeout,
+    };
+
+    match args.command {
+        Command::ListShards => list_shards(&location).await,
+        Command::DumpState { shard } => dump_state(&location, shard).await,
+        Command::Snapshot { shard, as_of } => snapshot(&location, shard, as_of).await,
+        Command::VerifyBlobs { shard } => verify_blobs(&location, shard).await,
+    }
+}
+
+/// An open [Location].
+struct OpenLocation {
+    blob: Arc<dyn BlobMulti + Send + Sync>,
+    consensus: Arc<dyn Consensus + Send + Sync>,
+    timeout: Duration,
+}
+
+impl OpenLocation {
+    async fn codecs(&self, shard: ShardId) -> anyhow::Result<ShardCodecs> {
+        inspect::fetch_codecs(self.consensus.as_ref(), self.timeout, shard)
+            .await?
+            .ok_or_else(|| anyhow!("shard {} has no state", shard))
+    }
+}
+
+/// A key or value type that persist shards are commonly created with.
+trait Decoded: Codec + Debug + Ord {
+    /// A human readable representation of the decoded data.
+    fn display(&self) -> String;
+}
+
+impl Decoded for Row {
+    fn display(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Decoded for String {
+    fn display(&self) -> String {
+        format!("{:?}", self)
+    }
+}
+
+impl Decoded for () {
+    fn display(&self) -> String {
+        "()".into()
+    }
+}
+
+impl Decoded for Vec<u8> {
+    fn display(&self) -> String {
+        format!("0x{}", hex::encode(self))
+    }
+}
+
+/// Calls `$f::<K, V>($args)` with the [Decoded] key and value types that
+/// match `$codecs`.
+///
+/// Only u64 timestamps and i64 diffs are supported.
+macro_rules! with_codecs {
+    (@val $codecs:expr, $K:ty, $f:ident($($arg:expr),*)) => {
+        match $codecs.val.as_str() {
+            "protobuf[Row]" => $f::<$K, Row>($($arg),*).await,
+            "String" => $f::<$K, String>($($arg),*).await,
+            "()" => $f::<$K, ()>($($arg),*).await,
+            "Vec<u8>" => $f::<$K, Vec<u8>>($($arg),*).await,
+            x => bail!("unsupported val codec: {}", x),
+        }
+    };
+    ($codecs:expr, $f:ident($($arg:expr),*)) => {{
+        let codecs: &ShardCodecs = $codecs;
+        if codecs.ts != "u64" || codecs.diff != "i64" {
+            bail!(
+                "unsupported timestamp and diff codecs: {} {}",
+                codecs.ts,
+                codecs.diff
+            );
+        }
+        match codecs.key.as_str() {
+            "protobuf[Row]" => with_codecs!(@val codecs, Row, $f($($arg),*)),
+            "String" => with_codecs!(@val codecs, String, $f($($arg),*)),
+            "()" => with_codecs!(@val codecs, (), $f($($arg),*)),
+            "Vec<u8>" => with_codecs!(@val codecs, Vec<u8>, $f($($arg),*)),
+            x => bail!("unsupported key codec: {}", x),
+        }
+    }};
+}
+
+fn display<X: Decoded>(x: &Result<X, String>) -> String {
+    match x {
+        Ok(x) => x.display(),
+        Err(err) => format!("<undecodable: {}>", err),
+    }
+}
+
+async fn fetch_state<K: Decoded, V: Decoded>(
+    location: &OpenLocation,
+    shard: ShardId,
+) -> anyhow::Result<StateSummary<u64>> {
+    inspect::fetch_state::<K, V, u64, i64>(
+        Arc::clone(&location.blob),
+        Arc::clone(&location.consensus),
+        location.timeout,
+        shard,
+    )
+    .await?
+    .ok_or_else(|| anyhow!("shard {} has no state", shard))
+}
+
+async fn fetch_snapshot<K: Decoded, V: Decoded>(
+    location: &OpenLocation,
+    shard: ShardId,
+    as_of: u64,
+) -> anyhow::Result<()> {
+    let updates = inspect::fetch_snapshot::<K, V, u64, i64>(
+        Arc::clone(&location.blob),
+        Arc::clone(&location.consensus),
+        location.timeout,
+        shard,
+        Antichain::from_elem(as_of),
+    )
+    .await??;
+    for ((k, v), t, d) in updates {
+        println!("{}\t{}\t{}\t{}", display(&k), display(&v), t, d);
+    }
+    Ok(())
+}
+
+async fn list_shards(location: &OpenLocation) -> anyhow::Result<()> {
+    for shard in inspect::list_shards(location.consensus.as_ref(), location.timeout).await? {
+        let codecs = location.codecs(shard).await?;
+        println!(
+            "{}\tkey={} val={} ts={} diff={}",
+            shard, codecs.key, codecs.val, codecs.ts, codecs.diff
+        );
+    }
+    Ok(())
+}
+
+async fn dump_state(location: &OpenLocation, shard: ShardId) -> anyhow::Result<()> {
+    let codecs = location.codecs(shard).await?;
+    let state = with_codecs!(&codecs, fetch_state(location, shard))?;
+    println!("{}", serde_json::to_string_pretty(&state)?);
+    Ok(())
+}
+
+async fn snapshot(location: &OpenLocation, shard: ShardId, as_of: u64) -> anyhow::Result<()> {
+    let codecs = location.codecs(shard).await?;
+    with_codecs!(&codecs, fetch_snapshot(location, shard, as_of))
+}
+
+async fn verify_blobs(location: &OpenLocation, shard: Option<ShardId>) -> anyhow::Result<()> {
+    let shards = match shard {
+        Some(shard) => vec![shard],
+        None => inspect::list_shards(location.consensus.as_ref(), location.timeout).await?,
+    };
+    let deadline = Instant::now() + location.timeout;
+    let blob_keys = location
+        .blob
+        .list_keys(deadline)
+        .await?
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    let mut missing = 0;
+    for shard in shards {
+        let codecs = location.codecs(shard).await?;
+        let state = with_codecs!(&codecs, fetch_state(location, shard))?;
+        for key in state.blob_keys() {
+            if !blob_keys.contains(key) {
+                println!("{}\tmissing blob {}", shard, key);
+                missing += 1;
+            }
+        }
+    }
+    if missing > 0 {
+        bail!("{} referenced blobs are missing", missing);
+    }
+    Ok(())
+}
@@ -282,6 +282,12 @@ impl PgConn {
                             parameters: body.parameters().collect().unwrap(),
                         })?,
                     ),
+                    Message::NotificationResponse(body) => (
+                        "NotificationResponse",
+                        serde_json::to_string(&NotificationResponse {
+                            channel: body.channel().unwrap().to_string(),
+                        })?,
+                    ),
                     Message::ParameterStatus(_) => continue,
                     Message::NoData => ("NoData", "".to_string()),
                     Message::EmptyQueryResponse => ("EmptyQueryResponse", "".to_string()),
@@ -420,6 +426,13 @@ pub struct CommandComplete {
     pub tag: String,
 }
 
+/// The process ID and payload of notifications are not reproducible, so
+/// only their channel is recorded.
+#[derive(Serialize)]
+pub struct NotificationResponse {
+    pub channel: String,
+}
+
 #[derive(Serialize)]
 pub struct ErrorResponse {
     pub fields: Vec<ErrorField>,
@@ -251,6 +251,11 @@ pub enum BackendMessage {
     },
     CopyData(Vec<u8>),
     CopyDone,
+    NotificationResponse {
+        conn_id: u32,
+        channel: String,
+        payload: String,
+    },
 }
 
 impl From<ErrorResponse> for BackendMessage {
@@ -366,6 +371,7 @@ impl ErrorResponse {
             CoordError::InvalidAlterOnDisabledIndex(_) => SqlState::INTERNAL_ERROR,
             CoordError::Catalog(_) => SqlState::INTERNAL_ERROR,
             CoordError::ChangedPlan => SqlState::FEATURE_NOT_SUPPORTED,
+            CoordError::CheckViolation { .. } => SqlState::CHECK_VIOLATION,
             CoordError::ConstrainedParameter { .. } => SqlState::INVALID_PARAMETER_VALUE,
             CoordError::AutomaticTimestampFailure { .. } => SqlState::INTERNAL_ERROR,
             CoordError::DuplicateCursor(_) => SqlState::DUPLICATE_CURSOR,
@@ -387,6 +393,7 @@ impl ErrorResponse {
             CoordError::InvalidParameterValue { .. } => SqlState::INVALID_PARAMETER_VALUE,
             CoordError::InvalidTableMutationSelection => SqlState::INVALID_TRANSACTION_STATE,
             CoordError::ConstraintViolation(NotNullViolation(_)) => SqlState::NOT_NULL_VIOLATION,
+            CoordError::OnConflictAffectsRowTwice => SqlState::CARDINALITY_VIOLATION,
             CoordError::OperationProhibitsTransaction(_) => SqlState::ACTIVE_SQL_TRANSACTION,
             CoordError::OperationRequiresTransaction(_) => SqlState::NO_ACTIVE_SQL_TRANSACTION,
             CoordError::Persistence(_) => SqlState::INTERNAL_ERROR,
@@ -399,7 +406,11 @@ impl ErrorResponse {
             CoordError::SafeModeViolation(_) => SqlState::INTERNAL_ERROR,
             CoordError::SqlCatalog(_) => SqlState::INTERNAL_ERROR,
             CoordError::TailOnlyTransaction => SqlState::INVALID_TRANSACTION_STATE,
+            CoordError::TailResumptionCompacted { .. } => {
+                SqlState::OBJECT_NOT_IN_PREREQUISITE_STATE
+            }
             CoordError::Transform(_) => SqlState::INTERNAL_ERROR,
+            CoordError::UniqueViolation { .. } => SqlState::UNIQUE_VIOLATION,
             CoordError::UnknownCursor(_) => SqlState::INVALID_CURSOR_NAME,
             CoordError::UnknownParameter(_) => SqlState::UNDEFINED_OBJECT,
             CoordError::UnknownPreparedStatement(_) => SqlState::UNDEFINED_PSTATEMENT,
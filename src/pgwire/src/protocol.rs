@@ -280,7 +280,30 @@ where
     }
 
     async fn advance_ready(&mut self) -> Result<State, io::Error> {
-        let message = self.conn.recv().await?;
+        // Like PostgreSQL, only deliver asynchronous notifications between
+        // transactions, so that a client never observes a notification in the
+        // middle of a transaction block.
+        let deliver_notifications = matches!(
+            self.coord_client.session().transaction(),
+            TransactionStatus::Default
+        );
+        let message = if deliver_notifications {
+            select! {
+                message = self.conn.recv() => message?,
+                notification = self.coord_client.recv_notification() => {
+                    let conn_id = self.coord_client.session().conn_id();
+                    self.send(BackendMessage::NotificationResponse {
+                        conn_id,
+                        channel: notification.channel,
+                        payload: notification.payload,
+                    })
+                    .await?;
+                    return self.flush().await;
+                }
+            }
+        } else {
+            self.conn.recv().await?
+        };
         let timer = Instant::now();
         let name = match &message {
             Some(message) => message.name(),
@@ -1073,6 +1096,9 @@ where
             ExecuteResponse::CreatedSecret { existed } => {
                 created!(existed, SqlState::DUPLICATE_OBJECT, "secret")
             }
+            ExecuteResponse::CreatedNotification { existed } => {
+                created!(existed, SqlState::DUPLICATE_OBJECT, "notification")
+            }
             ExecuteResponse::CreatedSource { existed } => {
                 created!(existed, SqlState::DUPLICATE_OBJECT, "source")
             }
@@ -1084,6 +1110,7 @@ where
                 created!(existed, SqlState::DUPLICATE_OBJECT, "view")
             }
             ExecuteResponse::CreatedType => command_complete!("CREATE TYPE"),
+            ExecuteResponse::CreatedFunction => command_complete!("CREATE FUNCTION"),
             ExecuteResponse::DeclaredCursor => {
                 self.complete_portal(&portal_name);
                 command_complete!("DECLARE CURSOR")
@@ -1102,6 +1129,8 @@ where
             ExecuteResponse::DroppedView => command_complete!("DROP VIEW"),
             ExecuteResponse::DroppedType => command_complete!("DROP TYPE"),
             ExecuteResponse::DroppedSecret => command_complete!("DROP SECRET"),
+            ExecuteResponse::DroppedNotification => command_complete!("DROP NOTIFICATION"),
+            ExecuteResponse::DroppedFunction => command_complete!("DROP FUNCTION"),
             ExecuteResponse::EmptyQuery => {
                 self.send(BackendMessage::EmptyQueryResponse).await?;
                 Ok(State::Ready)
@@ -1243,6 +1272,8 @@ where
             ExecuteResponse::AlteredObject(o) => command_complete!("ALTER {}", o),
             ExecuteResponse::AlteredIndexLogicalCompaction => command_complete!("ALTER INDEX"),
             ExecuteResponse::Prepare => command_complete!("PREPARE"),
+            ExecuteResponse::Listen => command_complete!("LISTEN"),
+            ExecuteResponse::Unlisten => command_complete!("UNLISTEN"),
             ExecuteResponse::Deallocate { all } => {
                 command_complete!("DEALLOCATE{}", if all { " ALL" } else { "" })
             }
@@ -7,7 +7,8 @@
 // the Business Source License, use of this software will be governed
 // by the Apache License, Version 2.0.
 use anyhow::Error;
-use mz_secrets::{SecretOp, SecretsController};
+use mz_expr::GlobalId;
+use mz_secrets::{SecretOp, SecretsController, SecretsReader};
 use std::fs;
 use std::fs::File;
 use std::io::Write;
@@ -45,3 +46,21 @@ impl SecretsController for FilesystemSecretsController {
         return Ok(());
     }
 }
+
+pub struct FilesystemSecretsReader {
+    secrets_storage_path: PathBuf,
+}
+
+impl FilesystemSecretsReader {
+    pub fn new(secrets_storage_path: PathBuf) -> Self {
+        Self {
+            secrets_storage_path,
+        }
+    }
+}
+
+impl SecretsReader for FilesystemSecretsReader {
+    fn read(&self, id: GlobalId) -> Result<Vec<u8>, Error> {
+        Ok(fs::read(self.secrets_storage_path.join(format!("{}", id)))?)
+    }
+}
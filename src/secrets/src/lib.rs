@@ -22,6 +22,12 @@ pub trait SecretsController: Send {
     fn apply(&mut self, ops: Vec<SecretOp>) -> Result<(), anyhow::Error>;
 }
 
+/// Reads secrets stored by a [`SecretsController`].
+pub trait SecretsReader: Send + Sync {
+    /// Returns the binary contents of the secret with the given ID.
+    fn read(&self, id: GlobalId) -> Result<Vec<u8>, anyhow::Error>;
+}
+
 /// An operation on a [`SecretsController`].
 pub enum SecretOp {
     /// Create or update the contents of a secret.
@@ -276,6 +276,14 @@ impl<T: AstInfo> AstDisplay for CreateSourceFormat<T> {
 }
 impl_display_t!(CreateSourceFormat);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonSchema {
+    /// The schema is to be inferred from sample messages.
+    Infer,
+    /// The JSON-serialized schema.
+    Inline(String),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Format<T: AstInfo> {
     Bytes,
@@ -286,8 +294,29 @@ pub enum Format<T: AstInfo> {
         columns: CsvColumns,
         delimiter: char,
     },
-    Json,
+    /// `JSON [WITH (INFER SCHEMA) | USING SCHEMA '<schema>']`
+    ///
+    /// Without a schema, each message is decoded into a single `jsonb`
+    /// column. `WITH (INFER SCHEMA)` is replaced with a schema inferred from
+    /// sample messages during purification.
+    Json {
+        schema: Option<JsonSchema>,
+    },
     Text,
+    /// `PARQUET [USING SCHEMA '<schema>']`
+    ///
+    /// If the schema is not specified, it is inferred from the footer of a
+    /// sample file during purification.
+    Parquet {
+        schema: Option<String>,
+    },
+    /// `ORC [USING SCHEMA '<schema>']`
+    ///
+    /// If the schema is not specified, it is inferred from the footer of a
+    /// sample file during purification.
+    Orc {
+        schema: Option<String>,
+    },
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -388,6 +417,38 @@ impl AstDisplay for Envelope {
 }
 impl_display!(Envelope);
 
+/// What a source does with a message that fails to decode.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum DecodeErrorAction<T: AstInfo> {
+    /// Report the error, making the source unreadable until it is retracted.
+    Fail,
+    /// Drop the message.
+    Skip,
+    /// Drop the message after writing it to a Kafka topic.
+    RedirectToTopic(String),
+    /// Drop the message after writing it to a table.
+    RedirectToTable(T::ObjectName),
+}
+
+impl<T: AstInfo> AstDisplay for DecodeErrorAction<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        match self {
+            Self::Fail => f.write_str("FAIL"),
+            Self::Skip => f.write_str("SKIP"),
+            Self::RedirectToTopic(topic) => {
+                f.write_str("REDIRECT TO TOPIC '");
+                f.write_node(&display::escape_single_quote_string(topic));
+                f.write_str("'");
+            }
+            Self::RedirectToTable(name) => {
+                f.write_str("REDIRECT TO TABLE ");
+                f.write_node(name);
+            }
+        }
+    }
+}
+impl_display_t!(DecodeErrorAction);
+
 impl<T: AstInfo> AstDisplay for Format<T> {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         match self {
@@ -415,8 +476,35 @@ impl<T: AstInfo> AstDisplay for Format<T> {
                     f.write_str("'");
                 }
             }
-            Self::Json => f.write_str("JSON"),
+            Self::Json { schema } => {
+                f.write_str("JSON");
+                match schema {
+                    None => (),
+                    Some(JsonSchema::Infer) => f.write_str(" WITH (INFER SCHEMA)"),
+                    Some(JsonSchema::Inline(schema)) => {
+                        f.write_str(" USING SCHEMA '");
+                        f.write_node(&display::escape_single_quote_string(schema));
+                        f.write_str("'");
+                    }
+                }
+            }
             Self::Text => f.write_str("TEXT"),
+            Self::Parquet { schema } => {
+                f.write_str("PARQUET");
+                if let Some(schema) = schema {
+                    f.write_str(" USING SCHEMA '");
+                    f.write_node(&display::escape_single_quote_string(schema));
+                    f.write_str("'");
+                }
+            }
+            Self::Orc { schema } => {
+                f.write_str("ORC");
+                if let Some(schema) = schema {
+                    f.write_str(" USING SCHEMA '");
+                    f.write_node(&display::escape_single_quote_string(schema));
+                    f.write_str("'");
+                }
+            }
         }
     }
 }
@@ -22,9 +22,10 @@ use std::fmt;
 
 use crate::ast::display::{self, AstDisplay, AstFormatter};
 use crate::ast::{
-    AstInfo, ColumnDef, CreateSinkConnector, CreateSourceConnector, CreateSourceFormat, Envelope,
-    Expr, Format, Ident, KeyConstraint, Query, SourceIncludeMetadata, TableAlias, TableConstraint,
-    TableWithJoins, UnresolvedDatabaseName, UnresolvedObjectName, UnresolvedSchemaName, Value,
+    AstInfo, ColumnDef, CreateSinkConnector, CreateSourceConnector, CreateSourceFormat,
+    DecodeErrorAction, Envelope, Expr, Format, Ident, KeyConstraint, Query, SourceIncludeMetadata,
+    TableAlias, TableConstraint, TableWithJoins, UnresolvedDatabaseName, UnresolvedObjectName,
+    UnresolvedSchemaName, Value,
 };
 
 /// A top-level statement (SELECT, INSERT, CREATE, etc.)
@@ -48,8 +49,11 @@ pub enum Statement<T: AstInfo> {
     CreateRole(CreateRoleStatement),
     CreateCluster(CreateClusterStatement),
     CreateSecret(CreateSecretStatement<T>),
+    CreateNotification(CreateNotificationStatement<T>),
+    CreateFunction(CreateFunctionStatement<T>),
     AlterObjectRename(AlterObjectRenameStatement<T>),
     AlterIndex(AlterIndexStatement<T>),
+    AlterSource(AlterSourceStatement<T>),
     AlterSecret(AlterSecretStatement<T>),
     AlterCluster(AlterClusterStatement),
     Discard(DiscardStatement),
@@ -83,6 +87,8 @@ pub enum Statement<T: AstInfo> {
     Execute(ExecuteStatement<T>),
     Deallocate(DeallocateStatement),
     Raise(RaiseStatement),
+    Listen(ListenStatement),
+    Unlisten(UnlistenStatement),
 }
 
 impl<T: AstInfo> AstDisplay for Statement<T> {
@@ -103,10 +109,13 @@ impl<T: AstInfo> AstDisplay for Statement<T> {
             Statement::CreateIndex(stmt) => f.write_node(stmt),
             Statement::CreateRole(stmt) => f.write_node(stmt),
             Statement::CreateSecret(stmt) => f.write_node(stmt),
+            Statement::CreateNotification(stmt) => f.write_node(stmt),
+            Statement::CreateFunction(stmt) => f.write_node(stmt),
             Statement::CreateType(stmt) => f.write_node(stmt),
             Statement::CreateCluster(stmt) => f.write_node(stmt),
             Statement::AlterObjectRename(stmt) => f.write_node(stmt),
             Statement::AlterIndex(stmt) => f.write_node(stmt),
+            Statement::AlterSource(stmt) => f.write_node(stmt),
             Statement::AlterSecret(stmt) => f.write_node(stmt),
             Statement::AlterCluster(stmt) => f.write_node(stmt),
             Statement::Discard(stmt) => f.write_node(stmt),
@@ -140,6 +149,8 @@ impl<T: AstInfo> AstDisplay for Statement<T> {
             Statement::Execute(stmt) => f.write_node(stmt),
             Statement::Deallocate(stmt) => f.write_node(stmt),
             Statement::Raise(stmt) => f.write_node(stmt),
+            Statement::Listen(stmt) => f.write_node(stmt),
+            Statement::Unlisten(stmt) => f.write_node(stmt),
         }
     }
 }
@@ -172,6 +183,8 @@ pub struct InsertStatement<T: AstInfo> {
     pub columns: Vec<Ident>,
     /// A SQL query that specifies what to insert.
     pub source: InsertSource<T>,
+    /// ON CONFLICT
+    pub on_conflict: Option<OnConflict<T>>,
 }
 
 impl<T: AstInfo> AstDisplay for InsertStatement<T> {
@@ -185,10 +198,68 @@ impl<T: AstInfo> AstDisplay for InsertStatement<T> {
         }
         f.write_str(" ");
         f.write_node(&self.source);
+        if let Some(on_conflict) = &self.on_conflict {
+            f.write_str(" ");
+            f.write_node(on_conflict);
+        }
     }
 }
 impl_display_t!(InsertStatement);
 
+/// `ON CONFLICT [(<columns>)] DO ...`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OnConflict<T: AstInfo> {
+    /// The columns of the unique constraint to detect conflicts on. Empty if
+    /// conflicts on any unique constraint should be detected.
+    pub columns: Vec<Ident>,
+    /// What to do with conflicting rows.
+    pub action: OnConflictAction<T>,
+}
+
+impl<T: AstInfo> AstDisplay for OnConflict<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("ON CONFLICT ");
+        if !self.columns.is_empty() {
+            f.write_str("(");
+            f.write_node(&display::comma_separated(&self.columns));
+            f.write_str(") ");
+        }
+        f.write_node(&self.action);
+    }
+}
+impl_display_t!(OnConflict);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum OnConflictAction<T: AstInfo> {
+    /// `DO NOTHING`
+    DoNothing,
+    /// `DO UPDATE SET <assignments> [WHERE <selection>]`
+    DoUpdate {
+        assignments: Vec<Assignment<T>>,
+        selection: Option<Expr<T>>,
+    },
+}
+
+impl<T: AstInfo> AstDisplay for OnConflictAction<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        match self {
+            OnConflictAction::DoNothing => f.write_str("DO NOTHING"),
+            OnConflictAction::DoUpdate {
+                assignments,
+                selection,
+            } => {
+                f.write_str("DO UPDATE SET ");
+                f.write_node(&display::comma_separated(assignments));
+                if let Some(selection) = selection {
+                    f.write_str(" WHERE ");
+                    f.write_node(selection);
+                }
+            }
+        }
+    }
+}
+impl_display_t!(OnConflictAction);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum CopyRelation<T: AstInfo> {
     Table {
@@ -386,6 +457,7 @@ pub struct CreateSourceStatement<T: AstInfo> {
     pub include_metadata: Vec<SourceIncludeMetadata>,
     pub format: CreateSourceFormat<T>,
     pub envelope: Envelope,
+    pub on_decode_error: Option<DecodeErrorAction<T>>,
     pub if_not_exists: bool,
     pub materialized: bool,
     pub key_constraint: Option<KeyConstraint>,
@@ -436,6 +508,11 @@ impl<T: AstInfo> AstDisplay for CreateSourceStatement<T> {
                 f.write_node(&self.envelope);
             }
         }
+
+        if let Some(on_decode_error) = &self.on_decode_error {
+            f.write_str(" ON DECODE ERROR = ");
+            f.write_node(on_decode_error);
+        }
     }
 }
 impl_display_t!(CreateSourceStatement);
@@ -813,6 +890,136 @@ impl<T: AstInfo> AstDisplay for CreateSecretStatement<T> {
 }
 impl_display_t!(CreateSecretStatement);
 
+/// A `CREATE NOTIFICATION` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreateNotificationStatement<T: AstInfo> {
+    pub name: UnresolvedObjectName,
+    pub if_not_exists: bool,
+    /// `ON` view name
+    pub on_name: T::ObjectName,
+    /// Only changes to rows that satisfy this predicate raise a notification.
+    pub predicate: Option<Expr<T>>,
+}
+
+impl<T: AstInfo> AstDisplay for CreateNotificationStatement<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("CREATE NOTIFICATION ");
+        if self.if_not_exists {
+            f.write_str("IF NOT EXISTS ");
+        }
+        f.write_node(&self.name);
+        f.write_str(" ON ");
+        f.write_node(&self.on_name);
+        if let Some(predicate) = &self.predicate {
+            f.write_str(" WHEN ");
+            f.write_node(predicate);
+        }
+    }
+}
+impl_display_t!(CreateNotificationStatement);
+
+/// A `CREATE FUNCTION` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CreateFunctionStatement<T: AstInfo> {
+    pub name: UnresolvedObjectName,
+    pub params: Vec<FunctionParameter<T>>,
+    pub returns: FunctionReturns<T>,
+    /// The body of the function, parsed from the string following `AS`.
+    pub body: FunctionBody<T>,
+}
+
+impl<T: AstInfo> AstDisplay for CreateFunctionStatement<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("CREATE FUNCTION ");
+        f.write_node(&self.name);
+        f.write_str("(");
+        f.write_node(&display::comma_separated(&self.params));
+        f.write_str(") RETURNS ");
+        f.write_node(&self.returns);
+        f.write_str(" LANGUAGE SQL AS ");
+        let body = match &self.body {
+            FunctionBody::Expr(expr) if f.stable() => expr.to_ast_string_stable(),
+            FunctionBody::Expr(expr) => expr.to_ast_string(),
+            FunctionBody::Query(query) if f.stable() => query.to_ast_string_stable(),
+            FunctionBody::Query(query) => query.to_ast_string(),
+        };
+        f.write_node(&Value::String(body));
+    }
+}
+impl_display_t!(CreateFunctionStatement);
+
+/// A parameter of a function created by `CREATE FUNCTION`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionParameter<T: AstInfo> {
+    pub name: Option<Ident>,
+    pub data_type: T::DataType,
+}
+
+impl<T: AstInfo> AstDisplay for FunctionParameter<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        if let Some(name) = &self.name {
+            f.write_node(name);
+            f.write_str(" ");
+        }
+        f.write_node(&self.data_type);
+    }
+}
+impl_display_t!(FunctionParameter);
+
+/// The `RETURNS` clause of a `CREATE FUNCTION` statement.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FunctionReturns<T: AstInfo> {
+    /// `RETURNS type`
+    Scalar(T::DataType),
+    /// `RETURNS SETOF type`
+    SetOf(T::DataType),
+    /// `RETURNS TABLE (name type, ...)`
+    Table(Vec<FunctionReturnColumn<T>>),
+}
+
+impl<T: AstInfo> AstDisplay for FunctionReturns<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        match self {
+            FunctionReturns::Scalar(data_type) => f.write_node(data_type),
+            FunctionReturns::SetOf(data_type) => {
+                f.write_str("SETOF ");
+                f.write_node(data_type);
+            }
+            FunctionReturns::Table(columns) => {
+                f.write_str("TABLE (");
+                f.write_node(&display::comma_separated(columns));
+                f.write_str(")");
+            }
+        }
+    }
+}
+impl_display_t!(FunctionReturns);
+
+/// A column of a function that `RETURNS TABLE`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FunctionReturnColumn<T: AstInfo> {
+    pub name: Ident,
+    pub data_type: T::DataType,
+}
+
+impl<T: AstInfo> AstDisplay for FunctionReturnColumn<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_node(&self.name);
+        f.write_str(" ");
+        f.write_node(&self.data_type);
+    }
+}
+impl_display_t!(FunctionReturnColumn);
+
+/// The body of a function created by `CREATE FUNCTION`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum FunctionBody<T: AstInfo> {
+    /// The expression computed by a function that returns a single value.
+    Expr(Expr<T>),
+    /// The query computed by a function that returns a set of rows.
+    Query(Query<T>),
+}
+
 /// `CREATE TYPE ..`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CreateTypeStatement<T: AstInfo> {
@@ -1003,6 +1210,43 @@ impl<T: AstInfo> AstDisplay for AlterIndexStatement<T> {
 
 impl_display_t!(AlterIndexStatement);
 
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AlterSourceAction {
+    /// Whether the new columns are propagated to the views that select all
+    /// columns of the source.
+    RefreshSchema { cascade: bool },
+}
+
+/// `ALTER SOURCE ... REFRESH SCHEMA [CASCADE]`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AlterSourceStatement<T: AstInfo> {
+    pub source_name: T::ObjectName,
+    pub if_exists: bool,
+    pub action: AlterSourceAction,
+}
+
+impl<T: AstInfo> AstDisplay for AlterSourceStatement<T> {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("ALTER SOURCE ");
+        if self.if_exists {
+            f.write_str("IF EXISTS ");
+        }
+        f.write_node(&self.source_name);
+        f.write_str(" ");
+
+        match &self.action {
+            AlterSourceAction::RefreshSchema { cascade } => {
+                f.write_str("REFRESH SCHEMA");
+                if *cascade {
+                    f.write_str(" CASCADE");
+                }
+            }
+        }
+    }
+}
+
+impl_display_t!(AlterSourceStatement);
+
 /// `ALTER SECRET ... AS`
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AlterSecretStatement<T: AstInfo> {
@@ -1332,6 +1576,8 @@ impl<T: AstInfo> AstDisplay for ShowObjectsStatement<T> {
             ObjectType::Cluster => "CLUSTERS",
             ObjectType::Object => "OBJECTS",
             ObjectType::Secret => "SECRETS",
+            ObjectType::Notification => "NOTIFICATIONS",
+            ObjectType::Func => "FUNCTIONS",
             ObjectType::Index => unreachable!(),
         });
         if let Some(from) = &self.from {
@@ -1549,6 +1795,7 @@ impl_display!(RollbackStatement);
 pub struct TailStatement<T: AstInfo> {
     pub relation: TailRelation<T>,
     pub options: Vec<WithOption>,
+    pub envelope: Option<TailEnvelope>,
     pub as_of: Option<Expr<T>>,
 }
 
@@ -1556,9 +1803,16 @@ impl<T: AstInfo> AstDisplay for TailStatement<T> {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         f.write_str("TAIL ");
         f.write_node(&self.relation);
-        if !self.options.is_empty() {
+        if !self.options.is_empty() || self.envelope.is_some() {
             f.write_str(" WITH (");
             f.write_node(&display::comma_separated(&self.options));
+            if let Some(envelope) = &self.envelope {
+                if !self.options.is_empty() {
+                    f.write_str(", ");
+                }
+                f.write_str("ENVELOPE ");
+                f.write_node(envelope);
+            }
             f.write_str(")");
         }
         if let Some(as_of) = &self.as_of {
@@ -1569,6 +1823,26 @@ impl<T: AstInfo> AstDisplay for TailStatement<T> {
 }
 impl_display_t!(TailStatement);
 
+/// The shape of the updates emitted by a `TAIL`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TailEnvelope {
+    /// Emit one row per key per timestamp describing the key's new value.
+    Upsert { key_columns: Vec<Ident> },
+}
+
+impl AstDisplay for TailEnvelope {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        match self {
+            TailEnvelope::Upsert { key_columns } => {
+                f.write_str("UPSERT (KEY (");
+                f.write_node(&display::comma_separated(key_columns));
+                f.write_str("))");
+            }
+        }
+    }
+}
+impl_display!(TailEnvelope);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum TailRelation<T: AstInfo> {
     Name(T::ObjectName),
@@ -1600,10 +1874,11 @@ pub struct ExplainStatement<T: AstInfo> {
 impl<T: AstInfo> AstDisplay for ExplainStatement<T> {
     fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
         f.write_str("EXPLAIN ");
-        if self.options.timing {
-            f.write_str("(TIMING ");
-            f.write_str(self.options.timing);
-            f.write_str(") ");
+        match (self.options.timing, self.options.estimates) {
+            (false, false) => (),
+            (true, false) => f.write_str("(TIMING true) "),
+            (false, true) => f.write_str("(ESTIMATES true) "),
+            (true, true) => f.write_str("(TIMING true, ESTIMATES true) "),
         }
         if self.options.typed {
             f.write_str("TYPED ");
@@ -1643,6 +1918,8 @@ pub enum ObjectType {
     Cluster,
     Object,
     Secret,
+    Notification,
+    Func,
 }
 
 impl AstDisplay for ObjectType {
@@ -1658,6 +1935,8 @@ impl AstDisplay for ObjectType {
             ObjectType::Cluster => "CLUSTER",
             ObjectType::Object => "OBJECT",
             ObjectType::Secret => "SECRET",
+            ObjectType::Notification => "NOTIFICATION",
+            ObjectType::Func => "FUNCTION",
         })
     }
 }
@@ -1877,6 +2156,8 @@ pub enum ExplainStage {
     PhysicalPlan,
     /// The dependent and selected timestamps
     Timestamp,
+    /// The render::plan::Plan annotated with runtime statistics
+    Analyze,
 }
 
 impl AstDisplay for ExplainStage {
@@ -1889,6 +2170,7 @@ impl AstDisplay for ExplainStage {
             ExplainStage::OptimizedPlan => f.write_str("OPTIMIZED PLAN"),
             ExplainStage::PhysicalPlan => f.write_str("PHYSICAL PLAN"),
             ExplainStage::Timestamp => f.write_str("TIMESTAMP"),
+            ExplainStage::Analyze => f.write_str("ANALYZE"),
         }
     }
 }
@@ -1897,6 +2179,7 @@ impl_display!(ExplainStage);
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Explainee<T: AstInfo> {
     View(T::ObjectName),
+    Index(T::ObjectName),
     Query(Query<T>),
 }
 
@@ -1904,6 +2187,7 @@ pub enum Explainee<T: AstInfo> {
 pub struct ExplainOptions {
     pub typed: bool,
     pub timing: bool,
+    pub estimates: bool,
 }
 
 impl<T: AstInfo> AstDisplay for Explainee<T> {
@@ -1913,6 +2197,10 @@ impl<T: AstInfo> AstDisplay for Explainee<T> {
                 f.write_str("VIEW ");
                 f.write_node(name);
             }
+            Self::Index(name) => {
+                f.write_str("INDEX ");
+                f.write_node(name);
+            }
             Self::Query(query) => f.write_node(query),
         }
     }
@@ -2065,6 +2353,38 @@ impl AstDisplay for RaiseStatement {
 }
 impl_display!(RaiseStatement);
 
+/// `LISTEN ...`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ListenStatement {
+    pub channel: Ident,
+}
+
+impl AstDisplay for ListenStatement {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("LISTEN ");
+        f.write_node(&self.channel);
+    }
+}
+impl_display!(ListenStatement);
+
+/// `UNLISTEN ...`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct UnlistenStatement {
+    /// The channel to stop listening on, or `None` for all channels.
+    pub channel: Option<Ident>,
+}
+
+impl AstDisplay for UnlistenStatement {
+    fn fmt<W: fmt::Write>(&self, f: &mut AstFormatter<W>) {
+        f.write_str("UNLISTEN ");
+        match &self.channel {
+            Some(channel) => f.write_node(channel),
+            None => f.write_str("*"),
+        }
+    }
+}
+impl_display!(UnlistenStatement);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum NoticeSeverity {
     Debug,
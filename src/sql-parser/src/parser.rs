@@ -87,6 +87,22 @@ pub fn parse_data_type(sql: &str) -> Result<UnresolvedDataType, ParserError> {
     }
 }
 
+/// Parses a SQL string containing a single, possibly qualified, object name.
+pub fn parse_object_name(sql: &str) -> Result<UnresolvedObjectName, ParserError> {
+    let tokens = lexer::lex(sql)?;
+    let mut parser = Parser::new(sql, tokens);
+    let name = parser.parse_object_name()?;
+    if parser.next_token().is_some() {
+        parser_err!(
+            parser,
+            parser.peek_prev_pos(),
+            "extra token after object name"
+        )
+    } else {
+        Ok(name)
+    }
+}
+
 macro_rules! maybe {
     ($e:expr) => {{
         if let Some(v) = $e {
@@ -260,6 +276,8 @@ impl<'a> Parser<'a> {
                 Token::Keyword(EXECUTE) => Ok(self.parse_execute()?),
                 Token::Keyword(DEALLOCATE) => Ok(self.parse_deallocate()?),
                 Token::Keyword(RAISE) => Ok(self.parse_raise()?),
+                Token::Keyword(LISTEN) => Ok(self.parse_listen()?),
+                Token::Keyword(UNLISTEN) => Ok(self.parse_unlisten()?),
                 Token::Keyword(kw) => parser_err!(
                     self,
                     self.peek_prev_pos(),
@@ -1578,6 +1596,10 @@ impl<'a> Parser<'a> {
             self.parse_create_table()
         } else if self.peek_keyword(SECRET) {
             self.parse_create_secret()
+        } else if self.peek_keyword(NOTIFICATION) {
+            self.parse_create_notification()
+        } else if self.peek_keyword(FUNCTION) {
+            self.parse_create_function()
         } else {
             let index = self.index;
 
@@ -1595,7 +1617,7 @@ impl<'a> Parser<'a> {
             } else {
                 self.expected(
                     self.peek_pos(),
-                    "DATABASE, SCHEMA, ROLE, USER, TYPE, INDEX, SINK, SOURCE, TABLE, SECRET or [OR REPLACE] [TEMPORARY] [MATERIALIZED] VIEW or VIEWS after CREATE",
+                    "DATABASE, SCHEMA, ROLE, USER, TYPE, INDEX, SINK, SOURCE, TABLE, SECRET, NOTIFICATION, FUNCTION or [OR REPLACE] [TEMPORARY] [MATERIALIZED] VIEW or VIEWS after CREATE",
                     self.peek_token(),
                 )
             }
@@ -1653,15 +1675,46 @@ impl<'a> Parser<'a> {
             };
             Format::Csv { columns, delimiter }
         } else if self.parse_keyword(JSON) {
-            Format::Json
+            // Only consume `WITH` if it starts an option list, so as not to
+            // swallow the `WITH SNAPSHOT` of a sink.
+            let schema = if self.peek_keyword(WITH) && self.peek_nth_token(1) == Some(Token::LParen)
+            {
+                self.expect_keyword(WITH)?;
+                self.expect_token(&Token::LParen)?;
+                self.expect_keywords(&[INFER, SCHEMA])?;
+                self.expect_token(&Token::RParen)?;
+                Some(JsonSchema::Infer)
+            } else if self.parse_keyword(USING) {
+                self.expect_keyword(SCHEMA)?;
+                Some(JsonSchema::Inline(self.parse_literal_string()?))
+            } else {
+                None
+            };
+            Format::Json { schema }
         } else if self.parse_keyword(TEXT) {
             Format::Text
         } else if self.parse_keyword(BYTES) {
             Format::Bytes
+        } else if self.parse_keyword(PARQUET) {
+            let schema = if self.parse_keyword(USING) {
+                self.expect_keyword(SCHEMA)?;
+                Some(self.parse_literal_string()?)
+            } else {
+                None
+            };
+            Format::Parquet { schema }
+        } else if self.parse_keyword(ORC) {
+            let schema = if self.parse_keyword(USING) {
+                self.expect_keyword(SCHEMA)?;
+                Some(self.parse_literal_string()?)
+            } else {
+                None
+            };
+            Format::Orc { schema }
         } else {
             return self.expected(
                 self.peek_pos(),
-                "AVRO, PROTOBUF, REGEX, CSV, JSON, TEXT, or BYTES",
+                "AVRO, PROTOBUF, REGEX, CSV, JSON, TEXT, BYTES, PARQUET, or ORC",
                 self.peek_token(),
             );
         };
@@ -1906,6 +1959,13 @@ impl<'a> Parser<'a> {
             Envelope::None
         };
 
+        let on_decode_error = if self.parse_keywords(&[ON, DECODE, ERROR]) {
+            self.expect_token(&Token::Eq)?;
+            Some(self.parse_decode_error_action()?)
+        } else {
+            None
+        };
+
         Ok(Statement::CreateSource(CreateSourceStatement {
             name,
             col_names,
@@ -1914,12 +1974,34 @@ impl<'a> Parser<'a> {
             format,
             include_metadata,
             envelope,
+            on_decode_error,
             if_not_exists,
             materialized,
             key_constraint,
         }))
     }
 
+    /// Parses the action of an `ON DECODE ERROR` clause, e.g.
+    ///
+    /// REDIRECT TO TOPIC 'dead-letters'
+    fn parse_decode_error_action(&mut self) -> Result<DecodeErrorAction<Raw>, ParserError> {
+        match self.expect_one_of_keywords(&[FAIL, SKIP, REDIRECT])? {
+            FAIL => Ok(DecodeErrorAction::Fail),
+            SKIP => Ok(DecodeErrorAction::Skip),
+            REDIRECT => {
+                self.expect_keyword(TO)?;
+                match self.expect_one_of_keywords(&[TOPIC, TABLE])? {
+                    TOPIC => Ok(DecodeErrorAction::RedirectToTopic(
+                        self.parse_literal_string()?,
+                    )),
+                    TABLE => Ok(DecodeErrorAction::RedirectToTable(self.parse_raw_name()?)),
+                    _ => unreachable!(),
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
     /// Parses the column section of a CREATE SOURCE statement which can be
     /// empty or a comma-separated list of column identifiers and a single key
     /// constraint, e.g.
@@ -2423,6 +2505,136 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    fn parse_create_notification(&mut self) -> Result<Statement<Raw>, ParserError> {
+        self.expect_keyword(NOTIFICATION)?;
+        let if_not_exists = self.parse_if_not_exists()?;
+        let name = self.parse_object_name()?;
+        self.expect_keyword(ON)?;
+        let on_name = self.parse_raw_name()?;
+        let predicate = if self.parse_keyword(WHEN) {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        Ok(Statement::CreateNotification(CreateNotificationStatement {
+            name,
+            if_not_exists,
+            on_name,
+            predicate,
+        }))
+    }
+
+    fn parse_create_function(&mut self) -> Result<Statement<Raw>, ParserError> {
+        self.expect_keyword(FUNCTION)?;
+        let name = self.parse_object_name()?;
+        self.expect_token(&Token::LParen)?;
+        let params = if self.consume_token(&Token::RParen) {
+            vec![]
+        } else {
+            let params = self.parse_comma_separated(Parser::parse_function_parameter)?;
+            self.expect_token(&Token::RParen)?;
+            params
+        };
+        self.expect_keyword(RETURNS)?;
+        let returns = if self.parse_keyword(SETOF) {
+            FunctionReturns::SetOf(self.parse_data_type()?)
+        } else if self.parse_keyword(TABLE) {
+            self.expect_token(&Token::LParen)?;
+            let columns = self.parse_comma_separated(|parser| {
+                Ok(FunctionReturnColumn {
+                    name: parser.parse_identifier()?,
+                    data_type: parser.parse_data_type()?,
+                })
+            })?;
+            self.expect_token(&Token::RParen)?;
+            FunctionReturns::Table(columns)
+        } else {
+            FunctionReturns::Scalar(self.parse_data_type()?)
+        };
+
+        // `LANGUAGE` and `AS` may appear in either order.
+        let mut language = false;
+        let mut body = None;
+        while !language || body.is_none() {
+            match self.parse_one_of_keywords(&[LANGUAGE, AS]) {
+                Some(LANGUAGE) if !language => {
+                    self.expect_keyword(SQL)?;
+                    language = true;
+                }
+                Some(AS) if body.is_none() => {
+                    let pos = self.peek_pos();
+                    let sql = self.parse_literal_string()?;
+                    body = Some(self.parse_function_body(&sql, &returns, pos)?);
+                }
+                _ => {
+                    let expected = match (language, &body) {
+                        (false, None) => "LANGUAGE or AS",
+                        (false, Some(_)) => "LANGUAGE",
+                        _ => "AS",
+                    };
+                    return self.expected(self.peek_pos(), expected, self.peek_token());
+                }
+            }
+        }
+
+        Ok(Statement::CreateFunction(CreateFunctionStatement {
+            name,
+            params,
+            returns,
+            body: body.unwrap(),
+        }))
+    }
+
+    fn parse_function_parameter(&mut self) -> Result<FunctionParameter<Raw>, ParserError> {
+        // A parameter is either `type` or `name type`. Try the former first,
+        // so that multi-word types like `double precision` are not mistaken
+        // for a name followed by a type.
+        let index = self.index;
+        if let Ok(data_type) = self.parse_data_type() {
+            if matches!(self.peek_token(), Some(Token::Comma) | Some(Token::RParen)) {
+                return Ok(FunctionParameter {
+                    name: None,
+                    data_type,
+                });
+            }
+        }
+        self.index = index;
+        Ok(FunctionParameter {
+            name: Some(self.parse_identifier()?),
+            data_type: self.parse_data_type()?,
+        })
+    }
+
+    /// Parses the body of a `CREATE FUNCTION` statement. Functions that
+    /// return a single value have an expression as their body, while functions
+    /// that return a set of rows have a query. Errors are reported at `pos`,
+    /// the position of the string containing the body.
+    fn parse_function_body(
+        &self,
+        sql: &str,
+        returns: &FunctionReturns<Raw>,
+        pos: usize,
+    ) -> Result<FunctionBody<Raw>, ParserError> {
+        let parse = || -> Result<FunctionBody<Raw>, ParserError> {
+            let mut parser = Parser::new(sql, lexer::lex(sql)?);
+            let body = match returns {
+                FunctionReturns::Scalar(_) => FunctionBody::Expr(parser.parse_expr()?),
+                FunctionReturns::SetOf(_) | FunctionReturns::Table(_) => {
+                    FunctionBody::Query(parser.parse_query()?)
+                }
+            };
+            // Permit a trailing semicolon, as in PostgreSQL.
+            let _ = parser.consume_token(&Token::Semicolon);
+            match parser.next_token() {
+                None => Ok(body),
+                Some(token) => {
+                    parser.expected(parser.peek_prev_pos(), "end of function body", Some(token))
+                }
+            }
+        };
+        parse().map_err(|e| self.error(pos, format!("invalid function body: {}", e.message)))
+    }
+
     fn parse_create_type(&mut self) -> Result<Statement<Raw>, ParserError> {
         self.expect_keyword(TYPE)?;
         let name = self.parse_object_name()?;
@@ -2569,7 +2781,20 @@ impl<'a> Parser<'a> {
         let materialized = self.parse_keyword(MATERIALIZED);
 
         let object_type = match self.parse_one_of_keywords(&[
-            DATABASE, INDEX, ROLE, CLUSTER, SECRET, SCHEMA, SINK, SOURCE, TABLE, TYPE, USER, VIEW,
+            DATABASE,
+            INDEX,
+            ROLE,
+            CLUSTER,
+            SECRET,
+            NOTIFICATION,
+            FUNCTION,
+            SCHEMA,
+            SINK,
+            SOURCE,
+            TABLE,
+            TYPE,
+            USER,
+            VIEW,
         ]) {
             Some(DATABASE) => {
                 let if_exists = self.parse_if_exists()?;
@@ -2625,11 +2850,13 @@ impl<'a> Parser<'a> {
             Some(TYPE) => ObjectType::Type,
             Some(VIEW) => ObjectType::View,
             Some(SECRET) => ObjectType::Secret,
+            Some(NOTIFICATION) => ObjectType::Notification,
+            Some(FUNCTION) => ObjectType::Func,
             _ => {
                 return self.expected(
                     self.peek_pos(),
-                    "DATABASE, INDEX, ROLE, CLUSTER, SECRET, SCHEMA, SINK, SOURCE, \
-                     TABLE, TYPE, USER, VIEW after DROP",
+                    "DATABASE, INDEX, ROLE, CLUSTER, SECRET, NOTIFICATION, FUNCTION, SCHEMA, \
+                     SINK, SOURCE, TABLE, TYPE, USER, VIEW after DROP",
                     self.peek_token(),
                 );
             }
@@ -2914,7 +3141,7 @@ impl<'a> Parser<'a> {
             .expect_one_of_keywords(&[SINK, SOURCE, VIEW, TABLE, INDEX, SECRET, CLUSTER])?
         {
             SINK => ObjectType::Sink,
-            SOURCE => ObjectType::Source,
+            SOURCE => return self.parse_alter_source(),
             VIEW => ObjectType::View,
             TABLE => ObjectType::Table,
             INDEX => return self.parse_alter_index(),
@@ -2984,6 +3211,35 @@ impl<'a> Parser<'a> {
         })
     }
 
+    fn parse_alter_source(&mut self) -> Result<Statement<Raw>, ParserError> {
+        let if_exists = self.parse_if_exists()?;
+        let name = self.parse_raw_name()?;
+
+        Ok(match self.expect_one_of_keywords(&[REFRESH, RENAME])? {
+            REFRESH => {
+                self.expect_keyword(SCHEMA)?;
+                let cascade = self.parse_keyword(CASCADE);
+                Statement::AlterSource(AlterSourceStatement {
+                    source_name: name,
+                    if_exists,
+                    action: AlterSourceAction::RefreshSchema { cascade },
+                })
+            }
+            RENAME => {
+                self.expect_keyword(TO)?;
+                let to_item_name = self.parse_identifier()?;
+
+                Statement::AlterObjectRename(AlterObjectRenameStatement {
+                    object_type: ObjectType::Source,
+                    if_exists,
+                    name,
+                    to_item_name,
+                })
+            }
+            _ => unreachable!(),
+        })
+    }
+
     fn parse_alter_secret(&mut self) -> Result<Statement<Raw>, ParserError> {
         let if_exists = self.parse_if_exists()?;
         let name = self.parse_raw_name()?;
@@ -4019,7 +4275,17 @@ impl<'a> Parser<'a> {
                 filter: self.parse_show_statement_filter()?,
             }))
         } else if let Some(object_type) = self.parse_one_of_keywords(&[
-            OBJECTS, ROLES, CLUSTERS, SINKS, SOURCES, TABLES, TYPES, USERS, VIEWS, SECRETS,
+            OBJECTS,
+            ROLES,
+            CLUSTERS,
+            SINKS,
+            SOURCES,
+            TABLES,
+            TYPES,
+            USERS,
+            VIEWS,
+            SECRETS,
+            NOTIFICATIONS,
         ]) {
             let object_type = match object_type {
                 OBJECTS => ObjectType::Object,
@@ -4031,6 +4297,7 @@ impl<'a> Parser<'a> {
                 TYPES => ObjectType::Type,
                 VIEWS => ObjectType::View,
                 SECRETS => ObjectType::Secret,
+                NOTIFICATIONS => ObjectType::Notification,
                 _ => unreachable!(),
             };
 
@@ -4394,13 +4661,42 @@ impl<'a> Parser<'a> {
         } else {
             InsertSource::Query(self.parse_query()?)
         };
+        let on_conflict = if self.parse_keywords(&[ON, CONFLICT]) {
+            Some(self.parse_on_conflict()?)
+        } else {
+            None
+        };
         Ok(Statement::Insert(InsertStatement {
             table_name,
             columns,
             source,
+            on_conflict,
         }))
     }
 
+    fn parse_on_conflict(&mut self) -> Result<OnConflict<Raw>, ParserError> {
+        let columns = self.parse_parenthesized_column_list(Optional)?;
+        self.expect_keyword(DO)?;
+        let action = match self.expect_one_of_keywords(&[NOTHING, UPDATE])? {
+            NOTHING => OnConflictAction::DoNothing,
+            UPDATE => {
+                self.expect_keyword(SET)?;
+                let assignments = self.parse_comma_separated(Parser::parse_assignment)?;
+                let selection = if self.parse_keyword(WHERE) {
+                    Some(self.parse_expr()?)
+                } else {
+                    None
+                };
+                OnConflictAction::DoUpdate {
+                    assignments,
+                    selection,
+                }
+            }
+            _ => unreachable!(),
+        };
+        Ok(OnConflict { columns, action })
+    }
+
     fn parse_update(&mut self) -> Result<Statement<Raw>, ParserError> {
         let table_name = RawObjectName::Name(self.parse_object_name()?);
 
@@ -4582,35 +4878,72 @@ impl<'a> Parser<'a> {
         } else {
             TailRelation::Name(self.parse_raw_name()?)
         };
-        let options = self.parse_opt_with_options()?;
+        let mut options = vec![];
+        let mut envelope = None;
+        if self.parse_keyword(WITH) {
+            self.expect_token(&Token::LParen)?;
+            self.parse_comma_separated(|parser| {
+                if parser.parse_keyword(ENVELOPE) {
+                    if envelope.is_some() {
+                        return parser_err!(
+                            parser,
+                            parser.peek_prev_pos(),
+                            "ENVELOPE specified more than once"
+                        );
+                    }
+                    envelope = Some(parser.parse_tail_envelope()?);
+                } else {
+                    options.push(parser.parse_with_option(true)?);
+                }
+                Ok(())
+            })?;
+            self.expect_token(&Token::RParen)?;
+        }
         let as_of = self.parse_optional_as_of()?;
         Ok(Statement::Tail(TailStatement {
             relation,
             options,
+            envelope,
             as_of,
         }))
     }
 
+    fn parse_tail_envelope(&mut self) -> Result<TailEnvelope, ParserError> {
+        self.expect_keyword(UPSERT)?;
+        self.expect_token(&Token::LParen)?;
+        self.expect_keyword(KEY)?;
+        let key_columns = self.parse_parenthesized_column_list(Mandatory)?;
+        self.expect_token(&Token::RParen)?;
+        Ok(TailEnvelope::Upsert { key_columns })
+    }
+
     /// Parse an `EXPLAIN` statement, assuming that the `EXPLAIN` token
     /// has already been consumed.
     fn parse_explain(&mut self) -> Result<Statement<Raw>, ParserError> {
         // (TYPED)?
         let typed = self.parse_keyword(TYPED);
         let mut timing = false;
+        let mut estimates = false;
 
-        // options: ( '(' TIMING (true|false) ')' )?
+        // options: ( '(' (TIMING | ESTIMATES) (true|false) [, ...] ')' )?
         if let Some(Token::LParen) = self.peek_token() {
             // Check whether a valid option is after the parentheses, since the
             // parentheses may belong to the actual query to be explained.
             match self.peek_nth_token(1) {
-                Some(Token::Keyword(TIMING)) => {
+                Some(Token::Keyword(TIMING)) | Some(Token::Keyword(ESTIMATES)) => {
                     self.next_token(); // Consume the LParen
-                    self.parse_comma_separated(|s| match s.expect_one_of_keywords(&[TIMING])? {
-                        TIMING => {
-                            timing = s.parse_boolean_value()?;
-                            Ok(())
+                    self.parse_comma_separated(|s| {
+                        match s.expect_one_of_keywords(&[TIMING, ESTIMATES])? {
+                            TIMING => {
+                                timing = s.parse_boolean_value()?;
+                                Ok(())
+                            }
+                            ESTIMATES => {
+                                estimates = s.parse_boolean_value()?;
+                                Ok(())
+                            }
+                            _ => unreachable!(),
                         }
-                        _ => unreachable!(),
                     })?;
                     self.expect_token(&Token::RParen)?;
                 }
@@ -4627,6 +4960,7 @@ impl<'a> Parser<'a> {
             PLAN,
             QUERY,
             TIMESTAMP,
+            ANALYZE,
         ]) {
             Some(RAW) => {
                 self.expect_keywords(&[PLAN, FOR])?;
@@ -4661,18 +4995,33 @@ impl<'a> Parser<'a> {
                 self.expect_keywords(&[FOR])?;
                 ExplainStage::Timestamp
             }
+            Some(ANALYZE) => {
+                self.expect_keywords(&[FOR])?;
+                ExplainStage::Analyze
+            }
             None => ExplainStage::OptimizedPlan,
             _ => unreachable!(),
         };
 
-        // VIEW view_name | query
-        let explainee = if self.parse_keyword(VIEW) {
+        // VIEW view_name | query, or INDEX index_name | VIEW view_name when
+        // analyzing, since only maintained dataflows can be analyzed.
+        let explainee = if stage == ExplainStage::Analyze {
+            match self.expect_one_of_keywords(&[INDEX, VIEW])? {
+                INDEX => Explainee::Index(self.parse_raw_name()?),
+                VIEW => Explainee::View(self.parse_raw_name()?),
+                _ => unreachable!(),
+            }
+        } else if self.parse_keyword(VIEW) {
             Explainee::View(self.parse_raw_name()?)
         } else {
             Explainee::Query(self.parse_query()?)
         };
 
-        let options = ExplainOptions { typed, timing };
+        let options = ExplainOptions {
+            typed,
+            timing,
+            estimates,
+        };
         Ok(Statement::Explain(ExplainStatement {
             stage,
             explainee,
@@ -4784,6 +5133,20 @@ impl<'a> Parser<'a> {
 
         Ok(Statement::Raise(RaiseStatement { severity }))
     }
+
+    fn parse_listen(&mut self) -> Result<Statement<Raw>, ParserError> {
+        let channel = self.parse_identifier()?;
+        Ok(Statement::Listen(ListenStatement { channel }))
+    }
+
+    fn parse_unlisten(&mut self) -> Result<Statement<Raw>, ParserError> {
+        let channel = if self.consume_token(&Token::Star) {
+            None
+        } else {
+            Some(self.parse_identifier()?)
+        };
+        Ok(Statement::Unlisten(UnlistenStatement { channel }))
+    }
 }
 
 impl CheckedRecursion for Parser<'_> {
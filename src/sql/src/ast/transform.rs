@@ -11,15 +11,21 @@
 
 use std::collections::{HashMap, HashSet};
 
+use mz_expr::GlobalId;
+use mz_ore::collections::CollectionExt;
 use mz_ore::str::StrExt;
-use mz_sql_parser::ast::{CreateSecretStatement, RawObjectName};
+use mz_repr::ColumnName;
+use mz_sql_parser::ast::{
+    CreateFunctionStatement, CreateNotificationStatement, CreateSecretStatement, FunctionBody,
+    RawObjectName, Select, SelectItem, SetExpr, TableAlias, TableFactor, TableWithJoins,
+};
 
 use crate::ast::visit::{self, Visit};
 use crate::ast::visit_mut::{self, VisitMut};
 use crate::ast::{
     AstInfo, CreateIndexStatement, CreateSinkStatement, CreateSourceStatement,
-    CreateTableStatement, CreateViewStatement, Expr, Ident, Query, Raw, Statement,
-    UnresolvedObjectName, ViewDefinition,
+    CreateTableStatement, CreateViewStatement, DecodeErrorAction, Expr, Ident, Query, Raw,
+    Statement, UnresolvedObjectName, ViewDefinition,
 };
 use crate::names::FullObjectName;
 
@@ -45,7 +51,9 @@ pub fn create_stmt_rename(create_stmt: &mut Statement<Raw>, to_item_name: String
             let object_name_len = name.0.len() - 1;
             name.0[object_name_len] = Ident::new(to_item_name);
         }
-        Statement::CreateSecret(CreateSecretStatement { name, .. }) => {
+        Statement::CreateSecret(CreateSecretStatement { name, .. })
+        | Statement::CreateNotification(CreateNotificationStatement { name, .. })
+        | Statement::CreateFunction(CreateFunctionStatement { name, .. }) => {
             let object_name_len = name.0.len() - 1;
             name.0[object_name_len] = Ident::new(to_item_name);
         }
@@ -89,19 +97,120 @@ pub fn create_stmt_rename_refs(
         Statement::CreateSink(CreateSinkStatement { from, .. }) => {
             maybe_update_object_name(from.name_mut());
         }
+        Statement::CreateNotification(CreateNotificationStatement { on_name, .. }) => {
+            maybe_update_object_name(on_name.name_mut());
+        }
         Statement::CreateView(CreateViewStatement {
             definition: ViewDefinition { query, .. },
             ..
         }) => {
             rewrite_query(from_name, to_item_name, query)?;
         }
-        Statement::CreateSource(_) | Statement::CreateTable(_) | Statement::CreateSecret(_) => {}
+        Statement::CreateFunction(CreateFunctionStatement { body, .. }) => match body {
+            FunctionBody::Query(query) => rewrite_query(from_name, to_item_name, query)?,
+            FunctionBody::Expr(expr) => {
+                // Expression bodies can only refer to items from within
+                // subqueries, so rewrite them as the projection of a query.
+                let mut query = Query::select(Select::default().project(SelectItem::Expr {
+                    expr: expr.clone(),
+                    alias: None,
+                }));
+                rewrite_query(from_name, to_item_name, &mut query)?;
+                match query.body {
+                    SetExpr::Select(select) => match select.projection.into_element() {
+                        SelectItem::Expr {
+                            expr: rewritten, ..
+                        } => *expr = rewritten,
+                        SelectItem::Wildcard => unreachable!(),
+                    },
+                    _ => unreachable!(),
+                }
+            }
+        },
+        Statement::CreateSource(CreateSourceStatement {
+            on_decode_error, ..
+        }) => {
+            if let Some(DecodeErrorAction::RedirectToTable(table)) = on_decode_error {
+                maybe_update_object_name(table.name_mut());
+            }
+        }
+        Statement::CreateTable(_) | Statement::CreateSecret(_) => {}
         _ => unreachable!("Internal error: only catalog items need to update item refs"),
     }
 
     Ok(())
 }
 
+/// Restricts the references to the relation `id`, which is named `name`, in
+/// the view defined by `create_stmt` to the relation's columns `columns`.
+///
+/// This keeps the columns of a view unchanged when it selects all columns of
+/// a relation that has gained columns.
+pub fn create_stmt_restrict_relation_columns(
+    create_stmt: &mut Statement<Raw>,
+    id: GlobalId,
+    name: FullObjectName,
+    columns: &[ColumnName],
+) {
+    struct ColumnRestricter<'a> {
+        id: String,
+        name: UnresolvedObjectName,
+        columns: &'a [ColumnName],
+    }
+
+    impl<'a, 'ast> VisitMut<'ast, Raw> for ColumnRestricter<'a> {
+        fn visit_table_factor_mut(&mut self, table_factor: &'ast mut TableFactor<Raw>) {
+            let refers_to_relation = |name: &RawObjectName| match name {
+                RawObjectName::Id(id, _) => *id == self.id,
+                RawObjectName::Name(name) => *name == self.name,
+            };
+            match table_factor {
+                TableFactor::Table { name, alias } if refers_to_relation(name) => {
+                    let mut select = Select::default().from(TableWithJoins {
+                        relation: TableFactor::Table {
+                            name: name.clone(),
+                            alias: None,
+                        },
+                        joins: vec![],
+                    });
+                    for column in self.columns {
+                        select = select.project(SelectItem::Expr {
+                            expr: Expr::Identifier(vec![Ident::new(column.as_str())]),
+                            alias: None,
+                        });
+                    }
+                    // Qualified column references must still resolve, so the
+                    // subquery is named after the relation if it has no alias.
+                    let alias = alias.take().unwrap_or_else(|| TableAlias {
+                        name: name.name().0.last().expect("names are not empty").clone(),
+                        columns: vec![],
+                        strict: false,
+                    });
+                    *table_factor = TableFactor::Derived {
+                        lateral: false,
+                        subquery: Box::new(Query::select(select)),
+                        alias: Some(alias),
+                    };
+                }
+                _ => visit_mut::visit_table_factor_mut(self, table_factor),
+            }
+        }
+    }
+
+    match create_stmt {
+        Statement::CreateView(CreateViewStatement {
+            definition: ViewDefinition { query, .. },
+            ..
+        }) => ColumnRestricter {
+            id: id.to_string(),
+            name: UnresolvedObjectName::from(name),
+            columns,
+        }
+        .visit_query_mut(query),
+        _ => unreachable!("Internal error: only views can have their columns restricted"),
+    }
+}
+
 /// Rewrites `query`'s references of `from` to `to` or errors if too ambiguous.
 fn rewrite_query(from: FullObjectName, to: String, query: &mut Query<Raw>) -> Result<(), String> {
     let from_ident = Ident::new(from.item.clone());
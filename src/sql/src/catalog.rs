@@ -28,12 +28,13 @@ use mz_repr::{ColumnName, RelationDesc, ScalarType};
 use mz_sql_parser::ast::Expr;
 use uuid::Uuid;
 
-use crate::func::Func;
+use crate::func::{Func, SqlFunc};
 use crate::names::{
     Aug, DatabaseId, FullObjectName, PartialObjectName, QualifiedObjectName, QualifiedSchemaName,
     ResolvedDatabaseSpecifier, SchemaSpecifier,
 };
 use crate::plan::statement::StatementDesc;
+use crate::plan::UniqueConstraint;
 
 /// A catalog keeps track of SQL objects and session state available to the
 /// planner.
@@ -298,6 +299,10 @@ pub trait CatalogItem {
     /// anything other than a function), it returns an error.
     fn func(&self) -> Result<&'static Func, CatalogError>;
 
+    /// Returns the function defined in SQL, if the catalog item is a function
+    /// created by `CREATE FUNCTION`.
+    fn sql_func(&self) -> Option<&SqlFunc>;
+
     /// Returns the resolved source connector.
     ///
     /// If the catalog item is not of a type that contains a `SourceConnector`
@@ -326,6 +331,10 @@ pub trait CatalogItem {
     /// catalog item is a table.
     fn table_details(&self) -> Option<&[Expr<Aug>]>;
 
+    /// Returns the `PRIMARY KEY` and `UNIQUE` constraints associated with the
+    /// catalog item, if the catalog item is a table.
+    fn table_unique_constraints(&self) -> Option<&[UniqueConstraint]>;
+
     /// Returns the type information associated with the catalog item, if the
     /// catalog item is a type.
     fn type_details(&self) -> Option<&CatalogTypeDetails<IdReference>>;
@@ -350,6 +359,8 @@ pub enum CatalogItemType {
     Func,
     /// A Secret.
     Secret,
+    /// A notification.
+    Notification,
 }
 
 impl fmt::Display for CatalogItemType {
@@ -363,6 +374,7 @@ impl fmt::Display for CatalogItemType {
             CatalogItemType::Type => f.write_str("type"),
             CatalogItemType::Func => f.write_str("func"),
             CatalogItemType::Secret => f.write_str("secret"),
+            CatalogItemType::Notification => f.write_str("notification"),
         }
     }
 }
@@ -20,7 +20,9 @@ use lazy_static::lazy_static;
 use mz_expr::func;
 use mz_ore::collections::CollectionExt;
 use mz_pgrepr::oid;
-use mz_repr::{ColumnName, ColumnType, Datum, RelationType, Row, ScalarBaseType, ScalarType};
+use mz_repr::{
+    ColumnName, ColumnType, Datum, RelationDesc, RelationType, Row, ScalarBaseType, ScalarType,
+};
 
 use crate::ast::{SelectStatement, Statement};
 use crate::names::{resolve_names, resolve_names_expr, PartialObjectName};
@@ -1582,7 +1584,7 @@ macro_rules! impl_def {
     // Return type can be automatically determined as a function of the
     // parameters.
     ($params:expr, $op:expr, $oid:expr) => {{
-        let pcx = crate::plan::PlanContext::new(chrono::MIN_DATETIME, false);
+        let pcx = crate::plan::PlanContext::new(chrono::MIN_DATETIME, false, true);
         let scx = StatementContext::new(None, &crate::catalog::DummyCatalog);
         // This lifetime is compatible with more functions.
         let qcx = QueryContext::root(&scx, QueryLifetime::OneShot(&pcx));
@@ -1661,6 +1663,40 @@ impl Func {
     }
 }
 
+/// A function defined in SQL by `CREATE FUNCTION`.
+///
+/// The body of the function is planned when the function is created. It refers
+/// to the function's arguments via [`HirScalarExpr::Parameter`], so that calls
+/// to the function are inlined by splicing the planned arguments into the body.
+#[derive(Clone, Debug)]
+pub struct SqlFunc {
+    /// The types of the function's parameters.
+    pub params: Vec<ScalarType>,
+    pub body: SqlFuncBody,
+}
+
+#[derive(Clone, Debug)]
+pub enum SqlFuncBody {
+    /// The body of a function that returns a single value.
+    Scalar {
+        expr: HirScalarExpr,
+        return_type: ScalarType,
+    },
+    /// The body of a function that returns a set of rows.
+    Table {
+        expr: HirRelationExpr,
+        desc: RelationDesc,
+    },
+}
+
+impl SqlFunc {
+    /// Reports whether the function returns a set of rows, i.e., whether it is
+    /// a table function.
+    pub fn returns_set(&self) -> bool {
+        matches!(self.body, SqlFuncBody::Table { .. })
+    }
+}
+
 /// Functions using this macro should be transformed/planned away before
 /// reaching function selection code, but still need to be present in the
 /// catalog during planning.
@@ -14,6 +14,7 @@ use std::convert::{self, TryInto};
 use std::fs::File;
 use std::io::Read;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use anyhow::bail;
 
@@ -21,7 +22,7 @@ use mz_kafka_util::client::MzClientContext;
 use mz_ore::task;
 use rdkafka::client::ClientContext;
 use rdkafka::consumer::{BaseConsumer, Consumer, ConsumerContext};
-use rdkafka::{Offset, TopicPartitionList};
+use rdkafka::{Message, Offset, TopicPartitionList};
 use reqwest::Url;
 use tokio::time::Duration;
 
@@ -410,6 +411,54 @@ pub async fn lookup_start_offsets(
     .await?
 }
 
+/// Returns the keys and payloads of up to `n` messages from the start of
+/// `topic`.
+///
+/// Fewer than `n` messages are returned if no more messages arrive within
+/// `timeout`, e.g. because the topic does not contain `n` messages.
+pub async fn sample_messages(
+    consumer: Arc<BaseConsumer<KafkaErrCheckContext>>,
+    topic: &str,
+    n: usize,
+    timeout: Duration,
+) -> Result<Vec<(Option<Vec<u8>>, Option<Vec<u8>>)>, anyhow::Error> {
+    task::spawn_blocking(|| format!("kafka_sample_messages:{topic}"), {
+        let topic = topic.to_string();
+        move || {
+            let num_partitions = mz_kafka_util::client::get_partitions(
+                consumer.as_ref().client(),
+                &topic,
+                Duration::from_secs(10),
+            )?
+            .len();
+
+            let mut tpl = TopicPartitionList::with_capacity(num_partitions);
+            tpl.add_partition_range(&topic, 0, num_partitions as i32 - 1);
+            tpl.set_all_offsets(Offset::Beginning)?;
+            consumer.assign(&tpl)?;
+
+            let deadline = Instant::now() + timeout;
+            let mut messages = vec![];
+            while messages.len() < n {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                match consumer.poll(remaining) {
+                    Some(message) => {
+                        let message = message?;
+                        messages.push((
+                            message.key().map(|key| key.to_vec()),
+                            message.payload().map(|payload| payload.to_vec()),
+                        ));
+                    }
+                    None => break,
+                }
+            }
+            consumer.unassign()?;
+            Ok(messages)
+        }
+    })
+    .await?
+}
+
 // Kafka supports bulk lookup of watermarks, but it is not exposed in rdkafka.
 // If that ever changes, we will want to first collect all pids that have no
 // offset for a given timestamp and then do a single request (instead of doing
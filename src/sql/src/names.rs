@@ -21,11 +21,11 @@ use mz_expr::{GlobalId, LocalId};
 use mz_ore::str::StrExt;
 
 use crate::ast::display::{AstDisplay, AstFormatter};
-use crate::ast::fold::Fold;
+use crate::ast::fold::{self, Fold};
 use crate::ast::visit_mut::VisitMut;
 use crate::ast::{
-    self, AstInfo, Cte, Expr, Ident, Query, Raw, RawIdent, RawObjectName, Statement,
-    UnresolvedDataType, UnresolvedObjectName,
+    self, AstInfo, Cte, Expr, Function, Ident, Query, Raw, RawIdent, RawObjectName, Statement,
+    TableFunction, UnresolvedDataType, UnresolvedObjectName,
 };
 use crate::catalog::{CatalogItemType, CatalogTypeDetails, SessionCatalog};
 use crate::normalize;
@@ -716,6 +716,21 @@ impl<'a> NameResolver<'a> {
             }
         }
     }
+
+    /// Records a dependency on the function named `name` if it is a function
+    /// created by `CREATE FUNCTION`.
+    ///
+    /// Function names are otherwise left unresolved, as they are resolved
+    /// during planning.
+    fn record_function_dependency(&mut self, name: &UnresolvedObjectName) {
+        if let Ok(name) = normalize::unresolved_object_name(name.clone()) {
+            if let Ok(item) = self.catalog.resolve_function(&name) {
+                if item.sql_func().is_some() {
+                    self.ids.insert(item.id());
+                }
+            }
+        }
+    }
 }
 
 impl<'a> Fold<Raw, Aug> for NameResolver<'a> {
@@ -773,6 +788,16 @@ impl<'a> Fold<Raw, Aug> for NameResolver<'a> {
         result
     }
 
+    fn fold_function(&mut self, node: Function<Raw>) -> Function<Aug> {
+        self.record_function_dependency(&node.name);
+        fold::fold_function(self, node)
+    }
+
+    fn fold_table_function(&mut self, node: TableFunction<Raw>) -> TableFunction<Aug> {
+        self.record_function_dependency(&node.name);
+        fold::fold_table_function(self, node)
+    }
+
     fn fold_cte_id(&mut self, _id: <Raw as AstInfo>::CteId) -> <Aug as AstInfo>::CteId {
         panic!("this should have been handled when walking the CTE");
     }
@@ -24,11 +24,11 @@ use mz_repr::ColumnName;
 use mz_sql_parser::ast::display::AstDisplay;
 use mz_sql_parser::ast::visit_mut::{self, VisitMut};
 use mz_sql_parser::ast::{
-    AstInfo, CreateIndexStatement, CreateSecretStatement, CreateSinkStatement,
-    CreateSourceStatement, CreateTableStatement, CreateTypeAs, CreateTypeStatement,
-    CreateViewStatement, Function, FunctionArgs, Ident, IfExistsBehavior, Op, Query, SqlOption,
-    Statement, TableFactor, TableFunction, UnresolvedObjectName, UnresolvedSchemaName, Value,
-    ViewDefinition,
+    AstInfo, CreateFunctionStatement, CreateIndexStatement, CreateNotificationStatement,
+    CreateSecretStatement, CreateSinkStatement, CreateSourceStatement, CreateTableStatement,
+    CreateTypeAs, CreateTypeStatement, CreateViewStatement, Function, FunctionArgs, Ident,
+    IfExistsBehavior, Op, Query, SqlOption, Statement, TableFactor, TableFunction,
+    UnresolvedObjectName, UnresolvedSchemaName, Value, ViewDefinition,
 };
 
 use crate::names::{
@@ -299,6 +299,7 @@ pub fn create_statement(
             format: _,
             include_metadata: _,
             envelope: _,
+            on_decode_error: _,
             if_not_exists,
             materialized,
             key_constraint: _,
@@ -430,6 +431,37 @@ pub fn create_statement(
             *if_not_exists = false;
         }
 
+        Statement::CreateNotification(CreateNotificationStatement {
+            name,
+            if_not_exists,
+            on_name: _,
+            predicate,
+        }) => {
+            *name = allocate_name(name)?;
+            if let Some(predicate) = predicate {
+                let mut normalizer = QueryNormalizer::new(scx);
+                normalizer.visit_expr_mut(predicate);
+                if let Some(err) = normalizer.err {
+                    return Err(err.into());
+                }
+            }
+            *if_not_exists = false;
+        }
+
+        Statement::CreateFunction(CreateFunctionStatement {
+            name,
+            params: _,
+            returns: _,
+            body,
+        }) => {
+            *name = allocate_name(name)?;
+            let mut normalizer = QueryNormalizer::new(scx);
+            normalizer.visit_function_body_mut(body);
+            if let Some(err) = normalizer.err {
+                return Err(err.into());
+            }
+        }
+
         _ => unreachable!(),
     }
 
@@ -9,4 +9,5 @@
 
 //! SQL parsing.
 
+pub use mz_sql_parser::parser::parse_object_name;
 pub use mz_sql_parser::parser::parse_statements as parse;
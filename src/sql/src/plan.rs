@@ -41,10 +41,11 @@ use mz_ore::now::{self, NOW_ZERO};
 use mz_repr::{ColumnName, Diff, RelationDesc, Row, ScalarType};
 
 use crate::ast::{
-    ExplainOptions, ExplainStage, Expr, FetchDirection, NoticeSeverity, ObjectType, Raw, Statement,
-    TransactionAccessMode,
+    CreateSourceStatement, ExplainOptions, ExplainStage, Expr, FetchDirection, NoticeSeverity,
+    ObjectType, Raw, Statement, TransactionAccessMode,
 };
 use crate::catalog::{CatalogType, IdReference};
+use crate::func::SqlFunc;
 use crate::names::{
     Aug, DatabaseId, FullObjectName, QualifiedObjectName, ResolvedDatabaseSpecifier, SchemaId,
 };
@@ -67,7 +68,9 @@ pub use error::PlanError;
 pub use explain::Explanation;
 pub use optimize::OptimizerConfig;
 pub use query::{QueryContext, QueryLifetime};
-pub use statement::{describe, plan, plan_copy_from, StatementContext, StatementDesc};
+pub use statement::{
+    describe, plan, plan_copy_from, plan_select_unbound, StatementContext, StatementDesc,
+};
 
 /// Instructions for executing a SQL query.
 #[derive(Debug)]
@@ -78,6 +81,8 @@ pub enum Plan {
     CreateComputeInstance(CreateComputeInstancePlan),
     CreateSource(CreateSourcePlan),
     CreateSecret(CreateSecretPlan),
+    CreateNotification(CreateNotificationPlan),
+    CreateFunction(CreateFunctionPlan),
     CreateSink(CreateSinkPlan),
     CreateTable(CreateTablePlan),
     CreateView(CreateViewPlan),
@@ -103,6 +108,7 @@ pub enum Plan {
     SendRows(SendRowsPlan),
     CopyFrom(CopyFromPlan),
     Explain(ExplainPlan),
+    ExplainAnalyze(ExplainAnalyzePlan),
     SendDiffs(SendDiffsPlan),
     Insert(InsertPlan),
     AlterNoop(AlterNoopPlan),
@@ -111,6 +117,7 @@ pub enum Plan {
     AlterIndexResetOptions(AlterIndexResetOptionsPlan),
     AlterIndexEnable(AlterIndexEnablePlan),
     AlterItemRename(AlterItemRenamePlan),
+    AlterSourceRefreshSchema(AlterSourceRefreshSchemaPlan),
     Declare(DeclarePlan),
     Fetch(FetchPlan),
     Close(ClosePlan),
@@ -119,6 +126,8 @@ pub enum Plan {
     Execute(ExecutePlan),
     Deallocate(DeallocatePlan),
     Raise(RaisePlan),
+    Listen(ListenPlan),
+    Unlisten(UnlistenPlan),
 }
 
 #[derive(Debug)]
@@ -200,6 +209,19 @@ pub struct CreateSecretPlan {
     pub if_not_exists: bool,
 }
 
+#[derive(Debug)]
+pub struct CreateNotificationPlan {
+    pub name: QualifiedObjectName,
+    pub notification: Notification,
+    pub if_not_exists: bool,
+}
+
+#[derive(Debug)]
+pub struct CreateFunctionPlan {
+    pub name: QualifiedObjectName,
+    pub function: Function,
+}
+
 #[derive(Debug)]
 pub struct CreateSinkPlan {
     pub name: QualifiedObjectName,
@@ -293,6 +315,25 @@ pub struct PeekPlan {
     pub copy_to: Option<CopyFormat>,
 }
 
+/// The plan of a `SELECT` statement whose parameters are not yet bound, so
+/// that it can be executed with any values of them.
+#[derive(Debug, Clone)]
+pub struct UnboundPeekPlan {
+    pub source: HirRelationExpr,
+    pub when: QueryWhen,
+    pub finishing: RowSetFinishing,
+    pub config: OptimizerConfig,
+}
+
+impl UnboundPeekPlan {
+    /// Binds the parameters of the plan to `params`, and lowers the result.
+    pub fn bind(&self, params: &Params) -> Result<MirRelationExpr, anyhow::Error> {
+        let mut source = self.source.clone();
+        source.bind_parameters(params)?;
+        Ok(source.optimize_and_lower(&self.config)?)
+    }
+}
+
 #[derive(Debug)]
 pub struct TailPlan {
     pub from: TailFrom,
@@ -300,6 +341,9 @@ pub struct TailPlan {
     pub when: QueryWhen,
     pub copy_to: Option<CopyFormat>,
     pub emit_progress: bool,
+    /// If set, the columns by which to key the upserts to emit instead of
+    /// raw updates.
+    pub upsert_key: Option<Vec<usize>>,
 }
 
 #[derive(Debug)]
@@ -332,6 +376,12 @@ pub struct ExplainPlan {
     pub options: ExplainOptions,
 }
 
+#[derive(Debug)]
+pub struct ExplainAnalyzePlan {
+    /// The index or view whose dataflow is analyzed.
+    pub id: GlobalId,
+}
+
 #[derive(Debug)]
 pub struct SendDiffsPlan {
     pub id: GlobalId,
@@ -352,6 +402,35 @@ pub struct ReadThenWritePlan {
     pub finishing: RowSetFinishing,
     pub assignments: HashMap<usize, mz_expr::MirScalarExpr>,
     pub kind: MutationKind,
+    /// How to resolve inserted rows that conflict with existing rows. Only
+    /// set for `INSERT ... ON CONFLICT`.
+    pub on_conflict: Option<OnConflictPlan>,
+}
+
+/// The `ON CONFLICT` clause of an `INSERT`.
+#[derive(Debug)]
+pub struct OnConflictPlan {
+    /// The unique keys, as column indexes, on which inserted rows conflict
+    /// with existing rows.
+    pub keys: Vec<Vec<usize>>,
+    pub resolution: ConflictResolution,
+}
+
+#[derive(Debug)]
+pub enum ConflictResolution {
+    /// Skip the inserted row.
+    DoNothing,
+    /// Replace the existing row with one computed by `assignments`, if
+    /// `selection` holds.
+    ///
+    /// Both are evaluated over the columns of the existing row followed by
+    /// the columns of the inserted row.
+    DoUpdate {
+        /// Map from column index to SET expression.
+        assignments: HashMap<usize, mz_expr::MirScalarExpr>,
+        /// WHERE filter.
+        selection: Option<mz_expr::MirScalarExpr>,
+    },
 }
 
 /// Generated by `ALTER ... IF EXISTS` if the named object did not exist.
@@ -391,6 +470,17 @@ pub struct AlterItemRenamePlan {
     pub object_type: ObjectType,
 }
 
+#[derive(Debug)]
+pub struct AlterSourceRefreshSchemaPlan {
+    pub id: GlobalId,
+    /// The statement that created the source, which must be purified again to
+    /// fetch the latest schema.
+    pub create_stmt: CreateSourceStatement<Raw>,
+    /// Whether to propagate the new columns to views that select all columns
+    /// of the source.
+    pub cascade: bool,
+}
+
 #[derive(Debug)]
 pub struct DeclarePlan {
     pub name: String,
@@ -432,20 +522,54 @@ pub struct RaisePlan {
     pub severity: NoticeSeverity,
 }
 
+#[derive(Debug)]
+pub struct ListenPlan {
+    /// The name of the notification to listen to.
+    pub channel: QualifiedObjectName,
+}
+
+#[derive(Debug)]
+pub struct UnlistenPlan {
+    /// The name of the notification to stop listening to, or `None` for all
+    /// channels.
+    pub channel: Option<QualifiedObjectName>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Table {
     pub create_sql: String,
     pub desc: RelationDesc,
     pub defaults: Vec<Expr<Aug>>,
+    pub unique_constraints: Vec<UniqueConstraint>,
+    pub check_constraints: Vec<CheckConstraint>,
     pub temporary: bool,
     pub depends_on: Vec<GlobalId>,
 }
 
+/// A `PRIMARY KEY` or `UNIQUE` constraint on a table.
+#[derive(Clone, Debug)]
+pub struct UniqueConstraint {
+    pub name: String,
+    /// The indexes of the constrained columns.
+    pub columns: Vec<usize>,
+    pub is_primary: bool,
+}
+
+/// A `CHECK` constraint on a table.
+#[derive(Clone, Debug)]
+pub struct CheckConstraint {
+    pub name: String,
+    /// A boolean expression over the columns of the table. Rows for which it
+    /// evaluates to false violate the constraint.
+    pub expr: MirScalarExpr,
+}
+
 #[derive(Clone, Debug)]
 pub struct Source {
     pub create_sql: String,
     pub connector: SourceConnector,
     pub desc: RelationDesc,
+    pub depends_on: Vec<GlobalId>,
 }
 
 #[derive(Clone, Debug)]
@@ -454,6 +578,25 @@ pub struct Secret {
     pub secret_as: MirScalarExpr,
 }
 
+#[derive(Clone, Debug)]
+pub struct Notification {
+    pub create_sql: String,
+    /// The view whose changes raise the notification.
+    pub on: GlobalId,
+    /// Only changes to rows that satisfy this predicate raise the
+    /// notification.
+    pub predicate: Option<MirScalarExpr>,
+    pub depends_on: Vec<GlobalId>,
+    pub compute_instance: ComputeInstanceId,
+}
+
+#[derive(Clone, Debug)]
+pub struct Function {
+    pub create_sql: String,
+    pub func: SqlFunc,
+    pub depends_on: Vec<GlobalId>,
+}
+
 #[derive(Clone, Debug)]
 pub struct Sink {
     pub create_sql: String,
@@ -490,7 +633,7 @@ pub struct Type {
 }
 
 /// Specifies when a `Peek` or `Tail` should occur.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum QueryWhen {
     /// The peek should occur at the latest possible timestamp that allows the
     /// peek to complete immediately.
@@ -563,13 +706,19 @@ impl Params {
 pub struct PlanContext {
     pub wall_time: DateTime<Utc>,
     pub qgm_optimizations: bool,
+    pub qgm_optimizations_fallback: bool,
 }
 
 impl PlanContext {
-    pub fn new(wall_time: DateTime<Utc>, qgm_optimizations: bool) -> Self {
+    pub fn new(
+        wall_time: DateTime<Utc>,
+        qgm_optimizations: bool,
+        qgm_optimizations_fallback: bool,
+    ) -> Self {
         Self {
             wall_time,
             qgm_optimizations,
+            qgm_optimizations_fallback,
         }
     }
 
@@ -580,6 +729,7 @@ impl PlanContext {
         PlanContext {
             wall_time: now::to_datetime(NOW_ZERO()),
             qgm_optimizations: false,
+            qgm_optimizations_fallback: true,
         }
     }
 }
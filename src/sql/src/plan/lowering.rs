@@ -1557,6 +1557,80 @@ pub(crate) fn derive_equijoin_cols(
     }
 }
 
+/// If the on clause of an outer join without an outer context is an equijoin,
+/// possibly restricted by predicates on the input whose records are not
+/// preserved, figure out the join keys and those predicates.
+///
+/// `la` and `ra` are the arities of the lhs and the rhs respectively. The
+/// returned predicates refer to the columns of the input they restrict.
+fn derive_outer_join_keys(
+    la: usize,
+    ra: usize,
+    on: mz_expr::MirScalarExpr,
+    kind: &JoinKind,
+) -> Option<(Vec<usize>, Vec<usize>, Vec<mz_expr::MirScalarExpr>)> {
+    use mz_expr::BinaryFunc;
+    let mut predicates = Vec::new();
+    let mut todo = vec![on];
+    while let Some(next) = todo.pop() {
+        if let mz_expr::MirScalarExpr::CallBinary {
+            expr1,
+            expr2,
+            func: BinaryFunc::And,
+        } = next
+        {
+            todo.push(*expr1);
+            todo.push(*expr2);
+        } else {
+            predicates.push(next)
+        }
+    }
+
+    let mut l_keys = Vec::new();
+    let mut r_keys = Vec::new();
+    let mut filters = Vec::new();
+    for mut predicate in predicates {
+        if let mz_expr::MirScalarExpr::CallBinary {
+            expr1,
+            expr2,
+            func: BinaryFunc::Eq,
+        } = &predicate
+        {
+            if let (mz_expr::MirScalarExpr::Column(c1), mz_expr::MirScalarExpr::Column(c2)) =
+                (&**expr1, &**expr2)
+            {
+                let (c1, c2) = (std::cmp::min(*c1, *c2), std::cmp::max(*c1, *c2));
+                if c1 < la && la <= c2 {
+                    l_keys.push(c1);
+                    r_keys.push(c2 - la);
+                    continue;
+                }
+            }
+        }
+        // Predicates on the input whose records are not preserved can be
+        // applied to that input before the join.
+        let support = predicate.support();
+        if support.is_empty() {
+            return None;
+        }
+        match kind {
+            JoinKind::LeftOuter if support.iter().all(|c| la <= *c && *c < la + ra) => {
+                predicate.permute(&(0..la).chain(0..ra).collect_vec());
+                filters.push(predicate);
+            }
+            JoinKind::RightOuter if support.iter().all(|c| *c < la) => {
+                filters.push(predicate);
+            }
+            _ => return None,
+        }
+    }
+    if l_keys.is_empty() {
+        None
+    } else {
+        Some((l_keys, r_keys, filters))
+    }
+}
+
 /// Attempts an efficient outer join, if `on` has equijoin structure.
 fn attempt_outer_join(
     left: mz_expr::MirRelationExpr,
@@ -1576,6 +1650,58 @@ fn attempt_outer_join(
     let rt = right.typ().column_types.into_iter().skip(oa).collect_vec();
     let ra = rt.len();
 
+    // Without an outer context, the outer join can be rendered directly: its
+    // keys match only when equal and non-null, exactly as the equalities in `on`.
+    if oa == 0 {
+        if let Some((l_keys, r_keys, filters)) = derive_outer_join_keys(la, ra, on.clone(), &kind) {
+            let l_key = l_keys
+                .iter()
+                .map(|c| mz_expr::MirScalarExpr::Column(*c))
+                .collect_vec();
+            let r_key = r_keys
+                .iter()
+                .map(|c| mz_expr::MirScalarExpr::Column(*c))
+                .collect_vec();
+            match kind {
+                JoinKind::LeftOuter => {
+                    let right = if filters.is_empty() {
+                        right
+                    } else {
+                        right.filter(filters)
+                    };
+                    return Some(left.outer_join(
+                        right,
+                        l_key,
+                        r_key,
+                        mz_expr::OuterJoinKind::Left,
+                    ));
+                }
+                JoinKind::FullOuter => {
+                    return Some(left.outer_join(
+                        right,
+                        l_key,
+                        r_key,
+                        mz_expr::OuterJoinKind::Full,
+                    ));
+                }
+                JoinKind::RightOuter => {
+                    let left = if filters.is_empty() {
+                        left
+                    } else {
+                        left.filter(filters)
+                    };
+                    // Preserve `right`, and restore the column order of `left` and `right`.
+                    return Some(
+                        right
+                            .outer_join(left, r_key, l_key, mz_expr::OuterJoinKind::Left)
+                            .project((ra..ra + la).chain(0..ra).collect()),
+                    );
+                }
+                JoinKind::Inner => {}
+            }
+        }
+    }
+
     let equijoin_keys = derive_equijoin_cols(oa, la, ra, vec![on.clone()]);
     if equijoin_keys.is_none() {
         return None;
@@ -14,9 +14,12 @@ use crate::query_model::{Model, QGMError};
 use super::StatementContext;
 
 /// Feature flags for the [`HirRelationExpr::optimize_and_lower()`] logic.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct OptimizerConfig {
     pub qgm_optimizations: bool,
+    /// Whether queries that the QGM path does not support are lowered
+    /// directly instead of failing.
+    pub qgm_optimizations_fallback: bool,
 }
 
 /// Convert a reference to a [`StatementContext`] to an [`OptimizerConfig`].
@@ -28,9 +31,11 @@ impl<'a> From<&StatementContext<'a>> for OptimizerConfig {
         match scx.pcx() {
             Ok(pcx) => OptimizerConfig {
                 qgm_optimizations: pcx.qgm_optimizations,
+                qgm_optimizations_fallback: pcx.qgm_optimizations_fallback,
             },
             Err(..) => OptimizerConfig {
                 qgm_optimizations: false,
+                qgm_optimizations_fallback: true,
             },
         }
     }
@@ -45,8 +50,15 @@ impl HirRelationExpr {
         config: &OptimizerConfig,
     ) -> Result<mz_expr::MirRelationExpr, QGMError> {
         if config.qgm_optimizations {
-            // try to go through the QGM path
-            self.try_qgm_path()
+            // try to go through the QGM path, falling back to the HIR path for
+            // queries that use features the QGM path does not support yet
+            // unless the fallback is disabled
+            match self.clone().try_qgm_path() {
+                Err(e) if config.qgm_optimizations_fallback && e.is_unsupported() => {
+                    Ok(self.lower())
+                }
+                result => result,
+            }
         } else {
             // directly decorrelate and lower into a MirRelationExpr
             Ok(self.lower())
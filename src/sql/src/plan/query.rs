@@ -33,7 +33,7 @@
 
 use std::borrow::Cow;
 use std::cell::RefCell;
-use std::collections::{BTreeMap, HashMap, HashSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::convert::{TryFrom, TryInto};
 
 use std::iter;
@@ -56,13 +56,14 @@ use mz_repr::{
 use mz_sql_parser::ast::visit_mut::{self, VisitMut};
 use mz_sql_parser::ast::{
     Assignment, DeleteStatement, Distinct, Expr, Function, FunctionArgs, HomogenizingFunction,
-    Ident, InsertSource, IsExprConstruct, Join, JoinConstraint, JoinOperator, Limit, OrderByExpr,
-    Query, Select, SelectItem, SetExpr, SetOperator, SubscriptPosition, TableAlias, TableFactor,
-    TableFunction, TableWithJoins, UnresolvedObjectName, UpdateStatement, Value, Values,
+    Ident, InsertSource, IsExprConstruct, Join, JoinConstraint, JoinOperator, Limit, OnConflict,
+    OnConflictAction, OrderByExpr, Query, Select, SelectItem, SetExpr, SetOperator,
+    SubscriptPosition, TableAlias, TableFactor, TableFunction, TableWithJoins,
+    UnresolvedObjectName, UpdateStatement, Value, Values,
 };
 
 use crate::catalog::{CatalogItemType, CatalogType, SessionCatalog};
-use crate::func::{self, Func, FuncSpec};
+use crate::func::{self, Func, FuncSpec, SqlFunc, SqlFuncBody};
 use crate::names::{Aug, PartialObjectName, ResolvedDataType, ResolvedObjectName};
 use crate::normalize;
 use crate::plan::error::PlanError;
@@ -489,6 +490,160 @@ pub fn plan_update_query(
     )
 }
 
+/// The `ON CONFLICT` clause of an `INSERT`.
+pub struct OnConflictPlan {
+    /// The unique keys on which inserted rows conflict with existing rows.
+    pub keys: Vec<Vec<usize>>,
+    pub resolution: ConflictResolution,
+}
+
+pub enum ConflictResolution {
+    DoNothing,
+    /// Expressions over the columns of the existing row followed by the
+    /// columns of the inserted row, which is named `excluded`.
+    DoUpdate {
+        /// Map from column index to SET expression.
+        assignments: HashMap<usize, HirScalarExpr>,
+        /// WHERE filter.
+        selection: Option<HirScalarExpr>,
+    },
+}
+
+pub fn plan_on_conflict(
+    scx: &StatementContext,
+    table_name: ResolvedObjectName,
+    mut on_conflict: OnConflict<Aug>,
+) -> Result<OnConflictPlan, PlanError> {
+    transform_ast::run_transforms(
+        scx,
+        |t, on_conflict| t.visit_on_conflict_mut(on_conflict),
+        &mut on_conflict,
+    )?;
+
+    let qcx = QueryContext::root(scx, QueryLifetime::OneShot(scx.pcx()?));
+    let table = scx.get_item_by_resolved_name(&table_name)?;
+    let desc = table.desc(&scx.catalog.resolve_full_name(table.name()))?;
+    let unique_constraints = table
+        .table_unique_constraints()
+        .expect("attempted to insert into non-table");
+
+    // Without a conflict target, rows conflict on any unique constraint.
+    // Otherwise, the target must name exactly the columns of one of them.
+    // Either way, there must be some constraint to conflict on.
+    let keys = if on_conflict.columns.is_empty() {
+        if let OnConflictAction::DoUpdate { .. } = on_conflict.action {
+            sql_bail!("ON CONFLICT DO UPDATE requires inference specification");
+        }
+        if unique_constraints.is_empty() {
+            sql_bail!(
+                "there is no unique or exclusion constraint matching the ON CONFLICT specification"
+            );
+        }
+        unique_constraints
+            .iter()
+            .map(|constraint| constraint.columns.clone())
+            .collect()
+    } else {
+        let mut target = BTreeSet::new();
+        for column in on_conflict.columns {
+            let name = normalize::column_name(column);
+            match desc.get_by_name(&name) {
+                Some((idx, _)) => {
+                    target.insert(idx);
+                }
+                None => sql_bail!(
+                    "column {} of relation {} does not exist",
+                    name.as_str().quoted(),
+                    table_name.full_name_str().quoted()
+                ),
+            }
+        }
+        match unique_constraints.iter().find(|constraint| {
+            constraint.columns.iter().copied().collect::<BTreeSet<_>>() == target
+        }) {
+            Some(constraint) => vec![constraint.columns.clone()],
+            None => sql_bail!(
+                "there is no unique or exclusion constraint matching the ON CONFLICT specification"
+            ),
+        }
+    };
+
+    let resolution = match on_conflict.action {
+        OnConflictAction::DoNothing => ConflictResolution::DoNothing,
+        OnConflictAction::DoUpdate {
+            assignments,
+            selection,
+        } => {
+            let (_, scope) = qcx.resolve_table_name(table_name)?;
+            let excluded = Scope::from_source(
+                Some(PartialObjectName {
+                    database: None,
+                    schema: None,
+                    item: "excluded".into(),
+                }),
+                desc.iter_names(),
+            );
+            let scope = scope.product(excluded)?;
+            let relation_type = RelationType::new(
+                desc.iter_types()
+                    .chain(desc.iter_types())
+                    .cloned()
+                    .collect(),
+            );
+
+            let selection = match selection {
+                Some(expr) => {
+                    let ecx = &ExprContext {
+                        qcx: &qcx,
+                        name: "WHERE clause",
+                        scope: &scope,
+                        relation_type: &relation_type,
+                        allow_aggregates: false,
+                        allow_subqueries: false,
+                        allow_windows: false,
+                    };
+                    Some(plan_expr(&ecx, &expr)?.type_as(&ecx, &ScalarType::Bool)?)
+                }
+                None => None,
+            };
+
+            let mut sets = HashMap::new();
+            for Assignment { id, value } in assignments {
+                let name = normalize::column_name(id);
+                match desc.get_by_name(&name) {
+                    Some((idx, typ)) => {
+                        let ecx = &ExprContext {
+                            qcx: &qcx,
+                            name: "SET clause",
+                            scope: &scope,
+                            relation_type: &relation_type,
+                            allow_aggregates: false,
+                            allow_subqueries: false,
+                            allow_windows: false,
+                        };
+                        let expr = plan_expr(&ecx, &value)?.cast_to(
+                            ecx,
+                            CastContext::Assignment,
+                            &typ.scalar_type,
+                        )?;
+                        if sets.insert(idx, expr).is_some() {
+                            sql_bail!("column {} set twice", name)
+                        }
+                    }
+                    None => sql_bail!("unknown column {}", name),
+                }
+            }
+
+            ConflictResolution::DoUpdate {
+                assignments: sets,
+                selection,
+            }
+        }
+    };
+
+    Ok(OnConflictPlan { keys, resolution })
+}
+
 pub fn plan_mutation_query_inner(
     qcx: QueryContext,
     table_name: ResolvedObjectName,
@@ -892,6 +1047,290 @@ pub fn plan_index_exprs<'a>(
     Ok(out)
 }
 
+/// Plans the expression of a `CHECK` constraint on a table with the given
+/// description.
+pub fn plan_check_expr(
+    scx: &StatementContext,
+    desc: &RelationDesc,
+    mut expr: Expr<Aug>,
+) -> Result<MirScalarExpr, PlanError> {
+    let scope = Scope::from_source(None, desc.iter_names());
+    let qcx = QueryContext::root(scx, QueryLifetime::Static);
+    let ecx = &ExprContext {
+        qcx: &qcx,
+        name: "CHECK constraint",
+        scope: &scope,
+        relation_type: desc.typ(),
+        allow_aggregates: false,
+        allow_subqueries: false,
+        allow_windows: false,
+    };
+    transform_ast::transform_expr(scx, &mut expr)?;
+    let expr = plan_expr(ecx, &expr)?
+        .type_as(ecx, &ScalarType::Bool)?
+        .lower_uncorrelated()?;
+    if expr.contains_unmaterializable() {
+        sql_bail!("CHECK constraint cannot refer to the current time or session");
+    }
+    Ok(expr)
+}
+
+/// Plans the `WHEN` predicate of a notification on a view with the given
+/// description.
+pub fn plan_notification_predicate(
+    scx: &StatementContext,
+    desc: &RelationDesc,
+    mut expr: Expr<Aug>,
+) -> Result<MirScalarExpr, PlanError> {
+    let scope = Scope::from_source(None, desc.iter_names());
+    let qcx = QueryContext::root(scx, QueryLifetime::Static);
+    let ecx = &ExprContext {
+        qcx: &qcx,
+        name: "CREATE NOTIFICATION",
+        scope: &scope,
+        relation_type: desc.typ(),
+        allow_aggregates: false,
+        allow_subqueries: false,
+        allow_windows: false,
+    };
+    transform_ast::transform_expr(scx, &mut expr)?;
+    let expr = plan_expr(ecx, &expr)?
+        .type_as(ecx, &ScalarType::Bool)?
+        .lower_uncorrelated()?;
+    if expr.contains_unmaterializable() {
+        sql_bail!("notification predicate cannot refer to the current time or session");
+    }
+    Ok(expr)
+}
+
+/// Plans the body of a function created by `CREATE FUNCTION` that returns a
+/// single value of type `return_type`.
+///
+/// The parameters of the function, described by `params`, may be referenced in
+/// the body by name or by position, as in `$1`. Both kinds of references are
+/// planned as [`HirScalarExpr::Parameter`]s.
+pub fn plan_sql_function_expr(
+    scx: &StatementContext,
+    params: &RelationDesc,
+    mut expr: Expr<Aug>,
+    return_type: &ScalarType,
+) -> Result<HirScalarExpr, PlanError> {
+    let scx = sql_function_statement_context(scx, params);
+    let qcx = sql_function_query_context(&scx, params);
+    let ecx = &ExprContext {
+        qcx: &qcx,
+        name: "function body",
+        scope: &Scope::empty(),
+        relation_type: &RelationType::empty(),
+        allow_aggregates: false,
+        allow_subqueries: true,
+        allow_windows: false,
+    };
+    transform_ast::transform_expr(&scx, &mut expr)?;
+    let expr = typeconv::plan_coerce(ecx, plan_expr(ecx, &expr)?, return_type)?;
+    let expr_type = ecx.scalar_type(&expr);
+    let mut expr =
+        typeconv::plan_cast(ecx, CastContext::Assignment, expr, return_type).map_err(|_| {
+            PlanError::Unstructured(format!(
+                "return type mismatch in function declared to return {}: body is of type {}",
+                ecx.humanize_scalar_type(return_type),
+                ecx.humanize_scalar_type(&expr_type),
+            ))
+        })?;
+    check_sql_function_parameters(&scx, params)?;
+    let _ = expr.visit_recursively_mut(0, &mut parameterize_sql_function_column);
+    Ok(expr)
+}
+
+/// Like [`plan_sql_function_expr`], but plans the body of a function that
+/// returns a set of rows described by `desc`.
+pub fn plan_sql_function_query(
+    scx: &StatementContext,
+    params: &RelationDesc,
+    mut query: Query<Aug>,
+    desc: &RelationDesc,
+) -> Result<HirRelationExpr, PlanError> {
+    let scx = sql_function_statement_context(scx, params);
+    let mut qcx = sql_function_query_context(&scx, params);
+    transform_ast::transform_query(&scx, &mut query)?;
+    let (expr, _scope) = plan_nested_query(&mut qcx, &query)?;
+    let arity = qcx.relation_type(&expr).arity();
+    if arity != desc.arity() {
+        sql_bail!(
+            "return type mismatch in function declared to return {} columns: body returns {} columns",
+            desc.arity(),
+            arity,
+        );
+    }
+    let target_types = desc.iter_types().map(|typ| &typ.scalar_type);
+    let mut expr =
+        cast_relation(&qcx, CastContext::Assignment, expr, target_types).map_err(|e| {
+            PlanError::Unstructured(format!(
+                "return type mismatch in function: column {} is of type {} but body returns type {}",
+                desc.get_name(e.column).as_str().quoted(),
+                qcx.humanize_scalar_type(&e.target_type),
+                qcx.humanize_scalar_type(&e.source_type),
+            ))
+        })?;
+    check_sql_function_parameters(&scx, params)?;
+    let _ = expr.visit_scalar_expressions_mut(0, &mut |e: &mut HirScalarExpr, depth| {
+        e.visit_recursively_mut(depth, &mut parameterize_sql_function_column)
+    });
+    Ok(expr)
+}
+
+/// Derives a `StatementContext` for planning the body of a function in which
+/// the types of `$1`, `$2`, etc. are bound to the types of the function's
+/// parameters.
+fn sql_function_statement_context<'a>(
+    scx: &StatementContext<'a>,
+    params: &RelationDesc,
+) -> StatementContext<'a> {
+    let mut scx = scx.clone();
+    scx.param_types = RefCell::new(
+        params
+            .iter_types()
+            .enumerate()
+            .map(|(i, typ)| (i + 1, typ.scalar_type.clone()))
+            .collect(),
+    );
+    scx
+}
+
+/// Constructs a `QueryContext` for planning the body of a function, in which
+/// the parameters of the function are the columns of the outermost scope.
+fn sql_function_query_context<'a>(
+    scx: &'a StatementContext,
+    params: &RelationDesc,
+) -> QueryContext<'a> {
+    let scope = Scope::from_source(None, params.iter_names().cloned());
+    QueryContext::root(scx, QueryLifetime::Static).derived_context(scope, params.typ().clone())
+}
+
+/// Ensures that the body of a function refers to no parameters beyond those
+/// declared by the function.
+fn check_sql_function_parameters(
+    scx: &StatementContext,
+    params: &RelationDesc,
+) -> Result<(), PlanError> {
+    if let Some(n) = scx
+        .param_types
+        .borrow()
+        .keys()
+        .find(|n| **n > params.arity())
+    {
+        sql_bail!("there is no parameter ${}", n);
+    }
+    Ok(())
+}
+
+/// Replaces a reference to a column of the outermost scope, i.e., a parameter
+/// of a function, with the corresponding [`HirScalarExpr::Parameter`].
+fn parameterize_sql_function_column(depth: usize, e: &mut HirScalarExpr) -> Result<(), ()> {
+    if let HirScalarExpr::Column(col) = e {
+        if col.level == depth + 1 {
+            let column = col.column;
+            *e = HirScalarExpr::Parameter(column + 1);
+        }
+    }
+    Ok(())
+}
+
+/// Resolves `name` to a function created by `CREATE FUNCTION`, if it refers to
+/// one.
+fn resolve_sql_func(ecx: &ExprContext, name: &UnresolvedObjectName) -> Option<SqlFunc> {
+    let item = ecx.qcx.scx.resolve_function(name.clone()).ok()?;
+    item.sql_func().cloned()
+}
+
+/// Plans the arguments to a call of a function created by `CREATE FUNCTION`,
+/// casting each argument to the type of the corresponding parameter.
+fn plan_sql_func_args(
+    ecx: &ExprContext,
+    name: &UnresolvedObjectName,
+    params: &[ScalarType],
+    args: Vec<CoercibleScalarExpr>,
+) -> Result<Vec<HirScalarExpr>, PlanError> {
+    let does_not_exist = |args: &[CoercibleScalarExpr]| {
+        let types: Vec<_> = args
+            .iter()
+            .map(|e| match ecx.scalar_type(e) {
+                Some(ty) => ecx.humanize_scalar_type(&ty),
+                None => "unknown".to_string(),
+            })
+            .collect();
+        PlanError::Unstructured(format!(
+            "function {}({}) does not exist",
+            name,
+            types.join(", ")
+        ))
+    };
+    if args.len() != params.len() {
+        return Err(does_not_exist(&args));
+    }
+    let mut out = Vec::with_capacity(args.len());
+    for (arg, ty) in args.iter().zip(params) {
+        match arg.clone().cast_to(ecx, CastContext::Implicit, ty) {
+            Ok(arg) => out.push(arg),
+            Err(_) => return Err(does_not_exist(&args)),
+        }
+    }
+    Ok(out)
+}
+
+/// Plans a call to a function created by `CREATE FUNCTION` that returns a
+/// single value by inlining the body of the function.
+fn plan_sql_function_call(
+    ecx: &ExprContext,
+    Function {
+        name,
+        args,
+        filter,
+        over,
+        distinct,
+    }: &Function<Aug>,
+    func: SqlFunc,
+) -> Result<HirScalarExpr, PlanError> {
+    let mut expr = match func.body {
+        SqlFuncBody::Scalar { expr, .. } => expr,
+        SqlFuncBody::Table { .. } => {
+            sql_bail!("table functions are not allowed in {}", ecx.name)
+        }
+    };
+    if over.is_some() {
+        bail_unsupported!(213, "window functions");
+    }
+    if *distinct {
+        sql_bail!(
+            "DISTINCT specified, but {} is not an aggregate function",
+            name
+        );
+    }
+    if filter.is_some() {
+        sql_bail!(
+            "FILTER specified, but {} is not an aggregate function",
+            name
+        );
+    }
+    let args = match args {
+        FunctionArgs::Star => {
+            sql_bail!("* argument is invalid with non-aggregate function {}", name)
+        }
+        FunctionArgs::Args { args, order_by } => {
+            if !order_by.is_empty() {
+                sql_bail!(
+                    "ORDER BY specified, but {} is not an aggregate function",
+                    name
+                );
+            }
+            plan_exprs(ecx, args)?
+        }
+    };
+    let args = plan_sql_func_args(ecx, name, &func.params, args)?;
+    expr.splice_parameters(&args, 0);
+    Ok(expr)
+}
+
 fn plan_expr_or_col_index(ecx: &ExprContext, e: &Expr<Aug>) -> Result<HirScalarExpr, PlanError> {
     match check_col_index(&ecx.name, e, ecx.relation_type.column_types.len())? {
         Some(column) => Ok(HirScalarExpr::column(column)),
@@ -2323,19 +2762,31 @@ fn plan_table_function_internal(
         item: table_name,
     });
 
-    let (mut expr, mut scope) = match resolve_func(ecx, name, args)? {
-        Func::Table(impls) => {
-            let tf = func::select_impl(
-                ecx,
-                FuncSpec::Func(&resolved_name),
-                impls,
-                scalar_args,
-                vec![],
-            )?;
-            let scope = Scope::from_source(scope_name.clone(), tf.column_names);
-            (tf.expr, scope)
+    let (mut expr, mut scope) = match resolve_sql_func(ecx, name) {
+        Some(SqlFunc {
+            params,
+            body: SqlFuncBody::Table { mut expr, desc },
+        }) => {
+            let args = plan_sql_func_args(ecx, name, &params, scalar_args)?;
+            expr.splice_parameters(&args, 0);
+            let scope = Scope::from_source(scope_name.clone(), desc.iter_names().cloned());
+            (expr, scope)
         }
-        _ => sql_bail!("{} is not a table function", name),
+        Some(_) => sql_bail!("{} is not a table function", name),
+        None => match resolve_func(ecx, name, args)? {
+            Func::Table(impls) => {
+                let tf = func::select_impl(
+                    ecx,
+                    FuncSpec::Func(&resolved_name),
+                    impls,
+                    scalar_args,
+                    vec![],
+                )?;
+                let scope = Scope::from_source(scope_name.clone(), tf.column_names);
+                (tf.expr, scope)
+            }
+            _ => sql_bail!("{} is not a table function", name),
+        },
     };
 
     if with_ordinality {
@@ -3955,7 +4406,7 @@ fn plan_op(
 
 fn plan_function<'a>(
     ecx: &ExprContext,
-    Function {
+    function @ Function {
         name,
         args,
         filter,
@@ -3965,6 +4416,10 @@ fn plan_function<'a>(
 ) -> Result<HirScalarExpr, PlanError> {
     let unresolved_name = normalize::unresolved_object_name(name.clone())?;
 
+    if let Some(func) = resolve_sql_func(ecx, name) {
+        return plan_sql_function_call(ecx, function, func);
+    }
+
     let impls = match resolve_func(ecx, name, args)? {
         Func::Aggregate(_) if ecx.allow_aggregates => {
             // should already have been caught by `scope.resolve_expr` in `plan_expr`
@@ -4576,7 +5031,9 @@ impl<'a> VisitMut<'_, Aug> for AggregateTableFuncVisitor<'a> {
                 // and save the table func so it can be planned elsewhere.
                 let mut table_func = None;
                 if let Ok(item) = self.scx.resolve_function(func.name.clone()) {
-                    if let Ok(Func::Table { .. }) = item.func() {
+                    let is_table_func = matches!(item.func(), Ok(Func::Table { .. }))
+                        || matches!(item.sql_func(), Some(func) if func.returns_set());
+                    if is_table_func {
                         if let Some(context) = self.table_disallowed_context.last() {
                             self.err = Some(PlanError::Unstructured(format!(
                                 "table functions are not allowed in {}",
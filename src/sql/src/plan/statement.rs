@@ -20,7 +20,7 @@ use mz_expr::GlobalId;
 use mz_repr::{ColumnType, RelationDesc, ScalarType};
 use mz_sql_parser::ast::{RawObjectName, UnresolvedDatabaseName, UnresolvedSchemaName};
 
-use crate::ast::{Ident, ObjectType, Raw, Statement, UnresolvedObjectName};
+use crate::ast::{Ident, ObjectType, Raw, SelectStatement, Statement, UnresolvedObjectName};
 use crate::catalog::{
     CatalogComputeInstance, CatalogDatabase, CatalogItem, CatalogItemType, CatalogSchema,
     SessionCatalog,
@@ -32,7 +32,7 @@ use crate::names::{
 };
 use crate::plan::error::PlanError;
 use crate::plan::query;
-use crate::plan::{Params, Plan, PlanContext};
+use crate::plan::{Params, Plan, PlanContext, UnboundPeekPlan};
 use crate::{normalize, DEFAULT_SCHEMA};
 
 mod ddl;
@@ -120,6 +120,8 @@ pub fn describe(
         Statement::CreateRole(stmt) => Some(ddl::describe_create_role(&scx, stmt)?),
         Statement::CreateCluster(stmt) => Some(ddl::describe_create_cluster(&scx, stmt)?),
         Statement::CreateSecret(stmt) => Some(ddl::describe_create_secret(&scx, stmt)?),
+        Statement::CreateNotification(stmt) => Some(ddl::describe_create_notification(&scx, stmt)?),
+        Statement::CreateFunction(stmt) => Some(ddl::describe_create_function(&scx, stmt)?),
         Statement::DropDatabase(stmt) => Some(ddl::describe_drop_database(&scx, stmt)?),
         Statement::DropSchema(stmt) => Some(ddl::describe_drop_schema(&scx, stmt)?),
         Statement::DropObjects(stmt) => Some(ddl::describe_drop_objects(&scx, stmt)?),
@@ -127,6 +129,7 @@ pub fn describe(
         Statement::DropClusters(stmt) => Some(ddl::describe_drop_cluster(&scx, stmt)?),
         Statement::AlterObjectRename(stmt) => Some(ddl::describe_alter_object_rename(&scx, stmt)?),
         Statement::AlterIndex(stmt) => Some(ddl::describe_alter_index_options(&scx, stmt)?),
+        Statement::AlterSource(stmt) => Some(ddl::describe_alter_source(&scx, stmt)?),
         Statement::AlterSecret(stmt) => Some(ddl::describe_alter_secret_options(&scx, stmt)?),
         Statement::AlterCluster(stmt) => Some(ddl::describe_alter_cluster(&scx, stmt)?),
 
@@ -152,6 +155,8 @@ pub fn describe(
         Statement::Prepare(stmt) => Some(scl::describe_prepare(&scx, stmt)?),
         Statement::Execute(_) => None,
         Statement::Deallocate(stmt) => Some(scl::describe_deallocate(&scx, stmt)?),
+        Statement::Listen(stmt) => Some(scl::describe_listen(&scx, stmt)?),
+        Statement::Unlisten(stmt) => Some(scl::describe_unlisten(&scx, stmt)?),
 
         // DML statements.
         Statement::Insert(_) => None,
@@ -317,6 +322,14 @@ pub fn plan(
             let (stmt, _) = resolve_stmt!(Statement::CreateSecret, scx, stmt);
             ddl::plan_create_secret(scx, stmt)
         }
+        stmt @ Statement::CreateNotification(_) => {
+            let (stmt, depends_on) = resolve_stmt!(Statement::CreateNotification, scx, stmt);
+            ddl::plan_create_notification(scx, stmt, depends_on)
+        }
+        stmt @ Statement::CreateFunction(_) => {
+            let (stmt, depends_on) = resolve_stmt!(Statement::CreateFunction, scx, stmt);
+            ddl::plan_create_function(scx, stmt, depends_on)
+        }
         Statement::DropDatabase(stmt) => ddl::plan_drop_database(scx, stmt),
         Statement::DropSchema(stmt) => ddl::plan_drop_schema(scx, stmt),
         Statement::DropObjects(stmt) => ddl::plan_drop_objects(scx, stmt),
@@ -332,6 +345,10 @@ pub fn plan(
             let (stmt, _) = resolve_stmt!(Statement::AlterIndex, scx, stmt);
             ddl::plan_alter_index_options(scx, stmt)
         }
+        stmt @ Statement::AlterSource(_) => {
+            let (stmt, _) = resolve_stmt!(Statement::AlterSource, scx, stmt);
+            ddl::plan_alter_source(scx, stmt)
+        }
         Statement::AlterObjectRename(stmt) => ddl::plan_alter_object_rename(scx, stmt),
 
         stmt @ Statement::AlterSecret(_) => {
@@ -446,6 +463,8 @@ pub fn plan(
             let (stmt, _) = resolve_stmt!(Statement::Deallocate, scx, stmt);
             scl::plan_deallocate(scx, stmt)
         }
+        Statement::Listen(stmt) => scl::plan_listen(scx, stmt),
+        Statement::Unlisten(stmt) => scl::plan_unlisten(scx, stmt),
 
         // TCL statements.
         stmt @ Statement::StartTransaction(_) => {
@@ -473,6 +492,31 @@ pub fn plan(
     }
 }
 
+/// Plans a `SELECT` statement like [`plan`], but without binding its
+/// parameters, which have the types `param_types`.
+pub fn plan_select_unbound(
+    pcx: &PlanContext,
+    catalog: &dyn SessionCatalog,
+    stmt: SelectStatement<Raw>,
+    param_types: &[ScalarType],
+) -> Result<UnboundPeekPlan, anyhow::Error> {
+    let param_types = param_types
+        .iter()
+        .enumerate()
+        .map(|(i, ty)| (i + 1, ty.clone()))
+        .collect();
+    let scx = &mut StatementContext {
+        pcx: Some(pcx),
+        catalog,
+        param_types: RefCell::new(param_types),
+    };
+    let stmt = match resolve_names_stmt(scx, Statement::Select(stmt))?.0 {
+        Statement::Select(stmt) => stmt,
+        stmt => panic!("name resolution turned a SELECT statement into {:?}", stmt),
+    };
+    dml::plan_select_unbound(scx, stmt)
+}
+
 pub fn plan_copy_from(
     pcx: &PlanContext,
     catalog: &dyn SessionCatalog,
@@ -498,7 +542,9 @@ impl PartialEq<ObjectType> for CatalogItemType {
             | (CatalogItemType::View, ObjectType::View)
             | (CatalogItemType::Index, ObjectType::Index)
             | (CatalogItemType::Type, ObjectType::Type)
-            | (CatalogItemType::Secret, ObjectType::Secret) => true,
+            | (CatalogItemType::Secret, ObjectType::Secret)
+            | (CatalogItemType::Notification, ObjectType::Notification)
+            | (CatalogItemType::Func, ObjectType::Func) => true,
             (_, _) => false,
         }
     }
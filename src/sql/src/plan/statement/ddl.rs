@@ -31,19 +31,20 @@ use tracing::{debug, warn};
 
 use mz_dataflow_types::postgres_source::PostgresSourceDetails;
 use mz_dataflow_types::sinks::{
-    AvroOcfSinkConnectorBuilder, KafkaSinkConnectorBuilder, KafkaSinkConnectorRetention,
-    KafkaSinkFormat, SinkConnectorBuilder, SinkEnvelope,
+    AvroOcfCodec, AvroOcfRollover, AvroOcfSinkConnectorBuilder, KafkaSinkConnectorBuilder,
+    KafkaSinkConnectorRetention, KafkaSinkFormat, SinkConnectorBuilder, SinkEnvelope,
 };
 use mz_dataflow_types::sources::encoding::{
     included_column_desc, AvroEncoding, AvroOcfEncoding, ColumnSpec, CsvEncoding, DataEncoding,
-    ProtobufEncoding, RegexEncoding, SourceDataEncoding,
+    JsonEncoding, OrcEncoding, ParquetEncoding, ProtobufEncoding, RegexEncoding,
+    SourceDataEncoding,
 };
 use mz_dataflow_types::sources::{
     provide_default_metadata, DebeziumDedupProjection, DebeziumEnvelope, DebeziumMode,
-    DebeziumSourceProjection, ExternalSourceConnector, FileSourceConnector, IncludedColumnPos,
-    KafkaSourceConnector, KeyEnvelope, KinesisSourceConnector, PostgresSourceConnector,
-    PubNubSourceConnector, S3SourceConnector, SourceConnector, SourceEnvelope, Timeline,
-    UnplannedSourceEnvelope, UpsertStyle,
+    DebeziumSourceProjection, DecodeErrorPolicy, ExternalSourceConnector, FileSourceConnector,
+    IncludedColumnPos, KafkaSourceConnector, KeyEnvelope, KinesisSourceConnector,
+    PostgresSourceConnector, PubNubSourceConnector, S3SourceConnector, SourceConnector,
+    SourceEnvelope, Timeline, UnplannedSourceEnvelope, UpsertStyle,
 };
 use mz_expr::{CollectionPlan, GlobalId};
 use mz_interchange::avro::{self, AvroSchemaGenerator};
@@ -56,21 +57,24 @@ use crate::ast::display::AstDisplay;
 use crate::ast::visit::Visit;
 use crate::ast::{
     AlterClusterStatement, AlterIndexAction, AlterIndexStatement, AlterObjectRenameStatement,
-    AlterSecretStatement, AstInfo, AvroSchema, ClusterOption, ColumnOption, Compression,
-    CreateClusterStatement, CreateDatabaseStatement, CreateIndexStatement, CreateRoleOption,
+    AlterSecretStatement, AlterSourceAction, AlterSourceStatement, AstInfo, AvroSchema,
+    ClusterOption, ColumnOption, Compression, CreateClusterStatement, CreateDatabaseStatement,
+    CreateFunctionStatement, CreateIndexStatement, CreateNotificationStatement, CreateRoleOption,
     CreateRoleStatement, CreateSchemaStatement, CreateSecretStatement, CreateSinkConnector,
     CreateSinkStatement, CreateSourceConnector, CreateSourceFormat, CreateSourceStatement,
     CreateTableStatement, CreateTypeAs, CreateTypeStatement, CreateViewStatement,
     CreateViewsDefinitions, CreateViewsSourceTarget, CreateViewsStatement, CsrConnectorAvro,
-    CsrConnectorProto, CsrSeedCompiled, CsrSeedCompiledOrLegacy, CsvColumns, DbzMode,
-    DropClustersStatement, DropDatabaseStatement, DropObjectsStatement, DropRolesStatement,
-    DropSchemaStatement, Envelope, Expr, Format, Ident, IfExistsBehavior, KafkaConsistency,
-    KeyConstraint, ObjectType, Op, ProtobufSchema, Query, Raw, Select, SelectItem, SetExpr,
-    SourceIncludeMetadata, SourceIncludeMetadataType, SqlOption, Statement, SubscriptPosition,
-    TableConstraint, TableFactor, TableWithJoins, UnresolvedDatabaseName, UnresolvedObjectName,
-    Value, ViewDefinition, WithOption,
+    CsrConnectorProto, CsrSeedCompiled, CsrSeedCompiledOrLegacy, CsvColumns, Cte, DbzMode,
+    DecodeErrorAction, DropClustersStatement, DropDatabaseStatement, DropObjectsStatement,
+    DropRolesStatement, DropSchemaStatement, Envelope, Expr, Format, FunctionBody, FunctionReturns,
+    Ident, IfExistsBehavior, JsonSchema, KafkaConsistency, KeyConstraint, ObjectType, Op,
+    ProtobufSchema, Query, Raw, RawObjectName, Select, SelectItem, SetExpr, SourceIncludeMetadata,
+    SourceIncludeMetadataType, SqlOption, Statement, SubscriptPosition, TableConstraint,
+    TableFactor, TableWithJoins, UnresolvedDatabaseName, UnresolvedObjectName, Value,
+    ViewDefinition, WithOption,
 };
 use crate::catalog::{CatalogItem, CatalogItemType, CatalogType, CatalogTypeDetails};
+use crate::func::{SqlFunc, SqlFuncBody};
 use crate::kafka_util;
 use crate::names::{
     resolve_names_data_type, resolve_object_name, Aug, FullSchemaName, QualifiedObjectName,
@@ -84,12 +88,14 @@ use crate::plan::query::QueryLifetime;
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{
     plan_utils, query, AlterComputeInstancePlan, AlterIndexEnablePlan, AlterIndexResetOptionsPlan,
-    AlterIndexSetOptionsPlan, AlterItemRenamePlan, AlterNoopPlan, ComputeInstanceConfig,
-    ComputeInstanceIntrospectionConfig, CreateComputeInstancePlan, CreateDatabasePlan,
-    CreateIndexPlan, CreateRolePlan, CreateSchemaPlan, CreateSecretPlan, CreateSinkPlan,
+    AlterIndexSetOptionsPlan, AlterItemRenamePlan, AlterNoopPlan, AlterSourceRefreshSchemaPlan,
+    CheckConstraint, ComputeInstanceConfig, ComputeInstanceIntrospectionConfig,
+    CreateComputeInstancePlan, CreateDatabasePlan, CreateFunctionPlan, CreateIndexPlan,
+    CreateNotificationPlan, CreateRolePlan, CreateSchemaPlan, CreateSecretPlan, CreateSinkPlan,
     CreateSourcePlan, CreateTablePlan, CreateTypePlan, CreateViewPlan, CreateViewsPlan,
     DropComputeInstancesPlan, DropDatabasePlan, DropItemsPlan, DropRolesPlan, DropSchemaPlan,
-    Index, IndexOption, IndexOptionName, Params, Plan, Secret, Sink, Source, Table, Type, View,
+    Function, Index, IndexOption, IndexOptionName, Notification, Params, Plan, Secret, Sink,
+    Source, Table, Type, UniqueConstraint, View,
 };
 use crate::pure::Schema;
 
@@ -190,7 +196,10 @@ pub fn plan_create_table(
     // and NOT NULL constraints.
     let mut column_types = Vec::with_capacity(columns.len());
     let mut defaults = Vec::with_capacity(columns.len());
-    let mut keys = Vec::new();
+    // (name, columns, is_primary)
+    let mut unique_constraints = Vec::new();
+    // (name, column, expr)
+    let mut check_constraints = Vec::new();
 
     for (i, c) in columns.into_iter().enumerate() {
         let aug_data_type = &c.data_type;
@@ -198,6 +207,10 @@ pub fn plan_create_table(
         let mut nullable = true;
         let mut default = Expr::null();
         for option in &c.options {
+            let constraint_name = option
+                .name
+                .as_ref()
+                .map(|name| normalize::ident(name.clone()));
             match &option.option {
                 ColumnOption::NotNull => nullable = false,
                 ColumnOption::Default(expr) => {
@@ -207,11 +220,14 @@ pub fn plan_create_table(
                     default = expr.clone();
                 }
                 ColumnOption::Unique { is_primary } => {
-                    keys.push(vec![i]);
+                    unique_constraints.push((constraint_name, vec![i], *is_primary));
                     if *is_primary {
                         nullable = false;
                     }
                 }
+                ColumnOption::Check(expr) => {
+                    check_constraints.push((constraint_name, Some(i), expr.clone()));
+                }
                 other => {
                     bail_unsupported!(format!("CREATE TABLE with column constraint: {}", other))
                 }
@@ -224,7 +240,7 @@ pub fn plan_create_table(
     for constraint in constraints {
         match constraint {
             TableConstraint::Unique {
-                name: _,
+                name,
                 columns,
                 is_primary,
             } => {
@@ -241,27 +257,52 @@ pub fn plan_create_table(
                         }
                     }
                 }
-                keys.push(key);
+                let name = name.as_ref().map(|name| normalize::ident(name.clone()));
+                unique_constraints.push((name, key, *is_primary));
             }
             TableConstraint::ForeignKey { .. } => {
                 // Foreign key constraints are not presently enforced. We allow
                 // them in experimental mode for sqllogictest's sake.
                 scx.require_experimental_mode("CREATE TABLE with a foreign key")?
             }
-            TableConstraint::Check { .. } => {
-                // Check constraints are not presently enforced. We allow them
-                // in experimental mode for sqllogictest's sake.
-                scx.require_experimental_mode("CREATE TABLE with a check constraint")?
+            TableConstraint::Check { name, expr } => {
+                let name = name.as_ref().map(|name| normalize::ident(name.clone()));
+                check_constraints.push((name, None, (**expr).clone()));
             }
         }
     }
 
-    if !keys.is_empty() {
-        // Unique constraints are not presently enforced. We allow them in
-        // experimental mode for sqllogictest's sake.
+    // Primary keys and unique and check constraints are enforced, but tables
+    // with them could be created in experimental mode before they were, so
+    // they remain experimental until such tables can no longer exist.
+    if !unique_constraints.is_empty() {
         scx.require_experimental_mode("CREATE TABLE with a primary key or unique constraint")?;
     }
+    if !check_constraints.is_empty() {
+        scx.require_experimental_mode("CREATE TABLE with a check constraint")?;
+    }
+
+    if unique_constraints
+        .iter()
+        .filter(|(_, _, is_primary)| *is_primary)
+        .count()
+        > 1
+    {
+        bail!(
+            "multiple primary keys for table {} are not allowed",
+            name.to_ast_string_stable()
+        );
+    }
 
+    // Unique constraints are enforced by the coordinator on every write, so
+    // the optimizer is free to rely on them. Rows whose key contains a NULL
+    // are exempt from the constraint, so only keys over non-nullable columns
+    // actually identify a row.
+    let keys = unique_constraints
+        .iter()
+        .filter(|(_, key, _)| key.iter().all(|i| !column_types[*i].nullable))
+        .map(|(_, key, _)| key.clone())
+        .collect();
     let typ = RelationType::new(column_types).with_keys(keys);
 
     let temporary = *temporary;
@@ -272,12 +313,76 @@ pub fn plan_create_table(
     };
     let desc = RelationDesc::new(typ, names);
 
+    // Name the constraints the same way PostgreSQL does, so that error
+    // messages about violations look familiar.
+    let name_for_constraint = name.item.clone();
+    let mut constraint_names = HashSet::new();
+    let mut name_constraint = |explicit: Option<String>, default: String| {
+        let name = match explicit {
+            Some(name) => {
+                if !constraint_names.insert(name.clone()) {
+                    bail!(
+                        "constraint {} for relation {} already exists",
+                        name.quoted(),
+                        name_for_constraint.quoted()
+                    );
+                }
+                name
+            }
+            None => {
+                let mut name = default.clone();
+                let mut i = 0;
+                while !constraint_names.insert(name.clone()) {
+                    i += 1;
+                    name = format!("{}{}", default, i);
+                }
+                name
+            }
+        };
+        Ok(name)
+    };
+    let unique_constraints = unique_constraints
+        .into_iter()
+        .map(|(explicit, columns, is_primary)| {
+            let default = if is_primary {
+                format!("{}_pkey", name_for_constraint)
+            } else {
+                let column_names = columns.iter().map(|i| desc.get_name(*i).as_str());
+                format!("{}_{}_key", name_for_constraint, column_names.format("_"))
+            };
+            Ok(UniqueConstraint {
+                name: name_constraint(explicit, default)?,
+                columns,
+                is_primary,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+    let check_constraints = check_constraints
+        .into_iter()
+        .map(|(explicit, column, expr)| {
+            let default = match column {
+                Some(i) => format!(
+                    "{}_{}_check",
+                    name_for_constraint,
+                    desc.get_name(i).as_str()
+                ),
+                None => format!("{}_check", name_for_constraint),
+            };
+            Ok(CheckConstraint {
+                name: name_constraint(explicit, default)?,
+                expr: query::plan_check_expr(scx, &desc, expr)?,
+            })
+        })
+        .collect::<Result<Vec<_>, anyhow::Error>>()?;
+
     let create_sql = normalize::create_statement(&scx, Statement::CreateTable(stmt.clone()))?;
     let depends_on = depends_on.into_iter().collect();
     let table = Table {
         create_sql,
         desc,
         defaults,
+        unique_constraints,
+        check_constraints,
         temporary,
         depends_on,
     };
@@ -305,6 +410,7 @@ pub fn plan_create_source(
         connector,
         with_options,
         envelope,
+        on_decode_error,
         if_not_exists,
         materialized,
         format,
@@ -609,6 +715,28 @@ pub fn plan_create_source(
             (connector, encoding)
         }
     };
+    let columnar_format = match encoding.value_ref() {
+        DataEncoding::Parquet(_) => Some("PARQUET"),
+        DataEncoding::Orc(_) => Some("ORC"),
+        _ => None,
+    };
+    if let Some(format) = columnar_format {
+        let compression = match &external_connector {
+            ExternalSourceConnector::File(FileSourceConnector { tail: true, .. }) => {
+                bail!("FORMAT {} does not support tailing files", format)
+            }
+            ExternalSourceConnector::File(FileSourceConnector { compression, .. })
+            | ExternalSourceConnector::S3(S3SourceConnector { compression, .. }) => compression,
+            _ => bail!(
+                "FORMAT {} is only supported for file and S3 sources",
+                format
+            ),
+        };
+        if *compression != mz_dataflow_types::sources::Compression::None {
+            bail!("FORMAT {} does not support COMPRESSION GZIP", format);
+        }
+    }
+
     let (key_desc, value_desc) = encoding.desc()?;
 
     let key_envelope = get_key_envelope(include_metadata, envelope, &encoding)?;
@@ -758,6 +886,44 @@ pub fn plan_create_source(
         }
     };
 
+    let mut depends_on = vec![];
+    let decode_errors = match on_decode_error {
+        None | Some(DecodeErrorAction::Fail) => DecodeErrorPolicy::Fail,
+        Some(_) if matches!(envelope, UnplannedSourceEnvelope::CdcV2) => {
+            bail_unsupported!("ON DECODE ERROR with ENVELOPE MATERIALIZE")
+        }
+        Some(_)
+            if matches!(
+                connector,
+                CreateSourceConnector::Postgres { .. } | CreateSourceConnector::PubNub { .. }
+            ) =>
+        {
+            bail!("ON DECODE ERROR is only supported for sources with a FORMAT")
+        }
+        Some(DecodeErrorAction::Skip) => DecodeErrorPolicy::Skip,
+        Some(DecodeErrorAction::RedirectToTopic(topic)) => {
+            if !matches!(connector, CreateSourceConnector::Kafka(_)) {
+                bail!("ON DECODE ERROR = REDIRECT TO TOPIC is only supported for Kafka sources");
+            }
+            DecodeErrorPolicy::RedirectToTopic {
+                topic: topic.clone(),
+            }
+        }
+        Some(DecodeErrorAction::RedirectToTable(table_name)) => {
+            if !matches!(
+                connector,
+                CreateSourceConnector::Kafka(_) | CreateSourceConnector::Kinesis { .. }
+            ) {
+                bail!(
+                    "ON DECODE ERROR = REDIRECT TO TABLE is only supported for Kafka and Kinesis sources"
+                );
+            }
+            let id = plan_dead_letter_table(scx, table_name)?;
+            depends_on.push(id);
+            DecodeErrorPolicy::RedirectToTable { id }
+        }
+    };
+
     // TODO(petrosagg): remove this inconsistency once INCLUDE (offset) syntax is implemented
     let include_defaults = provide_default_metadata(&envelope, encoding.value_ref());
     let metadata_columns = external_connector.metadata_columns(include_defaults);
@@ -866,11 +1032,13 @@ pub fn plan_create_source(
             connector: external_connector,
             encoding,
             envelope,
+            decode_errors,
             metadata_columns: metadata_column_types,
             ts_frequency,
             timeline,
         },
         desc,
+        depends_on,
     };
 
     normalize::ensure_empty_options(&with_options, "CREATE SOURCE")?;
@@ -883,6 +1051,60 @@ pub fn plan_create_source(
     }))
 }
 
+/// Validates that messages that fail to decode can be redirected to the named
+/// table, returning the ID of the table.
+///
+/// Messages are appended to the table without reading it, so the table must
+/// not have unique constraints. It must also accept `NULL` wherever a message
+/// may not have a value.
+fn plan_dead_letter_table(
+    scx: &StatementContext,
+    table_name: &ResolvedObjectName,
+) -> Result<GlobalId, anyhow::Error> {
+    let table = scx.get_item_by_resolved_name(table_name)?;
+    if table.item_type() != CatalogItemType::Table {
+        bail!(
+            "cannot redirect messages to {} '{}'",
+            table.item_type(),
+            table_name.full_name_str()
+        );
+    }
+    if table.id().is_system() {
+        bail!(
+            "cannot redirect messages to system table '{}'",
+            table_name.full_name_str()
+        );
+    }
+    if table
+        .table_unique_constraints()
+        .map_or(false, |constraints| !constraints.is_empty())
+    {
+        bail!(
+            "cannot redirect messages to table '{}' with unique constraints",
+            table_name.full_name_str()
+        );
+    }
+    let desc = table.desc(&scx.catalog.resolve_full_name(table.name()))?;
+    let expected = DecodeErrorPolicy::dead_letter_table_type();
+    let matches = desc.arity() == expected.arity()
+        && desc
+            .iter_types()
+            .zip(&expected.column_types)
+            .all(|(actual, expected)| {
+                actual.scalar_type == expected.scalar_type
+                    && (actual.nullable || !expected.nullable)
+            });
+    if !matches {
+        bail!(
+            "cannot redirect messages to table '{}': its columns must have the types \
+            (text, text, bigint, bytea, bytea, text), and the second, fourth and fifth \
+            columns must be nullable",
+            table_name.full_name_str()
+        );
+    }
+    Ok(table.id())
+}
+
 fn typecheck_debezium(value_desc: &RelationDesc) -> Result<(usize, usize), anyhow::Error> {
     let (before_idx, before_ty) = value_desc
         .get_by_name(&"before".into())
@@ -1241,8 +1463,43 @@ fn get_encoding_inner<T: mz_sql_parser::ast::AstInfo>(
                 },
             })
         }
-        Format::Json => bail_unsupported!("JSON sources"),
+        Format::Json { schema } => {
+            let schema = match schema {
+                None => None,
+                Some(JsonSchema::Infer) => {
+                    bail!("[internal error] json schema should be inferred in purify")
+                }
+                Some(JsonSchema::Inline(schema)) => {
+                    // Validate the schema now, rather than when the source is rendered.
+                    mz_interchange::json::JsonSourceSchema::from_json(schema)?;
+                    Some(schema.clone())
+                }
+            };
+            DataEncoding::Json(JsonEncoding { schema })
+        }
         Format::Text => DataEncoding::Text,
+        Format::Parquet { schema } => {
+            let schema = match schema {
+                Some(schema) => schema,
+                None => bail!("[internal error] parquet schema should be inferred in purify"),
+            };
+            // Validate the schema now, rather than when the source is rendered.
+            mz_interchange::parquet::ParquetSchema::from_json(schema)?;
+            DataEncoding::Parquet(ParquetEncoding {
+                schema: schema.clone(),
+            })
+        }
+        Format::Orc { schema } => {
+            let schema = match schema {
+                Some(schema) => schema,
+                None => bail!("[internal error] orc schema should be inferred in purify"),
+            };
+            // Validate the schema now, rather than when the source is rendered.
+            mz_interchange::orc::OrcSchema::from_json(schema)?;
+            DataEncoding::Orc(OrcEncoding {
+                schema: schema.clone(),
+            })
+        }
     }))
 }
 
@@ -1269,12 +1526,18 @@ fn get_key_envelope(
                 //
                 // Otherwise it gets the names of the columns in the type
                 let is_composite = match key {
-                    DataEncoding::AvroOcf { .. } | DataEncoding::Postgres => {
+                    DataEncoding::AvroOcf { .. }
+                    | DataEncoding::Parquet(_)
+                    | DataEncoding::Orc(_)
+                    | DataEncoding::Postgres => {
                         bail!("{} sources cannot use INCLUDE KEY", key.op_name())
                     }
-                    DataEncoding::Bytes | DataEncoding::Text => false,
+                    DataEncoding::Bytes
+                    | DataEncoding::Text
+                    | DataEncoding::Json(JsonEncoding { schema: None }) => false,
                     DataEncoding::Avro(_)
                     | DataEncoding::Csv(_)
+                    | DataEncoding::Json(JsonEncoding { schema: Some(_) })
                     | DataEncoding::Protobuf(_)
                     | DataEncoding::Regex { .. } => true,
                 };
@@ -1720,7 +1983,10 @@ fn kafka_sink_builder(
                 ccsr_config,
             }
         }
-        Some(Format::Json) => KafkaSinkFormat::Json,
+        Some(Format::Json { schema: None }) => KafkaSinkFormat::Json,
+        Some(Format::Json { schema: Some(_) }) => {
+            bail!("FORMAT JSON for sinks does not accept a schema")
+        }
         Some(format) => bail_unsupported!(format!("sink format {:?}", format)),
         None => bail_unsupported!("sink without format"),
     };
@@ -1935,6 +2201,7 @@ fn get_kafka_sink_consistency_config(
 
 fn avro_ocf_sink_builder(
     format: Option<Format<Aug>>,
+    with_options: &mut BTreeMap<String, Value>,
     path: String,
     file_name_suffix: String,
     value_desc: RelationDesc,
@@ -1949,10 +2216,57 @@ fn avro_ocf_sink_builder(
         bail!("avro ocf sink cannot write to a directory");
     }
 
+    let codec = match with_options.remove("codec") {
+        None => AvroOcfCodec::Null,
+        Some(Value::String(s)) => match s.to_lowercase().as_str() {
+            "null" => AvroOcfCodec::Null,
+            "deflate" => AvroOcfCodec::Deflate,
+            "snappy" => AvroOcfCodec::Snappy,
+            "zstandard" | "zstd" => AvroOcfCodec::Zstandard,
+            _ => bail!(
+                "codec for avro ocf sinks must be one of 'null', 'deflate', 'snappy' or 'zstandard'"
+            ),
+        },
+        Some(_) => bail!("codec for avro ocf sinks must be a string"),
+    };
+
+    let block_size = match with_options.remove("block_size") {
+        None => mz_avro::DEFAULT_BLOCK_SIZE,
+        Some(Value::Number(n)) => match n.parse::<usize>() {
+            Ok(n) if n > 0 => n,
+            _ => bail!("block size for avro ocf sinks must be a positive integer"),
+        },
+        Some(_) => bail!("block size for avro ocf sinks must be an integer"),
+    };
+
+    let rollover_bytes = match with_options.remove("rollover_bytes") {
+        None => None,
+        Some(Value::Number(n)) => match n.parse::<u64>() {
+            Ok(n) if n > 0 => Some(n),
+            _ => bail!("rollover bytes for avro ocf sinks must be a positive integer"),
+        },
+        Some(_) => bail!("rollover bytes for avro ocf sinks must be an integer"),
+    };
+
+    let rollover_duration = match with_options.remove("rollover_ms") {
+        None => None,
+        Some(Value::Number(n)) => match n.parse::<u64>() {
+            Ok(n) if n > 0 => Some(Duration::from_millis(n)),
+            _ => bail!("rollover ms for avro ocf sinks must be a positive integer"),
+        },
+        Some(_) => bail!("rollover ms for avro ocf sinks must be an integer"),
+    };
+
     Ok(SinkConnectorBuilder::AvroOcf(AvroOcfSinkConnectorBuilder {
         path,
         file_name_suffix,
         value_desc,
+        codec,
+        block_size,
+        rollover: AvroOcfRollover {
+            bytes: rollover_bytes,
+            duration: rollover_duration,
+        },
     }))
 }
 
@@ -2105,7 +2419,7 @@ pub fn plan_create_sink(
             &root_user_dependencies,
         )?,
         CreateSinkConnector::AvroOcf { path } => {
-            avro_ocf_sink_builder(format, path, suffix_nonce, value_desc)?
+            avro_ocf_sink_builder(format, &mut with_options, path, suffix_nonce, value_desc)?
         }
     };
 
@@ -2188,7 +2502,13 @@ fn get_root_dependencies<'a>(
 
     while let Some(dep) = work_queue.pop() {
         let item = scx.get_item(&dep);
-        let transitive_uses = item.uses().iter().filter(|id| id.is_user());
+        // Sources only use the tables that they redirect messages that fail
+        // to decode to, which are not inputs of the source.
+        let transitive_uses = match item.item_type() {
+            CatalogItemType::Source => &[][..],
+            _ => item.uses(),
+        };
+        let transitive_uses = transitive_uses.iter().filter(|id| id.is_user());
         let mut transitive_uses = transitive_uses.peekable();
         if let Some(_) = transitive_uses.peek() {
             for transitive_dep in transitive_uses {
@@ -2653,6 +2973,169 @@ pub fn plan_create_secret(
     }))
 }
 
+pub fn describe_create_notification(
+    _: &StatementContext,
+    _: &CreateNotificationStatement<Raw>,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_create_notification(
+    scx: &StatementContext,
+    stmt: CreateNotificationStatement<Aug>,
+    depends_on: HashSet<GlobalId>,
+) -> Result<Plan, anyhow::Error> {
+    let CreateNotificationStatement {
+        name,
+        if_not_exists,
+        on_name,
+        predicate,
+    } = &stmt;
+
+    let on = scx.get_item_by_resolved_name(on_name)?;
+    if CatalogItemType::View != on.item_type()
+        && CatalogItemType::Source != on.item_type()
+        && CatalogItemType::Table != on.item_type()
+    {
+        bail!(
+            "notification cannot be created on {} because it is a {}",
+            on_name.full_name_str(),
+            on.item_type()
+        )
+    }
+    let on_desc = on.desc(&scx.catalog.resolve_full_name(on.name()))?;
+    let predicate = predicate
+        .clone()
+        .map(|predicate| query::plan_notification_predicate(scx, &on_desc, predicate))
+        .transpose()?;
+
+    let name = scx.allocate_qualified_name(normalize::unresolved_object_name(name.to_owned())?)?;
+    let create_sql =
+        normalize::create_statement(&scx, Statement::CreateNotification(stmt.clone()))?;
+    let compute_instance = scx.resolve_compute_instance(None)?.id();
+
+    Ok(Plan::CreateNotification(CreateNotificationPlan {
+        name,
+        notification: Notification {
+            create_sql,
+            on: on.id(),
+            predicate,
+            depends_on: depends_on.into_iter().collect(),
+            compute_instance,
+        },
+        if_not_exists: *if_not_exists,
+    }))
+}
+
+pub fn describe_create_function(
+    _: &StatementContext,
+    _: &CreateFunctionStatement<Raw>,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_create_function(
+    scx: &StatementContext,
+    stmt: CreateFunctionStatement<Aug>,
+    depends_on: HashSet<GlobalId>,
+) -> Result<Plan, anyhow::Error> {
+    let CreateFunctionStatement {
+        name,
+        params,
+        returns,
+        body,
+    } = &stmt;
+
+    // CTE identifiers are only unique within a statement, so CTEs in the body
+    // of a function could clash with CTEs in the queries that call it.
+    struct CteFinder(bool);
+    impl<'ast> Visit<'ast, Aug> for CteFinder {
+        fn visit_cte(&mut self, _: &'ast Cte<Aug>) {
+            self.0 = true;
+        }
+    }
+    let mut cte_finder = CteFinder(false);
+    cte_finder.visit_function_body(body);
+    if cte_finder.0 {
+        bail_unsupported!("WITH in function bodies");
+    }
+
+    let name = scx.allocate_qualified_name(normalize::unresolved_object_name(name.clone())?)?;
+
+    let mut param_names = vec![];
+    let mut param_types = vec![];
+    for (i, param) in params.iter().enumerate() {
+        let param_name = match &param.name {
+            Some(param_name) => normalize::column_name(param_name.clone()),
+            // Unnamed parameters can only be referred to by position.
+            None => ColumnName::from(format!("${}", i + 1)),
+        };
+        if param_names.contains(&param_name) {
+            bail!(
+                "parameter name {} used more than once",
+                param_name.as_str().quoted()
+            );
+        }
+        param_names.push(param_name);
+        param_types.push(query::scalar_type_from_sql(scx, &param.data_type)?);
+    }
+    let params_desc = RelationDesc::new(
+        RelationType::new(
+            param_types
+                .iter()
+                .map(|ty| ty.clone().nullable(true))
+                .collect(),
+        ),
+        param_names,
+    );
+
+    let body = match (returns, body) {
+        (FunctionReturns::Scalar(data_type), FunctionBody::Expr(expr)) => {
+            let return_type = query::scalar_type_from_sql(scx, data_type)?;
+            let expr =
+                query::plan_sql_function_expr(scx, &params_desc, expr.clone(), &return_type)?;
+            SqlFuncBody::Scalar { expr, return_type }
+        }
+        (FunctionReturns::SetOf(data_type), FunctionBody::Query(body_query)) => {
+            // Like other table functions that return a single column, the
+            // column is named after the function.
+            let typ = query::scalar_type_from_sql(scx, data_type)?;
+            let desc = RelationDesc::empty().with_column(name.item.clone(), typ.nullable(true));
+            let expr =
+                query::plan_sql_function_query(scx, &params_desc, body_query.clone(), &desc)?;
+            SqlFuncBody::Table { expr, desc }
+        }
+        (FunctionReturns::Table(columns), FunctionBody::Query(body_query)) => {
+            let mut desc = RelationDesc::empty();
+            for column in columns {
+                let typ = query::scalar_type_from_sql(scx, &column.data_type)?;
+                desc = desc.with_column(
+                    normalize::column_name(column.name.clone()),
+                    typ.nullable(true),
+                );
+            }
+            let expr =
+                query::plan_sql_function_query(scx, &params_desc, body_query.clone(), &desc)?;
+            SqlFuncBody::Table { expr, desc }
+        }
+        _ => unreachable!("the parser pairs function bodies with return types"),
+    };
+
+    let create_sql = normalize::create_statement(scx, Statement::CreateFunction(stmt.clone()))?;
+
+    Ok(Plan::CreateFunction(CreateFunctionPlan {
+        name,
+        function: Function {
+            create_sql,
+            func: SqlFunc {
+                params: param_types,
+                body,
+            },
+            depends_on: depends_on.into_iter().collect(),
+        },
+    }))
+}
+
 pub fn describe_drop_database(
     _: &StatementContext,
     _: &DropDatabaseStatement<Raw>,
@@ -2709,6 +3192,10 @@ pub fn plan_drop_objects(
         );
     }
 
+    if object_type == ObjectType::Func {
+        return plan_drop_functions(scx, names, cascade, if_exists);
+    }
+
     let names: Vec<_> = names
         .into_iter()
         .map(|name| resolve_object_name(scx, name))
@@ -2733,13 +3220,43 @@ pub fn plan_drop_objects(
         | ObjectType::Index
         | ObjectType::Sink
         | ObjectType::Type
-        | ObjectType::Secret => plan_drop_items(scx, object_type, names, cascade),
+        | ObjectType::Secret
+        | ObjectType::Notification => plan_drop_items(scx, object_type, names, cascade),
         ObjectType::Role => unreachable!("DROP ROLE handled separately"),
         ObjectType::Cluster => unreachable!("DROP CLUSTER handled separately"),
+        ObjectType::Func => unreachable!("DROP FUNCTION handled separately"),
         ObjectType::Object => unreachable!("cannot drop generic OBJECT, must provide object type"),
     }
 }
 
+/// Plans `DROP FUNCTION`, which must resolve its names among functions rather
+/// than among other catalog items.
+fn plan_drop_functions(
+    scx: &StatementContext,
+    names: Vec<RawObjectName>,
+    cascade: bool,
+    if_exists: bool,
+) -> Result<Plan, anyhow::Error> {
+    let mut ids = vec![];
+    for name in names {
+        let name = match name {
+            RawObjectName::Name(name) | RawObjectName::Id(_, name) => name,
+        };
+        match scx.resolve_function(name) {
+            Ok(item) => ids.extend(plan_drop_item(scx, ObjectType::Func, item, cascade)?),
+            Err(_) if if_exists => {
+                // TODO(benesch/jkosh44): generate a notice indicating that the
+                // function does not exist.
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(Plan::DropItems(DropItemsPlan {
+        items: ids,
+        ty: ObjectType::Func,
+    }))
+}
+
 pub fn describe_drop_schema(
     _: &StatementContext,
     _: &DropSchemaStatement<Raw>,
@@ -2935,7 +3452,8 @@ pub fn plan_drop_item(
                     | CatalogItemType::View
                     | CatalogItemType::Sink
                     | CatalogItemType::Type
-                    | CatalogItemType::Secret => {
+                    | CatalogItemType::Secret
+                    | CatalogItemType::Notification => {
                         bail!(
                             "cannot drop {}: still depended upon by catalog item '{}'",
                             scx.catalog.resolve_full_name(catalog_entry.name()),
@@ -3034,6 +3552,69 @@ pub fn plan_alter_index_options(
     }
 }
 
+pub fn describe_alter_source(
+    _: &StatementContext,
+    _: &AlterSourceStatement<Raw>,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_alter_source(
+    scx: &StatementContext,
+    AlterSourceStatement {
+        source_name,
+        if_exists,
+        action,
+    }: AlterSourceStatement<Aug>,
+) -> Result<Plan, anyhow::Error> {
+    let entry = match scx.get_item_by_resolved_name(&source_name) {
+        Ok(source) => source,
+        Err(_) if if_exists => {
+            // TODO(benesch): generate a notice indicating this source does not
+            // exist.
+            return Ok(Plan::AlterNoop(AlterNoopPlan {
+                object_type: ObjectType::Source,
+            }));
+        }
+        Err(e) => return Err(e),
+    };
+    if entry.item_type() != CatalogItemType::Source {
+        bail!(
+            "{} is a {} not a source",
+            source_name.full_name_str(),
+            entry.item_type()
+        )
+    }
+
+    match action {
+        AlterSourceAction::RefreshSchema { cascade } => {
+            let create_stmt = match crate::parse::parse(entry.create_sql())?.into_element() {
+                Statement::CreateSource(stmt) => stmt,
+                stmt => bail!("source has unexpected create statement: {}", stmt),
+            };
+            let value_format = match &create_stmt.format {
+                CreateSourceFormat::Bare(format) => Some(format),
+                CreateSourceFormat::KeyValue { value, .. } => Some(value),
+                CreateSourceFormat::None => None,
+            };
+            if !matches!(value_format, Some(Format::Avro(AvroSchema::Csr { .. }))) {
+                bail!(
+                    "cannot refresh schema of {}: only sources that use \
+                     FORMAT AVRO USING CONFLUENT SCHEMA REGISTRY support REFRESH SCHEMA",
+                    source_name.full_name_str()
+                );
+            }
+            Ok(Plan::AlterSourceRefreshSchema(
+                AlterSourceRefreshSchemaPlan {
+                    id: entry.id(),
+                    create_stmt,
+                    cascade,
+                },
+            ))
+        }
+    }
+}
+
 pub fn describe_alter_object_rename(
     _: &StatementContext,
     _: &AlterObjectRenameStatement<Raw>,
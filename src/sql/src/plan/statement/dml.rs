@@ -16,25 +16,28 @@ use std::collections::{HashMap, HashSet};
 
 use anyhow::bail;
 
-use mz_expr::{GlobalId, MirRelationExpr};
+use mz_expr::{GlobalId, MirRelationExpr, MirScalarExpr, RowSetFinishing};
 use mz_ore::collections::CollectionExt;
+use mz_ore::str::StrExt;
 use mz_repr::adt::numeric::NumericMaxScale;
-use mz_repr::{RelationDesc, ScalarType};
+use mz_repr::{ColumnName, RelationDesc, ScalarType};
 use mz_sql_parser::ast::AstInfo;
 
 use crate::ast::{
     CopyDirection, CopyRelation, CopyStatement, CopyTarget, CreateViewStatement, DeleteStatement,
     ExplainStage, ExplainStatement, Explainee, Ident, InsertStatement, Query, SelectStatement,
-    Statement, TailRelation, TailStatement, UpdateStatement, ViewDefinition,
+    Statement, TailEnvelope, TailRelation, TailStatement, UpdateStatement, ViewDefinition,
 };
 use crate::catalog::CatalogItemType;
 use crate::names::{resolve_names, Aug, ResolvedObjectName};
+use crate::normalize;
 use crate::plan::query::QueryLifetime;
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{query, QueryContext};
 use crate::plan::{
-    CopyFormat, CopyFromPlan, CopyParams, ExplainPlan, InsertPlan, MutationKind, Params, PeekPlan,
-    Plan, ReadThenWritePlan, TailFrom, TailPlan,
+    ConflictResolution, CopyFormat, CopyFromPlan, CopyParams, ExplainAnalyzePlan, ExplainPlan,
+    InsertPlan, MutationKind, OnConflictPlan, Params, PeekPlan, Plan, ReadThenWritePlan, TailFrom,
+    TailPlan, UnboundPeekPlan,
 };
 
 // TODO(benesch): currently, describing a `SELECT` or `INSERT` query
@@ -49,10 +52,13 @@ pub fn describe_insert(
         table_name,
         columns,
         source,
-        ..
+        on_conflict,
     }: InsertStatement<Aug>,
 ) -> Result<StatementDesc, anyhow::Error> {
-    query::plan_insert_query(scx, table_name, columns, source)?;
+    query::plan_insert_query(scx, table_name.clone(), columns, source)?;
+    if let Some(on_conflict) = on_conflict {
+        query::plan_on_conflict(scx, table_name, on_conflict)?;
+    }
     Ok(StatementDesc::new(None))
 }
 
@@ -62,14 +68,83 @@ pub fn plan_insert(
         table_name,
         columns,
         source,
+        on_conflict,
     }: InsertStatement<Aug>,
     params: &Params,
 ) -> Result<Plan, anyhow::Error> {
-    let (id, mut expr) = query::plan_insert_query(scx, table_name, columns, source)?;
+    let (id, mut expr) = query::plan_insert_query(scx, table_name.clone(), columns, source)?;
     expr.bind_parameters(&params)?;
     let expr = expr.optimize_and_lower(&scx.into())?;
 
-    Ok(Plan::Insert(InsertPlan { id, values: expr }))
+    match on_conflict {
+        None => Ok(Plan::Insert(InsertPlan { id, values: expr })),
+        // Resolving conflicts requires reading the table, so inserts with an
+        // ON CONFLICT clause are planned as read-then-writes.
+        Some(on_conflict) => {
+            let on_conflict = query::plan_on_conflict(scx, table_name, on_conflict)?;
+            let arity = expr.arity();
+            Ok(Plan::ReadThenWrite(ReadThenWritePlan {
+                id,
+                selection: expr,
+                finishing: RowSetFinishing {
+                    order_by: vec![],
+                    limit: None,
+                    offset: 0,
+                    project: (0..arity).collect(),
+                },
+                assignments: HashMap::new(),
+                kind: MutationKind::Insert,
+                on_conflict: Some(plan_on_conflict(params, on_conflict)?),
+            }))
+        }
+    }
+}
+
+fn plan_on_conflict(
+    params: &Params,
+    query::OnConflictPlan { keys, resolution }: query::OnConflictPlan,
+) -> Result<OnConflictPlan, anyhow::Error> {
+    let resolution = match resolution {
+        query::ConflictResolution::DoNothing => ConflictResolution::DoNothing,
+        query::ConflictResolution::DoUpdate {
+            assignments,
+            selection,
+        } => {
+            let mut assignments_outer = HashMap::new();
+            for (idx, mut set) in assignments {
+                set.bind_parameters(&params)?;
+                let set = set.lower_uncorrelated()?;
+                check_on_conflict_expr(&set)?;
+                assignments_outer.insert(idx, set);
+            }
+            let selection = match selection {
+                Some(mut selection) => {
+                    selection.bind_parameters(&params)?;
+                    let selection = selection.lower_uncorrelated()?;
+                    check_on_conflict_expr(&selection)?;
+                    Some(selection)
+                }
+                None => None,
+            };
+            ConflictResolution::DoUpdate {
+                assignments: assignments_outer,
+                selection,
+            }
+        }
+    };
+    Ok(OnConflictPlan { keys, resolution })
+}
+
+/// Rejects `DO UPDATE` expressions that can't be evaluated when the conflicting
+/// rows are written.
+fn check_on_conflict_expr(expr: &MirScalarExpr) -> Result<(), anyhow::Error> {
+    if expr.contains_temporal() {
+        bail_unsupported!("calls to mz_logical_timestamp in ON CONFLICT DO UPDATE");
+    }
+    if expr.contains_unmaterializable() {
+        bail!("ON CONFLICT DO UPDATE cannot refer to the current time or session");
+    }
+    Ok(())
 }
 
 pub fn describe_delete(
@@ -132,6 +207,7 @@ pub fn plan_read_then_write(
         finishing,
         assignments: assignments_outer,
         kind,
+        on_conflict: None,
     }))
 }
 
@@ -146,20 +222,34 @@ pub fn describe_select(
 
 pub fn plan_select(
     scx: &StatementContext,
-    SelectStatement { query, as_of }: SelectStatement<Aug>,
+    stmt: SelectStatement<Aug>,
     params: &Params,
     copy_to: Option<CopyFormat>,
 ) -> Result<Plan, anyhow::Error> {
+    let plan = plan_select_unbound(scx, stmt)?;
+    Ok(Plan::Peek(PeekPlan {
+        source: plan.bind(params)?,
+        when: plan.when,
+        finishing: plan.finishing,
+        copy_to,
+    }))
+}
+
+/// Plans a `SELECT` statement without binding its parameters.
+pub fn plan_select_unbound(
+    scx: &StatementContext,
+    SelectStatement { query, as_of }: SelectStatement<Aug>,
+) -> Result<UnboundPeekPlan, anyhow::Error> {
     let query::PlannedQuery {
         expr, finishing, ..
-    } = plan_query(scx, query, params, QueryLifetime::OneShot(scx.pcx()?))?;
+    } = query::plan_root_query(scx, query, QueryLifetime::OneShot(scx.pcx()?))?;
     let when = query::plan_as_of(scx, as_of)?;
-    Ok(Plan::Peek(PeekPlan {
+    Ok(UnboundPeekPlan {
         source: expr,
         when,
         finishing,
-        copy_to,
-    }))
+        config: scx.into(),
+    })
 }
 
 pub fn describe_explain(
@@ -177,6 +267,7 @@ pub fn describe_explain(
             ExplainStage::OptimizedPlan { .. } => "Optimized Plan",
             ExplainStage::PhysicalPlan => "Physical Plan",
             ExplainStage::Timestamp => "Timestamp",
+            ExplainStage::Analyze => "Analyzed Plan",
         },
         ScalarType::String.nullable(false),
     )))
@@ -204,6 +295,32 @@ pub fn plan_explain(
     }: ExplainStatement<Aug>,
     params: &Params,
 ) -> Result<Plan, anyhow::Error> {
+    if stage == ExplainStage::Analyze {
+        let (name, expected_type) = match &explainee {
+            Explainee::View(name) => (name, CatalogItemType::View),
+            Explainee::Index(name) => (name, CatalogItemType::Index),
+            Explainee::Query(_) => bail!("EXPLAIN ANALYZE requires a view or an index"),
+        };
+        let item = scx.get_item_by_resolved_name(name)?;
+        if item.item_type() != expected_type {
+            let article = |typ| {
+                if matches!(typ, CatalogItemType::Index) {
+                    "an"
+                } else {
+                    "a"
+                }
+            };
+            bail!(
+                "Expected {} to be {} {}, not {} {}",
+                name,
+                article(expected_type),
+                expected_type,
+                article(item.item_type()),
+                item.item_type()
+            );
+        }
+        return Ok(Plan::ExplainAnalyze(ExplainAnalyzePlan { id: item.id() }));
+    }
     let is_view = matches!(explainee, Explainee::View(_));
     let query = match explainee {
         Explainee::View(name) => {
@@ -223,6 +340,7 @@ pub fn plan_explain(
             let mut qcx = QueryContext::root(&scx, QueryLifetime::OneShot(scx.pcx().unwrap()));
             resolve_names(&mut qcx, query)?
         }
+        Explainee::Index(_) => bail!("EXPLAIN {} FOR INDEX is not supported", stage),
         Explainee::Query(query) => query,
     };
     // Previously we would bail here for ORDER BY and LIMIT; this has been relaxed to silently
@@ -296,6 +414,7 @@ pub fn describe_tail(
     };
     let options = TailOptions::try_from(stmt.options)?;
     let progress = options.progress.unwrap_or(false);
+    let upsert_key = plan_tail_upsert_key(&relation_desc, stmt.envelope)?;
     let mut desc = RelationDesc::empty().with_column(
         "mz_timestamp",
         ScalarType::Numeric {
@@ -306,38 +425,114 @@ pub fn describe_tail(
     if progress {
         desc = desc.with_column("mz_progressed", ScalarType::Bool.nullable(false));
     }
-    desc = desc.with_column("mz_diff", ScalarType::Int64.nullable(true));
-    for (name, mut ty) in relation_desc.into_iter() {
-        if progress {
-            ty.nullable = true;
+    match upsert_key {
+        None => {
+            desc = desc.with_column("mz_diff", ScalarType::Int64.nullable(true));
+            for (name, mut ty) in relation_desc.into_iter() {
+                if progress {
+                    ty.nullable = true;
+                }
+                desc = desc.with_column(name, ty);
+            }
+        }
+        Some(key) => {
+            // Key columns come first, followed by the remaining columns of the
+            // key's previous value and then those of its new value, which are
+            // null unless the key had exactly one value.
+            desc = desc.with_column("mz_state", ScalarType::String.nullable(true));
+            let columns: Vec<_> = relation_desc.into_iter().collect();
+            for i in &key {
+                let (name, mut ty) = columns[*i].clone();
+                if progress {
+                    ty.nullable = true;
+                }
+                desc = desc.with_column(name, ty);
+            }
+            let values: Vec<_> = columns
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| !key.contains(i))
+                .map(|(_, (name, ty))| (name, ty.nullable(true)))
+                .collect();
+            for (name, ty) in values.iter() {
+                let name = ColumnName::from(format!("mz_before_{}", name.as_str()));
+                desc = desc.with_column(name, ty.clone());
+            }
+            for (name, ty) in values {
+                desc = desc.with_column(name, ty);
+            }
         }
-        desc = desc.with_column(name, ty);
     }
     return Ok(StatementDesc::new(Some(desc)));
 }
 
+/// Resolves the key columns of a `TAIL` with `ENVELOPE UPSERT` to their
+/// indices in `desc`.
+fn plan_tail_upsert_key(
+    desc: &RelationDesc,
+    envelope: Option<TailEnvelope>,
+) -> Result<Option<Vec<usize>>, anyhow::Error> {
+    let key_columns = match envelope {
+        None => return Ok(None),
+        Some(TailEnvelope::Upsert { key_columns }) => key_columns,
+    };
+    let mut key = vec![];
+    for column in key_columns {
+        let name = normalize::column_name(column);
+        let i = match desc.get_by_name(&name) {
+            Some((i, _)) => i,
+            None => bail!("column {} does not exist", name.as_str().quoted()),
+        };
+        if key.contains(&i) {
+            bail!("column {} specified more than once", name.as_str().quoted());
+        }
+        key.push(i);
+    }
+    // The previous value of each non-key column is output as a column named
+    // after it, which must not be ambiguous with any column of the relation.
+    for (i, name) in desc.iter_names().enumerate() {
+        if key.contains(&i) {
+            continue;
+        }
+        let before = ColumnName::from(format!("mz_before_{}", name.as_str()));
+        if desc.get_by_name(&before).is_some() {
+            bail!(
+                "column {} conflicts with the previous value of column {} in ENVELOPE UPSERT",
+                before.as_str().quoted(),
+                name.as_str().quoted()
+            );
+        }
+    }
+    Ok(Some(key))
+}
+
 pub fn plan_tail(
     scx: &StatementContext,
     TailStatement {
         relation,
         options,
+        envelope,
         as_of,
     }: TailStatement<Aug>,
     copy_to: Option<CopyFormat>,
     depends_on: HashSet<GlobalId>,
 ) -> Result<Plan, anyhow::Error> {
-    let from = match relation {
+    let (from, desc) = match relation {
         TailRelation::Name(name) => {
             let entry = scx.get_item_by_resolved_name(&name)?;
             match entry.item_type() {
                 CatalogItemType::Table | CatalogItemType::Source | CatalogItemType::View => {
-                    TailFrom::Id(entry.id())
+                    let desc = entry
+                        .desc(&scx.catalog.resolve_full_name(entry.name()))?
+                        .clone();
+                    (TailFrom::Id(entry.id()), desc)
                 }
                 CatalogItemType::Func
                 | CatalogItemType::Index
                 | CatalogItemType::Sink
                 | CatalogItemType::Type
-                | CatalogItemType::Secret => bail!(
+                | CatalogItemType::Secret
+                | CatalogItemType::Notification => bail!(
                     "'{}' cannot be tailed because it is a {}",
                     name.full_name_str(),
                     entry.item_type(),
@@ -358,13 +553,16 @@ pub fn plan_tail(
             )?;
             assert!(query.finishing.is_trivial(query.desc.arity()));
             let depends_on = depends_on.into_iter().collect();
-            TailFrom::Query {
+            let desc = query.desc.clone();
+            let from = TailFrom::Query {
                 expr: query.expr,
                 desc: query.desc,
                 depends_on,
-            }
+            };
+            (from, desc)
         }
     };
+    let upsert_key = plan_tail_upsert_key(&desc, envelope)?;
 
     let when = query::plan_as_of(scx, as_of)?;
     let options = TailOptions::try_from(options)?;
@@ -374,6 +572,7 @@ pub fn plan_tail(
         with_snapshot: options.snapshot.unwrap_or(true),
         copy_to,
         emit_progress: options.progress.unwrap_or(false),
+        upsert_key,
     }))
 }
 
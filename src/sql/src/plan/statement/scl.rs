@@ -20,14 +20,17 @@ use mz_repr::{RelationDesc, ScalarType};
 
 use crate::ast::{
     CloseStatement, DeallocateStatement, DeclareStatement, DiscardStatement, DiscardTarget,
-    ExecuteStatement, FetchStatement, PrepareStatement, Raw, SetVariableStatement,
-    SetVariableValue, ShowVariableStatement, Value,
+    ExecuteStatement, FetchStatement, Ident, ListenStatement, PrepareStatement, Raw, RawObjectName,
+    SetVariableStatement, SetVariableValue, ShowVariableStatement, UnlistenStatement,
+    UnresolvedObjectName, Value,
 };
-use crate::names::Aug;
+use crate::catalog::CatalogItemType;
+use crate::names::{Aug, PartialObjectName, QualifiedObjectName};
+use crate::normalize;
 use crate::plan::statement::{StatementContext, StatementDesc};
 use crate::plan::{
     describe, query, ClosePlan, DeallocatePlan, DeclarePlan, ExecutePlan, ExecuteTimeout,
-    FetchPlan, Plan, PreparePlan, SetVariablePlan, ShowVariablePlan,
+    FetchPlan, ListenPlan, Plan, PreparePlan, SetVariablePlan, ShowVariablePlan, UnlistenPlan,
 };
 
 pub fn describe_set_variable(
@@ -254,3 +257,58 @@ pub fn plan_deallocate(
         name: name.map(|name| name.to_string()),
     }))
 }
+
+pub fn describe_listen(
+    _: &StatementContext,
+    _: &ListenStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_listen(
+    scx: &StatementContext,
+    ListenStatement { channel }: ListenStatement,
+) -> Result<Plan, anyhow::Error> {
+    Ok(Plan::Listen(ListenPlan {
+        channel: resolve_channel(scx, channel)?,
+    }))
+}
+
+pub fn describe_unlisten(
+    _: &StatementContext,
+    _: &UnlistenStatement,
+) -> Result<StatementDesc, anyhow::Error> {
+    Ok(StatementDesc::new(None))
+}
+
+pub fn plan_unlisten(
+    scx: &StatementContext,
+    UnlistenStatement { channel }: UnlistenStatement,
+) -> Result<Plan, anyhow::Error> {
+    Ok(Plan::Unlisten(UnlistenPlan {
+        channel: channel
+            .map(|channel| resolve_channel(scx, channel))
+            .transpose()?,
+    }))
+}
+
+/// Resolves a notification channel to the name of the notification that
+/// raises it.
+///
+/// Notifications are looked up like any other item, so that notifications of
+/// the same name in different schemas are different channels. A channel that
+/// names no notification yet refers to a notification in the default schema.
+fn resolve_channel(
+    scx: &StatementContext,
+    channel: Ident,
+) -> Result<QualifiedObjectName, anyhow::Error> {
+    let name = RawObjectName::Name(UnresolvedObjectName(vec![channel.clone()]));
+    match scx.resolve_item(name) {
+        Ok(item) if item.item_type() == CatalogItemType::Notification => Ok(item.name().clone()),
+        _ => scx.allocate_qualified_name(PartialObjectName {
+            database: None,
+            schema: None,
+            item: normalize::ident(channel),
+        }),
+    }
+}
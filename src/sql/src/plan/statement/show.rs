@@ -274,6 +274,8 @@ pub fn show_objects<'a>(
         ObjectType::Role => bail_unsupported!("SHOW ROLES"),
         ObjectType::Cluster => show_clusters(scx, filter),
         ObjectType::Secret => show_secrets(scx, from, filter),
+        ObjectType::Notification => show_notifications(scx, from, filter),
+        ObjectType::Func => bail_unsupported!("SHOW FUNCTIONS"),
         ObjectType::Index => unreachable!("SHOW INDEX handled separately"),
     }
 }
@@ -640,6 +642,25 @@ pub fn show_secrets<'a>(
     ShowSelect::new(scx, query, filter, None, None)
 }
 
+pub fn show_notifications<'a>(
+    scx: &'a StatementContext<'a>,
+    from: Option<ResolvedSchemaName>,
+    filter: Option<ShowStatementFilter<Aug>>,
+) -> Result<ShowSelect<'a>, anyhow::Error> {
+    let schema_spec = scx.resolve_optional_schema(&from)?;
+
+    let query = format!(
+        "SELECT notifications.name, objects.name AS on, clusters.name AS cluster
+        FROM mz_catalog.mz_notifications AS notifications
+        JOIN mz_catalog.mz_objects AS objects ON notifications.on_id = objects.id
+        JOIN mz_catalog.mz_clusters AS clusters ON notifications.cluster_id = clusters.id
+        WHERE notifications.schema_id = {}",
+        schema_spec,
+    );
+
+    ShowSelect::new(scx, query, filter, None, None)
+}
+
 /// An intermediate result when planning a `SHOW` query.
 ///
 /// Can be interrogated for its columns, or converted into a proper [`Plan`].
@@ -12,13 +12,16 @@
 //! See the [crate-level documentation](crate) for details.
 
 use std::collections::BTreeMap;
+use std::io::BufRead;
 use std::iter;
 use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::{anyhow, bail, ensure, Context};
 use aws_arn::ARN;
 use csv::ReaderBuilder;
+use globset::GlobBuilder;
 use mz_sql_parser::ast::KafkaSourceConnector;
 use prost::Message;
 use protobuf_native::compiler::{SourceTreeDescriptorDatabase, VirtualSourceTree};
@@ -32,13 +35,18 @@ use uuid::Uuid;
 use mz_ccsr::{Client, GetBySubjectError};
 use mz_dataflow_types::postgres_source::PostgresSourceDetails;
 use mz_dataflow_types::sources::{AwsConfig, AwsExternalId};
+use mz_interchange::avro::parse_schema;
+use mz_interchange::columnar::PartialFile;
+use mz_interchange::json::JsonSourceSchema;
+use mz_interchange::orc::{self, OrcSchema};
+use mz_interchange::parquet::{self, ParquetSchema};
 use mz_repr::strconv;
 
 use crate::ast::{
-    AvroSchema, CreateSourceConnector, CreateSourceFormat, CreateSourceStatement, CsrConnectorAvro,
-    CsrConnectorProto, CsrSeed, CsrSeedCompiled, CsrSeedCompiledEncoding, CsrSeedCompiledOrLegacy,
-    CsvColumns, DbzMode, Envelope, Format, Ident, ProtobufSchema, Raw, SqlOption, Value,
-    WithOption, WithOptionValue,
+    AvroSchema, Compression, CreateSourceConnector, CreateSourceFormat, CreateSourceStatement,
+    CsrConnectorAvro, CsrConnectorProto, CsrSeed, CsrSeedCompiled, CsrSeedCompiledEncoding,
+    CsrSeedCompiledOrLegacy, CsvColumns, DbzMode, Envelope, Format, Ident, JsonSchema,
+    ProtobufSchema, Raw, S3KeySource, SqlOption, Value, WithOption, WithOptionValue,
 };
 use crate::kafka_util;
 use crate::normalize;
@@ -123,6 +131,25 @@ pub async fn purify_create_source(
                         }
                         _ => {}
                     }
+
+                    let (key_schema, value_schema) = json_schemas_to_infer(format);
+                    if key_schema.is_some() || value_schema.is_some() {
+                        let messages = kafka_util::sample_messages(
+                            Arc::clone(&consumer),
+                            &topic,
+                            JSON_SAMPLE_SIZE,
+                            KAFKA_SAMPLE_TIMEOUT,
+                        )
+                        .await?;
+                        if let Some(schema) = key_schema {
+                            let keys = messages.iter().filter_map(|(key, _)| key.as_deref());
+                            infer_json_schema(schema, keys)?;
+                        }
+                        if let Some(schema) = value_schema {
+                            let values = messages.iter().filter_map(|(_, value)| value.as_deref());
+                            infer_json_schema(schema, values)?;
+                        }
+                    }
                 }
             }
         }
@@ -144,16 +171,111 @@ pub async fn purify_create_source(
             })?;
         }
         // Report an error if a file cannot be opened, or if it is a directory.
-        CreateSourceConnector::File { path, .. } => {
+        CreateSourceConnector::File { path, compression } => {
             let f = File::open(&path).await?;
             if f.metadata().await?.is_dir() {
                 bail!("Expected a regular file, but {} is a directory.", path);
             }
+            if let CreateSourceFormat::Bare(Format::Parquet {
+                schema: schema @ None,
+            }) = format
+            {
+                let path = path.clone();
+                let inferred = task::block_in_place(|| {
+                    // The Parquet reader needs to seek to the footer, which
+                    // tokio's `File` does not support synchronously.
+                    let mut f = std::fs::File::open(path)?;
+                    ParquetSchema::infer(&mut f)
+                })?;
+                *schema = Some(inferred.to_json());
+            }
+            if let CreateSourceFormat::Bare(Format::Orc {
+                schema: schema @ None,
+            }) = format
+            {
+                let path = path.clone();
+                let inferred = task::block_in_place(|| {
+                    let mut f = std::fs::File::open(path)?;
+                    OrcSchema::infer(&mut f)
+                })?;
+                *schema = Some(inferred.to_json());
+            }
+            if let (_, Some(schema)) = json_schemas_to_infer(format) {
+                ensure_uncompressed_json_sample(compression)?;
+                let path = path.clone();
+                let lines = task::block_in_place(|| {
+                    let f = std::io::BufReader::new(std::fs::File::open(path)?);
+                    let mut lines = vec![];
+                    for line in f.split(b'\n') {
+                        let line = line?;
+                        if !is_blank(&line) {
+                            lines.push(line);
+                        }
+                        if lines.len() == JSON_SAMPLE_SIZE {
+                            break;
+                        }
+                    }
+                    Ok::<_, anyhow::Error>(lines)
+                })?;
+                infer_json_schema(schema, lines)?;
+            }
             file = Some(f);
         }
-        CreateSourceConnector::S3 { .. } => {
+        CreateSourceConnector::S3 {
+            key_sources,
+            pattern,
+            compression,
+        } => {
             let aws_config = normalize::aws_config(&mut with_options_map, None)?;
-            validate_aws_credentials(&aws_config, aws_external_id).await?;
+            validate_aws_credentials(&aws_config, aws_external_id.clone()).await?;
+            if let CreateSourceFormat::Bare(Format::Parquet {
+                schema: schema @ None,
+            }) = format
+            {
+                let object = find_s3_object(
+                    &aws_config,
+                    aws_external_id.clone(),
+                    key_sources,
+                    pattern.as_deref(),
+                )
+                .await?;
+                let inferred = object
+                    .infer_from_footer(parquet::FOOTER_READ_SIZE, ParquetSchema::infer)
+                    .await?;
+                *schema = Some(inferred.to_json());
+            }
+            if let CreateSourceFormat::Bare(Format::Orc {
+                schema: schema @ None,
+            }) = format
+            {
+                let object = find_s3_object(
+                    &aws_config,
+                    aws_external_id.clone(),
+                    key_sources,
+                    pattern.as_deref(),
+                )
+                .await?;
+                let inferred = object
+                    .infer_from_footer(orc::FOOTER_READ_SIZE, OrcSchema::infer)
+                    .await?;
+                *schema = Some(inferred.to_json());
+            }
+            if let (_, Some(schema)) = json_schemas_to_infer(format) {
+                ensure_uncompressed_json_sample(compression)?;
+                let object = find_s3_object(
+                    &aws_config,
+                    aws_external_id,
+                    key_sources,
+                    pattern.as_deref(),
+                )
+                .await?;
+                let sample = object.lines(JSON_SAMPLE_BYTES).await?;
+                let lines = sample
+                    .split(|b| *b == b'\n')
+                    .filter(|line| !is_blank(line))
+                    .take(JSON_SAMPLE_SIZE);
+                infer_json_schema(schema, lines)?;
+            }
         }
         CreateSourceConnector::Kinesis { arn } => {
             let region = arn
@@ -203,6 +325,85 @@ pub async fn purify_create_source(
     Ok(stmt)
 }
 
+/// Purifies the statement that created a source whose schema is being
+/// refreshed with `ALTER SOURCE ... REFRESH SCHEMA`.
+///
+/// The schemas that were fetched from the Confluent Schema Registry when the
+/// source was created are discarded, and the latest schemas are fetched in
+/// their place. The latest schemas must be able to read data that was written
+/// with the discarded ones.
+pub async fn purify_refresh_source_schema(
+    now: u64,
+    aws_external_id: AwsExternalId,
+    mut stmt: CreateSourceStatement<Raw>,
+) -> Result<CreateSourceStatement<Raw>, anyhow::Error> {
+    // A source with a key and a value format has a seed for each of them
+    // that uses the Confluent Schema Registry.
+    let old_seeds = csr_connectors_avro(&mut stmt.format)
+        .map(|csr_connector| csr_connector.seed.take())
+        .collect::<Option<Vec<_>>>()
+        .filter(|seeds| !seeds.is_empty())
+        .ok_or_else(|| {
+            anyhow!("[internal-error] source does not have a Confluent Schema Registry seed")
+        })?;
+
+    let mut stmt = purify_create_source(now, aws_external_id, stmt).await?;
+
+    let new_seeds = csr_connectors_avro(&mut stmt.format)
+        .map(|csr_connector| {
+            csr_connector
+                .seed
+                .as_ref()
+                .expect("purification fetches the Confluent Schema Registry seed")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(old_seeds.len(), new_seeds.len());
+    for (old_seed, new_seed) in old_seeds.iter().zip(new_seeds) {
+        check_schema_backward_compatible("value", &old_seed.value_schema, &new_seed.value_schema)?;
+        match (&old_seed.key_schema, &new_seed.key_schema) {
+            (Some(old), Some(new)) => check_schema_backward_compatible("key", old, new)?,
+            (None, None) => (),
+            (Some(_), None) => bail!("latest key schema has been deleted"),
+            (None, Some(_)) => bail!("source does not have a key schema to refresh"),
+        }
+    }
+    Ok(stmt)
+}
+
+/// Returns the Avro Confluent Schema Registry connectors of the key format, if
+/// any, and then of the value format, if any.
+fn csr_connectors_avro(
+    format: &mut CreateSourceFormat<Raw>,
+) -> impl Iterator<Item = &mut CsrConnectorAvro<Raw>> {
+    let formats = match format {
+        CreateSourceFormat::None => vec![],
+        CreateSourceFormat::Bare(format) => vec![format],
+        CreateSourceFormat::KeyValue { key, value } => vec![key, value],
+    };
+    formats.into_iter().filter_map(|format| match format {
+        Format::Avro(AvroSchema::Csr { csr_connector }) => Some(csr_connector),
+        _ => None,
+    })
+}
+
+/// Checks that data written with the `old` Avro schema can be read with the
+/// `new` one, following the Avro schema resolution rules.
+fn check_schema_backward_compatible(
+    which: &str,
+    old: &str,
+    new: &str,
+) -> Result<(), anyhow::Error> {
+    let old = parse_schema(old)?;
+    let new = parse_schema(new)?;
+    mz_avro::schema::resolve_schemas(&old, &new).with_context(|| {
+        format!(
+            "latest {} schema is not backward compatible with the schema the source uses",
+            which
+        )
+    })?;
+    Ok(())
+}
+
 async fn purify_source_format(
     format: &mut CreateSourceFormat<Raw>,
     connector: &mut CreateSourceConnector,
@@ -343,7 +544,21 @@ async fn purify_source_format_single(
         } => {
             purify_csv(file, connector, *delimiter, columns).await?;
         }
-        Format::Bytes | Format::Regex(_) | Format::Json | Format::Text => (),
+        // JSON schemas are inferred along with the connector, which knows
+        // where to find sample messages, so any that remain cannot be.
+        Format::Json {
+            schema: Some(JsonSchema::Infer),
+        } => bail!(
+            "FORMAT JSON WITH (INFER SCHEMA) is only supported for Kafka, S3 and file sources"
+        ),
+        // Parquet and ORC schemas are inferred along with the connector,
+        // which knows where to find a sample file.
+        Format::Bytes
+        | Format::Regex(_)
+        | Format::Json { .. }
+        | Format::Text
+        | Format::Parquet { .. }
+        | Format::Orc { .. } => (),
     }
     Ok(())
 }
@@ -652,6 +867,209 @@ async fn compile_proto(
     })
 }
 
+/// An object of an S3 source whose contents are sampled to infer the schema of
+/// the source.
+struct S3SampleObject {
+    client: mz_aws_util::s3::Client,
+    bucket: String,
+    key: String,
+    size: u64,
+}
+
+impl S3SampleObject {
+    /// Downloads the bytes in `start..end` of the object with a ranged GET.
+    async fn range(&self, start: u64, end: u64) -> Result<Vec<u8>, anyhow::Error> {
+        if start >= end {
+            return Ok(vec![]);
+        }
+        let context = || format!("downloading S3 object {}/{}", self.bucket, self.key);
+        let object = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .range(format!("bytes={}-{}", start, end - 1))
+            .send()
+            .await
+            .with_context(context)?;
+        let body = object.body.collect().await.with_context(context)?;
+        Ok(body.into_bytes().to_vec())
+    }
+
+    /// Downloads at most the first `max_bytes` of the object, cut back to its
+    /// last complete line.
+    async fn lines(&self, max_bytes: u64) -> Result<Vec<u8>, anyhow::Error> {
+        let mut sample = self.range(0, self.size.min(max_bytes)).await?;
+        if self.size > max_bytes {
+            let complete = sample.iter().rposition(|b| *b == b'\n').unwrap_or(0);
+            sample.truncate(complete);
+        }
+        Ok(sample)
+    }
+
+    /// Infers a schema from the footer of the object, like that of a Parquet
+    /// or ORC file.
+    ///
+    /// Only the last `footer_read_size` bytes are downloaded at first. If
+    /// `infer` fails because it needed earlier bytes, those are downloaded
+    /// too and `infer` is retried once.
+    async fn infer_from_footer<T>(
+        &self,
+        footer_read_size: u64,
+        infer: impl Fn(&mut PartialFile) -> Result<T, anyhow::Error>,
+    ) -> Result<T, anyhow::Error> {
+        let start = self.size.saturating_sub(footer_read_size);
+        let mut file = PartialFile::tail(self.size, self.range(start, self.size).await?);
+        match infer(&mut file) {
+            Ok(inferred) => Ok(inferred),
+            Err(e) => match file.first_missing() {
+                Some(missing) if missing < start => {
+                    file.retain_head(self.range(missing, start).await?);
+                    infer(&mut file)
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+/// Finds the first object of an S3 source, which is sampled to infer the
+/// schema of formats that describe themselves, like Parquet and ORC, or of
+/// JSON with `INFER SCHEMA`.
+///
+/// Only `BUCKET SCAN` key sources are considered, as there is no way to peek
+/// at an SQS queue without consuming its notifications. The object is not
+/// downloaded; see [`S3SampleObject`] for reading the parts of it that are
+/// needed.
+async fn find_s3_object(
+    config: &AwsConfig,
+    external_id: AwsExternalId,
+    key_sources: &[S3KeySource],
+    pattern: Option<&str>,
+) -> Result<S3SampleObject, anyhow::Error> {
+    let glob = pattern
+        .map(|p| {
+            GlobBuilder::new(p)
+                .literal_separator(true)
+                .backslash_escape(true)
+                .build()
+                .map(|g| g.compile_matcher())
+        })
+        .transpose()?;
+    // Listing can be restricted to the literal prefix of the pattern.
+    let prefix = pattern.map(|p| {
+        p.chars()
+            .take_while(|c| !matches!(c, '*' | '?' | '[' | '{' | '\\'))
+            .collect::<String>()
+    });
+
+    let config = config.load(external_id).await;
+    let client = mz_aws_util::s3::client(&config);
+    for key_source in key_sources {
+        let bucket = match key_source {
+            S3KeySource::Scan { bucket } => bucket,
+            S3KeySource::SqsNotifications { .. } => continue,
+        };
+        let mut continuation_token = None;
+        loop {
+            let response = client
+                .list_objects_v2()
+                .bucket(bucket)
+                .set_prefix(prefix.clone())
+                .set_continuation_token(continuation_token)
+                .send()
+                .await
+                .with_context(|| format!("listing objects in S3 bucket {}", bucket))?;
+            let object = response
+                .contents
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|object| Some((object.key?, object.size)))
+                .find(|(key, _)| glob.as_ref().map(|g| g.is_match(key)).unwrap_or(true));
+            if let Some((key, size)) = object {
+                return Ok(S3SampleObject {
+                    client,
+                    bucket: bucket.clone(),
+                    key,
+                    size: u64::try_from(size).unwrap_or(0),
+                });
+            }
+            match response.next_continuation_token {
+                Some(token) => continuation_token = Some(token),
+                None => break,
+            }
+        }
+    }
+    bail!(
+        "unable to infer the schema of the source: no objects match the pattern in any \
+         BUCKET SCAN; specify the schema with USING SCHEMA"
+    )
+}
+
+/// The number of messages to infer the schema of a JSON source from.
+const JSON_SAMPLE_SIZE: usize = 100;
+
+/// The number of bytes to download from the start of an S3 object to find
+/// `JSON_SAMPLE_SIZE` messages in.
+const JSON_SAMPLE_BYTES: u64 = 1 << 20;
+
+/// How long to wait for a Kafka topic to produce `JSON_SAMPLE_SIZE` messages.
+const KAFKA_SAMPLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Returns the key and value schemas of `format` that are to be inferred from
+/// sample messages, i.e., those specified as `JSON WITH (INFER SCHEMA)`.
+fn json_schemas_to_infer(
+    format: &mut CreateSourceFormat<Raw>,
+) -> (
+    Option<&mut Option<JsonSchema>>,
+    Option<&mut Option<JsonSchema>>,
+) {
+    fn to_infer(format: &mut Format<Raw>) -> Option<&mut Option<JsonSchema>> {
+        match format {
+            Format::Json {
+                schema: schema @ Some(JsonSchema::Infer),
+            } => Some(schema),
+            _ => None,
+        }
+    }
+    match format {
+        CreateSourceFormat::None => (None, None),
+        CreateSourceFormat::Bare(value) => (None, to_infer(value)),
+        CreateSourceFormat::KeyValue { key, value } => (to_infer(key), to_infer(value)),
+    }
+}
+
+/// Replaces `schema` with the schema inferred from `samples`.
+fn infer_json_schema<I, B>(schema: &mut Option<JsonSchema>, samples: I) -> Result<(), anyhow::Error>
+where
+    I: IntoIterator<Item = B>,
+    B: AsRef<[u8]>,
+{
+    let inferred = JsonSourceSchema::infer(samples).map_err(|e| {
+        anyhow!(
+            "unable to infer the schema of the source: {}; \
+             specify the schema with FORMAT JSON USING SCHEMA",
+            e
+        )
+    })?;
+    *schema = Some(JsonSchema::Inline(inferred.to_json()));
+    Ok(())
+}
+
+fn ensure_uncompressed_json_sample(compression: &Compression) -> Result<(), anyhow::Error> {
+    if *compression != Compression::None {
+        bail!(
+            "FORMAT JSON WITH (INFER SCHEMA) does not support compressed data; \
+             specify the schema with FORMAT JSON USING SCHEMA"
+        );
+    }
+    Ok(())
+}
+
+fn is_blank(line: &[u8]) -> bool {
+    line.iter().all(|b| b.is_ascii_whitespace())
+}
+
 /// Makes an always-valid AWS API call to perform a basic sanity check of
 /// whether the specified AWS configuration is valid.
 async fn validate_aws_credentials(
@@ -43,6 +43,14 @@ pub enum QGMError {
 
 impl Error for QGMError {}
 
+impl QGMError {
+    /// Whether the error is due to a construct that the QGM path does not
+    /// support yet, as opposed to an internal error.
+    pub fn is_unsupported(&self) -> bool {
+        !matches!(self, QGMError::Internal(..))
+    }
+}
+
 impl fmt::Display for QGMError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -24,7 +24,7 @@ use itertools::Itertools;
 use mz_ore::collections::CollectionExt;
 use mz_ore::id_gen::IdGen;
 use mz_repr::{Datum, RelationType, ScalarType};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 impl TryFrom<Model> for mz_expr::MirRelationExpr {
     type Error = QGMError;
@@ -47,8 +47,9 @@ struct Lowerer<'a> {
     /// Stack of common expressions that have been given a [`mz_expr::LocalId`].
     lets: Vec<(mz_expr::LocalId, mz_expr::MirRelationExpr)>,
     /// Map of (BoxIds whose MIR representation has been given a
-    /// [`mz_expr::LocalId`]) -> (position of its MIR representation in `lets`)
-    common_boxes: HashMap<BoxId, usize>,
+    /// [`mz_expr::LocalId`], together with the outer relation they were
+    /// applied to) -> (position of its MIR representation in `lets`)
+    common_boxes: HashMap<(BoxId, mz_expr::Id), usize>,
 }
 
 impl<'a> Lowerer<'a> {
@@ -112,7 +113,13 @@ impl<'a> Lowerer<'a> {
         get_outer: mz_expr::MirRelationExpr,
         outer_column_map: &ColumnMap,
     ) -> Result<mz_expr::MirRelationExpr, QGMError> {
-        if let Some(let_pos) = self.common_boxes.get(&box_id) {
+        // A box shared by several quantifiers is only lowered once per outer
+        // relation.
+        let common_key = match &get_outer {
+            mz_expr::MirRelationExpr::Get { id, .. } => Some((box_id, *id)),
+            _ => None,
+        };
+        if let Some(let_pos) = common_key.and_then(|key| self.common_boxes.get(&key)) {
             return Ok(mz_expr::MirRelationExpr::Get {
                 id: mz_expr::Id::Local(self.lets[*let_pos].0),
                 typ: self.lets[*let_pos].1.typ(),
@@ -177,19 +184,24 @@ impl<'a> Lowerer<'a> {
                 // in that order.
 
                 // 1) Lower the join component of the Select box.
-                // TODO(asenac) We could join first all non-correlated quantifiers
-                // and then apply the correlated ones one by one in order of dependency
-                // on top the join built so far, adding the predicates as soon as their
-                // dependencies are satisfied.
+                // Non-correlated quantifiers are joined first, and the correlated
+                // ones are then applied one by one in order of dependency on top
+                // of the join built so far.
+                // TODO(asenac) Add the predicates as soon as their dependencies
+                // are satisfied.
                 let correlation_info = the_box.correlation_info();
-                if !correlation_info.is_empty() {
-                    let msg = String::from("correlated joins are not supported yet");
-                    return Err(QGMError::from(UnsupportedDecorrelation { msg }));
-                }
 
                 let outer_arity = get_outer.arity();
-                let (mut input, column_map) =
-                    self.lower_join(get_outer, outer_column_map, &the_box.quantifiers)?;
+                let (mut input, column_map) = if correlation_info.is_empty() {
+                    self.lower_join(get_outer, outer_column_map, &the_box.quantifiers)?
+                } else {
+                    self.lower_correlated_join(
+                        get_outer,
+                        outer_column_map,
+                        &the_box.quantifiers,
+                        &correlation_info,
+                    )?
+                };
                 let input_arity = input.arity();
 
                 let lowered_predicates: Vec<_> = select
@@ -218,11 +230,17 @@ impl<'a> Lowerer<'a> {
                 let (mut input, column_map) =
                     self.lower_join(get_outer.clone(), outer_column_map, &the_box.quantifiers)?;
 
-                // Build the reduction
-                let group_key = grouping
-                    .key
-                    .iter()
-                    .map(|k| Self::lower_expression(k, &column_map).unwrap())
+                // Build the reduction, grouping by the columns of the outer
+                // relation as well.
+                let outer_arity = get_outer.arity();
+                let group_key = (0..outer_arity)
+                    .map(mz_expr::MirScalarExpr::Column)
+                    .chain(
+                        grouping
+                            .key
+                            .iter()
+                            .map(|k| Self::lower_expression(k, &column_map).unwrap()),
+                    )
                     .collect_vec();
                 let aggregates = the_box
                     .columns
@@ -245,7 +263,7 @@ impl<'a> Lowerer<'a> {
                     })
                     .collect_vec();
 
-                if group_key.is_empty() {
+                if grouping.key.is_empty() {
                     // SQL semantics require default values for global aggregates
                     let input_type = input.typ();
                     let default = aggregates
@@ -281,19 +299,19 @@ impl<'a> Lowerer<'a> {
                     // of the Grouping box, we should be able to lower any semantically valid
                     // query graph.
                     let mut aggregate_count = 0;
-                    let projection = the_box.columns.iter().map(|c| {
+                    let projection = (0..outer_arity).chain(the_box.columns.iter().map(|c| {
                         if let BoxScalarExpr::Aggregate { .. } = &c.expr {
-                            let aggregate_pos = grouping.key.len() + aggregate_count;
+                            let aggregate_pos = outer_arity + grouping.key.len() + aggregate_count;
                             aggregate_count += 1;
                             aggregate_pos
                         } else {
-                            grouping
+                            outer_arity + grouping
                                 .key
                                 .iter()
                                 .position(|k| c.expr == *k)
                                 .expect("expression in the projection of a Grouping box not included in the grouping key")
                         }
-                    }).collect_vec();
+                    })).collect_vec();
 
                     input = SR::Reduce {
                         input: Box::new(input),
@@ -306,14 +324,14 @@ impl<'a> Lowerer<'a> {
                     Ok(input.project(projection))
                 }
             }
+            BoxType::OuterJoin(box_struct) if !the_box.correlation_info().is_empty() => self
+                .lower_correlated_outer_join(
+                    &the_box,
+                    &box_struct.predicates,
+                    get_outer,
+                    outer_column_map,
+                ),
             BoxType::OuterJoin(box_struct) => {
-                // TODO: Correlated outer joins are not yet supported.
-                let correlation_info = the_box.correlation_info();
-                if !correlation_info.is_empty() {
-                    let msg = String::from("correlated joins are not supported yet");
-                    return Err(QGMError::from(UnsupportedDecorrelation { msg }));
-                }
-
                 let ot = get_outer.typ();
                 let oa = ot.arity();
 
@@ -496,8 +514,8 @@ impl<'a> Lowerer<'a> {
         };
         let input = if the_box.ranging_quantifiers.iter().count() > 1 {
             let (result, let_stack_pos) = self.push_let(input);
-            if let Some(let_stack_pos) = let_stack_pos {
-                self.common_boxes.insert(box_id, let_stack_pos);
+            if let (Some(key), Some(let_stack_pos)) = (common_key, let_stack_pos) {
+                self.common_boxes.insert(key, let_stack_pos);
             }
             result
         } else {
@@ -511,10 +529,6 @@ impl<'a> Lowerer<'a> {
     /// used to lower expressions that sit directly on top of the join.
     ///
     /// The quantifiers are joined on the columns of the outer relation.
-    /// TODO(asenac) Since decorrelation is not yet supported the outer relation is
-    /// currently always the join identity, so the result of this method is always
-    /// a cross-join of the given quantifiers, which makes part of this code untesteable
-    /// at the moment.
     fn lower_join(
         &mut self,
         get_outer: mz_expr::MirRelationExpr,
@@ -580,6 +594,195 @@ impl<'a> Lowerer<'a> {
         (join, column_map)
     }
 
+    /// Same as `lower_join` except some of the given quantifiers are correlated
+    /// with their siblings, as described by `correlation_info`.
+    ///
+    /// The non-correlated quantifiers are joined first, and the correlated ones
+    /// are then applied in order of dependency on top of the join built so far.
+    fn lower_correlated_join(
+        &mut self,
+        get_outer: mz_expr::MirRelationExpr,
+        outer_column_map: &ColumnMap,
+        quantifiers: &QuantifierSet,
+        correlation_info: &BTreeMap<QuantifierId, HashSet<ColumnReference>>,
+    ) -> Result<(mz_expr::MirRelationExpr, ColumnMap), QGMError> {
+        let outer_arity = get_outer.arity();
+        let uncorrelated = quantifiers
+            .iter()
+            .filter(|q_id| !correlation_info.contains_key(*q_id))
+            .cloned()
+            .collect::<QuantifierSet>();
+        let (mut input, mut column_map) = if uncorrelated.is_empty() {
+            (get_outer, outer_column_map.clone())
+        } else {
+            self.lower_join(get_outer, outer_column_map, &uncorrelated)?
+        };
+
+        let mut pending = correlation_info.keys().cloned().collect_vec();
+        while !pending.is_empty() {
+            // Pick a quantifier whose dependencies have already been lowered.
+            let q_id = match pending.iter().position(|q_id| {
+                correlation_info[q_id]
+                    .iter()
+                    .all(|c| column_map.contains_key(c))
+            }) {
+                Some(position) => pending.remove(position),
+                None => {
+                    let msg = String::from("cyclic correlation among quantifiers");
+                    return Err(QGMError::from(UnsupportedDecorrelation { msg }));
+                }
+            };
+            input = self.apply_correlated_quantifier(
+                input,
+                &mut column_map,
+                outer_arity,
+                q_id,
+                &correlation_info[&q_id],
+            )?;
+        }
+
+        Ok((input, column_map))
+    }
+
+    /// Applies the correlated quantifier `quantifier_id` to `input`, a relation
+    /// containing the columns in `correlated_columns`, and extends `column_map`
+    /// with the columns of the quantifier.
+    ///
+    /// The quantifier is applied to the distinct values of the outer columns
+    /// and the correlated columns of `input`, and the result is joined back to
+    /// `input` on those columns.
+    fn apply_correlated_quantifier(
+        &mut self,
+        input: mz_expr::MirRelationExpr,
+        column_map: &mut ColumnMap,
+        outer_arity: usize,
+        quantifier_id: QuantifierId,
+        correlated_columns: &HashSet<ColumnReference>,
+    ) -> Result<mz_expr::MirRelationExpr, QGMError> {
+        let correlated_columns = correlated_columns
+            .iter()
+            .sorted_by_key(|c| (c.quantifier_id, c.position))
+            .collect_vec();
+        let key = (0..outer_arity)
+            .chain(correlated_columns.iter().map(|c| column_map[*c]))
+            .collect_vec();
+
+        let (input, _) = self.push_let(input);
+        let (get_key, _) = self.push_let(input.clone().project(key.clone()).distinct());
+
+        // Within the key relation, the columns of the outer relation keep their
+        // positions and the correlated columns follow them.
+        let mut key_column_map = column_map
+            .iter()
+            .filter(|(_, position)| **position < outer_arity)
+            .map(|(c, position)| (c.clone(), *position))
+            .collect::<ColumnMap>();
+        for (i, c) in correlated_columns.iter().enumerate() {
+            key_column_map.insert((*c).clone(), outer_arity + i);
+        }
+
+        let applied = self.lower_quantifier(quantifier_id, get_key, &key_column_map)?;
+        let input_arity = input.arity();
+        let applied_arity = applied.arity();
+        let equivalences = key
+            .iter()
+            .enumerate()
+            .map(|(i, c)| {
+                vec![
+                    mz_expr::MirScalarExpr::Column(*c),
+                    mz_expr::MirScalarExpr::Column(input_arity + i),
+                ]
+            })
+            .collect_vec();
+        let join = mz_expr::MirRelationExpr::join_scalars(vec![input, applied], equivalences)
+            .project(
+                (0..input_arity)
+                    .chain((input_arity + key.len())..(input_arity + applied_arity))
+                    .collect_vec(),
+            );
+
+        for position in 0..(applied_arity - key.len()) {
+            column_map.insert(
+                ColumnReference {
+                    quantifier_id,
+                    position,
+                },
+                input_arity + position,
+            );
+        }
+        Ok(join)
+    }
+
+    /// Lowers an OuterJoin box whose non-preserved side is correlated with its
+    /// preserved side, ie. a `LEFT JOIN LATERAL`.
+    fn lower_correlated_outer_join(
+        &mut self,
+        the_box: &BoundRef<'_, QueryBox>,
+        predicates: &[BoxScalarExpr],
+        get_outer: mz_expr::MirRelationExpr,
+        outer_column_map: &ColumnMap,
+    ) -> Result<mz_expr::MirRelationExpr, QGMError> {
+        let correlation_info = the_box.correlation_info();
+        let mut q_iter = the_box.input_quantifiers();
+        let lhs = q_iter.next().unwrap();
+        let rhs = q_iter.next().unwrap();
+        let (preserved_id, correlated_id) = match (lhs.quantifier_type, rhs.quantifier_type) {
+            (QuantifierType::PreservedForeach, QuantifierType::Foreach) => (lhs.id, rhs.id),
+            (QuantifierType::Foreach, QuantifierType::PreservedForeach) => (rhs.id, lhs.id),
+            _ => {
+                let msg = String::from("correlated full outer joins are not supported yet");
+                return Err(QGMError::from(UnsupportedDecorrelation { msg }));
+            }
+        };
+        if correlation_info.keys().any(|q_id| *q_id != correlated_id) {
+            let msg = String::from("correlated preserved sides are not supported yet");
+            return Err(QGMError::from(UnsupportedDecorrelation { msg }));
+        }
+
+        let oa = get_outer.arity();
+
+        // 1) Lower the preserved side.
+        let preserved = self.lower_quantifier(preserved_id, get_outer, outer_column_map)?;
+        let (preserved, _) = self.push_let(preserved);
+        let pa = preserved.arity();
+        let (join, mut column_map) = self.lower_join_inner(
+            outer_column_map,
+            oa,
+            vec![(preserved_id, preserved.clone())],
+        );
+
+        // 2) Apply the correlated side to it and lower the predicates as a
+        //    filter following the join.
+        let join = self.apply_correlated_quantifier(
+            join,
+            &mut column_map,
+            oa,
+            correlated_id,
+            &correlation_info[&correlated_id],
+        )?;
+        let lowered_predicates: Vec<_> = predicates
+            .iter()
+            .map(|p| Self::lower_expression(p, &column_map))
+            .try_collect()?;
+        let (join, _) = self.push_let(join.filter(lowered_predicates.into_iter()));
+        let join_arity = join.arity();
+
+        // 3) Add the preserved rows without a match, padded with nulls.
+        let fill = join
+            .typ()
+            .column_types
+            .into_iter()
+            .skip(pa)
+            .map(|typ| (Datum::Null, typ.scalar_type))
+            .collect();
+        let result = join
+            .clone()
+            .union(preserved.anti_lookup(&mut self.id_gen, join, fill));
+
+        // 4) Lower the project component.
+        Self::lower_box_columns(result, the_box, &column_map, oa, join_arity)
+    }
+
     /// Lowers the given quantifier by applying it to the outer relation.
     fn lower_quantifier(
         &mut self,
@@ -636,6 +839,20 @@ impl<'a> Lowerer<'a> {
                 let default = vec![(Datum::Null, col_type.scalar_type)];
                 input = get_outer.lookup(&mut self.id_gen, guarded, default);
             }
+            QuantifierType::Existential => {
+                // Determine, for each `get_outer` prefix, whether the subquery
+                // returns any row.
+                let outer_arity = get_outer.arity();
+                let exists = input
+                    .project((0..outer_arity).collect_vec())
+                    .distinct()
+                    .map_one(mz_expr::MirScalarExpr::literal_ok(
+                        Datum::True,
+                        ScalarType::Bool,
+                    ));
+                let default = vec![(Datum::False, ScalarType::Bool)];
+                input = get_outer.lookup(&mut self.id_gen, exists, default);
+            }
             other => {
                 return Err(QGMError::from(UnsupportedQuantifierType {
                     quantifier_type: other.clone(),
@@ -680,9 +897,6 @@ impl<'a> Lowerer<'a> {
     }
 
     /// Join the given inputs on a shared common prefix.
-    ///
-    /// TODO(asenac) Given the lack of support for decorrelation at the moment, this
-    /// method is always called with `prefix_length` 0, and hence, it remains untested.
     fn join_on_prefix(
         join_inputs: Vec<mz_expr::MirRelationExpr>,
         prefix_length: usize,
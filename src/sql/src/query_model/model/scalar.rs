@@ -230,6 +230,53 @@ impl BoxScalarExpr {
         f(self);
     }
 
+    /// Replaces every reference to a column of the given quantifier with
+    /// the expression at the same position in `columns`.
+    pub fn substitute_quantifier_columns(
+        &mut self,
+        quantifier_id: QuantifierId,
+        columns: &[BoxScalarExpr],
+    ) {
+        self.visit_mut_post(&mut |expr| {
+            if let BoxScalarExpr::ColumnReference(c) = expr {
+                if c.quantifier_id == quantifier_id {
+                    *expr = columns[c.position].clone();
+                }
+            }
+        })
+    }
+
+    /// Returns the set of quantifiers referenced by this expression.
+    pub fn referenced_quantifiers(&self) -> QuantifierSet {
+        let mut quantifiers = QuantifierSet::new();
+        self.visit_post(&mut |expr| {
+            if let BoxScalarExpr::ColumnReference(c) = expr {
+                quantifiers.insert(c.quantifier_id);
+            }
+        });
+        quantifiers
+    }
+
+    /// Splits a tree of `AND` calls into the list of its conjuncts.
+    pub fn into_conjuncts(self) -> Vec<BoxScalarExpr> {
+        let mut conjuncts = vec![];
+        let mut stack = vec![self];
+        while let Some(expr) = stack.pop() {
+            match expr {
+                BoxScalarExpr::CallBinary {
+                    func: BinaryFunc::And,
+                    expr1,
+                    expr2,
+                } => {
+                    stack.push(*expr2);
+                    stack.push(*expr1);
+                }
+                other => conjuncts.push(other),
+            }
+        }
+        conjuncts
+    }
+
     pub fn collect_column_references_from_context(
         &self,
         context: &QuantifierSet,
@@ -127,15 +127,48 @@ pub fn rewrite_model(model: &mut Model) {
     let rules: Vec<Box<dyn ApplyRule>> = vec![
         // simplify outer joins first
         Box::new(rule::simplify_outer_joins::SimplifyOuterJoins),
+        // then unnest subqueries and normalize the resulting graph
+        Box::new(rule::unnest_existential::UnnestExistential),
+        Box::new(rule::select_merge::SelectMerge),
+        Box::new(rule::predicate_pushdown::PredicatePushdown),
     ];
     apply_rules_to_model(model, rules);
     model.garbage_collect();
 
+    // Remove unused columns once the shape of the graph is settled.
+    let rules: Vec<Box<dyn ApplyRule>> = vec![Box::new(rule::column_removal::ColumnRemoval)];
+    apply_rules_to_model(model, rules);
+    model.garbage_collect();
+
     // At the end of the process, update box and quantifier ids to make it
     // easier to compare the graph before and after optimization.
     model.update_ids();
 }
 
+/// Apply the rewrite rules with the given names, and only those, to the model.
+#[cfg(test)]
+pub(crate) fn apply_rules_by_name(model: &mut Model, names: &[String]) -> Result<(), String> {
+    let rules = names
+        .iter()
+        .map(|name| -> Result<Box<dyn ApplyRule>, String> {
+            match name.as_str() {
+                "column_removal" => Ok(Box::new(rule::column_removal::ColumnRemoval)),
+                "predicate_pushdown" => Ok(Box::new(rule::predicate_pushdown::PredicatePushdown)),
+                "select_merge" => Ok(Box::new(rule::select_merge::SelectMerge)),
+                "simplify_outer_joins" => {
+                    Ok(Box::new(rule::simplify_outer_joins::SimplifyOuterJoins))
+                }
+                "unnest_existential" => Ok(Box::new(rule::unnest_existential::UnnestExistential)),
+                other => Err(format!("unknown rewrite rule: {}", other)),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    apply_rules_to_model(model, rules);
+    model.garbage_collect();
+    model.update_ids();
+    Ok(())
+}
+
 /// Transform the model by applying a list of rewrite rules.
 fn apply_rules_to_model(model: &mut Model, rules: Vec<Box<dyn ApplyRule>>) {
     // collect a set of attributes required by the given rules
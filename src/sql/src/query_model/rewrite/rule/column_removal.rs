@@ -0,0 +1,158 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Removes the columns of a box that are not referenced by any other box.
+//!
+//! Only boxes whose output multiplicity does not depend on their projection
+//! (Select, Grouping and OuterJoin boxes that preserve duplicates) and that
+//! are only consumed through [`QuantifierType::Foreach`] or
+//! [`QuantifierType::PreservedForeach`] quantifiers of boxes that reference
+//! their columns by position are pruned. The top box is never pruned, since
+//! its projection is the result of the query.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use itertools::Itertools;
+
+use crate::query_model::attribute::core::Attribute;
+use crate::query_model::model::{
+    BoxScalarExpr, BoxType, ColumnReference, DistinctOperation, QuantifierType,
+};
+use crate::query_model::rewrite::ApplyStrategy;
+use crate::query_model::rewrite::Rule;
+use crate::query_model::{BoxId, Model, QuantifierId};
+
+pub(crate) struct ColumnRemoval;
+
+impl Rule for ColumnRemoval {
+    /// A (non-empty) sequence of boxes with unused columns, together with the
+    /// positions of the columns to retain in each of them.
+    type Match = Vec<(BoxId, Vec<usize>)>;
+
+    fn name(&self) -> &'static str {
+        "ColumnRemoval"
+    }
+
+    fn strategy(&self) -> ApplyStrategy {
+        ApplyStrategy::TopBox
+    }
+
+    fn required_attributes(&self) -> HashSet<Box<dyn Attribute>> {
+        HashSet::new()
+    }
+
+    fn check(&self, model: &Model, top_box: BoxId) -> Option<Self::Match> {
+        // Collect all the column references in the graph.
+        let mut boxes = BTreeSet::new();
+        let mut used = HashSet::new();
+        let _ = model.try_visit_pre_post_descendants(
+            &mut |m, box_id| -> Result<(), ()> {
+                boxes.insert(*box_id);
+                m.get_box(*box_id).visit_expressions(&mut |expr| {
+                    expr.visit_post(&mut |expr| {
+                        if let BoxScalarExpr::ColumnReference(c) = expr {
+                            used.insert(c.clone());
+                        }
+                    });
+                    Ok(())
+                })
+            },
+            &mut |_, _| Ok(()),
+            top_box,
+        );
+
+        let mut prunable = vec![];
+        for box_id in boxes {
+            if box_id == top_box {
+                continue;
+            }
+            let the_box = model.get_box(box_id);
+            let prunable_type = matches!(
+                the_box.box_type,
+                BoxType::Select(..) | BoxType::Grouping(..) | BoxType::OuterJoin(..)
+            );
+            let prunable_parents = the_box.ranging_quantifiers().all(|q| {
+                matches!(
+                    q.quantifier_type,
+                    QuantifierType::Foreach | QuantifierType::PreservedForeach
+                ) && matches!(
+                    q.parent_box().box_type,
+                    BoxType::Select(..) | BoxType::Grouping(..) | BoxType::OuterJoin(..)
+                )
+            });
+            if !prunable_type
+                || !prunable_parents
+                || the_box.distinct != DistinctOperation::Preserve
+            {
+                continue;
+            }
+
+            let retained = (0..the_box.columns.len())
+                .filter(|position| {
+                    the_box.ranging_quantifiers.iter().any(|q_id| {
+                        used.contains(&ColumnReference {
+                            quantifier_id: *q_id,
+                            position: *position,
+                        })
+                    })
+                })
+                .collect_vec();
+            if retained.len() < the_box.columns.len() {
+                prunable.push((box_id, retained));
+            }
+        }
+
+        if prunable.is_empty() {
+            None
+        } else {
+            Some(prunable)
+        }
+    }
+
+    fn rewrite(&self, model: &mut Model, mat: Self::Match) {
+        // Prune the unused columns, remembering the new position of the
+        // retained ones for every quantifier ranging over the pruned box.
+        let mut new_positions = HashMap::<QuantifierId, HashMap<usize, usize>>::new();
+        for (box_id, retained) in mat {
+            let mut the_box = model.get_mut_box(box_id);
+            let columns = std::mem::take(&mut the_box.columns);
+            the_box.columns = columns
+                .into_iter()
+                .enumerate()
+                .filter(|(position, _)| retained.contains(position))
+                .map(|(_, c)| c)
+                .collect();
+            let positions = retained
+                .into_iter()
+                .enumerate()
+                .map(|(new, old)| (old, new))
+                .collect::<HashMap<_, _>>();
+            for q_id in the_box.ranging_quantifiers.iter() {
+                new_positions.insert(*q_id, positions.clone());
+            }
+        }
+
+        // Update the column references to the pruned boxes.
+        let _ = model.try_visit_mut_pre_post(
+            &mut |m, box_id| -> Result<(), ()> {
+                m.get_mut_box(*box_id).visit_expressions_mut(&mut |expr| {
+                    expr.visit_mut_post(&mut |expr| {
+                        if let BoxScalarExpr::ColumnReference(c) = expr {
+                            if let Some(positions) = new_positions.get(&c.quantifier_id) {
+                                c.position = positions[&c.position];
+                            }
+                        }
+                    });
+                    Ok(())
+                })
+            },
+            &mut |_, _| Ok(()),
+        );
+    }
+}
@@ -15,4 +15,8 @@
 //! ACM Transactions on Database Systems (TODS) 22.1 (1997): 43-74.
 //! ](https://www.academia.edu/26160408/Outerjoin_simplification_and_reordering_for_query_optimization)
 
+pub(crate) mod column_removal;
+pub(crate) mod predicate_pushdown;
+pub(crate) mod select_merge;
 pub(crate) mod simplify_outer_joins;
+pub(crate) mod unnest_existential;
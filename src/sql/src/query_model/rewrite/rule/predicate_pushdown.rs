@@ -0,0 +1,157 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Pushes predicates of a Select box down into its inputs.
+//!
+//! A conjunct of a predicate that only references the columns of a single
+//! [`QuantifierType::Foreach`] quantifier of the box (plus, optionally,
+//! columns from the outer context) is moved into the input box of that
+//! quantifier, rewritten in terms of the projection of the input box. Only
+//! Select boxes and, for conjuncts on grouping key columns, the Select box
+//! feeding a Grouping box are valid targets.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::query_model::attribute::core::Attribute;
+use crate::query_model::model::{BoxScalarExpr, BoxType, QuantifierType};
+use crate::query_model::rewrite::ApplyStrategy;
+use crate::query_model::rewrite::Rule;
+use crate::query_model::rewrite::VisitOrder;
+use crate::query_model::{BoxId, Model, QuantifierId};
+
+pub(crate) struct PredicatePushdown;
+
+/// A [`Rule::Match`] for the [`PredicatePushdown`] rule.
+pub(crate) struct Pushdown {
+    /// The box that currently holds the predicate.
+    box_id: BoxId,
+    /// The position of the predicate within the box.
+    predicate: usize,
+    /// The position of the conjunct within the predicate.
+    conjunct: usize,
+    /// The box the conjunct is pushed into.
+    target: BoxId,
+    /// The conjunct rewritten in terms of the quantifiers of `target`.
+    pushed: BoxScalarExpr,
+}
+
+impl Rule for PredicatePushdown {
+    type Match = Pushdown;
+
+    fn name(&self) -> &'static str {
+        "PredicatePushdown"
+    }
+
+    fn strategy(&self) -> ApplyStrategy {
+        ApplyStrategy::AllBoxes(VisitOrder::Pre)
+    }
+
+    fn required_attributes(&self) -> HashSet<Box<dyn Attribute>> {
+        HashSet::new()
+    }
+
+    fn check(&self, model: &Model, box_id: BoxId) -> Option<Self::Match> {
+        let the_box = model.get_box(box_id);
+        let select = match &the_box.box_type {
+            BoxType::Select(select) => select,
+            _ => return None,
+        };
+
+        for (predicate, expr) in select.predicates.iter().enumerate() {
+            for (conjunct, expr) in expr.clone().into_conjuncts().iter().enumerate() {
+                // Constant predicates and predicates involving several
+                // quantifiers of the box stay where they are.
+                let referenced = expr.referenced_quantifiers();
+                let local = referenced.intersection(&the_box.quantifiers).collect_vec();
+                if let [q_id] = local[..] {
+                    if let Some((target, pushed)) = pushdown(model, *q_id, expr) {
+                        return Some(Pushdown {
+                            box_id,
+                            predicate,
+                            conjunct,
+                            target,
+                            pushed,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    fn rewrite(&self, model: &mut Model, mat: Self::Match) {
+        let mut the_box = model.get_mut_box(mat.box_id);
+        if let BoxType::Select(select) = &mut the_box.box_type {
+            let mut conjuncts = select.predicates.remove(mat.predicate).into_conjuncts();
+            conjuncts.remove(mat.conjunct);
+            for (i, conjunct) in conjuncts.into_iter().enumerate() {
+                select.predicates.insert(mat.predicate + i, conjunct);
+            }
+        }
+        drop(the_box);
+
+        let mut target = model.get_mut_box(mat.target);
+        if let BoxType::Select(select) = &mut target.box_type {
+            select.predicates.push(mat.pushed);
+        }
+    }
+}
+
+/// Returns the Select box below `q_id` that `predicate`, written in terms of
+/// the columns of `q_id`, can be pushed into, together with the predicate
+/// rewritten in terms of the quantifiers of that box.
+fn pushdown(
+    model: &Model,
+    q_id: QuantifierId,
+    predicate: &BoxScalarExpr,
+) -> Option<(BoxId, BoxScalarExpr)> {
+    let q = model.get_quantifier(q_id);
+    if q.quantifier_type != QuantifierType::Foreach {
+        return None;
+    }
+    let input_box = model.get_box(q.input_box);
+    if input_box.ranging_quantifiers.len() != 1 {
+        return None;
+    }
+    let columns = input_box
+        .columns
+        .iter()
+        .map(|c| c.expr.clone())
+        .collect_vec();
+
+    match &input_box.box_type {
+        BoxType::Select(select) if select.limit.is_none() && select.offset.is_none() => {
+            let mut predicate = predicate.clone();
+            predicate.substitute_quantifier_columns(q_id, &columns);
+            Some((input_box.id, predicate))
+        }
+        BoxType::Grouping(grouping) if !grouping.key.is_empty() => {
+            // Only predicates on the grouping key can be evaluated before
+            // the aggregation.
+            let mut on_key = true;
+            predicate.visit_post(&mut |expr| {
+                if let BoxScalarExpr::ColumnReference(c) = expr {
+                    if c.quantifier_id == q_id {
+                        on_key &= !matches!(columns[c.position], BoxScalarExpr::Aggregate { .. });
+                    }
+                }
+            });
+            if !on_key {
+                return None;
+            }
+            let mut predicate = predicate.clone();
+            predicate.substitute_quantifier_columns(q_id, &columns);
+            let input_q_id = *input_box.quantifiers.iter().exactly_one().ok()?;
+            pushdown(model, input_q_id, &predicate)
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Merges a Select box into the Select box that consumes it.
+//!
+//! A Select box that is only ranged over by a single [`QuantifierType::Foreach`]
+//! quantifier of another Select box can be merged into that box: its
+//! quantifiers and predicates are moved into the parent box, and references
+//! to the columns of the removed quantifier are replaced with the expressions
+//! projected by the merged box.
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::query_model::attribute::core::Attribute;
+use crate::query_model::model::{BoxType, DistinctOperation, QuantifierType};
+use crate::query_model::rewrite::ApplyStrategy;
+use crate::query_model::rewrite::Rule;
+use crate::query_model::rewrite::VisitOrder;
+use crate::query_model::{BoxId, Model, QuantifierId};
+
+pub(crate) struct SelectMerge;
+
+impl Rule for SelectMerge {
+    /// The id of the Select box to merge into, and the id of the quantifier
+    /// ranging over the Select box to be merged.
+    type Match = (BoxId, QuantifierId);
+
+    fn name(&self) -> &'static str {
+        "SelectMerge"
+    }
+
+    fn strategy(&self) -> ApplyStrategy {
+        ApplyStrategy::AllBoxes(VisitOrder::Pre)
+    }
+
+    fn required_attributes(&self) -> HashSet<Box<dyn Attribute>> {
+        HashSet::new()
+    }
+
+    fn check(&self, model: &Model, box_id: BoxId) -> Option<Self::Match> {
+        let the_box = model.get_box(box_id);
+        if !the_box.is_select() {
+            return None;
+        }
+
+        the_box
+            .input_quantifiers()
+            .find(|q| {
+                if q.quantifier_type != QuantifierType::Foreach {
+                    return false;
+                }
+                let input_box = model.get_box(q.input_box);
+                match &input_box.box_type {
+                    BoxType::Select(select) => {
+                        input_box.ranging_quantifiers.len() == 1
+                            && !input_box.quantifiers.is_empty()
+                            && input_box.distinct == DistinctOperation::Preserve
+                            && select.order_key.is_none()
+                            && select.limit.is_none()
+                            && select.offset.is_none()
+                    }
+                    _ => false,
+                }
+            })
+            .map(|q| (box_id, q.id))
+    }
+
+    fn rewrite(&self, model: &mut Model, mat: Self::Match) {
+        let (box_id, q_id) = mat;
+        let input_box_id = model.get_quantifier(q_id).input_box;
+
+        // Detach the merged box from the graph.
+        let (columns, predicates, quantifiers) = {
+            let mut input_box = model.get_mut_box(input_box_id);
+            input_box.ranging_quantifiers.remove(&q_id);
+            let columns = input_box
+                .columns
+                .iter()
+                .map(|c| c.expr.clone())
+                .collect_vec();
+            let predicates = match &mut input_box.box_type {
+                BoxType::Select(select) => select.predicates.split_off(0),
+                _ => unreachable!(),
+            };
+            let quantifiers = std::mem::take(&mut input_box.quantifiers);
+            (columns, predicates, quantifiers)
+        };
+
+        // Move its quantifiers and predicates into the parent box.
+        for child_q_id in quantifiers.iter() {
+            model.get_mut_quantifier(*child_q_id).parent_box = box_id;
+        }
+        let mut the_box = model.get_mut_box(box_id);
+        the_box.quantifiers.remove(&q_id);
+        the_box.quantifiers.extend(quantifiers);
+        if let BoxType::Select(select) = &mut the_box.box_type {
+            select.predicates.extend(predicates);
+        }
+        drop(the_box);
+
+        // Replace the references to the removed quantifier, both in the
+        // parent box and in any subquery correlated with it.
+        let _ = model.try_visit_mut_pre_post_descendants(
+            &mut |m, b| -> Result<(), ()> {
+                m.get_mut_box(*b)
+                    .visit_expressions_mut(&mut |e| -> Result<(), ()> {
+                        e.substitute_quantifier_columns(q_id, &columns);
+                        Ok(())
+                    })
+            },
+            &mut |_, _| Ok(()),
+            box_id,
+        );
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Unnests existential subqueries that are only correlated via equalities.
+//!
+//! An [`QuantifierType::Existential`] quantifier used as a conjunct of a
+//! Select box, whose subquery is a Select box where every predicate that
+//! refers to the outer box is an equality of the form `inner = outer`, is
+//! turned into a [`QuantifierType::Foreach`] quantifier over the distinct
+//! `inner` values of the subquery, joined with the outer box on
+//! `outer = inner`:
+//!
+//! ```text
+//! SELECT ... FROM x WHERE EXISTS (SELECT * FROM y WHERE y.a = x.a AND y.b > 0)
+//! ```
+//!
+//! becomes
+//!
+//! ```text
+//! SELECT ... FROM x, (SELECT DISTINCT y.a FROM y WHERE y.b > 0) q WHERE x.a = q.a
+//! ```
+
+use std::collections::HashSet;
+
+use itertools::Itertools;
+
+use crate::plan::expr::BinaryFunc;
+use crate::query_model::attribute::core::Attribute;
+use crate::query_model::model::{
+    BoundRef, BoxScalarExpr, BoxType, Column, ColumnReference, DistinctOperation, QuantifierType,
+    QueryBox,
+};
+use crate::query_model::rewrite::ApplyStrategy;
+use crate::query_model::rewrite::Rule;
+use crate::query_model::rewrite::VisitOrder;
+use crate::query_model::{BoxId, Model, QuantifierId};
+
+pub(crate) struct UnnestExistential;
+
+/// A [`Rule::Match`] for the [`UnnestExistential`] rule.
+pub(crate) struct Unnest {
+    /// The Select box containing the existential quantifier.
+    box_id: BoxId,
+    /// The existential quantifier to unnest.
+    quantifier_id: QuantifierId,
+    /// The correlated equalities of the subquery, as `(outer, inner)` pairs.
+    keys: Vec<(BoxScalarExpr, BoxScalarExpr)>,
+    /// The remaining, uncorrelated, predicates of the subquery.
+    predicates: Vec<BoxScalarExpr>,
+}
+
+impl Rule for UnnestExistential {
+    type Match = Unnest;
+
+    fn name(&self) -> &'static str {
+        "UnnestExistential"
+    }
+
+    fn strategy(&self) -> ApplyStrategy {
+        ApplyStrategy::AllBoxes(VisitOrder::Pre)
+    }
+
+    fn required_attributes(&self) -> HashSet<Box<dyn Attribute>> {
+        HashSet::new()
+    }
+
+    fn check(&self, model: &Model, box_id: BoxId) -> Option<Self::Match> {
+        let the_box = model.get_box(box_id);
+        let conjuncts = match &the_box.box_type {
+            BoxType::Select(select) => select
+                .predicates
+                .iter()
+                .flat_map(|p| p.clone().into_conjuncts())
+                .collect_vec(),
+            _ => return None,
+        };
+        let correlation_info = the_box.correlation_info();
+
+        for q in the_box.input_quantifiers() {
+            if q.quantifier_type != QuantifierType::Existential {
+                continue;
+            }
+            // The subquery must be a top-level conjunct of the box and must
+            // not be referenced anywhere else.
+            if !conjuncts.contains(&exists(q.id)) {
+                continue;
+            }
+            let mut uses = 0;
+            the_box
+                .visit_expressions(&mut |expr| -> Result<(), ()> {
+                    expr.visit_post(&mut |expr| {
+                        if let BoxScalarExpr::ColumnReference(c) = expr {
+                            if c.quantifier_id == q.id {
+                                uses += 1;
+                            }
+                        }
+                    });
+                    Ok(())
+                })
+                .unwrap();
+            if uses != 1
+                || correlation_info
+                    .values()
+                    .any(|refs| refs.iter().any(|c| c.quantifier_id == q.id))
+            {
+                continue;
+            }
+            if let Some((keys, predicates)) = split_correlated_equalities(model, &the_box, q.id) {
+                return Some(Unnest {
+                    box_id,
+                    quantifier_id: q.id,
+                    keys,
+                    predicates,
+                });
+            }
+        }
+        None
+    }
+
+    fn rewrite(&self, model: &mut Model, mat: Self::Match) {
+        let q_id = mat.quantifier_id;
+
+        // Project the distinct inner sides of the equalities.
+        let input_box_id = model.get_quantifier(q_id).input_box;
+        let mut input_box = model.get_mut_box(input_box_id);
+        input_box.columns = mat
+            .keys
+            .iter()
+            .map(|(_, inner)| Column {
+                expr: inner.clone(),
+                alias: None,
+            })
+            .collect();
+        input_box.distinct = DistinctOperation::Enforce;
+        if let BoxType::Select(select) = &mut input_box.box_type {
+            select.predicates = mat.predicates;
+        }
+        drop(input_box);
+
+        model.get_mut_quantifier(q_id).quantifier_type = QuantifierType::Foreach;
+
+        // Replace the existential conjunct with the join predicates.
+        let mut the_box = model.get_mut_box(mat.box_id);
+        if let BoxType::Select(select) = &mut the_box.box_type {
+            let exists = exists(q_id);
+            select.predicates = select
+                .predicates
+                .drain(..)
+                .flat_map(|p| p.into_conjuncts())
+                .filter(|p| *p != exists)
+                .chain(
+                    mat.keys
+                        .into_iter()
+                        .enumerate()
+                        .map(|(position, (outer, _))| BoxScalarExpr::CallBinary {
+                            func: BinaryFunc::Eq,
+                            expr1: Box::new(outer),
+                            expr2: Box::new(BoxScalarExpr::ColumnReference(ColumnReference {
+                                quantifier_id: q_id,
+                                position,
+                            })),
+                        }),
+                )
+                .collect();
+        }
+    }
+}
+
+/// The boolean column of an existential quantifier.
+fn exists(quantifier_id: QuantifierId) -> BoxScalarExpr {
+    BoxScalarExpr::ColumnReference(ColumnReference {
+        quantifier_id,
+        position: 0,
+    })
+}
+
+/// Splits the predicates of the subquery of the existential quantifier `q_id`
+/// into `(outer, inner)` equalities correlated with `the_box` and the
+/// remaining, uncorrelated, predicates.
+///
+/// Returns `None` if the subquery is correlated with `the_box` in any other
+/// way.
+fn split_correlated_equalities(
+    model: &Model,
+    the_box: &BoundRef<'_, QueryBox>,
+    q_id: QuantifierId,
+) -> Option<(Vec<(BoxScalarExpr, BoxScalarExpr)>, Vec<BoxScalarExpr>)> {
+    let input_box = model.get_box(model.get_quantifier(q_id).input_box);
+    let select = match &input_box.box_type {
+        BoxType::Select(select)
+            if select.order_key.is_none() && select.limit.is_none() && select.offset.is_none() =>
+        {
+            select
+        }
+        _ => return None,
+    };
+    if input_box.ranging_quantifiers.len() != 1 {
+        return None;
+    }
+
+    let outer = &the_box.quantifiers;
+    let inner = &input_box.quantifiers;
+    let mut keys = vec![];
+    let mut predicates = vec![];
+    for conjunct in select
+        .predicates
+        .iter()
+        .flat_map(|p| p.clone().into_conjuncts())
+    {
+        if conjunct.referenced_quantifiers().is_disjoint(outer) {
+            predicates.push(conjunct);
+            continue;
+        }
+        match conjunct {
+            BoxScalarExpr::CallBinary {
+                func: BinaryFunc::Eq,
+                expr1,
+                expr2,
+            } => {
+                let refs1 = expr1.referenced_quantifiers();
+                let refs2 = expr2.referenced_quantifiers();
+                if refs1.is_disjoint(outer) && refs2.is_disjoint(inner) {
+                    keys.push((*expr2, *expr1));
+                } else if refs2.is_disjoint(outer) && refs1.is_disjoint(inner) {
+                    keys.push((*expr1, *expr2));
+                } else {
+                    return None;
+                }
+            }
+            _ => return None,
+        }
+    }
+
+    // Nothing else below the subquery may be correlated with `the_box`.
+    let mut column_refs = HashSet::new();
+    for q in input_box.input_quantifiers() {
+        model
+            .try_visit_pre_post_descendants(
+                &mut |m, box_id| -> Result<(), ()> {
+                    m.get_box(*box_id).visit_expressions(&mut |expr| {
+                        expr.collect_column_references_from_context(outer, &mut column_refs);
+                        Ok(())
+                    })
+                },
+                &mut |_, _| Ok(()),
+                q.input_box,
+            )
+            .unwrap();
+    }
+    if !column_refs.is_empty() {
+        return None;
+    }
+
+    Some((keys, predicates))
+}
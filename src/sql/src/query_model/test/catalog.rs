@@ -12,12 +12,12 @@ use crate::catalog::{
     CatalogComputeInstance, CatalogConfig, CatalogDatabase, CatalogError, CatalogItem,
     CatalogItemType, CatalogRole, CatalogSchema, CatalogTypeDetails, IdReference, SessionCatalog,
 };
-use crate::func::{Func, MZ_CATALOG_BUILTINS, MZ_INTERNAL_BUILTINS, PG_CATALOG_BUILTINS};
+use crate::func::{Func, SqlFunc, MZ_CATALOG_BUILTINS, MZ_INTERNAL_BUILTINS, PG_CATALOG_BUILTINS};
 use crate::names::{
     Aug, DatabaseId, FullObjectName, ObjectQualifiers, PartialObjectName, QualifiedObjectName,
     RawDatabaseSpecifier, ResolvedDatabaseSpecifier, SchemaId, SchemaSpecifier,
 };
-use crate::plan::StatementDesc;
+use crate::plan::{StatementDesc, UniqueConstraint};
 use crate::DEFAULT_SCHEMA;
 use chrono::MIN_DATETIME;
 use lazy_static::lazy_static;
@@ -108,6 +108,10 @@ impl CatalogItem for TestCatalogItem {
         }
     }
 
+    fn sql_func(&self) -> Option<&SqlFunc> {
+        None
+    }
+
     fn source_connector(&self) -> Result<&SourceConnector, CatalogError> {
         unimplemented!()
     }
@@ -139,6 +143,10 @@ impl CatalogItem for TestCatalogItem {
         unimplemented!()
     }
 
+    fn table_unique_constraints(&self) -> Option<&[UniqueConstraint]> {
+        unimplemented!()
+    }
+
     fn type_details(&self) -> Option<&CatalogTypeDetails<IdReference>> {
         unimplemented!()
     }
@@ -15,6 +15,7 @@ use crate::plan::{HirRelationExpr, StatementContext};
 use mz_expr_test_util::generate_explanation;
 use mz_lowertest::*;
 
+use crate::query_model::rewrite::apply_rules_by_name;
 use crate::query_model::Model;
 use catalog::TestCatalog;
 
@@ -26,8 +27,9 @@ use std::collections::HashMap;
 /// Tests to run on a Query Graph Model.
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, MzReflect)]
 enum Directive {
-    // TODO: support build apply=(<stuff>)
     /// Apply any number of specific changes to the model.
+    ///
+    /// The rewrite rules to apply are listed with `apply=(<rule>,...)`.
     Build,
     /// Decorrelate the model and convert it to a `MirRelationExpr`.
     Lower,
@@ -109,6 +111,8 @@ fn run_command(
 
     if matches!(directive, Directive::Opt | Directive::EndToEnd) {
         model.optimize();
+    } else if let (Directive::Build, Some(rules)) = (directive, args.get("apply")) {
+        apply_rules_by_name(&mut model, rules)?;
     }
 
     // TODO: allow printing multiple stages of the transformation of the query.
@@ -52,6 +52,10 @@ struct Args {
     /// Stop on first failure.
     #[clap(long)]
     fail_fast: bool,
+    /// Plan queries through the Query Graph Model optimization path, failing
+    /// those that it does not support.
+    #[clap(long)]
+    qgm_optimizations: bool,
 }
 
 #[tokio::main]
@@ -68,6 +72,7 @@ async fn main() {
         workers: args.workers,
         no_fail: args.no_fail,
         fail_fast: args.fail_fast,
+        qgm_optimizations: args.qgm_optimizations,
     };
 
     if args.rewrite_results {
@@ -294,6 +294,7 @@ pub(crate) struct Runner {
     clients: HashMap<String, tokio_postgres::Client>,
     server: materialized::Server,
     _temp_dir: TempDir,
+    qgm_optimizations: bool,
 }
 
 #[derive(Debug)]
@@ -576,13 +577,14 @@ impl Runner {
             now: SYSTEM_TIME.clone(),
         };
         let server = materialized::serve(mz_config).await?;
-        let client = connect(&server).await;
+        let client = connect(&server, config.qgm_optimizations).await;
 
         Ok(Runner {
             server,
             _temp_dir: temp_dir,
             client,
             clients: HashMap::new(),
+            qgm_optimizations: config.qgm_optimizations,
         })
     }
 
@@ -868,7 +870,7 @@ impl Runner {
             None => &self.client,
             Some(name) => {
                 if !self.clients.contains_key(name) {
-                    let client = connect(&self.server).await;
+                    let client = connect(&self.server, self.qgm_optimizations).await;
                     self.clients.insert(name.into(), client);
                 }
                 self.clients.get(name).unwrap()
@@ -917,7 +919,7 @@ impl Runner {
     }
 }
 
-async fn connect(server: &materialized::Server) -> tokio_postgres::Client {
+async fn connect(server: &materialized::Server, qgm_optimizations: bool) -> tokio_postgres::Client {
     let addr = server.local_addr();
     let (client, connection) = tokio_postgres::connect(
         &format!("host={} port={} user=materialize", addr.ip(), addr.port()),
@@ -931,6 +933,18 @@ async fn connect(server: &materialized::Server) -> tokio_postgres::Client {
             eprintln!("connection error: {}", e);
         }
     });
+    if qgm_optimizations {
+        // Queries that the QGM path does not support fail rather than
+        // silently taking the default path, so that they are not mistaken for
+        // ones that the QGM path handles correctly.
+        client
+            .batch_execute(
+                "SET qgm_optimizations_experimental = true; \
+                 SET qgm_optimizations_fallback_experimental = false",
+            )
+            .await
+            .unwrap();
+    }
     client
 }
 
@@ -945,6 +959,7 @@ pub struct RunConfig<'a> {
     pub workers: usize,
     pub no_fail: bool,
     pub fail_fast: bool,
+    pub qgm_optimizations: bool,
 }
 
 fn print_record(config: &RunConfig<'_>, record: &Record) {
@@ -0,0 +1,303 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Decoding of columnar files (Parquet and ORC) read by byte stream sources.
+//!
+//! Columnar files cannot be decoded incrementally, as the metadata needed to
+//! interpret them lives in the footer. Decoding therefore happens in two
+//! stages: the worker that reads a file buffers it until the file's EOF and
+//! then hands out its chunks (Parquet row groups or ORC stripes) to all
+//! workers, which decode them in parallel. Each worker is only sent the parts
+//! of the file that hold its chunks and the file's footer.
+
+use std::collections::{BTreeMap, HashSet};
+
+use timely::dataflow::channels::pact::{Exchange, Pipeline};
+use timely::dataflow::operators::Operator;
+use timely::dataflow::{Scope, Stream};
+
+use mz_dataflow_types::sources::encoding::{OrcEncoding, ParquetEncoding};
+use mz_dataflow_types::sources::IncludedColumnSource;
+use mz_dataflow_types::{DecodeError, LinearOperator};
+use mz_expr::PartitionId;
+use mz_interchange::columnar::{FileLayout, PartialFile};
+use mz_interchange::orc::{self, OrcSchema};
+use mz_interchange::parquet::{self, ParquetSchema};
+use mz_ore::cast::CastFrom;
+use mz_repr::{MessagePayload, Row, Timestamp};
+
+use super::metrics::DecodeMetrics;
+use super::to_metadata_row;
+use crate::source::{DecodeResult, SourceOutput};
+
+/// A decoder for a columnar file format.
+trait ColumnarDecoder: 'static {
+    /// The name of the format, as used in metrics and error messages.
+    const FORMAT: &'static str;
+    /// The name of the format, as used in operator names.
+    const OP_NAME: &'static str;
+
+    /// Returns the layout of `file`.
+    fn layout(file: &[u8]) -> Result<FileLayout, anyhow::Error>;
+
+    /// Decodes the rows of the given chunks of `file`, in order, passing each
+    /// to `emit`.
+    fn decode(
+        &mut self,
+        file: &mut PartialFile,
+        chunks: &[usize],
+        emit: &mut dyn FnMut(Row),
+    ) -> Result<(), anyhow::Error>;
+}
+
+impl ColumnarDecoder for parquet::Decoder {
+    const FORMAT: &'static str = "parquet";
+    const OP_NAME: &'static str = "Parquet";
+
+    fn layout(file: &[u8]) -> Result<FileLayout, anyhow::Error> {
+        parquet::layout(file)
+    }
+
+    fn decode(
+        &mut self,
+        file: &mut PartialFile,
+        chunks: &[usize],
+        emit: &mut dyn FnMut(Row),
+    ) -> Result<(), anyhow::Error> {
+        parquet::Decoder::decode(self, file, chunks, emit)
+    }
+}
+
+impl ColumnarDecoder for orc::Decoder {
+    const FORMAT: &'static str = "orc";
+    const OP_NAME: &'static str = "Orc";
+
+    fn layout(file: &[u8]) -> Result<FileLayout, anyhow::Error> {
+        orc::layout(file)
+    }
+
+    fn decode(
+        &mut self,
+        file: &mut PartialFile,
+        chunks: &[usize],
+        emit: &mut dyn FnMut(Row),
+    ) -> Result<(), anyhow::Error> {
+        orc::Decoder::decode(self, file, chunks, emit)
+    }
+}
+
+/// The chunks of a single columnar file that one worker is to decode.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+struct ChunkAssignment {
+    /// The worker that decodes the chunks.
+    worker: usize,
+    /// The parts of the file needed to decode the chunks.
+    file: PartialFile,
+    /// The indexes of the chunks to decode, each with the position of its
+    /// first row.
+    chunks: Vec<(usize, i64)>,
+    upstream_time_millis: Option<i64>,
+    partition: PartitionId,
+}
+
+/// Decodes a stream of Parquet files, as produced by file and S3 sources.
+///
+/// Only the columns demanded by `operators` are read from the files. Unlike
+/// other decoders, this leaves `operators` in place, as the columns that are
+/// not read are decoded as `NULL` rather than removed.
+pub fn render_decode_parquet<G>(
+    stream: &Stream<G, SourceOutput<(), MessagePayload>>,
+    encoding: ParquetEncoding,
+    metadata_items: Vec<IncludedColumnSource>,
+    operators: &Option<LinearOperator>,
+    metrics: DecodeMetrics,
+) -> Stream<G, DecodeResult>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let schema = ParquetSchema::from_json(&encoding.schema)
+        .expect("Schema was verified to be correct during planning");
+    let demanded = demanded_columns(schema.fields.len(), operators);
+    let decoder = parquet::Decoder::new(schema, demanded);
+    render_decode_columnar(stream, decoder, metadata_items, metrics)
+}
+
+/// Decodes a stream of ORC files, as produced by file and S3 sources.
+///
+/// As for [`render_decode_parquet`], only the columns demanded by
+/// `operators` are read from the files.
+pub fn render_decode_orc<G>(
+    stream: &Stream<G, SourceOutput<(), MessagePayload>>,
+    encoding: OrcEncoding,
+    metadata_items: Vec<IncludedColumnSource>,
+    operators: &Option<LinearOperator>,
+    metrics: DecodeMetrics,
+) -> Stream<G, DecodeResult>
+where
+    G: Scope<Timestamp = Timestamp>,
+{
+    let schema = OrcSchema::from_json(&encoding.schema)
+        .expect("Schema was verified to be correct during planning");
+    let demanded = demanded_columns(schema.fields.len(), operators);
+    let decoder = orc::Decoder::new(schema, demanded);
+    render_decode_columnar(stream, decoder, metadata_items, metrics)
+}
+
+fn render_decode_columnar<G, D>(
+    stream: &Stream<G, SourceOutput<(), MessagePayload>>,
+    mut decoder: D,
+    metadata_items: Vec<IncludedColumnSource>,
+    metrics: DecodeMetrics,
+) -> Stream<G, DecodeResult>
+where
+    G: Scope<Timestamp = Timestamp>,
+    D: ColumnarDecoder,
+{
+    let peers = stream.scope().peers();
+
+    // Historically, non-delimited sources have their offset start at 1.
+    let mut next_position = 1;
+    let mut file_buf = vec![];
+    let assemble_name = format!("{}Assemble", D::OP_NAME);
+    let assignments = stream.unary(Pipeline, &assemble_name, move |_, _| {
+        move |input, output| {
+            input.for_each(|cap, data| {
+                let mut session = output.session(&cap);
+                for SourceOutput {
+                    value,
+                    upstream_time_millis,
+                    partition,
+                    ..
+                } in data.iter()
+                {
+                    match value {
+                        MessagePayload::Data(data) => file_buf.extend_from_slice(data),
+                        MessagePayload::EOF => {
+                            let file = std::mem::take(&mut file_buf);
+                            let layout = match D::layout(&file) {
+                                Ok(layout) => layout,
+                                // Hand the whole file to a single worker,
+                                // which will fail to decode it and report the
+                                // error in place of the file's rows.
+                                Err(_) => {
+                                    session.give(ChunkAssignment {
+                                        worker: 0,
+                                        file: PartialFile::whole(file),
+                                        chunks: vec![(0, next_position)],
+                                        upstream_time_millis: *upstream_time_millis,
+                                        partition: partition.clone(),
+                                    });
+                                    next_position += 1;
+                                    continue;
+                                }
+                            };
+                            let mut assignments = BTreeMap::new();
+                            for (i, chunk) in layout.chunks.iter().enumerate() {
+                                let worker = i % peers;
+                                assignments
+                                    .entry(worker)
+                                    .or_insert_with(Vec::new)
+                                    .push((i, next_position));
+                                next_position += i64::cast_from(chunk.num_rows);
+                            }
+                            for (worker, chunks) in assignments {
+                                let indexes: Vec<_> = chunks.iter().map(|(i, _)| *i).collect();
+                                session.give(ChunkAssignment {
+                                    worker,
+                                    file: layout.slice(&file, &indexes),
+                                    chunks,
+                                    upstream_time_millis: *upstream_time_millis,
+                                    partition: partition.clone(),
+                                });
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    assignments.unary(
+        Exchange::new(|a: &ChunkAssignment| u64::cast_from(a.worker)),
+        &format!("{}Decode", D::OP_NAME),
+        move |_, _| {
+            move |input, output| {
+                let mut n_errors = 0;
+                let mut n_successes = 0;
+                input.for_each(|cap, data| {
+                    let mut session = output.session(&cap);
+                    for assignment in data.iter() {
+                        let mut file = assignment.file.clone();
+                        for (chunk, start) in &assignment.chunks {
+                            let mut position = *start;
+                            let mut results = vec![];
+                            let result = decoder.decode(&mut file, &[*chunk], &mut |row| {
+                                results.push((position, Ok(row)));
+                                position += 1;
+                            });
+                            if let Err(e) = result {
+                                results.push((
+                                    position,
+                                    Err(DecodeError::Text(format!(
+                                        "Failed to decode {} file: {:#}",
+                                        D::FORMAT,
+                                        e
+                                    ))),
+                                ));
+                            }
+                            for (position, value) in results {
+                                if value.is_ok() {
+                                    n_successes += 1;
+                                } else {
+                                    n_errors += 1;
+                                }
+                                let metadata = to_metadata_row(
+                                    &metadata_items,
+                                    assignment.partition.clone(),
+                                    position,
+                                    assignment.upstream_time_millis,
+                                    None,
+                                );
+                                session.give(DecodeResult {
+                                    key: None,
+                                    value: Some(value),
+                                    position,
+                                    upstream_time_millis: assignment.upstream_time_millis,
+                                    partition: assignment.partition.clone(),
+                                    metadata,
+                                });
+                            }
+                        }
+                    }
+                });
+                if n_errors > 0 {
+                    metrics.count_format(D::FORMAT, false, n_errors);
+                }
+                if n_successes > 0 {
+                    metrics.count_format(D::FORMAT, true, n_successes);
+                }
+            }
+        },
+    )
+}
+
+/// Determines which of the first `n_cols` columns of a source must be read to
+/// evaluate `operators`.
+fn demanded_columns(n_cols: usize, operators: &Option<LinearOperator>) -> Vec<bool> {
+    let demanded: Option<HashSet<usize>> = operators.as_ref().map(|o| {
+        o.predicates
+            .iter()
+            .flat_map(|p| p.support())
+            .chain(o.projection.iter().copied())
+            .collect()
+    });
+    (0..n_cols)
+        .map(|c| demanded.as_ref().map(|d| d.contains(&c)).unwrap_or(true))
+        .collect()
+}
@@ -0,0 +1,295 @@
+// Copyright Materialize, Inc. and contributors. All rights reserved.
+//
+// Use of this software is governed by the Business Source License
+// included in the LICENSE file.
+//
+// As of the Change Date specified in that file, in accordance with
+// the Business Source License, use of this software will be governed
+// by the Apache License, Version 2.0.
+
+//! Handling of messages that fail to decode, as configured by the
+//! `ON DECODE ERROR` clause of a source.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use rdkafka::message::{Header, OwnedHeaders};
+use rdkafka::producer::{BaseRecord, ThreadedProducer};
+use rdkafka::ClientConfig;
+use timely::dataflow::channels::pact::Pipeline;
+use timely::dataflow::operators::Operator;
+use timely::dataflow::{Scope, Stream};
+use tracing::warn;
+
+use mz_dataflow_types::sources::KafkaSourceConnector;
+use mz_dataflow_types::DecodeError;
+use mz_expr::{GlobalId, PartitionId, SourceInstanceId};
+use mz_kafka_util::client::MzClientContext;
+use mz_repr::{Datum, Row};
+
+use crate::source::DecodeResult;
+use crate::{Logger, StorageEvent};
+
+/// The number of dropped messages that each worker keeps recorded in the
+/// `mz_source_decode_errors` log for a source.
+const MAX_LOGGED_DECODE_ERRORS: usize = 1_000;
+
+/// Drops the messages in `results` that failed to decode, recording each of
+/// them in the `mz_source_decode_errors` log.
+///
+/// Only the [`MAX_LOGGED_DECODE_ERRORS`] most recent errors are kept in the
+/// log: older errors are retracted as new ones are recorded, and the
+/// remaining ones are retracted when the operator is dropped.
+pub fn render_drop_decode_errors<G>(
+    results: &Stream<G, DecodeResult>,
+    source_id: SourceInstanceId,
+    logger: Option<Logger>,
+) -> Stream<G, DecodeResult>
+where
+    G: Scope,
+{
+    let mut log = DecodeErrorLog {
+        source_id,
+        logger,
+        logged: VecDeque::new(),
+    };
+    let mut buffer = vec![];
+    results.unary(Pipeline, "DropDecodeErrors", move |_, _| {
+        move |input, output| {
+            input.for_each(|cap, data| {
+                data.swap(&mut buffer);
+                let mut session = output.session(&cap);
+                for result in buffer.drain(..) {
+                    match decode_error(&result) {
+                        Some(error) => log.record(&result.partition, result.position, error),
+                        None => session.give(result),
+                    }
+                }
+            });
+        }
+    })
+}
+
+/// Returns the description of the error that `result` failed to decode
+/// with, if any.
+pub fn decode_error(result: &DecodeResult) -> Option<String> {
+    match (&result.key, &result.value) {
+        (Some(Err(DecodeError::Text(e))), _) => Some(format!("key: {}", e)),
+        (_, Some(Err(DecodeError::Text(e)))) => Some(e.clone()),
+        _ => None,
+    }
+}
+
+/// The errors that a source has recorded in `mz_source_decode_errors`, from
+/// oldest to newest.
+struct DecodeErrorLog {
+    source_id: SourceInstanceId,
+    logger: Option<Logger>,
+    logged: VecDeque<(Option<String>, i64, String)>,
+}
+
+impl DecodeErrorLog {
+    fn record(&mut self, partition: &PartitionId, offset: i64, error: String) {
+        if self.logger.is_none() {
+            return;
+        }
+        if self.logged.len() == MAX_LOGGED_DECODE_ERRORS {
+            let oldest = self.logged.pop_front().unwrap();
+            self.log(oldest, -1);
+        }
+        let entry = (partition.into(), offset, error);
+        self.log(entry.clone(), 1);
+        self.logged.push_back(entry);
+    }
+
+    fn log(&mut self, (partition_id, offset, error): (Option<String>, i64, String), diff: i64) {
+        if let Some(logger) = self.logger.as_mut() {
+            logger.log(StorageEvent::SourceDecodeError {
+                source_id: self.source_id,
+                partition_id,
+                offset,
+                error,
+                diff,
+            });
+        }
+    }
+}
+
+impl Drop for DecodeErrorLog {
+    fn drop(&mut self) {
+        while let Some(entry) = self.logged.pop_front() {
+            self.log(entry, -1);
+        }
+    }
+}
+
+/// Writes messages that failed to decode to where the `ON DECODE ERROR`
+/// clause of their source redirects them.
+///
+/// Writes are not deduplicated. Messages that are read again, because
+/// `materialized` restarts or the source is rendered again (e.g. by another
+/// index on it), are written again.
+pub enum DeadLetterWriter {
+    Topic(DeadLetterTopic),
+    Table(DeadLetterTable),
+}
+
+impl DeadLetterWriter {
+    /// Writes the key and value of a message that failed to decode, along
+    /// with where the message came from and why it could not be decoded.
+    pub fn write(
+        &mut self,
+        key: Option<&[u8]>,
+        value: Option<&[u8]>,
+        partition: &PartitionId,
+        offset: i64,
+        error: &str,
+    ) {
+        match self {
+            DeadLetterWriter::Topic(topic) => topic.write(key, value, partition, offset, error),
+            DeadLetterWriter::Table(table) => table.write(key, value, partition, offset, error),
+        }
+    }
+}
+
+/// Writes Kafka messages that failed to decode to a dead-letter topic on the
+/// cluster they were read from.
+///
+/// Messages are written on a best-effort basis: failures to write a message
+/// are logged, and the message is dropped regardless.
+pub struct DeadLetterTopic {
+    source_id: SourceInstanceId,
+    topic: String,
+    config: ClientConfig,
+    /// Created when the first message is written, so that sources without
+    /// decoding errors never connect a producer.
+    producer: Option<ThreadedProducer<MzClientContext>>,
+}
+
+impl DeadLetterTopic {
+    pub fn new(
+        source_id: SourceInstanceId,
+        connector: &KafkaSourceConnector,
+        topic: String,
+    ) -> Self {
+        let mut config = ClientConfig::new();
+        config.set("bootstrap.servers", &connector.addrs.to_string());
+        // Use the same security settings as the source's consumer.
+        for (k, v) in &connector.config_options {
+            config.set(k, v);
+        }
+        DeadLetterTopic {
+            source_id,
+            topic,
+            config,
+            producer: None,
+        }
+    }
+
+    /// Writes the key and value of a message that failed to decode, with
+    /// headers that describe where the message came from and why it could
+    /// not be decoded.
+    fn write(
+        &mut self,
+        key: Option<&[u8]>,
+        value: Option<&[u8]>,
+        partition: &PartitionId,
+        offset: i64,
+        error: &str,
+    ) {
+        if self.producer.is_none() {
+            match self.config.create_with_context(MzClientContext) {
+                Ok(producer) => self.producer = Some(producer),
+                Err(e) => {
+                    warn!(
+                        "failed to create producer for dead-letter topic {}: {}",
+                        self.topic, e
+                    );
+                    return;
+                }
+            }
+        }
+        let producer = self.producer.as_ref().unwrap();
+
+        let source_id = self.source_id.source_id.to_string();
+        let partition = partition.to_string();
+        let offset = offset.to_string();
+        let headers = OwnedHeaders::new()
+            .insert(Header {
+                key: "materialize-source-id",
+                value: Some(source_id.as_bytes()),
+            })
+            .insert(Header {
+                key: "materialize-partition",
+                value: Some(partition.as_bytes()),
+            })
+            .insert(Header {
+                key: "materialize-offset",
+                value: Some(offset.as_bytes()),
+            })
+            .insert(Header {
+                key: "materialize-error",
+                value: Some(error.as_bytes()),
+            });
+        let mut record = BaseRecord::<[u8], [u8]>::to(&self.topic).headers(headers);
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+        if let Some(value) = value {
+            record = record.payload(value);
+        }
+        if let Err((e, _)) = producer.send(record) {
+            warn!(
+                "failed to write message to dead-letter topic {}: {}",
+                self.topic, e
+            );
+        }
+    }
+}
+
+/// Writes messages that failed to decode to a table, as rows of the type
+/// described by `DecodeErrorPolicy::dead_letter_table_type`.
+///
+/// The rows are buffered in the storage state of the worker, which forwards
+/// them to the coordinator to be appended to the table.
+pub struct DeadLetterTable {
+    source_id: SourceInstanceId,
+    table_id: GlobalId,
+    buffer: Rc<RefCell<Vec<(GlobalId, Row)>>>,
+}
+
+impl DeadLetterTable {
+    pub fn new(
+        source_id: SourceInstanceId,
+        table_id: GlobalId,
+        buffer: Rc<RefCell<Vec<(GlobalId, Row)>>>,
+    ) -> Self {
+        DeadLetterTable {
+            source_id,
+            table_id,
+            buffer,
+        }
+    }
+
+    fn write(
+        &mut self,
+        key: Option<&[u8]>,
+        value: Option<&[u8]>,
+        partition: &PartitionId,
+        offset: i64,
+        error: &str,
+    ) {
+        let source_id = self.source_id.source_id.to_string();
+        let partition_id: Option<String> = partition.into();
+        let row = Row::pack_slice(&[
+            Datum::String(&source_id),
+            partition_id.as_deref().map_or(Datum::Null, Datum::String),
+            Datum::Int64(offset),
+            key.map_or(Datum::Null, Datum::Bytes),
+            value.map_or(Datum::Null, Datum::Bytes),
+            Datum::String(error),
+        ]);
+        self.buffer.borrow_mut().push((self.table_id, row));
+    }
+}
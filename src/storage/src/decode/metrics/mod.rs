@@ -40,8 +40,13 @@ impl DecodeMetrics {
                 PreDelimitedFormat::Text => "text",
                 PreDelimitedFormat::Regex(..) => "regex",
                 PreDelimitedFormat::Protobuf(..) => "protobuf",
+                PreDelimitedFormat::Json(..) => "json",
             },
         };
+        self.count_format(format_label, success, n);
+    }
+
+    pub(crate) fn count_format(&self, format_label: &str, success: bool, n: usize) {
         let success_label = if success { "success" } else { "error" };
         self.events_read
             .with_label_values(&[format_label, success_label])
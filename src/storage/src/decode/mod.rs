@@ -25,12 +25,13 @@ use timely::scheduling::SyncActivator;
 
 use mz_dataflow_types::{
     sources::{
-        encoding::{AvroEncoding, AvroOcfEncoding, DataEncoding, RegexEncoding},
+        encoding::{AvroEncoding, AvroOcfEncoding, DataEncoding, JsonEncoding, RegexEncoding},
         IncludedColumnSource, SourceEnvelope,
     },
     DecodeError, LinearOperator,
 };
 use mz_interchange::avro::ConfluentAvroResolver;
+use mz_interchange::json::{JsonDecoder, JsonSourceSchema};
 use mz_repr::Datum;
 use mz_repr::{Diff, Row, Timestamp};
 use tracing::error;
@@ -41,8 +42,14 @@ use self::protobuf::ProtobufDecoderState;
 use crate::source::{DecodeResult, SourceOutput};
 use metrics::DecodeMetrics;
 
+pub use dead_letter::{
+    render_drop_decode_errors, DeadLetterTable, DeadLetterTopic, DeadLetterWriter,
+};
+
 mod avro;
+mod columnar;
 mod csv;
+mod dead_letter;
 pub mod metrics;
 mod protobuf;
 
@@ -122,6 +129,7 @@ pub(crate) enum PreDelimitedFormat {
     Text,
     Regex(Regex, Row),
     Protobuf(ProtobufDecoderState),
+    Json(JsonDecoder),
 }
 
 impl PreDelimitedFormat {
@@ -149,6 +157,10 @@ impl PreDelimitedFormat {
                 Ok(Some(row_buf.clone()))
             }
             PreDelimitedFormat::Protobuf(pb) => pb.get_value(bytes).transpose(),
+            PreDelimitedFormat::Json(json) => json
+                .decode(bytes)
+                .map(Some)
+                .map_err(|e| DecodeError::Text(format!("Failed to decode JSON: {:#}", e))),
         }
     }
 }
@@ -258,6 +270,7 @@ fn get_decoder(
         }
         DataEncoding::Text
         | DataEncoding::Bytes
+        | DataEncoding::Json(_)
         | DataEncoding::Protobuf(_)
         | DataEncoding::Regex(_) => {
             let after_delimiting = match encoding {
@@ -270,6 +283,13 @@ fn get_decoder(
                                     client creation in purification.",
                     ))
                 }
+                DataEncoding::Json(JsonEncoding { schema }) => {
+                    let schema = schema.map(|schema| {
+                        JsonSourceSchema::from_json(&schema)
+                            .expect("Schema was verified to be correct during planning")
+                    });
+                    PreDelimitedFormat::Json(JsonDecoder::new(schema))
+                }
                 DataEncoding::Bytes => PreDelimitedFormat::Bytes,
                 DataEncoding::Text => PreDelimitedFormat::Text,
                 _ => unreachable!(),
@@ -300,6 +320,12 @@ fn get_decoder(
                 metrics,
             }
         }
+        DataEncoding::Parquet(_) => {
+            unreachable!("Parquet sources are decoded by `render_decode_parquet`.")
+        }
+        DataEncoding::Orc(_) => {
+            unreachable!("ORC sources are decoded by `render_decode_orc`.")
+        }
         DataEncoding::Postgres => {
             unreachable!("Postgres sources should not go through the general decoding path.")
         }
@@ -348,6 +374,8 @@ pub fn render_decode_delimited<G>(
     // `None`.
     operators: &mut Option<LinearOperator>,
     metrics: DecodeMetrics,
+    // Where to write messages that fail to decode, if anywhere.
+    mut dead_letters: Option<DeadLetterWriter>,
 ) -> (Stream<G, DecodeResult>, Option<Box<dyn Any>>)
 where
     G: Scope,
@@ -377,8 +405,8 @@ where
             input.for_each(|cap, data| {
                 let mut session = output.session(&cap);
                 for SourceOutput {
-                    key,
-                    value,
+                    key: raw_key,
+                    value: raw_value,
                     position,
                     upstream_time_millis,
                     partition,
@@ -387,9 +415,9 @@ where
                 {
                     let key = key_decoder
                         .as_mut()
-                        .and_then(|decoder| try_decode(decoder, key.as_ref()));
+                        .and_then(|decoder| try_decode(decoder, raw_key.as_ref()));
 
-                    let value = try_decode(&mut value_decoder, value.as_ref());
+                    let value = try_decode(&mut value_decoder, raw_value.as_ref());
 
                     if matches!(&key, Some(Err(_))) || matches!(&value, Some(Err(_))) {
                         n_errors += 1;
@@ -397,7 +425,7 @@ where
                         n_successes += 1;
                     }
 
-                    session.give(DecodeResult {
+                    let result = DecodeResult {
                         key,
                         value,
                         position: *position,
@@ -410,7 +438,19 @@ where
                             *upstream_time_millis,
                             headers.as_deref(),
                         ),
-                    });
+                    };
+                    if let Some(dead_letters) = dead_letters.as_mut() {
+                        if let Some(error) = dead_letter::decode_error(&result) {
+                            dead_letters.write(
+                                raw_key.as_deref(),
+                                raw_value.as_deref(),
+                                partition,
+                                *position,
+                                &error,
+                            );
+                        }
+                    }
+                    session.give(result);
                 }
             });
             // Matching historical practice, we only log metrics on the value decoder.
@@ -454,6 +494,25 @@ pub fn render_decode<G>(
 where
     G: Scope<Timestamp = Timestamp>,
 {
+    match value_encoding {
+        DataEncoding::Parquet(encoding) => {
+            let results = columnar::render_decode_parquet(
+                stream,
+                encoding,
+                metadata_items,
+                operators,
+                metrics,
+            );
+            return (results, None);
+        }
+        DataEncoding::Orc(encoding) => {
+            let results =
+                columnar::render_decode_orc(stream, encoding, metadata_items, operators, metrics);
+            return (results, None);
+        }
+        _ => (),
+    }
+
     let op_name = format!("{}Decode", value_encoding.op_name());
 
     let mut value_decoder = get_decoder(value_encoding, debug_name, operators, false, metrics);
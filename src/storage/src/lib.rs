@@ -54,4 +54,17 @@ pub enum StorageEvent {
         /// Difference between the previous timestamp and current highest timestamp we've seen
         timestamp: i64,
     },
+    /// Tracks a message that a source dropped because it failed to decode
+    SourceDecodeError {
+        /// Source identifier
+        source_id: SourceInstanceId,
+        /// Partition identifier
+        partition_id: Option<String>,
+        /// Offset of the message within its partition
+        offset: i64,
+        /// Description of why the message failed to decode
+        error: String,
+        /// `1` when the message is dropped, `-1` when the source is dropped
+        diff: i64,
+    },
 }
@@ -38,6 +38,9 @@ use mz_repr::{Diff, Row, RowPacker, Timestamp};
 use crate::decode::decode_cdcv2;
 use crate::decode::render_decode;
 use crate::decode::render_decode_delimited;
+use crate::decode::{
+    render_drop_decode_errors, DeadLetterTable, DeadLetterTopic, DeadLetterWriter,
+};
 use crate::render::envelope_none;
 use crate::render::envelope_none::PersistentEnvelopeNoneConfig;
 use crate::source::timestamp::{AssignedTimestamp, SourceTimestamp};
@@ -233,6 +236,7 @@ where
             connector,
             encoding,
             envelope,
+            decode_errors,
             metadata_columns,
             ts_frequency,
             timeline: _,
@@ -283,7 +287,7 @@ where
                 timestamp_frequency: ts_frequency,
                 worker_id: scope.index(),
                 worker_count: scope.peers(),
-                logger: materialized_logging,
+                logger: materialized_logging.clone(),
                 encoding: encoding.clone(),
                 now: storage_state.now.clone(),
                 base_metrics: &storage_state.source_metrics,
@@ -411,6 +415,24 @@ where
                         needed_tokens.push(Rc::new(token));
                         (oks, None)
                     } else {
+                        let dead_letters = match (&decode_errors, &connector) {
+                            (
+                                DecodeErrorPolicy::RedirectToTopic { topic },
+                                ExternalSourceConnector::Kafka(kc),
+                            ) => Some(DeadLetterWriter::Topic(DeadLetterTopic::new(
+                                uid,
+                                kc,
+                                topic.clone(),
+                            ))),
+                            (DecodeErrorPolicy::RedirectToTable { id }, _) => {
+                                Some(DeadLetterWriter::Table(DeadLetterTable::new(
+                                    uid,
+                                    *id,
+                                    Rc::clone(&storage_state.dead_letters),
+                                )))
+                            }
+                            _ => None,
+                        };
                         let (results, extra_token) = match ok_source {
                             SourceType::Delimited(source) => render_decode_delimited(
                                 &source,
@@ -421,6 +443,7 @@ where
                                 metadata_columns,
                                 &mut linear_operators,
                                 storage_state.decode_metrics.clone(),
+                                dead_letters,
                             ),
                             SourceType::ByteStream(source) => render_decode(
                                 &source,
@@ -435,6 +458,22 @@ where
                             needed_tokens.push(Rc::new(tok));
                         }
 
+                        // Unless the source fails on decoding errors, drop the
+                        // messages that failed to decode before they reach the
+                        // envelope and become errors.
+                        let results = match decode_errors {
+                            DecodeErrorPolicy::Fail => results,
+                            DecodeErrorPolicy::Skip
+                            | DecodeErrorPolicy::RedirectToTopic { .. }
+                            | DecodeErrorPolicy::RedirectToTable { .. } => {
+                                render_drop_decode_errors(
+                                    &results,
+                                    uid,
+                                    materialized_logging.clone(),
+                                )
+                            }
+                        };
+
                         // render envelopes
                         match &envelope {
                             SourceEnvelope::Debezium(dbz_envelope) => {
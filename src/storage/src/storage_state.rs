@@ -71,6 +71,9 @@ pub struct StorageState {
     pub persisted_sources: PersistedSourceManager,
     /// Decoding metrics reported by all dataflows.
     pub decode_metrics: DecodeMetrics,
+    /// Rows describing messages that failed to decode, to be appended to the
+    /// tables that their sources redirect them to.
+    pub dead_letters: Rc<RefCell<Vec<(GlobalId, Row)>>>,
     /// Handle to the persistence runtime. None if disabled.
     pub persist: Option<RuntimeClient>,
     /// Tracks the conditional write frontiers we have reported.
@@ -468,6 +471,20 @@ impl<'a, A: Allocate, B: StorageCapture> ActiveStorageState<'a, A, B> {
         }
     }
 
+    /// Forwards the rows of messages that failed to decode to the
+    /// coordinator, which appends them to their tables.
+    pub fn report_dead_letters(&mut self) {
+        let mut letters_by_table = HashMap::<_, Vec<_>>::new();
+        for (id, row) in self.storage_state.dead_letters.borrow_mut().drain(..) {
+            letters_by_table.entry(id).or_default().push(row);
+        }
+        if !letters_by_table.is_empty() {
+            self.send_storage_response(StorageResponse::DeadLetters(
+                letters_by_table.into_iter().collect(),
+            ));
+        }
+    }
+
     /// Send a response to the coordinator.
     fn send_storage_response(&self, response: StorageResponse) {
         // Ignore send errors because the coordinator is free to ignore our
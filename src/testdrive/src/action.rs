@@ -474,6 +474,9 @@ pub(crate) async fn build(
                     "avro-ocf-verify" => {
                         Box::new(avro_ocf::build_verify(builtin).map_err(wrap_err)?)
                     }
+                    "avro-ocf-verify-manifest" => {
+                        Box::new(avro_ocf::build_verify_manifest(builtin).map_err(wrap_err)?)
+                    }
                     "file-append" => Box::new(file::build_append(builtin).map_err(wrap_err)?),
                     "file-delete" => Box::new(file::build_delete(builtin).map_err(wrap_err)?),
                     "http-request" => Box::new(http::build_request(builtin).map_err(wrap_err)?),
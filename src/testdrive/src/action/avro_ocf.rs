@@ -8,12 +8,12 @@
 // by the Apache License, Version 2.0.
 
 use std::ffi::OsString;
-use std::fs::{File, OpenOptions};
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
 use std::os::unix::ffi::OsStringExt;
-use std::path::{self, PathBuf};
+use std::path::{self, Path, PathBuf};
 
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
 use async_trait::async_trait;
 
 use mz_ore::retry::Retry;
@@ -153,24 +153,7 @@ impl Action for VerifyAction {
     }
 
     async fn redo(&self, state: &mut State) -> Result<ControlFlow, anyhow::Error> {
-        let path = Retry::default()
-            .max_duration(state.default_timeout)
-            .retry_async_canceling(|_| async {
-                let row = state
-                    .pgclient
-                    .query_one(
-                        "SELECT path FROM mz_catalog_names
-                     JOIN mz_avro_ocf_sinks ON global_id = sink_id
-                     WHERE name = $1",
-                        &[&self.sink],
-                    )
-                    .await
-                    .context("querying materialize")?;
-                let bytes: Vec<u8> = row.get("path");
-                Ok::<_, anyhow::Error>(PathBuf::from(OsString::from_vec(bytes)))
-            })
-            .await
-            .context("retrieving path")?;
+        let path = sink_path(state, &self.sink).await?;
 
         println!("Verifying results in file {}", path.display());
 
@@ -198,3 +181,152 @@ impl Action for VerifyAction {
         Ok(ControlFlow::Continue)
     }
 }
+
+pub struct VerifyManifestAction {
+    sink: String,
+    min_files: usize,
+    expected: Vec<String>,
+}
+
+pub fn build_verify_manifest(
+    mut cmd: BuiltinCommand,
+) -> Result<VerifyManifestAction, anyhow::Error> {
+    let sink = cmd.args.string("sink")?;
+    let min_files = cmd.args.opt_parse("min-files")?.unwrap_or(1);
+    let expected = cmd.input;
+    cmd.args.done()?;
+    if sink.contains(path::MAIN_SEPARATOR) {
+        // The goal isn't security, but preventing mistakes.
+        bail!("separators in file sink names are forbidden");
+    }
+    Ok(VerifyManifestAction {
+        sink,
+        min_files,
+        expected,
+    })
+}
+
+#[async_trait]
+impl Action for VerifyManifestAction {
+    async fn undo(&self, _state: &mut State) -> Result<(), anyhow::Error> {
+        Ok(())
+    }
+
+    async fn redo(&self, state: &mut State) -> Result<ControlFlow, anyhow::Error> {
+        let path = sink_path(state, &self.sink).await?;
+        let manifest_path = path.with_extension("manifest");
+
+        println!(
+            "Verifying files listed in manifest {}",
+            manifest_path.display()
+        );
+
+        // Files are completed asynchronously, so wait for the manifest to
+        // list them.
+        Retry::default()
+            .max_duration(state.default_timeout)
+            .retry_async_canceling(|_| async {
+                tokio::task::block_in_place(|| self.verify(&manifest_path, state))
+            })
+            .await
+    }
+}
+
+impl VerifyManifestAction {
+    /// Checks that the manifest lists at least `min_files` contiguous files,
+    /// whose records are the expected ones.
+    fn verify(&self, manifest_path: &Path, state: &State) -> Result<ControlFlow, anyhow::Error> {
+        let manifest = fs::read_to_string(manifest_path)
+            .with_context(|| format!("reading manifest {}", manifest_path.display()))?;
+        let entries = manifest
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<serde_json::Value>, _>>()
+            .context("parsing manifest")?;
+        if entries.len() < self.min_files {
+            bail!(
+                "manifest lists {} files, but expected at least {}",
+                entries.len(),
+                self.min_files
+            );
+        }
+
+        let mut schema = None;
+        let mut actual = vec![];
+        let mut prev_upper = None;
+        for entry in entries {
+            let field = |name| {
+                entry[name]
+                    .as_u64()
+                    .ok_or_else(|| anyhow!("manifest entry {} has no {}", entry, name))
+            };
+            let (lower, upper, records) = (field("lower")?, field("upper")?, field("records")?);
+            if lower >= upper {
+                bail!("manifest entry {} has an empty time range", entry);
+            }
+            if let Some(prev_upper) = prev_upper {
+                if lower != prev_upper {
+                    bail!(
+                        "manifest entry {} does not start at the previous file's upper {}",
+                        entry,
+                        prev_upper
+                    );
+                }
+            }
+            prev_upper = Some(upper);
+
+            let path = entry["path"]
+                .as_str()
+                .ok_or_else(|| anyhow!("manifest entry {} has no path", entry))?;
+            let file = File::open(path).with_context(|| format!("reading sink file {}", path))?;
+            let reader = Reader::new(file).context("creating avro reader")?;
+            schema.get_or_insert_with(|| reader.writer_schema().clone());
+            let values = reader
+                .map(|res| res.map(|val| (None, Some(val))))
+                .collect::<Result<Vec<_>, _>>()
+                .context("reading avro values from file")?;
+            if u64::try_from(values.len())? != records {
+                bail!(
+                    "sink file {} holds {} records, but the manifest lists {}",
+                    path,
+                    values.len(),
+                    records
+                );
+            }
+            actual.extend(values);
+        }
+
+        let schema = schema.expect("manifest lists at least one file");
+        avro::validate_sink(
+            None,
+            &schema,
+            &self.expected,
+            &actual,
+            &state.regex,
+            &state.regex_replacement,
+        )?;
+        Ok(ControlFlow::Continue)
+    }
+}
+
+/// Looks up the path of the first file of an Avro OCF sink.
+async fn sink_path(state: &mut State, sink: &str) -> Result<PathBuf, anyhow::Error> {
+    Retry::default()
+        .max_duration(state.default_timeout)
+        .retry_async_canceling(|_| async {
+            let row = state
+                .pgclient
+                .query_one(
+                    "SELECT path FROM mz_catalog_names
+                     JOIN mz_avro_ocf_sinks ON global_id = sink_id
+                     WHERE name = $1",
+                    &[&sink],
+                )
+                .await
+                .context("querying materialize")?;
+            let bytes: Vec<u8> = row.get("path");
+            Ok::<_, anyhow::Error>(PathBuf::from(OsString::from_vec(bytes)))
+        })
+        .await
+        .context("retrieving path")
+}
@@ -28,7 +28,8 @@ use mz_ore::now::NOW_ZERO;
 use mz_ore::retry::Retry;
 use mz_pgrepr::{Interval, Jsonb, Numeric};
 use mz_sql_parser::ast::{
-    CreateClusterStatement, CreateDatabaseStatement, CreateSchemaStatement, CreateSecretStatement,
+    CreateClusterStatement, CreateDatabaseStatement, CreateFunctionStatement,
+    CreateNotificationStatement, CreateSchemaStatement, CreateSecretStatement,
     CreateSourceStatement, CreateTableStatement, CreateViewStatement, Raw, Statement,
     ViewDefinition,
 };
@@ -114,6 +115,20 @@ impl Action for SqlAction {
                 )
                 .await
             }
+            Statement::CreateNotification(CreateNotificationStatement { name, .. }) => {
+                self.try_drop(
+                    &mut state.pgclient,
+                    &format!("DROP NOTIFICATION IF EXISTS {} CASCADE", name),
+                )
+                .await
+            }
+            Statement::CreateFunction(CreateFunctionStatement { name, .. }) => {
+                self.try_drop(
+                    &mut state.pgclient,
+                    &format!("DROP FUNCTION IF EXISTS {} CASCADE", name),
+                )
+                .await
+            }
             _ => Ok(()),
         }
     }
@@ -131,8 +146,8 @@ impl Action for SqlAction {
             // DDL statements should always provide the expected result on the first try
             CreateDatabase(_) | CreateSchema(_) | CreateSource(_) | CreateSink(_)
             | CreateView(_) | CreateViews(_) | CreateTable(_) | CreateIndex(_) | CreateType(_)
-            | CreateRole(_) | AlterObjectRename(_) | AlterIndex(_) | Discard(_)
-            | DropDatabase(_) | DropObjects(_) | SetVariable(_) | ShowDatabases(_)
+            | CreateRole(_) | AlterObjectRename(_) | AlterIndex(_) | AlterSource(_)
+            | Discard(_) | DropDatabase(_) | DropObjects(_) | SetVariable(_) | ShowDatabases(_)
             | ShowObjects(_) | ShowIndexes(_) | ShowColumns(_) | ShowCreateView(_)
             | ShowCreateSource(_) | ShowCreateTable(_) | ShowCreateSink(_) | ShowCreateIndex(_)
             | ShowVariable(_) => false,
@@ -226,6 +226,41 @@ impl ColumnKnowledge {
 
                     Ok(knowledges)
                 }
+                MirRelationExpr::OuterJoin {
+                    left,
+                    right,
+                    left_key,
+                    right_key,
+                    kind,
+                } => {
+                    let mut knowledges = Vec::new();
+                    for (input, key) in [(left, left_key), (right, right_key)] {
+                        let mut input_knowledge =
+                            self.harvest(input, knowledge, knowledge_stack)?;
+                        // As for joins, do not propagate error literals into the keys.
+                        for knowledge in input_knowledge.iter_mut() {
+                            if let Some((Err(_), _)) = knowledge.value {
+                                knowledge.value = None;
+                            }
+                        }
+                        let input_typ = input.typ();
+                        for expr in key.iter_mut() {
+                            optimize(expr, &input_typ, &input_knowledge[..], knowledge_stack);
+                        }
+                        knowledges.push(input_knowledge);
+                    }
+
+                    // The columns of unmatched records are padded with nulls.
+                    let right_knowledge = knowledges.pop().unwrap();
+                    let mut left_knowledge = knowledges.pop().unwrap();
+                    if *kind == mz_expr::OuterJoinKind::Full {
+                        left_knowledge = vec![DatumKnowledge::default(); left_knowledge.len()];
+                    }
+                    left_knowledge.extend(
+                        std::iter::repeat(DatumKnowledge::default()).take(right_knowledge.len()),
+                    );
+                    Ok(left_knowledge)
+                }
                 MirRelationExpr::Reduce {
                     input,
                     group_key,
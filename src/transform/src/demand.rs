@@ -13,7 +13,7 @@ use std::collections::{HashMap, HashSet};
 
 use mz_expr::{
     AggregateExpr, AggregateFunc, Id, JoinInputMapper, MirRelationExpr, MirScalarExpr,
-    RECURSION_LIMIT,
+    OuterJoinKind, RECURSION_LIMIT,
 };
 use mz_ore::stack::{CheckedRecursion, RecursionGuard};
 use mz_repr::{Datum, Row};
@@ -216,6 +216,62 @@ impl Demand {
 
                     Ok(())
                 }
+                MirRelationExpr::OuterJoin {
+                    left,
+                    right,
+                    left_key,
+                    right_key,
+                    kind,
+                } => {
+                    let left_arity = left.arity();
+                    // A left outer join produces exactly one record for each
+                    // record of `left` if `right` has at most one record with
+                    // each key. If no column of `right` is demanded, the join
+                    // can be replaced by `left`.
+                    if *kind == OuterJoinKind::Left && columns.iter().all(|c| *c < left_arity) {
+                        let right_type = right.typ();
+                        let key_columns = right_key
+                            .iter()
+                            .filter_map(|e| match e {
+                                MirScalarExpr::Column(c) => Some(*c),
+                                _ => None,
+                            })
+                            .collect::<HashSet<_>>();
+                        if right_type
+                            .keys
+                            .iter()
+                            .any(|key| key.iter().all(|c| key_columns.contains(c)))
+                        {
+                            let dummies = right_type
+                                .column_types
+                                .into_iter()
+                                .map(|typ| MirScalarExpr::literal_ok(Datum::Dummy, typ.scalar_type))
+                                .collect();
+                            *relation = left.take_dangerous().map(dummies);
+                            return self.action(relation, columns, gets);
+                        }
+                    }
+
+                    // Each input must produce its key columns, in addition to
+                    // the demanded columns.
+                    let mut left_columns = left_key
+                        .iter()
+                        .flat_map(|e| e.support())
+                        .collect::<HashSet<_>>();
+                    let mut right_columns = right_key
+                        .iter()
+                        .flat_map(|e| e.support())
+                        .collect::<HashSet<_>>();
+                    for column in columns {
+                        if column < left_arity {
+                            left_columns.insert(column);
+                        } else {
+                            right_columns.insert(column - left_arity);
+                        }
+                    }
+                    self.action(left, left_columns, gets)?;
+                    self.action(right, right_columns, gets)
+                }
                 MirRelationExpr::Reduce {
                     input,
                     group_key,
@@ -15,9 +15,17 @@
 //! This includes determining the type of join (e.g. differential linear, or delta queries),
 //! determining the orders of collections, lifting predicates if useful arrangements exist,
 //! and identifying opportunities to use indexes to replace filters.
+//!
+//! When the [`IndexOracle`](crate::IndexOracle) knows the sizes of the
+//! arrangements that back indexes, orders that look up records through
+//! arrangements with many records per key are avoided, as each lookup may
+//! substantially inflate the number of intermediate records. Likewise, when it
+//! knows the number of records in a collection, cross joins with small
+//! collections are preferred over cross joins with large ones.
 
 use std::collections::HashMap;
 
+use mz_dataflow_types::client::ArrangementStatistics;
 use mz_expr::{JoinInputMapper, MapFilterProject, MirRelationExpr, MirScalarExpr, RECURSION_LIMIT};
 use mz_ore::stack::{CheckedRecursion, RecursionGuard};
 
@@ -120,6 +128,8 @@ impl JoinImplementation {
                 .map(|typ| typ.keys)
                 .collect::<Vec<_>>();
             let mut available_arrangements = vec![Vec::new(); inputs.len()];
+            let mut arrangement_statistics = vec![HashMap::new(); inputs.len()];
+            let mut cardinalities = vec![None; inputs.len()];
             for index in 0..inputs.len() {
                 // We can work around mfps, as we can lift the mfps into the join execution.
                 let (mfp, input) = MapFilterProject::extract_non_errors_from_expr(&inputs[index]);
@@ -129,6 +139,8 @@ impl JoinImplementation {
                     MirRelationExpr::Get { id, typ: _ } => {
                         available_arrangements[index]
                             .extend(indexes.get(*id).map(|key| key.to_vec()));
+                        arrangement_statistics[index].extend(indexes.statistics(*id));
+                        cardinalities[index] = indexes.cardinality(*id);
                     }
                     MirRelationExpr::ArrangeBy { input, keys } => {
                         // We may use any presented arrangement keys.
@@ -136,6 +148,8 @@ impl JoinImplementation {
                         if let MirRelationExpr::Get { id, typ: _ } = &**input {
                             available_arrangements[index]
                                 .extend(indexes.get(*id).map(|key| key.to_vec()));
+                            arrangement_statistics[index].extend(indexes.statistics(*id));
+                            cardinalities[index] = indexes.cardinality(*id);
                         }
                     }
                     MirRelationExpr::Reduce { group_key, .. } => {
@@ -164,6 +178,20 @@ impl JoinImplementation {
                         key.permute_map(&reverse_project);
                     }
                 }
+                // Likewise for the keys of arrangements with known sizes.
+                arrangement_statistics[index] = std::mem::take(&mut arrangement_statistics[index])
+                    .into_iter()
+                    .filter(|(key, _)| {
+                        key.iter()
+                            .all(|k| k.support().iter().all(|c| reverse_project.contains_key(c)))
+                    })
+                    .map(|(mut key, statistics)| {
+                        for k in key.iter_mut() {
+                            k.permute_map(&reverse_project);
+                        }
+                        (key, statistics)
+                    })
+                    .collect();
                 // Currently we only support using arrangements all of whose
                 // keys can be found in some equivalence.
                 // Note: because `order_input` currently only finds arrangements
@@ -187,12 +215,16 @@ impl JoinImplementation {
                 relation,
                 &input_mapper,
                 &available_arrangements,
+                &arrangement_statistics,
+                &cardinalities,
                 &unique_keys,
             );
             let differential_plan = differential::plan(
                 relation,
                 &input_mapper,
                 &available_arrangements,
+                &arrangement_statistics,
+                &cardinalities,
                 &unique_keys,
             );
 
@@ -206,6 +238,7 @@ impl JoinImplementation {
 mod index_map {
     use std::collections::HashMap;
 
+    use mz_dataflow_types::client::ArrangementStatistics;
     use mz_expr::{Id, LocalId, MirScalarExpr};
 
     use crate::IndexOracle;
@@ -251,11 +284,43 @@ mod index_map {
                 ),
             }
         }
+
+        /// Returns the keys of the indexes on the specified collection whose
+        /// sizes are known, along with their size estimates.
+        ///
+        /// Size estimates are only known for global indexes.
+        pub fn statistics(&self, id: Id) -> Vec<(Vec<MirScalarExpr>, ArrangementStatistics)> {
+            match id {
+                Id::Global(id) => self
+                    .global
+                    .indexes_on(id)
+                    .filter_map(|key| {
+                        let statistics = self.global.statistics(id, key)?;
+                        Some((key.to_vec(), statistics))
+                    })
+                    .collect(),
+                Id::Local(_) => Vec::new(),
+            }
+        }
+
+        /// Returns an estimate of the number of records in the specified
+        /// collection, if one is known.
+        ///
+        /// Estimates are only known for global collections.
+        pub fn cardinality(&self, id: Id) -> Option<u64> {
+            match id {
+                Id::Global(id) => self.global.cardinality(id),
+                Id::Local(_) => None,
+            }
+        }
     }
 }
 
 mod delta_queries {
 
+    use std::collections::HashMap;
+
+    use mz_dataflow_types::client::ArrangementStatistics;
     use mz_expr::{JoinImplementation, JoinInputMapper, MirRelationExpr, MirScalarExpr};
 
     /// Creates a delta query plan, and any predicates that need to be lifted.
@@ -265,6 +330,8 @@ mod delta_queries {
         join: &MirRelationExpr,
         input_mapper: &JoinInputMapper,
         available: &[Vec<Vec<MirScalarExpr>>],
+        statistics: &[HashMap<Vec<MirScalarExpr>, ArrangementStatistics>],
+        cardinalities: &[Option<u64>],
         unique_keys: &[Vec<Vec<usize>>],
     ) -> Option<MirRelationExpr> {
         let mut new_join = join.clone();
@@ -285,7 +352,14 @@ mod delta_queries {
             }
 
             // Determine a viable order for each relation, or return `None` if none found.
-            let orders = super::optimize_orders(equivalences, available, unique_keys, input_mapper);
+            let orders = super::optimize_orders(
+                equivalences,
+                available,
+                statistics,
+                cardinalities,
+                unique_keys,
+                input_mapper,
+            );
 
             // A viable delta query requires that, for every order,
             // there is an arrangement for every input except for
@@ -326,6 +400,9 @@ mod delta_queries {
 
 mod differential {
 
+    use std::collections::HashMap;
+
+    use mz_dataflow_types::client::ArrangementStatistics;
     use mz_expr::{JoinImplementation, JoinInputMapper, MirRelationExpr, MirScalarExpr};
 
     /// Creates a linear differential plan, and any predicates that need to be lifted.
@@ -333,6 +410,8 @@ mod differential {
         join: &MirRelationExpr,
         input_mapper: &JoinInputMapper,
         available: &[Vec<Vec<MirScalarExpr>>],
+        statistics: &[HashMap<Vec<MirScalarExpr>, ArrangementStatistics>],
+        cardinalities: &[Option<u64>],
         unique_keys: &[Vec<Vec<usize>>],
     ) -> Option<MirRelationExpr> {
         let mut new_join = join.clone();
@@ -348,8 +427,14 @@ mod differential {
             // Important, we should choose something stable under re-ordering, to converge under fixed
             // point iteration; we choose to start with the first input optimizing our criteria, which
             // should remain stable even when promoted to the first position.
-            let mut orders =
-                super::optimize_orders(equivalences, available, unique_keys, input_mapper);
+            let mut orders = super::optimize_orders(
+                equivalences,
+                available,
+                statistics,
+                cardinalities,
+                unique_keys,
+                input_mapper,
+            );
 
             // For differential join, it is not as important for the starting
             // input to have good characteristics because the other ones
@@ -528,10 +613,19 @@ fn install_lifted_mfp(new_join: &mut MirRelationExpr, mfp: MapFilterProject) {
 fn optimize_orders(
     equivalences: &[Vec<MirScalarExpr>],
     available: &[Vec<Vec<MirScalarExpr>>],
+    statistics: &[HashMap<Vec<MirScalarExpr>, ArrangementStatistics>],
+    cardinalities: &[Option<u64>],
     unique_keys: &[Vec<Vec<usize>>],
     input_mapper: &JoinInputMapper,
 ) -> Vec<Vec<(Characteristics, Vec<MirScalarExpr>, usize)>> {
-    let mut orderer = Orderer::new(equivalences, available, unique_keys, input_mapper);
+    let mut orderer = Orderer::new(
+        equivalences,
+        available,
+        statistics,
+        cardinalities,
+        unique_keys,
+        input_mapper,
+    );
     (0..available.len())
         .map(move |i| orderer.optimize_order_for(i))
         .collect::<Vec<_>>()
@@ -541,13 +635,18 @@ fn optimize_orders(
 ///
 /// A candidate is described by a collection and a key, and may have various liabilities.
 /// Primarily, the candidate may risk substantial inflation of records, which is something
-/// that concerns us greatly. Additionally the candidate may be unarranged, and we would
-/// prefer candidates that do not require additional memory. Finally, we prefer lower id
-/// collections in the interest of consistent tie-breaking.
-#[derive(Eq, PartialEq, Ord, PartialOrd, Debug, Clone)]
+/// that concerns us greatly. When the sizes of arrangements are known, we prefer candidates
+/// that are expected to match fewer records per lookup, even if they are looked up by fewer
+/// columns. Additionally the candidate may be unarranged, and we would prefer candidates
+/// that do not require additional memory. Finally, we prefer lower id collections in the
+/// interest of consistent tie-breaking.
+#[derive(Debug, Clone)]
 pub struct Characteristics {
     // An excellent indication that record count will not increase.
     unique_key: bool,
+    // An estimate of how much record count will increase, if statistics about the
+    // collection are known. It is only compared between candidates that both have one.
+    fanout: Option<u32>,
     // A weaker signal that record count will not increase.
     key_length: usize,
     // Indicates that there will be no additional in-memory footprint.
@@ -557,9 +656,16 @@ pub struct Characteristics {
 }
 
 impl Characteristics {
-    fn new(unique_key: bool, key_length: usize, arranged: bool, input: usize) -> Self {
+    fn new(
+        unique_key: bool,
+        key_length: usize,
+        fanout: Option<u32>,
+        arranged: bool,
+        input: usize,
+    ) -> Self {
         Self {
             unique_key,
+            fanout,
             key_length,
             arranged,
             input: std::cmp::Reverse(input),
@@ -567,6 +673,54 @@ impl Characteristics {
     }
 }
 
+impl Ord for Characteristics {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        // Lower fanouts are better. Without statistics on both sides, the candidates are
+        // ordered as if there were no statistics at all.
+        let fanout = match (self.fanout, other.fanout) {
+            (Some(fanout), Some(other_fanout)) => other_fanout.cmp(&fanout),
+            _ => std::cmp::Ordering::Equal,
+        };
+        self.unique_key
+            .cmp(&other.unique_key)
+            .then(fanout)
+            .then(self.key_length.cmp(&other.key_length))
+            .then(self.arranged.cmp(&other.arranged))
+            .then(self.input.cmp(&other.input))
+    }
+}
+
+impl PartialOrd for Characteristics {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Characteristics {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Characteristics {}
+
+/// Buckets the estimated number of records that match each lookup.
+///
+/// Buckets grow by a factor of [`FANOUT_BUCKET_BASE`], so that only
+/// substantial differences in fanout influence the order, and small changes
+/// to the estimates do not cause plans to flip between orders.
+fn fanout_bucket(mut fanout: u64) -> u32 {
+    let mut bucket = 0;
+    while fanout >= FANOUT_BUCKET_BASE {
+        fanout /= FANOUT_BUCKET_BASE;
+        bucket += 1;
+    }
+    bucket
+}
+
+/// The factor by which the fanout of successive buckets grows.
+const FANOUT_BUCKET_BASE: u64 = 16;
+
 struct Orderer<'a> {
     inputs: usize,
     equivalences: &'a [Vec<MirScalarExpr>],
@@ -575,6 +729,8 @@ struct Orderer<'a> {
     input_mapper: &'a JoinInputMapper,
     reverse_equivalences: Vec<Vec<(usize, usize)>>,
     unique_arrangement: Vec<Vec<bool>>,
+    arrangement_fanout: Vec<Vec<Option<u32>>>,
+    cross_join_fanout: Vec<Option<u32>>,
 
     order: Vec<(Characteristics, Vec<MirScalarExpr>, usize)>,
     placed: Vec<bool>,
@@ -588,6 +744,8 @@ impl<'a> Orderer<'a> {
     fn new(
         equivalences: &'a [Vec<MirScalarExpr>],
         arrangements: &'a [Vec<Vec<MirScalarExpr>>],
+        statistics: &[HashMap<Vec<MirScalarExpr>, ArrangementStatistics>],
+        cardinalities: &[Option<u64>],
         unique_keys: &'a [Vec<Vec<usize>>],
         input_mapper: &'a JoinInputMapper,
     ) -> Self {
@@ -611,6 +769,35 @@ impl<'a> Orderer<'a> {
                 }));
             }
         }
+        // Per-input estimates of the records that match each record in a cross join,
+        // which are all of the records of the input.
+        let empty_key: &[MirScalarExpr] = &[];
+        let cross_join_fanout = statistics
+            .iter()
+            .zip(cardinalities)
+            .map(|(statistics, cardinality)| {
+                statistics
+                    .get(empty_key)
+                    .map(|statistics| statistics.records)
+                    .or(*cardinality)
+                    .map(fanout_bucket)
+            })
+            .collect::<Vec<_>>();
+        // Per-arrangement estimates of the records that match each lookup. Lookups into
+        // arrangements with empty keys are cross joins.
+        let arrangement_fanout = arrangements
+            .iter()
+            .zip(statistics)
+            .zip(cross_join_fanout.iter())
+            .map(|((keys, statistics), cross_join_fanout)| {
+                keys.iter()
+                    .map(|key| match statistics.get(key) {
+                        _ if key.is_empty() => *cross_join_fanout,
+                        statistics => statistics.map(|s| fanout_bucket(s.fanout())),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
 
         let order = Vec::with_capacity(inputs);
         let placed = vec![false; inputs];
@@ -626,6 +813,8 @@ impl<'a> Orderer<'a> {
             input_mapper,
             reverse_equivalences,
             unique_arrangement,
+            arrangement_fanout,
+            cross_join_fanout,
             order,
             placed,
             bound,
@@ -658,14 +847,16 @@ impl<'a> Orderer<'a> {
                 .position(|key| key.is_empty())
             {
                 self.arrangement_active[input].push(pos);
+                let fanout = self.cross_join_fanout[input];
                 self.priority_queue.push((
-                    Characteristics::new(is_unique, 0, true, input),
+                    Characteristics::new(is_unique, 0, fanout, true, input),
                     vec![],
                     input,
                 ));
             } else {
+                let fanout = self.cross_join_fanout[input];
                 self.priority_queue.push((
-                    Characteristics::new(is_unique, 0, false, input),
+                    Characteristics::new(is_unique, 0, fanout, false, input),
                     vec![],
                     input,
                 ));
@@ -690,7 +881,11 @@ impl<'a> Orderer<'a> {
 
         // calculate characteristics of an arrangement, if any on the starting input
         // by default, there is no arrangement on the starting input
-        let mut start_tuple = (Characteristics::new(false, 0, false, start), vec![], start);
+        let mut start_tuple = (
+            Characteristics::new(false, 0, None, false, start),
+            vec![],
+            start,
+        );
         // use an arrangement if there exists one that lines up with the keys of
         // the second input
         if let Some((_, key, second)) = self.order.get(0) {
@@ -711,8 +906,15 @@ impl<'a> Orderer<'a> {
                     .position(|k| k == &candidate_start_key)
                 {
                     let is_unique = self.unique_arrangement[start][pos];
+                    let fanout = self.arrangement_fanout[start][pos];
                     start_tuple = (
-                        Characteristics::new(is_unique, candidate_start_key.len(), true, start),
+                        Characteristics::new(
+                            is_unique,
+                            candidate_start_key.len(),
+                            fanout,
+                            true,
+                            start,
+                        ),
                         candidate_start_key,
                         start,
                     );
@@ -779,10 +981,12 @@ impl<'a> Orderer<'a> {
                                             self.arrangement_active[rel].push(pos);
                                             // TODO: This could be pre-computed, as it is independent of the order.
                                             let is_unique = self.unique_arrangement[rel][pos];
+                                            let fanout = self.arrangement_fanout[rel][pos];
                                             self.priority_queue.push((
                                                 Characteristics::new(
                                                     is_unique,
                                                     keys.len(),
+                                                    fanout,
                                                     true,
                                                     rel,
                                                 ),
@@ -797,10 +1001,36 @@ impl<'a> Orderer<'a> {
                                         self.bound[rel].contains(&MirScalarExpr::Column(*c))
                                     })
                                 });
+                                // Looking up all bound columns matches no more records
+                                // than looking up any arrangement key among them, and no
+                                // fewer than looking up any arrangement key that contains
+                                // them all.
+                                let mut upper_bound = None;
+                                let mut lower_bound = None;
+                                for (keys, fanout) in self.arrangements[rel]
+                                    .iter()
+                                    .zip(self.arrangement_fanout[rel].iter())
+                                {
+                                    let fanout = match fanout {
+                                        Some(fanout) => *fanout,
+                                        None => continue,
+                                    };
+                                    if keys.iter().all(|k| self.bound[rel].contains(k)) {
+                                        upper_bound = Some(
+                                            upper_bound.map_or(fanout, |b: u32| b.min(fanout)),
+                                        );
+                                    } else if self.bound[rel].iter().all(|b| keys.contains(b)) {
+                                        lower_bound = Some(
+                                            lower_bound.map_or(fanout, |b: u32| b.max(fanout)),
+                                        );
+                                    }
+                                }
+                                let fanout = upper_bound.or(lower_bound);
                                 self.priority_queue.push((
                                     Characteristics::new(
                                         is_unique,
                                         self.bound[rel].len(),
+                                        fanout,
                                         false,
                                         rel,
                                     ),
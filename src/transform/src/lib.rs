@@ -25,6 +25,7 @@ use std::error::Error;
 use std::fmt;
 use std::iter;
 
+use mz_dataflow_types::client::ArrangementStatistics;
 use mz_expr::GlobalId;
 use mz_expr::MirRelationExpr;
 use mz_expr::MirScalarExpr;
@@ -120,6 +121,24 @@ pub trait IndexOracle: fmt::Debug {
     // better somehow? Making the entire optimizer generic over this iterator
     // type doesn't presently seem worthwhile.
     fn indexes_on(&self, id: GlobalId) -> Box<dyn Iterator<Item = &[MirScalarExpr]> + '_>;
+
+    /// Returns size estimates for the index on the identified collection
+    /// with the specified key.
+    ///
+    /// Returns `None` if no such index exists, or if no estimates are known
+    /// for it.
+    fn statistics(&self, _id: GlobalId, _key: &[MirScalarExpr]) -> Option<ArrangementStatistics> {
+        None
+    }
+
+    /// Returns an estimate of the number of records in the identified
+    /// collection.
+    ///
+    /// Returns `None` if the collection is unknown, or if no estimate is known
+    /// for it.
+    fn cardinality(&self, _id: GlobalId) -> Option<u64> {
+        None
+    }
 }
 
 /// An [`IndexOracle`] that knows about no indexes.
@@ -24,7 +24,9 @@ use std::collections::HashMap;
 
 use itertools::Itertools;
 
-use mz_expr::{Id, JoinInputMapper, MirRelationExpr, MirScalarExpr, RECURSION_LIMIT};
+use mz_expr::{
+    Id, JoinInputMapper, MirRelationExpr, MirScalarExpr, OuterJoinKind, RECURSION_LIMIT,
+};
 use mz_ore::stack::{CheckedRecursion, RecursionGuard};
 use mz_repr::{Row, RowPacker};
 
@@ -487,6 +489,53 @@ impl LiteralLifting {
                     }
                     Ok(Vec::new())
                 }
+                MirRelationExpr::OuterJoin {
+                    left,
+                    right,
+                    left_key,
+                    right_key: _,
+                    kind,
+                } => {
+                    // Literals of an input that can be padded with nulls must
+                    // stay below the join, as must error literals.
+                    let mut left_literals = self.action(left, gets)?;
+                    if !left_literals.is_empty()
+                        && (*kind == OuterJoinKind::Full
+                            || left_literals.iter().any(|l| l.is_literal_err()))
+                    {
+                        **left = left.take_dangerous().map(left_literals);
+                        left_literals = Vec::new();
+                    }
+                    let right_literals = self.action(right, gets)?;
+                    if !right_literals.is_empty() {
+                        **right = right.take_dangerous().map(right_literals);
+                    }
+
+                    if !left_literals.is_empty() {
+                        let left_arity = left.arity();
+                        let right_arity = right.arity();
+                        for expr in left_key.iter_mut() {
+                            expr.visit_mut_post(&mut |e| {
+                                if let MirScalarExpr::Column(c) = e {
+                                    if *c >= left_arity {
+                                        *e = left_literals[*c - left_arity].clone();
+                                    }
+                                }
+                            });
+                        }
+                        // Put the lifted literals back in their place among
+                        // the columns of `left`.
+                        let projection = (0..left_arity)
+                            .chain((0..left_literals.len()).map(|i| left_arity + right_arity + i))
+                            .chain(left_arity..left_arity + right_arity)
+                            .collect();
+                        *relation = relation
+                            .take_dangerous()
+                            .map(left_literals)
+                            .project(projection);
+                    }
+                    Ok(Vec::new())
+                }
                 MirRelationExpr::Reduce {
                     input,
                     group_key,
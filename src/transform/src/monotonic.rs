@@ -97,6 +97,13 @@ impl MonotonicFlag {
                     }
                     monotonic
                 }
+                MirRelationExpr::OuterJoin { left, right, .. } => {
+                    // The null-padded records of unmatched inputs are retracted
+                    // once a match appears, even if both inputs are monotonic.
+                    self.apply(left, sources, locals)?;
+                    self.apply(right, sources, locals)?;
+                    false
+                }
                 MirRelationExpr::Constant { rows: Ok(rows), .. } => {
                     rows.iter().all(|(_, diff)| diff > &0)
                 }
@@ -26,7 +26,9 @@ use std::collections::{HashMap, HashSet};
 
 use crate::TransformArgs;
 use itertools::{Either, Itertools};
-use mz_expr::{Id, JoinInputMapper, MirRelationExpr, MirScalarExpr, RECURSION_LIMIT};
+use mz_expr::{
+    BinaryFunc, Id, JoinInputMapper, MirRelationExpr, MirScalarExpr, OuterJoinKind, RECURSION_LIMIT,
+};
 use mz_ore::stack::{CheckedRecursion, RecursionGuard};
 
 /// Push non-null requirements toward sources.
@@ -202,6 +204,79 @@ impl NonNullRequirements {
                     }
                     Ok(())
                 }
+                MirRelationExpr::OuterJoin {
+                    left,
+                    right,
+                    left_key,
+                    right_key,
+                    kind,
+                } => {
+                    // The records of an input that are padded with nulls can
+                    // be discarded if any of the padded columns must be
+                    // non-null, in which case the other input need not be
+                    // preserved.
+                    let left_arity = left.arity();
+                    let right_arity = right.arity();
+                    let left_required = columns.iter().any(|c| *c < left_arity);
+                    let right_required = columns.iter().any(|c| *c >= left_arity);
+                    match (*kind, left_required, right_required) {
+                        (OuterJoinKind::Left, _, true) | (OuterJoinKind::Full, true, true) => {
+                            // Neither input is preserved; plan an inner join
+                            // on the equality of the keys.
+                            let shift = (left_arity..left_arity + right_arity).collect::<Vec<_>>();
+                            let predicates = left_key
+                                .iter()
+                                .zip(right_key.iter())
+                                .map(|(l, r)| {
+                                    let mut r = r.clone();
+                                    r.permute(&shift);
+                                    l.clone().call_binary(r, BinaryFunc::Eq)
+                                })
+                                .collect();
+                            *relation = MirRelationExpr::join_scalars(
+                                vec![left.take_dangerous(), right.take_dangerous()],
+                                vec![],
+                            )
+                            .filter(predicates);
+                            self.action(relation, columns, gets)
+                        }
+                        (OuterJoinKind::Full, false, true) => {
+                            *kind = OuterJoinKind::Left;
+                            self.action(relation, columns, gets)
+                        }
+                        (OuterJoinKind::Full, true, false) => {
+                            // Only `right` is preserved; swap the inputs and
+                            // restore the column order.
+                            *relation = right
+                                .take_dangerous()
+                                .outer_join(
+                                    left.take_dangerous(),
+                                    right_key.clone(),
+                                    left_key.clone(),
+                                    OuterJoinKind::Left,
+                                )
+                                .project(
+                                    (right_arity..right_arity + left_arity)
+                                        .chain(0..right_arity)
+                                        .collect(),
+                                );
+                            self.action(relation, columns, gets)
+                        }
+                        _ => {
+                            // Records of a preserved input whose columns must
+                            // be non-null can be discarded, as each of them
+                            // results only in output records with the same
+                            // values.
+                            if *kind == OuterJoinKind::Left {
+                                columns.retain(|c| *c < left_arity);
+                            } else {
+                                columns.clear();
+                            }
+                            self.action(left, columns, gets)?;
+                            self.action(right, HashSet::new(), gets)
+                        }
+                    }
+                }
                 MirRelationExpr::Reduce {
                     input,
                     group_key,
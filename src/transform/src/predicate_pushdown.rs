@@ -79,7 +79,9 @@ use std::collections::{HashMap, HashSet};
 
 use crate::TransformArgs;
 use itertools::Itertools;
-use mz_expr::{func, AggregateFunc, Id, MirRelationExpr, MirScalarExpr, RECURSION_LIMIT};
+use mz_expr::{
+    func, AggregateFunc, Id, MirRelationExpr, MirScalarExpr, OuterJoinKind, RECURSION_LIMIT,
+};
 use mz_ore::stack::{CheckedRecursion, RecursionGuard};
 use mz_repr::{Datum, ScalarType};
 
@@ -418,6 +420,27 @@ impl PredicatePushdown {
                             // ... and keep pushing predicates down
                             self.action(input, get_predicates)?;
                         }
+                        MirRelationExpr::OuterJoin {
+                            left,
+                            right,
+                            kind: OuterJoinKind::Left,
+                            ..
+                        } => {
+                            // Every record of `left` produces output records with
+                            // the same values, so predicates on its columns can be
+                            // evaluated before the join.
+                            let left_arity = left.arity();
+                            let (pushdown, mut retained): (Vec<_>, Vec<_>) = predicates
+                                .drain(..)
+                                .partition(|p| p.support().iter().all(|c| *c < left_arity));
+                            std::mem::swap(&mut retained, predicates);
+
+                            if !pushdown.is_empty() {
+                                **left = left.take_dangerous().filter(pushdown);
+                            }
+                            self.action(left, get_predicates)?;
+                            self.action(right, get_predicates)?;
+                        }
                         MirRelationExpr::Union { base, inputs } => {
                             let predicates = std::mem::replace(predicates, Vec::new());
                             *base = Box::new(base.take_dangerous().filter(predicates.clone()));
@@ -205,6 +205,45 @@ impl ProjectionLifting {
                     }
                     Ok(())
                 }
+                MirRelationExpr::OuterJoin {
+                    left,
+                    right,
+                    left_key,
+                    right_key,
+                    kind: _,
+                } => {
+                    self.action(left, gets)?;
+                    self.action(right, gets)?;
+
+                    // Track the location of the projected columns in the un-projected join.
+                    let mut projection = Vec::new();
+                    let mut temp_arity = 0;
+                    let mut lifted = false;
+                    for (input, key) in [(left, left_key), (right, right_key)] {
+                        if let MirRelationExpr::Project {
+                            input: inner,
+                            outputs,
+                        } = &mut **input
+                        {
+                            for expr in key.iter_mut() {
+                                expr.permute(outputs);
+                            }
+                            projection.extend(outputs.iter().map(|c| temp_arity + *c));
+                            temp_arity += inner.arity();
+                            **input = inner.take_dangerous();
+                            lifted = true;
+                        } else {
+                            let arity = input.arity();
+                            projection.extend(temp_arity..(temp_arity + arity));
+                            temp_arity += arity;
+                        }
+                    }
+
+                    if lifted {
+                        *relation = relation.take_dangerous().project(projection);
+                    }
+                    Ok(())
+                }
                 MirRelationExpr::Reduce {
                     input,
                     group_key,
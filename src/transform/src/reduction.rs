@@ -14,7 +14,9 @@ use std::collections::{BTreeMap, HashSet};
 use std::convert::TryInto;
 use std::iter;
 
-use mz_expr::{AggregateExpr, ColumnOrder, EvalError, MirRelationExpr, MirScalarExpr, TableFunc};
+use mz_expr::{
+    AggregateExpr, ColumnOrder, EvalError, MirRelationExpr, MirScalarExpr, OuterJoinKind, TableFunc,
+};
 use mz_repr::{Datum, Diff, RelationType, Row, RowArena};
 
 use crate::{TransformArgs, TransformError};
@@ -346,6 +348,44 @@ impl FoldConstants {
                 }
                 // TODO: General constant folding for all constant inputs.
             }
+            MirRelationExpr::OuterJoin {
+                left,
+                right,
+                left_key,
+                right_key,
+                kind,
+            } => {
+                for key in left_key.iter_mut() {
+                    key.reduce(&input_types[0]);
+                }
+                for key in right_key.iter_mut() {
+                    key.reduce(&input_types[1]);
+                }
+
+                // Without records to match against, the preserved inputs are
+                // padded with nulls.
+                let left_arity = input_types[0].arity();
+                let right_arity = input_types[1].arity();
+                if left.is_empty() && (right.is_empty() || *kind == OuterJoinKind::Left) {
+                    relation.take_safely();
+                } else if right.is_empty() {
+                    let nulls = relation_type.column_types[left_arity..]
+                        .iter()
+                        .map(|typ| MirScalarExpr::literal_null(typ.scalar_type.clone()))
+                        .collect();
+                    *relation = left.take_dangerous().map(nulls);
+                } else if left.is_empty() {
+                    let nulls = relation_type.column_types[..left_arity]
+                        .iter()
+                        .map(|typ| MirScalarExpr::literal_null(typ.scalar_type.clone()))
+                        .collect();
+                    *relation = right.take_dangerous().map(nulls).project(
+                        (right_arity..right_arity + left_arity)
+                            .chain(0..right_arity)
+                            .collect(),
+                    );
+                }
+            }
             MirRelationExpr::Union { base, inputs } => {
                 if let Some(e) = iter::once(&mut **base)
                     .chain(&mut *inputs)
@@ -231,6 +231,14 @@ impl RedundantJoin {
                     }
                 }
 
+                MirRelationExpr::OuterJoin { left, right, .. } => {
+                    // Outer joins may pad columns with nulls, and we do not
+                    // currently track their provenance.
+                    self.action(left, lets)?;
+                    self.action(right, lets)?;
+                    Ok(Vec::new())
+                }
+
                 MirRelationExpr::Filter { input, .. } => {
                     // Filter may drop records, and so we unset `exact`.
                     let mut result = self.action(input, lets)?;
@@ -19,8 +19,9 @@ mod tests {
     use std::collections::HashMap;
     use std::fmt::Write;
 
-    use anyhow::{anyhow, Error};
-    use mz_expr::{GlobalId, Id, MirRelationExpr};
+    use anyhow::{anyhow, bail, Error};
+    use mz_dataflow_types::client::ArrangementStatistics;
+    use mz_expr::{GlobalId, Id, MirRelationExpr, MirScalarExpr};
     use mz_expr_test_util::{
         build_rel, generate_explanation, json_to_spec, MirRelationExprDeserializeContext,
         TestCatalog, RTI,
@@ -28,12 +29,14 @@ mod tests {
     use mz_lowertest::{deserialize, tokenize};
     use mz_ore::str::separated;
     use mz_transform::dataflow::{optimize_dataflow_demand_inner, optimize_dataflow_filters_inner};
-    use mz_transform::{EmptyIndexOracle, Optimizer, Transform, TransformArgs};
+    use mz_transform::{IndexOracle, Optimizer, Transform, TransformArgs};
     use proc_macro2::TokenTree;
 
     // Global options
     const IN: &str = "in";
     const FORMAT: &str = "format";
+    const INDEX: &str = "index";
+    const RECORDS: &str = "records";
     // Values that can be supplied for global options
     const JSON: &str = "json";
     const TEST: &str = "test";
@@ -69,6 +72,87 @@ mod tests {
         Steps,
     }
 
+    /// An [`IndexOracle`] that knows about the indexes specified by
+    /// `args[index]` and the collection sizes specified by `args[records]`.
+    ///
+    /// Each index is specified as `name:columns`, optionally followed by
+    /// `:records:keys` to specify its size, where `columns` is a list of
+    /// column numbers separated by periods. Each collection size is specified
+    /// as `name:records`.
+    #[derive(Debug, Default)]
+    struct TestIndexOracle {
+        indexes: HashMap<GlobalId, Vec<(Vec<MirScalarExpr>, Option<ArrangementStatistics>)>>,
+        cardinalities: HashMap<GlobalId, u64>,
+    }
+
+    impl TestIndexOracle {
+        fn new(cat: &TestCatalog, args: &HashMap<String, Vec<String>>) -> Result<Self, Error> {
+            let mut oracle = TestIndexOracle::default();
+            for spec in args.get(INDEX).into_iter().flatten() {
+                let parts = spec.split(':').collect::<Vec<_>>();
+                let id = match cat.get_source_id(parts[0]) {
+                    Some(id) => id,
+                    None => bail!("unknown object in index {}", spec),
+                };
+                let key = match parts.get(1) {
+                    Some(columns) => columns
+                        .split('.')
+                        .map(|c| -> Result<_, Error> { Ok(MirScalarExpr::Column(c.parse()?)) })
+                        .collect::<Result<Vec<_>, Error>>()?,
+                    None => bail!("missing columns in index {}", spec),
+                };
+                let statistics = match parts[2..] {
+                    [] => None,
+                    [records, keys] => Some(ArrangementStatistics {
+                        records: records.parse()?,
+                        keys: keys.parse()?,
+                    }),
+                    _ => bail!("invalid size in index {}", spec),
+                };
+                oracle
+                    .indexes
+                    .entry(id)
+                    .or_default()
+                    .push((key, statistics));
+            }
+            for spec in args.get(RECORDS).into_iter().flatten() {
+                let (id, records) = match spec.split(':').collect::<Vec<_>>()[..] {
+                    [name, records] => match cat.get_source_id(name) {
+                        Some(id) => (id, records.parse()?),
+                        None => bail!("unknown object in records {}", spec),
+                    },
+                    _ => bail!("invalid records {}", spec),
+                };
+                oracle.cardinalities.insert(id, records);
+            }
+            Ok(oracle)
+        }
+    }
+
+    impl IndexOracle for TestIndexOracle {
+        fn indexes_on(&self, id: GlobalId) -> Box<dyn Iterator<Item = &[MirScalarExpr]> + '_> {
+            Box::new(
+                self.indexes
+                    .get(&id)
+                    .into_iter()
+                    .flatten()
+                    .map(|(key, _)| key.as_slice()),
+            )
+        }
+
+        fn statistics(&self, id: GlobalId, key: &[MirScalarExpr]) -> Option<ArrangementStatistics> {
+            self.indexes
+                .get(&id)?
+                .iter()
+                .find(|(k, _)| k == key)
+                .and_then(|(_, statistics)| *statistics)
+        }
+
+        fn cardinality(&self, id: GlobalId) -> Option<u64> {
+            self.cardinalities.get(&id).copied()
+        }
+    }
+
     /// Parses the output format from `args[format]`.
     fn get_format_type<'a>(args: &'a HashMap<String, Vec<String>>) -> FormatType<'a> {
         if let Some(format) = args.get(FORMAT) {
@@ -122,13 +206,14 @@ mod tests {
         test_type: TestType,
     ) -> Result<String, Error> {
         let mut rel = parse_relation(s, cat, args)?;
+        let indexes = TestIndexOracle::new(cat, args)?;
         let mut id_gen = Default::default();
         for t in args.get("apply").cloned().unwrap_or_else(Vec::new).iter() {
             get_transform(t)?.transform(
                 &mut rel,
                 TransformArgs {
                     id_gen: &mut id_gen,
-                    indexes: &EmptyIndexOracle,
+                    indexes: &indexes,
                 },
             )?;
         }
@@ -142,7 +227,7 @@ mod tests {
                         &mut rel,
                         TransformArgs {
                             id_gen: &mut id_gen,
-                            indexes: &EmptyIndexOracle,
+                            indexes: &indexes,
                         },
                     )?;
                 }
@@ -166,7 +251,7 @@ mod tests {
                             &mut rel,
                             TransformArgs {
                                 id_gen: &mut id_gen,
-                                indexes: &EmptyIndexOracle,
+                                indexes: &indexes,
                             },
                         )?;
 
@@ -237,6 +322,9 @@ mod tests {
             })),
             "FlatMapToMap" => Ok(Box::new(mz_transform::fusion::flatmap_to_map::FlatMapToMap)),
             "JoinFusion" => Ok(Box::new(mz_transform::fusion::join::Join)),
+            "JoinImplementation" => Ok(Box::new(
+                mz_transform::join_implementation::JoinImplementation::default(),
+            )),
             "LiteralLifting" => Ok(Box::new(
                 mz_transform::map_lifting::LiteralLifting::default(),
             )),
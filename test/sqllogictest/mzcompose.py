@@ -24,11 +24,16 @@ def workflow_sqllogictest(c: Composition) -> None:
     run_sqllogictest(c, "ci/slt/slt.sh")
 
 
-def run_sqllogictest(c: Composition, command: str) -> None:
+def workflow_qgm(c: Composition) -> None:
+    "Run fast SQL logic tests through the QGM optimization path, without falling back to the default path"
+    run_sqllogictest(c, "ci/test/slt-fast.sh", "--qgm-optimizations")
+
+
+def run_sqllogictest(c: Composition, command: str, *args: str) -> None:
     c.up("postgres")
     c.wait_for_postgres(dbname="postgres")
     try:
         junit_report = ci_util.junit_report_filename(c.name)
-        c.run("sqllogictest-svc", command, f"--junit-report={junit_report}")
+        c.run("sqllogictest-svc", command, *args, f"--junit-report={junit_report}")
     finally:
         ci_util.upload_junit_report(c.name, ROOT / junit_report)